@@ -0,0 +1,112 @@
+//! Azure DevOps authentication
+
+use crate::auth::AuthSource;
+use crate::error::{Error, Result};
+use crate::redact::REDACTED;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use tracing::debug;
+
+/// Environment variable naming the organization (or collection) URL that
+/// `ryu auth azure-devops test` checks the token against
+pub const AZURE_DEVOPS_ORG_URL_ENV: &str = "AZURE_DEVOPS_ORG_URL";
+
+/// Azure DevOps authentication configuration
+///
+/// `Debug` and `Display` never show the token.
+#[derive(Clone)]
+pub struct AzureDevOpsAuthConfig {
+    /// Personal access token
+    pub token: String,
+    /// Where the token was obtained from
+    pub source: AuthSource,
+}
+
+impl fmt::Debug for AzureDevOpsAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureDevOpsAuthConfig")
+            .field("token", &format_args!("{REDACTED}"))
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl fmt::Display for AzureDevOpsAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Azure DevOps token {REDACTED} (from {})", self.source)
+    }
+}
+
+/// Get Azure DevOps authentication
+///
+/// Reads a personal access token from `AZURE_DEVOPS_PAT`, then
+/// `AZURE_DEVOPS_EXT_PAT` (the variable the `az devops` CLI extension
+/// reads). The token needs the Code (read & write) scope, which covers pull
+/// requests and their threads, and Identity (read) to request reviewers.
+pub fn get_azure_devops_auth() -> Result<AzureDevOpsAuthConfig> {
+    debug!("checking Azure DevOps env vars");
+    for var in ["AZURE_DEVOPS_PAT", "AZURE_DEVOPS_EXT_PAT"] {
+        if let Ok(token) = env::var(var) {
+            debug!(var, "obtained Azure DevOps token from env var");
+            return Ok(AzureDevOpsAuthConfig {
+                token,
+                source: AuthSource::EnvVar,
+            });
+        }
+    }
+
+    debug!("no Azure DevOps authentication found");
+    Err(Error::Auth(
+        "No Azure DevOps authentication found. Set AZURE_DEVOPS_PAT (or AZURE_DEVOPS_EXT_PAT)"
+            .to_string(),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionData {
+    authenticated_user: AuthenticatedUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatedUser {
+    #[serde(default)]
+    provider_display_name: String,
+}
+
+/// Test Azure DevOps authentication against an organization URL (e.g.
+/// `https://dev.azure.com/myorg`)
+pub async fn test_azure_devops_auth(
+    config: &AzureDevOpsAuthConfig,
+    organization_url: &str,
+) -> Result<String> {
+    let url = format!(
+        "{}/_apis/connectionData",
+        organization_url.trim_end_matches('/')
+    );
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| Error::AzureDevOpsApi(format!("failed to create HTTP client: {e}")))?;
+
+    let response = client
+        .get(&url)
+        .basic_auth("", Some(&config.token))
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| Error::Auth(format!("Invalid token: {e}")))?;
+    // A rejected token can get the sign-in page rather than a 401
+    if response.status() == StatusCode::NON_AUTHORITATIVE_INFORMATION {
+        return Err(Error::Auth(
+            "Invalid token: Azure DevOps answered with its sign-in page".to_string(),
+        ));
+    }
+    let data: ConnectionData = response.json().await?;
+
+    Ok(data.authenticated_user.provider_display_name)
+}
@@ -0,0 +1,97 @@
+//! Bitbucket Cloud authentication
+
+use crate::auth::AuthSource;
+use crate::error::{Error, Result};
+use crate::redact::REDACTED;
+use reqwest::Client;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use tracing::debug;
+
+/// Bitbucket Cloud authentication configuration
+///
+/// Bitbucket has no CLI to borrow credentials from, so this is always an app
+/// password from the environment. `Debug` and `Display` never show it.
+#[derive(Clone)]
+pub struct BitbucketAuthConfig {
+    /// Bitbucket username the app password belongs to
+    pub username: String,
+    /// App password
+    pub app_password: String,
+    /// Where the credentials were obtained from
+    pub source: AuthSource,
+}
+
+impl fmt::Debug for BitbucketAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitbucketAuthConfig")
+            .field("username", &self.username)
+            .field("app_password", &format_args!("{REDACTED}"))
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl fmt::Display for BitbucketAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Bitbucket app password {REDACTED} for {} (from {})",
+            self.username, self.source
+        )
+    }
+}
+
+/// Get Bitbucket Cloud authentication
+///
+/// Reads `BITBUCKET_USERNAME` and `BITBUCKET_APP_PASSWORD`. The app password
+/// needs the pullrequest:write and issue:write scopes.
+pub fn get_bitbucket_auth() -> Result<BitbucketAuthConfig> {
+    debug!("checking Bitbucket env vars");
+    match (
+        env::var("BITBUCKET_USERNAME"),
+        env::var("BITBUCKET_APP_PASSWORD"),
+    ) {
+        (Ok(username), Ok(app_password)) => {
+            debug!("obtained Bitbucket app password from env vars");
+            Ok(BitbucketAuthConfig {
+                username,
+                app_password,
+                source: AuthSource::EnvVar,
+            })
+        }
+        _ => {
+            debug!("no Bitbucket authentication found");
+            Err(Error::Auth(
+                "No Bitbucket authentication found. Set BITBUCKET_USERNAME and BITBUCKET_APP_PASSWORD"
+                    .to_string(),
+            ))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BitbucketUser {
+    nickname: String,
+}
+
+/// Test Bitbucket authentication
+pub async fn test_bitbucket_auth(config: &BitbucketAuthConfig) -> Result<String> {
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| Error::BitbucketApi(format!("failed to create HTTP client: {e}")))?;
+
+    let user: BitbucketUser = client
+        .get("https://api.bitbucket.org/2.0/user")
+        .basic_auth(&config.username, Some(&config.app_password))
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| Error::Auth(format!("Invalid app password: {e}")))?
+        .json()
+        .await?;
+
+    Ok(user.nickname)
+}
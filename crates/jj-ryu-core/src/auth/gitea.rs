@@ -0,0 +1,102 @@
+//! Gitea/Forgejo authentication
+
+use crate::auth::AuthSource;
+use crate::error::{Error, Result};
+use crate::redact::REDACTED;
+use reqwest::Client;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use tracing::debug;
+
+/// Host used when neither the remote nor `GITEA_HOST` names one
+pub const DEFAULT_GITEA_HOST: &str = "codeberg.org";
+
+/// Gitea/Forgejo authentication configuration
+///
+/// `Debug` and `Display` never show the token.
+#[derive(Clone)]
+pub struct GiteaAuthConfig {
+    /// Access token
+    pub token: String,
+    /// Where the token was obtained from
+    pub source: AuthSource,
+    /// Gitea host (e.g., "codeberg.org")
+    pub host: String,
+}
+
+impl fmt::Debug for GiteaAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GiteaAuthConfig")
+            .field("token", &format_args!("{REDACTED}"))
+            .field("source", &self.source)
+            .field("host", &self.host)
+            .finish()
+    }
+}
+
+impl fmt::Display for GiteaAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Gitea token {REDACTED} for {} (from {})",
+            self.host, self.source
+        )
+    }
+}
+
+/// Get Gitea/Forgejo authentication
+///
+/// `tea` keeps its tokens in a config file rather than handing them out, so
+/// only environment variables are read, in order: `GITEA_TOKEN`,
+/// `FORGEJO_TOKEN`. The token needs the repository and issue write scopes.
+pub fn get_gitea_auth(host: Option<&str>) -> Result<GiteaAuthConfig> {
+    let host = host
+        .map(String::from)
+        .or_else(|| env::var("GITEA_HOST").ok())
+        .unwrap_or_else(|| DEFAULT_GITEA_HOST.to_string());
+
+    debug!(host = %host, "checking Gitea env vars");
+    for var in ["GITEA_TOKEN", "FORGEJO_TOKEN"] {
+        if let Ok(token) = env::var(var) {
+            debug!(var, "obtained Gitea token from env var");
+            return Ok(GiteaAuthConfig {
+                token,
+                source: AuthSource::EnvVar,
+                host,
+            });
+        }
+    }
+
+    debug!("no Gitea authentication found");
+    Err(Error::Auth(
+        "No Gitea authentication found. Set GITEA_TOKEN (or FORGEJO_TOKEN)".to_string(),
+    ))
+}
+
+#[derive(Deserialize)]
+struct GiteaUser {
+    login: String,
+}
+
+/// Test Gitea authentication
+pub async fn test_gitea_auth(config: &GiteaAuthConfig) -> Result<String> {
+    let url = format!("https://{}/api/v1/user", config.host);
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| Error::GiteaApi(format!("failed to create HTTP client: {e}")))?;
+
+    let user: GiteaUser = client
+        .get(&url)
+        .bearer_auth(&config.token)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| Error::Auth(format!("Invalid token: {e}")))?
+        .json()
+        .await?;
+
+    Ok(user.login)
+}
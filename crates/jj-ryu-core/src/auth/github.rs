@@ -2,12 +2,16 @@
 
 use crate::auth::AuthSource;
 use crate::error::{Error, Result};
+use crate::redact::REDACTED;
 use std::env;
+use std::fmt;
 use tokio::process::Command;
 use tracing::debug;
 
 /// GitHub authentication configuration
-#[derive(Debug, Clone)]
+///
+/// `Debug` and `Display` never show the token.
+#[derive(Clone)]
 pub struct GitHubAuthConfig {
     /// Authentication token
     pub token: String,
@@ -15,6 +19,21 @@ pub struct GitHubAuthConfig {
     pub source: AuthSource,
 }
 
+impl fmt::Debug for GitHubAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitHubAuthConfig")
+            .field("token", &format_args!("{REDACTED}"))
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl fmt::Display for GitHubAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GitHub token {REDACTED} (from {})", self.source)
+    }
+}
+
 /// Get GitHub authentication
 ///
 /// Priority:
@@ -87,6 +106,7 @@ async fn get_gh_cli_token() -> Option<String> {
 }
 
 /// Test GitHub authentication
+#[cfg(feature = "github")]
 pub async fn test_github_auth(config: &GitHubAuthConfig) -> Result<String> {
     let octocrab = octocrab::Octocrab::builder()
         .personal_token(config.token.clone())
@@ -101,3 +121,12 @@ pub async fn test_github_auth(config: &GitHubAuthConfig) -> Result<String> {
 
     Ok(user.login)
 }
+
+/// Test GitHub authentication
+///
+/// Always fails: this build has no GitHub client to verify the token with.
+#[cfg(not(feature = "github"))]
+#[allow(clippy::unused_async)]
+pub async fn test_github_auth(_config: &GitHubAuthConfig) -> Result<String> {
+    Err(Error::PlatformNotCompiled(crate::types::Platform::GitHub))
+}
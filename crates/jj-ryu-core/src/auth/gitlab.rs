@@ -2,14 +2,18 @@
 
 use crate::auth::AuthSource;
 use crate::error::{Error, Result};
+use crate::redact::REDACTED;
 use reqwest::Client;
 use serde::Deserialize;
 use std::env;
+use std::fmt;
 use tokio::process::Command;
 use tracing::debug;
 
 /// GitLab authentication configuration
-#[derive(Debug, Clone)]
+///
+/// `Debug` and `Display` never show the token.
+#[derive(Clone)]
 pub struct GitLabAuthConfig {
     /// Authentication token
     pub token: String,
@@ -19,6 +23,26 @@ pub struct GitLabAuthConfig {
     pub host: String,
 }
 
+impl fmt::Debug for GitLabAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitLabAuthConfig")
+            .field("token", &format_args!("{REDACTED}"))
+            .field("source", &self.source)
+            .field("host", &self.host)
+            .finish()
+    }
+}
+
+impl fmt::Display for GitLabAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GitLab token {REDACTED} for {} (from {})",
+            self.host, self.source
+        )
+    }
+}
+
 /// Get GitLab authentication
 ///
 /// Priority:
@@ -0,0 +1,35 @@
+//! Authentication for GitHub, GitLab, Bitbucket, Gitea and Azure DevOps
+//!
+//! Supports CLI-based auth (gh, glab) and environment variables.
+
+mod azure_devops;
+mod bitbucket;
+mod gitea;
+mod github;
+mod gitlab;
+
+pub use azure_devops::{
+    AZURE_DEVOPS_ORG_URL_ENV, AzureDevOpsAuthConfig, get_azure_devops_auth, test_azure_devops_auth,
+};
+pub use bitbucket::{BitbucketAuthConfig, get_bitbucket_auth, test_bitbucket_auth};
+pub use gitea::{DEFAULT_GITEA_HOST, GiteaAuthConfig, get_gitea_auth, test_gitea_auth};
+pub use github::{GitHubAuthConfig, get_github_auth, test_github_auth};
+pub use gitlab::{GitLabAuthConfig, get_gitlab_auth, test_gitlab_auth};
+
+/// Source of authentication token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthSource {
+    /// Token from CLI tool (gh or glab)
+    Cli,
+    /// Token from environment variable
+    EnvVar,
+}
+
+impl std::fmt::Display for AuthSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cli => write!(f, "CLI"),
+            Self::EnvVar => write!(f, "environment variable"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Which segment of a stack introduced a regression
+//!
+//! `ryu bisect-stack` checks out each segment boundary - the commit a
+//! segment's bookmark points at - from trunk upwards and runs a test command
+//! there. The first boundary that fails names the PR that broke it.
+//! Reviewers think in PRs, and a stack has far fewer boundaries than
+//! commits, so this is much quicker than `git bisect` over every commit.
+//!
+//! Trunk is tested first: if it already fails, no segment is to blame.
+
+use crate::error::Result;
+use crate::submit::SubmissionAnalysis;
+
+/// A commit to test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BisectStep {
+    /// Bookmark of the segment ending here, `None` for trunk
+    pub bookmark: Option<String>,
+    /// Commit to check out (hex)
+    pub commit_id: String,
+}
+
+/// What testing the boundaries found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BisectResult {
+    /// Trunk already fails, so the regression predates the stack
+    TrunkFails,
+    /// This segment is the first whose boundary fails
+    FirstFailing {
+        /// Bookmark of the segment
+        bookmark: String,
+        /// Its position in the stack, counted from trunk
+        index: usize,
+    },
+    /// Every boundary passes
+    AllPass,
+}
+
+/// Trunk, then each segment's boundary from trunk towards the target
+pub fn bisect_steps(analysis: &SubmissionAnalysis, trunk_commit_id: &str) -> Vec<BisectStep> {
+    std::iter::once(BisectStep {
+        bookmark: None,
+        commit_id: trunk_commit_id.to_string(),
+    })
+    .chain(analysis.segments.iter().map(|segment| BisectStep {
+        bookmark: Some(segment.bookmark.name.clone()),
+        commit_id: segment.bookmark.commit_id.clone(),
+    }))
+    .collect()
+}
+
+/// Run `test` on each step in order until one fails
+///
+/// `test` returns whether the step passes; an error stops the search.
+pub fn find_first_failing(
+    steps: &[BisectStep],
+    mut test: impl FnMut(&BisectStep) -> Result<bool>,
+) -> Result<BisectResult> {
+    let mut segment = 0;
+    for step in steps {
+        if test(step)? {
+            if step.bookmark.is_some() {
+                segment += 1;
+            }
+            continue;
+        }
+        return Ok(match &step.bookmark {
+            None => BisectResult::TrunkFails,
+            Some(bookmark) => BisectResult::FirstFailing {
+                bookmark: bookmark.clone(),
+                index: segment,
+            },
+        });
+    }
+    Ok(BisectResult::AllPass)
+}
@@ -0,0 +1,96 @@
+//! Per-run API budget
+//!
+//! A full sync of a big monorepo can use up the platform's hourly rate
+//! limit. `--max-api-calls` and `--budget` cap one run instead: once either
+//! is used up, submission stops before its next step and leaves its journal
+//! behind for `--resume`, and sync leaves the remaining stacks for the next
+//! run. Calls are counted where [`crate::metrics`] counts them, in
+//! [`MeteredPlatform`], against the [`BudgetUse`] of the run's
+//! [`RunContext`].
+//!
+//! [`MeteredPlatform`]: crate::platform::MeteredPlatform
+//! [`RunContext`]: crate::run::RunContext
+
+use crate::error::{Error, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Limits for one run; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiBudget {
+    /// Platform calls the run may make
+    pub max_calls: Option<u64>,
+    /// Wall-clock time the run may take
+    pub time: Option<Duration>,
+}
+
+/// Calls and time one run has used of its [`ApiBudget`]
+#[derive(Debug)]
+pub struct BudgetUse {
+    budget: ApiBudget,
+    started: Instant,
+    calls: AtomicU64,
+}
+
+impl BudgetUse {
+    /// Start using `budget`, starting its clock
+    pub fn new(budget: ApiBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a platform call against the budget
+    pub fn record_call(&self) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Platform calls counted so far
+    pub fn calls_made(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Why the budget is used up, or `None` while there's some left
+    pub fn exhausted(&self) -> Option<String> {
+        let calls = self.calls_made();
+        if let Some(max) = self.budget.max_calls.filter(|max| calls >= *max) {
+            return Some(format!("{calls} of {max} API calls used"));
+        }
+        self.budget
+            .time
+            .filter(|limit| self.started.elapsed() >= *limit)
+            .map(|limit| format!("{}s time budget used", limit.as_secs()))
+    }
+}
+
+impl Default for BudgetUse {
+    /// An unlimited budget
+    fn default() -> Self {
+        Self::new(ApiBudget::default())
+    }
+}
+
+/// Parse a time budget: seconds, optionally suffixed `s`, `m` or `h`
+/// (`90`, `30s`, `5m`, `1h`)
+pub fn parse_time_budget(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().last() {
+        Some((i, 's')) => (&value[..i], 1),
+        Some((i, 'm')) => (&value[..i], 60),
+        Some((i, 'h')) => (&value[..i], 60 * 60),
+        _ => (value, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .map(|n| Duration::from_secs(n * unit))
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "invalid time budget '{value}' (expected e.g. 30s, 5m or 1h)"
+            ))
+        })
+}
@@ -0,0 +1,214 @@
+//! CODEOWNERS parsing and matching
+//!
+//! Predicts which owners the platform will request on each PR by matching a
+//! segment's changed paths against the workspace's CODEOWNERS file. Matching
+//! follows the platforms' gitignore-style rules: the last matching pattern
+//! wins, and GitLab `[Section]`s each contribute their own last match.
+
+use crate::error::Result;
+use crate::repo::JjWorkspace;
+use crate::submit::SubmissionAnalysis;
+use std::path::Path;
+
+/// Where platforms look for CODEOWNERS, in the order they look
+pub const CODEOWNERS_PATHS: &[&str] = &[
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// One `pattern owner...` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    section: usize,
+    pattern: String,
+    owners: Vec<String>,
+}
+
+/// Parsed CODEOWNERS file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Parse CODEOWNERS content
+    ///
+    /// Comments, blank lines, and rules without owners are ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut section = 0;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // GitLab section headers: `[Name]`, `^[Optional]`, `[Name][2] @owner`
+            if line.starts_with('[') || line.starts_with("^[") {
+                section += 1;
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners: Vec<String> = fields
+                .take_while(|f| !f.starts_with('#'))
+                .map(ToString::to_string)
+                .collect();
+            if !owners.is_empty() {
+                rules.push(Rule {
+                    section,
+                    pattern: pattern.replace("\\ ", " "),
+                    owners,
+                });
+            }
+        }
+        Self { rules }
+    }
+
+    /// Load the workspace's CODEOWNERS, if it has one
+    pub fn load(workspace_root: &Path) -> Result<Option<Self>> {
+        for relative in CODEOWNERS_PATHS {
+            let path = workspace_root.join(relative);
+            if path.is_file() {
+                return Ok(Some(Self::parse(&std::fs::read_to_string(path)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Owners of a repo-relative path (the last match in each section)
+    pub fn owners_for(&self, path: &str) -> Vec<&str> {
+        let mut owners: Vec<&str> = Vec::new();
+        let mut matched_sections: Vec<usize> = Vec::new();
+        for rule in self.rules.iter().rev() {
+            if matched_sections.contains(&rule.section) || !pattern_matches(&rule.pattern, path) {
+                continue;
+            }
+            matched_sections.push(rule.section);
+            for owner in &rule.owners {
+                if !owners.contains(&owner.as_str()) {
+                    owners.push(owner);
+                }
+            }
+        }
+        owners
+    }
+
+    /// Owners of any of `paths`, deduplicated in first-seen order
+    pub fn owners_for_paths<S: AsRef<str>>(&self, paths: &[S]) -> Vec<String> {
+        let mut owners: Vec<String> = Vec::new();
+        for path in paths {
+            for owner in self.owners_for(path.as_ref()) {
+                if !owners.iter().any(|o| o == owner) {
+                    owners.push(owner.to_string());
+                }
+            }
+        }
+        owners
+    }
+}
+
+/// Expected code owners for one bookmark's PR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentOwners {
+    /// Bookmark name
+    pub bookmark: String,
+    /// Owners the platform will request (users, teams, or emails)
+    pub owners: Vec<String>,
+}
+
+/// Match each segment's changed paths against CODEOWNERS
+pub fn expected_owners(
+    workspace: &JjWorkspace,
+    codeowners: &CodeOwners,
+    analysis: &SubmissionAnalysis,
+) -> Result<Vec<SegmentOwners>> {
+    let mut result = Vec::with_capacity(analysis.segments.len());
+    for segment in &analysis.segments {
+        let mut paths = Vec::new();
+        for change in &segment.changes {
+            paths.extend(workspace.changed_paths(&change.commit_id)?);
+        }
+        result.push(SegmentOwners {
+            bookmark: segment.bookmark.name.clone(),
+            owners: codeowners.owners_for_paths(&paths),
+        });
+    }
+    Ok(result)
+}
+
+/// Whether a CODEOWNERS pattern matches a repo-relative file path
+///
+/// A pattern containing a slash (other than a trailing one) is anchored at
+/// the repo root; otherwise it may match at any depth. A match on a
+/// directory covers everything under it, except when the pattern ends in a
+/// wildcard segment like `docs/*`, which only covers direct children.
+pub fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return false;
+    }
+
+    let pattern: Vec<&str> = trimmed.split('/').collect();
+    let path: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let nested = !pattern
+        .last()
+        .is_some_and(|last| *last != "**" && (last.contains('*') || last.contains('?')));
+
+    if anchored {
+        segments_match(&pattern, &path, dir_only, nested)
+    } else {
+        (0..path.len()).any(|start| segments_match(&pattern, &path[start..], dir_only, nested))
+    }
+}
+
+fn segments_match(pattern: &[&str], path: &[&str], dir_only: bool, nested: bool) -> bool {
+    match pattern.split_first() {
+        // A fully matched path is a file; leftover segments mean a directory matched
+        None => {
+            if path.is_empty() {
+                !dir_only
+            } else {
+                nested
+            }
+        }
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skip| segments_match(rest, &path[skip..], dir_only, nested))
+        }
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path_rest)| {
+            glob_segment(segment, name) && segments_match(rest, path_rest, dir_only, nested)
+        }),
+    }
+}
+
+/// Match one path segment against a pattern with `*` and `?` wildcards
+fn glob_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
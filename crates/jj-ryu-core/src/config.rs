@@ -0,0 +1,347 @@
+//! Config files: `~/.config/ryu/config.toml` and `<repo>/.ryu.toml`
+//!
+//! Both files hold the same keys. The repository file wins over the user
+//! file, environment variables win over both, and command-line flags win over
+//! everything. Every key is optional; unset keys keep ryu's built-in
+//! behavior.
+//!
+//! ```toml
+//! remote = "upstream"
+//! default-branch = "develop"
+//! trunk-revset = "develop@origin"
+//! branch-prefix = "alice/"
+//! branch-pattern = "^[a-z0-9/-]+$"
+//! mine-only = true
+//! exclude-bookmarks = ["backup/*"]
+//! mirror-remotes = ["backup"]
+//! pr-remotes = ["gitlab"]
+//! project = "acme/7"
+//! push-force = false
+//! read-only = true
+//! draft = true
+//! stack-info = "body"
+//! interdiff-comments = false
+//! tracking-issue = true
+//! merge-method = "rebase"
+//! auto-bookmark-template = "{user}/{slug}"
+//! merge-commits = "first-parent"
+//! gc-retention-days = 14
+//! sync-prune = true
+//! analyze-checks = true
+//! lock-timeout = 60
+//! reviewers = ["alice"]
+//! reviewer-pool = ["carol", "dave"]
+//! reviewer-policy = "least-loaded"
+//! labels = ["stacked"]
+//! assignees = ["bob"]
+//! theme = "colorblind"
+//!
+//! [colors]
+//! success = "bright-blue bold"
+//!
+//! [templates.api-change]
+//! segments = [{ bookmark = "schema" }, { bookmark = "implementation" }]
+//! ```
+//!
+//! `platform = "fake"` swaps the remote's platform for the simulated one in
+//! [`crate::platform`], for demos and offline trials.
+
+use crate::error::{Error, Result};
+use crate::reviewers::ReviewerPolicy;
+use crate::template::StackTemplate;
+use crate::types::{MergeMethod, StackInfo};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Repository config file name, relative to the workspace root
+pub const REPO_CONFIG_FILE: &str = ".ryu.toml";
+
+/// Platform selected by config instead of detected from the remote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlatformChoice {
+    /// The simulated platform kept in the workspace
+    Fake,
+}
+
+impl std::fmt::Display for PlatformChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fake => write!(f, "fake"),
+        }
+    }
+}
+
+/// How stacks treat merge commits between trunk and a bookmark
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeCommits {
+    /// Leave the bookmark, and bookmarks stacked on it, out of stacks
+    #[default]
+    Exclude,
+    /// Follow each merge's first parent, so the stack runs along that line
+    FirstParent,
+}
+
+impl std::fmt::Display for MergeCommits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exclude => write!(f, "exclude"),
+            Self::FirstParent => write!(f, "first-parent"),
+        }
+    }
+}
+
+/// Named palette for terminal output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Cyan, green, red and yellow
+    #[default]
+    Default,
+    /// Blue for success and magenta for failure instead of green and red
+    Colorblind,
+    /// No colors, only bold, dim and underline
+    Monochrome,
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Colorblind => write!(f, "colorblind"),
+            Self::Monochrome => write!(f, "monochrome"),
+        }
+    }
+}
+
+/// Per-role overrides of the theme's styles
+///
+/// Each value is a space-separated list of a color (`red`, `bright-blue`,
+/// `#d55e00`, ...) and attributes (`bold`, `dimmed`, `italic`,
+/// `underline`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorOverrides {
+    /// Bookmarks, counts and URLs
+    pub accent: Option<String>,
+    /// Checkmarks and completed steps
+    pub success: Option<String>,
+    /// Failures
+    pub error: Option<String>,
+    /// Warnings and "needs push"
+    pub warn: Option<String>,
+    /// Hints and metadata
+    pub muted: Option<String>,
+    /// Headers
+    pub emphasis: Option<String>,
+}
+
+impl ColorOverrides {
+    /// Combine two layers, roles set in `over` winning
+    #[must_use]
+    pub fn overlay(self, over: Self) -> Self {
+        Self {
+            accent: over.accent.or(self.accent),
+            success: over.success.or(self.success),
+            error: over.error.or(self.error),
+            warn: over.warn.or(self.warn),
+            muted: over.muted.or(self.muted),
+            emphasis: over.emphasis.or(self.emphasis),
+        }
+    }
+}
+
+/// Settings from ryu's config files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RyuConfig {
+    /// Remote used when `--remote` isn't given
+    pub remote: Option<String>,
+    /// Remote of your fork: bookmarks are pushed there and PRs opened from
+    /// it against the primary remote's repository
+    pub fork_remote: Option<String>,
+    /// Push-only remotes every bookmark is also pushed to, as with `--mirror`
+    pub mirror_remotes: Option<Vec<String>>,
+    /// Remotes whose platforms also get PRs, as with `--pr-remote`
+    pub pr_remotes: Option<Vec<String>>,
+    /// Project new PRs are filed into, as with `--project`
+    pub project: Option<String>,
+    /// Trunk branch, overriding detection from the remote HEAD
+    pub default_branch: Option<String>,
+    /// Revset for trunk, overriding jj's `trunk()` alias
+    pub trunk_revset: Option<String>,
+    /// Only bookmarks starting with this prefix form stacks
+    pub branch_prefix: Option<String>,
+    /// Regex bookmark names must match before they're pushed
+    pub branch_pattern: Option<String>,
+    /// Only bookmarks on changes you authored form stacks
+    pub mine_only: Option<bool>,
+    /// Only bookmarks matching one of these globs form stacks
+    pub include_bookmarks: Option<Vec<String>>,
+    /// Bookmarks matching any of these globs are left out of stacks
+    pub exclude_bookmarks: Option<Vec<String>>,
+    /// Allow pushes that rewrite a bookmark's history on the remote
+    /// (default true)
+    pub push_force: Option<bool>,
+    /// Skip every platform write and repo change, as with `--read-only`
+    pub read_only: Option<bool>,
+    /// Create new PRs as drafts
+    pub draft: Option<bool>,
+    /// Post and update stack comments on PRs (default true); superseded
+    /// by `stack-info`
+    pub stack_comments: Option<bool>,
+    /// Where the stack navigation goes: a comment (default), the PR body or
+    /// nowhere
+    pub stack_info: Option<StackInfo>,
+    /// Comment on resubmitted PRs with what changed since the last submit
+    /// (default true)
+    pub interdiff_comments: Option<bool>,
+    /// Keep a tracking issue for the stack on every `submit` and `sync`
+    pub tracking_issue: Option<bool>,
+    /// Default method for `ryu merge`
+    pub merge_method: Option<MergeMethod>,
+    /// Name template for `submit --auto-bookmark`
+    pub auto_bookmark_template: Option<String>,
+    /// How stacks treat merge commits (default: exclude the bookmark)
+    pub merge_commits: Option<MergeCommits>,
+    /// Platform to use instead of the one detected from the remote
+    pub platform: Option<PlatformChoice>,
+    /// Record local usage stats (only honored in the user file)
+    pub telemetry: Option<bool>,
+    /// Where `ryu stats --tool --upload` posts the usage summary
+    pub telemetry_upload_url: Option<String>,
+    /// Days `ryu gc` keeps journals and usage events (default 30)
+    pub gc_retention_days: Option<u32>,
+    /// Prune merged bookmarks on every `ryu sync` (default false)
+    pub sync_prune: Option<bool>,
+    /// Look up open PRs with their CI and review state in `ryu` (default
+    /// false)
+    pub analyze_checks: Option<bool>,
+    /// Seconds to wait for a repo locked by another process (default 30; 0
+    /// fails at once)
+    pub lock_timeout: Option<u64>,
+    /// Reviewers requested on every PR ryu creates
+    pub reviewers: Option<Vec<String>>,
+    /// Pool that one reviewer is picked from for each new PR
+    pub reviewer_pool: Option<Vec<String>>,
+    /// How reviewers are picked from `reviewer-pool` (default round-robin)
+    pub reviewer_policy: Option<ReviewerPolicy>,
+    /// Labels added to every PR ryu creates
+    pub labels: Option<Vec<String>>,
+    /// Users assigned to every PR ryu creates
+    pub assignees: Option<Vec<String>>,
+    /// Palette for terminal output (only honored in the user file)
+    pub theme: Option<Theme>,
+    /// Per-role style overrides on top of the theme (user file only)
+    pub colors: ColorOverrides,
+    /// Stack templates for `ryu new-stack`, by name
+    pub templates: BTreeMap<String, StackTemplate>,
+}
+
+impl RyuConfig {
+    /// Path of the user config file (`$XDG_CONFIG_HOME/ryu/config.toml`,
+    /// defaulting to `~/.config/ryu/config.toml`)
+    pub fn user_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join("ryu").join("config.toml"))
+    }
+
+    /// Path of the repository config file for a workspace
+    pub fn repo_path(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(REPO_CONFIG_FILE)
+    }
+
+    /// Parse config file contents; `path` is only used in error messages
+    pub fn parse(contents: &str, path: &Path) -> Result<Self> {
+        toml::from_str(contents)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Read a config file, returning `None` if it doesn't exist
+    pub fn from_file(path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents, path).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load the user file overlaid with the workspace's repository file
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let user = match Self::user_path() {
+            Some(path) => Self::from_file(&path)?.unwrap_or_default(),
+            None => Self::default(),
+        };
+        let repo = Self::from_file(&Self::repo_path(workspace_root))?.unwrap_or_default();
+        Ok(user.overlay(repo))
+    }
+
+    /// Combine two layers, keys set in `over` winning
+    #[must_use]
+    pub fn overlay(self, over: Self) -> Self {
+        Self {
+            remote: over.remote.or(self.remote),
+            fork_remote: over.fork_remote.or(self.fork_remote),
+            mirror_remotes: over.mirror_remotes.or(self.mirror_remotes),
+            pr_remotes: over.pr_remotes.or(self.pr_remotes),
+            project: over.project.or(self.project),
+            default_branch: over.default_branch.or(self.default_branch),
+            trunk_revset: over.trunk_revset.or(self.trunk_revset),
+            branch_prefix: over.branch_prefix.or(self.branch_prefix),
+            branch_pattern: over.branch_pattern.or(self.branch_pattern),
+            mine_only: over.mine_only.or(self.mine_only),
+            include_bookmarks: over.include_bookmarks.or(self.include_bookmarks),
+            exclude_bookmarks: over.exclude_bookmarks.or(self.exclude_bookmarks),
+            push_force: over.push_force.or(self.push_force),
+            read_only: over.read_only.or(self.read_only),
+            draft: over.draft.or(self.draft),
+            stack_comments: over.stack_comments.or(self.stack_comments),
+            stack_info: over.stack_info.or(self.stack_info),
+            interdiff_comments: over.interdiff_comments.or(self.interdiff_comments),
+            tracking_issue: over.tracking_issue.or(self.tracking_issue),
+            merge_method: over.merge_method.or(self.merge_method),
+            auto_bookmark_template: over.auto_bookmark_template.or(self.auto_bookmark_template),
+            merge_commits: over.merge_commits.or(self.merge_commits),
+            platform: over.platform.or(self.platform),
+            telemetry: over.telemetry.or(self.telemetry),
+            telemetry_upload_url: over.telemetry_upload_url.or(self.telemetry_upload_url),
+            gc_retention_days: over.gc_retention_days.or(self.gc_retention_days),
+            sync_prune: over.sync_prune.or(self.sync_prune),
+            analyze_checks: over.analyze_checks.or(self.analyze_checks),
+            lock_timeout: over.lock_timeout.or(self.lock_timeout),
+            reviewers: over.reviewers.or(self.reviewers),
+            reviewer_pool: over.reviewer_pool.or(self.reviewer_pool),
+            reviewer_policy: over.reviewer_policy.or(self.reviewer_policy),
+            labels: over.labels.or(self.labels),
+            assignees: over.assignees.or(self.assignees),
+            theme: over.theme.or(self.theme),
+            colors: self.colors.overlay(over.colors),
+            // Templates are merged by name, the repository's winning
+            templates: self.templates.into_iter().chain(over.templates).collect(),
+        }
+    }
+
+    /// Whether a bookmark is covered by `branch-prefix`
+    pub fn matches_prefix(&self, bookmark: &str) -> bool {
+        self.branch_prefix
+            .as_deref()
+            .is_none_or(|prefix| bookmark.starts_with(prefix))
+    }
+
+    /// Where the stack navigation goes: `stack-info`, else nowhere with
+    /// `stack-comments = false`, else a comment
+    pub fn stack_info(&self) -> StackInfo {
+        match (self.stack_info, self.stack_comments) {
+            (Some(mode), _) => mode,
+            (None, Some(false)) => StackInfo::None,
+            (None, _) => StackInfo::Comment,
+        }
+    }
+}
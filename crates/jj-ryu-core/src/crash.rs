@@ -0,0 +1,121 @@
+//! Crash reports for panics
+//!
+//! A panic means a bug in ryu. Instead of a bare backtrace, write a report
+//! with enough context to reproduce it and point the user at the issue
+//! tracker. Everything in the report goes through [`redact_secrets`].
+
+use crate::redact::redact_secrets;
+use crate::repo::installed_jj_version;
+use chrono::Utc;
+use std::backtrace::Backtrace;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Where to file bug reports
+pub const ISSUES_URL: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "/issues");
+
+/// Context captured when ryu panics
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    /// ryu version
+    pub version: String,
+    /// Output of `jj --version`, if jj is installed
+    pub jj_version: Option<String>,
+    /// Operating system and architecture
+    pub platform: String,
+    /// Command line that crashed
+    pub command: String,
+    /// Panic message
+    pub message: String,
+    /// Source location of the panic
+    pub location: Option<String>,
+    /// Captured backtrace
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    /// Capture a report for a panic
+    pub fn capture(message: &str, location: Option<String>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            jj_version: installed_jj_version(),
+            platform: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            command: std::env::args().collect::<Vec<_>>().join(" "),
+            message: message.to_string(),
+            location,
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+    }
+
+    /// Render the report as plain text, with secrets redacted
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "ryu crash report");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "version:  {}", self.version);
+        let _ = writeln!(
+            out,
+            "jj:       {}",
+            self.jj_version.as_deref().unwrap_or("(not found)")
+        );
+        let _ = writeln!(out, "platform: {}", self.platform);
+        let _ = writeln!(out, "command:  {}", self.command);
+        let _ = writeln!(
+            out,
+            "location: {}",
+            self.location.as_deref().unwrap_or("(unknown)")
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out, "message:");
+        let _ = writeln!(out, "{}", self.message);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "backtrace:");
+        let _ = writeln!(out, "{}", self.backtrace);
+        redact_secrets(&out)
+    }
+
+    /// Write the report to a timestamped file in `dir`
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        let name = format!("ryu-crash-{}.txt", Utc::now().format("%Y%m%dT%H%M%S%.3fZ"));
+        let path = dir.join(name);
+        std::fs::write(&path, self.render())?;
+        Ok(path)
+    }
+}
+
+/// Install a panic hook that writes a crash report
+///
+/// The report goes to the system temp directory. The terminal only gets a
+/// short redacted summary and the path to attach to an issue.
+pub fn install_crash_handler() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+        let report = CrashReport::capture(&message, location);
+        eprintln!("ryu crashed - this is a bug.");
+        eprintln!();
+        eprintln!("  {}", redact_secrets(&message));
+        eprintln!();
+        match report.write_to(&std::env::temp_dir()) {
+            Ok(path) => {
+                eprintln!("A crash report (tokens redacted) was written to:");
+                eprintln!("  {}", path.display());
+                eprintln!();
+                eprintln!("Please attach it to an issue at {ISSUES_URL}");
+            }
+            Err(_) => {
+                eprintln!("Please report this at {ISSUES_URL} with the following:");
+                eprintln!();
+                eprintln!("{}", report.render());
+            }
+        }
+    }));
+}
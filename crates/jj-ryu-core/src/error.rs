@@ -0,0 +1,258 @@
+//! Error types for jj-ryu
+//!
+//! Uses thiserror for structured errors that can be mapped to HTTP status codes
+//! in future web server implementations.
+//!
+//! Messages that can carry request URLs or remote output are passed through
+//! [`redact_secrets`] so tokens never reach the terminal.
+
+use crate::redact::redact_secrets;
+use crate::types::Platform;
+use thiserror::Error;
+
+/// Main error type for jj-ryu operations
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Failed to load or interact with jj workspace
+    #[error("workspace error: {0}")]
+    Workspace(String),
+
+    /// Failed to parse jj output or data
+    #[error("parse error: {0}")]
+    Parse(String),
+
+    /// Bookmark not found in repository
+    #[error("bookmark '{0}' not found")]
+    BookmarkNotFound(String),
+
+    /// No supported remotes (GitHub/GitLab/Bitbucket/Gitea/Azure DevOps) found
+    #[error(
+        "no supported remotes found (GitHub/GitLab/Bitbucket/Gitea/Azure DevOps) - map self-hosted hosts with RYU_PLATFORM_HOSTS"
+    )]
+    NoSupportedRemotes,
+
+    /// Specified remote not found
+    #[error("remote '{0}' not found")]
+    RemoteNotFound(String),
+
+    /// Authentication failed
+    #[error("authentication failed: {}", redact_secrets(.0))]
+    Auth(String),
+
+    /// GitHub API error
+    #[error("GitHub API error: {}", redact_secrets(.0))]
+    GitHubApi(String),
+
+    /// GitLab API error
+    #[error("GitLab API error: {}", redact_secrets(.0))]
+    GitLabApi(String),
+
+    /// Bitbucket API error
+    #[error("Bitbucket API error: {}", redact_secrets(.0))]
+    BitbucketApi(String),
+
+    /// Gitea/Forgejo API error
+    #[error("Gitea API error: {}", redact_secrets(.0))]
+    GiteaApi(String),
+
+    /// Azure DevOps API error
+    #[error("Azure DevOps API error: {}", redact_secrets(.0))]
+    AzureDevOpsApi(String),
+
+    /// Merge commit detected (cannot stack)
+    #[error("merge commit detected in bookmark '{0}' history - rebasing required")]
+    MergeCommitDetected(String),
+
+    /// Revset evaluation failed
+    #[error("revset error: {0}")]
+    Revset(String),
+
+    /// Git operation failed
+    #[error("git operation failed: {}", redact_secrets(.0))]
+    Git(String),
+
+    /// Invalid configuration
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// HTTP request error
+    #[error("HTTP error: {}", redact_secrets(&.0.to_string()))]
+    Http(#[from] reqwest::Error),
+
+    /// JSON serialization/deserialization error
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// URL parsing error
+    #[error("URL parse error: {0}")]
+    UrlParse(#[from] url::ParseError),
+
+    /// Octocrab (GitHub) error
+    #[cfg(feature = "github")]
+    #[error("GitHub client error: {}", redact_secrets(&.0.to_string()))]
+    Octocrab(#[from] octocrab::Error),
+
+    /// Platform service not compiled into this build
+    #[error(
+        "{0} support is not compiled into this build - rebuild with the `{feature}` cargo feature",
+        feature = .0.feature_name()
+    )]
+    PlatformNotCompiled(Platform),
+
+    /// Platform API error (generic)
+    #[error("platform error: {}", redact_secrets(.0))]
+    Platform(String),
+
+    /// Internal error (unexpected state)
+    #[error("internal error: {0}")]
+    Internal(String),
+
+    /// Scheduler detected a cycle in execution dependencies (indicates a bug)
+    #[error("scheduler cycle detected: {message}")]
+    SchedulerCycle {
+        /// Human-readable description
+        message: String,
+        /// Node names involved in the cycle (for debugging)
+        cycle_nodes: Vec<String>,
+    },
+
+    /// Submission contains commits authored by someone else
+    #[error(
+        "{0} commit(s) in the stack were authored by someone else - pass --allow-foreign-commits to submit anyway"
+    )]
+    ForeignCommits(usize),
+
+    /// Bookmarks in the submission have names git or `branch-pattern` reject
+    #[error(
+        "{0} bookmark name(s) can't be pushed - rename them with `jj bookmark rename <old> <new>`"
+    )]
+    InvalidBookmarkNames(usize),
+
+    /// Some stacks of a multi-stack submission failed
+    #[error("{0} stack(s) couldn't be submitted - see the errors above")]
+    StacksFailed(usize),
+
+    /// Warnings were reported in `--strict` mode
+    #[error("{0} warning(s) reported and --strict is set - see the warnings above")]
+    StrictWarnings(usize),
+
+    /// A push would rewrite remote history while `push-force` is disabled
+    #[error(
+        "pushing '{bookmark}' would overwrite {behind} commit(s) on {remote} and force pushes are disabled (push-force = false)\nRebase onto the remote bookmark so the push fast-forwards: `jj rebase -b {bookmark} -d {bookmark}@{remote}`"
+    )]
+    ForcePushDisabled {
+        /// Bookmark that would be force pushed
+        bookmark: String,
+        /// Remote being pushed to
+        remote: String,
+        /// Remote commits the push would drop
+        behind: usize,
+    },
+
+    /// Invalid command-line argument
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// Repository data written by a jj newer than the linked jj-lib
+    #[error(
+        "failed to read the repo: {detail}\nThe installed jj {installed} is newer than jj {supported}, which this ryu was built for - upgrade ryu"
+    )]
+    IncompatibleJj {
+        /// Installed jj version
+        installed: String,
+        /// jj release series ryu was built against
+        supported: String,
+        /// Underlying jj-lib error
+        detail: String,
+    },
+
+    /// Another operation holds a lock on the stack
+    #[error(
+        "stack is locked by `ryu {operation}` ({holder}) since {since} - wait for it to finish"
+    )]
+    StackLocked {
+        /// Operation holding the lock
+        operation: String,
+        /// Who holds the lock
+        holder: String,
+        /// When the lock was taken (RFC 3339)
+        since: String,
+    },
+
+    /// Rebasing stacks onto the updated trunk produced conflicted commits
+    #[error(
+        "restacking onto trunk left conflicts in {}\nResolve them and run `ryu rebase-continue`, or `jj undo` to drop the restack",
+        .0.join(", ")
+    )]
+    RestackConflicts(Vec<String>),
+
+    /// A PR was changed on the platform after the submission was planned
+    #[error(
+        "PR #{pr_number} changed since the submission was planned ({detail}) - rerun to plan against its current state"
+    )]
+    PrChangedSincePlan {
+        /// PR that changed
+        pr_number: u64,
+        /// What changed
+        detail: String,
+    },
+
+    /// An exported plan doesn't fit the repo it's being applied to
+    #[error("plan can't be applied here: {0}")]
+    PlanMismatch(String),
+
+    /// Self-update could not complete
+    #[error("self-update failed: {0}")]
+    SelfUpdate(String),
+}
+
+impl Error {
+    /// Coarse category for usage stats; carries none of the error's details
+    // The octocrab arm can't join the other platform errors behind its cfg
+    #[allow(clippy::match_same_arms)]
+    pub const fn category(&self) -> &'static str {
+        match self {
+            Self::Workspace(_)
+            | Self::Parse(_)
+            | Self::Revset(_)
+            | Self::MergeCommitDetected(_)
+            | Self::IncompatibleJj { .. } => "repo",
+            Self::Git(_) => "git",
+            Self::Auth(_) => "auth",
+            Self::GitHubApi(_)
+            | Self::GitLabApi(_)
+            | Self::BitbucketApi(_)
+            | Self::GiteaApi(_)
+            | Self::AzureDevOpsApi(_)
+            | Self::Platform(_)
+            | Self::Http(_) => "platform",
+            #[cfg(feature = "github")]
+            Self::Octocrab(_) => "platform",
+            Self::BookmarkNotFound(_)
+            | Self::NoSupportedRemotes
+            | Self::RemoteNotFound(_)
+            | Self::Config(_)
+            | Self::InvalidArgument(_)
+            | Self::PlatformNotCompiled(_) => "usage",
+            Self::Io(_) | Self::Json(_) | Self::UrlParse(_) => "io",
+            Self::ForeignCommits(_)
+            | Self::InvalidBookmarkNames(_)
+            | Self::StacksFailed(_)
+            | Self::StrictWarnings(_)
+            | Self::ForcePushDisabled { .. }
+            | Self::StackLocked { .. }
+            | Self::RestackConflicts(_)
+            | Self::PrChangedSincePlan { .. }
+            | Self::PlanMismatch(_) => "blocked",
+            Self::Internal(_) | Self::SchedulerCycle { .. } => "internal",
+            Self::SelfUpdate(_) => "selfupdate",
+        }
+    }
+}
+
+/// Result type alias for jj-ryu operations
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,255 @@
+//! Pruning ryu's local files
+//!
+//! ryu keeps its bookkeeping under `.jj/ryu` (the state store and the
+//! submission and restack journals) and, with telemetry on, a usage log in
+//! the user's state directory. Over time they collect entries for bookmarks
+//! that are gone and journals nobody will resume. `ryu gc` prunes all of
+//! them; a successful `sync` runs the light pass, which leaves the usage log
+//! and leftover temp files alone.
+
+use crate::config::RyuConfig;
+use crate::error::Result;
+use crate::repo::JjWorkspace;
+use crate::restack::{clear_restack_journal, load_restack_journal};
+use crate::state::RyuState;
+use crate::submit::{prune_journals, prune_snapshots};
+use crate::telemetry::prune_events;
+use crate::types::ChangeGraph;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Days journals and usage events are kept when `gc-retention-days` is unset
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Days journals and usage events are kept (`gc-retention-days`)
+pub fn retention_days(config: &RyuConfig) -> u32 {
+    config.gc_retention_days.unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// How much to prune
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcScope {
+    /// The state store and journals only; cheap enough to run after a sync
+    Light,
+    /// Also the usage log and temp files left by interrupted writes
+    Full,
+}
+
+/// What to prune and when "now" is
+#[derive(Debug, Clone)]
+pub struct GcOptions {
+    /// How much to prune
+    pub scope: GcScope,
+    /// Journals and usage events older than this many days are dropped
+    pub retention_days: u32,
+    /// Usage log to prune (full scope only)
+    pub usage_path: Option<PathBuf>,
+    /// Report what would be pruned without writing anything
+    pub dry_run: bool,
+    /// Reference time for ages and stale locks
+    pub now: DateTime<Utc>,
+}
+
+impl GcOptions {
+    /// Options for a real run of `scope` as of now, without a usage log
+    pub fn new(scope: GcScope, retention_days: u32) -> Self {
+        Self {
+            scope,
+            retention_days,
+            usage_path: None,
+            dry_run: false,
+            now: Utc::now(),
+        }
+    }
+}
+
+/// What a garbage collection pruned (or would have, in a dry run)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Bookmarks whose PR base override was dropped
+    pub base_overrides: Vec<String>,
+    /// Bookmarks whose adopted PR record was dropped
+    pub adopted_prs: Vec<String>,
+    /// Stale stack locks dropped, by stack key
+    pub stale_locks: Vec<String>,
+    /// Landed-change records dropped for changes no longer in any stack
+    pub landed: usize,
+    /// Submission journals dropped, by remote and leaf bookmark
+    pub journals: Vec<String>,
+    /// Interdiff snapshots dropped for deleted bookmarks, by remote and
+    /// bookmark
+    pub snapshots: Vec<String>,
+    /// Whether the restack journal was dropped
+    pub restack_journal: bool,
+    /// Usage events dropped
+    pub usage_events: usize,
+    /// Temp files removed from the state directory
+    pub temp_files: Vec<PathBuf>,
+}
+
+impl GcReport {
+    /// Whether nothing was pruned
+    pub fn is_empty(&self) -> bool {
+        self.base_overrides.is_empty()
+            && self.adopted_prs.is_empty()
+            && self.stale_locks.is_empty()
+            && self.landed == 0
+            && self.journals.is_empty()
+            && self.snapshots.is_empty()
+            && !self.restack_journal
+            && self.usage_events == 0
+            && self.temp_files.is_empty()
+    }
+}
+
+/// Prune ryu's local files for a workspace
+///
+/// Dropped: base overrides, adopted PR records, interdiff snapshots and
+/// journals for bookmarks that no longer exist, stale stack locks, landed records for changes that
+/// left `graph`, and journals and usage events older than the retention
+/// period. A restack
+/// journal is kept while any of its bookmarks exists, however old, since
+/// `ryu rebase-continue` still needs it.
+pub fn collect_garbage(
+    workspace_root: &Path,
+    graph: &ChangeGraph,
+    local_bookmarks: &HashSet<String>,
+    options: &GcOptions,
+) -> Result<GcReport> {
+    let mut report = GcReport::default();
+    let cutoff = options.now - chrono::Duration::days(i64::from(options.retention_days));
+
+    let mut state = RyuState::load(workspace_root)?;
+    report.base_overrides = state
+        .base_overrides
+        .keys()
+        .filter(|bookmark| !local_bookmarks.contains(*bookmark))
+        .cloned()
+        .collect();
+    report.adopted_prs = state
+        .adopted_prs
+        .keys()
+        .filter(|bookmark| !local_bookmarks.contains(*bookmark))
+        .cloned()
+        .collect();
+    report.stale_locks = state
+        .stack_locks
+        .iter()
+        .filter(|(_, lock)| lock.is_stale(options.now))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let in_graph: HashSet<&str> = graph
+        .stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.changes)
+        .map(|change| change.change_id.as_str())
+        .collect();
+    report.landed = state
+        .landed
+        .keys()
+        .filter(|change_id| !in_graph.contains(change_id.as_str()))
+        .count();
+    if !options.dry_run
+        && (!report.base_overrides.is_empty()
+            || !report.adopted_prs.is_empty()
+            || !report.stale_locks.is_empty()
+            || report.landed > 0)
+    {
+        state
+            .base_overrides
+            .retain(|bookmark, _| local_bookmarks.contains(bookmark));
+        state
+            .adopted_prs
+            .retain(|bookmark, _| local_bookmarks.contains(bookmark));
+        state
+            .stack_locks
+            .retain(|_, lock| !lock.is_stale(options.now));
+        state
+            .landed
+            .retain(|change_id, _| in_graph.contains(change_id.as_str()));
+        state.save(workspace_root)?;
+    }
+
+    report.journals = prune_journals(
+        workspace_root,
+        |journal| {
+            journal.started_at >= cutoff
+                && journal
+                    .plan
+                    .segments
+                    .iter()
+                    .all(|segment| local_bookmarks.contains(&segment.bookmark.name))
+        },
+        options.dry_run,
+    )?;
+    report.snapshots = prune_snapshots(
+        workspace_root,
+        |snapshot| local_bookmarks.contains(&snapshot.bookmark),
+        options.dry_run,
+    )?;
+
+    if let Some(journal) = load_restack_journal(workspace_root)? {
+        report.restack_journal = !journal
+            .bookmarks()
+            .iter()
+            .any(|bookmark| local_bookmarks.contains(bookmark));
+        if report.restack_journal && !options.dry_run {
+            clear_restack_journal(workspace_root)?;
+        }
+    }
+
+    if options.scope == GcScope::Full {
+        if let Some(path) = &options.usage_path {
+            report.usage_events = prune_events(path, cutoff, options.dry_run)?;
+        }
+        report.temp_files = remove_temp_files(&RyuState::dir(workspace_root), options.dry_run)?;
+    }
+
+    Ok(report)
+}
+
+/// Names of the workspace's local bookmarks, as [`collect_garbage`] takes them
+pub fn local_bookmark_names(workspace: &JjWorkspace) -> Result<HashSet<String>> {
+    Ok(workspace
+        .local_bookmarks()?
+        .into_iter()
+        .map(|bookmark| bookmark.name)
+        .collect())
+}
+
+/// Run the light pass for a workspace, e.g. after a successful sync
+///
+/// Failing to is only worth a debug log; whatever ran before went through.
+pub fn light_gc(workspace: &JjWorkspace, graph: &ChangeGraph) {
+    let options = GcOptions::new(GcScope::Light, retention_days(workspace.config()));
+    let result = local_bookmark_names(workspace).and_then(|bookmarks| {
+        collect_garbage(workspace.workspace_root(), graph, &bookmarks, &options)
+    });
+    match result {
+        Ok(report) => debug!(?report, "pruned local state"),
+        Err(e) => debug!(error = %e, "failed to prune local state"),
+    }
+}
+
+/// Remove the `*.tmp` files interrupted atomic writes left in `dir`
+fn remove_temp_files(dir: &Path, dry_run: bool) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "tmp") && path.is_file() {
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+            removed.push(path);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
@@ -1,21 +1,40 @@
 //! Change graph builder
 //!
 //! Builds a `ChangeGraph` from jj workspace state using jj-lib APIs.
-
-use crate::error::Result;
+//!
+//! One revset query loads every change between trunk and the bookmarks;
+//! each bookmark's `trunk()..bookmark` is then read off that list instead of
+//! being queried again, so overlapping history is only walked once. Stacks
+//! aimed at another long-lived branch are measured from that branch instead
+//! ([`build_change_graph_onto`]).
+//!
+//! By default a bookmark with a merge commit between it and trunk is
+//! excluded, along with bookmarks stacked on it. With `merge-commits =
+//! "first-parent"` the traversal instead follows each merge's first parent,
+//! so the linear line leading up to (and through) a megamerge still stacks.
+
+use crate::config::MergeCommits;
+use crate::error::{Error, Result};
+use crate::graph::BookmarkFilter;
 use crate::repo::JjWorkspace;
-use crate::types::{Bookmark, BookmarkSegment, BranchStack, ChangeGraph, LogEntry};
+use crate::types::{
+    Bookmark, BookmarkSegment, BranchStack, ChangeGraph, ExcludedBookmark, ExclusionReason,
+    LogEntry,
+};
 use std::collections::{HashMap, HashSet};
 use tracing::debug;
 
+/// Revset stacks are measured from by default
+const TRUNK: &str = "trunk()";
+
 /// Result from traversing a bookmark toward trunk
 struct TraversalResult {
     /// Segments discovered (ordered from bookmark back to trunk)
     segments: Vec<RawSegment>,
     /// If we hit a fully-collected bookmark, its change ID
     already_seen_change_id: Option<String>,
-    /// Number of bookmarks excluded due to merge commits
-    excluded_bookmark_count: usize,
+    /// Set if the bookmark was excluded (merge commit or tainted ancestor)
+    exclusion: Option<ExclusionReason>,
     /// Change IDs that should be marked as tainted (due to merge commits)
     newly_tainted_change_ids: Vec<String>,
 }
@@ -26,16 +45,98 @@ struct RawSegment {
     changes: Vec<LogEntry>,
 }
 
+/// Every change between trunk and the bookmarks, in revset order
+/// (children before parents)
+struct ChangeIndex {
+    changes: Vec<LogEntry>,
+    /// Position in `changes` by commit ID
+    positions: HashMap<String, usize>,
+}
+
+impl ChangeIndex {
+    fn new(changes: Vec<LogEntry>) -> Self {
+        let positions = changes
+            .iter()
+            .enumerate()
+            .map(|(i, change)| (change.commit_id.clone(), i))
+            .collect();
+        Self { changes, positions }
+    }
+
+    /// Load `base..(all bookmark heads)` in one query
+    fn load(workspace: &JjWorkspace, base: &str, bookmarks: &[Bookmark]) -> Result<Self> {
+        if bookmarks.is_empty() {
+            return Ok(Self::new(Vec::new()));
+        }
+        let heads: Vec<&str> = bookmarks.iter().map(|b| b.commit_id.as_str()).collect();
+        let changes = workspace.resolve_revset(&format!("({base})..({})", heads.join(" | ")))?;
+        debug!(
+            "Loaded {} changes under {} bookmarks",
+            changes.len(),
+            bookmarks.len()
+        );
+        Ok(Self::new(changes))
+    }
+
+    /// `base..tip`, in revset order
+    ///
+    /// Every ancestor of `tip` outside the base is reachable through parents
+    /// that are outside it too, so walking parents within the index finds
+    /// exactly that set.
+    fn ancestry(&self, tip: &str) -> Vec<LogEntry> {
+        let mut found: Vec<usize> = Vec::new();
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut pending = vec![tip];
+        while let Some(commit_id) = pending.pop() {
+            let Some(&i) = self.positions.get(commit_id) else {
+                continue;
+            };
+            if visited.insert(i) {
+                found.push(i);
+                pending.extend(self.changes[i].parents.iter().map(String::as_str));
+            }
+        }
+        found.sort_unstable();
+        found.into_iter().map(|i| self.changes[i].clone()).collect()
+    }
+
+    /// The changes on `tip`'s first-parent line, tip first
+    ///
+    /// The line ends where it reaches trunk. Changes only reachable through
+    /// a merge's other parents are left out.
+    fn first_parent_line(&self, tip: &str) -> Vec<LogEntry> {
+        let mut line = Vec::new();
+        let mut next = Some(tip);
+        while let Some(&i) = next.and_then(|commit_id| self.positions.get(commit_id)) {
+            let change = &self.changes[i];
+            next = change.parents.first().map(String::as_str);
+            line.push(change.clone());
+        }
+        line
+    }
+}
+
 /// Build a change graph from the current workspace state
 ///
 /// This analyzes all bookmarks owned by the current user and builds
 /// a graph showing how they stack on top of each other.
-#[allow(clippy::too_many_lines)]
 pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
-    debug!("Discovering user bookmarks...");
+    build_change_graph_onto(workspace, TRUNK)
+}
 
-    // Get all local bookmarks
-    let all_bookmarks = workspace.local_bookmarks()?;
+/// Build a change graph whose stacks start at `base` (a revset) rather than
+/// at trunk
+///
+/// For stacks aimed at a long-lived branch such as a release branch: changes
+/// already on `base` belong to no segment, and a stack's root is the first
+/// bookmark above it.
+#[allow(clippy::too_many_lines)]
+pub fn build_change_graph_onto(workspace: &JjWorkspace, base: &str) -> Result<ChangeGraph> {
+    debug!(base, "Discovering user bookmarks...");
+
+    // Get local bookmarks (within `branch-prefix` and the bookmark filter)
+    let all_bookmarks: Vec<Bookmark> = BookmarkFilter::from_config(workspace.config())
+        .apply(workspace, workspace.local_bookmarks()?)?;
 
     debug!(
         "Found {} bookmarks: {:?}",
@@ -49,6 +150,9 @@ pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
         .map(|b| (b.name.clone(), b.clone()))
         .collect();
 
+    // Everything between trunk and the bookmarks, queried once
+    let index = ChangeIndex::load(workspace, base, &all_bookmarks)?;
+
     // Data structures for the algorithm
     let mut fully_collected_bookmarks: HashSet<String> = HashSet::new();
     let mut bookmark_to_change_id: HashMap<String, String> = HashMap::new();
@@ -56,7 +160,7 @@ pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
     let mut bookmarked_change_id_to_segment: HashMap<String, Vec<LogEntry>> = HashMap::new();
     let mut stack_roots: HashSet<String> = HashSet::new();
     let mut tainted_change_ids: HashSet<String> = HashSet::new();
-    let mut total_excluded_bookmark_count = 0;
+    let mut excluded_bookmarks: Vec<ExcludedBookmark> = Vec::new();
 
     // Process each bookmark to collect segment changes
     for bookmark in &all_bookmarks {
@@ -69,20 +173,21 @@ pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
 
         let result = traverse_and_discover_segments(
             workspace,
+            &index,
             bookmark,
             &fully_collected_bookmarks,
             &tainted_change_ids,
         )?;
 
         // Handle excluded bookmarks (those that encountered merges)
-        if result.excluded_bookmark_count > 0 {
+        if let Some(reason) = result.exclusion {
             // Add newly tainted change IDs for future traversals
             tainted_change_ids.extend(result.newly_tainted_change_ids);
-            total_excluded_bookmark_count += result.excluded_bookmark_count;
-            debug!(
-                "  Excluded {} due to merge commit in history",
-                bookmark.name
-            );
+            debug!("  Excluded {}: {}", bookmark.name, reason);
+            excluded_bookmarks.push(ExcludedBookmark {
+                name: bookmark.name.clone(),
+                reason,
+            });
             continue;
         }
 
@@ -137,7 +242,7 @@ pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
                 }
             }
         } else if let Some(root_segment) = result.segments.last() {
-            // We reached trunk, so the last segment is a root
+            // We reached the base, so the last segment is a root
             if !root_segment.changes.is_empty() {
                 let root_id = root_segment.changes[0].change_id.clone();
                 stack_roots.insert(root_id);
@@ -179,13 +284,66 @@ pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
         stack_leafs,
         stack_roots,
         stacks,
-        excluded_bookmark_count: total_excluded_bookmark_count,
+        excluded_bookmarks,
+    })
+}
+
+/// Commit a base branch points at, for [`build_change_graph_onto`]
+///
+/// The branch as last fetched from `remote` is preferred, since that's what
+/// PRs are opened against; a local bookmark of that name is the fallback.
+pub fn resolve_base_branch(workspace: &JjWorkspace, branch: &str, remote: &str) -> Result<String> {
+    let bookmark = match workspace.get_remote_bookmark(branch, remote)? {
+        Some(bookmark) => Some(bookmark),
+        None => workspace.get_local_bookmark(branch)?,
+    };
+    bookmark.map(|b| b.commit_id).ok_or_else(|| {
+        Error::InvalidArgument(format!(
+            "base branch '{branch}' not found on {remote} or locally"
+        ))
     })
 }
 
+/// Bookmark of the stack segment the working copy is on
+///
+/// Looks at `trunk()..@`, so `@` itself counts when it is inside a segment,
+/// and otherwise its closest ancestor in one (e.g. an unbookmarked change
+/// on top of a stack). `None` when `@` isn't on any stack.
+pub fn working_copy_bookmark(
+    workspace: &JjWorkspace,
+    graph: &ChangeGraph,
+) -> Result<Option<String>> {
+    let ancestry = workspace.resolve_revset("trunk()..@")?;
+    Ok(closest_segment_bookmark(graph, &ancestry))
+}
+
+/// Bookmark of the segment holding the first of `ancestry` that is in one
+///
+/// `ancestry` is in revset order (children before parents). A segment with
+/// several bookmarks yields its first.
+pub fn closest_segment_bookmark(graph: &ChangeGraph, ancestry: &[LogEntry]) -> Option<String> {
+    let segment_by_commit: HashMap<&str, &BookmarkSegment> = graph
+        .stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| {
+            segment
+                .changes
+                .iter()
+                .map(move |change| (change.commit_id.as_str(), segment))
+        })
+        .collect();
+    ancestry
+        .iter()
+        .find_map(|change| segment_by_commit.get(change.commit_id.as_str()))
+        .and_then(|segment| segment.bookmarks.first())
+        .map(|bookmark| bookmark.name.clone())
+}
+
 /// Traverse from a bookmark toward trunk, discovering segments and relationships
 fn traverse_and_discover_segments(
     workspace: &JjWorkspace,
+    index: &ChangeIndex,
     bookmark: &Bookmark,
     fully_collected_bookmarks: &HashSet<String>,
     tainted_change_ids: &HashSet<String>,
@@ -195,19 +353,25 @@ fn traverse_and_discover_segments(
     let mut already_seen_change_id: Option<String> = None;
     let mut seen_change_ids: Vec<String> = Vec::new();
 
-    // Query trunk..bookmark to get all commits in between
-    let revset = format!("trunk()..{}", bookmark.commit_id);
-    let changes = workspace.resolve_revset(&revset)?;
+    // trunk..bookmark, read off the index
+    let first_parent =
+        workspace.config().merge_commits.unwrap_or_default() == MergeCommits::FirstParent;
+    let changes = if first_parent {
+        index.first_parent_line(&bookmark.commit_id)
+    } else {
+        index.ancestry(&bookmark.commit_id)
+    };
 
     // Check for merge commits or already-tainted changes
     for change in &changes {
         seen_change_ids.push(change.change_id.clone());
 
         // Check if this change is a merge commit or already tainted
-        if change.parents.len() > 1 || tainted_change_ids.contains(&change.change_id) {
+        let is_merge = change.parents.len() > 1 && !first_parent;
+        if is_merge || tainted_change_ids.contains(&change.change_id) {
             debug!(
                 "Found {} in bookmark {} - excluding bookmark and descendants",
-                if change.parents.len() > 1 {
+                if is_merge {
                     "merge commit"
                 } else {
                     "tainted change"
@@ -215,11 +379,21 @@ fn traverse_and_discover_segments(
                 bookmark.name
             );
 
+            let reason = if is_merge {
+                ExclusionReason::MergeCommit {
+                    commit_id: change.commit_id.clone(),
+                }
+            } else {
+                ExclusionReason::TaintedAncestor {
+                    commit_id: change.commit_id.clone(),
+                }
+            };
+
             // Return the seen change IDs as newly tainted
             return Ok(TraversalResult {
                 segments: Vec::new(),
                 already_seen_change_id: None,
-                excluded_bookmark_count: 1,
+                exclusion: Some(reason),
                 newly_tainted_change_ids: seen_change_ids,
             });
         }
@@ -269,7 +443,7 @@ fn traverse_and_discover_segments(
     Ok(TraversalResult {
         segments,
         already_seen_change_id,
-        excluded_bookmark_count: 0,
+        exclusion: None,
         newly_tainted_change_ids: Vec::new(),
     })
 }
@@ -287,7 +461,10 @@ fn group_segments_into_stacks(
         let stack_change_ids = build_path_to_root(leaf_change_id, adjacency_list);
         let segments = build_segments(&stack_change_ids, bookmarks, change_id_to_segment);
 
-        stacks.push(BranchStack { segments });
+        stacks.push(BranchStack {
+            id: leaf_change_id.clone(),
+            segments,
+        });
     }
 
     stacks
@@ -331,6 +508,7 @@ fn build_segments(
                 .collect();
 
             segments.push(BookmarkSegment {
+                id: change_id.clone(),
                 bookmarks: bookmark_list,
                 changes: changes.clone(),
             });
@@ -361,6 +539,61 @@ mod tests {
         assert_eq!(path, vec!["a", "b", "c"]);
     }
 
+    fn entry(commit_id: &str, parents: &[&str]) -> LogEntry {
+        LogEntry {
+            commit_id: commit_id.to_string(),
+            change_id: format!("change-{commit_id}"),
+            author_name: String::new(),
+            author_email: String::new(),
+            description_first_line: String::new(),
+            description: String::new(),
+            parents: parents.iter().map(ToString::to_string).collect(),
+            local_bookmarks: Vec::new(),
+            remote_bookmarks: Vec::new(),
+            is_working_copy: false,
+            authored_at: chrono::Utc::now(),
+            committed_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_first_parent_line_skips_merged_side() {
+        // top -> merge(a, side) -> a -> trunk; side -> trunk
+        let index = ChangeIndex::new(vec![
+            entry("top", &["merge"]),
+            entry("merge", &["a", "side"]),
+            entry("side", &["trunk"]),
+            entry("a", &["trunk"]),
+        ]);
+        let line: Vec<String> = index
+            .first_parent_line("top")
+            .into_iter()
+            .map(|c| c.commit_id)
+            .collect();
+        assert_eq!(line, vec!["top", "merge", "a"]);
+    }
+
+    #[test]
+    fn test_ancestry_keeps_revset_order_and_skips_other_heads() {
+        // b2 -> b1 -> trunk; c -> a -> trunk, listed in revset order
+        let index = ChangeIndex::new(vec![
+            entry("c", &["a"]),
+            entry("b2", &["b1"]),
+            entry("b1", &["trunk"]),
+            entry("a", &["trunk"]),
+        ]);
+        let ancestry = |tip: &str| -> Vec<String> {
+            index
+                .ancestry(tip)
+                .into_iter()
+                .map(|c| c.commit_id)
+                .collect()
+        };
+        assert_eq!(ancestry("b2"), vec!["b2", "b1"]);
+        assert_eq!(ancestry("c"), vec!["c", "a"]);
+        assert!(ancestry("trunk").is_empty());
+    }
+
     #[test]
     fn test_build_segments_empty() {
         let bookmarks: HashMap<String, Bookmark> = HashMap::new();
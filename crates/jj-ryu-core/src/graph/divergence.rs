@@ -0,0 +1,50 @@
+//! Ahead/behind computation between two commits
+//!
+//! Tells whether moving a bookmark from one commit to another is a
+//! fast-forward, e.g. whether pushing a local bookmark needs a force push.
+
+use crate::error::Result;
+use crate::repo::JjWorkspace;
+use crate::types::Bookmark;
+
+/// How two commits have diverged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AheadBehind {
+    /// Commits reachable from the local commit but not the other one
+    pub ahead: usize,
+    /// Commits reachable from the other commit but not the local one
+    pub behind: usize,
+}
+
+impl AheadBehind {
+    /// Whether moving from the other commit to the local one drops nothing
+    pub const fn is_fast_forward(&self) -> bool {
+        self.behind == 0
+    }
+}
+
+/// Count commits `local` is ahead of and behind `other` (both commit IDs)
+pub fn ahead_behind(workspace: &JjWorkspace, local: &str, other: &str) -> Result<AheadBehind> {
+    Ok(AheadBehind {
+        ahead: workspace
+            .resolve_revset(&format!("{other}..{local}"))?
+            .len(),
+        behind: workspace
+            .resolve_revset(&format!("{local}..{other}"))?
+            .len(),
+    })
+}
+
+/// Divergence of a local bookmark from its counterpart on `remote`
+///
+/// Returns `None` if the bookmark doesn't exist on the remote.
+pub fn remote_ahead_behind(
+    workspace: &JjWorkspace,
+    bookmark: &Bookmark,
+    remote: &str,
+) -> Result<Option<AheadBehind>> {
+    let Some(remote_bookmark) = workspace.get_remote_bookmark(&bookmark.name, remote)? else {
+        return Ok(None);
+    };
+    ahead_behind(workspace, &bookmark.commit_id, &remote_bookmark.commit_id).map(Some)
+}
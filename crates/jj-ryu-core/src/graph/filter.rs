@@ -0,0 +1,79 @@
+//! Which local bookmarks stacks are built from
+//!
+//! In large shared repos most local bookmarks belong to someone else.
+//! `mine-only` keeps bookmarks on changes you authored (jj's `mine()`), and
+//! `include-bookmarks` / `exclude-bookmarks` take jj-style globs on bookmark
+//! names. Command-line flags reach it as the run's config overlay (see
+//! [`RunContext::with_config`]), replacing the config files' values.
+//!
+//! [`RunContext::with_config`]: crate::run::RunContext::with_config
+
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::repo::JjWorkspace;
+use crate::types::Bookmark;
+use jj_lib::str_util::{StringMatcher, StringPattern};
+use std::collections::HashSet;
+
+/// Bookmark filter from the config files, or flags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookmarkFilter {
+    /// Only bookmarks whose change you authored
+    pub mine_only: bool,
+    /// Name globs a bookmark must match one of (empty: any)
+    pub include: Vec<String>,
+    /// Name globs that drop a bookmark
+    pub exclude: Vec<String>,
+}
+
+impl BookmarkFilter {
+    /// Filter from `config`
+    pub fn from_config(config: &RyuConfig) -> Self {
+        Self {
+            mine_only: config.mine_only.unwrap_or(false),
+            include: config.include_bookmarks.clone().unwrap_or_default(),
+            exclude: config.exclude_bookmarks.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Keep the bookmarks the filter (and `branch-prefix`) lets through
+    pub fn apply(
+        &self,
+        workspace: &JjWorkspace,
+        bookmarks: Vec<Bookmark>,
+    ) -> Result<Vec<Bookmark>> {
+        let include = matchers(&self.include)?;
+        let exclude = matchers(&self.exclude)?;
+        let mine: Option<HashSet<String>> = if self.mine_only {
+            Some(
+                workspace
+                    .resolve_revset("mine() & bookmarks()")?
+                    .into_iter()
+                    .map(|entry| entry.commit_id)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        Ok(bookmarks
+            .into_iter()
+            .filter(|b| workspace.config().matches_prefix(&b.name))
+            .filter(|b| include.is_empty() || include.iter().any(|m| m.is_match(&b.name)))
+            .filter(|b| !exclude.iter().any(|m| m.is_match(&b.name)))
+            .filter(|b| mine.as_ref().is_none_or(|mine| mine.contains(&b.commit_id)))
+            .collect())
+    }
+}
+
+/// Compile name globs, as in jj's `glob:` patterns
+fn matchers(globs: &[String]) -> Result<Vec<StringMatcher>> {
+    globs
+        .iter()
+        .map(|glob| {
+            StringPattern::glob(glob)
+                .map(|pattern| pattern.to_matcher())
+                .map_err(|e| Error::Config(format!("invalid bookmark glob '{glob}': {e}")))
+        })
+        .collect()
+}
@@ -0,0 +1,14 @@
+//! Change graph building
+//!
+//! Analyzes jj bookmarks to build a graph of stacked changes.
+
+mod builder;
+mod divergence;
+mod filter;
+
+pub use builder::{
+    build_change_graph, build_change_graph_onto, closest_segment_bookmark, resolve_base_branch,
+    working_copy_bookmark,
+};
+pub use divergence::{AheadBehind, ahead_behind, remote_ahead_behind};
+pub use filter::BookmarkFilter;
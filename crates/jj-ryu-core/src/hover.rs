@@ -0,0 +1,102 @@
+//! Which PR a line of code is under review in
+//!
+//! `ryu hover <file>:<line>` blames the line in the working copy and looks
+//! for the stack segment whose changes last touched it, so an editor can
+//! show "under review in #123" next to the code. Lines last changed on trunk
+//! or outside any stack have no context.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::types::{BookmarkSegment, BranchStack, ChangeGraph, LogEntry, PullRequest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The stack segment and PR a line was last changed in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LineContext {
+    /// File, relative to the workspace root
+    pub path: String,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Commit that last changed the line
+    pub commit_id: String,
+    /// Its change ID
+    pub change_id: String,
+    /// First line of its description
+    pub description: String,
+    /// Bookmark of the segment the change belongs to
+    pub bookmark: String,
+    /// Leaf bookmark of the stack
+    pub stack: String,
+    /// The bookmark's open PR, if it has been submitted
+    pub pr: Option<PullRequest>,
+}
+
+/// Parse a `<file>:<line>` argument
+pub fn parse_location(location: &str) -> Result<(&str, usize)> {
+    location
+        .rsplit_once(':')
+        .and_then(|(file, line)| Some((file, line.parse().ok()?)))
+        .filter(|(file, line)| !file.is_empty() && *line > 0)
+        .ok_or_else(|| Error::InvalidArgument(format!("expected <file>:<line>, got '{location}'")))
+}
+
+/// The stack, segment and change holding `commit_id`, if any
+pub fn find_change<'a>(
+    graph: &'a ChangeGraph,
+    commit_id: &str,
+) -> Option<(&'a BranchStack, &'a BookmarkSegment, &'a LogEntry)> {
+    graph.stacks.iter().find_map(|stack| {
+        stack.segments.iter().find_map(|segment| {
+            segment
+                .changes
+                .iter()
+                .find(|change| change.commit_id == commit_id)
+                .map(|change| (stack, segment, change))
+        })
+    })
+}
+
+/// Context for line `line` of `path` (relative to the workspace root)
+///
+/// `None` if the line was last changed outside every stack. The PR is
+/// looked up for the segment's first bookmark.
+pub async fn line_context(
+    workspace: &JjWorkspace,
+    graph: &ChangeGraph,
+    platform: &dyn PlatformService,
+    path: &str,
+    line: usize,
+) -> Result<Option<LineContext>> {
+    let Some(commit_id) = workspace.line_origin(path, line)? else {
+        return Err(Error::InvalidArgument(format!("{path} has no line {line}")));
+    };
+    let Some((stack, segment, change)) = find_change(graph, &commit_id) else {
+        return Ok(None);
+    };
+    let Some(bookmark) = segment.bookmarks.first() else {
+        return Ok(None);
+    };
+    let leaf = stack
+        .segments
+        .last()
+        .and_then(|segment| segment.bookmarks.first())
+        .map_or_else(|| bookmark.name.clone(), |b| b.name.clone());
+    let pr = if bookmark.has_remote {
+        platform.find_existing_pr(&bookmark.name).await?
+    } else {
+        None
+    };
+
+    Ok(Some(LineContext {
+        path: path.to_string(),
+        line,
+        commit_id,
+        change_id: change.change_id.clone(),
+        description: change.description_first_line.clone(),
+        bookmark: bookmark.name.clone(),
+        stack: leaf,
+        pr,
+    }))
+}
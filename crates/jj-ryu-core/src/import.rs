@@ -0,0 +1,136 @@
+//! Reconstructing someone else's stack from its stack comment
+//!
+//! Every PR of a submitted stack carries a stack comment whose embedded
+//! [`StackCommentData`] lists the stack's bookmarks and PRs, root first.
+//! `ryu import` reads it from any one of the PRs, fetches just those
+//! branches and tracks them, so the stack shows up locally as bookmarked
+//! changes ready to review or build on.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::submit::{StackCommentData, find_stack_data};
+use crate::types::PlatformConfig;
+
+/// Path segments that precede the PR number in each platform's PR URLs
+const PR_PATH_SEGMENTS: &[&str] = &[
+    "pull",
+    "pulls",
+    "merge_requests",
+    "pull-requests",
+    "pullrequest",
+];
+
+/// What importing did with one bookmark of the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStatus {
+    /// Created locally, tracking the remote branch
+    Created,
+    /// Already there at the remote's commit
+    Unchanged,
+    /// A local bookmark of that name points elsewhere and was left alone
+    Conflicting,
+    /// The branch is gone from the remote, e.g. its PR was merged
+    Missing,
+}
+
+/// One bookmark of an imported stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedBookmark {
+    /// The bookmark
+    pub bookmark: String,
+    /// Its PR
+    pub pr_number: u64,
+    /// What was done with it
+    pub status: ImportStatus,
+}
+
+/// PR number from a PR URL of the repository `config` describes, or from a
+/// plain number such as `42` or `#42`
+pub fn parse_pr_reference(reference: &str, config: &PlatformConfig) -> Result<u64> {
+    let invalid = || Error::InvalidArgument(format!("not a PR URL or number: {reference}"));
+    let reference = reference.trim();
+    if let Ok(number) = reference.trim_start_matches('#').parse() {
+        return Ok(number);
+    }
+
+    let strip_scheme = |url: &str| {
+        url.split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .to_lowercase()
+    };
+    let url = strip_scheme(reference);
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let repo_url = strip_scheme(&config.web_url());
+    let Some(path) = url.strip_prefix(&format!("{repo_url}/")) else {
+        return Err(Error::InvalidArgument(format!(
+            "{reference} isn't a PR of {}",
+            config.web_url()
+        )));
+    };
+
+    let mut segments = path.split('/').skip_while(|segment| *segment == "-");
+    match (segments.next(), segments.next()) {
+        (Some(kind), Some(number)) if PR_PATH_SEGMENTS.contains(&kind) => {
+            number.parse().map_err(|_| invalid())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// The stack data of the stack comment on PR `pr_number`, or of the stack
+/// section of its body
+pub async fn find_stack_comment(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+) -> Result<StackCommentData> {
+    find_stack_data(platform, pr_number)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::InvalidArgument(format!("PR #{pr_number} has no ryu stack comment")))
+}
+
+/// Fetch the stack's branches from `remote` and track each as a local
+/// bookmark, root first
+///
+/// Existing local bookmarks are never moved: one already at the remote's
+/// commit is reported as unchanged, any other as conflicting.
+pub fn import_stack(
+    workspace: &mut JjWorkspace,
+    data: &StackCommentData,
+    remote: &str,
+) -> Result<Vec<ImportedBookmark>> {
+    let branches: Vec<String> = data
+        .stack
+        .iter()
+        .map(|item| item.bookmark_name.clone())
+        .collect();
+    workspace.git_fetch_branches(remote, &branches)?;
+
+    let mut imported = Vec::with_capacity(data.stack.len());
+    for item in &data.stack {
+        let bookmark = &item.bookmark_name;
+        let status = match workspace.get_remote_bookmark(bookmark, remote)? {
+            None => ImportStatus::Missing,
+            Some(on_remote) => match workspace.get_local_bookmark(bookmark)? {
+                Some(local) if local.commit_id == on_remote.commit_id => ImportStatus::Unchanged,
+                Some(_) => ImportStatus::Conflicting,
+                None => {
+                    workspace.track_bookmark(bookmark, remote)?;
+                    // Tracking doesn't recreate a local bookmark that was deleted
+                    if workspace.get_local_bookmark(bookmark)?.is_none() {
+                        workspace.set_bookmark(bookmark, &on_remote.commit_id)?;
+                    }
+                    ImportStatus::Created
+                }
+            },
+        };
+        imported.push(ImportedBookmark {
+            bookmark: bookmark.clone(),
+            pr_number: item.pr_number,
+            status,
+        });
+    }
+    Ok(imported)
+}
@@ -0,0 +1,90 @@
+//! Where merged PRs landed
+//!
+//! Squash and rebase merges put new commits on trunk, so the local changes a
+//! PR was made from stay outside `trunk()` and nothing in the repository links
+//! the two. Sync asks the platform for each merged PR's merge commit and
+//! records it in the state store against the change IDs of the bookmark's
+//! segment. Analyze then shows where a segment landed, and clean knows
+//! exactly which local changes are redundant.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::state::RyuState;
+use crate::types::{ChangeGraph, PrState};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// A local change whose PR was merged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LandedChange {
+    /// Bookmark the PR was opened from
+    pub bookmark: String,
+    /// The merged PR
+    pub pr_number: u64,
+    /// Commit the PR landed as on its base
+    pub commit: String,
+}
+
+/// Record where the merged PRs of the graph's bookmarks landed
+///
+/// Only pushed bookmarks can have a PR, and segments already recorded aren't
+/// looked up again. Entries for changes no longer in the graph (abandoned,
+/// or their bookmark deleted) are dropped. Returns whether `state` changed.
+pub async fn refresh_landed(
+    graph: &ChangeGraph,
+    platform: &dyn PlatformService,
+    state: &mut RyuState,
+) -> Result<bool> {
+    let in_graph: HashSet<&str> = graph
+        .stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.changes)
+        .map(|change| change.change_id.as_str())
+        .collect();
+    let before = state.landed.len();
+    state
+        .landed
+        .retain(|change_id, _| in_graph.contains(change_id.as_str()));
+    let mut changed = state.landed.len() != before;
+
+    for segment in graph.stacks.iter().flat_map(|stack| &stack.segments) {
+        if state.landed.contains_key(&segment.id) {
+            continue;
+        }
+        for bookmark in segment.bookmarks.iter().filter(|b| b.has_remote) {
+            let Some((pr, PrState::Merged)) = platform.find_latest_pr(&bookmark.name).await? else {
+                continue;
+            };
+            let Some(commit) = platform.get_merge_commit(pr.number).await? else {
+                continue;
+            };
+            for change in &segment.changes {
+                state.landed.insert(
+                    change.change_id.clone(),
+                    LandedChange {
+                        bookmark: bookmark.name.clone(),
+                        pr_number: pr.number,
+                        commit: commit.clone(),
+                    },
+                );
+            }
+            changed = true;
+            break;
+        }
+    }
+    Ok(changed)
+}
+
+/// Recorded changes that landed through one of `bookmarks`' PRs, by change ID
+pub fn landed_through<'a>(
+    state: &'a RyuState,
+    bookmarks: &[String],
+) -> BTreeMap<&'a str, &'a LandedChange> {
+    state
+        .landed
+        .iter()
+        .filter(|(_, landed)| bookmarks.contains(&landed.bookmark))
+        .map(|(change_id, landed)| (change_id.as_str(), landed))
+        .collect()
+}
@@ -0,0 +1,58 @@
+//! jj-ryu-core - Stacked PRs for Jujutsu
+//!
+//! This library provides the core functionality for managing stacked pull requests
+//! when using Jujutsu (jj) as your version control system. It supports GitHub,
+//! GitLab, Bitbucket Cloud, Gitea/Forgejo and Azure DevOps.
+//!
+//! # Architecture
+//!
+//! The library is designed to be interface-agnostic, allowing it to be used from:
+//! - CLI applications
+//! - TUI applications
+//! - Web servers / REST APIs
+//! - WebSocket servers
+//!
+//! All I/O is async and state is passed explicitly (no globals).
+
+pub mod auth;
+pub mod bisect;
+pub mod budget;
+pub mod codeowners;
+pub mod config;
+pub mod crash;
+pub mod error;
+pub mod gc;
+pub mod graph;
+pub mod hover;
+pub mod import;
+pub mod landed;
+pub mod lock;
+pub mod merge;
+pub mod metrics;
+pub mod next_steps;
+pub mod notify;
+pub mod platform;
+pub mod prune;
+pub mod pull;
+pub mod read_only;
+pub mod redact;
+pub mod repo;
+pub mod restack;
+pub mod reviewers;
+pub mod run;
+pub mod schema;
+pub mod state;
+pub mod status;
+pub mod submit;
+pub mod suggest;
+pub mod telemetry;
+pub mod template;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod types;
+pub mod undo;
+pub mod update;
+pub mod verify;
+
+pub use error::{Error, Result};
+pub use types::*;
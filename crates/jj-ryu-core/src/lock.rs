@@ -0,0 +1,233 @@
+//! Stack-level locks
+//!
+//! A long-running operation on a stack (landing it PR by PR) retargets bases
+//! as it goes; a concurrent `sync` or `submit` would undo that work. Such an
+//! operation records a [`StackLock`] in the state store, and optionally as a
+//! marker comment on the stack's bottom PR so other machines and webhook
+//! workers see it too. Locks older than [`STACK_LOCK_TTL_MINUTES`] are stale:
+//! the holder presumably crashed, so they are ignored and cleaned up.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::state::RyuState;
+use crate::submit::SubmissionPlan;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Minutes after which a lock is considered stale
+pub const STACK_LOCK_TTL_MINUTES: i64 = 60;
+
+/// Prefix for lock data in PR comments
+pub const LOCK_COMMENT_PREFIX: &str = "<!--- JJ-RYU_LOCK: ";
+/// Postfix for lock data in PR comments
+const LOCK_COMMENT_POSTFIX: &str = " --->";
+
+/// A lock on a stack held by a long-running operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackLock {
+    /// Operation holding the lock (e.g. "merge")
+    pub operation: String,
+    /// Bookmarks in the locked stack, trunk first
+    pub bookmarks: Vec<String>,
+    /// Who holds the lock (host and process)
+    pub holder: String,
+    /// When the lock was taken
+    pub acquired_at: DateTime<Utc>,
+}
+
+impl StackLock {
+    /// A lock held by this process, taken now
+    pub fn new(operation: &str, bookmarks: Vec<String>) -> Self {
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_else(|_| "unknown host".to_string());
+        Self {
+            operation: operation.to_string(),
+            bookmarks,
+            holder: format!("{host} (pid {})", std::process::id()),
+            acquired_at: Utc::now(),
+        }
+    }
+
+    /// Whether the lock has outlived [`STACK_LOCK_TTL_MINUTES`]
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now - self.acquired_at > chrono::Duration::minutes(STACK_LOCK_TTL_MINUTES)
+    }
+
+    /// Whether the lock covers any of `bookmarks`
+    pub fn covers<S: AsRef<str>>(&self, bookmarks: &[S]) -> bool {
+        bookmarks
+            .iter()
+            .any(|b| self.bookmarks.iter().any(|l| l == b.as_ref()))
+    }
+
+    /// Error reported to operations blocked by this lock
+    pub fn to_error(&self) -> Error {
+        Error::StackLocked {
+            operation: self.operation.clone(),
+            holder: self.holder.clone(),
+            since: self.acquired_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Live lock in `state` covering any of `bookmarks`
+pub fn find_stack_lock<'a, S: AsRef<str>>(
+    state: &'a RyuState,
+    bookmarks: &[S],
+    now: DateTime<Utc>,
+) -> Option<&'a StackLock> {
+    state
+        .stack_locks
+        .values()
+        .find(|lock| !lock.is_stale(now) && lock.covers(bookmarks))
+}
+
+/// Lock held in the state store, released when dropped
+#[derive(Debug)]
+pub struct StackLockGuard {
+    workspace_root: PathBuf,
+    key: String,
+    released: bool,
+}
+
+impl StackLockGuard {
+    /// Release the lock, reporting failure to update the state store
+    pub fn release(mut self) -> Result<()> {
+        self.released = true;
+        remove_lock(&self.workspace_root, &self.key)
+    }
+}
+
+impl Drop for StackLockGuard {
+    fn drop(&mut self) {
+        if !self.released {
+            if let Err(e) = remove_lock(&self.workspace_root, &self.key) {
+                warn!(key = %self.key, error = %e, "failed to release stack lock");
+            }
+        }
+    }
+}
+
+fn remove_lock(workspace_root: &Path, key: &str) -> Result<()> {
+    let mut state = RyuState::load(workspace_root)?;
+    if state.stack_locks.remove(key).is_some() {
+        state.save(workspace_root)?;
+    }
+    Ok(())
+}
+
+/// Record `lock` in the state store under `key` (usually the stack's leaf)
+///
+/// Fails with [`Error::StackLocked`] if a live lock already covers any of its
+/// bookmarks. Stale locks are dropped.
+pub fn acquire_stack_lock(
+    workspace_root: &Path,
+    key: &str,
+    lock: StackLock,
+) -> Result<StackLockGuard> {
+    let mut state = RyuState::load(workspace_root)?;
+    let now = Utc::now();
+    if let Some(existing) = find_stack_lock(&state, &lock.bookmarks, now) {
+        return Err(existing.to_error());
+    }
+
+    state.stack_locks.retain(|_, l| !l.is_stale(now));
+    state.stack_locks.insert(key.to_string(), lock);
+    state.save(workspace_root)?;
+
+    Ok(StackLockGuard {
+        workspace_root: workspace_root.to_path_buf(),
+        key: key.to_string(),
+        released: false,
+    })
+}
+
+/// Format the marker comment announcing `lock` on a PR
+pub fn format_lock_comment(lock: &StackLock) -> Result<String> {
+    let encoded = BASE64.encode(serde_json::to_string(lock)?);
+    Ok(format!(
+        "{LOCK_COMMENT_PREFIX}{encoded}{LOCK_COMMENT_POSTFIX}\n\
+         🔒 This stack is locked by `ryu {}` ({}) since {}. \
+         `ryu sync` and `ryu submit` will leave it alone until it finishes.",
+        lock.operation,
+        lock.holder,
+        lock.acquired_at.format("%Y-%m-%d %H:%M UTC")
+    ))
+}
+
+/// Decode the lock embedded in a marker comment
+pub fn parse_lock_comment(body: &str) -> Option<StackLock> {
+    let (_, rest) = body.split_once(LOCK_COMMENT_PREFIX)?;
+    let (encoded, _) = rest.split_once(LOCK_COMMENT_POSTFIX)?;
+    let json = BASE64.decode(encoded.trim()).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Find the marker comment on a PR, returning its ID and lock
+pub async fn find_lock_comment(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+) -> Result<Option<(u64, StackLock)>> {
+    Ok(platform
+        .list_pr_comments(pr_number)
+        .await?
+        .into_iter()
+        .find_map(|c| parse_lock_comment(&c.body).map(|lock| (c.id, lock))))
+}
+
+/// Announce `lock` on a PR, reusing an earlier marker comment if present
+pub async fn post_lock_comment(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    lock: &StackLock,
+) -> Result<()> {
+    let body = format_lock_comment(lock)?;
+    match find_lock_comment(platform, pr_number).await? {
+        Some((id, _)) => platform.update_pr_comment(pr_number, id, &body).await,
+        None => platform.create_pr_comment(pr_number, &body).await,
+    }
+}
+
+/// Mark the lock comment on a PR as released
+///
+/// Platforms don't let us delete comments uniformly, so the marker is
+/// replaced with a note instead.
+pub async fn release_lock_comment(platform: &dyn PlatformService, pr_number: u64) -> Result<()> {
+    if let Some((id, lock)) = find_lock_comment(platform, pr_number).await? {
+        let body = format!(
+            "🔓 Stack lock released (`ryu {}` finished).",
+            lock.operation
+        );
+        platform.update_pr_comment(pr_number, id, &body).await?;
+    }
+    Ok(())
+}
+
+/// Live lock blocking `plan`, from the state store or the bottom PR's comments
+pub async fn check_stack_lock(
+    state: &RyuState,
+    platform: &dyn PlatformService,
+    plan: &SubmissionPlan,
+) -> Result<Option<StackLock>> {
+    let bookmarks: Vec<&str> = plan
+        .segments
+        .iter()
+        .map(|s| s.bookmark.name.as_str())
+        .collect();
+    let now = Utc::now();
+    if let Some(lock) = find_stack_lock(state, &bookmarks, now) {
+        return Ok(Some(lock.clone()));
+    }
+
+    let Some(bottom_pr) = bookmarks.first().and_then(|b| plan.existing_prs.get(*b)) else {
+        return Ok(None);
+    };
+    Ok(find_lock_comment(platform, bottom_pr.number)
+        .await?
+        .map(|(_, lock)| lock)
+        .filter(|lock| !lock.is_stale(now)))
+}
@@ -0,0 +1,318 @@
+//! Landing the bottom PR of a stack
+//!
+//! Merging is asynchronous on some platforms (Bitbucket accepts large merges
+//! with 202, GitLab may still be running its merge train), so after asking
+//! for a merge ryu polls the PR's state until it reads merged.
+//!
+//! [`stack_roots`] finds the bottom PR of every stack with its review and CI
+//! state, for `ryu land --all-ready` to merge whichever are ready.
+//! [`land_stack`] merges one stack's PRs bottom-up for `ryu land --stack`.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::restack::Restack;
+use crate::submit::{Phase, ProgressCallback, select_bookmark_for_segment};
+use crate::types::{ChangeGraph, ChecksState, MergeMethod, PrReadiness, PrState, PullRequest};
+use std::time::Duration;
+use tracing::debug;
+
+/// How often the PR state is polled while waiting for a merge
+pub const MERGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to wait for a merge before giving up
+pub const MERGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How often CI state is polled while waiting for checks
+pub const CHECKS_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long to wait for a PR's checks before giving up
+pub const CHECKS_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Merge `pr` and wait until the platform reports it merged
+///
+/// Fails if the PR is closed instead, or is still open after `timeout`.
+pub async fn merge_and_wait(
+    platform: &dyn PlatformService,
+    pr: &PullRequest,
+    method: MergeMethod,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<()> {
+    platform.merge_pr(pr.number, method).await?;
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        match platform.find_latest_pr(&pr.head_ref).await? {
+            Some((latest, PrState::Merged)) if latest.number == pr.number => return Ok(()),
+            Some((latest, PrState::Closed)) if latest.number == pr.number => {
+                return Err(Error::Platform(format!(
+                    "PR #{} was closed without merging",
+                    pr.number
+                )));
+            }
+            state => debug!(pr_number = pr.number, ?state, "waiting for merge"),
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(Error::Platform(format!(
+                "PR #{} was not merged within {}s - check it on the platform",
+                pr.number,
+                timeout.as_secs()
+            )));
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// The bottom PR of one or more stacks
+#[derive(Debug, Clone)]
+pub struct StackRoot {
+    /// Bookmark at the bottom of the stack
+    pub bookmark: String,
+    /// Its open PR, targeting trunk
+    pub pr: PullRequest,
+    /// Review and CI state of the PR
+    pub readiness: PrReadiness,
+    /// Bookmarks above it in every stack sharing it, bottom first
+    pub rest: Vec<String>,
+    /// Segments directly above it, to rebase onto trunk once it lands so
+    /// they drop the merged commits
+    pub restacks: Vec<Restack>,
+}
+
+/// Bottom PRs of the stacks in `graph`, with their review and CI state
+///
+/// Stacks forking above a shared bottom share an entry. Bottoms without an
+/// open PR, drafts and PRs not yet targeting `default_branch` are left out,
+/// since none of them can land as they are.
+pub async fn stack_roots(
+    graph: &ChangeGraph,
+    platform: &dyn PlatformService,
+    default_branch: &str,
+) -> Result<Vec<StackRoot>> {
+    let mut roots: Vec<StackRoot> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    for stack in &graph.stacks {
+        let Some(bottom) = stack.segments.first() else {
+            continue;
+        };
+        let bookmark = select_bookmark_for_segment(bottom, None).name;
+        if skipped.contains(&bookmark) {
+            continue;
+        }
+
+        let index = if let Some(index) = roots.iter().position(|root| root.bookmark == bookmark) {
+            index
+        } else {
+            let pr = platform
+                .find_existing_pr(&bookmark)
+                .await?
+                .filter(|pr| !pr.is_draft && pr.base_ref == default_branch);
+            let Some(pr) = pr else {
+                debug!(bookmark, "stack root can't land");
+                skipped.push(bookmark);
+                continue;
+            };
+            let readiness = platform.get_pr_readiness(pr.number).await?;
+            roots.push(StackRoot {
+                bookmark,
+                pr,
+                readiness,
+                rest: Vec::new(),
+                restacks: Vec::new(),
+            });
+            roots.len() - 1
+        };
+
+        let root = &mut roots[index];
+        let Some(next_root) = stack
+            .segments
+            .get(1)
+            .and_then(|segment| segment.changes.last())
+        else {
+            continue;
+        };
+        let above: Vec<String> = stack.segments[1..]
+            .iter()
+            .map(|segment| select_bookmark_for_segment(segment, None).name)
+            .collect();
+        for name in &above {
+            if !root.rest.contains(name) {
+                root.rest.push(name.clone());
+            }
+        }
+        match root
+            .restacks
+            .iter_mut()
+            .find(|restack| restack.root_commit_id == next_root.commit_id)
+        {
+            Some(restack) => {
+                for name in above {
+                    if !restack.bookmarks.contains(&name) {
+                        restack.bookmarks.push(name);
+                    }
+                }
+            }
+            None => root.restacks.push(Restack {
+                root_commit_id: next_root.commit_id.clone(),
+                bookmarks: above,
+            }),
+        }
+    }
+    Ok(roots)
+}
+
+/// Wait until the checks on `pr` finish
+///
+/// Succeeds once they pass (or if there are none) and fails as soon as one
+/// fails, or if they are still running after `timeout`.
+pub async fn wait_for_checks(
+    platform: &dyn PlatformService,
+    pr: &PullRequest,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        match platform.get_check_status(pr.number).await? {
+            ChecksState::Passing | ChecksState::None => return Ok(()),
+            ChecksState::Failing => {
+                return Err(Error::Platform(format!(
+                    "checks failed on PR #{}",
+                    pr.number
+                )));
+            }
+            ChecksState::Pending => debug!(pr_number = pr.number, "waiting for checks"),
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(Error::Platform(format!(
+                "checks on PR #{} were still running after {}s",
+                pr.number,
+                timeout.as_secs()
+            )));
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// How [`land_stack`] merges
+#[derive(Debug, Clone, Copy)]
+pub struct LandOptions {
+    /// How each PR is merged
+    pub method: MergeMethod,
+    /// Wait for each PR's checks to pass before merging it; otherwise a PR
+    /// whose checks are failing or still running stops the landing
+    pub wait_for_checks: bool,
+    /// How often merge and CI state are polled
+    pub poll_interval: Duration,
+    /// How long to wait for one merge
+    pub merge_timeout: Duration,
+    /// How long to wait for one PR's checks
+    pub checks_timeout: Duration,
+}
+
+impl LandOptions {
+    /// Default timings for `method`
+    pub const fn new(method: MergeMethod, wait_for_checks: bool) -> Self {
+        Self {
+            method,
+            wait_for_checks,
+            poll_interval: MERGE_POLL_INTERVAL,
+            merge_timeout: MERGE_TIMEOUT,
+            checks_timeout: CHECKS_TIMEOUT,
+        }
+    }
+}
+
+/// What [`land_stack`] got through
+#[derive(Debug)]
+pub struct StackLanding {
+    /// Bookmarks whose PRs were merged, bottom first
+    pub merged: Vec<String>,
+    /// The bookmark that stopped the landing, and why
+    pub stopped: Option<(String, Error)>,
+}
+
+/// Merge the PRs of a stack bottom-up
+///
+/// `prs` pairs each segment's bookmark with its open PR, bottom first. Each
+/// PR is retargeted onto `default_branch` once the one below it has landed,
+/// its checks are checked (or waited for), and it is merged and waited on
+/// before moving up. The first failure stops the landing; PRs merged until
+/// then stay merged.
+///
+/// The PRs above aren't rebased between merges, so the platform merges each
+/// one as it stands. That works for merge and squash merges; a rebase merge
+/// would replay the commits that already landed, so it is refused.
+pub async fn land_stack(
+    platform: &dyn PlatformService,
+    prs: &[(String, PullRequest)],
+    default_branch: &str,
+    options: LandOptions,
+    progress: &dyn ProgressCallback,
+) -> Result<StackLanding> {
+    if options.method == MergeMethod::Rebase && prs.len() > 1 {
+        return Err(Error::InvalidArgument(
+            "a stack can't be landed with rebase merges; use merge or squash, \
+             or land one PR at a time with `ryu merge`"
+                .to_string(),
+        ));
+    }
+    progress.on_phase(Phase::Merging).await;
+
+    let mut landing = StackLanding {
+        merged: Vec::new(),
+        stopped: None,
+    };
+    for (bookmark, pr) in prs {
+        if let Err(e) = land_one(platform, bookmark, pr, default_branch, options, progress).await {
+            landing.stopped = Some((bookmark.clone(), e));
+            break;
+        }
+        landing.merged.push(bookmark.clone());
+    }
+    Ok(landing)
+}
+
+async fn land_one(
+    platform: &dyn PlatformService,
+    bookmark: &str,
+    pr: &PullRequest,
+    default_branch: &str,
+    options: LandOptions,
+    progress: &dyn ProgressCallback,
+) -> Result<()> {
+    if pr.base_ref != default_branch {
+        let updated = platform.update_pr_base(pr.number, default_branch).await?;
+        progress.on_pr_updated(bookmark, &updated).await;
+    }
+
+    if options.wait_for_checks {
+        progress
+            .on_message(&format!("Waiting for checks on #{}...", pr.number))
+            .await;
+        wait_for_checks(platform, pr, options.poll_interval, options.checks_timeout).await?;
+    } else {
+        match platform.get_check_status(pr.number).await? {
+            ChecksState::Passing | ChecksState::None => {}
+            checks => {
+                return Err(Error::Platform(format!(
+                    "PR #{} has {checks}; pass --wait-for-checks to wait for them",
+                    pr.number
+                )));
+            }
+        }
+    }
+
+    merge_and_wait(
+        platform,
+        pr,
+        options.method,
+        options.poll_interval,
+        options.merge_timeout,
+    )
+    .await?;
+    progress.on_pr_merged(bookmark, pr).await;
+    Ok(())
+}
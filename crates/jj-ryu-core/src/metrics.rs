@@ -0,0 +1,155 @@
+//! Per-run counters for verbose summaries
+//!
+//! Off until [`MetricsRecorder::enable`] is called. From then on platform
+//! calls (by operation), response bytes, cache hits and git operations are
+//! tallied so the CLI can say where a slow command spent its time. The
+//! counting happens deep inside platform clients and the workspace, which
+//! reach the recorder through the [`RunContext`] they were given.
+//!
+//! Response bytes are counted for the clients ryu drives over plain HTTP
+//! (GitLab, Bitbucket, Gitea, Azure DevOps); octocrab doesn't expose
+//! GitHub's raw bodies.
+//!
+//! [`RunContext`]: crate::run::RunContext
+
+use crate::error::Result;
+use crate::run::RunContext;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Number of calls and the time they took
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallStats {
+    /// Number of calls
+    pub calls: u64,
+    /// Total time spent in them
+    pub time: Duration,
+}
+
+impl CallStats {
+    fn add(&mut self, elapsed: Duration) {
+        self.calls += 1;
+        self.time += elapsed;
+    }
+}
+
+/// Everything counted since [`MetricsRecorder::enable`]
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    /// When counting started
+    pub started: Instant,
+    /// Platform calls by operation, e.g. `find_open_prs`
+    pub api_calls: BTreeMap<&'static str, CallStats>,
+    /// Response body bytes received from the platform
+    pub response_bytes: u64,
+    /// Platform answers reused instead of asked for again
+    pub cache_hits: u64,
+    /// Git operations by kind, e.g. `push`
+    pub git_ops: BTreeMap<&'static str, CallStats>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            api_calls: BTreeMap::new(),
+            response_bytes: 0,
+            cache_hits: 0,
+            git_ops: BTreeMap::new(),
+        }
+    }
+
+    /// All platform calls together
+    pub fn api_total(&self) -> CallStats {
+        total(&self.api_calls)
+    }
+
+    /// All git operations together
+    pub fn git_total(&self) -> CallStats {
+        total(&self.git_ops)
+    }
+}
+
+fn total(stats: &BTreeMap<&'static str, CallStats>) -> CallStats {
+    stats
+        .values()
+        .fold(CallStats::default(), |acc, s| CallStats {
+            calls: acc.calls + s.calls,
+            time: acc.time + s.time,
+        })
+}
+
+/// Counters of one run, off until enabled
+#[derive(Debug, Default)]
+pub struct MetricsRecorder {
+    metrics: Mutex<Option<Metrics>>,
+}
+
+impl MetricsRecorder {
+    fn metrics(&self) -> MutexGuard<'_, Option<Metrics>> {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start counting (again, from zero)
+    pub fn enable(&self) {
+        *self.metrics() = Some(Metrics::new());
+    }
+
+    /// What was counted so far, or `None` if counting is off
+    pub fn snapshot(&self) -> Option<Metrics> {
+        self.metrics().clone()
+    }
+
+    /// Count a platform call to `operation`
+    pub fn record_api_call(&self, operation: &'static str, elapsed: Duration) {
+        if let Some(m) = self.metrics().as_mut() {
+            m.api_calls.entry(operation).or_default().add(elapsed);
+        }
+    }
+
+    /// Count a response body of `bytes` bytes
+    pub fn record_response_bytes(&self, bytes: usize) {
+        if let Some(m) = self.metrics().as_mut() {
+            m.response_bytes += bytes as u64;
+        }
+    }
+
+    /// Count a platform answer that was reused
+    pub fn record_cache_hit(&self) {
+        if let Some(m) = self.metrics().as_mut() {
+            m.cache_hits += 1;
+        }
+    }
+
+    /// Count a git operation of `kind`
+    pub fn record_git_op(&self, kind: &'static str, elapsed: Duration) {
+        if let Some(m) = self.metrics().as_mut() {
+            m.git_ops.entry(kind).or_default().add(elapsed);
+        }
+    }
+}
+
+/// `Response::json`, counting the body's size
+#[cfg_attr(
+    not(any(
+        feature = "gitlab",
+        feature = "bitbucket",
+        feature = "gitea",
+        feature = "azure-devops"
+    )),
+    allow(dead_code)
+)]
+pub(crate) trait MeteredJson {
+    /// Read the body as JSON, counting it in `run`'s metrics
+    async fn metered_json<T: DeserializeOwned>(self, run: &RunContext) -> Result<T>;
+}
+
+impl MeteredJson for reqwest::Response {
+    async fn metered_json<T: DeserializeOwned>(self, run: &RunContext) -> Result<T> {
+        let body = self.bytes().await?;
+        run.metrics().record_response_bytes(body.len());
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
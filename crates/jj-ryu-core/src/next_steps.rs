@@ -0,0 +1,166 @@
+//! Suggestions for what to run after submit and sync
+//!
+//! Looks at the PRs a run left behind, with their review and CI state, and
+//! at the bookmarks the change graph excluded, and points at the command
+//! that moves the stacks along: merging a root that can land, chasing
+//! reviews, publishing drafts or fixing excluded bookmarks.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::{SubmissionPlan, SubmissionResult};
+use crate::types::{ChecksState, PrReadiness, PullRequest};
+use std::collections::{HashMap, HashSet};
+
+/// A command worth running next, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// What calls for it, e.g. "2 PRs await review"
+    pub reason: String,
+    /// Command to run
+    pub command: String,
+}
+
+/// A PR of a stack with its review and CI state
+#[derive(Debug, Clone)]
+pub struct StackPr {
+    /// Bookmark the PR is for
+    pub bookmark: String,
+    /// The PR
+    pub pr: PullRequest,
+    /// Review and CI state
+    pub readiness: PrReadiness,
+}
+
+/// PRs per bookmark once `plan` was carried out: the ones that existed,
+/// replaced by their latest version where `result` created or updated them
+pub fn prs_after(plan: &SubmissionPlan, result: &SubmissionResult) -> HashMap<String, PullRequest> {
+    let mut prs = plan.existing_prs.clone();
+    for pr in result.created_prs.iter().chain(&result.updated_prs) {
+        prs.insert(pr.head_ref.clone(), pr.clone());
+    }
+    prs
+}
+
+/// Look up the review and CI state of each bookmark's PR
+///
+/// `bookmarks` run bottom first; bookmarks missing from `prs` are left out.
+/// Drafts aren't reviewed, so their state isn't queried.
+pub async fn stack_prs(
+    platform: &dyn PlatformService,
+    bookmarks: &[String],
+    prs: &HashMap<String, PullRequest>,
+) -> Result<Vec<StackPr>> {
+    let mut stack = Vec::new();
+    for bookmark in bookmarks {
+        let Some(pr) = prs.get(bookmark) else {
+            continue;
+        };
+        let readiness = if pr.is_draft {
+            PrReadiness {
+                approved: false,
+                checks: ChecksState::None,
+            }
+        } else {
+            platform.get_pr_readiness(pr.number).await?
+        };
+        stack.push(StackPr {
+            bookmark: bookmark.clone(),
+            pr: pr.clone(),
+            readiness,
+        });
+    }
+    Ok(stack)
+}
+
+/// Suggestions for `stacks` (each bottom first, as from [`stack_prs`])
+///
+/// `excluded` is the number of bookmarks the change graph left out of
+/// stacks. Most pressing first: roots that can land, roots with failing
+/// checks, PRs awaiting review, drafts, then excluded bookmarks.
+pub fn suggest_next_steps(
+    stacks: &[Vec<StackPr>],
+    default_branch: &str,
+    excluded: usize,
+) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+
+    // Stacks forking above a shared root list it more than once
+    let mut seen = HashSet::new();
+    let roots: Vec<&StackPr> = stacks
+        .iter()
+        .filter_map(|stack| stack.first())
+        .filter(|root| !root.pr.is_draft && root.pr.base_ref == default_branch)
+        .filter(|root| seen.insert(root.pr.number))
+        .collect();
+
+    let ready: Vec<&&StackPr> = roots
+        .iter()
+        .filter(|root| root.readiness.is_ready())
+        .collect();
+    match ready.as_slice() {
+        [] => {}
+        [root] => suggestions.push(Suggestion {
+            reason: format!("Root PR #{} is green and approved", root.pr.number),
+            command: format!("ryu merge {}", root.bookmark),
+        }),
+        _ => suggestions.push(Suggestion {
+            reason: format!("{} root PRs are green and approved", ready.len()),
+            command: "ryu land --all-ready".to_string(),
+        }),
+    }
+
+    for root in roots
+        .iter()
+        .filter(|root| root.readiness.checks == ChecksState::Failing)
+    {
+        suggestions.push(Suggestion {
+            reason: format!("Checks are failing on root PR #{}", root.pr.number),
+            command: format!("ryu open {}", root.bookmark),
+        });
+    }
+
+    let mut seen = HashSet::new();
+    let awaiting = stacks
+        .iter()
+        .flatten()
+        .filter(|entry| !entry.pr.is_draft && !entry.readiness.approved)
+        .filter(|entry| seen.insert(entry.pr.number))
+        .count();
+    if awaiting > 0 {
+        suggestions.push(Suggestion {
+            reason: format!(
+                "{awaiting} PR{} await{} review",
+                if awaiting == 1 { "" } else { "s" },
+                if awaiting == 1 { "s" } else { "" }
+            ),
+            command: "ryu status".to_string(),
+        });
+    }
+
+    for stack in stacks {
+        let drafts = stack.iter().filter(|entry| entry.pr.is_draft).count();
+        let Some(leaf) = stack.last().filter(|_| drafts > 0) else {
+            continue;
+        };
+        suggestions.push(Suggestion {
+            reason: format!(
+                "{drafts} draft PR{} in {}'s stack",
+                if drafts == 1 { "" } else { "s" },
+                leaf.bookmark
+            ),
+            command: format!("ryu submit {} --publish", leaf.bookmark),
+        });
+    }
+
+    if excluded > 0 {
+        suggestions.push(Suggestion {
+            reason: format!(
+                "{excluded} bookmark{} excluded from stacks",
+                if excluded == 1 { "" } else { "s" }
+            ),
+            command: "ryu".to_string(),
+        });
+    }
+
+    suggestions
+}
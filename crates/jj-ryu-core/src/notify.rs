@@ -0,0 +1,115 @@
+//! Outbound webhook notifications
+//!
+//! Posts a JSON summary of a submission (or merge) to a configured URL so
+//! teams can relay it to chat without wrapper scripts. The payload carries a
+//! top-level `text` field, which Slack-compatible incoming webhooks render
+//! directly; other receivers can use the structured fields.
+
+use crate::error::{Error, Result};
+use crate::platform::DEFAULT_TIMEOUT_SECS;
+use crate::read_only::is_read_only;
+use crate::run::RunContext;
+use crate::submit::SubmissionResult;
+use serde::Serialize;
+use std::time::Duration;
+use tracing::debug;
+
+/// Environment variable holding the webhook URL
+pub const WEBHOOK_URL_ENV: &str = "RYU_WEBHOOK_URL";
+
+/// What happened to trigger the notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    /// A stack was submitted
+    Submit,
+    /// Stacks were synced
+    Sync,
+    /// A PR was merged
+    Merge,
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Submit => write!(f, "submitted"),
+            Self::Sync => write!(f, "synced"),
+            Self::Merge => write!(f, "merged"),
+        }
+    }
+}
+
+/// JSON body posted to the webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload<'a> {
+    /// Event kind
+    pub event: WebhookEvent,
+    /// Human-readable one-line summary
+    pub text: String,
+    /// Repository as `owner/repo`
+    pub repository: String,
+    /// Who ran ryu, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Leaf bookmark of the stack
+    pub stack: String,
+    /// Number of PRs in the stack
+    pub pr_count: usize,
+    /// Execution result
+    pub result: &'a SubmissionResult,
+}
+
+impl<'a> WebhookPayload<'a> {
+    /// Build a payload with a generated summary line
+    pub fn new(
+        event: WebhookEvent,
+        repository: &str,
+        user: Option<String>,
+        stack: &str,
+        pr_count: usize,
+        result: &'a SubmissionResult,
+    ) -> Self {
+        let text = format!(
+            "{} {event} a {pr_count}-PR stack ({stack}) in {repository}",
+            user.as_deref().unwrap_or("Someone"),
+        );
+
+        Self {
+            event,
+            text,
+            repository: repository.to_string(),
+            user,
+            stack: stack.to_string(),
+            pr_count,
+            result,
+        }
+    }
+}
+
+/// Post a payload to a webhook URL
+///
+/// Webhook URLs usually embed a secret, so it is stripped from errors.
+/// Skipped in read-only mode, which is reported to `run`.
+pub async fn send_webhook(url: &str, payload: &WebhookPayload<'_>, run: &RunContext) -> Result<()> {
+    if is_read_only() {
+        run.skipped("webhook notification");
+        return Ok(());
+    }
+    debug!(event = ?payload.event, "sending webhook");
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| Error::Platform(format!("failed to create HTTP client: {e}")))?;
+
+    client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| Error::Http(e.without_url()))?
+        .error_for_status()
+        .map_err(|e| Error::Platform(format!("webhook failed: {}", e.without_url())))?;
+
+    debug!("sent webhook");
+    Ok(())
+}
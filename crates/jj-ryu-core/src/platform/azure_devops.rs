@@ -0,0 +1,813 @@
+//! Azure DevOps Repos platform service implementation
+//!
+//! Talks to the Git REST API of Azure DevOps Services (dev.azure.com) and
+//! Azure DevOps Server. PRs there have no per-PR comment objects: comments
+//! live in threads, so a [`PrComment`] is a thread, identified by the
+//! thread ID, whose body is the thread's first comment.
+
+use crate::error::{Error, Result};
+use crate::metrics::MeteredJson;
+use crate::platform::{DEFAULT_TIMEOUT_SECS, PlatformService};
+use crate::run::RunContext;
+use crate::types::{
+    ChecksState, Issue, MergeMethod, Platform, PlatformConfig, PrComment, PrMetadata, PrReadiness,
+    PrReview, PrState, PullRequest, ReviewState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tracing::debug;
+
+/// REST API version requested on every call
+const API_VERSION: &str = "7.1";
+
+/// Page size for paginated PR listings
+const PAGE_SIZE: usize = 100;
+
+/// Host of Azure DevOps Services
+const DEFAULT_AZURE_DEVOPS_HOST: &str = "dev.azure.com";
+
+/// ID of the first comment of a thread, which holds the thread's text
+const FIRST_COMMENT_ID: u64 = 1;
+
+/// Reviewer vote for "approved" (10) and "approved with suggestions" (5)
+const VOTE_APPROVED: i32 = 5;
+/// Reviewer vote for "waiting for author" (-5) and "rejected" (-10)
+const VOTE_WAITING: i32 = -5;
+
+/// Azure DevOps service using the Git REST API
+pub struct AzureDevOpsService {
+    client: Client,
+    token: String,
+    /// Scheme and host, e.g. `https://dev.azure.com`
+    base_url: String,
+    /// Organization (or collection) URL, e.g. `https://dev.azure.com/org`
+    organization_url: String,
+    /// Root of identity lookups, which Azure DevOps Services serves
+    /// from its own host
+    identities_url: String,
+    config: PlatformConfig,
+    run: Arc<RunContext>,
+}
+
+/// A list response; Azure DevOps wraps every list in `value`
+#[derive(Deserialize)]
+struct List<T> {
+    value: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Identity {
+    #[serde(default)]
+    display_name: String,
+    #[serde(default)]
+    unique_name: String,
+}
+
+impl Identity {
+    /// Name shown for the identity; the unique name (usually an email
+    /// address) when it has one
+    fn name(&self) -> &str {
+        if self.unique_name.is_empty() {
+            &self.display_name
+        } else {
+            &self.unique_name
+        }
+    }
+
+    /// Whether `name` names this identity, by unique or display name
+    fn is(&self, name: &str) -> bool {
+        self.unique_name.eq_ignore_ascii_case(name) || self.display_name.eq_ignore_ascii_case(name)
+    }
+}
+
+#[derive(Deserialize)]
+struct Reviewer {
+    #[serde(flatten)]
+    identity: Identity,
+    #[serde(default)]
+    vote: i32,
+}
+
+/// Each reviewer's approval or change request
+///
+/// Azure DevOps keeps one vote per reviewer; "approved with suggestions"
+/// counts as an approval and "waiting for author" as a change request.
+fn verdicts(reviewers: &[Reviewer]) -> Vec<PrReview> {
+    reviewers
+        .iter()
+        .filter_map(|reviewer| {
+            let state = match reviewer.vote {
+                vote if vote >= VOTE_APPROVED => ReviewState::Approved,
+                vote if vote <= VOTE_WAITING => ReviewState::ChangesRequested,
+                _ => return None,
+            };
+            Some(PrReview {
+                reviewer: reviewer.identity.name().to_string(),
+                state,
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitRef {
+    commit_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzurePr {
+    pull_request_id: u64,
+    title: String,
+    /// Cut short in list responses; fetch the PR for all of it
+    #[serde(default)]
+    description: Option<String>,
+    status: String,
+    #[serde(default)]
+    is_draft: bool,
+    source_ref_name: String,
+    target_ref_name: String,
+    #[serde(default)]
+    creation_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    closed_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    created_by: Option<Identity>,
+    #[serde(default)]
+    reviewers: Vec<Reviewer>,
+    #[serde(default)]
+    last_merge_source_commit: Option<CommitRef>,
+    #[serde(default)]
+    last_merge_commit: Option<CommitRef>,
+}
+
+impl AzurePr {
+    fn state(&self) -> PrState {
+        match self.status.as_str() {
+            "completed" => PrState::Merged,
+            "abandoned" => PrState::Closed,
+            _ if self.is_draft => PrState::Draft,
+            _ => PrState::Open,
+        }
+    }
+
+    fn head_branch(&self) -> &str {
+        branch_name(&self.source_ref_name)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusContext {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    genre: String,
+}
+
+#[derive(Deserialize)]
+struct PrStatus {
+    #[serde(default)]
+    state: String,
+    context: StatusContext,
+}
+
+/// Combined state of a PR's statuses
+///
+/// Statuses come oldest first; a later status of the same context replaces
+/// an earlier one.
+fn checks_state(statuses: &[PrStatus]) -> ChecksState {
+    let mut latest: Vec<&PrStatus> = Vec::new();
+    for status in statuses {
+        let same_context = |s: &&PrStatus| {
+            s.context.name == status.context.name && s.context.genre == status.context.genre
+        };
+        latest.retain(|s| !same_context(s));
+        latest.push(status);
+    }
+    if latest.is_empty() {
+        ChecksState::None
+    } else if latest
+        .iter()
+        .any(|s| matches!(s.state.as_str(), "failed" | "error"))
+    {
+        ChecksState::Failing
+    } else if latest
+        .iter()
+        .all(|s| matches!(s.state.as_str(), "succeeded" | "notApplicable"))
+    {
+        ChecksState::Passing
+    } else {
+        ChecksState::Pending
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadComment {
+    id: u64,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    comment_type: String,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Thread {
+    id: u64,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    comments: Vec<ThreadComment>,
+}
+
+impl Thread {
+    /// The thread as a PR comment, unless it's deleted or system-written
+    fn into_comment(self) -> Option<PrComment> {
+        if self.is_deleted {
+            return None;
+        }
+        let first = self
+            .comments
+            .into_iter()
+            .find(|c| c.id == FIRST_COMMENT_ID)?;
+        if first.is_deleted || first.comment_type != "text" {
+            return None;
+        }
+        Some(PrComment {
+            id: self.id,
+            body: first.content.unwrap_or_default(),
+            node_id: None,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionData {
+    authenticated_user: AuthenticatedUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatedUser {
+    #[serde(default)]
+    provider_display_name: String,
+    #[serde(default)]
+    properties: serde_json::Value,
+}
+
+impl AuthenticatedUser {
+    /// Account name (usually an email address), which reviewer and author
+    /// unique names match
+    fn account(self) -> String {
+        self.properties["Account"]["$value"]
+            .as_str()
+            .map_or(self.provider_display_name, String::from)
+    }
+}
+
+#[derive(Deserialize)]
+struct IdentityId {
+    id: String,
+}
+
+/// Branch name of a full ref name
+fn branch_name(ref_name: &str) -> &str {
+    ref_name.strip_prefix("refs/heads/").unwrap_or(ref_name)
+}
+
+/// Full ref name of a branch
+fn branch_ref(branch: &str) -> String {
+    format!("refs/heads/{branch}")
+}
+
+impl AzureDevOpsService {
+    /// Create a new Azure DevOps service
+    ///
+    /// `owner` is the `organization/project` path (for Azure DevOps Server,
+    /// the path to the project's collection and the project). `host`
+    /// defaults to dev.azure.com. A host with a scheme (e.g.
+    /// `http://tfs.local:8080`) is used as-is; otherwise HTTPS is assumed.
+    pub fn new(token: String, owner: String, repo: String, host: Option<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| Error::AzureDevOpsApi(format!("failed to create HTTP client: {e}")))?;
+
+        let base = host.as_deref().unwrap_or(DEFAULT_AZURE_DEVOPS_HOST);
+        let base_url = if base.contains("://") {
+            base.trim_end_matches('/').to_string()
+        } else {
+            format!("https://{base}")
+        };
+        let (organization, _project) = owner.rsplit_once('/').ok_or_else(|| {
+            Error::AzureDevOpsApi(format!("expected organization/project, got '{owner}'"))
+        })?;
+        let organization_url = format!("{base_url}/{organization}");
+        let identities_url = if host.is_none() {
+            format!("https://vssps.{DEFAULT_AZURE_DEVOPS_HOST}/{organization}/_apis/identities")
+        } else {
+            format!("{organization_url}/_apis/identities")
+        };
+
+        Ok(Self {
+            client,
+            token,
+            base_url,
+            organization_url,
+            identities_url,
+            config: PlatformConfig {
+                platform: Platform::AzureDevOps,
+                owner,
+                repo,
+                host,
+                head_owner: None,
+            },
+            run: Arc::default(),
+        })
+    }
+
+    /// Count responses in `run`'s metrics
+    #[must_use]
+    pub fn with_run(mut self, run: Arc<RunContext>) -> Self {
+        self.run = run;
+        self
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}/_apis/git/repositories/{}{path}",
+            self.base_url, self.config.owner, self.config.repo
+        )
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth("", Some(&self.token))
+            .query(&[("api-version", API_VERSION)])
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    fn post(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::POST, url)
+    }
+
+    fn patch(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::PATCH, url)
+    }
+
+    fn put(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::PUT, url)
+    }
+
+    async fn send_raw(request: RequestBuilder) -> Result<reqwest::Response> {
+        let response = request
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?;
+        // A rejected token can get the sign-in page rather than a 401
+        if response.status() == StatusCode::NON_AUTHORITATIVE_INFORMATION {
+            return Err(Error::Auth(
+                "Azure DevOps rejected the token (it answered with its sign-in page)".to_string(),
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        Ok(Self::send_raw(request)
+            .await?
+            .metered_json(&self.run)
+            .await?)
+    }
+
+    async fn send_empty(request: RequestBuilder) -> Result<()> {
+        Self::send_raw(request).await?;
+        Ok(())
+    }
+
+    async fn get_pr(&self, pr_number: u64) -> Result<AzurePr> {
+        self.send(self.get(&self.repo_url(&format!("/pullrequests/{pr_number}"))))
+            .await
+    }
+
+    async fn edit_pr(&self, pr_number: u64, changes: &serde_json::Value) -> Result<AzurePr> {
+        self.send(
+            self.patch(&self.repo_url(&format!("/pullrequests/{pr_number}")))
+                .json(changes),
+        )
+        .await
+    }
+
+    /// PRs in `status` (`active`, `completed`, `abandoned` or `all`), of
+    /// `head_branch` if given
+    async fn list_prs(&self, status: &str, head_branch: Option<&str>) -> Result<Vec<AzurePr>> {
+        let source_ref = head_branch.map(branch_ref);
+        let top = PAGE_SIZE.to_string();
+        let mut prs = Vec::new();
+        loop {
+            let skip = prs.len().to_string();
+            let mut request = self.get(&self.repo_url("/pullrequests")).query(&[
+                ("searchCriteria.status", status),
+                ("$top", top.as_str()),
+                ("$skip", skip.as_str()),
+            ]);
+            if let Some(source_ref) = &source_ref {
+                request = request.query(&[("searchCriteria.sourceRefName", source_ref)]);
+            }
+            let mut batch: List<AzurePr> = self.send(request).await?;
+            let last = batch.value.len() < PAGE_SIZE;
+            prs.append(&mut batch.value);
+            if last {
+                break;
+            }
+        }
+        Ok(prs)
+    }
+
+    fn pull_request(&self, pr: AzurePr) -> PullRequest {
+        PullRequest {
+            number: pr.pull_request_id,
+            html_url: format!(
+                "{}/pullrequest/{}",
+                self.config.web_url(),
+                pr.pull_request_id
+            ),
+            base_ref: branch_name(&pr.target_ref_name).to_string(),
+            head_ref: branch_name(&pr.source_ref_name).to_string(),
+            title: pr.title,
+            node_id: None, // Azure DevOps doesn't use GraphQL node IDs
+            is_draft: pr.is_draft,
+            updated_at: pr.closed_date.or(pr.creation_date),
+        }
+    }
+
+    async fn list_statuses(&self, pr_number: u64) -> Result<Vec<PrStatus>> {
+        let statuses: List<PrStatus> = self
+            .send(self.get(&self.repo_url(&format!("/pullrequests/{pr_number}/statuses"))))
+            .await?;
+        Ok(statuses.value)
+    }
+
+    /// ID of the identity `name` (unique or display name) refers to
+    async fn identity_id(&self, name: &str) -> Result<String> {
+        let found: List<IdentityId> = self
+            .send(self.get(&self.identities_url).query(&[
+                ("searchFilter", "General"),
+                ("filterValue", name),
+                ("queryMembership", "None"),
+            ]))
+            .await?;
+        found
+            .value
+            .into_iter()
+            .next()
+            .map(|identity| identity.id)
+            .ok_or_else(|| Error::AzureDevOpsApi(format!("no Azure DevOps user named '{name}'")))
+    }
+}
+
+#[async_trait]
+impl PlatformService for AzureDevOpsService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        debug!(head_branch, "finding existing PR");
+
+        // Oldest PR wins so duplicates can't flip-flop between runs
+        let result = self
+            .find_open_prs(head_branch)
+            .await?
+            .into_iter()
+            .min_by_key(|pr| pr.number);
+        if let Some(ref pr) = result {
+            debug!(pr_number = pr.number, "found existing PR");
+        } else {
+            debug!("no existing PR found");
+        }
+        Ok(result)
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        debug!(head_branch, "listing open PRs");
+        let result: Vec<PullRequest> = self
+            .list_prs("active", Some(head_branch))
+            .await?
+            .into_iter()
+            .map(|pr| self.pull_request(pr))
+            .collect();
+        debug!(head_branch, count = result.len(), "listed open PRs");
+        Ok(result)
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        debug!(head_branch, "finding latest PR");
+        Ok(self
+            .list_prs("all", Some(head_branch))
+            .await?
+            .into_iter()
+            .filter(|pr| pr.head_branch() == head_branch)
+            .max_by_key(|pr| pr.pull_request_id)
+            .map(|pr| {
+                let state = pr.state();
+                (self.pull_request(pr), state)
+            }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        debug!(author, "listing open PRs by author");
+        let result: Vec<PullRequest> = self
+            .list_prs("active", None)
+            .await?
+            .into_iter()
+            .filter(|pr| pr.created_by.as_ref().is_some_and(|user| user.is(author)))
+            .map(|pr| self.pull_request(pr))
+            .collect();
+        debug!(author, count = result.len(), "listed open PRs by author");
+        Ok(result)
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        // connectionData takes no API version
+        let data: ConnectionData = self
+            .send(
+                self.client
+                    .get(format!("{}/_apis/connectionData", self.organization_url))
+                    .basic_auth("", Some(&self.token)),
+            )
+            .await?;
+        Ok(data.authenticated_user.account())
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating PR");
+        let pr: AzurePr = self
+            .send(
+                self.post(&self.repo_url("/pullrequests"))
+                    .json(&serde_json::json!({
+                        "sourceRefName": branch_ref(head),
+                        "targetRefName": branch_ref(base),
+                        "title": title,
+                        "description": body,
+                        "isDraft": draft,
+                    })),
+            )
+            .await?;
+
+        debug!(pr_number = pr.pull_request_id, "created PR");
+        Ok(self.pull_request(pr))
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        debug!(pr_number, new_base, "updating PR base");
+        let pr = self
+            .edit_pr(
+                pr_number,
+                &serde_json::json!({ "targetRefName": branch_ref(new_base) }),
+            )
+            .await?;
+        debug!(pr_number, "updated PR base");
+        Ok(self.pull_request(pr))
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        Ok(self
+            .get_pr(pr_number)
+            .await?
+            .description
+            .unwrap_or_default())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        let pr = self.get_pr(pr_number).await?;
+        if pr.state() != PrState::Merged {
+            return Ok(None);
+        }
+        Ok(pr.last_merge_commit.map(|commit| commit.commit_id))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "updating PR body");
+        self.edit_pr(pr_number, &serde_json::json!({ "description": body }))
+            .await?;
+        debug!(pr_number, "updated PR body");
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        debug!(pr_number, "publishing PR");
+        let pr = self
+            .edit_pr(pr_number, &serde_json::json!({ "isDraft": false }))
+            .await?;
+        debug!(pr_number, "published PR");
+        Ok(self.pull_request(pr))
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_number, "closing PR");
+        self.edit_pr(pr_number, &serde_json::json!({ "status": "abandoned" }))
+            .await?;
+        debug!(pr_number, "closed PR");
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(pr_number, %method, "merging PR");
+        let strategy = match method {
+            MergeMethod::Merge => "noFastForward",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+        // Completing needs the source commit the merge was checked at
+        let pr = self.get_pr(pr_number).await?;
+        let source = pr.last_merge_source_commit.ok_or_else(|| {
+            Error::AzureDevOpsApi(format!("PR !{pr_number} has no merge source commit yet"))
+        })?;
+        // Azure DevOps completes the PR in the background
+        self.edit_pr(
+            pr_number,
+            &serde_json::json!({
+                "status": "completed",
+                "lastMergeSourceCommit": { "commitId": source.commit_id },
+                "completionOptions": {
+                    "mergeStrategy": strategy,
+                    "deleteSourceBranch": false,
+                },
+            }),
+        )
+        .await?;
+        debug!(pr_number, "merged PR");
+        Ok(())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        debug!(pr_number, "checking PR readiness");
+        let pr = self.get_pr(pr_number).await?;
+        let statuses = self.list_statuses(pr_number).await?;
+
+        let approved = pr.reviewers.iter().any(|r| r.vote >= VOTE_APPROVED)
+            && !pr.reviewers.iter().any(|r| r.vote <= VOTE_WAITING);
+        let checks = checks_state(&statuses);
+        debug!(pr_number, approved, %checks, "checked PR readiness");
+        Ok(PrReadiness { approved, checks })
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        debug!(pr_number, "listing PR reviews");
+        let verdicts = verdicts(&self.get_pr(pr_number).await?.reviewers);
+        debug!(pr_number, count = verdicts.len(), "listed PR reviews");
+        Ok(verdicts)
+    }
+
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        Ok(checks_state(&self.list_statuses(pr_number).await?))
+    }
+
+    async fn add_pr_to_project(&self, _pr: &PullRequest, _project: &str) -> Result<()> {
+        Err(Error::AzureDevOpsApi(
+            "ryu can't add Azure DevOps PRs to boards; link a work item instead".to_string(),
+        ))
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        debug!(pr_number, ?reviewers, "requesting reviews");
+        for reviewer in reviewers {
+            let id = self.identity_id(reviewer).await?;
+            Self::send_empty(
+                self.put(&self.repo_url(&format!("/pullrequests/{pr_number}/reviewers/{id}")))
+                    .json(&serde_json::json!({ "vote": 0 })),
+            )
+            .await?;
+        }
+        debug!(pr_number, "requested reviews");
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        debug!(pr_number, ?metadata, "updating PR metadata");
+        if !metadata.assignees.is_empty() {
+            return Err(Error::AzureDevOpsApi(
+                "Azure DevOps PRs have no assignees".to_string(),
+            ));
+        }
+        if !metadata.reviewers.is_empty() {
+            self.request_reviewers(pr_number, &metadata.reviewers)
+                .await?;
+        }
+        for label in &metadata.labels {
+            Self::send_empty(
+                self.post(&self.repo_url(&format!("/pullrequests/{pr_number}/labels")))
+                    .json(&serde_json::json!({ "name": label })),
+            )
+            .await?;
+        }
+        debug!(pr_number, "updated PR metadata");
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        // A reviewer who hasn't voted yet still owes a review
+        let count = self
+            .list_prs("active", None)
+            .await?
+            .iter()
+            .filter(|pr| {
+                pr.reviewers
+                    .iter()
+                    .any(|r| r.vote == 0 && r.identity.is(reviewer))
+            })
+            .count();
+        debug!(reviewer, count, "counted open review requests");
+        Ok(count)
+    }
+
+    async fn create_issue(&self, _title: &str, _body: &str) -> Result<Issue> {
+        Err(Error::AzureDevOpsApi(
+            "Azure Repos has no issues to track stacks in".to_string(),
+        ))
+    }
+
+    async fn get_issue_body(&self, _issue_number: u64) -> Result<String> {
+        Err(Error::AzureDevOpsApi(
+            "Azure Repos has no issues to track stacks in".to_string(),
+        ))
+    }
+
+    async fn update_issue(&self, _issue_number: u64, _body: &str) -> Result<()> {
+        Err(Error::AzureDevOpsApi(
+            "Azure Repos has no issues to track stacks in".to_string(),
+        ))
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        debug!(pr_number, "listing PR comments");
+        let threads: List<Thread> = self
+            .send(self.get(&self.repo_url(&format!("/pullrequests/{pr_number}/threads"))))
+            .await?;
+
+        let result: Vec<PrComment> = threads
+            .value
+            .into_iter()
+            .filter_map(Thread::into_comment)
+            .collect();
+        debug!(pr_number, count = result.len(), "listed PR comments");
+        Ok(result)
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "creating PR comment");
+        // Closed, so the thread doesn't hold up completion under a
+        // "resolve all comments" branch policy
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/pullrequests/{pr_number}/threads")))
+                .json(&serde_json::json!({
+                    "comments": [{
+                        "parentCommentId": 0,
+                        "content": body,
+                        "commentType": "text",
+                    }],
+                    "status": "closed",
+                })),
+        )
+        .await?;
+        debug!(pr_number, "created PR comment");
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        debug!(pr_number, comment_id, "updating PR comment");
+        Self::send_empty(
+            self.patch(&self.repo_url(&format!(
+                "/pullrequests/{pr_number}/threads/{comment_id}/comments/{FIRST_COMMENT_ID}"
+            )))
+            .json(&serde_json::json!({ "content": body })),
+        )
+        .await?;
+        debug!(pr_number, comment_id, "updated PR comment");
+        Ok(())
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
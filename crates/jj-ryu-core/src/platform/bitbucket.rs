@@ -0,0 +1,690 @@
+//! Bitbucket Cloud platform service implementation
+
+use crate::error::{Error, Result};
+use crate::metrics::MeteredJson;
+use crate::platform::{DEFAULT_TIMEOUT_SECS, PlatformService};
+use crate::run::RunContext;
+use crate::types::{
+    ChecksState, Issue, MergeMethod, Platform, PlatformConfig, PrComment, PrMetadata, PrReadiness,
+    PrReview, PrState, PullRequest, ReviewState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Bitbucket Cloud REST API root
+const API_ROOT: &str = "https://api.bitbucket.org/2.0";
+
+/// Page size for paginated list requests (Bitbucket's maximum is 50)
+const PAGE_LEN: &str = "50";
+
+/// Bitbucket Cloud service using the 2.0 REST API
+pub struct BitbucketService {
+    client: Client,
+    api_root: String,
+    username: String,
+    app_password: String,
+    config: PlatformConfig,
+    run: Arc<RunContext>,
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BranchRef {
+    branch: BranchName,
+}
+
+#[derive(Deserialize)]
+struct BranchName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    html: Link,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    #[serde(default)]
+    nickname: String,
+    #[serde(default)]
+    uuid: String,
+    #[serde(default)]
+    account_id: Option<String>,
+}
+
+impl Account {
+    /// Whether `name` (nickname or account ID) refers to this account
+    fn is(&self, name: &str) -> bool {
+        self.nickname.eq_ignore_ascii_case(name) || self.account_id.as_deref() == Some(name)
+    }
+}
+
+#[derive(Deserialize)]
+struct BitbucketPr {
+    id: u64,
+    title: String,
+    #[serde(default)]
+    description: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    updated_on: Option<DateTime<Utc>>,
+    source: BranchRef,
+    destination: BranchRef,
+    links: Links,
+    author: Option<Account>,
+    #[serde(default)]
+    reviewers: Vec<Account>,
+    #[serde(default)]
+    participants: Vec<Participant>,
+    #[serde(default)]
+    merge_commit: Option<MergeCommit>,
+}
+
+#[derive(Deserialize)]
+struct MergeCommit {
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct Participant {
+    #[serde(default)]
+    approved: bool,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    user: Option<Account>,
+}
+
+impl Participant {
+    /// The participant's current verdict, if they gave one
+    fn review(&self) -> Option<PrReview> {
+        let state = if self.state.as_deref() == Some("changes_requested") {
+            ReviewState::ChangesRequested
+        } else if self.approved {
+            ReviewState::Approved
+        } else {
+            return None;
+        };
+        let user = self.user.as_ref()?;
+        let reviewer = match &user.account_id {
+            Some(id) if user.nickname.is_empty() => id.clone(),
+            _ => user.nickname.clone(),
+        };
+        Some(PrReview { reviewer, state })
+    }
+}
+
+#[derive(Deserialize)]
+struct CommitStatus {
+    state: String,
+}
+
+impl BitbucketPr {
+    fn state(&self) -> PrState {
+        match self.state.as_str() {
+            "MERGED" => PrState::Merged,
+            "DECLINED" | "SUPERSEDED" => PrState::Closed,
+            _ if self.draft => PrState::Draft,
+            _ => PrState::Open,
+        }
+    }
+}
+
+impl From<BitbucketPr> for PullRequest {
+    fn from(pr: BitbucketPr) -> Self {
+        Self {
+            number: pr.id,
+            html_url: pr.links.html.href,
+            base_ref: pr.destination.branch.name,
+            head_ref: pr.source.branch.name,
+            title: pr.title,
+            node_id: None, // Bitbucket doesn't use GraphQL node IDs
+            is_draft: pr.draft,
+            updated_at: pr.updated_on,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Content {
+    #[serde(default)]
+    raw: String,
+}
+
+#[derive(Deserialize)]
+struct BitbucketComment {
+    id: u64,
+    content: Content,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Deserialize)]
+struct BitbucketIssue {
+    id: u64,
+    content: Option<Content>,
+    links: Links,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceMember {
+    user: Account,
+}
+
+impl BitbucketService {
+    /// Create a new Bitbucket service
+    ///
+    /// `owner` is the workspace and `repo` the repository slug.
+    pub fn new(
+        username: String,
+        app_password: String,
+        owner: String,
+        repo: String,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| Error::BitbucketApi(format!("failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            client,
+            api_root: API_ROOT.to_string(),
+            username,
+            app_password,
+            config: PlatformConfig {
+                platform: Platform::Bitbucket,
+                owner,
+                repo,
+                host: None,
+                head_owner: None,
+            },
+            run: Arc::default(),
+        })
+    }
+
+    /// Count responses in `run`'s metrics
+    #[must_use]
+    pub fn with_run(mut self, run: Arc<RunContext>) -> Self {
+        self.run = run;
+        self
+    }
+
+    /// Send requests to `api_root` instead of Bitbucket Cloud's API, e.g. a
+    /// proxy or a mock server
+    #[must_use]
+    pub fn with_api_root(mut self, api_root: impl Into<String>) -> Self {
+        self.api_root = api_root.into();
+        self
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!(
+            "{}/repositories/{}/{}{path}",
+            self.api_root, self.config.owner, self.config.repo
+        )
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        self.client
+            .get(url)
+            .basic_auth(&self.username, Some(&self.app_password))
+    }
+
+    fn post(&self, url: &str) -> RequestBuilder {
+        self.client
+            .post(url)
+            .basic_auth(&self.username, Some(&self.app_password))
+    }
+
+    fn put(&self, url: &str) -> RequestBuilder {
+        self.client
+            .put(url)
+            .basic_auth(&self.username, Some(&self.app_password))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        Ok(request
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::BitbucketApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?)
+    }
+
+    async fn send_empty(request: RequestBuilder) -> Result<()> {
+        request
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::BitbucketApi(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Fetch every page of a list endpoint, following `next` links
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let mut page: Page<T> = self
+            .send(self.get(url).query(query).query(&[("pagelen", PAGE_LEN)]))
+            .await?;
+        let mut values = std::mem::take(&mut page.values);
+        while let Some(next) = page.next.take() {
+            // `next` already carries the query
+            page = self.send(self.get(&next)).await?;
+            values.append(&mut page.values);
+        }
+        Ok(values)
+    }
+
+    async fn get_pr(&self, pr_number: u64) -> Result<BitbucketPr> {
+        self.send(self.get(&self.repo_url(&format!("/pullrequests/{pr_number}"))))
+            .await
+    }
+
+    /// Apply a partial update to a PR (the title must always be sent)
+    async fn update_pr(
+        &self,
+        pr: &BitbucketPr,
+        mut changes: serde_json::Value,
+    ) -> Result<BitbucketPr> {
+        changes["title"] = serde_json::Value::String(pr.title.clone());
+        self.send(
+            self.put(&self.repo_url(&format!("/pullrequests/{}", pr.id)))
+                .json(&changes),
+        )
+        .await
+    }
+
+    /// Open PRs matching a BBQL `query`, with reviewers included
+    async fn list_open_prs(&self, query: Option<&str>) -> Result<Vec<BitbucketPr>> {
+        let mut params = vec![("state", "OPEN"), ("fields", "+values.reviewers")];
+        if let Some(query) = query {
+            params.push(("q", query));
+        }
+        self.get_all(&self.repo_url("/pullrequests"), &params).await
+    }
+}
+
+/// BBQL string literal
+fn bbql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[async_trait]
+impl PlatformService for BitbucketService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        debug!(head_branch, "finding existing PR");
+
+        // Oldest PR wins so duplicates can't flip-flop between runs
+        let result = self
+            .find_open_prs(head_branch)
+            .await?
+            .into_iter()
+            .min_by_key(|pr| pr.number);
+        if let Some(ref pr) = result {
+            debug!(pr_number = pr.number, "found existing PR");
+        } else {
+            debug!("no existing PR found");
+        }
+        Ok(result)
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        debug!(head_branch, "listing open PRs");
+        let query = format!("source.branch.name = {}", bbql_string(head_branch));
+        let result: Vec<PullRequest> = self
+            .list_open_prs(Some(&query))
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        debug!(head_branch, count = result.len(), "listed open PRs");
+        Ok(result)
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        debug!(head_branch, "finding latest PR");
+        let query = format!("source.branch.name = {}", bbql_string(head_branch));
+        // Without explicit states Bitbucket only lists open PRs
+        let page: Page<BitbucketPr> = self
+            .send(self.get(&self.repo_url("/pullrequests")).query(&[
+                ("state", "OPEN"),
+                ("state", "MERGED"),
+                ("state", "DECLINED"),
+                ("state", "SUPERSEDED"),
+                ("q", query.as_str()),
+                ("sort", "-created_on"),
+                ("pagelen", "1"),
+            ]))
+            .await?;
+
+        Ok(page.values.into_iter().next().map(|pr| {
+            let state = pr.state();
+            (pr.into(), state)
+        }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        debug!(author, "listing open PRs by author");
+        let result: Vec<PullRequest> = self
+            .list_open_prs(None)
+            .await?
+            .into_iter()
+            .filter(|pr| pr.author.as_ref().is_some_and(|a| a.is(author)))
+            .map(Into::into)
+            .collect();
+        debug!(author, count = result.len(), "listed open PRs by author");
+        Ok(result)
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        let user: Account = self
+            .send(self.get(&format!("{}/user", self.api_root)))
+            .await?;
+        Ok(user.nickname)
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating PR");
+        let pr: BitbucketPr = self
+            .send(
+                self.post(&self.repo_url("/pullrequests"))
+                    .json(&serde_json::json!({
+                        "title": title,
+                        "description": body,
+                        "source": { "branch": { "name": head } },
+                        "destination": { "branch": { "name": base } },
+                        "draft": draft,
+                    })),
+            )
+            .await?;
+
+        debug!(pr_number = pr.id, "created PR");
+        Ok(pr.into())
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        debug!(pr_number, new_base, "updating PR destination");
+        let pr = self.get_pr(pr_number).await?;
+        let updated = self
+            .update_pr(
+                &pr,
+                serde_json::json!({ "destination": { "branch": { "name": new_base } } }),
+            )
+            .await?;
+        debug!(pr_number, "updated PR destination");
+        Ok(updated.into())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        Ok(self.get_pr(pr_number).await?.description)
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        let pr = self.get_pr(pr_number).await?;
+        if pr.state() != PrState::Merged {
+            return Ok(None);
+        }
+        Ok(pr.merge_commit.map(|commit| commit.hash))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "updating PR description");
+        let pr = self.get_pr(pr_number).await?;
+        self.update_pr(&pr, serde_json::json!({ "description": body }))
+            .await?;
+        debug!(pr_number, "updated PR description");
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        debug!(pr_number, "publishing PR");
+        let pr = self.get_pr(pr_number).await?;
+        let updated = self
+            .update_pr(&pr, serde_json::json!({ "draft": false }))
+            .await?;
+        debug!(pr_number, "published PR");
+        Ok(updated.into())
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_number, "declining PR");
+        Self::send_empty(self.post(&self.repo_url(&format!("/pullrequests/{pr_number}/decline"))))
+            .await?;
+        debug!(pr_number, "declined PR");
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(pr_number, %method, "merging PR");
+        let strategy = match method {
+            MergeMethod::Merge => "merge_commit",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase_fast_forward",
+        };
+        // Large merges are accepted with 202 and finish in the background
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/pullrequests/{pr_number}/merge")))
+                .json(&serde_json::json!({ "merge_strategy": strategy })),
+        )
+        .await?;
+        debug!(pr_number, "merged PR");
+        Ok(())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        debug!(pr_number, "checking PR readiness");
+        let pr = self.get_pr(pr_number).await?;
+        let statuses: Vec<CommitStatus> = self
+            .get_all(
+                &self.repo_url(&format!("/pullrequests/{pr_number}/statuses")),
+                &[],
+            )
+            .await?;
+
+        let approved = pr.participants.iter().any(|p| p.approved)
+            && !pr
+                .participants
+                .iter()
+                .any(|p| p.state.as_deref() == Some("changes_requested"));
+        let checks = if statuses.is_empty() {
+            ChecksState::None
+        } else if statuses
+            .iter()
+            .any(|s| matches!(s.state.as_str(), "FAILED" | "STOPPED"))
+        {
+            ChecksState::Failing
+        } else if statuses.iter().all(|s| s.state == "SUCCESSFUL") {
+            ChecksState::Passing
+        } else {
+            ChecksState::Pending
+        };
+        debug!(pr_number, approved, %checks, "checked PR readiness");
+        Ok(PrReadiness { approved, checks })
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        debug!(pr_number, "listing PR reviews");
+        let pr = self.get_pr(pr_number).await?;
+        let reviews: Vec<PrReview> = pr
+            .participants
+            .iter()
+            .filter_map(Participant::review)
+            .collect();
+        debug!(pr_number, count = reviews.len(), "listed PR reviews");
+        Ok(reviews)
+    }
+
+    async fn add_pr_to_project(&self, _pr: &PullRequest, _project: &str) -> Result<()> {
+        Err(Error::BitbucketApi(
+            "Bitbucket has no project boards to add PRs to".to_string(),
+        ))
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        debug!(pr_number, ?reviewers, "requesting reviews");
+
+        // PR reviewers are set by UUID; resolve nicknames via the workspace
+        let members: Vec<WorkspaceMember> = self
+            .get_all(
+                &format!("{}/workspaces/{}/members", self.api_root, self.config.owner),
+                &[],
+            )
+            .await?;
+        let pr = self.get_pr(pr_number).await?;
+        let mut uuids: Vec<String> = pr.reviewers.iter().map(|r| r.uuid.clone()).collect();
+        for name in reviewers {
+            let member = members
+                .iter()
+                .find(|m| m.user.is(name))
+                .ok_or_else(|| Error::BitbucketApi(format!("user '{name}' not found")))?;
+            if !uuids.contains(&member.user.uuid) {
+                uuids.push(member.user.uuid.clone());
+            }
+        }
+
+        let reviewers: Vec<_> = uuids
+            .iter()
+            .map(|uuid| serde_json::json!({ "uuid": uuid }))
+            .collect();
+        self.update_pr(&pr, serde_json::json!({ "reviewers": reviewers }))
+            .await?;
+        debug!(pr_number, "requested reviews");
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        if !metadata.reviewers.is_empty() {
+            self.request_reviewers(pr_number, &metadata.reviewers)
+                .await?;
+        }
+        // Reviewers are still worth setting when the rest can't be
+        if !metadata.labels.is_empty() || !metadata.assignees.is_empty() {
+            return Err(Error::BitbucketApi(
+                "Bitbucket PRs have no labels or assignees".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        let count = self
+            .list_open_prs(None)
+            .await?
+            .iter()
+            .filter(|pr| pr.reviewers.iter().any(|r| r.is(reviewer)))
+            .count();
+        debug!(reviewer, count, "counted open review requests");
+        Ok(count)
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        debug!(title, "creating issue");
+        let issue: BitbucketIssue = self
+            .send(
+                self.post(&self.repo_url("/issues"))
+                    .json(&serde_json::json!({ "title": title, "content": { "raw": body } })),
+            )
+            .await?;
+
+        debug!(issue_number = issue.id, "created issue");
+        Ok(Issue {
+            number: issue.id,
+            html_url: issue.links.html.href,
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        let issue: BitbucketIssue = self
+            .send(self.get(&self.repo_url(&format!("/issues/{issue_number}"))))
+            .await?;
+        Ok(issue.content.map(|c| c.raw).unwrap_or_default())
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        debug!(issue_number, "updating issue content");
+        Self::send_empty(
+            self.put(&self.repo_url(&format!("/issues/{issue_number}")))
+                .json(&serde_json::json!({ "content": { "raw": body } })),
+        )
+        .await?;
+        debug!(issue_number, "updated issue content");
+        Ok(())
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        debug!(pr_number, "listing PR comments");
+        let comments: Vec<BitbucketComment> = self
+            .get_all(
+                &self.repo_url(&format!("/pullrequests/{pr_number}/comments")),
+                &[],
+            )
+            .await?;
+
+        let result: Vec<PrComment> = comments
+            .into_iter()
+            .filter(|c| !c.deleted)
+            .map(|c| PrComment {
+                id: c.id,
+                body: c.content.raw,
+                node_id: None,
+            })
+            .collect();
+        debug!(pr_number, count = result.len(), "listed PR comments");
+        Ok(result)
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "creating PR comment");
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/pullrequests/{pr_number}/comments")))
+                .json(&serde_json::json!({ "content": { "raw": body } })),
+        )
+        .await?;
+        debug!(pr_number, "created PR comment");
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        debug!(pr_number, comment_id, "updating PR comment");
+        Self::send_empty(
+            self.put(&self.repo_url(&format!("/pullrequests/{pr_number}/comments/{comment_id}")))
+                .json(&serde_json::json!({ "content": { "raw": body } })),
+        )
+        .await?;
+        debug!(pr_number, comment_id, "updated PR comment");
+        Ok(())
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
@@ -0,0 +1,515 @@
+//! Platform detection from remote URLs
+
+use crate::config::PlatformChoice;
+use crate::error::{Error, Result};
+use crate::types::{Platform, PlatformConfig};
+use std::env;
+use std::path::Path;
+
+/// Host and repository path of a git remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RemoteUrl {
+    /// Lowercased hostname, without user or port
+    host: String,
+    /// Repository path without leading/trailing slashes or `.git`
+    path: String,
+}
+
+/// Parse any common git remote URL syntax
+///
+/// Supports scheme URLs (`https://`, `http://`, `ssh://`, `git+ssh://`,
+/// `git://`, with optional user and port) and scp-like `[user@]host:path`.
+fn parse_remote_url(url: &str) -> Option<RemoteUrl> {
+    let url = url.trim();
+
+    let (host, path) = if url.contains("://") {
+        let parsed = url::Url::parse(url).ok()?;
+        (parsed.host_str()?.to_string(), parsed.path().to_string())
+    } else {
+        // scp-like: the colon must come before any slash, else it's a path
+        let (authority, path) = url.split_once(':')?;
+        if authority.contains('/') {
+            return None;
+        }
+        let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+        (host.to_string(), path.to_string())
+    };
+
+    let path = path.trim_matches('/');
+    let path = path
+        .strip_suffix(".git")
+        .unwrap_or(path)
+        .trim_end_matches('/');
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(RemoteUrl {
+        host: host.trim_matches(['[', ']']).to_ascii_lowercase(),
+        path: path.to_string(),
+    })
+}
+
+/// Environment variable mapping host aliases to real hosts
+///
+/// Format: `alias=host[,alias=host...]`, e.g. `gh-work=github.com`.
+pub const HOST_ALIASES_ENV: &str = "RYU_HOST_ALIASES";
+
+/// Environment variable mapping hosts to platforms
+///
+/// Format: `host=platform[,host=platform...]`, e.g.
+/// `git.example.com=gitea,code.corp=gitlab`. Needed for self-hosted Gitea and
+/// Forgejo, and for Azure DevOps Server (`azure-devops`), whose hostnames
+/// give nothing away.
+pub const PLATFORM_HOSTS_ENV: &str = "RYU_PLATFORM_HOSTS";
+
+/// Detect platform (GitHub, GitLab, Bitbucket, Gitea or Azure DevOps) from a
+/// remote URL
+///
+/// Host aliases (see [`resolve_host_alias`]) are resolved first.
+pub fn detect_platform(url: &str) -> Option<Platform> {
+    let hostname = resolve_host_alias(&parse_remote_url(url)?.host);
+    platform_for_host(&hostname)
+}
+
+/// Resolve a host alias to the real host
+///
+/// Known platform hosts are returned unchanged. Otherwise the alias map in
+/// `RYU_HOST_ALIASES` is consulted, then `HostName` entries in
+/// `~/.ssh/config` (e.g. `Host gh-work` / `HostName github.com`).
+pub fn resolve_host_alias(host: &str) -> String {
+    if platform_for_host(host).is_some() {
+        return host.to_string();
+    }
+
+    if let Ok(aliases) = env::var(HOST_ALIASES_ENV) {
+        let mapped = aliases
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(alias, _)| alias.trim() == host)
+            .map(|(_, real)| real.trim().to_string());
+        if let Some(real) = mapped {
+            return real;
+        }
+    }
+
+    dirs::home_dir()
+        .and_then(|home| std::fs::read_to_string(home.join(".ssh").join("config")).ok())
+        .and_then(|config| ssh_config_hostname(&config, host))
+        .unwrap_or_else(|| host.to_string())
+}
+
+/// Find the `HostName` for an exact `Host` alias in ssh config text
+///
+/// Wildcard patterns are ignored; the first matching value wins, as in ssh.
+pub fn ssh_config_hostname(config: &str, alias: &str) -> Option<String> {
+    let mut in_matching_host = false;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(|c: char| c.is_whitespace() || c == '=') else {
+            continue;
+        };
+        let value = value
+            .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+            .trim();
+
+        match key.to_ascii_lowercase().as_str() {
+            "host" => in_matching_host = value.split_whitespace().any(|p| p == alias),
+            "match" => in_matching_host = false,
+            "hostname" if in_matching_host => return Some(value.replace("%h", alias)),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A git `url.<base>.insteadOf` or `url.<base>.pushInsteadOf` rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlRewrite {
+    /// Prefix substituted in (`<base>`)
+    pub base: String,
+    /// Prefix it replaces
+    pub instead_of: String,
+    /// Whether this is a `pushInsteadOf` rule
+    pub push: bool,
+}
+
+/// Parse URL rewrite rules from `git config --get-regexp` output
+///
+/// Each line is a lowercased key and its value, e.g.
+/// `url.git@github.com:.insteadof https://github.com/`; the `<base>`
+/// subsection keeps its case. Other keys are ignored.
+pub fn parse_url_rewrites(config: &str) -> Vec<UrlRewrite> {
+    config
+        .lines()
+        .filter_map(|line| {
+            let (key, instead_of) = line.trim().split_once(' ')?;
+            let (base, variable) = key.strip_prefix("url.")?.rsplit_once('.')?;
+            let push = match variable.to_ascii_lowercase().as_str() {
+                "insteadof" => false,
+                "pushinsteadof" => true,
+                _ => return None,
+            };
+            let instead_of = instead_of.trim();
+            (!instead_of.is_empty()).then(|| UrlRewrite {
+                base: base.to_string(),
+                instead_of: instead_of.to_string(),
+                push,
+            })
+        })
+        .collect()
+}
+
+/// Apply git URL rewrite rules to a remote URL
+///
+/// As in git, the rule with the longest matching prefix wins. For a push URL
+/// (`push`), `pushInsteadOf` rules are tried before `insteadOf` ones;
+/// otherwise only `insteadOf` rules apply.
+pub fn rewrite_remote_url(url: &str, rewrites: &[UrlRewrite], push: bool) -> String {
+    let longest_match = |push_rules: bool| {
+        rewrites
+            .iter()
+            .filter(|rule| rule.push == push_rules && url.starts_with(&rule.instead_of))
+            .max_by_key(|rule| rule.instead_of.len())
+    };
+    let rule = if push {
+        longest_match(true).or_else(|| longest_match(false))
+    } else {
+        longest_match(false)
+    };
+    rule.map_or_else(
+        || url.to_string(),
+        |rule| format!("{}{}", rule.base, &url[rule.instead_of.len()..]),
+    )
+}
+
+/// Platform named in a `RYU_PLATFORM_HOSTS` entry
+fn platform_from_name(name: &str) -> Option<Platform> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "github" => Some(Platform::GitHub),
+        "gitlab" => Some(Platform::GitLab),
+        "bitbucket" => Some(Platform::Bitbucket),
+        "gitea" | "forgejo" => Some(Platform::Gitea),
+        "azure-devops" | "azure" => Some(Platform::AzureDevOps),
+        _ => None,
+    }
+}
+
+/// Platform explicitly mapped to `hostname` in a `RYU_PLATFORM_HOSTS` value
+fn mapped_platform(mapping: &str, hostname: &str) -> Option<Platform> {
+    mapping
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(host, _)| host.trim().eq_ignore_ascii_case(hostname))
+        .and_then(|(_, platform)| platform_from_name(platform))
+}
+
+/// Detect platform from a (resolved) hostname
+fn platform_for_host(hostname: &str) -> Option<Platform> {
+    if let Some(platform) = env::var(PLATFORM_HOSTS_ENV)
+        .ok()
+        .and_then(|mapping| mapped_platform(&mapping, hostname))
+    {
+        return Some(platform);
+    }
+
+    let gh_host = env::var("GH_HOST").ok();
+    let gitlab_host = env::var("GITLAB_HOST").ok();
+    let gitea_host = env::var("GITEA_HOST").ok();
+
+    // Check GitHub
+    if hostname == "github.com"
+        || hostname.ends_with(".github.com")
+        || gh_host.as_ref().is_some_and(|h| hostname == *h)
+    {
+        return Some(Platform::GitHub);
+    }
+
+    // Check GitLab
+    if hostname == "gitlab.com"
+        || hostname.ends_with(".gitlab.com")
+        || gitlab_host.as_ref().is_some_and(|h| hostname == *h)
+    {
+        return Some(Platform::GitLab);
+    }
+
+    // Check Bitbucket (Cloud only; Data Center has a different API)
+    if hostname == "bitbucket.org" {
+        return Some(Platform::Bitbucket);
+    }
+
+    // Check Gitea/Forgejo (self-hosted instances need an explicit mapping)
+    if hostname == "codeberg.org" || gitea_host.as_ref().is_some_and(|h| hostname == *h) {
+        return Some(Platform::Gitea);
+    }
+
+    // Check Azure DevOps (Azure DevOps Server needs an explicit mapping)
+    if is_azure_devops_cloud(hostname) {
+        return Some(Platform::AzureDevOps);
+    }
+
+    None
+}
+
+/// Whether `hostname` is Azure DevOps Services, old `visualstudio.com`
+/// hosts included
+fn is_azure_devops_cloud(hostname: &str) -> bool {
+    hostname == "dev.azure.com"
+        || hostname == "ssh.dev.azure.com"
+        || hostname.ends_with(".visualstudio.com")
+}
+
+/// Parse an Azure DevOps repository path
+///
+/// HTTPS paths are `[organization/]project/_git/repo`, with the organization
+/// in the hostname for `*.visualstudio.com`; SSH paths are
+/// `v3/organization/project/repo`. A project's default repository can leave
+/// the project out (`organization/_git/project`). Azure DevOps Server paths
+/// keep their collection (and any virtual directory) in the owner.
+fn azure_devops_repo_info(hostname: &str, path: &str) -> Result<PlatformConfig> {
+    let invalid = || Error::Parse(format!("invalid Azure DevOps repo path: {path}"));
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+
+    let (mut owner, repo) = if let Some(rest) = parts.strip_prefix(&["v3"]) {
+        match rest {
+            [organization, project, repo] => (vec![*organization, *project], *repo),
+            _ => return Err(invalid()),
+        }
+    } else {
+        let git = parts
+            .iter()
+            .position(|p| *p == "_git")
+            .ok_or_else(invalid)?;
+        let repo = *parts.get(git + 1).ok_or_else(invalid)?;
+        let mut owner = parts[..git].to_vec();
+        if let Some(organization) = hostname.strip_suffix(".visualstudio.com") {
+            if owner
+                .first()
+                .is_some_and(|p| p.eq_ignore_ascii_case("DefaultCollection"))
+            {
+                owner.remove(0);
+            }
+            owner.insert(0, organization);
+        }
+        (owner, repo)
+    };
+    if owner.is_empty() {
+        return Err(invalid());
+    }
+    if owner.len() == 1 {
+        owner.push(repo);
+    }
+
+    Ok(PlatformConfig {
+        platform: Platform::AzureDevOps,
+        owner: owner.join("/"),
+        repo: repo.to_string(),
+        host: (!is_azure_devops_cloud(hostname)).then(|| hostname.to_string()),
+        head_owner: None,
+    })
+}
+
+/// Parse repository info (owner/repo) from a remote URL
+pub fn parse_repo_info(url: &str) -> Result<PlatformConfig> {
+    let remote = parse_remote_url(url).ok_or(Error::NoSupportedRemotes)?;
+    let hostname = resolve_host_alias(&remote.host);
+    let platform = platform_for_host(&hostname).ok_or(Error::NoSupportedRemotes)?;
+
+    // Split path into owner and repo (GitLab supports nested groups)
+    let path = remote.path.as_str();
+    if platform == Platform::AzureDevOps {
+        return azure_devops_repo_info(&hostname, path);
+    }
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.len() < 2 {
+        return Err(Error::Parse(format!("invalid repo path: {path}")));
+    }
+
+    let repo = parts.last().unwrap().to_string();
+    let owner = parts[..parts.len() - 1].join("/");
+
+    // Determine if self-hosted
+    let default_host = match platform {
+        Platform::GitHub => "github.com",
+        Platform::GitLab => "gitlab.com",
+        Platform::Bitbucket => "bitbucket.org",
+        Platform::Gitea => "codeberg.org",
+        // Parsed above
+        Platform::AzureDevOps => "dev.azure.com",
+        // Never detected from a URL; see `fake_platform_config`
+        Platform::Fake => "",
+    };
+    let host = (hostname != default_host).then_some(hostname);
+
+    Ok(PlatformConfig {
+        platform,
+        owner,
+        repo,
+        host,
+        head_owner: None,
+    })
+}
+
+/// Platform config for the simulated platform of a workspace
+///
+/// The repository is named after the workspace directory and `host` holds
+/// the workspace root, where the fake platform keeps its store.
+pub fn fake_platform_config(workspace_root: &Path) -> PlatformConfig {
+    let repo = workspace_root.file_name().map_or_else(
+        || "repo".to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    PlatformConfig {
+        platform: Platform::Fake,
+        owner: "local".to_string(),
+        repo,
+        host: Some(workspace_root.display().to_string()),
+        head_owner: None,
+    }
+}
+
+/// Platform config for a remote, unless `choice` selects a platform
+///
+/// `choice` comes from `--platform` or the `platform` config key.
+pub fn resolve_platform_config(
+    url: &str,
+    workspace_root: &Path,
+    choice: Option<PlatformChoice>,
+) -> Result<PlatformConfig> {
+    match choice {
+        Some(PlatformChoice::Fake) => Ok(fake_platform_config(workspace_root)),
+        None => parse_repo_info(url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_github_https() {
+        assert_eq!(
+            detect_platform("https://github.com/owner/repo.git"),
+            Some(Platform::GitHub)
+        );
+    }
+
+    #[test]
+    fn test_detect_github_ssh() {
+        assert_eq!(
+            detect_platform("git@github.com:owner/repo.git"),
+            Some(Platform::GitHub)
+        );
+    }
+
+    #[test]
+    fn test_detect_gitlab_https() {
+        assert_eq!(
+            detect_platform("https://gitlab.com/owner/repo.git"),
+            Some(Platform::GitLab)
+        );
+    }
+
+    #[test]
+    fn test_known_hosts_not_resolved() {
+        assert_eq!(resolve_host_alias("github.com"), "github.com");
+    }
+
+    #[test]
+    fn test_parse_github_repo() {
+        let config = parse_repo_info("https://github.com/owner/repo.git").unwrap();
+        assert_eq!(config.platform, Platform::GitHub);
+        assert_eq!(config.owner, "owner");
+        assert_eq!(config.repo, "repo");
+        assert!(config.host.is_none());
+    }
+
+    #[test]
+    fn test_parse_gitlab_nested_groups() {
+        let config = parse_repo_info("https://gitlab.com/group/subgroup/repo.git").unwrap();
+        assert_eq!(config.platform, Platform::GitLab);
+        assert_eq!(config.owner, "group/subgroup");
+        assert_eq!(config.repo, "repo");
+    }
+
+    #[test]
+    fn test_parse_bitbucket_repo() {
+        for url in [
+            "https://alice@bitbucket.org/workspace/repo.git",
+            "git@bitbucket.org:workspace/repo.git",
+        ] {
+            let config = parse_repo_info(url).unwrap();
+            assert_eq!(config.platform, Platform::Bitbucket);
+            assert_eq!(config.owner, "workspace");
+            assert_eq!(config.repo, "repo");
+            assert!(config.host.is_none());
+        }
+    }
+
+    #[test]
+    fn test_parse_codeberg_repo() {
+        let config = parse_repo_info("git@codeberg.org:owner/repo.git").unwrap();
+        assert_eq!(config.platform, Platform::Gitea);
+        assert_eq!(config.owner, "owner");
+        assert_eq!(config.repo, "repo");
+        assert!(config.host.is_none());
+    }
+
+    #[test]
+    fn test_parse_azure_devops_repo() {
+        for url in [
+            "https://myorg@dev.azure.com/myorg/My%20Project/_git/repo",
+            "git@ssh.dev.azure.com:v3/myorg/My%20Project/repo",
+            "https://myorg.visualstudio.com/My%20Project/_git/repo",
+            "https://myorg.visualstudio.com/DefaultCollection/My%20Project/_git/repo",
+            "myorg@vs-ssh.visualstudio.com:v3/myorg/My%20Project/repo",
+        ] {
+            let config = parse_repo_info(url).unwrap();
+            assert_eq!(config.platform, Platform::AzureDevOps, "{url}");
+            assert_eq!(config.owner, "myorg/My%20Project", "{url}");
+            assert_eq!(config.repo, "repo", "{url}");
+            assert!(config.host.is_none(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_parse_azure_devops_default_repo() {
+        let config = parse_repo_info("https://dev.azure.com/myorg/_git/proj").unwrap();
+        assert_eq!(config.owner, "myorg/proj");
+        assert_eq!(config.repo, "proj");
+        assert_eq!(
+            config.web_url(),
+            "https://dev.azure.com/myorg/proj/_git/proj"
+        );
+    }
+
+    #[test]
+    fn test_parse_azure_devops_server_repo() {
+        let config =
+            azure_devops_repo_info("tfs.corp", "tfs/DefaultCollection/proj/_git/repo").unwrap();
+        assert_eq!(config.owner, "tfs/DefaultCollection/proj");
+        assert_eq!(config.repo, "repo");
+        assert_eq!(config.host.as_deref(), Some("tfs.corp"));
+        assert!(azure_devops_repo_info("tfs.corp", "proj/repo").is_err());
+    }
+
+    #[test]
+    fn test_mapped_platform() {
+        let mapping = "git.example.com=gitea, code.corp = Forgejo,gl.corp=gitlab,x=svn";
+        assert_eq!(
+            mapped_platform(mapping, "git.example.com"),
+            Some(Platform::Gitea)
+        );
+        assert_eq!(mapped_platform(mapping, "code.corp"), Some(Platform::Gitea));
+        assert_eq!(mapped_platform(mapping, "gl.corp"), Some(Platform::GitLab));
+        assert_eq!(
+            mapped_platform("tfs.corp=azure-devops", "tfs.corp"),
+            Some(Platform::AzureDevOps)
+        );
+        assert_eq!(mapped_platform(mapping, "x"), None);
+        assert_eq!(mapped_platform(mapping, "other.example.com"), None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Platform service factory
+//!
+//! Creates platform services based on configuration.
+
+#[cfg(feature = "azure-devops")]
+use crate::auth::get_azure_devops_auth;
+#[cfg(feature = "bitbucket")]
+use crate::auth::get_bitbucket_auth;
+#[cfg(feature = "gitea")]
+use crate::auth::get_gitea_auth;
+#[cfg(feature = "github")]
+use crate::auth::get_github_auth;
+#[cfg(feature = "gitlab")]
+use crate::auth::get_gitlab_auth;
+use crate::error::{Error, Result};
+#[cfg(feature = "azure-devops")]
+use crate::platform::AzureDevOpsService;
+#[cfg(feature = "bitbucket")]
+use crate::platform::BitbucketService;
+#[cfg(feature = "fake")]
+use crate::platform::FakeService;
+#[cfg(feature = "github")]
+use crate::platform::GitHubService;
+#[cfg(feature = "gitlab")]
+use crate::platform::GitLabService;
+#[cfg(feature = "gitea")]
+use crate::platform::GiteaService;
+use crate::platform::{MeteredPlatform, PlatformService, ReadOnlyPlatform};
+use crate::read_only::is_read_only;
+use crate::run::RunContext;
+use crate::types::{Platform, PlatformConfig};
+use std::sync::Arc;
+
+/// Environment variable with a GitHub token used only for stack comments
+pub const GITHUB_COMMENT_TOKEN_ENV: &str = "RYU_GITHUB_COMMENT_TOKEN";
+/// Environment variable with a GitLab token used only for stack comments
+pub const GITLAB_COMMENT_TOKEN_ENV: &str = "RYU_GITLAB_COMMENT_TOKEN";
+/// Environment variable with Bitbucket credentials (`username:app_password`)
+/// used only for stack comments
+pub const BITBUCKET_COMMENT_TOKEN_ENV: &str = "RYU_BITBUCKET_COMMENT_TOKEN";
+/// Environment variable with a Gitea/Forgejo token used only for stack comments
+pub const GITEA_COMMENT_TOKEN_ENV: &str = "RYU_GITEA_COMMENT_TOKEN";
+/// Environment variable with an Azure DevOps personal access token used only
+/// for stack comments
+pub const AZURE_DEVOPS_COMMENT_TOKEN_ENV: &str = "RYU_AZURE_DEVOPS_COMMENT_TOKEN";
+
+/// Environment variable holding the comment (bot) token for `platform`, if
+/// it takes one
+pub const fn comment_token_env(platform: Platform) -> Option<&'static str> {
+    match platform {
+        Platform::GitHub => Some(GITHUB_COMMENT_TOKEN_ENV),
+        Platform::GitLab => Some(GITLAB_COMMENT_TOKEN_ENV),
+        Platform::Bitbucket => Some(BITBUCKET_COMMENT_TOKEN_ENV),
+        Platform::Gitea => Some(GITEA_COMMENT_TOKEN_ENV),
+        Platform::AzureDevOps => Some(AZURE_DEVOPS_COMMENT_TOKEN_ENV),
+        Platform::Fake => None,
+    }
+}
+
+/// Whether this build includes the service for `platform`
+pub const fn is_platform_compiled(platform: Platform) -> bool {
+    match platform {
+        Platform::GitHub => cfg!(feature = "github"),
+        Platform::GitLab => cfg!(feature = "gitlab"),
+        Platform::Bitbucket => cfg!(feature = "bitbucket"),
+        Platform::Gitea => cfg!(feature = "gitea"),
+        Platform::AzureDevOps => cfg!(feature = "azure-devops"),
+        Platform::Fake => cfg!(feature = "fake"),
+    }
+}
+
+/// Create a platform service from configuration
+///
+/// Handles authentication and client construction for every platform.
+/// Returns [`Error::PlatformNotCompiled`] if the platform's cargo feature was
+/// disabled at build time. The service is wrapped in a [`MeteredPlatform`],
+/// and in read-only mode in a [`ReadOnlyPlatform`] reporting to `run` too.
+pub async fn create_platform_service(
+    config: &PlatformConfig,
+    run: &Arc<RunContext>,
+) -> Result<Box<dyn PlatformService>> {
+    Ok(wrap_service(create_service(config, run).await?, run))
+}
+
+fn wrap_service(
+    service: Box<dyn PlatformService>,
+    run: &Arc<RunContext>,
+) -> Box<dyn PlatformService> {
+    let metered = Box::new(MeteredPlatform::new(service, Arc::clone(run)));
+    if is_read_only() {
+        Box::new(ReadOnlyPlatform::new(metered, Arc::clone(run)))
+    } else {
+        metered
+    }
+}
+
+#[cfg_attr(
+    not(any(feature = "github", feature = "gitlab")),
+    allow(clippy::unused_async)
+)]
+#[cfg_attr(
+    not(any(
+        feature = "github",
+        feature = "gitlab",
+        feature = "bitbucket",
+        feature = "gitea",
+        feature = "azure-devops"
+    )),
+    allow(unused_variables)
+)]
+async fn create_service(
+    config: &PlatformConfig,
+    run: &Arc<RunContext>,
+) -> Result<Box<dyn PlatformService>> {
+    match config.platform {
+        #[cfg(feature = "github")]
+        Platform::GitHub => {
+            let auth = get_github_auth().await?;
+            Ok(Box::new(
+                GitHubService::new(
+                    &auth.token,
+                    config.owner.clone(),
+                    config.repo.clone(),
+                    config.host.clone(),
+                )?
+                .with_head_owner(config.head_owner.clone())
+                .with_run(Arc::clone(run)),
+            ))
+        }
+        #[cfg(feature = "gitlab")]
+        Platform::GitLab => {
+            let auth = get_gitlab_auth(config.host.as_deref()).await?;
+            Ok(Box::new(
+                GitLabService::new(
+                    auth.token.clone(),
+                    config.owner.clone(),
+                    config.repo.clone(),
+                    Some(auth.host),
+                )?
+                .with_run(Arc::clone(run)),
+            ))
+        }
+        #[cfg(feature = "bitbucket")]
+        Platform::Bitbucket => {
+            let auth = get_bitbucket_auth()?;
+            Ok(Box::new(
+                BitbucketService::new(
+                    auth.username,
+                    auth.app_password,
+                    config.owner.clone(),
+                    config.repo.clone(),
+                )?
+                .with_run(Arc::clone(run)),
+            ))
+        }
+        #[cfg(feature = "gitea")]
+        Platform::Gitea => {
+            let auth = get_gitea_auth(config.host.as_deref())?;
+            Ok(Box::new(
+                GiteaService::new(
+                    auth.token,
+                    config.owner.clone(),
+                    config.repo.clone(),
+                    config.host.clone(),
+                )?
+                .with_run(Arc::clone(run)),
+            ))
+        }
+        #[cfg(feature = "azure-devops")]
+        Platform::AzureDevOps => {
+            let auth = get_azure_devops_auth()?;
+            Ok(Box::new(
+                AzureDevOpsService::new(
+                    auth.token,
+                    config.owner.clone(),
+                    config.repo.clone(),
+                    config.host.clone(),
+                )?
+                .with_run(Arc::clone(run)),
+            ))
+        }
+        #[cfg(feature = "fake")]
+        Platform::Fake => Ok(Box::new(FakeService::new(config.clone())?)),
+        #[allow(unreachable_patterns)]
+        platform => Err(Error::PlatformNotCompiled(platform)),
+    }
+}
+
+/// Create the platform service used to write stack comments, if configured
+///
+/// When the platform's comment token (e.g. `RYU_GITHUB_COMMENT_TOKEN`) is set,
+/// stack comments are written as that account - typically a bot - so they
+/// don't notify or @-mention as the submitting human. Returns `None` when no
+/// comment token is set; the main service then writes comments too.
+pub fn create_comment_platform_service(
+    config: &PlatformConfig,
+    run: &Arc<RunContext>,
+) -> Result<Option<Box<dyn PlatformService>>> {
+    Ok(create_comment_service(config, run)?.map(|service| wrap_service(service, run)))
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "github",
+        feature = "gitlab",
+        feature = "bitbucket",
+        feature = "gitea",
+        feature = "azure-devops"
+    )),
+    allow(unused_variables)
+)]
+fn create_comment_service(
+    config: &PlatformConfig,
+    run: &Arc<RunContext>,
+) -> Result<Option<Box<dyn PlatformService>>> {
+    let Some(Ok(token)) = comment_token_env(config.platform).map(std::env::var) else {
+        return Ok(None);
+    };
+    match config.platform {
+        #[cfg(feature = "github")]
+        Platform::GitHub => Ok(Some(Box::new(
+            GitHubService::new(
+                &token,
+                config.owner.clone(),
+                config.repo.clone(),
+                config.host.clone(),
+            )?
+            .with_head_owner(config.head_owner.clone())
+            .with_run(Arc::clone(run)),
+        ))),
+        #[cfg(feature = "gitlab")]
+        Platform::GitLab => Ok(Some(Box::new(
+            GitLabService::new(
+                token,
+                config.owner.clone(),
+                config.repo.clone(),
+                config
+                    .host
+                    .clone()
+                    .or_else(|| std::env::var("GITLAB_HOST").ok()),
+            )?
+            .with_run(Arc::clone(run)),
+        ))),
+        #[cfg(feature = "bitbucket")]
+        Platform::Bitbucket => {
+            let (username, app_password) = token.split_once(':').ok_or_else(|| {
+                Error::Config(format!(
+                    "{BITBUCKET_COMMENT_TOKEN_ENV} must be username:app_password"
+                ))
+            })?;
+            Ok(Some(Box::new(
+                BitbucketService::new(
+                    username.to_string(),
+                    app_password.to_string(),
+                    config.owner.clone(),
+                    config.repo.clone(),
+                )?
+                .with_run(Arc::clone(run)),
+            )))
+        }
+        #[cfg(feature = "gitea")]
+        Platform::Gitea => Ok(Some(Box::new(
+            GiteaService::new(
+                token,
+                config.owner.clone(),
+                config.repo.clone(),
+                config.host.clone(),
+            )?
+            .with_run(Arc::clone(run)),
+        ))),
+        #[cfg(feature = "azure-devops")]
+        Platform::AzureDevOps => Ok(Some(Box::new(
+            AzureDevOpsService::new(
+                token,
+                config.owner.clone(),
+                config.repo.clone(),
+                config.host.clone(),
+            )?
+            .with_run(Arc::clone(run)),
+        ))),
+        #[allow(unreachable_patterns)]
+        platform => {
+            let _ = token;
+            Err(Error::PlatformNotCompiled(platform))
+        }
+    }
+}
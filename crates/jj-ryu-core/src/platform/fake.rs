@@ -0,0 +1,497 @@
+//! Simulated platform for demos and offline trials
+//!
+//! Selected with `platform = "fake"` in the config files (or `ryu --platform
+//! fake`) instead of being detected from a remote. PRs, comments and issues
+//! are kept in `fake-platform.json` in the workspace's state directory, so
+//! the whole submit/sync flow can be tried on a scratch repository without
+//! tokens or network. Pushes still go to the git remote; use a local bare
+//! repository as `origin`.
+//!
+//! Nothing reviews or tests a fake PR: set `"approved": true` or `"checks"`
+//! on a PR in the file to see how `land` and `merge` react. Merging only
+//! marks the PR merged; trunk doesn't move. Set `"merge_commit"` on a merged
+//! PR to the commit it landed as.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::state::RyuState;
+use crate::types::{
+    ChecksState, Issue, MergeMethod, PlatformConfig, PrComment, PrMetadata, PrReadiness, PrReview,
+    PrState, PullRequest, ReviewState,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Store file name, in the workspace's state directory
+const STORE_FILE: &str = "fake-platform.json";
+
+/// A PR as the fake platform stores it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FakePr {
+    number: u64,
+    head: String,
+    base: String,
+    title: String,
+    #[serde(default)]
+    body: String,
+    state: PrState,
+    author: String,
+    #[serde(default)]
+    reviewers: Vec<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    assignees: Vec<String>,
+    #[serde(default)]
+    approved: bool,
+    #[serde(default = "no_checks")]
+    checks: ChecksState,
+    #[serde(default)]
+    comments: Vec<PrComment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    merge_commit: Option<String>,
+}
+
+/// Reviewer named for approvals of PRs nobody was asked to review
+const FAKE_REVIEWER: &str = "fake-reviewer";
+
+const fn no_checks() -> ChecksState {
+    ChecksState::None
+}
+
+/// An issue as the fake platform stores it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FakeIssue {
+    number: u64,
+    title: String,
+    body: String,
+}
+
+/// Everything the fake platform knows
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FakeStore {
+    /// Last PR or issue number handed out (they share a sequence)
+    #[serde(default)]
+    last_number: u64,
+    /// Last comment ID handed out
+    #[serde(default)]
+    last_comment_id: u64,
+    #[serde(default)]
+    prs: Vec<FakePr>,
+    #[serde(default)]
+    issues: Vec<FakeIssue>,
+}
+
+/// Platform service backed by a JSON file in the workspace
+pub struct FakeService {
+    path: PathBuf,
+    user: String,
+    config: PlatformConfig,
+    /// Serializes read-modify-write cycles on the store
+    lock: Mutex<()>,
+}
+
+impl FakeService {
+    /// Create the fake platform for a config from [`fake_platform_config`]
+    ///
+    /// [`fake_platform_config`]: crate::platform::fake_platform_config
+    pub fn new(config: PlatformConfig) -> Result<Self> {
+        let root = config.host.as_deref().ok_or_else(|| {
+            Error::Internal("fake platform config lacks the workspace root".to_string())
+        })?;
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "you".to_string());
+        Ok(Self {
+            path: RyuState::dir(Path::new(root)).join(STORE_FILE),
+            user,
+            config,
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn load(&self) -> Result<FakeStore> {
+        if !self.path.exists() {
+            return Ok(FakeStore::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(&self.path)?)?)
+    }
+
+    fn save(&self, store: &FakeStore) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(store)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Read the store, apply `change` and write it back
+    fn update<T>(&self, change: impl FnOnce(&mut FakeStore) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut store = self.load()?;
+        let value = change(&mut store)?;
+        self.save(&store)?;
+        Ok(value)
+    }
+
+    /// Read the store and apply `read`
+    fn read<T>(&self, read: impl FnOnce(&FakeStore) -> T) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(read(&self.load()?))
+    }
+
+    fn to_pull_request(&self, pr: &FakePr) -> PullRequest {
+        PullRequest {
+            number: pr.number,
+            html_url: format!("{}/pull/{}", self.config.web_url(), pr.number),
+            base_ref: pr.base.clone(),
+            head_ref: pr.head.clone(),
+            title: pr.title.clone(),
+            node_id: None,
+            is_draft: pr.state == PrState::Draft,
+            updated_at: None,
+        }
+    }
+}
+
+/// Whether a stored PR is still open
+fn is_open(pr: &FakePr) -> bool {
+    matches!(pr.state, PrState::Open | PrState::Draft)
+}
+
+/// The PR numbered `number`, for changing
+fn pr_mut(store: &mut FakeStore, number: u64) -> Result<&mut FakePr> {
+    store
+        .prs
+        .iter_mut()
+        .find(|pr| pr.number == number)
+        .ok_or_else(|| Error::Platform(format!("PR #{number} not found")))
+}
+
+#[async_trait]
+impl PlatformService for FakeService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .rev()
+                .find(|pr| pr.head == head_branch && is_open(pr))
+                .map(|pr| self.to_pull_request(pr))
+        })
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .filter(|pr| pr.head == head_branch && is_open(pr))
+                .map(|pr| self.to_pull_request(pr))
+                .collect()
+        })
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .rev()
+                .find(|pr| pr.head == head_branch)
+                .map(|pr| (self.to_pull_request(pr), pr.state))
+        })
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .filter(|pr| pr.author == author && is_open(pr))
+                .map(|pr| self.to_pull_request(pr))
+                .collect()
+        })
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        Ok(self.user.clone())
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating fake PR");
+        self.update(|store| {
+            store.last_number += 1;
+            let pr = FakePr {
+                number: store.last_number,
+                head: head.to_string(),
+                base: base.to_string(),
+                title: title.to_string(),
+                body: body.to_string(),
+                state: if draft { PrState::Draft } else { PrState::Open },
+                author: self.user.clone(),
+                reviewers: Vec::new(),
+                labels: Vec::new(),
+                assignees: Vec::new(),
+                approved: false,
+                checks: ChecksState::None,
+                comments: Vec::new(),
+                merge_commit: None,
+            };
+            let created = self.to_pull_request(&pr);
+            store.prs.push(pr);
+            Ok(created)
+        })
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        self.update(|store| {
+            let pr = pr_mut(store, pr_number)?;
+            pr.base = new_base.to_string();
+            Ok(self.to_pull_request(pr))
+        })
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .find(|pr| pr.number == pr_number)
+                .map(|pr| pr.body.clone())
+        })?
+        .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .find(|pr| pr.number == pr_number)
+                .map(|pr| {
+                    pr.merge_commit
+                        .clone()
+                        .filter(|_| pr.state == PrState::Merged)
+                })
+        })?
+        .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.update(|store| {
+            pr_mut(store, pr_number)?.body = body.to_string();
+            Ok(())
+        })
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        self.update(|store| {
+            let pr = pr_mut(store, pr_number)?;
+            if pr.state == PrState::Draft {
+                pr.state = PrState::Open;
+            }
+            Ok(self.to_pull_request(pr))
+        })
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.update(|store| {
+            pr_mut(store, pr_number)?.state = PrState::Closed;
+            Ok(())
+        })
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(pr_number, %method, "merging fake PR");
+        self.update(|store| {
+            let pr = pr_mut(store, pr_number)?;
+            if !is_open(pr) {
+                return Err(Error::Platform(format!(
+                    "PR #{pr_number} is {} and can't be merged",
+                    pr.state
+                )));
+            }
+            pr.state = PrState::Merged;
+            Ok(())
+        })
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .find(|pr| pr.number == pr_number)
+                .map(|pr| PrReadiness {
+                    approved: pr.approved,
+                    checks: pr.checks,
+                })
+        })?
+        .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        let pr = self
+            .read(|store| store.prs.iter().find(|pr| pr.number == pr_number).cloned())?
+            .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))?;
+        if !pr.approved {
+            return Ok(Vec::new());
+        }
+        // Approval is one flag here; credit it to the requested reviewers
+        let reviewers = if pr.reviewers.is_empty() {
+            vec![FAKE_REVIEWER.to_string()]
+        } else {
+            pr.reviewers
+        };
+        Ok(reviewers
+            .into_iter()
+            .map(|reviewer| PrReview {
+                reviewer,
+                state: ReviewState::Approved,
+            })
+            .collect())
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        debug!(
+            pr_number = pr.number,
+            project, "fake platform has no projects"
+        );
+        Ok(())
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        self.update(|store| {
+            let pr = pr_mut(store, pr_number)?;
+            for reviewer in reviewers {
+                if !pr.reviewers.contains(reviewer) {
+                    pr.reviewers.push(reviewer.clone());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        self.update(|store| {
+            let pr = pr_mut(store, pr_number)?;
+            for (have, add) in [
+                (&mut pr.reviewers, &metadata.reviewers),
+                (&mut pr.labels, &metadata.labels),
+                (&mut pr.assignees, &metadata.assignees),
+            ] {
+                for value in add {
+                    if !have.contains(value) {
+                        have.push(value.clone());
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .filter(|pr| is_open(pr) && pr.reviewers.iter().any(|r| r == reviewer))
+                .count()
+        })
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        self.update(|store| {
+            store.last_number += 1;
+            let number = store.last_number;
+            store.issues.push(FakeIssue {
+                number,
+                title: title.to_string(),
+                body: body.to_string(),
+            });
+            Ok(Issue {
+                number,
+                html_url: format!("{}/issues/{number}", self.config.web_url()),
+            })
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        self.read(|store| {
+            store
+                .issues
+                .iter()
+                .find(|issue| issue.number == issue_number)
+                .map(|issue| issue.body.clone())
+        })?
+        .ok_or_else(|| Error::Platform(format!("issue #{issue_number} not found")))
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        self.update(|store| {
+            let issue = store
+                .issues
+                .iter_mut()
+                .find(|issue| issue.number == issue_number)
+                .ok_or_else(|| Error::Platform(format!("issue #{issue_number} not found")))?;
+            issue.body = body.to_string();
+            Ok(())
+        })
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        self.read(|store| {
+            store
+                .prs
+                .iter()
+                .find(|pr| pr.number == pr_number)
+                .map(|pr| pr.comments.clone())
+        })?
+        .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.update(|store| {
+            store.last_comment_id += 1;
+            let id = store.last_comment_id;
+            pr_mut(store, pr_number)?.comments.push(PrComment {
+                id,
+                body: body.to_string(),
+                node_id: None,
+            });
+            Ok(())
+        })
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        self.update(|store| {
+            let comment = pr_mut(store, pr_number)?
+                .comments
+                .iter_mut()
+                .find(|comment| comment.id == comment_id)
+                .ok_or_else(|| {
+                    Error::Platform(format!("comment {comment_id} not found on #{pr_number}"))
+                })?;
+            comment.body = body.to_string();
+            Ok(())
+        })
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
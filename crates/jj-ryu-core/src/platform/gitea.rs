@@ -0,0 +1,650 @@
+//! Gitea/Forgejo platform service implementation
+//!
+//! Forgejo (and so Codeberg) keeps Gitea's v1 REST API, so one client serves
+//! all three.
+
+use crate::auth::DEFAULT_GITEA_HOST;
+use crate::error::{Error, Result};
+use crate::metrics::MeteredJson;
+use crate::platform::{DEFAULT_TIMEOUT_SECS, PlatformService};
+use crate::run::RunContext;
+use crate::types::{
+    ChecksState, Issue, MergeMethod, Platform, PlatformConfig, PrComment, PrMetadata, PrReadiness,
+    PrReview, PrState, PullRequest, ReviewState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Page size for paginated list requests (Gitea's default maximum is 50)
+const PAGE_LIMIT: usize = 50;
+
+/// Title prefix Gitea uses to mark a PR as work in progress
+const WIP_PREFIX: &str = "WIP: ";
+
+/// Gitea/Forgejo service using the v1 REST API
+pub struct GiteaService {
+    client: Client,
+    token: String,
+    api_root: String,
+    config: PlatformConfig,
+    run: Arc<RunContext>,
+}
+
+#[derive(Deserialize)]
+struct BranchRef {
+    #[serde(rename = "ref")]
+    ref_field: String,
+    #[serde(default)]
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct Review {
+    state: String,
+    #[serde(default)]
+    dismissed: bool,
+    #[serde(default)]
+    stale: bool,
+    #[serde(default)]
+    user: Option<User>,
+}
+
+/// Each reviewer's latest approval or change request, oldest reviewer first
+///
+/// `reviews` come oldest first, as Gitea lists them. Dismissed reviews and
+/// reviews of an older head no longer count.
+fn latest_verdicts(reviews: &[Review]) -> Vec<PrReview> {
+    let mut verdicts: Vec<PrReview> = Vec::new();
+    for review in reviews.iter().filter(|r| !r.dismissed && !r.stale) {
+        let state = match review.state.as_str() {
+            "APPROVED" => ReviewState::Approved,
+            "REQUEST_CHANGES" => ReviewState::ChangesRequested,
+            _ => continue,
+        };
+        let Some(user) = &review.user else {
+            continue;
+        };
+        match verdicts.iter_mut().find(|v| v.reviewer == user.login) {
+            Some(verdict) => verdict.state = state,
+            None => verdicts.push(PrReview {
+                reviewer: user.login.clone(),
+                state,
+            }),
+        }
+    }
+    verdicts
+}
+
+#[derive(Deserialize)]
+struct CombinedStatus {
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    total_count: u64,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaPr {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    merge_commit_sha: Option<String>,
+    /// Only reported by recent releases; older ones rely on the title prefix
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+    html_url: String,
+    head: BranchRef,
+    base: BranchRef,
+    user: Option<User>,
+    #[serde(default)]
+    requested_reviewers: Option<Vec<User>>,
+}
+
+impl GiteaPr {
+    fn is_draft(&self) -> bool {
+        self.draft || is_wip_title(&self.title)
+    }
+
+    fn state(&self) -> PrState {
+        if self.merged {
+            PrState::Merged
+        } else if self.state == "closed" {
+            PrState::Closed
+        } else if self.is_draft() {
+            PrState::Draft
+        } else {
+            PrState::Open
+        }
+    }
+}
+
+impl From<GiteaPr> for PullRequest {
+    fn from(pr: GiteaPr) -> Self {
+        let is_draft = pr.is_draft();
+        Self {
+            number: pr.number,
+            html_url: pr.html_url,
+            base_ref: pr.base.ref_field,
+            head_ref: pr.head.ref_field,
+            title: pr.title,
+            node_id: None, // Gitea doesn't use GraphQL node IDs
+            is_draft,
+            updated_at: pr.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GiteaComment {
+    id: u64,
+    #[serde(default)]
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaIssue {
+    number: u64,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+}
+
+/// Whether a title carries one of Gitea's work-in-progress prefixes
+fn is_wip_title(title: &str) -> bool {
+    let lower = title.to_ascii_lowercase();
+    lower.starts_with("wip:") || lower.starts_with("[wip]")
+}
+
+/// Title with any work-in-progress prefix removed
+fn strip_wip(title: &str) -> &str {
+    if !is_wip_title(title) {
+        return title;
+    }
+    let rest = if title.starts_with('[') {
+        &title["[wip]".len()..]
+    } else {
+        &title["wip:".len()..]
+    };
+    rest.trim_start()
+}
+
+impl GiteaService {
+    /// Create a new Gitea service
+    ///
+    /// `host` defaults to codeberg.org. A host with a scheme (e.g.
+    /// `http://gitea.local:3000`) is used as-is; otherwise HTTPS is assumed.
+    pub fn new(token: String, owner: String, repo: String, host: Option<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| Error::GiteaApi(format!("failed to create HTTP client: {e}")))?;
+
+        let base = host.as_deref().unwrap_or(DEFAULT_GITEA_HOST);
+        let api_root = if base.contains("://") {
+            format!("{}/api/v1", base.trim_end_matches('/'))
+        } else {
+            format!("https://{base}/api/v1")
+        };
+
+        Ok(Self {
+            client,
+            token,
+            api_root,
+            config: PlatformConfig {
+                platform: Platform::Gitea,
+                owner,
+                repo,
+                host,
+                head_owner: None,
+            },
+            run: Arc::default(),
+        })
+    }
+
+    /// Count responses in `run`'s metrics
+    #[must_use]
+    pub fn with_run(mut self, run: Arc<RunContext>) -> Self {
+        self.run = run;
+        self
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!(
+            "{}/repos/{}/{}{path}",
+            self.api_root, self.config.owner, self.config.repo
+        )
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url).bearer_auth(&self.token)
+    }
+
+    fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url).bearer_auth(&self.token)
+    }
+
+    fn patch(&self, url: &str) -> RequestBuilder {
+        self.client.patch(url).bearer_auth(&self.token)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        Ok(request
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GiteaApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?)
+    }
+
+    async fn send_empty(request: RequestBuilder) -> Result<()> {
+        request
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GiteaApi(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Fetch every page of a list endpoint
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let limit = PAGE_LIMIT.to_string();
+        let mut values = Vec::new();
+        for page in 1.. {
+            let page = page.to_string();
+            let mut batch: Vec<T> = self
+                .send(
+                    self.get(url)
+                        .query(query)
+                        .query(&[("page", page.as_str()), ("limit", limit.as_str())]),
+                )
+                .await?;
+            let last = batch.len() < PAGE_LIMIT;
+            values.append(&mut batch);
+            if last {
+                break;
+            }
+        }
+        Ok(values)
+    }
+
+    async fn get_pr(&self, pr_number: u64) -> Result<GiteaPr> {
+        self.send(self.get(&self.repo_url(&format!("/pulls/{pr_number}"))))
+            .await
+    }
+
+    async fn edit_pr(&self, pr_number: u64, changes: &serde_json::Value) -> Result<GiteaPr> {
+        self.send(
+            self.patch(&self.repo_url(&format!("/pulls/{pr_number}")))
+                .json(changes),
+        )
+        .await
+    }
+
+    /// PRs in `state` (`open`, `closed` or `all`)
+    async fn list_prs(&self, state: &str) -> Result<Vec<GiteaPr>> {
+        self.get_all(&self.repo_url("/pulls"), &[("state", state)])
+            .await
+    }
+}
+
+#[async_trait]
+impl PlatformService for GiteaService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        debug!(head_branch, "finding existing PR");
+
+        // Oldest PR wins so duplicates can't flip-flop between runs
+        let result = self
+            .find_open_prs(head_branch)
+            .await?
+            .into_iter()
+            .min_by_key(|pr| pr.number);
+        if let Some(ref pr) = result {
+            debug!(pr_number = pr.number, "found existing PR");
+        } else {
+            debug!("no existing PR found");
+        }
+        Ok(result)
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        debug!(head_branch, "listing open PRs");
+        // The list endpoint can't filter by head branch
+        let result: Vec<PullRequest> = self
+            .list_prs("open")
+            .await?
+            .into_iter()
+            .filter(|pr| pr.head.ref_field == head_branch)
+            .map(Into::into)
+            .collect();
+        debug!(head_branch, count = result.len(), "listed open PRs");
+        Ok(result)
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        debug!(head_branch, "finding latest PR");
+        Ok(self
+            .list_prs("all")
+            .await?
+            .into_iter()
+            .filter(|pr| pr.head.ref_field == head_branch)
+            .max_by_key(|pr| pr.number)
+            .map(|pr| {
+                let state = pr.state();
+                (pr.into(), state)
+            }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        debug!(author, "listing open PRs by author");
+        let result: Vec<PullRequest> = self
+            .list_prs("open")
+            .await?
+            .into_iter()
+            .filter(|pr| {
+                pr.user
+                    .as_ref()
+                    .is_some_and(|u| u.login.eq_ignore_ascii_case(author))
+            })
+            .map(Into::into)
+            .collect();
+        debug!(author, count = result.len(), "listed open PRs by author");
+        Ok(result)
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        let user: User = self
+            .send(self.get(&format!("{}/user", self.api_root)))
+            .await?;
+        Ok(user.login)
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating PR");
+        let title = if draft && !is_wip_title(title) {
+            format!("{WIP_PREFIX}{title}")
+        } else {
+            title.to_string()
+        };
+        let pr: GiteaPr = self
+            .send(
+                self.post(&self.repo_url("/pulls"))
+                    .json(&serde_json::json!({
+                        "head": head,
+                        "base": base,
+                        "title": title,
+                        "body": body,
+                    })),
+            )
+            .await?;
+
+        debug!(pr_number = pr.number, "created PR");
+        Ok(pr.into())
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        debug!(pr_number, new_base, "updating PR base");
+        let pr = self
+            .edit_pr(pr_number, &serde_json::json!({ "base": new_base }))
+            .await?;
+        debug!(pr_number, "updated PR base");
+        Ok(pr.into())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        Ok(self.get_pr(pr_number).await?.body.unwrap_or_default())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        let pr = self.get_pr(pr_number).await?;
+        Ok(pr.merge_commit_sha.filter(|_| pr.merged))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "updating PR body");
+        self.edit_pr(pr_number, &serde_json::json!({ "body": body }))
+            .await?;
+        debug!(pr_number, "updated PR body");
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        debug!(pr_number, "publishing PR");
+        // Drafts are PRs whose title carries a WIP prefix
+        let pr = self.get_pr(pr_number).await?;
+        let title = strip_wip(&pr.title).to_string();
+        let updated = self
+            .edit_pr(pr_number, &serde_json::json!({ "title": title }))
+            .await?;
+        debug!(pr_number, "published PR");
+        Ok(updated.into())
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_number, "closing PR");
+        self.edit_pr(pr_number, &serde_json::json!({ "state": "closed" }))
+            .await?;
+        debug!(pr_number, "closed PR");
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(pr_number, %method, "merging PR");
+        let style = match method {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/pulls/{pr_number}/merge")))
+                .json(&serde_json::json!({ "Do": style })),
+        )
+        .await?;
+        debug!(pr_number, "merged PR");
+        Ok(())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        debug!(pr_number, "checking PR readiness");
+        let pr = self.get_pr(pr_number).await?;
+        let reviews: Vec<Review> = self
+            .get_all(&self.repo_url(&format!("/pulls/{pr_number}/reviews")), &[])
+            .await?;
+        let status: CombinedStatus = self
+            .send(self.get(&self.repo_url(&format!("/commits/{}/status", pr.head.sha))))
+            .await?;
+
+        // Reviews on an older head or dismissed ones no longer count
+        let current: Vec<&Review> = reviews
+            .iter()
+            .filter(|review| !review.dismissed && !review.stale)
+            .collect();
+        let approved = current.iter().any(|review| review.state == "APPROVED")
+            && !current
+                .iter()
+                .any(|review| review.state == "REQUEST_CHANGES");
+        let checks = match status.state.as_str() {
+            _ if status.total_count == 0 => ChecksState::None,
+            "success" => ChecksState::Passing,
+            "failure" | "error" => ChecksState::Failing,
+            _ => ChecksState::Pending,
+        };
+        debug!(pr_number, approved, %checks, "checked PR readiness");
+        Ok(PrReadiness { approved, checks })
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        debug!(pr_number, "listing PR reviews");
+        let reviews: Vec<Review> = self
+            .get_all(&self.repo_url(&format!("/pulls/{pr_number}/reviews")), &[])
+            .await?;
+        let verdicts = latest_verdicts(&reviews);
+        debug!(pr_number, count = verdicts.len(), "listed PR reviews");
+        Ok(verdicts)
+    }
+
+    async fn add_pr_to_project(&self, _pr: &PullRequest, _project: &str) -> Result<()> {
+        Err(Error::GiteaApi(
+            "Gitea's API can't add PRs to project boards".to_string(),
+        ))
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        debug!(pr_number, ?reviewers, "requesting reviews");
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/pulls/{pr_number}/requested_reviewers")))
+                .json(&serde_json::json!({ "reviewers": reviewers })),
+        )
+        .await?;
+        debug!(pr_number, "requested reviews");
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        debug!(pr_number, ?metadata, "updating PR metadata");
+        if !metadata.reviewers.is_empty() {
+            self.request_reviewers(pr_number, &metadata.reviewers)
+                .await?;
+        }
+        // Labels are added by name through the PR's issue
+        if !metadata.labels.is_empty() {
+            Self::send_empty(
+                self.post(&self.repo_url(&format!("/issues/{pr_number}/labels")))
+                    .json(&serde_json::json!({ "labels": metadata.labels })),
+            )
+            .await?;
+        }
+        if !metadata.assignees.is_empty() {
+            Self::send_empty(
+                self.patch(&self.repo_url(&format!("/pulls/{pr_number}")))
+                    .json(&serde_json::json!({ "assignees": metadata.assignees })),
+            )
+            .await?;
+        }
+        debug!(pr_number, "updated PR metadata");
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        let count = self
+            .list_prs("open")
+            .await?
+            .iter()
+            .filter(|pr| {
+                pr.requested_reviewers
+                    .iter()
+                    .flatten()
+                    .any(|r| r.login.eq_ignore_ascii_case(reviewer))
+            })
+            .count();
+        debug!(reviewer, count, "counted open review requests");
+        Ok(count)
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        debug!(title, "creating issue");
+        let issue: GiteaIssue = self
+            .send(
+                self.post(&self.repo_url("/issues"))
+                    .json(&serde_json::json!({ "title": title, "body": body })),
+            )
+            .await?;
+
+        debug!(issue_number = issue.number, "created issue");
+        Ok(Issue {
+            number: issue.number,
+            html_url: issue.html_url,
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        let issue: GiteaIssue = self
+            .send(self.get(&self.repo_url(&format!("/issues/{issue_number}"))))
+            .await?;
+        Ok(issue.body.unwrap_or_default())
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        debug!(issue_number, "updating issue body");
+        Self::send_empty(
+            self.patch(&self.repo_url(&format!("/issues/{issue_number}")))
+                .json(&serde_json::json!({ "body": body })),
+        )
+        .await?;
+        debug!(issue_number, "updated issue body");
+        Ok(())
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        debug!(pr_number, "listing PR comments");
+        // PR conversation comments live on the PR's issue
+        let comments: Vec<GiteaComment> = self
+            .send(self.get(&self.repo_url(&format!("/issues/{pr_number}/comments"))))
+            .await?;
+
+        let result: Vec<PrComment> = comments
+            .into_iter()
+            .map(|c| PrComment {
+                id: c.id,
+                body: c.body,
+                node_id: None,
+            })
+            .collect();
+        debug!(pr_number, count = result.len(), "listed PR comments");
+        Ok(result)
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "creating PR comment");
+        Self::send_empty(
+            self.post(&self.repo_url(&format!("/issues/{pr_number}/comments")))
+                .json(&serde_json::json!({ "body": body })),
+        )
+        .await?;
+        debug!(pr_number, "created PR comment");
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        debug!(pr_number, comment_id, "updating PR comment");
+        Self::send_empty(
+            self.patch(&self.repo_url(&format!("/issues/comments/{comment_id}")))
+                .json(&serde_json::json!({ "body": body })),
+        )
+        .await?;
+        debug!(pr_number, comment_id, "updated PR comment");
+        Ok(())
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
@@ -0,0 +1,1366 @@
+//! GitHub platform service implementation
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::platform::retry::{Retries, Transient};
+use crate::run::RunContext;
+use crate::types::{
+    BranchPrs, ChecksState, CommentWrite, Issue, MergeMethod, Platform, PlatformConfig, PrComment,
+    PrMetadata, PrReadiness, PrReview, PrState, PullRequest, RateLimit, ReviewState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use octocrab::{Octocrab, Page};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+// GraphQL response types for publish_pr mutation
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+    /// Field the error belongs to, starting with its alias
+    #[serde(default)]
+    path: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkReadyForReviewData {
+    mark_pull_request_ready_for_review: MarkReadyPayload,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkReadyPayload {
+    pull_request: GraphQlPullRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlPullRequest {
+    number: u64,
+    url: String,
+    base_ref_name: String,
+    head_ref_name: String,
+    title: String,
+    id: String,
+    is_draft: bool,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<GraphQlPullRequest> for PullRequest {
+    fn from(pr: GraphQlPullRequest) -> Self {
+        Self {
+            number: pr.number,
+            html_url: pr.url,
+            base_ref: pr.base_ref_name,
+            head_ref: pr.head_ref_name,
+            title: pr.title,
+            node_id: Some(pr.id),
+            is_draft: pr.is_draft,
+            updated_at: pr.updated_at,
+        }
+    }
+}
+
+// GraphQL response types for add_pr_to_project
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectOwnerData {
+    repository_owner: Option<ProjectOwner>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectOwner {
+    project_v2: Option<ProjectNode>,
+}
+
+#[derive(Deserialize)]
+struct ProjectNode {
+    id: String,
+}
+
+// GraphQL response types for get_pr_readiness
+
+#[derive(Deserialize)]
+struct ReadinessData {
+    repository: Option<ReadinessRepository>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessRepository {
+    pull_request: Option<ReadinessPullRequest>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessPullRequest {
+    review_decision: Option<String>,
+    approvals: TotalCount,
+    commits: Nodes<ReadinessCommitNode>,
+}
+
+#[derive(Deserialize)]
+struct ChecksData {
+    repository: Option<ChecksRepository>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChecksRepository {
+    pull_request: Option<ChecksPullRequest>,
+}
+
+#[derive(Deserialize)]
+struct ChecksPullRequest {
+    commits: Nodes<ReadinessCommitNode>,
+}
+
+/// Checks state from the status check rollup of a PR's last commit
+fn checks_state(commits: &Nodes<ReadinessCommitNode>) -> ChecksState {
+    let rollup = commits
+        .nodes
+        .first()
+        .and_then(|node| node.commit.status_check_rollup.as_ref());
+    match rollup.map(|r| r.state.as_str()) {
+        None => ChecksState::None,
+        Some("SUCCESS") => ChecksState::Passing,
+        Some("PENDING" | "EXPECTED") => ChecksState::Pending,
+        Some(_) => ChecksState::Failing,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalCount {
+    total_count: u64,
+}
+
+// GraphQL response types for list_reviews
+
+#[derive(Deserialize)]
+struct ReviewsData {
+    repository: Option<ReviewsRepository>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewsRepository {
+    pull_request: Option<ReviewsPullRequest>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewsPullRequest {
+    latest_opinionated_reviews: Nodes<ReviewNode>,
+}
+
+#[derive(Deserialize)]
+struct ReviewNode {
+    author: Option<Login>,
+    state: String,
+}
+
+/// Reviewers' latest approvals and change requests
+fn pr_reviews(reviews: &Nodes<ReviewNode>) -> Vec<PrReview> {
+    reviews
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            let state = match node.state.as_str() {
+                "APPROVED" => ReviewState::Approved,
+                "CHANGES_REQUESTED" => ReviewState::ChangesRequested,
+                _ => return None,
+            };
+            // Deleted accounts have no author; GitHub shows them as ghost
+            let reviewer = node
+                .author
+                .as_ref()
+                .map_or_else(|| "ghost".to_string(), |author| author.login.clone());
+            Some(PrReview { reviewer, state })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct ReadinessCommitNode {
+    commit: ReadinessCommit,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessCommit {
+    status_check_rollup: Option<StatusCheckRollup>,
+}
+
+#[derive(Deserialize)]
+struct StatusCheckRollup {
+    state: String,
+}
+
+/// Readiness from a PR's review decision, approvals and last commit
+fn pr_readiness(
+    review_decision: Option<&str>,
+    approvals: &TotalCount,
+    commits: &Nodes<ReadinessCommitNode>,
+) -> PrReadiness {
+    // Without required reviews there is no decision; any approval counts
+    let approved = match review_decision {
+        Some(decision) => decision == "APPROVED",
+        None => approvals.total_count > 0,
+    };
+    PrReadiness {
+        approved,
+        checks: checks_state(commits),
+    }
+}
+
+// GraphQL response types for find_branch_prs
+
+#[derive(Deserialize)]
+struct BranchPrsData {
+    /// One aliased connection per branch (`b0`, `b1`, ...)
+    repository: Option<std::collections::HashMap<String, Nodes<BranchPrNode>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BranchPrNode {
+    #[serde(flatten)]
+    pr: GraphQlPullRequest,
+    head_repository_owner: Option<Login>,
+    review_decision: Option<String>,
+    approvals: TotalCount,
+    commits: Nodes<ReadinessCommitNode>,
+    latest_opinionated_reviews: Nodes<ReviewNode>,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    login: String,
+}
+
+/// Branches looked up in one GraphQL query
+const BRANCHES_PER_QUERY: usize = 20;
+
+/// Reviewers fetched per PR; more than this on one PR is unheard of
+const REVIEWS_PER_PR: usize = 50;
+
+/// One query with an aliased `pullRequests` connection per branch
+fn branch_prs_query(owner: &str, repo: &str, branches: &[String]) -> serde_json::Value {
+    let mut params = vec!["$owner: String!".to_string(), "$repo: String!".to_string()];
+    let mut fields = Vec::with_capacity(branches.len());
+    let mut variables = serde_json::Map::new();
+    variables.insert("owner".to_string(), owner.into());
+    variables.insert("repo".to_string(), repo.into());
+    for (i, branch) in branches.iter().enumerate() {
+        params.push(format!("$b{i}: String!"));
+        fields.push(format!(
+            "b{i}: pullRequests(headRefName: $b{i}, states: OPEN, first: 10, \
+             orderBy: {{ field: CREATED_AT, direction: ASC }}) {{ nodes {{ ...BranchPr }} }}"
+        ));
+        variables.insert(format!("b{i}"), branch.as_str().into());
+    }
+    serde_json::json!({
+        "query": format!(
+            "query BranchPrs({}) {{ repository(owner: $owner, name: $repo) {{ {} }} }} \
+             fragment BranchPr on PullRequest {{ \
+                 number url baseRefName headRefName title id isDraft updatedAt \
+                 headRepositoryOwner {{ login }} reviewDecision \
+                 approvals: reviews(states: APPROVED) {{ totalCount }} \
+                 commits(last: 1) {{ nodes {{ commit {{ statusCheckRollup {{ state }} }} }} }} \
+                 latestOpinionatedReviews(first: {REVIEWS_PER_PR}) {{ \
+                     nodes {{ author {{ login }} state }} \
+                 }} \
+             }}",
+            params.join(", "),
+            fields.join(" ")
+        ),
+        "variables": variables,
+    })
+}
+
+// GraphQL response types for rate_limit
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitData {
+    rate_limit: Option<RateLimitNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitNode {
+    limit: u64,
+    remaining: u64,
+    reset_at: Option<DateTime<Utc>>,
+}
+
+/// Turn GraphQL errors into an error result
+fn check_graphql_errors(errors: Option<Vec<GraphQlError>>) -> Result<()> {
+    match errors {
+        Some(errors) if !errors.is_empty() => {
+            let messages: Vec<_> = errors.into_iter().map(|e| e.message).collect();
+            Err(Error::GitHubApi(format!(
+                "GraphQL error: {}",
+                messages.join(", ")
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Comment writes sent in one GraphQL request
+const COMMENT_WRITES_PER_REQUEST: usize = 20;
+
+/// A comment write that can go into a batched mutation
+#[derive(Clone, Copy)]
+enum CommentTarget<'a> {
+    /// Add a comment to the PR with this node ID
+    Create(&'a str),
+    /// Replace the body of the comment with this node ID
+    Update(&'a str),
+}
+
+impl<'a> CommentTarget<'a> {
+    /// Batch target for `write`, if the node IDs it needs are known
+    fn of(write: &'a CommentWrite) -> Option<Self> {
+        match &write.existing {
+            Some(comment) => comment.node_id.as_deref().map(Self::Update),
+            None => write.pr_node_id.as_deref().map(Self::Create),
+        }
+    }
+}
+
+/// One mutation with an aliased field per write (`w0`, `w1`, ...)
+fn comment_mutation(batch: &[(CommentTarget<'_>, &str)]) -> serde_json::Value {
+    let mut params = Vec::with_capacity(batch.len());
+    let mut fields = Vec::with_capacity(batch.len());
+    let mut variables = serde_json::Map::new();
+    for (i, (target, body)) in batch.iter().enumerate() {
+        let (field, id) = match target {
+            CommentTarget::Create(pr_id) => (
+                format!("w{i}: addComment(input: {{ subjectId: $id{i}, body: $body{i} }})"),
+                pr_id,
+            ),
+            CommentTarget::Update(comment_id) => (
+                format!("w{i}: updateIssueComment(input: {{ id: $id{i}, body: $body{i} }})"),
+                comment_id,
+            ),
+        };
+        params.push(format!("$id{i}: ID!, $body{i}: String!"));
+        fields.push(format!("{field} {{ clientMutationId }}"));
+        variables.insert(format!("id{i}"), (*id).into());
+        variables.insert(format!("body{i}"), (*body).into());
+    }
+    serde_json::json!({
+        "query": format!(
+            "mutation WriteComments({}) {{ {} }}",
+            params.join(", "),
+            fields.join(" ")
+        ),
+        "variables": variables,
+    })
+}
+
+/// Result of write `index` of a batch, from the errors of the response
+///
+/// Errors without a path failed the whole request.
+fn comment_write_result(errors: &[GraphQlError], index: usize) -> Result<()> {
+    let alias = format!("w{index}");
+    let messages: Vec<&str> = errors
+        .iter()
+        .filter(|e| e.path.is_empty() || e.path[0].as_str() == Some(alias.as_str()))
+        .map(|e| e.message.as_str())
+        .collect();
+    if messages.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::GitHubApi(format!(
+            "GraphQL error: {}",
+            messages.join(", ")
+        )))
+    }
+}
+
+/// Parse a Projects (v2) reference of the form `owner/number`
+fn parse_project_ref(project: &str) -> Result<(&str, u64)> {
+    project
+        .split_once('/')
+        .and_then(|(owner, number)| Some((owner, number.parse().ok()?)))
+        .filter(|(owner, _)| !owner.is_empty())
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "invalid GitHub project '{project}' (expected owner/number)"
+            ))
+        })
+}
+
+/// GitHub asks clients that hit a secondary rate limit to wait at least a
+/// minute when it doesn't say how long
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Whether an octocrab error is worth retrying, and how
+///
+/// Server errors and dropped connections are only retried for `idempotent`
+/// calls, since GitHub may have acted on the request before failing.
+fn transient(error: &octocrab::Error, idempotent: bool) -> Option<Transient> {
+    match error {
+        octocrab::Error::GitHub { source, .. } => {
+            let message = source.message.to_lowercase();
+            match source.status_code.as_u16() {
+                403 if message.contains("secondary rate limit") => {
+                    Some(Transient::rate_limited(Some(SECONDARY_RATE_LIMIT_WAIT)))
+                }
+                429 => Some(Transient::rate_limited(None)),
+                403 if message.contains("rate limit") => Some(Transient::rate_limited(None)),
+                500 | 502..=504 if idempotent => Some(Transient::failure()),
+                _ => None,
+            }
+        }
+        octocrab::Error::Hyper { .. } | octocrab::Error::Service { .. } if idempotent => {
+            Some(Transient::failure())
+        }
+        _ => None,
+    }
+}
+
+/// GitHub service using octocrab
+pub struct GitHubService {
+    client: Octocrab,
+    config: PlatformConfig,
+    run: Arc<RunContext>,
+}
+
+impl GitHubService {
+    /// Create a new GitHub service
+    pub fn new(token: &str, owner: String, repo: String, host: Option<String>) -> Result<Self> {
+        let api_root = host.as_ref().map(|h| format!("https://{h}/api/v3"));
+        Self::build(token, owner, repo, host, api_root.as_deref())
+    }
+
+    /// Create a GitHub service sending requests to `api_root` instead of
+    /// GitHub's API, e.g. a proxy or a mock server
+    pub fn with_api_root(token: &str, owner: String, repo: String, api_root: &str) -> Result<Self> {
+        Self::build(token, owner, repo, None, Some(api_root))
+    }
+
+    fn build(
+        token: &str,
+        owner: String,
+        repo: String,
+        host: Option<String>,
+        api_root: Option<&str>,
+    ) -> Result<Self> {
+        let mut builder = Octocrab::builder().personal_token(token.to_string());
+
+        if let Some(api_root) = api_root {
+            builder = builder
+                .base_uri(api_root)
+                .map_err(|e| Error::GitHubApi(e.to_string()))?;
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| Error::GitHubApi(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            config: PlatformConfig {
+                platform: Platform::GitHub,
+                owner,
+                repo,
+                host,
+                head_owner: None,
+            },
+            run: Arc::default(),
+        })
+    }
+
+    /// Retry requests and count them under `run`'s policy and budget
+    #[must_use]
+    pub fn with_run(mut self, run: Arc<RunContext>) -> Self {
+        self.run = run;
+        self
+    }
+
+    /// Open PRs from branches of `head_owner`'s fork instead of the
+    /// repository itself
+    #[must_use]
+    pub fn with_head_owner(mut self, head_owner: Option<String>) -> Self {
+        self.config.head_owner = head_owner;
+        self
+    }
+
+    /// Owner of the repository PR head branches live in
+    fn head_owner(&self) -> &str {
+        self.config
+            .head_owner
+            .as_deref()
+            .unwrap_or(&self.config.owner)
+    }
+
+    /// Items of `first` and every page after it
+    ///
+    /// Each further page is a request of its own, so it's retried like any
+    /// other call and counted against the run's API budget.
+    async fn all_pages<T: DeserializeOwned>(
+        &self,
+        request: &str,
+        first: Page<T>,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut page = first;
+        loop {
+            items.append(&mut page.take_items());
+            if page.next.is_none() {
+                break;
+            }
+            self.run.budget().record_call();
+            let next = &page.next;
+            match self
+                .retry(request, true, || self.client.get_page::<T>(next))
+                .await?
+            {
+                Some(next) => page = next,
+                None => break,
+            }
+        }
+        Ok(items)
+    }
+
+    /// Run an octocrab call, retrying transient failures as the
+    /// [retry policy](RunContext::retry_policy) of the run allows
+    async fn retry<T, F, Fut>(
+        &self,
+        request: &str,
+        idempotent: bool,
+        mut call: F,
+    ) -> octocrab::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = octocrab::Result<T>>,
+    {
+        let mut retries = Retries::new(request, &self.run);
+        loop {
+            let error = match call().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let Some(mut transient) = transient(&error, idempotent) else {
+                return Err(error);
+            };
+            if transient.rate_limited && transient.retry_after.is_none() {
+                transient.retry_after = self.rate_limit_reset().await;
+            }
+            if !retries.wait(transient).await {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Fetch a PR
+    async fn get_pr(&self, pr_number: u64) -> Result<octocrab::models::pulls::PullRequest> {
+        Ok(self
+            .retry("get_pr", true, || async move {
+                self.client
+                    .pulls(&self.config.owner, &self.config.repo)
+                    .get(pr_number)
+                    .await
+            })
+            .await?)
+    }
+
+    /// Time until the rate limit resets
+    ///
+    /// Octocrab doesn't pass on the response headers that say, so this asks.
+    async fn rate_limit_reset(&self) -> Option<Duration> {
+        let resets_at = self.rate_limit().await.ok()??.resets_at?;
+        let wait = (resets_at - Utc::now()).to_std().unwrap_or_default();
+        Some(wait + Duration::from_secs(1))
+    }
+}
+
+/// Octocrab's name for a merge method
+const fn octocrab_merge_method(method: MergeMethod) -> octocrab::params::pulls::MergeMethod {
+    match method {
+        MergeMethod::Merge => octocrab::params::pulls::MergeMethod::Merge,
+        MergeMethod::Squash => octocrab::params::pulls::MergeMethod::Squash,
+        MergeMethod::Rebase => octocrab::params::pulls::MergeMethod::Rebase,
+    }
+}
+
+/// Helper to convert octocrab PR to our `PullRequest` type
+fn pr_from_octocrab(pr: &octocrab::models::pulls::PullRequest) -> PullRequest {
+    PullRequest {
+        number: pr.number,
+        html_url: pr
+            .html_url
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+        base_ref: pr.base.ref_field.clone(),
+        head_ref: pr.head.ref_field.clone(),
+        title: pr.title.as_deref().unwrap_or_default().to_string(),
+        node_id: pr.node_id.clone(),
+        is_draft: pr.draft.unwrap_or(false),
+        updated_at: pr.updated_at,
+    }
+}
+
+#[async_trait]
+impl PlatformService for GitHubService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        debug!(head_branch, "finding existing PR");
+
+        // Oldest PR wins so duplicates can't flip-flop between runs
+        let result = self
+            .find_open_prs(head_branch)
+            .await?
+            .into_iter()
+            .min_by_key(|pr| pr.number);
+        if let Some(ref pr) = result {
+            debug!(pr_number = pr.number, "found existing PR");
+        } else {
+            debug!("no existing PR found");
+        }
+        Ok(result)
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        debug!(head_branch, "listing open PRs");
+        let head = format!("{}:{}", self.head_owner(), head_branch);
+
+        let head = &head;
+        let prs = self
+            .retry("find_open_prs", true, || async move {
+                self.client
+                    .pulls(&self.config.owner, &self.config.repo)
+                    .list()
+                    .head(head)
+                    .state(octocrab::params::State::Open)
+                    .send()
+                    .await
+            })
+            .await?;
+
+        let result: Vec<PullRequest> = prs.items.iter().map(pr_from_octocrab).collect();
+        debug!(head_branch, count = result.len(), "listed open PRs");
+        Ok(result)
+    }
+
+    async fn find_branch_prs(&self, head_branches: &[String]) -> Result<Vec<BranchPrs>> {
+        debug!(count = head_branches.len(), "finding PRs of branches");
+        let head_owner = self.head_owner();
+        let mut found = Vec::with_capacity(head_branches.len());
+        for chunk in head_branches.chunks(BRANCHES_PER_QUERY) {
+            let query = branch_prs_query(&self.config.owner, &self.config.repo, chunk);
+            let response: GraphQlResponse<BranchPrsData> = self
+                .retry("find_branch_prs", true, || self.client.graphql(&query))
+                .await
+                .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+            check_graphql_errors(response.errors)?;
+            let mut connections = response
+                .data
+                .and_then(|data| data.repository)
+                .ok_or_else(|| Error::GitHubApi("repository not found".to_string()))?;
+
+            for (i, head_branch) in chunk.iter().enumerate() {
+                // headRefName matches branches of every fork; keep the head owner's
+                let mut nodes: Vec<BranchPrNode> = connections
+                    .remove(&format!("b{i}"))
+                    .map(|c| c.nodes)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|node| {
+                        node.head_repository_owner
+                            .as_ref()
+                            .is_some_and(|o| o.login.eq_ignore_ascii_case(head_owner))
+                    })
+                    .collect();
+                nodes.sort_by_key(|node| node.pr.number);
+                let readiness = nodes.first().map(|node| {
+                    pr_readiness(
+                        node.review_decision.as_deref(),
+                        &node.approvals,
+                        &node.commits,
+                    )
+                });
+                let reviews = nodes
+                    .first()
+                    .map(|node| pr_reviews(&node.latest_opinionated_reviews));
+                found.push(BranchPrs {
+                    head_branch: head_branch.clone(),
+                    prs: nodes.into_iter().map(|node| node.pr.into()).collect(),
+                    readiness,
+                    reviews,
+                });
+            }
+        }
+        debug!(count = found.len(), "found PRs of branches");
+        Ok(found)
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        debug!(head_branch, "finding latest PR");
+        let head = format!("{}:{}", self.head_owner(), head_branch);
+
+        let head = &head;
+        let prs = self
+            .retry("find_latest_pr", true, || async move {
+                self.client
+                    .pulls(&self.config.owner, &self.config.repo)
+                    .list()
+                    .head(head)
+                    .state(octocrab::params::State::All)
+                    .sort(octocrab::params::pulls::Sort::Created)
+                    .direction(octocrab::params::Direction::Descending)
+                    .per_page(1)
+                    .send()
+                    .await
+            })
+            .await?;
+
+        Ok(prs.items.first().map(|pr| {
+            let state = if pr.merged_at.is_some() {
+                PrState::Merged
+            } else if pr.state == Some(octocrab::models::IssueState::Closed) {
+                PrState::Closed
+            } else if pr.draft.unwrap_or(false) {
+                PrState::Draft
+            } else {
+                PrState::Open
+            };
+            (pr_from_octocrab(pr), state)
+        }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        debug!(author, "listing open PRs by author");
+        let page = self
+            .retry("list_open_prs_by_author", true, || async move {
+                self.client
+                    .pulls(&self.config.owner, &self.config.repo)
+                    .list()
+                    .state(octocrab::params::State::Open)
+                    .per_page(100)
+                    .send()
+                    .await
+            })
+            .await?;
+        let prs = self.all_pages("list_open_prs_by_author", page).await?;
+
+        let result: Vec<PullRequest> = prs
+            .iter()
+            .filter(|pr| {
+                pr.user
+                    .as_ref()
+                    .is_some_and(|user| user.login.eq_ignore_ascii_case(author))
+            })
+            .map(pr_from_octocrab)
+            .collect();
+        debug!(author, count = result.len(), "listed open PRs by author");
+        Ok(result)
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        let user = self
+            .retry("current_user", true, || async move {
+                self.client.current().user().await
+            })
+            .await?;
+        Ok(user.login)
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating PR");
+        let pulls = &self.client.pulls(&self.config.owner, &self.config.repo);
+        // Fork PRs let maintainers push fixups to the branch before merging
+        let pr = match &self.config.head_owner {
+            Some(head_owner) => {
+                let fork_head = &format!("{head_owner}:{head}");
+                self.retry("create_pr", false, || async move {
+                    pulls
+                        .create(title, fork_head, base)
+                        .body(body)
+                        .draft(draft)
+                        .maintainer_can_modify(true)
+                        .send()
+                        .await
+                })
+                .await?
+            }
+            None => {
+                self.retry("create_pr", false, || async move {
+                    pulls
+                        .create(title, head, base)
+                        .body(body)
+                        .draft(draft)
+                        .send()
+                        .await
+                })
+                .await?
+            }
+        };
+
+        let result = pr_from_octocrab(&pr);
+        debug!(pr_number = result.number, "created PR");
+        Ok(result)
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        debug!(pr_number, new_base, "updating PR base");
+        let pr = self
+            .retry("update_pr_base", true, || async move {
+                self.client
+                    .pulls(&self.config.owner, &self.config.repo)
+                    .update(pr_number)
+                    .base(new_base)
+                    .send()
+                    .await
+            })
+            .await?;
+
+        debug!(pr_number, "updated PR base");
+        Ok(pr_from_octocrab(&pr))
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        let pr = self.get_pr(pr_number).await?;
+        Ok(pr.body.unwrap_or_default())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        let pr = self.get_pr(pr_number).await?;
+        // Before merging this is the test merge commit; only trust it after
+        Ok(pr.merged_at.and(pr.merge_commit_sha))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "updating PR body");
+        self.retry("update_pr_body", true, || async move {
+            self.client
+                .pulls(&self.config.owner, &self.config.repo)
+                .update(pr_number)
+                .body(body)
+                .send()
+                .await
+        })
+        .await?;
+        debug!(pr_number, "updated PR body");
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        debug!(pr_number, "publishing PR");
+        // Fetch PR to get node_id for GraphQL mutation
+        let pr = self.get_pr(pr_number).await?;
+
+        let node_id = pr.node_id.as_ref().ok_or_else(|| {
+            Error::GitHubApi("PR missing node_id for GraphQL mutation".to_string())
+        })?;
+
+        // Execute GraphQL mutation to mark PR as ready for review
+        let query = serde_json::json!({
+            "query": r"
+                mutation MarkPullRequestReadyForReview($pullRequestId: ID!) {
+                    markPullRequestReadyForReview(input: { pullRequestId: $pullRequestId }) {
+                        pullRequest {
+                            number
+                            url
+                            baseRefName
+                            headRefName
+                            title
+                            id
+                            isDraft
+                            updatedAt
+                        }
+                    }
+                }
+            ",
+            "variables": {
+                "pullRequestId": node_id
+            }
+        });
+        let response: GraphQlResponse<MarkReadyForReviewData> = self
+            .retry("publish_pr", false, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL mutation failed: {e}")))?;
+
+        check_graphql_errors(response.errors)?;
+
+        // Extract typed response
+        let data = response
+            .data
+            .ok_or_else(|| Error::GitHubApi("No data in GraphQL response".to_string()))?;
+
+        debug!(pr_number, "published PR");
+        Ok(data.mark_pull_request_ready_for_review.pull_request.into())
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_number, "closing PR");
+        self.retry("close_pr", true, || async move {
+            self.client
+                .pulls(&self.config.owner, &self.config.repo)
+                .update(pr_number)
+                .state(octocrab::params::pulls::State::Closed)
+                .send()
+                .await
+        })
+        .await?;
+        debug!(pr_number, "closed PR");
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(pr_number, %method, "merging PR");
+        self.retry("merge_pr", false, || async move {
+            self.client
+                .pulls(&self.config.owner, &self.config.repo)
+                .merge(pr_number)
+                .method(octocrab_merge_method(method))
+                .send()
+                .await
+        })
+        .await?;
+        debug!(pr_number, "merged PR");
+        Ok(())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        debug!(pr_number, "checking PR readiness");
+        let query = serde_json::json!({
+            "query": r"
+                query Readiness($owner: String!, $repo: String!, $number: Int!) {
+                    repository(owner: $owner, name: $repo) {
+                        pullRequest(number: $number) {
+                            reviewDecision
+                            approvals: reviews(states: APPROVED) { totalCount }
+                            commits(last: 1) {
+                                nodes { commit { statusCheckRollup { state } } }
+                            }
+                        }
+                    }
+                }
+            ",
+            "variables": {
+                "owner": self.config.owner,
+                "repo": self.config.repo,
+                "number": pr_number,
+            }
+        });
+        let response: GraphQlResponse<ReadinessData> = self
+            .retry("get_pr_readiness", true, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        let pr = response
+            .data
+            .and_then(|data| data.repository)
+            .and_then(|repo| repo.pull_request)
+            .ok_or_else(|| Error::GitHubApi(format!("PR #{pr_number} not found")))?;
+
+        let readiness = pr_readiness(pr.review_decision.as_deref(), &pr.approvals, &pr.commits);
+        debug!(
+            pr_number,
+            approved = readiness.approved,
+            checks = %readiness.checks,
+            "checked PR readiness"
+        );
+        Ok(readiness)
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        debug!(pr_number, "listing PR reviews");
+        let query = serde_json::json!({
+            "query": format!(r"
+                query Reviews($owner: String!, $repo: String!, $number: Int!) {{
+                    repository(owner: $owner, name: $repo) {{
+                        pullRequest(number: $number) {{
+                            latestOpinionatedReviews(first: {REVIEWS_PER_PR}) {{
+                                nodes {{ author {{ login }} state }}
+                            }}
+                        }}
+                    }}
+                }}
+            "),
+            "variables": {
+                "owner": self.config.owner,
+                "repo": self.config.repo,
+                "number": pr_number,
+            }
+        });
+        let response: GraphQlResponse<ReviewsData> = self
+            .retry("list_reviews", true, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        let pr = response
+            .data
+            .and_then(|data| data.repository)
+            .and_then(|repo| repo.pull_request)
+            .ok_or_else(|| Error::GitHubApi(format!("PR #{pr_number} not found")))?;
+        let reviews = pr_reviews(&pr.latest_opinionated_reviews);
+        debug!(pr_number, count = reviews.len(), "listed PR reviews");
+        Ok(reviews)
+    }
+
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        debug!(pr_number, "checking PR checks");
+        let query = serde_json::json!({
+            "query": r"
+                query Checks($owner: String!, $repo: String!, $number: Int!) {
+                    repository(owner: $owner, name: $repo) {
+                        pullRequest(number: $number) {
+                            commits(last: 1) {
+                                nodes { commit { statusCheckRollup { state } } }
+                            }
+                        }
+                    }
+                }
+            ",
+            "variables": {
+                "owner": self.config.owner,
+                "repo": self.config.repo,
+                "number": pr_number,
+            }
+        });
+        let response: GraphQlResponse<ChecksData> = self
+            .retry("get_check_status", true, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        let pr = response
+            .data
+            .and_then(|data| data.repository)
+            .and_then(|repo| repo.pull_request)
+            .ok_or_else(|| Error::GitHubApi(format!("PR #{pr_number} not found")))?;
+        let checks = checks_state(&pr.commits);
+        debug!(pr_number, %checks, "checked PR checks");
+        Ok(checks)
+    }
+
+    async fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        // GraphQL requests are what ryu spends most; querying costs nothing
+        let response: GraphQlResponse<RateLimitData> = self
+            .client
+            .graphql(&serde_json::json!({
+                "query": "query { rateLimit { limit remaining resetAt } }",
+            }))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        Ok(response
+            .data
+            .and_then(|data| data.rate_limit)
+            .map(|rate| RateLimit {
+                limit: rate.limit,
+                remaining: rate.remaining,
+                resets_at: rate.reset_at,
+            }))
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        debug!(pr_number = pr.number, project, "adding PR to project");
+        let (owner, number) = parse_project_ref(project)?;
+        let content_id = pr.node_id.as_ref().ok_or_else(|| {
+            Error::GitHubApi("PR missing node_id for GraphQL mutation".to_string())
+        })?;
+
+        // Users and organizations both own projects; resolve either
+        let query = serde_json::json!({
+            "query": r"
+                query ProjectId($login: String!, $number: Int!) {
+                    repositoryOwner(login: $login) {
+                        ... on ProjectV2Owner {
+                            projectV2(number: $number) { id }
+                        }
+                    }
+                }
+            ",
+            "variables": { "login": owner, "number": number }
+        });
+        let response: GraphQlResponse<ProjectOwnerData> = self
+            .retry("add_pr_to_project", true, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL query failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        let project_id = response
+            .data
+            .and_then(|d| d.repository_owner)
+            .and_then(|o| o.project_v2)
+            .map(|p| p.id)
+            .ok_or_else(|| Error::GitHubApi(format!("project '{project}' not found")))?;
+
+        let query = serde_json::json!({
+            "query": r"
+                mutation AddProjectItem($projectId: ID!, $contentId: ID!) {
+                    addProjectV2ItemById(input: { projectId: $projectId, contentId: $contentId }) {
+                        item { id }
+                    }
+                }
+            ",
+            "variables": { "projectId": project_id, "contentId": content_id }
+        });
+        let response: GraphQlResponse<serde_json::Value> = self
+            .retry("add_pr_to_project", false, || self.client.graphql(&query))
+            .await
+            .map_err(|e| Error::GitHubApi(format!("GraphQL mutation failed: {e}")))?;
+        check_graphql_errors(response.errors)?;
+
+        debug!(pr_number = pr.number, project, "added PR to project");
+        Ok(())
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        debug!(pr_number, ?reviewers, "requesting reviews");
+        self.retry("request_reviewers", true, || async move {
+            self.client
+                .pulls(&self.config.owner, &self.config.repo)
+                .request_reviews(pr_number, reviewers.to_vec(), Vec::<String>::new())
+                .await
+        })
+        .await?;
+        debug!(pr_number, "requested reviews");
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        debug!(pr_number, ?metadata, "updating PR metadata");
+        if !metadata.reviewers.is_empty() {
+            self.request_reviewers(pr_number, &metadata.reviewers)
+                .await?;
+        }
+        // Labels and assignees live on the PR's issue; adding either is
+        // harmless to repeat
+        if !metadata.labels.is_empty() {
+            let labels = &metadata.labels;
+            self.retry("add_labels", true, || async move {
+                self.client
+                    .issues(&self.config.owner, &self.config.repo)
+                    .add_labels(pr_number, labels)
+                    .await
+            })
+            .await?;
+        }
+        if !metadata.assignees.is_empty() {
+            let assignees: Vec<&str> = metadata.assignees.iter().map(String::as_str).collect();
+            let assignees = &assignees;
+            self.retry("add_assignees", true, || async move {
+                self.client
+                    .issues(&self.config.owner, &self.config.repo)
+                    .add_assignees(pr_number, assignees)
+                    .await
+            })
+            .await?;
+        }
+        debug!(pr_number, "updated PR metadata");
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        let query = format!(
+            "repo:{}/{} is:pr is:open review-requested:{reviewer}",
+            self.config.owner, self.config.repo
+        );
+        let query = &query;
+        let page = self
+            .retry("count_review_requests", true, || async move {
+                self.client
+                    .search()
+                    .issues_and_pull_requests(query)
+                    .per_page(1)
+                    .send()
+                    .await
+            })
+            .await?;
+        let count = page.total_count.unwrap_or_default();
+        debug!(reviewer, count, "counted open review requests");
+        Ok(usize::try_from(count).unwrap_or(usize::MAX))
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        debug!(title, "creating issue");
+        let issue = self
+            .retry("create_issue", false, || async move {
+                self.client
+                    .issues(&self.config.owner, &self.config.repo)
+                    .create(title)
+                    .body(body)
+                    .send()
+                    .await
+            })
+            .await?;
+
+        debug!(issue_number = issue.number, "created issue");
+        Ok(Issue {
+            number: issue.number,
+            html_url: issue.html_url.to_string(),
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        let issue = self
+            .retry("get_issue_body", true, || async move {
+                self.client
+                    .issues(&self.config.owner, &self.config.repo)
+                    .get(issue_number)
+                    .await
+            })
+            .await?;
+
+        Ok(issue.body.unwrap_or_default())
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        debug!(issue_number, "updating issue body");
+        self.retry("update_issue", true, || async move {
+            self.client
+                .issues(&self.config.owner, &self.config.repo)
+                .update(issue_number)
+                .body(body)
+                .send()
+                .await
+        })
+        .await?;
+
+        debug!(issue_number, "updated issue body");
+        Ok(())
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        debug!(pr_number, "listing PR comments");
+        // Every page: a stack comment missed on a later page gets posted again.
+        // GitHub can't filter comments by their text, so all are fetched.
+        let page = self
+            .retry("list_pr_comments", true, || async move {
+                self.client
+                    .issues(&self.config.owner, &self.config.repo)
+                    .list_comments(pr_number)
+                    .per_page(100)
+                    .send()
+                    .await
+            })
+            .await?;
+        let comments = self.all_pages("list_pr_comments", page).await?;
+
+        let result: Vec<PrComment> = comments
+            .into_iter()
+            .map(|c| PrComment {
+                id: c.id.0,
+                body: c.body.unwrap_or_default(),
+                node_id: Some(c.node_id),
+            })
+            .collect();
+        debug!(pr_number, count = result.len(), "listed PR comments");
+        Ok(result)
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "creating PR comment");
+        self.retry("create_pr_comment", false, || async move {
+            self.client
+                .issues(&self.config.owner, &self.config.repo)
+                .create_comment(pr_number, body)
+                .await
+        })
+        .await?;
+        debug!(pr_number, "created PR comment");
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, _pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        debug!(comment_id, "updating PR comment");
+        self.retry("update_pr_comment", true, || async move {
+            self.client
+                .issues(&self.config.owner, &self.config.repo)
+                .update_comment(octocrab::models::CommentId(comment_id), body)
+                .await
+        })
+        .await?;
+        debug!(comment_id, "updated PR comment");
+        Ok(())
+    }
+
+    async fn write_pr_comments(&self, writes: &[CommentWrite]) -> Vec<Result<()>> {
+        let mut results: Vec<Option<Result<()>>> = writes.iter().map(|_| None).collect();
+        let batchable: Vec<(usize, CommentTarget<'_>)> = writes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, write)| CommentTarget::of(write).map(|target| (i, target)))
+            .collect();
+
+        for chunk in batchable.chunks(COMMENT_WRITES_PER_REQUEST) {
+            debug!(count = chunk.len(), "writing PR comments in one request");
+            let batch: Vec<(CommentTarget<'_>, &str)> = chunk
+                .iter()
+                .map(|(i, target)| (*target, writes[*i].body.as_str()))
+                .collect();
+            let mutation = comment_mutation(&batch);
+            let response: std::result::Result<GraphQlResponse<serde_json::Value>, _> = self
+                .retry("write_pr_comments", false, || {
+                    self.client.graphql(&mutation)
+                })
+                .await;
+            for (n, (i, _)) in chunk.iter().enumerate() {
+                results[*i] = Some(match &response {
+                    Ok(response) => {
+                        comment_write_result(response.errors.as_deref().unwrap_or_default(), n)
+                    }
+                    Err(e) => Err(Error::GitHubApi(format!("GraphQL mutation failed: {e}"))),
+                });
+            }
+        }
+
+        // Writes without node IDs go through REST one at a time
+        for (slot, write) in results.iter_mut().zip(writes) {
+            if slot.is_none() {
+                *slot = Some(match &write.existing {
+                    Some(comment) => {
+                        self.update_pr_comment(write.pr_number, comment.id, &write.body)
+                            .await
+                    }
+                    None => self.create_pr_comment(write.pr_number, &write.body).await,
+                });
+            }
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
@@ -0,0 +1,949 @@
+//! GitLab platform service implementation
+
+use crate::error::{Error, Result};
+use crate::metrics::MeteredJson;
+use crate::platform::retry::SendRetrying;
+use crate::platform::{DEFAULT_TIMEOUT_SECS, PlatformService};
+use crate::run::RunContext;
+use crate::types::{
+    ChecksState, Issue, MergeMethod, Platform, PlatformConfig, PrComment, PrMetadata, PrReadiness,
+    PrReview, PrState, PullRequest, ReviewState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, LINK};
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+
+/// GitLab service using reqwest
+pub struct GitLabService {
+    client: Client,
+    token: String,
+    /// Root of the REST API, `https://<host>/api/v4`
+    api_base: String,
+    config: PlatformConfig,
+    project_path: String,
+    run: Arc<RunContext>,
+}
+
+#[derive(Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    web_url: String,
+    source_branch: String,
+    target_branch: String,
+    title: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    merge_commit_sha: Option<String>,
+    #[serde(default)]
+    squash_commit_sha: Option<String>,
+    #[serde(default)]
+    sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProjectIssue {
+    iid: u64,
+    web_url: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CurrentUser {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct UserId {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct MrApprovals {
+    approved: bool,
+    #[serde(default)]
+    approved_by: Vec<MrUser>,
+}
+
+/// An approver or reviewer entry, which wraps the user
+#[derive(Deserialize)]
+struct MrUser {
+    user: Username,
+}
+
+#[derive(Deserialize)]
+struct Username {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct MrReviewer {
+    user: Username,
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct MrPipeline {
+    #[serde(default)]
+    head_pipeline: Option<Pipeline>,
+}
+
+#[derive(Deserialize)]
+struct Pipeline {
+    status: String,
+}
+
+/// Page size for paginated list requests
+const PER_PAGE: usize = 100;
+
+/// URL of the page after the one `current` fetched, if there is one
+///
+/// Offset-paginated lists name the next page in `x-next-page`, which is
+/// empty on the last page; keyset-paginated ones only give a `Link` header.
+/// A next page on another host is never followed, as it would get the token.
+fn next_page_url(current: &Url, headers: &HeaderMap) -> Option<Url> {
+    let from_header = headers
+        .get("x-next-page")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|page| !page.is_empty())
+        .map(|page| {
+            let pairs: Vec<(String, String)> = current
+                .query_pairs()
+                .filter(|(key, _)| key != "page")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+            let mut url = current.clone();
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs)
+                .append_pair("page", page);
+            url
+        });
+    let next = from_header.or_else(|| {
+        let link = headers.get(LINK)?.to_str().ok()?;
+        link.split(',').find_map(|entry| {
+            let (target, params) = entry.split_once(';')?;
+            params
+                .split(';')
+                .any(|param| param.trim() == "rel=\"next\"")
+                .then(|| Url::parse(target.trim().trim_start_matches('<').trim_end_matches('>')))?
+                .ok()
+        })
+    })?;
+    (next.host_str() == current.host_str()).then_some(next)
+}
+
+#[derive(Deserialize)]
+struct MrNote {
+    id: u64,
+    body: String,
+    system: bool,
+}
+
+impl From<MergeRequest> for PullRequest {
+    fn from(mr: MergeRequest) -> Self {
+        Self {
+            number: mr.iid,
+            html_url: mr.web_url,
+            base_ref: mr.target_branch,
+            head_ref: mr.source_branch,
+            title: mr.title,
+            node_id: None, // GitLab doesn't use GraphQL node IDs
+            is_draft: mr.draft,
+            updated_at: mr.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CreateMrPayload {
+    source_branch: String,
+    target_branch: String,
+    title: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    draft: Option<bool>,
+}
+
+impl GitLabService {
+    /// Create a new GitLab service
+    pub fn new(token: String, owner: String, repo: String, host: Option<String>) -> Result<Self> {
+        let host = host.unwrap_or_else(|| "gitlab.com".to_string());
+        let project_path = format!("{owner}/{repo}");
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| Error::GitLabApi(format!("failed to create HTTP client: {e}")))?;
+
+        let config_host = if host == "gitlab.com" {
+            None
+        } else {
+            Some(host.clone())
+        };
+
+        Ok(Self {
+            client,
+            token,
+            api_base: format!("https://{host}/api/v4"),
+            config: PlatformConfig {
+                platform: Platform::GitLab,
+                owner,
+                repo,
+                host: config_host,
+                head_owner: None,
+            },
+            project_path,
+            run: Arc::default(),
+        })
+    }
+
+    /// Retry requests and count them under `run`'s policy, budget and
+    /// metrics
+    #[must_use]
+    pub fn with_run(mut self, run: Arc<RunContext>) -> Self {
+        self.run = run;
+        self
+    }
+
+    /// Send requests to `api_root` instead of the host's `/api/v4`, e.g. a
+    /// proxy or a mock server
+    #[must_use]
+    pub fn with_api_root(mut self, api_root: impl Into<String>) -> Self {
+        self.api_base = api_root.into();
+        self
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base, path)
+    }
+
+    fn encoded_project(&self) -> String {
+        urlencoding::encode(&self.project_path).into_owned()
+    }
+
+    /// Look up user IDs, which the MR API takes instead of usernames
+    async fn user_ids(&self, usernames: &[String]) -> Result<Vec<u64>> {
+        let mut ids = Vec::with_capacity(usernames.len());
+        for username in usernames {
+            let users: Vec<UserId> = self
+                .get_all(&self.api_url("/users"), &[("username", username)])
+                .await?;
+            let user = users
+                .first()
+                .ok_or_else(|| Error::GitLabApi(format!("user '{username}' not found")))?;
+            ids.push(user.id);
+        }
+        Ok(ids)
+    }
+
+    /// Fetch every page of a list endpoint
+    ///
+    /// Each page after the first is counted against the run's API budget,
+    /// like any other request.
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let per_page = PER_PAGE.to_string();
+        let mut request = self
+            .client
+            .get(url)
+            .query(query)
+            .query(&[("per_page", per_page.as_str())]);
+        let mut values = Vec::new();
+        loop {
+            let response = request
+                .header("PRIVATE-TOKEN", &self.token)
+                .send_retrying(&self.run)
+                .await?
+                .error_for_status()
+                .map_err(|e| Error::GitLabApi(e.to_string()))?;
+            let next = next_page_url(response.url(), response.headers());
+            let mut batch: Vec<T> = response.metered_json(&self.run).await?;
+            let empty = batch.is_empty();
+            values.append(&mut batch);
+            match next {
+                Some(next) if !empty => {
+                    self.run.budget().record_call();
+                    request = self.client.get(next);
+                }
+                _ => break,
+            }
+        }
+        Ok(values)
+    }
+
+    /// Apply `changes` to an MR
+    async fn update_mr(&self, mr_iid: u64, changes: &serde_json::Value) -> Result<()> {
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            mr_iid
+        ));
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(changes)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PlatformService for GitLabService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        debug!(head_branch, "finding existing MR");
+
+        // Oldest MR wins so duplicates can't flip-flop between runs
+        let result = self
+            .find_open_prs(head_branch)
+            .await?
+            .into_iter()
+            .min_by_key(|pr| pr.number);
+        if let Some(ref pr) = result {
+            debug!(mr_iid = pr.number, "found existing MR");
+        } else {
+            debug!("no existing MR found");
+        }
+        Ok(result)
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        debug!(head_branch, "listing open MRs");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests",
+            self.encoded_project()
+        ));
+
+        let mrs: Vec<MergeRequest> = self
+            .get_all(&url, &[("source_branch", head_branch), ("state", "opened")])
+            .await?;
+
+        let result: Vec<PullRequest> = mrs.into_iter().map(Into::into).collect();
+        debug!(head_branch, count = result.len(), "listed open MRs");
+        Ok(result)
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        debug!(head_branch, "finding latest MR");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests",
+            self.encoded_project()
+        ));
+
+        let mrs: Vec<MergeRequest> = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .query(&[
+                ("source_branch", head_branch),
+                ("state", "all"),
+                ("order_by", "created_at"),
+                ("sort", "desc"),
+                ("per_page", "1"),
+            ])
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        Ok(mrs.into_iter().next().map(|mr| {
+            let state = match mr.state.as_str() {
+                "merged" => PrState::Merged,
+                "closed" | "locked" => PrState::Closed,
+                _ if mr.draft => PrState::Draft,
+                _ => PrState::Open,
+            };
+            (mr.into(), state)
+        }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        debug!(author, "listing open MRs by author");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests",
+            self.encoded_project()
+        ));
+
+        let result: Vec<PullRequest> = self
+            .get_all::<MergeRequest>(&url, &[("state", "opened"), ("author_username", author)])
+            .await?
+            .into_iter()
+            .map(PullRequest::from)
+            .collect();
+
+        debug!(author, count = result.len(), "listed open MRs by author");
+        Ok(result)
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        let user: CurrentUser = self
+            .client
+            .get(self.api_url("/user"))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+        Ok(user.username)
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        debug!(head, base, draft, "creating MR");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests",
+            self.encoded_project()
+        ));
+
+        let payload = CreateMrPayload {
+            source_branch: head.to_string(),
+            target_branch: base.to_string(),
+            title: title.to_string(),
+            description: body.to_string(),
+            draft: if draft { Some(true) } else { None },
+        };
+
+        let mr: MergeRequest = self
+            .client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&payload)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        let pr: PullRequest = mr.into();
+        debug!(mr_iid = pr.number, "created MR");
+        Ok(pr)
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        debug!(mr_iid = pr_number, new_base, "updating MR base");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let mr: MergeRequest = self
+            .client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "target_branch": new_base }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        debug!(mr_iid = pr_number, "updated MR base");
+        Ok(mr.into())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let mr: MergeRequest = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        Ok(mr.description.unwrap_or_default())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let mr: MergeRequest = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        if mr.state != "merged" {
+            return Ok(None);
+        }
+        // Fast-forward merges create no commit of their own: the head landed
+        Ok(mr.merge_commit_sha.or(mr.squash_commit_sha).or(mr.sha))
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(mr_iid = pr_number, "updating MR description");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "description": body }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr_number, "updated MR description");
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        debug!(mr_iid = pr_number, "publishing MR");
+        // GitLab: Use state_event to mark MR as ready
+        // We need to remove the draft/WIP status
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        // GitLab uses state_event: "ready" to mark as ready for review
+        let mr: MergeRequest = self
+            .client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "state_event": "ready" }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        debug!(mr_iid = pr_number, "published MR");
+        Ok(mr.into())
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(mr_iid = pr_number, "closing MR");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "state_event": "close" }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr_number, "closed MR");
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        debug!(mr_iid = pr_number, %method, "merging MR");
+        // Fast-forward vs merge commit is a project setting on GitLab; only
+        // squashing can be chosen per MR
+        let squash = match method {
+            MergeMethod::Merge => false,
+            MergeMethod::Squash => true,
+            MergeMethod::Rebase => {
+                return Err(Error::GitLabApi(
+                    "GitLab merges with the project's merge method; use --method merge or squash"
+                        .to_string(),
+                ));
+            }
+        };
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}/merge",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "squash": squash }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr_number, "merged MR");
+        Ok(())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        debug!(mr_iid = pr_number, "checking MR readiness");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let approvals: MrApprovals = self
+            .client
+            .get(format!("{url}/approvals"))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+        let checks = self.get_check_status(pr_number).await?;
+
+        // Projects without approval rules report `approved` up front, so
+        // also require someone to have approved
+        let approved = approvals.approved && !approvals.approved_by.is_empty();
+        debug!(mr_iid = pr_number, approved, %checks, "checked MR readiness");
+        Ok(PrReadiness { approved, checks })
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        debug!(mr_iid = pr_number, "listing MR reviews");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        // Approvers needn't be reviewers, so both lists are needed
+        let approvals: MrApprovals = self
+            .client
+            .get(format!("{url}/approvals"))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+        let reviewers: Vec<MrReviewer> = self.get_all(&format!("{url}/reviewers"), &[]).await?;
+
+        let mut reviews: Vec<PrReview> = approvals
+            .approved_by
+            .into_iter()
+            .map(|approver| PrReview {
+                reviewer: approver.user.username,
+                state: ReviewState::Approved,
+            })
+            .collect();
+        for reviewer in reviewers {
+            if reviewer.state != "requested_changes" {
+                continue;
+            }
+            reviews.retain(|review| review.reviewer != reviewer.user.username);
+            reviews.push(PrReview {
+                reviewer: reviewer.user.username,
+                state: ReviewState::ChangesRequested,
+            });
+        }
+        debug!(
+            mr_iid = pr_number,
+            count = reviews.len(),
+            "listed MR reviews"
+        );
+        Ok(reviews)
+    }
+
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+        let mr: MrPipeline = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        Ok(match mr.head_pipeline.as_ref().map(|p| p.status.as_str()) {
+            None | Some("skipped") => ChecksState::None,
+            Some("success") => ChecksState::Passing,
+            Some("failed" | "canceled") => ChecksState::Failing,
+            Some(_) => ChecksState::Pending,
+        })
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        debug!(
+            mr_iid = pr.number,
+            label = project,
+            "labelling MR for board"
+        );
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr.number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "add_labels": project }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr.number, label = project, "labelled MR for board");
+        Ok(())
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        debug!(mr_iid = pr_number, ?reviewers, "requesting reviews");
+
+        let reviewer_ids = self.user_ids(reviewers).await?;
+        self.update_mr(
+            pr_number,
+            &serde_json::json!({ "reviewer_ids": reviewer_ids }),
+        )
+        .await?;
+
+        debug!(mr_iid = pr_number, "requested reviews");
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        debug!(mr_iid = pr_number, ?metadata, "updating MR metadata");
+
+        // One update for everything; reviewers and assignees replace the
+        // MR's current ones, labels are added
+        let mut changes = serde_json::Map::new();
+        if !metadata.reviewers.is_empty() {
+            changes.insert(
+                "reviewer_ids".to_string(),
+                self.user_ids(&metadata.reviewers).await?.into(),
+            );
+        }
+        if !metadata.assignees.is_empty() {
+            changes.insert(
+                "assignee_ids".to_string(),
+                self.user_ids(&metadata.assignees).await?.into(),
+            );
+        }
+        if !metadata.labels.is_empty() {
+            changes.insert("add_labels".to_string(), metadata.labels.join(",").into());
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.update_mr(pr_number, &changes.into()).await?;
+
+        debug!(mr_iid = pr_number, "updated MR metadata");
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests",
+            self.encoded_project()
+        ));
+
+        let count = self
+            .get_all::<serde_json::Value>(
+                &url,
+                &[("state", "opened"), ("reviewer_username", reviewer)],
+            )
+            .await?
+            .len();
+
+        debug!(reviewer, count, "counted open review requests");
+        Ok(count)
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        debug!(title, "creating issue");
+        let url = self.api_url(&format!("/projects/{}/issues", self.encoded_project()));
+
+        let issue: ProjectIssue = self
+            .client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "title": title, "description": body }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        debug!(issue_iid = issue.iid, "created issue");
+        Ok(Issue {
+            number: issue.iid,
+            html_url: issue.web_url,
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        let url = self.api_url(&format!(
+            "/projects/{}/issues/{}",
+            self.encoded_project(),
+            issue_number
+        ));
+
+        let issue: ProjectIssue = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .metered_json(&self.run)
+            .await?;
+
+        Ok(issue.description.unwrap_or_default())
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        debug!(issue_iid = issue_number, "updating issue description");
+        let url = self.api_url(&format!(
+            "/projects/{}/issues/{}",
+            self.encoded_project(),
+            issue_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "description": body }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(issue_iid = issue_number, "updated issue description");
+        Ok(())
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        debug!(mr_iid = pr_number, "listing MR comments");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}/notes",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let notes: Vec<MrNote> = self.get_all(&url, &[]).await?;
+
+        let comments: Vec<PrComment> = notes
+            .into_iter()
+            .filter(|n| !n.system)
+            .map(|n| PrComment {
+                id: n.id,
+                body: n.body,
+                node_id: None,
+            })
+            .collect();
+        debug!(
+            mr_iid = pr_number,
+            count = comments.len(),
+            "listed MR comments"
+        );
+        Ok(comments)
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(mr_iid = pr_number, "creating MR comment");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}/notes",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "body": body }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr_number, "created MR comment");
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        debug!(mr_iid = pr_number, comment_id, "updating MR comment");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}/notes/{}",
+            self.encoded_project(),
+            pr_number,
+            comment_id
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "body": body }))
+            .send_retrying(&self.run)
+            .await?
+            .error_for_status()
+            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+
+        debug!(mr_iid = pr_number, comment_id, "updated MR comment");
+        Ok(())
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        &self.config
+    }
+}
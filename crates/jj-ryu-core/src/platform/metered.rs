@@ -0,0 +1,309 @@
+//! Platform service wrapper that feeds the run's metrics
+//!
+//! Times every call by operation for [`crate::metrics`], counts it against
+//! the run's [`crate::budget`], and remembers the authenticated user, which
+//! several steps of one run ask for. Successful writes to PRs are noted for
+//! [`crate::undo`].
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::run::RunContext;
+use crate::types::{
+    BranchPrs, ChecksState, CommentWrite, Issue, MergeMethod, PlatformConfig, PrComment,
+    PrMetadata, PrReadiness, PrReview, PrState, PullRequest, RateLimit,
+};
+use crate::undo::RemoteChange;
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Wraps a platform service, counting its calls
+pub struct MeteredPlatform {
+    inner: Box<dyn PlatformService>,
+    run: Arc<RunContext>,
+    user: Mutex<Option<String>>,
+}
+
+impl MeteredPlatform {
+    /// Wrap `inner`, counting its calls against `run`'s budget
+    pub fn new(inner: Box<dyn PlatformService>, run: Arc<RunContext>) -> Self {
+        Self {
+            inner,
+            run,
+            user: Mutex::new(None),
+        }
+    }
+
+    async fn timed<T>(&self, operation: &'static str, call: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        let result = call.await;
+        self.run
+            .metrics()
+            .record_api_call(operation, started.elapsed());
+        self.run.budget().record_call();
+        result
+    }
+
+    /// Note `result`'s PR as created if the call succeeded
+    fn created(&self, result: Result<PullRequest>) -> Result<PullRequest> {
+        result.inspect(|pr| {
+            self.run.undo().record(RemoteChange::CreatedPr {
+                number: pr.number,
+                url: pr.html_url.clone(),
+                platform: self.inner.config().clone(),
+            });
+        })
+    }
+
+    /// Note `change` if the call succeeded
+    fn noted<T>(&self, result: Result<T>, change: RemoteChange) -> Result<T> {
+        if result.is_ok() {
+            self.run.undo().record(change);
+        }
+        result
+    }
+
+    /// Note PR `number` as updated if the call succeeded
+    fn updated<T>(&self, result: Result<T>, number: u64) -> Result<T> {
+        self.noted(result, RemoteChange::UpdatedPr { number })
+    }
+}
+
+#[async_trait]
+impl PlatformService for MeteredPlatform {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        self.timed("find_existing_pr", self.inner.find_existing_pr(head_branch))
+            .await
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        self.timed("find_open_prs", self.inner.find_open_prs(head_branch))
+            .await
+    }
+
+    async fn find_branch_prs(&self, head_branches: &[String]) -> Result<Vec<BranchPrs>> {
+        self.timed("find_branch_prs", self.inner.find_branch_prs(head_branches))
+            .await
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        self.timed("find_latest_pr", self.inner.find_latest_pr(head_branch))
+            .await
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        self.timed(
+            "list_open_prs_by_author",
+            self.inner.list_open_prs_by_author(author),
+        )
+        .await
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        let cached = self.user.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(user) = cached {
+            self.run.metrics().record_cache_hit();
+            return Ok(user);
+        }
+        let user = self
+            .timed("current_user", self.inner.current_user())
+            .await?;
+        *self.user.lock().unwrap_or_else(|e| e.into_inner()) = Some(user.clone());
+        Ok(user)
+    }
+
+    async fn create_pr(&self, head: &str, base: &str, title: &str) -> Result<PullRequest> {
+        self.created(
+            self.timed("create_pr", self.inner.create_pr(head, base, title))
+                .await,
+        )
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        self.created(
+            self.timed(
+                "create_pr",
+                self.inner
+                    .create_pr_with_options(head, base, title, body, draft),
+            )
+            .await,
+        )
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        self.updated(
+            self.timed(
+                "update_pr_base",
+                self.inner.update_pr_base(pr_number, new_base),
+            )
+            .await,
+            pr_number,
+        )
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        self.timed("get_pr_body", self.inner.get_pr_body(pr_number))
+            .await
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.updated(
+            self.timed("update_pr_body", self.inner.update_pr_body(pr_number, body))
+                .await,
+            pr_number,
+        )
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        self.updated(
+            self.timed("publish_pr", self.inner.publish_pr(pr_number))
+                .await,
+            pr_number,
+        )
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.noted(
+            self.timed("close_pr", self.inner.close_pr(pr_number)).await,
+            RemoteChange::ClosedPr { number: pr_number },
+        )
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        self.noted(
+            self.timed("merge_pr", self.inner.merge_pr(pr_number, method))
+                .await,
+            RemoteChange::MergedPr { number: pr_number },
+        )
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        self.timed("get_merge_commit", self.inner.get_merge_commit(pr_number))
+            .await
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        self.timed("get_pr_readiness", self.inner.get_pr_readiness(pr_number))
+            .await
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        self.timed("list_reviews", self.inner.list_reviews(pr_number))
+            .await
+    }
+
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        self.timed("get_check_status", self.inner.get_check_status(pr_number))
+            .await
+    }
+
+    async fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        self.timed("rate_limit", self.inner.rate_limit()).await
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        self.timed(
+            "add_pr_to_project",
+            self.inner.add_pr_to_project(pr, project),
+        )
+        .await
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        self.updated(
+            self.timed(
+                "request_reviewers",
+                self.inner.request_reviewers(pr_number, reviewers),
+            )
+            .await,
+            pr_number,
+        )
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        self.updated(
+            self.timed(
+                "update_pr_metadata",
+                self.inner.update_pr_metadata(pr_number, metadata),
+            )
+            .await,
+            pr_number,
+        )
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        self.timed(
+            "count_review_requests",
+            self.inner.count_review_requests(reviewer),
+        )
+        .await
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        self.timed("create_issue", self.inner.create_issue(title, body))
+            .await
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        self.timed("get_issue_body", self.inner.get_issue_body(issue_number))
+            .await
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        self.timed("update_issue", self.inner.update_issue(issue_number, body))
+            .await
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        self.timed("list_pr_comments", self.inner.list_pr_comments(pr_number))
+            .await
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.updated(
+            self.timed(
+                "create_pr_comment",
+                self.inner.create_pr_comment(pr_number, body),
+            )
+            .await,
+            pr_number,
+        )
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        self.updated(
+            self.timed(
+                "update_pr_comment",
+                self.inner.update_pr_comment(pr_number, comment_id, body),
+            )
+            .await,
+            pr_number,
+        )
+    }
+
+    async fn write_pr_comments(&self, writes: &[CommentWrite]) -> Vec<Result<()>> {
+        let results = self
+            .timed("write_pr_comments", self.inner.write_pr_comments(writes))
+            .await;
+        for (write, result) in writes.iter().zip(&results) {
+            if result.is_ok() {
+                self.run.undo().record(RemoteChange::UpdatedPr {
+                    number: write.pr_number,
+                });
+            }
+        }
+        results
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,272 @@
+//! Platform services for GitHub, GitLab, Bitbucket, Gitea and Azure DevOps
+//!
+//! Provides a unified interface for PR/MR operations across platforms, plus a
+//! simulated platform ([`FakeService`]) for demos and offline trials.
+
+//!
+//! Each service sits behind a cargo feature (`github`, `gitlab`, `bitbucket`,
+//! `gitea`, `azure-devops`, `fake`, all on by default) so single-platform builds skip the
+//! other clients.
+//! [`create_platform_service`] reports [`Error::PlatformNotCompiled`] for a
+//! remote whose platform was compiled out.
+//!
+//! [`Error::PlatformNotCompiled`]: crate::error::Error::PlatformNotCompiled
+
+#[cfg(feature = "azure-devops")]
+mod azure_devops;
+#[cfg(feature = "bitbucket")]
+mod bitbucket;
+mod detection;
+mod factory;
+#[cfg(feature = "fake")]
+mod fake;
+#[cfg(feature = "gitea")]
+mod gitea;
+#[cfg(feature = "github")]
+mod github;
+#[cfg(feature = "gitlab")]
+mod gitlab;
+mod metered;
+mod read_only;
+mod retry;
+
+#[cfg(feature = "azure-devops")]
+pub use azure_devops::AzureDevOpsService;
+#[cfg(feature = "bitbucket")]
+pub use bitbucket::BitbucketService;
+pub use detection::{
+    HOST_ALIASES_ENV, PLATFORM_HOSTS_ENV, UrlRewrite, detect_platform, fake_platform_config,
+    parse_repo_info, parse_url_rewrites, resolve_host_alias, resolve_platform_config,
+    rewrite_remote_url, ssh_config_hostname,
+};
+pub use factory::{
+    AZURE_DEVOPS_COMMENT_TOKEN_ENV, BITBUCKET_COMMENT_TOKEN_ENV, GITEA_COMMENT_TOKEN_ENV,
+    GITHUB_COMMENT_TOKEN_ENV, GITLAB_COMMENT_TOKEN_ENV, comment_token_env,
+    create_comment_platform_service, create_platform_service, is_platform_compiled,
+};
+#[cfg(feature = "fake")]
+pub use fake::FakeService;
+#[cfg(feature = "gitea")]
+pub use gitea::GiteaService;
+#[cfg(feature = "github")]
+pub use github::GitHubService;
+#[cfg(feature = "gitlab")]
+pub use gitlab::GitLabService;
+pub use metered::MeteredPlatform;
+pub use read_only::ReadOnlyPlatform;
+pub use retry::{RetryObserver, RetryPolicy, RetryWait, backoff, retry_after};
+
+/// Default request timeout in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+use crate::error::Result;
+use crate::types::{
+    BranchPrs, ChecksState, CommentWrite, Issue, MergeMethod, PlatformConfig, PrComment,
+    PrMetadata, PrReadiness, PrReview, PrState, PullRequest, RateLimit,
+};
+use async_trait::async_trait;
+
+/// Platform service trait for PR/MR operations
+///
+/// This trait abstracts GitHub and GitLab operations, allowing the same
+/// submission logic to work with either platform.
+#[async_trait]
+pub trait PlatformService: Send + Sync {
+    /// Find an existing open PR for a head branch
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>>;
+
+    /// Find all open PRs for a head branch
+    ///
+    /// Normally there is at most one, but manual creation races can leave
+    /// duplicates behind. The default implementation wraps [`find_existing_pr`].
+    ///
+    /// [`find_existing_pr`]: Self::find_existing_pr
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        Ok(self
+            .find_existing_pr(head_branch)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// Find the open PRs of several head branches, one entry per branch
+    ///
+    /// Meant for a whole stack at once. The default implementation asks
+    /// [`find_open_prs`] branch by branch and leaves readiness and reviews
+    /// unset; platforms that can fetch every branch, with review and CI
+    /// state, in one request override it.
+    ///
+    /// [`find_open_prs`]: Self::find_open_prs
+    async fn find_branch_prs(&self, head_branches: &[String]) -> Result<Vec<BranchPrs>> {
+        let mut found = Vec::with_capacity(head_branches.len());
+        for head_branch in head_branches {
+            let mut prs = self.find_open_prs(head_branch).await?;
+            prs.sort_by_key(|pr| pr.number);
+            found.push(BranchPrs {
+                head_branch: head_branch.clone(),
+                prs,
+                readiness: None,
+                reviews: None,
+            });
+        }
+        Ok(found)
+    }
+
+    /// Find the most recently created PR for a head branch, in any state
+    ///
+    /// Unlike [`find_existing_pr`], this also sees merged and closed PRs.
+    ///
+    /// [`find_existing_pr`]: Self::find_existing_pr
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>>;
+
+    /// List open PRs authored by `author` (login or username)
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>>;
+
+    /// Login or username of the authenticated user
+    async fn current_user(&self) -> Result<String>;
+
+    /// Create a new PR with default options (empty body, non-draft).
+    ///
+    /// This is a convenience method that delegates to [`create_pr_with_options`]
+    /// with an empty body and `draft: false`. Implementors should override
+    /// `create_pr_with_options`, not this method.
+    ///
+    /// [`create_pr_with_options`]: Self::create_pr_with_options
+    async fn create_pr(&self, head: &str, base: &str, title: &str) -> Result<PullRequest> {
+        self.create_pr_with_options(head, base, title, "", false)
+            .await
+    }
+
+    /// Create a new PR with a body (description) and explicit draft option.
+    ///
+    /// Implementors must provide this method. The default [`create_pr`] method
+    /// delegates here with an empty body and `draft: false`.
+    ///
+    /// [`create_pr`]: Self::create_pr
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest>;
+
+    /// Update the base branch of an existing PR
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest>;
+
+    /// Get the body (description) of a PR
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String>;
+
+    /// Replace the body (description) of a PR
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()>;
+
+    /// Publish a draft PR (convert to ready for review)
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest>;
+
+    /// Close a PR without merging it
+    async fn close_pr(&self, pr_number: u64) -> Result<()>;
+
+    /// Merge a PR into its base
+    ///
+    /// Returns once the platform accepted the merge; some platforms finish
+    /// merging in the background, so callers poll [`find_latest_pr`] for
+    /// [`PrState::Merged`].
+    ///
+    /// [`find_latest_pr`]: Self::find_latest_pr
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()>;
+
+    /// Commit a merged PR landed as on its base
+    ///
+    /// The merge commit, the squashed commit, or the tip of the rebased
+    /// commits, depending on how it was merged. `None` for PRs that aren't
+    /// merged (or whose platform doesn't say).
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>>;
+
+    /// Whether a PR is approved and its checks have passed
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness>;
+
+    /// Latest review of each reviewer on a PR
+    ///
+    /// Comment-only and dismissed reviews are left out; a reviewer who
+    /// approved and later asked for changes is listed once, as the latter.
+    /// [`ReviewState::from_reviews`] sums them up.
+    ///
+    /// [`ReviewState::from_reviews`]: crate::types::ReviewState::from_reviews
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>>;
+
+    /// Combined CI state of a PR's head commit
+    ///
+    /// The default implementation asks [`get_pr_readiness`]; platforms that
+    /// can skip the review lookup override it.
+    ///
+    /// [`get_pr_readiness`]: Self::get_pr_readiness
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        Ok(self.get_pr_readiness(pr_number).await?.checks)
+    }
+
+    /// API requests left, for platforms that report a budget
+    async fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        Ok(None)
+    }
+
+    /// Add a PR to a team planning view
+    ///
+    /// On GitHub `project` is a Projects (v2) reference, `owner/number`.
+    /// GitLab boards are driven by labels, so there `project` is the label
+    /// of the board list to file the MR under.
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()>;
+
+    /// Request reviews on a PR from users (logins or usernames)
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()>;
+
+    /// Request reviewers, add labels and assign users on a PR
+    ///
+    /// Labels are added to the PR's existing ones; reviewers and assignees
+    /// may replace existing ones where the platform only sets them whole.
+    /// Platforms without labels or assignees fail if asked to set them.
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()>;
+
+    /// Number of open PRs in the repository awaiting review from `reviewer`
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize>;
+
+    /// Open an issue in the repository
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue>;
+
+    /// Get the body of an issue
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String>;
+
+    /// Replace the body of an issue
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()>;
+
+    /// List comments on a PR
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>>;
+
+    /// Create a comment on a PR
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()>;
+
+    /// Update an existing comment on a PR
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()>;
+
+    /// Create or update comments on several PRs, one result per write
+    ///
+    /// The default implementation writes them one at a time; platforms that
+    /// can send several writes in one request override it.
+    async fn write_pr_comments(&self, writes: &[CommentWrite]) -> Vec<Result<()>> {
+        let mut results = Vec::with_capacity(writes.len());
+        for write in writes {
+            let result = match &write.existing {
+                Some(comment) => {
+                    self.update_pr_comment(write.pr_number, comment.id, &write.body)
+                        .await
+                }
+                None => self.create_pr_comment(write.pr_number, &write.body).await,
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Get the platform configuration
+    fn config(&self) -> &PlatformConfig;
+}
@@ -0,0 +1,201 @@
+//! Platform service wrapper for read-only mode
+//!
+//! Forwards every read and turns every write into a skip reported to the
+//! run's [`RunContext`]. Writes that
+//! return a PR or issue get a placeholder built from their arguments, so the
+//! caller carries on as if the write had gone through. Batched comment
+//! writes use the trait's default, so each one is skipped on its own.
+//!
+//! [`RunContext`]: crate::run::RunContext
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::run::RunContext;
+use crate::types::{
+    BranchPrs, ChecksState, Issue, MergeMethod, PlatformConfig, PrComment, PrMetadata, PrReadiness,
+    PrReview, PrState, PullRequest, RateLimit,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Wraps a platform service, skipping its writes
+pub struct ReadOnlyPlatform {
+    inner: Box<dyn PlatformService>,
+    run: Arc<RunContext>,
+}
+
+impl ReadOnlyPlatform {
+    /// Wrap `inner`, reporting skipped writes to `run`
+    pub fn new(inner: Box<dyn PlatformService>, run: Arc<RunContext>) -> Self {
+        Self { inner, run }
+    }
+
+    /// Stand-in for a PR a skipped write would have returned
+    fn placeholder_pr(number: u64, head: &str, base: &str, title: &str) -> PullRequest {
+        PullRequest {
+            number,
+            html_url: String::new(),
+            base_ref: base.to_string(),
+            head_ref: head.to_string(),
+            title: title.to_string(),
+            node_id: None,
+            is_draft: false,
+            updated_at: None,
+        }
+    }
+}
+
+#[async_trait]
+impl PlatformService for ReadOnlyPlatform {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        self.inner.find_existing_pr(head_branch).await
+    }
+
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        self.inner.find_open_prs(head_branch).await
+    }
+
+    async fn find_branch_prs(&self, head_branches: &[String]) -> Result<Vec<BranchPrs>> {
+        self.inner.find_branch_prs(head_branches).await
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        self.inner.find_latest_pr(head_branch).await
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        self.inner.list_open_prs_by_author(author).await
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        self.inner.current_user().await
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        _body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        self.run
+            .skipped(&format!("create PR for {head} onto {base}"));
+        Ok(PullRequest {
+            is_draft: draft,
+            ..Self::placeholder_pr(0, head, base, title)
+        })
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        self.run
+            .skipped(&format!("retarget PR #{pr_number} onto {new_base}"));
+        Ok(Self::placeholder_pr(pr_number, "", new_base, ""))
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        self.inner.get_pr_body(pr_number).await
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, _body: &str) -> Result<()> {
+        self.run
+            .skipped(&format!("update the body of PR #{pr_number}"));
+        Ok(())
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        self.run.skipped(&format!("publish PR #{pr_number}"));
+        Ok(Self::placeholder_pr(pr_number, "", "", ""))
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.run.skipped(&format!("close PR #{pr_number}"));
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        self.run
+            .skipped(&format!("merge PR #{pr_number} ({method})"));
+        Ok(())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        self.inner.get_merge_commit(pr_number).await
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        self.inner.get_pr_readiness(pr_number).await
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        self.inner.list_reviews(pr_number).await
+    }
+
+    async fn get_check_status(&self, pr_number: u64) -> Result<ChecksState> {
+        self.inner.get_check_status(pr_number).await
+    }
+
+    async fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        self.inner.rate_limit().await
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        self.run
+            .skipped(&format!("add PR #{} to {project}", pr.number));
+        Ok(())
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        self.run.skipped(&format!(
+            "request reviews on PR #{pr_number} from {}",
+            reviewers.join(", ")
+        ));
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, _metadata: &PrMetadata) -> Result<()> {
+        self.run
+            .skipped(&format!("set reviewers and labels on PR #{pr_number}"));
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        self.inner.count_review_requests(reviewer).await
+    }
+
+    async fn create_issue(&self, title: &str, _body: &str) -> Result<Issue> {
+        self.run.skipped(&format!("open issue \"{title}\""));
+        Ok(Issue {
+            number: 0,
+            html_url: String::new(),
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        self.inner.get_issue_body(issue_number).await
+    }
+
+    async fn update_issue(&self, issue_number: u64, _body: &str) -> Result<()> {
+        self.run.skipped(&format!("update issue #{issue_number}"));
+        Ok(())
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        self.inner.list_pr_comments(pr_number).await
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, _body: &str) -> Result<()> {
+        self.run.skipped(&format!("comment on PR #{pr_number}"));
+        Ok(())
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, _body: &str) -> Result<()> {
+        self.run
+            .skipped(&format!("update comment {comment_id} on PR #{pr_number}"));
+        Ok(())
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,260 @@
+//! Retrying transient platform failures
+//!
+//! A 502 from the API or a dropped connection halfway through a long sync
+//! shouldn't fail the whole submission. Platform requests are retried with
+//! jittered exponential backoff, and rate-limited ones wait for the time
+//! the platform names (`Retry-After`, or the reset time of the rate limit)
+//! instead of failing. Requests that may have reached the server are only
+//! repeated when that's safe (reads, and `PUT`/`DELETE`); creating a PR is
+//! never retried after a server error, since it may have gone through.
+//!
+//! `--max-retries` and `--retry-budget` bound the retries of each request;
+//! a wait longer than what's left of the budget fails right away. Both come
+//! from the service's [`RunContext`], whose [`RetryObserver`] is told about
+//! every pause.
+
+use crate::error::Result;
+use crate::run::RunContext;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// First backoff delay; doubles up to [`MAX_BACKOFF`]
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest backoff between retries (rate-limit waits can be longer)
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How many times, and for how long, one request is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Total time one request may spend waiting between attempts
+    pub budget: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            budget: Duration::from_secs(120),
+        }
+    }
+}
+
+/// A pause before retrying a platform request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryWait {
+    /// What was being requested, e.g. "GET /projects/.../merge_requests"
+    pub request: String,
+    /// Whether the platform's rate limit was hit (rather than a server or
+    /// connection error)
+    pub rate_limited: bool,
+    /// Retry number, starting at 1
+    pub attempt: u32,
+    /// Pause before the retry
+    pub delay: Duration,
+}
+
+/// Told when a platform request pauses before a retry
+pub trait RetryObserver: Send + Sync {
+    /// About to sleep before retrying
+    fn waiting(&self, wait: &RetryWait);
+}
+
+/// Why a failed request can be tried again
+///
+/// Only the GitHub and GitLab services retry, so builds without either
+/// leave this and [`Retries`] unused.
+#[cfg_attr(not(any(feature = "github", feature = "gitlab")), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transient {
+    /// The rate limit was hit
+    pub rate_limited: bool,
+    /// How long the platform asked to wait, if it said
+    pub retry_after: Option<Duration>,
+}
+
+#[cfg_attr(not(any(feature = "github", feature = "gitlab")), allow(dead_code))]
+impl Transient {
+    /// A server or connection error, retried after backoff
+    pub const fn failure() -> Self {
+        Self {
+            rate_limited: false,
+            retry_after: None,
+        }
+    }
+
+    /// The rate limit was hit; `retry_after` is when it resets, if known
+    pub const fn rate_limited(retry_after: Option<Duration>) -> Self {
+        Self {
+            rate_limited: true,
+            retry_after,
+        }
+    }
+}
+
+/// Retries left for one request under its run's policy
+#[cfg_attr(not(any(feature = "github", feature = "gitlab")), allow(dead_code))]
+pub struct Retries<'a> {
+    run: &'a RunContext,
+    policy: RetryPolicy,
+    request: String,
+    attempt: u32,
+    waited: Duration,
+}
+
+#[cfg_attr(not(any(feature = "github", feature = "gitlab")), allow(dead_code))]
+impl<'a> Retries<'a> {
+    /// Start counting retries of `request` (used in logs and messages)
+    /// under `run`'s policy
+    pub fn new(request: impl Into<String>, run: &'a RunContext) -> Self {
+        Self {
+            run,
+            policy: run.retry_policy(),
+            request: request.into(),
+            attempt: 0,
+            waited: Duration::ZERO,
+        }
+    }
+
+    /// Wait out `transient` before the next attempt; `false` when retries
+    /// or the budget are used up and the failure should be returned
+    pub async fn wait(&mut self, transient: Transient) -> bool {
+        let delay = transient
+            .retry_after
+            .unwrap_or_else(|| backoff(self.attempt));
+        if self.attempt >= self.policy.max_retries || self.waited + delay > self.policy.budget {
+            debug!(request = %self.request, attempt = self.attempt, ?delay, "giving up on retries");
+            return false;
+        }
+
+        self.attempt += 1;
+        self.waited += delay;
+        let wait = RetryWait {
+            request: self.request.clone(),
+            rate_limited: transient.rate_limited,
+            attempt: self.attempt,
+            delay,
+        };
+        debug!(?wait, "platform request failed, retrying");
+        self.run.retry_waiting(&wait);
+        tokio::time::sleep(delay).await;
+        true
+    }
+}
+
+/// Jittered exponential backoff before retry `attempt` (0-based)
+///
+/// Half the delay is fixed and half random, so concurrent requests that
+/// failed together don't retry in lockstep.
+pub fn backoff(attempt: u32) -> Duration {
+    let delay = INITIAL_BACKOFF
+        .saturating_mul(2_u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF);
+    delay / 2 + (delay / 2).mul_f64(jitter())
+}
+
+/// A number in `[0, 1)` that differs between calls
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    f64::from(nanos % 1000) / 1000.0
+}
+
+/// How long the headers of a rate-limited or unavailable response ask to wait
+///
+/// Reads `Retry-After` (seconds or an HTTP date), then the reset time of
+/// GitHub's `X-RateLimit-Reset` or GitLab's `RateLimit-Reset` (Unix seconds).
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    if let Some(value) = header("retry-after") {
+        if let Ok(seconds) = value.trim().parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value) {
+            let at = u64::try_from(date.timestamp()).unwrap_or_default();
+            return Some(Duration::from_secs(at.saturating_sub(now)));
+        }
+    }
+    ["x-ratelimit-reset", "ratelimit-reset"]
+        .iter()
+        .find_map(|name| header(name)?.trim().parse::<u64>().ok())
+        // A second past the reset, so the limit has surely been replenished
+        .map(|reset| Duration::from_secs(reset.saturating_sub(now) + 1))
+}
+
+/// Whether a response is worth retrying, and how
+#[cfg_attr(not(feature = "gitlab"), allow(dead_code))]
+fn response_transient(response: &reqwest::Response, idempotent: bool) -> Option<Transient> {
+    let status = response.status().as_u16();
+    let headers = response.headers();
+    let exhausted = ["x-ratelimit-remaining", "ratelimit-remaining"]
+        .iter()
+        .any(|name| headers.get(*name).is_some_and(|v| v == "0"));
+    match status {
+        429 => Some(Transient::rate_limited(retry_after(headers))),
+        403 if exhausted || headers.contains_key("retry-after") => {
+            Some(Transient::rate_limited(retry_after(headers)))
+        }
+        // 503 with Retry-After means the request was turned away unprocessed
+        503 if headers.contains_key("retry-after") => Some(Transient {
+            rate_limited: false,
+            retry_after: retry_after(headers),
+        }),
+        500 | 502..=504 if idempotent => Some(Transient::failure()),
+        _ => None,
+    }
+}
+
+/// Whether a request error is worth retrying
+#[cfg_attr(not(feature = "gitlab"), allow(dead_code))]
+fn error_transient(error: &reqwest::Error, idempotent: bool) -> Option<Transient> {
+    // A failed connect never reached the server; a timeout may have
+    (error.is_connect() || (idempotent && error.is_timeout())).then(Transient::failure)
+}
+
+/// `RequestBuilder::send`, retrying transient failures
+#[cfg_attr(not(feature = "gitlab"), allow(dead_code))]
+pub trait SendRetrying {
+    /// Send the request, retrying as `run`'s [`RetryPolicy`] allows
+    ///
+    /// Once retries run out the last response is returned as is, for the
+    /// caller's usual status handling.
+    async fn send_retrying(self, run: &RunContext) -> Result<reqwest::Response>;
+}
+
+impl SendRetrying for reqwest::RequestBuilder {
+    async fn send_retrying(self, run: &RunContext) -> Result<reqwest::Response> {
+        let Some(request) = self.try_clone().and_then(|b| b.build().ok()) else {
+            // Streaming bodies can't be sent twice
+            return Ok(self.send().await?);
+        };
+        let idempotent = request.method().is_idempotent();
+        let mut retries = Retries::new(
+            format!("{} {}", request.method(), request.url().path()),
+            run,
+        );
+
+        loop {
+            let Some(attempt) = self.try_clone() else {
+                return Ok(self.send().await?);
+            };
+            let result = attempt.send().await;
+            let transient = match &result {
+                Ok(response) => response_transient(response, idempotent),
+                Err(error) => error_transient(error, idempotent),
+            };
+            match transient {
+                Some(transient) if retries.wait(transient).await => {}
+                _ => return Ok(result?),
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Cleaning up after merged PRs
+//!
+//! `ryu sync --prune` (or `sync-prune = true`) does in one pass what `clean`
+//! and a manual `jj abandon` do separately: the local bookmark of every
+//! merged PR goes, the changes that landed through it are abandoned along
+//! with any the merge left empty, and its branch is deleted on the remote
+//! unless the platform already did.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::state::RyuState;
+use crate::status::merged_bookmarks;
+use crate::types::BranchStack;
+use std::collections::HashSet;
+
+/// A bookmark pruned because its PR was merged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedBookmark {
+    /// The bookmark
+    pub bookmark: String,
+    /// Its merged PR
+    pub pr_number: u64,
+    /// Whether its branch was still on the remote (and is deleted there)
+    pub on_remote: bool,
+}
+
+/// What a prune removed (or would have, in a dry run)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Bookmarks deleted, in stack order
+    pub bookmarks: Vec<PrunedBookmark>,
+    /// Change IDs of the abandoned changes
+    pub abandoned: Vec<String>,
+}
+
+impl PruneReport {
+    /// Whether nothing was pruned
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+}
+
+/// Delete the merged bookmarks of `stacks` and abandon their changes
+///
+/// A segment's changes are abandoned only when every bookmark on it is
+/// merged, and then only those recorded as landed (see [`crate::landed`]) or
+/// left empty; anything else stays for the user to look at. Descendants are
+/// rebased onto the abandoned changes' parents. Bookmarks go first, so
+/// abandoning doesn't move them, and remote branches last: the jj CLI
+/// backend only pushes the deletion of a bookmark that's gone locally.
+pub async fn prune_merged(
+    workspace: &mut JjWorkspace,
+    stacks: &[&BranchStack],
+    platform: &dyn PlatformService,
+    remote: &str,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let segments: Vec<_> = stacks.iter().flat_map(|stack| &stack.segments).collect();
+    // Only pushed bookmarks can have a PR
+    let candidates: Vec<String> = segments
+        .iter()
+        .flat_map(|segment| &segment.bookmarks)
+        .filter(|b| b.has_remote)
+        .map(|b| b.name.clone())
+        .collect();
+    let merged = merged_bookmarks(platform, &candidates).await?;
+    if merged.is_empty() {
+        return Ok(PruneReport::default());
+    }
+    let merged_names: HashSet<&str> = merged.iter().map(|(name, _)| name.as_str()).collect();
+
+    let state = RyuState::load(workspace.workspace_root())?;
+    let mut abandon = Vec::new();
+    for segment in &segments {
+        if !segment
+            .bookmarks
+            .iter()
+            .all(|b| merged_names.contains(b.name.as_str()))
+        {
+            continue;
+        }
+        let commits: Vec<&str> = segment
+            .changes
+            .iter()
+            .map(|change| change.commit_id.as_str())
+            .collect();
+        let empty: HashSet<String> = workspace
+            .resolve_revset(&format!("({}) & empty()", commits.join(" | ")))?
+            .into_iter()
+            .map(|change| change.commit_id)
+            .collect();
+        abandon.extend(segment.changes.iter().filter(|change| {
+            empty.contains(&change.commit_id)
+                || state.landed.get(&change.change_id).is_some_and(|landed| {
+                    segment.bookmarks.iter().any(|b| b.name == landed.bookmark)
+                })
+        }));
+    }
+
+    let mut report = PruneReport {
+        abandoned: abandon.iter().map(|c| c.change_id.clone()).collect(),
+        ..PruneReport::default()
+    };
+    for (bookmark, pr) in &merged {
+        report.bookmarks.push(PrunedBookmark {
+            bookmark: bookmark.clone(),
+            pr_number: pr.number,
+            on_remote: workspace.get_remote_bookmark(bookmark, remote)?.is_some(),
+        });
+    }
+    if dry_run {
+        return Ok(report);
+    }
+
+    for pruned in &report.bookmarks {
+        workspace.delete_bookmark(&pruned.bookmark)?;
+    }
+    if !abandon.is_empty() {
+        let commits: Vec<String> = abandon.iter().map(|c| c.commit_id.clone()).collect();
+        workspace.abandon(&commits)?;
+    }
+    for pruned in report.bookmarks.iter().filter(|p| p.on_remote) {
+        workspace.delete_remote_bookmark(&pruned.bookmark, remote)?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,162 @@
+//! Commits pushed to a PR branch by someone else
+//!
+//! Reviewers sometimes apply suggestions through the platform's web UI,
+//! which adds commits on top of the pushed branch. Pushing the local bookmark
+//! again would drop them, so `sync` remembers where each pushed bookmark was
+//! before fetching, spots branches that moved ahead of that, and rebases the
+//! new commits onto the local change before anything is pushed.
+
+use crate::error::{Error, Result};
+use crate::graph::ahead_behind;
+use crate::repo::JjWorkspace;
+use crate::types::BranchStack;
+
+/// A pushed bookmark as it was before fetching
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushedHead {
+    /// The bookmark
+    pub bookmark: String,
+    /// Local commit
+    pub local: String,
+    /// Commit on the remote
+    pub remote: String,
+}
+
+/// Commits that appeared on a bookmark's remote branch since the last fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteCommits {
+    /// The bookmark
+    pub bookmark: String,
+    /// Local commit before fetching
+    pub local: String,
+    /// Commit on the remote before fetching
+    pub previous: String,
+    /// Commit on the remote now
+    pub remote: String,
+    /// Authors of the new commits, oldest first, without repeats
+    pub authors: Vec<String>,
+    /// Number of new commits
+    pub count: usize,
+}
+
+/// Record where the pushed bookmarks of `stacks` are, ahead of a fetch
+pub fn pushed_heads(
+    workspace: &JjWorkspace,
+    stacks: &[&BranchStack],
+    remote: &str,
+) -> Result<Vec<PushedHead>> {
+    let mut heads = Vec::new();
+    for bookmark in stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.bookmarks)
+        .filter(|b| b.has_remote)
+    {
+        if let Some(on_remote) = workspace.get_remote_bookmark(&bookmark.name, remote)? {
+            heads.push(PushedHead {
+                bookmark: bookmark.name.clone(),
+                local: bookmark.commit_id.clone(),
+                remote: on_remote.commit_id,
+            });
+        }
+    }
+    Ok(heads)
+}
+
+/// Bookmarks whose remote branch gained commits since `heads` was recorded
+///
+/// Only branches that moved forward count: a rewritten remote branch is
+/// left for the force-push checks, and one the local change already
+/// contains needs nothing pulled.
+pub fn find_remote_commits(
+    workspace: &JjWorkspace,
+    heads: &[PushedHead],
+    remote: &str,
+) -> Result<Vec<RemoteCommits>> {
+    let mut found = Vec::new();
+    for head in heads {
+        let Some(now) = workspace.get_remote_bookmark(&head.bookmark, remote)? else {
+            continue;
+        };
+        if now.commit_id == head.remote {
+            continue;
+        }
+        let moved = ahead_behind(workspace, &now.commit_id, &head.remote)?;
+        if moved.ahead == 0 || !moved.is_fast_forward() {
+            continue;
+        }
+        if ahead_behind(workspace, &head.local, &now.commit_id)?.is_fast_forward() {
+            continue;
+        }
+
+        let mut authors: Vec<String> = Vec::new();
+        let added = workspace.resolve_revset(&format!("{}..{}", head.remote, now.commit_id))?;
+        for entry in added.iter().rev() {
+            if !authors.contains(&entry.author_name) {
+                authors.push(entry.author_name.clone());
+            }
+        }
+        found.push(RemoteCommits {
+            bookmark: head.bookmark.clone(),
+            local: head.local.clone(),
+            previous: head.remote.clone(),
+            remote: now.commit_id,
+            authors,
+            count: moved.ahead,
+        });
+    }
+    Ok(found)
+}
+
+/// Rebase the new remote commits onto the local change and move the
+/// bookmark to the last of them
+///
+/// Whatever was stacked on the local change moves on top, so the stack
+/// stays linear. When the local change is still what was pushed the new
+/// commits already sit on it and only the bookmark and its children move.
+pub fn pull_remote_commits(workspace: &mut JjWorkspace, commits: &RemoteCommits) -> Result<()> {
+    let RemoteCommits {
+        local,
+        previous,
+        remote,
+        ..
+    } = commits;
+    let upstack: Vec<String> = workspace
+        .resolve_revset(&format!("children({local}) ~ ::{remote}"))?
+        .into_iter()
+        .map(|entry| entry.commit_id)
+        .collect();
+
+    let head = if local == previous {
+        remote.clone()
+    } else {
+        let roots = workspace.resolve_revset(&format!("roots({previous}..{remote})"))?;
+        let [root] = roots.as_slice() else {
+            return Err(Error::Workspace(format!(
+                "the commits pushed to {} don't form a single line; rebase them with jj",
+                commits.bookmark
+            )));
+        };
+        workspace.rebase_onto(&root.commit_id, local)?;
+
+        let mut pulled = format!("{local}:: ~ {local}");
+        if !upstack.is_empty() {
+            pulled = format!("({pulled}) ~ ({})::", upstack.join(" | "));
+        }
+        match workspace
+            .resolve_revset(&format!("heads({pulled})"))?
+            .into_iter()
+            .next()
+        {
+            Some(head) => head.commit_id,
+            // Nothing was rebased, e.g. in read-only mode
+            None => return Ok(()),
+        }
+    };
+
+    workspace.set_bookmark(&commits.bookmark, &head)?;
+    for child in &upstack {
+        workspace.rebase_onto(child, &head)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Read-only mode
+//!
+//! `--read-only` (or `RYU_READ_ONLY=1`, or `read-only = true` in the config
+//! files) guarantees a run changes nothing: every platform write and every
+//! repo mutation becomes a logged no-op.
+//! Unlike `--dry-run`, which each command honours by not planning the
+//! writes, this is enforced where the writes happen - in [`ReadOnlyPlatform`]
+//! and in [`JjWorkspace`]'s mutating methods - so it also covers commands
+//! without a dry run and code paths added later. Reads still go through, so
+//! a read-only run shows what it would have done against real data. ryu's
+//! own state and submission journals aren't saved either, so nothing a
+//! read-only run skipped is remembered as done. Each skipped write is
+//! reported through the run's [`RunContext`].
+//!
+//! [`RunContext`]: crate::run::RunContext
+//! [`ReadOnlyPlatform`]: crate::platform::ReadOnlyPlatform
+//! [`JjWorkspace`]: crate::repo::JjWorkspace
+
+use crate::error::{Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable turning read-only mode on
+pub const READ_ONLY_ENV: &str = "RYU_READ_ONLY";
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Told about each write read-only mode skips
+pub trait ReadOnlyObserver: Send + Sync {
+    /// `action` (e.g. "push feat-a to origin") was not carried out
+    fn skipped(&self, action: &str);
+}
+
+/// Turn read-only mode on or off for the process
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+/// Whether writes are being skipped
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Whether `RYU_READ_ONLY` asks for read-only mode; `None` when it's unset
+pub fn read_only_from_env() -> Result<Option<bool>> {
+    let Ok(value) = std::env::var(READ_ONLY_ENV) else {
+        return Ok(None);
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(Some(true)),
+        "" | "false" | "0" | "no" | "off" => Ok(Some(false)),
+        _ => Err(Error::Config(format!(
+            "{READ_ONLY_ENV} must be true or false, got '{value}'"
+        ))),
+    }
+}
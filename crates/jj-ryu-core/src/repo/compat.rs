@@ -0,0 +1,85 @@
+//! Compatibility between the installed jj and the linked jj-lib
+//!
+//! ryu reads the repository through the jj-lib it was built with. A newer jj
+//! may write operation or view data that this jj-lib cannot decode, which
+//! otherwise surfaces as an opaque deserialization error.
+
+use std::fmt;
+use std::process::Command;
+
+/// jj release series (`major.minor`) matching the linked jj-lib
+///
+/// Keep in sync with the `jj-lib` dependency in crates/jj-ryu-core/Cargo.toml.
+pub const SUPPORTED_JJ_VERSION: (u64, u64) = (0, 36);
+
+/// How the installed jj relates to the linked jj-lib
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JjCompatibility {
+    /// Same release series
+    Compatible,
+    /// Installed jj is older; it may not read data ryu writes
+    Older(String),
+    /// Installed jj is newer; ryu may not read data it writes
+    Newer(String),
+    /// jj is not installed or its version could not be parsed
+    Unknown,
+}
+
+/// Human-readable supported jj version, e.g. `0.36`
+pub fn supported_jj_version() -> String {
+    let (major, minor) = SUPPORTED_JJ_VERSION;
+    format!("{major}.{minor}")
+}
+
+/// Output of `jj --version`, if jj is installed
+pub fn installed_jj_version() -> Option<String> {
+    Command::new("jj")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Parse `major.minor.patch` from `jj --version` output
+///
+/// Accepts `jj 0.36.0`, `jj 0.36.0-<commit hash>`, or a bare version.
+pub fn parse_jj_version(output: &str) -> Option<(u64, u64, u64)> {
+    let version = output
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(str::parse::<u64>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next().and_then(Result::ok).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Compare a `jj --version` output against [`SUPPORTED_JJ_VERSION`]
+pub fn check_jj_compatibility(installed: Option<&str>) -> JjCompatibility {
+    let Some(output) = installed else {
+        return JjCompatibility::Unknown;
+    };
+    let Some((major, minor, patch)) = parse_jj_version(output) else {
+        return JjCompatibility::Unknown;
+    };
+    let version = format!("{major}.{minor}.{patch}");
+    match (major, minor).cmp(&SUPPORTED_JJ_VERSION) {
+        std::cmp::Ordering::Equal => JjCompatibility::Compatible,
+        std::cmp::Ordering::Less => JjCompatibility::Older(version),
+        std::cmp::Ordering::Greater => JjCompatibility::Newer(version),
+    }
+}
+
+impl fmt::Display for JjCompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let supported = supported_jj_version();
+        match self {
+            Self::Compatible => write!(f, "jj {supported} (compatible)"),
+            Self::Older(v) => write!(f, "jj {v} (older than supported jj {supported})"),
+            Self::Newer(v) => write!(f, "jj {v} (newer than supported jj {supported})"),
+            Self::Unknown => write!(f, "jj version unknown"),
+        }
+    }
+}
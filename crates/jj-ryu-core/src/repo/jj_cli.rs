@@ -0,0 +1,542 @@
+//! Degraded repository access through the `jj` CLI
+//!
+//! Used when the linked jj-lib can't open a repo (version skew, backends it
+//! wasn't built with). Every query runs `jj` with a template that prints one
+//! JSON object per line, so results don't depend on jj's human output format.
+
+use crate::error::{Error, Result};
+use crate::types::{Bookmark, GitRemote, LogEntry};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::debug;
+
+/// Bookmark names tried when `trunk()` has no remote bookmarks
+const TRUNK_NAMES: &[&str] = &["main", "master", "trunk"];
+
+/// Pseudo-remote jj uses for the backing git repo's own refs
+const GIT_PSEUDO_REMOTE: &str = "git";
+
+/// One row of `jj bookmark list --all-remotes`
+#[derive(Debug, Deserialize)]
+struct BookmarkRef {
+    name: String,
+    /// Empty for local bookmarks
+    remote: String,
+    /// `None` for conflicted or deleted bookmarks
+    commit_id: Option<String>,
+    change_id: Option<String>,
+}
+
+/// Repository accessed by running the `jj` binary
+pub struct JjCli {
+    root: PathBuf,
+    /// Revset `trunk()` resolves to, overriding jj's config
+    trunk_revset: Option<String>,
+}
+
+impl JjCli {
+    /// Locate the workspace containing `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let output = Command::new("jj")
+            .args(["root", "--ignore-working-copy"])
+            .current_dir(path)
+            .output()
+            .map_err(|e| Error::Workspace(format!("Failed to run jj: {e}")))?;
+        if !output.status.success() {
+            return Err(Error::Workspace(format!(
+                "jj root failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Self {
+            root: PathBuf::from(root),
+            trunk_revset: None,
+        })
+    }
+
+    /// Make `trunk()` resolve to `revset` in every jj invocation
+    pub fn set_trunk_revset(&mut self, revset: Option<String>) {
+        self.trunk_revset = revset;
+    }
+
+    /// Workspace root path
+    pub fn workspace_root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Run jj against this workspace, returning stdout or stderr on failure
+    ///
+    /// Like the jj-lib backend, this reads the repo at its head operation
+    /// without snapshotting the working copy.
+    fn jj(&self, args: &[&str]) -> std::result::Result<String, String> {
+        self.run_jj(args, true)
+    }
+
+    /// Run jj like [`Self::jj`], but snapshotting the working copy first
+    /// and updating it after, as jj does when run by hand
+    fn jj_with_working_copy(&self, args: &[&str]) -> std::result::Result<String, String> {
+        self.run_jj(args, false)
+    }
+
+    fn run_jj(
+        &self,
+        args: &[&str],
+        ignore_working_copy: bool,
+    ) -> std::result::Result<String, String> {
+        debug!(?args, "running jj");
+        // Run from the root so any paths jj prints are repo-relative
+        let mut command = Command::new("jj");
+        command
+            .arg("--repository")
+            .arg(&self.root)
+            .current_dir(&self.root)
+            .args(["--no-pager", "--color", "never"]);
+        if ignore_working_copy {
+            command.arg("--ignore-working-copy");
+        }
+        if let Some(trunk) = &self.trunk_revset {
+            let value = toml::Value::String(trunk.clone());
+            command
+                .arg("--config")
+                .arg(format!("revset-aliases.\"trunk()\"={value}"));
+        }
+        let output = command
+            .args(args)
+            .output()
+            .map_err(|e| format!("failed to run jj: {e}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// Every local and remote bookmark ref
+    fn bookmark_refs(&self) -> Result<Vec<BookmarkRef>> {
+        let template = json_template(&[
+            ("name", "stringify(name).escape_json()"),
+            ("remote", "stringify(remote).escape_json()"),
+            (
+                "commit_id",
+                r#"if(normal_target, stringify(normal_target.commit_id()).escape_json(), "null")"#,
+            ),
+            (
+                "change_id",
+                r#"if(normal_target, normal_target.change_id().normal_hex().escape_json(), "null")"#,
+            ),
+        ]);
+        let stdout = self
+            .jj(&["bookmark", "list", "--all-remotes", "-T", &template])
+            .map_err(|e| Error::Workspace(format!("Failed to list bookmarks: {e}")))?;
+        parse_json_lines(&stdout)
+    }
+
+    /// Get all local bookmarks
+    pub fn local_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        Ok(bookmarks_from_refs(&self.bookmark_refs()?))
+    }
+
+    /// Get a specific local bookmark
+    pub fn get_local_bookmark(&self, name: &str) -> Result<Option<Bookmark>> {
+        Ok(self
+            .local_bookmarks()?
+            .into_iter()
+            .find(|bookmark| bookmark.name == name))
+    }
+
+    /// Get a remote bookmark
+    pub fn get_remote_bookmark(&self, name: &str, remote: &str) -> Result<Option<Bookmark>> {
+        Ok(self
+            .bookmark_refs()?
+            .into_iter()
+            .find(|r| r.name == name && r.remote == remote)
+            .and_then(|r| {
+                Some(Bookmark {
+                    name: r.name,
+                    commit_id: r.commit_id?,
+                    change_id: r.change_id?,
+                    has_remote: true,
+                    is_synced: true,
+                })
+            }))
+    }
+
+    /// Resolve a revset expression to commits
+    pub fn resolve_revset(&self, expr: &str) -> Result<Vec<LogEntry>> {
+        let stdout = self
+            .jj(&["log", "--no-graph", "-r", expr, "-T", &log_entry_template()])
+            .map_err(|e| Error::Revset(format!("Failed to resolve revset: {e}")))?;
+        parse_json_lines(&stdout)
+    }
+
+    /// Paths changed by a commit relative to its parents
+    pub fn changed_paths(&self, commit_id: &str) -> Result<Vec<String>> {
+        let stdout = self
+            .jj(&["diff", "--name-only", "-r", commit_id])
+            .map_err(|e| Error::Workspace(format!("Failed to diff commit: {e}")))?;
+        Ok(stdout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.replace('\\', "/"))
+            .collect())
+    }
+
+    /// Paths whose contents differ between two commits
+    pub fn diff_paths(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let stdout = self
+            .jj(&["diff", "--name-only", "--from", from, "--to", to])
+            .map_err(|e| Error::Workspace(format!("Failed to diff {from} and {to}: {e}")))?;
+        Ok(stdout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.replace('\\', "/"))
+            .collect())
+    }
+
+    /// Commit that last changed line `line` (1-based) of `path` at `@`
+    pub fn line_origin(&self, path: &str, line: usize) -> Result<Option<String>> {
+        let stdout = self
+            .jj(&[
+                "file",
+                "annotate",
+                "-r",
+                "@",
+                "-T",
+                r#"commit.commit_id() ++ "\n""#,
+                path,
+            ])
+            .map_err(|e| Error::Workspace(format!("Failed to annotate {path}: {e}")))?;
+        Ok(line
+            .checked_sub(1)
+            .and_then(|index| stdout.lines().nth(index))
+            .map(|id| id.trim().to_string()))
+    }
+
+    /// Get all git remotes
+    pub fn git_remotes(&self) -> Result<Vec<GitRemote>> {
+        let stdout = self
+            .jj(&["git", "remote", "list"])
+            .map_err(|e| Error::Git(format!("Failed to list remotes: {e}")))?;
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.trim().split_once(' '))
+            .map(|(name, url)| GitRemote {
+                name: name.to_string(),
+                url: url.trim().to_string(),
+            })
+            .collect())
+    }
+
+    /// Fetch from a git remote
+    pub fn git_fetch(&self, remote: &str) -> Result<()> {
+        self.jj(&["git", "fetch", "--remote", remote])
+            .map_err(|e| Error::Git(format!("Failed to fetch: {e}")))?;
+        Ok(())
+    }
+
+    /// Fetch only the named branches from a git remote
+    pub fn git_fetch_branches(&self, remote: &str, branches: &[String]) -> Result<()> {
+        let patterns: Vec<String> = branches.iter().map(|b| format!("exact:{b}")).collect();
+        let mut args = vec!["git", "fetch", "--remote", remote];
+        for pattern in &patterns {
+            args.extend(["--branch", pattern.as_str()]);
+        }
+        self.jj(&args)
+            .map_err(|e| Error::Git(format!("Failed to fetch: {e}")))?;
+        Ok(())
+    }
+
+    /// Fetch several remotes in one `jj git fetch`
+    pub fn git_fetch_all(&self, remotes: &[String]) -> Result<()> {
+        let mut args = vec!["git", "fetch"];
+        for remote in remotes {
+            args.extend(["--remote", remote.as_str()]);
+        }
+        self.jj(&args)
+            .map_err(|e| Error::Git(format!("Failed to fetch: {e}")))?;
+        Ok(())
+    }
+
+    /// Push a bookmark to a remote
+    pub fn git_push(&self, bookmark: &str, remote: &str) -> Result<()> {
+        self.git_push_many(&[bookmark], remote)
+    }
+
+    /// Push several bookmarks to a remote in one `jj git push`
+    pub fn git_push_many(&self, bookmarks: &[&str], remote: &str) -> Result<()> {
+        let mut args = vec!["git", "push", "--remote", remote];
+        for bookmark in bookmarks {
+            args.extend(["--bookmark", bookmark]);
+        }
+        match self.jj(&args) {
+            Ok(_) => Ok(()),
+            // jj releases that require opting in to pushing new bookmarks
+            Err(e) if e.contains("--allow-new") => {
+                args.push("--allow-new");
+                self.jj(&args)
+                    .map(|_| ())
+                    .map_err(|e| Error::Git(format!("Failed to push: {e}")))
+            }
+            Err(e) => Err(Error::Git(format!("Failed to push: {e}"))),
+        }
+    }
+
+    /// Delete a bookmark's branch on a remote, once deleted locally
+    pub fn delete_remote_bookmark(&self, bookmark: &str, remote: &str) -> Result<()> {
+        if self.get_local_bookmark(bookmark)?.is_some() {
+            return Err(Error::Git(format!(
+                "Can't delete {bookmark}@{remote} while {bookmark} exists locally"
+            )));
+        }
+        self.jj(&[
+            "git",
+            "push",
+            "--remote",
+            remote,
+            "--bookmark",
+            &format!("exact:{bookmark}"),
+        ])
+        .map_err(|e| Error::Git(format!("Failed to delete remote branch: {e}")))?;
+        Ok(())
+    }
+
+    /// Delete a local bookmark (remote-tracking bookmarks are left alone)
+    pub fn delete_bookmark(&self, bookmark: &str) -> Result<()> {
+        if self.get_local_bookmark(bookmark)?.is_none() {
+            return Err(Error::BookmarkNotFound(bookmark.to_string()));
+        }
+        // `forget` would drop the remote-tracking bookmark too
+        self.jj(&["bookmark", "delete", &format!("exact:{bookmark}")])
+            .map_err(|e| Error::Workspace(format!("Failed to delete bookmark: {e}")))?;
+        Ok(())
+    }
+
+    /// Create a local bookmark pointing at `commit_id`
+    pub fn create_bookmark(&self, bookmark: &str, commit_id: &str) -> Result<()> {
+        self.jj(&["bookmark", "create", bookmark, "-r", commit_id])
+            .map_err(|e| Error::Workspace(format!("Failed to create bookmark: {e}")))?;
+        Ok(())
+    }
+
+    /// Track a remote bookmark, creating the local bookmark if there is none
+    pub fn track_bookmark(&self, bookmark: &str, remote: &str) -> Result<()> {
+        self.jj(&["bookmark", "track", &format!("exact:{bookmark}@{remote}")])
+            .map_err(|e| Error::Workspace(format!("Failed to track bookmark: {e}")))?;
+        Ok(())
+    }
+
+    /// Point a local bookmark at `commit_id`, creating it if needed
+    pub fn set_bookmark(&self, bookmark: &str, commit_id: &str) -> Result<()> {
+        self.jj(&[
+            "bookmark",
+            "set",
+            bookmark,
+            "-r",
+            commit_id,
+            "--allow-backwards",
+        ])
+        .map_err(|e| Error::Workspace(format!("Failed to set bookmark: {e}")))?;
+        Ok(())
+    }
+
+    /// Rename a local bookmark, keeping its target
+    pub fn rename_bookmark(&self, old: &str, new: &str) -> Result<()> {
+        self.jj(&["bookmark", "rename", old, new])
+            .map_err(|e| Error::Workspace(format!("Failed to rename bookmark: {e}")))?;
+        Ok(())
+    }
+
+    /// Create an empty change on `parent` without editing it; returns its
+    /// commit ID
+    pub fn new_change(&self, parent: &str, description: &str) -> Result<String> {
+        self.jj(&["new", "--no-edit", parent, "-m", description])
+            .map_err(|e| Error::Workspace(format!("Failed to create change: {e}")))?;
+        // The newest child of the parent is the one just created
+        let stdout = self
+            .jj(&[
+                "log",
+                "--no-graph",
+                "-r",
+                &format!("latest(children({parent}))"),
+                "-T",
+                "commit_id",
+            ])
+            .map_err(|e| Error::Workspace(format!("Failed to read new change: {e}")))?;
+        Ok(stdout.trim().to_string())
+    }
+
+    /// ID of the repo's head operation (hex)
+    pub fn operation_id(&self) -> Result<String> {
+        let stdout = self
+            .jj(&["operation", "log", "--no-graph", "--limit", "1", "-T", "id"])
+            .map_err(|e| Error::Workspace(format!("Failed to read operation log: {e}")))?;
+        Ok(stdout.trim().to_string())
+    }
+
+    /// ID and description of the repo's head operation
+    pub fn head_operation(&self) -> Result<(String, String)> {
+        let stdout = self
+            .jj(&[
+                "operation",
+                "log",
+                "--no-graph",
+                "--limit",
+                "1",
+                "-T",
+                r#"id ++ "\t" ++ description.first_line()"#,
+            ])
+            .map_err(|e| Error::Workspace(format!("Failed to read operation log: {e}")))?;
+        let (id, description) = stdout
+            .trim()
+            .split_once('\t')
+            .unwrap_or((stdout.trim(), ""));
+        Ok((id.to_string(), description.to_string()))
+    }
+
+    /// Rebase `root` and its descendants onto `onto`
+    pub fn rebase_onto(&self, root: &str, onto: &str) -> Result<()> {
+        self.jj(&["rebase", "-s", root, "-d", onto])
+            .map_err(|e| Error::Workspace(format!("Failed to rebase {root}: {e}")))?;
+        Ok(())
+    }
+
+    /// Abandon `commits`, rebasing their descendants onto their parents
+    pub fn abandon(&self, commits: &[String]) -> Result<()> {
+        let mut args = vec!["abandon"];
+        args.extend(commits.iter().map(String::as_str));
+        self.jj(&args)
+            .map_err(|e| Error::Workspace(format!("Failed to abandon changes: {e}")))?;
+        Ok(())
+    }
+
+    /// ID of the repo's head operation once the working copy is snapshotted
+    pub fn snapshot_operation_id(&self) -> Result<String> {
+        let stdout = self
+            .jj_with_working_copy(&["operation", "log", "--no-graph", "--limit", "1", "-T", "id"])
+            .map_err(|e| Error::Workspace(format!("Failed to read operation log: {e}")))?;
+        Ok(stdout.trim().to_string())
+    }
+
+    /// Restore the repo's commits and local bookmarks to operation `op_id`,
+    /// updating the working copy to match
+    pub fn restore_operation(&self, op_id: &str) -> Result<()> {
+        self.jj_with_working_copy(&["operation", "restore", "--what", "repo", op_id])
+            .map_err(|e| Error::Workspace(format!("Failed to restore operation {op_id}: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the default branch name from `trunk()`, then common names
+    pub fn default_branch(&self) -> Result<String> {
+        let template = r#"remote_bookmarks.map(|b| stringify(b.name()) ++ "@" ++ stringify(b.remote())).join("\n") ++ "\n""#;
+        let trunk = self
+            .jj(&["log", "--no-graph", "-r", "trunk()", "-T", template])
+            .unwrap_or_default();
+        let from_trunk = trunk
+            .lines()
+            .filter_map(|symbol| symbol.rsplit_once('@'))
+            .find(|(_, remote)| *remote != GIT_PSEUDO_REMOTE)
+            .map(|(name, _)| name.to_string());
+        if let Some(branch) = from_trunk {
+            return Ok(branch);
+        }
+
+        let local = self.local_bookmarks()?;
+        Ok(TRUNK_NAMES
+            .iter()
+            .copied()
+            .find(|name| local.iter().any(|b| b.name == *name))
+            .unwrap_or("main")
+            .to_string())
+    }
+}
+
+/// Local bookmarks with remote tracking state, as the jj-lib backend reports them
+fn bookmarks_from_refs(refs: &[BookmarkRef]) -> Vec<Bookmark> {
+    let remotes: Vec<&BookmarkRef> = refs
+        .iter()
+        .filter(|r| !r.remote.is_empty() && r.remote != GIT_PSEUDO_REMOTE)
+        .collect();
+
+    refs.iter()
+        .filter(|r| r.remote.is_empty())
+        .filter_map(|local| {
+            let commit_id = local.commit_id.clone()?;
+            let change_id = local.change_id.clone()?;
+            let same_name = || remotes.iter().filter(|r| r.name == local.name);
+            Some(Bookmark {
+                name: local.name.clone(),
+                has_remote: same_name().next().is_some(),
+                is_synced: same_name().any(|r| r.commit_id.as_ref() == Some(&commit_id)),
+                commit_id,
+                change_id,
+            })
+        })
+        .collect()
+}
+
+/// Template printing a [`LogEntry`] as one JSON line
+fn log_entry_template() -> String {
+    const TIMESTAMP: &str = r#""%Y-%m-%dT%H:%M:%S%:z""#;
+    json_template(&[
+        ("commit_id", "stringify(commit_id).escape_json()"),
+        ("change_id", "change_id.normal_hex().escape_json()"),
+        ("author_name", "stringify(author.name()).escape_json()"),
+        ("author_email", "stringify(author.email()).escape_json()"),
+        (
+            "description_first_line",
+            "description.first_line().escape_json()",
+        ),
+        ("description", "description.escape_json()"),
+        (
+            "parents",
+            r#""[" ++ parents.map(|c| stringify(c.commit_id()).escape_json()).join(",") ++ "]""#,
+        ),
+        (
+            "local_bookmarks",
+            r#""[" ++ local_bookmarks.map(|b| stringify(b.name()).escape_json()).join(",") ++ "]""#,
+        ),
+        (
+            "remote_bookmarks",
+            r#""[" ++ remote_bookmarks.map(|b| stringify(b.name() ++ "@" ++ b.remote()).escape_json()).join(",") ++ "]""#,
+        ),
+        (
+            "is_working_copy",
+            r#"if(current_working_copy, "true", "false")"#,
+        ),
+        (
+            "authored_at",
+            &format!("stringify(author.timestamp().format({TIMESTAMP})).escape_json()"),
+        ),
+        (
+            "committed_at",
+            &format!("stringify(committer.timestamp().format({TIMESTAMP})).escape_json()"),
+        ),
+    ])
+}
+
+/// Build a jj template printing a JSON object per line
+///
+/// Each field pairs a key with a template expression that renders valid JSON.
+fn json_template(fields: &[(&str, &str)]) -> String {
+    let mut template = String::from(r#""{""#);
+    for (i, (key, expr)) in fields.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        let _ = write!(template, r#" ++ "{sep}\"{key}\":" ++ {expr}"#);
+    }
+    template.push_str(r#" ++ "}\n""#);
+    template
+}
+
+fn parse_json_lines<T: for<'de> Deserialize<'de>>(stdout: &str) -> Result<Vec<T>> {
+    stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| Error::Parse(format!("Unexpected jj output {line:?}: {e}")))
+        })
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Waiting out lock contention on the repo
+//!
+//! Editor integrations and other jj commands briefly lock the operation
+//! store, the working copy or git refs. Instead of failing on the first
+//! "locked" error, mutating [`JjWorkspace`] calls retry with exponential
+//! backoff and tell the run's [`LockWaitObserver`] while they wait.
+//!
+//! [`JjWorkspace`]: super::JjWorkspace
+
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::run::RunContext;
+use std::time::Duration;
+use tracing::debug;
+
+/// Environment variable bounding how long to wait for a lock, in seconds
+pub const LOCK_TIMEOUT_ENV: &str = "RYU_LOCK_TIMEOUT";
+
+/// Default for `lock-timeout` and [`LOCK_TIMEOUT_ENV`]
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// First retry delay; doubles up to [`MAX_DELAY`]
+const INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest pause between retries
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Error text (lowercased) from jj, jj-lib and git that means "locked by
+/// someone else, try again"
+const CONTENTION_MARKERS: &[&str] = &[
+    "index.lock",
+    ".lock': file exists",
+    "cannot lock ref",
+    "unable to lock",
+    "failed to lock",
+    "is locked",
+    "lock is held",
+    "resource temporarily unavailable",
+    "resource busy",
+];
+
+/// Whether an error message means the repo was locked by another process
+pub fn is_lock_contention(message: &str) -> bool {
+    let message = message.to_lowercase();
+    CONTENTION_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+/// A retry that's about to sleep because the repo is locked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockWait {
+    /// What ryu was doing, e.g. "push feat-a"
+    pub action: String,
+    /// Retry number, starting at 1
+    pub attempt: u32,
+    /// Pause before the retry
+    pub delay: Duration,
+    /// Description of the operation another process committed while ryu
+    /// waited, if one did
+    pub competing_operation: Option<String>,
+}
+
+/// Told when ryu starts and stops waiting for a lock
+pub trait LockWaitObserver: Send + Sync {
+    /// About to sleep before retrying
+    fn waiting(&self, wait: &LockWait);
+    /// The lock was acquired or ryu gave up
+    fn finished(&self);
+}
+
+/// How long to keep retrying (`lock-timeout` seconds, default 30; 0
+/// disables retrying)
+///
+/// `RYU_LOCK_TIMEOUT` wins over the config files.
+pub fn lock_timeout(config: &RyuConfig) -> Result<Duration> {
+    let Ok(value) = std::env::var(LOCK_TIMEOUT_ENV) else {
+        return Ok(config
+            .lock_timeout
+            .map_or(DEFAULT_LOCK_TIMEOUT, Duration::from_secs));
+    };
+    value
+        .trim()
+        .parse::<u64>()
+        .map(Duration::from_secs)
+        .map_err(|_| {
+            Error::Config(format!(
+                "{LOCK_TIMEOUT_ENV} must be a number of seconds, got '{value}'"
+            ))
+        })
+}
+
+/// Delays between retries that fit in `timeout`
+pub fn backoff_delays(timeout: Duration) -> Vec<Duration> {
+    let mut delays = Vec::new();
+    let mut total = Duration::ZERO;
+    let mut delay = INITIAL_DELAY;
+    while total + delay <= timeout {
+        delays.push(delay);
+        total += delay;
+        delay = (delay * 2).min(MAX_DELAY);
+    }
+    delays
+}
+
+/// Run `op` on `target`, retrying while it fails with lock contention for
+/// up to `timeout` and telling `run` about each wait
+///
+/// `head_operation` gives the ID and description of the repo's head
+/// operation. It's first read when the lock is found taken, so an operation
+/// the other process commits while ryu waits can be named.
+pub(crate) fn retry_on_lock<S, T>(
+    action: &str,
+    timeout: Duration,
+    run: &RunContext,
+    target: &mut S,
+    head_operation: impl Fn(&S) -> Option<(String, String)>,
+    mut op: impl FnMut(&mut S) -> Result<T>,
+) -> Result<T> {
+    let mut delays = backoff_delays(timeout).into_iter();
+    let mut start_op: Option<Option<String>> = None;
+    let mut attempt = 0;
+
+    let result = loop {
+        match op(target) {
+            Err(err) if is_lock_contention(&err.to_string()) => {
+                let Some(delay) = delays.next() else {
+                    break Err(err);
+                };
+                attempt += 1;
+                let head = head_operation(target);
+                let start_id =
+                    start_op.get_or_insert_with(|| head.as_ref().map(|(id, _)| id.clone()));
+                let competing_operation = head
+                    .filter(|(id, _)| start_id.as_ref() != Some(id))
+                    .map(|(_, description)| description);
+                let wait = LockWait {
+                    action: action.to_string(),
+                    attempt,
+                    delay,
+                    competing_operation,
+                };
+                debug!(?wait, %err, "repo locked, retrying");
+                run.lock_waiting(&wait);
+                std::thread::sleep(delay);
+            }
+            other => break other,
+        }
+    };
+
+    if attempt > 0 {
+        run.lock_wait_finished();
+    }
+    result
+}
@@ -0,0 +1,21 @@
+//! jj-lib workspace wrapper
+//!
+//! Provides a high-level interface to jj repository operations.
+
+mod compat;
+mod jj_cli;
+mod lock_wait;
+mod workspace;
+
+pub use compat::{
+    JjCompatibility, SUPPORTED_JJ_VERSION, check_jj_compatibility, installed_jj_version,
+    parse_jj_version, supported_jj_version,
+};
+pub use lock_wait::{
+    LOCK_TIMEOUT_ENV, LockWait, LockWaitObserver, backoff_delays, is_lock_contention, lock_timeout,
+};
+pub use workspace::{
+    JJ_BACKEND_ENV, JjBackend, JjWorkspace, MIRROR_REMOTES_ENV, PR_REMOTES_ENV, UPSTREAM_REMOTE,
+    requested_remotes, select_fetch_remotes, select_mirror_remotes, select_primary_remote,
+    select_push_remote, select_remote,
+};
@@ -0,0 +1,1885 @@
+//! `JjWorkspace` - wrapper around jj-lib for repository operations
+//!
+//! Falls back to driving the `jj` CLI when jj-lib can't open the repo.
+
+use super::compat::{
+    JjCompatibility, check_jj_compatibility, installed_jj_version, supported_jj_version,
+};
+use super::jj_cli::JjCli;
+use super::lock_wait::{lock_timeout, retry_on_lock};
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::platform::{parse_url_rewrites, rewrite_remote_url};
+use crate::read_only::{is_read_only, read_only_from_env, set_read_only};
+use crate::run::RunContext;
+use crate::types::{Bookmark, GitRemote, LogEntry};
+use crate::undo::RemoteChange;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::StreamExt as _;
+use futures::executor::block_on;
+use jj_lib::annotate::FileAnnotator;
+use jj_lib::backend::{CommitId, Timestamp};
+use jj_lib::commit::Commit;
+use jj_lib::config::{ConfigLayer, ConfigSource, StackedConfig};
+use jj_lib::git::{
+    self, GitFetch, GitRefUpdate, GitSettings, RemoteCallbacks, expand_fetch_refspecs,
+};
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::{RefTarget, RemoteRef, RemoteRefState};
+use jj_lib::ref_name::{RefName, RemoteName};
+use jj_lib::repo::{Repo, StoreFactories};
+use jj_lib::repo_path::{RepoPathBuf, RepoPathUiConverter};
+use jj_lib::revset::{
+    self, RevsetExpression, RevsetExtensions, RevsetParseContext, RevsetWorkspaceContext,
+    SymbolResolver,
+};
+use jj_lib::rewrite;
+use jj_lib::settings::UserSettings;
+use jj_lib::str_util::{StringExpression, StringMatcher, StringPattern};
+use jj_lib::workspace::{Workspace, default_working_copy_factories};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// Conventional name of the canonical repo's remote in a fork
+pub const UPSTREAM_REMOTE: &str = "upstream";
+
+/// jj config table holding revset aliases
+const REVSET_ALIASES_TABLE: &str = "revset-aliases";
+
+/// Environment variable selecting the [`JjBackend`]
+pub const JJ_BACKEND_ENV: &str = "RYU_JJ_BACKEND";
+
+/// How [`JjWorkspace`] reads and writes the repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JjBackend {
+    /// jj-lib, falling back to the jj CLI if jj-lib can't open the repo
+    #[default]
+    Auto,
+    /// jj-lib only
+    Lib,
+    /// The `jj` CLI only (slower, but tracks the installed jj)
+    Cli,
+}
+
+impl JjBackend {
+    /// Backend selected by `RYU_JJ_BACKEND`, defaulting to [`JjBackend::Auto`]
+    pub fn from_env() -> Result<Self> {
+        std::env::var(JJ_BACKEND_ENV).map_or(Ok(Self::Auto), |value| value.parse())
+    }
+}
+
+impl FromStr for JjBackend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" | "" => Ok(Self::Auto),
+            "lib" => Ok(Self::Lib),
+            "cli" => Ok(Self::Cli),
+            other => Err(Error::Config(format!(
+                "invalid {JJ_BACKEND_ENV} '{other}': expected auto, lib, or cli"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for JjBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Lib => write!(f, "lib"),
+            Self::Cli => write!(f, "cli"),
+        }
+    }
+}
+
+/// Wrapper around a jj workspace and repository
+pub struct JjWorkspace {
+    backend: Backend,
+    /// jj-lib's error when the CLI backend was chosen automatically
+    fallback_reason: Option<String>,
+    /// Settings from the user and repository config files
+    config: RyuConfig,
+    /// Context of the run the workspace was opened for
+    run: Arc<RunContext>,
+}
+
+enum Backend {
+    Lib(LibWorkspace),
+    Cli(JjCli),
+}
+
+/// Workspace accessed in-process through jj-lib
+struct LibWorkspace {
+    workspace: Workspace,
+    settings: UserSettings,
+    /// Revset `trunk()` resolves to, overriding jj's config
+    trunk_revset: Option<String>,
+}
+
+/// Create `UserSettings` with defaults for read operations
+///
+/// Reads the user's jj config and, when given, the repository's
+/// (`.jj/repo/config.toml`), so revset aliases and git settings match what
+/// `jj` itself would use.
+fn create_user_settings(repo_config: Option<&Path>) -> Result<UserSettings> {
+    let mut config = StackedConfig::with_defaults();
+
+    // Add minimal user config - required by UserSettings::from_config
+    let mut user_layer = ConfigLayer::empty(ConfigSource::User);
+    user_layer
+        .set_value("user.name", "jj-ryu")
+        .map_err(|e| Error::Config(format!("Failed to set user.name: {e}")))?;
+    user_layer
+        .set_value("user.email", "jj-ryu@localhost")
+        .map_err(|e| Error::Config(format!("Failed to set user.email: {e}")))?;
+    config.add_layer(user_layer);
+
+    // Load the user's jj config (git.executable-path etc. matter for push)
+    let candidates = jj_config_candidates(
+        std::env::var_os("JJ_CONFIG"),
+        dirs::config_dir(),
+        dirs::home_dir(),
+    );
+    for path in candidates {
+        if path.is_dir() {
+            let _ = config.load_dir(ConfigSource::User, &path);
+        } else if path.is_file() {
+            let _ = config.load_file(ConfigSource::User, &path);
+        }
+    }
+    if let Some(path) = repo_config.filter(|path| path.is_file()) {
+        let _ = config.load_file(ConfigSource::Repo, path);
+    }
+
+    UserSettings::from_config(config)
+        .map_err(|e| Error::Config(format!("Failed to create settings: {e}")))
+}
+
+/// User config locations, in the order jj reads them
+///
+/// `JJ_CONFIG` (a path list) replaces the defaults. Otherwise the platform
+/// config dir (`%APPDATA%` on Windows, `~/Library/Application Support` on
+/// macOS) is read, then `~/.config/jj`, which jj honors on every platform.
+fn jj_config_candidates(
+    jj_config: Option<OsString>,
+    config_dir: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    if let Some(paths) = jj_config.filter(|p| !p.is_empty()) {
+        return std::env::split_paths(&paths)
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect();
+    }
+
+    let mut candidates = Vec::new();
+    let dirs = config_dir
+        .into_iter()
+        .chain(home_dir.map(|home| home.join(".config")));
+    for dir in dirs {
+        let jj_dir = dir.join("jj");
+        for path in [jj_dir.join("config.toml"), jj_dir.join("conf.d")] {
+            if !candidates.contains(&path) {
+                candidates.push(path);
+            }
+        }
+    }
+    candidates
+}
+
+impl LibWorkspace {
+    /// Open a jj workspace at the given path
+    ///
+    /// Loads the head operation once so unreadable repo data fails here,
+    /// where the caller can still fall back to the CLI.
+    fn open(path: &Path) -> Result<Self> {
+        let settings = create_user_settings(None)?;
+
+        let workspace = Workspace::load(
+            &settings,
+            path,
+            &StoreFactories::default(),
+            &default_working_copy_factories(),
+        )
+        .map_err(|e| load_error("Failed to open workspace", &e))?;
+
+        // The repo config's location is only known once the workspace is
+        let repo_config = workspace.repo_path().join("config.toml");
+        let settings = if repo_config.is_file() {
+            create_user_settings(Some(&repo_config))?
+        } else {
+            settings
+        };
+
+        let lib = Self {
+            workspace,
+            settings,
+            trunk_revset: None,
+        };
+        lib.repo()?;
+        Ok(lib)
+    }
+
+    /// Get the readonly repo at head operation
+    fn repo(&self) -> Result<Arc<jj_lib::repo::ReadonlyRepo>> {
+        self.workspace
+            .repo_loader()
+            .load_at_head()
+            .map_err(|e| load_error("Failed to load repo", &e))
+    }
+
+    /// ID of the repo's head operation (hex)
+    pub fn operation_id(&self) -> Result<String> {
+        Ok(self.repo()?.op_id().hex())
+    }
+
+    /// ID and description of the repo's head operation
+    pub fn head_operation(&self) -> Result<(String, String)> {
+        let repo = self.repo()?;
+        let operation = repo.operation();
+        Ok((
+            operation.id().hex(),
+            operation.metadata().description.clone(),
+        ))
+    }
+
+    /// Get git settings from user settings
+    fn git_settings(&self) -> Result<GitSettings> {
+        GitSettings::from_settings(&self.settings)
+            .map_err(|e| Error::Config(format!("Invalid git settings: {e}")))
+    }
+
+    /// Get all local bookmarks
+    pub fn local_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        let repo = self.repo()?;
+        let view = repo.view();
+
+        let mut bookmarks = Vec::new();
+        for (name, target) in view.local_bookmarks() {
+            if let Some(commit_id) = target.as_normal() {
+                let commit = repo
+                    .store()
+                    .get_commit(commit_id)
+                    .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+                // Check if bookmark has remote tracking (excluding @git pseudo-remote)
+                let name_matcher = StringPattern::exact(name.as_str()).to_matcher();
+                let remote_matcher = StringMatcher::All;
+                let has_remote = view
+                    .remote_bookmarks_matching(&name_matcher, &remote_matcher)
+                    .any(|(symbol, _)| symbol.remote.as_str() != "git");
+
+                // Check if synced with remote (excluding @git pseudo-remote)
+                let is_synced = view
+                    .remote_bookmarks_matching(&name_matcher, &remote_matcher)
+                    .filter(|(symbol, _)| symbol.remote.as_str() != "git")
+                    .any(|(_, remote_ref)| {
+                        remote_ref
+                            .target
+                            .as_normal()
+                            .is_some_and(|id| id == commit_id)
+                    });
+
+                bookmarks.push(Bookmark {
+                    name: name.as_str().to_string(),
+                    commit_id: commit_id.hex(),
+                    change_id: commit.change_id().hex(),
+                    has_remote,
+                    is_synced,
+                });
+            }
+        }
+
+        Ok(bookmarks)
+    }
+
+    /// Get a specific local bookmark
+    pub fn get_local_bookmark(&self, name: &str) -> Result<Option<Bookmark>> {
+        let repo = self.repo()?;
+        let view = repo.view();
+
+        let ref_name = RefName::new(name);
+        let target = view.get_local_bookmark(ref_name);
+
+        if !target.is_present() {
+            return Ok(None);
+        }
+
+        let Some(commit_id) = target.as_normal() else {
+            return Ok(None);
+        };
+
+        let commit = repo
+            .store()
+            .get_commit(commit_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        // Check if bookmark has remote tracking (excluding @git pseudo-remote)
+        let name_matcher = StringPattern::exact(name).to_matcher();
+        let remote_matcher = StringMatcher::All;
+        let has_remote = view
+            .remote_bookmarks_matching(&name_matcher, &remote_matcher)
+            .any(|(symbol, _)| symbol.remote.as_str() != "git");
+
+        // Check if synced with remote (excluding @git pseudo-remote)
+        let is_synced = view
+            .remote_bookmarks_matching(&name_matcher, &remote_matcher)
+            .filter(|(symbol, _)| symbol.remote.as_str() != "git")
+            .any(|(_, remote_ref)| {
+                remote_ref
+                    .target
+                    .as_normal()
+                    .is_some_and(|id| id == commit_id)
+            });
+
+        Ok(Some(Bookmark {
+            name: name.to_string(),
+            commit_id: commit_id.hex(),
+            change_id: commit.change_id().hex(),
+            has_remote,
+            is_synced,
+        }))
+    }
+
+    /// Get a remote bookmark
+    pub fn get_remote_bookmark(&self, name: &str, remote: &str) -> Result<Option<Bookmark>> {
+        let repo = self.repo()?;
+        let view = repo.view();
+
+        let ref_name = RefName::new(name);
+        let remote_name = RemoteName::new(remote);
+        let symbol = ref_name.to_remote_symbol(remote_name);
+        let remote_ref = view.get_remote_bookmark(symbol);
+
+        if !remote_ref.target.is_present() {
+            return Ok(None);
+        }
+
+        let Some(commit_id) = remote_ref.target.as_normal() else {
+            return Ok(None);
+        };
+
+        let commit = repo
+            .store()
+            .get_commit(commit_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        Ok(Some(Bookmark {
+            name: name.to_string(),
+            commit_id: commit_id.hex(),
+            change_id: commit.change_id().hex(),
+            has_remote: true,
+            is_synced: true,
+        }))
+    }
+
+    /// Preferred remote order for detecting default branch
+    const REMOTE_PREFERENCE: &[&str] = &["origin", "upstream"];
+
+    /// Default `trunk()` revset alias - matches jj CLI's built-in default
+    ///
+    /// Uses `latest()` to pick the newest commit if multiple trunk candidates exist,
+    /// checking main/master/trunk on origin and upstream remotes, falling back to `root()`.
+    const DEFAULT_TRUNK_ALIAS: &str = r#"latest(
+        remote_bookmarks(exact:"main", exact:"origin") |
+        remote_bookmarks(exact:"master", exact:"origin") |
+        remote_bookmarks(exact:"trunk", exact:"origin") |
+        remote_bookmarks(exact:"main", exact:"upstream") |
+        remote_bookmarks(exact:"master", exact:"upstream") |
+        remote_bookmarks(exact:"trunk", exact:"upstream") |
+        root()
+    )"#;
+
+    /// Detect default branch from git remote HEAD (e.g., refs/remotes/origin/HEAD)
+    ///
+    /// Returns `(branch_name, remote_name)` if found.
+    fn detect_default_branch_from_remote(
+        git_repo: &gix::Repository,
+    ) -> Option<(String, &'static str)> {
+        for &remote in Self::REMOTE_PREFERENCE {
+            let ref_name = format!("refs/remotes/{remote}/HEAD");
+            if let Some(reference) = git_repo.try_find_reference(&ref_name).ok().flatten() {
+                if let Some(target_name) = reference.target().try_name() {
+                    let target_str = target_name.to_string();
+                    let prefix = format!("refs/remotes/{remote}/");
+                    if let Some(branch) = target_str.strip_prefix(&prefix) {
+                        return Some((branch.to_string(), remote));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Compute `trunk()` alias by checking remote HEAD first, then falling back to default
+    fn compute_trunk_alias(repo: &Arc<jj_lib::repo::ReadonlyRepo>) -> String {
+        if let Ok(git_repo) = git::get_git_repo(repo.store()) {
+            if let Some((branch, remote)) = Self::detect_default_branch_from_remote(&git_repo) {
+                return format!(r#"remote_bookmarks(exact:"{branch}", exact:"{remote}")"#);
+            }
+        }
+        Self::DEFAULT_TRUNK_ALIAS.to_string()
+    }
+
+    /// Revset aliases from the user's and repository's jj config
+    ///
+    /// `trunk()` follows the remote HEAD (see [`Self::compute_trunk_alias`])
+    /// unless the config defines it; [`Self::trunk_revset`] beats both.
+    /// Malformed aliases are skipped with a warning, as jj does.
+    fn revset_aliases(&self, repo: &Arc<jj_lib::repo::ReadonlyRepo>) -> revset::RevsetAliasesMap {
+        let mut aliases = revset::RevsetAliasesMap::default();
+        aliases
+            .insert("trunk()", Self::compute_trunk_alias(repo))
+            .expect("trunk() alias declaration is valid");
+
+        for layer in self.settings.config().layers() {
+            let Ok(Some(table)) = layer.look_up_table(REVSET_ALIASES_TABLE) else {
+                continue;
+            };
+            for (decl, item) in table.iter() {
+                let result = item
+                    .as_str()
+                    .ok_or_else(|| format!("expected a string, got {}", item.type_name()))
+                    .and_then(|value| aliases.insert(decl, value).map_err(|e| e.to_string()));
+                if let Err(error) = result {
+                    warn!(alias = decl, %error, "ignoring revset alias from jj config");
+                }
+            }
+        }
+
+        if let Some(trunk) = &self.trunk_revset {
+            aliases
+                .insert("trunk()", trunk)
+                .expect("trunk() alias declaration is valid");
+        }
+        aliases
+    }
+
+    /// Whether `trunk()` comes from the jj config or an override rather
+    /// than ryu's remote HEAD detection
+    fn trunk_customized(&self) -> bool {
+        self.trunk_revset.is_some()
+            || self.settings.config().layers().iter().any(|layer| {
+                layer
+                    .look_up_table(REVSET_ALIASES_TABLE)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|table| table.contains_key("trunk()"))
+            })
+    }
+
+    /// Resolve a revset expression to commits
+    pub fn resolve_revset(&self, expr: &str) -> Result<Vec<LogEntry>> {
+        let repo = self.repo()?;
+
+        // Parse and evaluate the revset
+        let extensions = RevsetExtensions::default();
+        let aliases = self.revset_aliases(&repo);
+
+        let date_context = jj_lib::time_util::DatePatternContext::Local(chrono::Local::now());
+
+        // Create workspace context for trunk() resolution
+        let workspace_root = self.workspace.workspace_root().to_path_buf();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: workspace_root.clone(),
+            base: workspace_root,
+        };
+        let workspace_name = self.workspace.workspace_name();
+        let workspace_ctx = RevsetWorkspaceContext {
+            path_converter: &path_converter,
+            workspace_name,
+        };
+
+        let context = RevsetParseContext {
+            aliases_map: &aliases,
+            local_variables: std::collections::HashMap::new(),
+            user_email: self.settings.user_email(),
+            date_pattern_context: date_context,
+            default_ignored_remote: Some(git::REMOTE_NAME_FOR_LOCAL_GIT_REPO),
+            use_glob_by_default: false,
+            extensions: &extensions,
+            workspace: Some(workspace_ctx),
+        };
+
+        let mut diagnostics = revset::RevsetDiagnostics::new();
+        let expression = revset::parse(&mut diagnostics, expr, &context)
+            .map_err(|e| Error::Parse(format!("Failed to parse revset: {e}")))?;
+
+        let empty_extensions: &[Box<dyn jj_lib::revset::SymbolResolverExtension>] = &[];
+        let symbol_resolver = SymbolResolver::new(repo.as_ref(), empty_extensions);
+        let resolved = expression
+            .resolve_user_expression(repo.as_ref(), &symbol_resolver)
+            .map_err(|e| Error::Revset(format!("Failed to resolve revset: {e}")))?;
+
+        let revset = resolved
+            .evaluate(repo.as_ref())
+            .map_err(|e| Error::Revset(format!("Failed to evaluate revset: {e}")))?;
+
+        let mut entries = Vec::new();
+        for commit_id in revset.iter() {
+            let commit_id =
+                commit_id.map_err(|e| Error::Revset(format!("Failed to iterate revset: {e}")))?;
+            let commit = repo
+                .store()
+                .get_commit(&commit_id)
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+            entries.push(Self::commit_to_log_entry(&repo, &commit));
+        }
+
+        Ok(entries)
+    }
+
+    /// Convert a jj commit to a `LogEntry`
+    fn commit_to_log_entry(repo: &Arc<jj_lib::repo::ReadonlyRepo>, commit: &Commit) -> LogEntry {
+        let view = repo.view();
+
+        // Get bookmarks pointing to this commit
+        let local_bookmarks: Vec<String> = view
+            .local_bookmarks_for_commit(commit.id())
+            .map(|(name, _)| name.as_str().to_string())
+            .collect();
+
+        let remote_bookmarks: Vec<String> = view
+            .all_remote_bookmarks()
+            .filter(|(_, remote_ref)| {
+                remote_ref
+                    .target
+                    .as_normal()
+                    .is_some_and(|id| id == commit.id())
+            })
+            .map(|(symbol, _)| format!("{}@{}", symbol.name.as_str(), symbol.remote.as_str()))
+            .collect();
+
+        // Get parents
+        let parents: Vec<String> = commit.parent_ids().iter().map(ObjectId::hex).collect();
+
+        // Get description first line
+        let description = commit.description();
+        let description_first_line = description.lines().next().unwrap_or("").to_string();
+
+        // Get timestamps
+        let author = commit.author();
+        let committer = commit.committer();
+
+        let authored_at = timestamp_to_datetime(&author.timestamp);
+        let committed_at = timestamp_to_datetime(&committer.timestamp);
+
+        // Check if this is the working copy
+        let is_working_copy = repo
+            .view()
+            .wc_commit_ids()
+            .values()
+            .any(|id| id == commit.id());
+
+        LogEntry {
+            commit_id: commit.id().hex(),
+            change_id: commit.change_id().hex(),
+            author_name: author.name.clone(),
+            author_email: author.email.clone(),
+            description_first_line,
+            description: description.to_string(),
+            parents,
+            local_bookmarks,
+            remote_bookmarks,
+            is_working_copy,
+            authored_at,
+            committed_at,
+        }
+    }
+
+    /// Paths changed by a commit relative to its parents
+    pub fn changed_paths(&self, commit_id: &str) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let id = CommitId::try_from_hex(commit_id)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {commit_id}")))?;
+        let commit = repo
+            .store()
+            .get_commit(&id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+        let parent_tree = commit
+            .parent_tree(repo.as_ref())
+            .map_err(|e| Error::Workspace(format!("Failed to get parent tree: {e}")))?;
+        let tree = commit.tree();
+
+        let entries: Vec<_> = block_on(
+            parent_tree
+                .diff_stream(&tree, &EverythingMatcher)
+                .collect::<Vec<_>>(),
+        );
+        Ok(entries
+            .into_iter()
+            .map(|entry| entry.path.as_internal_file_string().to_string())
+            .collect())
+    }
+
+    /// Paths whose contents differ between two commits
+    pub fn diff_paths(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let get_tree = |commit_id: &str| {
+            let id = CommitId::try_from_hex(commit_id)
+                .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {commit_id}")))?;
+            repo.store()
+                .get_commit(&id)
+                .map(|commit| commit.tree())
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))
+        };
+        let from_tree = get_tree(from)?;
+        let to_tree = get_tree(to)?;
+
+        let entries: Vec<_> = block_on(
+            from_tree
+                .diff_stream(&to_tree, &EverythingMatcher)
+                .collect::<Vec<_>>(),
+        );
+        Ok(entries
+            .into_iter()
+            .map(|entry| entry.path.as_internal_file_string().to_string())
+            .collect())
+    }
+
+    /// Commit that last changed line `line` (1-based) of `path` in the
+    /// working-copy commit (`jj file annotate`); `None` past the end of the
+    /// file
+    pub fn line_origin(&self, path: &str, line: usize) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let wc_id = repo
+            .view()
+            .get_wc_commit_id(self.workspace.workspace_name())
+            .ok_or_else(|| Error::Workspace("No working-copy commit".to_string()))?;
+        let commit = repo
+            .store()
+            .get_commit(wc_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+        let repo_path = RepoPathBuf::from_internal_string(path)
+            .map_err(|e| Error::Workspace(format!("Invalid path {path}: {e}")))?;
+
+        let mut annotator = FileAnnotator::from_commit(&commit, &repo_path)
+            .map_err(|e| Error::Workspace(format!("Failed to read {path}: {e}")))?;
+        annotator
+            .compute(repo.as_ref(), &RevsetExpression::all())
+            .map_err(|e| Error::Revset(format!("Failed to annotate {path}: {e}")))?;
+        let annotation = annotator.to_annotation();
+        // Lines that predate the searched history still name where it stopped
+        Ok(line
+            .checked_sub(1)
+            .and_then(|index| annotation.line_origins().nth(index))
+            .map(|(origin, _)| match origin {
+                Ok(origin) | Err(origin) => origin.commit_id.hex(),
+            }))
+    }
+
+    /// Get all git remotes
+    pub fn git_remotes(&self) -> Result<Vec<GitRemote>> {
+        let repo = self.repo()?;
+
+        let remote_names = git::get_all_remote_names(repo.store())
+            .map_err(|_| Error::Git("Not a git-backed repo".to_string()))?;
+
+        // Get the git repo for URL lookup
+        let git_repo = git::get_git_repo(repo.store())
+            .map_err(|_| Error::Git("Not a git-backed repo".to_string()))?;
+
+        let mut remotes = Vec::new();
+        for name in remote_names {
+            // Prefer git's own view so insteadOf rewrites from every config
+            // layer apply; try_find_remote returns Option<Result<Remote, Error>>
+            let url = rewritten_remote_url(git_repo.git_dir(), name.as_str())
+                .or_else(|| {
+                    git_repo
+                        .try_find_remote(name.as_str())
+                        .and_then(std::result::Result::ok)
+                        .and_then(|remote| {
+                            remote
+                                .url(gix::remote::Direction::Push)
+                                .map(|u| u.to_bstring().to_string())
+                        })
+                })
+                .unwrap_or_default();
+
+            remotes.push(GitRemote {
+                name: name.as_str().to_string(),
+                url,
+            });
+        }
+
+        Ok(remotes)
+    }
+
+    /// Fetch from a git remote
+    pub fn git_fetch(&mut self, remote: &str) -> Result<()> {
+        self.fetch_matching(&[remote], &StringExpression::all())
+    }
+
+    /// Fetch only the named branches from a git remote
+    pub fn git_fetch_branches(&mut self, remote: &str, branches: &[String]) -> Result<()> {
+        let names = branches
+            .iter()
+            .map(|branch| StringExpression::exact(branch.as_str()))
+            .collect();
+        self.fetch_matching(&[remote], &StringExpression::union_all(names))
+    }
+
+    /// Fetch several remotes in turn and import them in one operation
+    pub fn git_fetch_all(&mut self, remotes: &[String]) -> Result<()> {
+        let remotes: Vec<&str> = remotes.iter().map(String::as_str).collect();
+        self.fetch_matching(&remotes, &StringExpression::all())
+    }
+
+    /// Fetch the branches `branches` matches from each of `remotes`, one
+    /// after another, as a single jj operation
+    fn fetch_matching(&mut self, remotes: &[&str], branches: &StringExpression) -> Result<()> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+
+        // Start a transaction for the fetch
+        let mut tx = repo.start_transaction();
+
+        let mut fetch = GitFetch::new(tx.repo_mut(), &git_settings)
+            .map_err(|e| Error::Git(format!("Failed to create fetch: {e}")))?;
+
+        for &remote in remotes {
+            let remote_name = RemoteName::new(remote);
+            let refspecs = expand_fetch_refspecs(remote_name, branches.clone())
+                .map_err(|e| Error::Git(format!("Failed to expand refspecs: {e}")))?;
+            fetch
+                .fetch(
+                    remote_name,
+                    refspecs,
+                    RemoteCallbacks::default(),
+                    None,
+                    None,
+                )
+                .map_err(|e| Error::Git(format!("Failed to fetch {remote}: {e}")))?;
+        }
+
+        // Import the fetched refs
+        fetch
+            .import_refs()
+            .map_err(|e| Error::Git(format!("Failed to import refs: {e}")))?;
+
+        // Commit the transaction
+        tx.commit(format!("fetch from {}", remotes.join(", ")))
+            .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Push a bookmark to a remote
+    pub fn git_push(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        self.git_push_many(&[bookmark], remote)
+    }
+
+    /// Push several bookmarks to a remote in one git push
+    pub fn git_push_many(&mut self, bookmarks: &[&str], remote: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+        let view = repo.view();
+        let remote_name = RemoteName::new(remote);
+
+        // Local targets and the remote-tracking targets the push expects
+        let mut pushes = Vec::with_capacity(bookmarks.len());
+        for &bookmark in bookmarks {
+            let ref_name = RefName::new(bookmark);
+            let target = view.get_local_bookmark(ref_name);
+            if !target.is_present() {
+                return Err(Error::BookmarkNotFound(bookmark.to_string()));
+            }
+            let remote_ref = view.get_remote_bookmark(ref_name.to_remote_symbol(remote_name));
+            let update = GitRefUpdate {
+                qualified_name: format!("refs/heads/{bookmark}").into(),
+                expected_current_target: remote_ref.target.as_normal().cloned(),
+                new_target: target.as_normal().cloned(),
+            };
+            pushes.push((ref_name, target.clone(), update));
+        }
+
+        // Start a transaction first - needed for export_refs
+        let mut tx = repo.start_transaction();
+
+        // Export refs to underlying git repo before pushing
+        // This is essential for new bookmarks that don't exist in .git/refs/heads/ yet
+        let export_stats = git::export_refs(tx.repo_mut())
+            .map_err(|e| Error::Git(format!("Failed to export refs: {e}")))?;
+
+        // Check if any of our bookmarks failed to export
+        if let Some((symbol, _)) = export_stats
+            .failed_bookmarks
+            .iter()
+            .find(|(symbol, _)| bookmarks.contains(&symbol.name.as_str()))
+        {
+            return Err(Error::Git(format!(
+                "Failed to export bookmark '{}' to git",
+                symbol.name.as_str()
+            )));
+        }
+
+        let updates: Vec<GitRefUpdate> =
+            pushes.iter().map(|(_, _, update)| update.clone()).collect();
+        let stats = git::push_updates(
+            tx.repo_mut().base_repo().as_ref(),
+            &git_settings,
+            remote_name,
+            &updates,
+            RemoteCallbacks::default(),
+        )
+        .map_err(|e| Error::Git(format!("Failed to push: {e}")))?;
+
+        // Update the remote tracking refs of the branches the remote took, so
+        // they show as synced; a rejected one keeps its old remote state
+        for (ref_name, target, update) in pushes {
+            if !stats.pushed.contains(&update.qualified_name) {
+                continue;
+            }
+            let remote_ref = RemoteRef {
+                target,
+                state: RemoteRefState::Tracked,
+            };
+            tx.repo_mut()
+                .set_remote_bookmark(ref_name.to_remote_symbol(remote_name), remote_ref);
+        }
+
+        tx.commit(format!("push {} to {remote}", bookmarks.join(", ")))
+            .map_err(|e| Error::Git(format!("Failed to commit push: {e}")))?;
+
+        let rejected: Vec<String> = stats
+            .rejected
+            .iter()
+            .chain(&stats.remote_rejected)
+            .map(|(name, reason)| {
+                let name = name.as_str().trim_start_matches("refs/heads/");
+                match reason {
+                    Some(reason) => format!("{name} ({reason})"),
+                    None => name.to_string(),
+                }
+            })
+            .collect();
+        if !rejected.is_empty() {
+            return Err(Error::Git(format!(
+                "{remote} rejected {}",
+                rejected.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Delete a bookmark's branch on a remote and forget its tracking bookmark
+    pub fn delete_remote_bookmark(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+
+        let remote_name = RemoteName::new(remote);
+        let remote_symbol = RefName::new(bookmark).to_remote_symbol(remote_name);
+        let remote_ref = repo.view().get_remote_bookmark(remote_symbol);
+        if !remote_ref.is_present() {
+            return Err(Error::BookmarkNotFound(format!("{bookmark}@{remote}")));
+        }
+
+        let update = GitRefUpdate {
+            qualified_name: format!("refs/heads/{bookmark}").into(),
+            expected_current_target: remote_ref.target.as_normal().cloned(),
+            new_target: None,
+        };
+
+        let mut tx = repo.start_transaction();
+        git::push_updates(
+            tx.repo_mut().base_repo().as_ref(),
+            &git_settings,
+            remote_name,
+            &[update],
+            RemoteCallbacks::default(),
+        )
+        .map_err(|e| Error::Git(format!("Failed to delete remote branch: {e}")))?;
+
+        tx.repo_mut()
+            .set_remote_bookmark(remote_symbol, RemoteRef::absent());
+        tx.commit(format!("delete {bookmark} on {remote}"))
+            .map_err(|e| Error::Git(format!("Failed to commit push: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Delete a local bookmark (remote-tracking bookmarks are left alone)
+    pub fn delete_bookmark(&mut self, bookmark: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let ref_name = RefName::new(bookmark);
+        if !repo.view().get_local_bookmark(ref_name).is_present() {
+            return Err(Error::BookmarkNotFound(bookmark.to_string()));
+        }
+
+        let mut tx = repo.start_transaction();
+        tx.repo_mut()
+            .set_local_bookmark_target(ref_name, RefTarget::absent());
+        tx.commit(format!("delete bookmark {bookmark}"))
+            .map_err(|e| Error::Workspace(format!("Failed to delete bookmark: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Create a local bookmark pointing at `commit_id`
+    pub fn create_bookmark(&mut self, bookmark: &str, commit_id: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let ref_name = RefName::new(bookmark);
+        if repo.view().get_local_bookmark(ref_name).is_present() {
+            return Err(Error::Workspace(format!(
+                "bookmark '{bookmark}' already exists"
+            )));
+        }
+        let id = CommitId::try_from_hex(commit_id)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {commit_id}")))?;
+
+        let mut tx = repo.start_transaction();
+        tx.repo_mut()
+            .set_local_bookmark_target(ref_name, RefTarget::normal(id));
+        tx.commit(format!("create bookmark {bookmark}"))
+            .map_err(|e| Error::Workspace(format!("Failed to create bookmark: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Track a remote bookmark (`jj bookmark track name@remote`), creating
+    /// the local bookmark at the remote's commit if there is none
+    pub fn track_bookmark(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let symbol = RefName::new(bookmark).to_remote_symbol(RemoteName::new(remote));
+        if !repo.view().get_remote_bookmark(symbol).is_present() {
+            return Err(Error::BookmarkNotFound(format!("{bookmark}@{remote}")));
+        }
+
+        let mut tx = repo.start_transaction();
+        tx.repo_mut()
+            .track_remote_bookmark(symbol)
+            .map_err(|e| Error::Workspace(format!("Failed to track bookmark: {e}")))?;
+        tx.commit(format!("track remote bookmark {bookmark}@{remote}"))
+            .map_err(|e| Error::Workspace(format!("Failed to track bookmark: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Point a local bookmark at `commit_id`, creating it if needed
+    ///
+    /// Also resolves a bookmark left conflicted by a fetch.
+    pub fn set_bookmark(&mut self, bookmark: &str, commit_id: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let id = CommitId::try_from_hex(commit_id)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {commit_id}")))?;
+
+        let mut tx = repo.start_transaction();
+        tx.repo_mut()
+            .set_local_bookmark_target(RefName::new(bookmark), RefTarget::normal(id));
+        tx.commit(format!("point bookmark {bookmark} to commit {commit_id}"))
+            .map_err(|e| Error::Workspace(format!("Failed to set bookmark: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Rename a local bookmark, keeping its target
+    ///
+    /// Remote-tracking bookmarks keep the old name, as with `jj bookmark rename`.
+    pub fn rename_bookmark(&mut self, old: &str, new: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let (old_ref, new_ref) = (RefName::new(old), RefName::new(new));
+        let target = repo.view().get_local_bookmark(old_ref).clone();
+        if !target.is_present() {
+            return Err(Error::BookmarkNotFound(old.to_string()));
+        }
+        if repo.view().get_local_bookmark(new_ref).is_present() {
+            return Err(Error::Workspace(format!("bookmark '{new}' already exists")));
+        }
+
+        let mut tx = repo.start_transaction();
+        tx.repo_mut().set_local_bookmark_target(new_ref, target);
+        tx.repo_mut()
+            .set_local_bookmark_target(old_ref, RefTarget::absent());
+        tx.commit(format!("rename bookmark {old} to {new}"))
+            .map_err(|e| Error::Workspace(format!("Failed to rename bookmark: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Create an empty change on `parent` without editing it
+    /// (`jj new --no-edit parent -m description`); returns its commit ID
+    pub fn new_change(&mut self, parent: &str, description: &str) -> Result<String> {
+        let repo = self.repo()?;
+        let parent_id = CommitId::try_from_hex(parent)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {parent}")))?;
+        let parent_commit = repo
+            .store()
+            .get_commit(&parent_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        let mut tx = repo.start_transaction();
+        let commit = tx
+            .repo_mut()
+            .new_commit(vec![parent_id], parent_commit.tree())
+            .set_description(description)
+            .write()
+            .map_err(|e| Error::Workspace(format!("Failed to create change: {e}")))?;
+        tx.commit(format!("new empty commit on {parent}"))
+            .map_err(|e| Error::Workspace(format!("Failed to commit new change: {e}")))?;
+
+        Ok(commit.id().hex())
+    }
+
+    /// Rebase `root` and its descendants onto `onto` (`jj rebase -s root -d onto`)
+    pub fn rebase_onto(&mut self, root: &str, onto: &str) -> Result<()> {
+        let repo = self.repo()?;
+        let root_id = CommitId::try_from_hex(root)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {root}")))?;
+        let onto_id = CommitId::try_from_hex(onto)
+            .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {onto}")))?;
+        let root_commit = repo
+            .store()
+            .get_commit(&root_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        let mut tx = repo.start_transaction();
+        block_on(rewrite::rebase_commit(
+            tx.repo_mut(),
+            root_commit,
+            vec![onto_id],
+        ))
+        .map_err(|e| Error::Workspace(format!("Failed to rebase {root}: {e}")))?;
+        // Moves descendants and any bookmarks pointing at rewritten commits
+        tx.repo_mut()
+            .rebase_descendants()
+            .map_err(|e| Error::Workspace(format!("Failed to rebase descendants: {e}")))?;
+        tx.commit(format!("rebase {root} onto {onto}"))
+            .map_err(|e| Error::Workspace(format!("Failed to commit rebase: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Abandon `commits`, rebasing their descendants onto their parents
+    /// (`jj abandon`); bookmarks on them move to the parents too
+    pub fn abandon(&mut self, commits: &[String]) -> Result<()> {
+        let repo = self.repo()?;
+        let mut tx = repo.start_transaction();
+        for commit in commits {
+            let id = CommitId::try_from_hex(commit)
+                .ok_or_else(|| Error::Workspace(format!("Invalid commit ID: {commit}")))?;
+            let commit = repo
+                .store()
+                .get_commit(&id)
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+            tx.repo_mut().record_abandoned_commit(&commit);
+        }
+        tx.repo_mut()
+            .rebase_descendants()
+            .map_err(|e| Error::Workspace(format!("Failed to rebase descendants: {e}")))?;
+        tx.commit(format!("abandon {} commits", commits.len()))
+            .map_err(|e| Error::Workspace(format!("Failed to commit abandon: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Get the default branch name by checking remote HEAD first, then common names
+    pub fn default_branch(&self) -> Result<String> {
+        // A customized trunk() names the branch, e.g. develop@origin
+        if self.trunk_customized() {
+            if let Some(branch) = self.trunk_branch()? {
+                return Ok(branch);
+            }
+        }
+
+        let repo = self.repo()?;
+
+        // Try to detect from git remote HEAD (handles custom default branches like "develop")
+        if let Ok(git_repo) = git::get_git_repo(repo.store()) {
+            if let Some((branch, _)) = Self::detect_default_branch_from_remote(&git_repo) {
+                return Ok(branch);
+            }
+        }
+
+        // Fall back to checking local bookmarks for common names
+        let view = repo.view();
+        for name in &["main", "master", "trunk"] {
+            let target = view.get_local_bookmark(RefName::new(name));
+            if target.is_present() {
+                return Ok((*name).to_string());
+            }
+        }
+
+        // Final fallback
+        Ok("main".to_string())
+    }
+
+    /// Name of the remote bookmark `trunk()` resolves to, preferring
+    /// origin, then upstream
+    fn trunk_branch(&self) -> Result<Option<String>> {
+        let Some(trunk) = self.resolve_revset("trunk()")?.into_iter().next() else {
+            return Ok(None);
+        };
+        let repo = self.repo()?;
+        let mut at_trunk: Vec<(String, String)> = repo
+            .view()
+            .all_remote_bookmarks()
+            .filter(|(symbol, _)| symbol.remote.as_str() != "git")
+            .filter(|(_, remote_ref)| {
+                remote_ref
+                    .target
+                    .as_normal()
+                    .is_some_and(|id| id.hex() == trunk.commit_id)
+            })
+            .map(|(symbol, _)| {
+                (
+                    symbol.name.as_str().to_string(),
+                    symbol.remote.as_str().to_string(),
+                )
+            })
+            .collect();
+        at_trunk.sort_by_key(|(_, remote)| {
+            Self::REMOTE_PREFERENCE
+                .iter()
+                .position(|preferred| preferred == remote)
+                .unwrap_or(Self::REMOTE_PREFERENCE.len())
+        });
+        Ok(at_trunk.into_iter().next().map(|(name, _)| name))
+    }
+
+    /// Get the workspace root path
+    pub fn workspace_root(&self) -> &Path {
+        self.workspace.workspace_root()
+    }
+}
+
+impl JjWorkspace {
+    /// Open a jj workspace at the given path
+    ///
+    /// Uses the backend selected by `RYU_JJ_BACKEND` (default: auto).
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_in(path, Arc::default())
+    }
+
+    /// Open a jj workspace for the run `run`
+    ///
+    /// Like [`JjWorkspace::open`], but `run`'s settings are overlaid on the
+    /// config files, and platform services created for the workspace and its
+    /// own writes report to `run`.
+    pub fn open_in(path: &Path, run: Arc<RunContext>) -> Result<Self> {
+        Self::open_backend(path, JjBackend::from_env()?, run)
+    }
+
+    /// Open a jj workspace with an explicit backend
+    ///
+    /// Also loads the user and repository config files (see [`RyuConfig`]).
+    pub fn open_with(path: &Path, backend: JjBackend) -> Result<Self> {
+        Self::open_backend(path, backend, Arc::default())
+    }
+
+    fn open_backend(path: &Path, backend: JjBackend, run: Arc<RunContext>) -> Result<Self> {
+        let (backend, fallback_reason) = match backend {
+            JjBackend::Lib => (Backend::Lib(LibWorkspace::open(path)?), None),
+            JjBackend::Cli => (Backend::Cli(JjCli::open(path)?), None),
+            JjBackend::Auto => match LibWorkspace::open(path) {
+                Ok(lib) => (Backend::Lib(lib), None),
+                // If jj can't open it either, jj-lib's error is the useful one
+                Err(err) => match JjCli::open(path) {
+                    Ok(cli) => {
+                        warn!(%err, "jj-lib could not open the repo; using the jj CLI");
+                        (Backend::Cli(cli), Some(err.to_string()))
+                    }
+                    Err(_) => return Err(err),
+                },
+            },
+        };
+        let workspace_root = match &backend {
+            Backend::Lib(lib) => lib.workspace_root(),
+            Backend::Cli(cli) => cli.workspace_root(),
+        };
+        let config = RyuConfig::load(workspace_root)?.overlay(run.config().clone());
+        // `--read-only` and RYU_READ_ONLY were applied at startup; the config
+        // files can only be read now, and the variable still wins over them
+        if config.read_only == Some(true) && read_only_from_env()?.is_none() {
+            set_read_only(true);
+        }
+
+        // `--trunk-revset` arrives in the run's overlay and beats the files
+        let trunk_revset = config.trunk_revset.clone();
+        let mut backend = backend;
+        match &mut backend {
+            Backend::Lib(lib) => lib.trunk_revset = trunk_revset,
+            Backend::Cli(cli) => cli.set_trunk_revset(trunk_revset),
+        }
+        Ok(Self {
+            backend,
+            fallback_reason,
+            config,
+            run,
+        })
+    }
+
+    /// Settings from the user and repository config files
+    pub const fn config(&self) -> &RyuConfig {
+        &self.config
+    }
+
+    /// Context of the run the workspace was opened for
+    pub const fn run(&self) -> &Arc<RunContext> {
+        &self.run
+    }
+
+    /// Backend in use (never [`JjBackend::Auto`])
+    pub const fn backend(&self) -> JjBackend {
+        match self.backend {
+            Backend::Lib(_) => JjBackend::Lib,
+            Backend::Cli(_) => JjBackend::Cli,
+        }
+    }
+
+    /// jj-lib's error, if it failed and the CLI backend was used instead
+    pub fn fallback_reason(&self) -> Option<&str> {
+        self.fallback_reason.as_deref()
+    }
+
+    /// ID of the repo's head operation (hex)
+    ///
+    /// Operation IDs are local to one copy of the repo; clones of the same
+    /// remote have different ones.
+    pub fn operation_id(&self) -> Result<String> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.operation_id(),
+            Backend::Cli(cli) => cli.operation_id(),
+        }
+    }
+
+    /// Get all local bookmarks
+    pub fn local_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.local_bookmarks(),
+            Backend::Cli(cli) => cli.local_bookmarks(),
+        }
+    }
+
+    /// Get a specific local bookmark
+    pub fn get_local_bookmark(&self, name: &str) -> Result<Option<Bookmark>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.get_local_bookmark(name),
+            Backend::Cli(cli) => cli.get_local_bookmark(name),
+        }
+    }
+
+    /// Get a remote bookmark
+    pub fn get_remote_bookmark(&self, name: &str, remote: &str) -> Result<Option<Bookmark>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.get_remote_bookmark(name, remote),
+            Backend::Cli(cli) => cli.get_remote_bookmark(name, remote),
+        }
+    }
+
+    /// Resolve a revset expression to commits
+    pub fn resolve_revset(&self, expr: &str) -> Result<Vec<LogEntry>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.resolve_revset(expr),
+            Backend::Cli(cli) => cli.resolve_revset(expr),
+        }
+    }
+
+    /// Paths changed by a commit relative to its parents
+    pub fn changed_paths(&self, commit_id: &str) -> Result<Vec<String>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.changed_paths(commit_id),
+            Backend::Cli(cli) => cli.changed_paths(commit_id),
+        }
+    }
+
+    /// Paths whose contents differ between two commits
+    pub fn diff_paths(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.diff_paths(from, to),
+            Backend::Cli(cli) => cli.diff_paths(from, to),
+        }
+    }
+
+    /// Commit that last changed line `line` (1-based) of `path` (relative
+    /// to the workspace root) in the working copy; `None` past the end
+    pub fn line_origin(&self, path: &str, line: usize) -> Result<Option<String>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.line_origin(path, line),
+            Backend::Cli(cli) => cli.line_origin(path, line),
+        }
+    }
+
+    /// Get all git remotes
+    pub fn git_remotes(&self) -> Result<Vec<GitRemote>> {
+        match &self.backend {
+            Backend::Lib(lib) => lib.git_remotes(),
+            Backend::Cli(cli) => cli.git_remotes(),
+        }
+    }
+
+    /// Run a mutating call, waiting out locks held by other processes
+    ///
+    /// In read-only mode the call is skipped and returns `T::default()`.
+    fn with_lock_retry<T: Default>(
+        &mut self,
+        action: &str,
+        op: impl FnMut(&mut Backend) -> Result<T>,
+    ) -> Result<T> {
+        if is_read_only() {
+            self.run.skipped(action);
+            return Ok(T::default());
+        }
+        let timeout = lock_timeout(&self.config)?;
+        retry_on_lock(
+            action,
+            timeout,
+            &self.run,
+            &mut self.backend,
+            |backend| {
+                match backend {
+                    Backend::Lib(lib) => lib.head_operation(),
+                    Backend::Cli(cli) => cli.head_operation(),
+                }
+                .ok()
+            },
+            op,
+        )
+    }
+
+    /// Fetch from a git remote
+    pub fn git_fetch(&mut self, remote: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.with_lock_retry(&format!("fetch {remote}"), |backend| match backend {
+            Backend::Lib(lib) => lib.git_fetch(remote),
+            Backend::Cli(cli) => cli.git_fetch(remote),
+        });
+        self.run.metrics().record_git_op("fetch", started.elapsed());
+        result
+    }
+
+    /// Fetch only the named branches from a git remote
+    pub fn git_fetch_branches(&mut self, remote: &str, branches: &[String]) -> Result<()> {
+        let started = Instant::now();
+        let result = self.with_lock_retry(&format!("fetch {remote}"), |backend| match backend {
+            Backend::Lib(lib) => lib.git_fetch_branches(remote, branches),
+            Backend::Cli(cli) => cli.git_fetch_branches(remote, branches),
+        });
+        self.run.metrics().record_git_op("fetch", started.elapsed());
+        result
+    }
+
+    /// Fetch from several git remotes, e.g. a fork's `origin` and
+    /// `upstream`
+    ///
+    /// The remotes are fetched one after another, since concurrent fetches
+    /// into one git repo contend for its ref locks, and imported as a single
+    /// jj operation.
+    pub fn git_fetch_all(&mut self, remotes: &[String]) -> Result<()> {
+        let started = Instant::now();
+        let result =
+            self.with_lock_retry(
+                &format!("fetch {}", remotes.join(", ")),
+                |backend| match backend {
+                    Backend::Lib(lib) => lib.git_fetch_all(remotes),
+                    Backend::Cli(cli) => cli.git_fetch_all(remotes),
+                },
+            );
+        self.run.metrics().record_git_op("fetch", started.elapsed());
+        result
+    }
+
+    /// Push a bookmark to a remote
+    pub fn git_push(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.with_lock_retry(&format!("push {bookmark}"), |backend| match backend {
+            Backend::Lib(lib) => lib.git_push(bookmark, remote),
+            Backend::Cli(cli) => cli.git_push(bookmark, remote),
+        });
+        self.run.metrics().record_git_op("push", started.elapsed());
+        if result.is_ok() {
+            self.run.undo().record(RemoteChange::Pushed {
+                bookmark: bookmark.to_string(),
+                remote: remote.to_string(),
+            });
+        }
+        result
+    }
+
+    /// Push several bookmarks to a remote in one git push
+    ///
+    /// Cheaper than a push per bookmark: one connection, one negotiation
+    /// and one jj operation. The remote can still reject some bookmarks and
+    /// take the rest; the error then names the rejected ones, and the others
+    /// show as pushed in their remote bookmarks.
+    pub fn git_push_many(&mut self, bookmarks: &[&str], remote: &str) -> Result<()> {
+        if let [bookmark] = bookmarks {
+            return self.git_push(bookmark, remote);
+        }
+        let started = Instant::now();
+        let result =
+            self.with_lock_retry(
+                &format!("push {}", bookmarks.join(", ")),
+                |backend| match backend {
+                    Backend::Lib(lib) => lib.git_push_many(bookmarks, remote),
+                    Backend::Cli(cli) => cli.git_push_many(bookmarks, remote),
+                },
+            );
+        self.run.metrics().record_git_op("push", started.elapsed());
+        if result.is_ok() {
+            for bookmark in bookmarks {
+                self.run.undo().record(RemoteChange::Pushed {
+                    bookmark: (*bookmark).to_string(),
+                    remote: remote.to_string(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Delete a bookmark's branch on a remote
+    ///
+    /// Delete the local bookmark first: the jj CLI backend pushes the
+    /// deletion of a tracked bookmark, and would push the bookmark back
+    /// while it still exists locally.
+    pub fn delete_remote_bookmark(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let started = Instant::now();
+        let result =
+            self.with_lock_retry(
+                &format!("delete {bookmark}@{remote}"),
+                |backend| match backend {
+                    Backend::Lib(lib) => lib.delete_remote_bookmark(bookmark, remote),
+                    Backend::Cli(cli) => cli.delete_remote_bookmark(bookmark, remote),
+                },
+            );
+        self.run.metrics().record_git_op("push", started.elapsed());
+        if result.is_ok() {
+            self.run.undo().record(RemoteChange::DeletedBranch {
+                bookmark: bookmark.to_string(),
+                remote: remote.to_string(),
+            });
+        }
+        result
+    }
+
+    /// Delete a local bookmark (remote-tracking bookmarks are left alone)
+    pub fn delete_bookmark(&mut self, bookmark: &str) -> Result<()> {
+        self.with_lock_retry(&format!("delete {bookmark}"), |backend| match backend {
+            Backend::Lib(lib) => lib.delete_bookmark(bookmark),
+            Backend::Cli(cli) => cli.delete_bookmark(bookmark),
+        })
+    }
+
+    /// Create a local bookmark pointing at `commit_id`
+    pub fn create_bookmark(&mut self, bookmark: &str, commit_id: &str) -> Result<()> {
+        self.with_lock_retry(&format!("create {bookmark}"), |backend| match backend {
+            Backend::Lib(lib) => lib.create_bookmark(bookmark, commit_id),
+            Backend::Cli(cli) => cli.create_bookmark(bookmark, commit_id),
+        })
+    }
+
+    /// Track a remote bookmark, creating the local bookmark if there is none
+    pub fn track_bookmark(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        self.with_lock_retry(&format!("track {bookmark}"), |backend| match backend {
+            Backend::Lib(lib) => lib.track_bookmark(bookmark, remote),
+            Backend::Cli(cli) => cli.track_bookmark(bookmark, remote),
+        })
+    }
+
+    /// Point a local bookmark at `commit_id`, creating it if needed
+    pub fn set_bookmark(&mut self, bookmark: &str, commit_id: &str) -> Result<()> {
+        self.with_lock_retry(&format!("set {bookmark}"), |backend| match backend {
+            Backend::Lib(lib) => lib.set_bookmark(bookmark, commit_id),
+            Backend::Cli(cli) => cli.set_bookmark(bookmark, commit_id),
+        })
+    }
+
+    /// Rename a local bookmark, keeping its target
+    pub fn rename_bookmark(&mut self, old: &str, new: &str) -> Result<()> {
+        self.with_lock_retry(&format!("rename {old}"), |backend| match backend {
+            Backend::Lib(lib) => lib.rename_bookmark(old, new),
+            Backend::Cli(cli) => cli.rename_bookmark(old, new),
+        })
+    }
+
+    /// Create an empty change on `parent` without editing it; returns its
+    /// commit ID
+    pub fn new_change(&mut self, parent: &str, description: &str) -> Result<String> {
+        self.with_lock_retry(
+            &format!("new change on {parent}"),
+            |backend| match backend {
+                Backend::Lib(lib) => lib.new_change(parent, description),
+                Backend::Cli(cli) => cli.new_change(parent, description),
+            },
+        )
+    }
+
+    /// Rebase `root` and its descendants onto `onto`; bookmarks move with them
+    pub fn rebase_onto(&mut self, root: &str, onto: &str) -> Result<()> {
+        self.with_lock_retry(&format!("rebase {root}"), |backend| match backend {
+            Backend::Lib(lib) => lib.rebase_onto(root, onto),
+            Backend::Cli(cli) => cli.rebase_onto(root, onto),
+        })
+    }
+
+    /// Abandon `commits`; their descendants and bookmarks move to the parents
+    pub fn abandon(&mut self, commits: &[String]) -> Result<()> {
+        self.with_lock_retry("abandon", |backend| match backend {
+            Backend::Lib(lib) => lib.abandon(commits),
+            Backend::Cli(cli) => cli.abandon(commits),
+        })
+    }
+
+    /// ID of the repo's head operation once edits to the working copy are
+    /// snapshotted, so they count as a change to the repo
+    ///
+    /// Runs the jj binary with either backend, as snapshotting is left to
+    /// jj itself everywhere else.
+    pub fn snapshot_operation_id(&self) -> Result<String> {
+        JjCli::open(self.workspace_root())?.snapshot_operation_id()
+    }
+
+    /// Restore the repo's commits and local bookmarks to operation `op_id`
+    /// (`jj operation restore --what repo`)
+    ///
+    /// Remote-tracking bookmarks keep their current positions, which still
+    /// match the remote. Runs the jj binary with either backend, since
+    /// restoring also updates the working copy on disk.
+    pub fn restore_operation(&mut self, op_id: &str) -> Result<()> {
+        let root = self.workspace_root().to_path_buf();
+        self.with_lock_retry(&format!("restore operation {op_id}"), |_| {
+            JjCli::open(&root)?.restore_operation(op_id)
+        })
+    }
+
+    /// Get the default branch name
+    ///
+    /// Uses `default-branch` from the config files if set, otherwise checks
+    /// the remote HEAD first, then common names.
+    pub fn default_branch(&self) -> Result<String> {
+        if let Some(branch) = &self.config.default_branch {
+            return Ok(branch.clone());
+        }
+        match &self.backend {
+            Backend::Lib(lib) => lib.default_branch(),
+            Backend::Cli(cli) => cli.default_branch(),
+        }
+    }
+
+    /// Remote-tracking bookmark `trunk()` resolves to, as `(bookmark, remote)`
+    ///
+    /// `None` unless the default branch is at trunk on exactly one remote;
+    /// when a fork and its upstream agree, nothing singles either out.
+    pub fn trunk_bookmark(&self) -> Result<Option<(String, String)>> {
+        let Some(trunk) = self.resolve_revset("trunk()")?.into_iter().next() else {
+            return Ok(None);
+        };
+        let branch = self.default_branch()?;
+        let mut tracking = Vec::new();
+        for remote in self.git_remotes()? {
+            let at_trunk = self
+                .get_remote_bookmark(&branch, &remote.name)?
+                .is_some_and(|bookmark| bookmark.commit_id == trunk.commit_id);
+            if at_trunk {
+                tracking.push(remote.name);
+            }
+        }
+        Ok(match <[String; 1]>::try_from(tracking) {
+            Ok([remote]) => Some((branch, remote)),
+            Err(_) => None,
+        })
+    }
+
+    /// Select the remote to work against
+    ///
+    /// `specified` (a `--remote` flag) wins, then `remote` from the config
+    /// files, then the remote trunk tracks (see [`select_primary_remote`]).
+    pub fn primary_remote(&self, remotes: &[GitRemote], specified: Option<&str>) -> Result<String> {
+        let specified = specified.or(self.config.remote.as_deref());
+        let trunk = if specified.is_none() && remotes.len() > 1 {
+            self.trunk_bookmark().unwrap_or_else(|e| {
+                debug!(error = %e, "couldn't tell which remote trunk tracks");
+                None
+            })
+        } else {
+            None
+        };
+        if let Some((bookmark, remote)) = &trunk {
+            debug!(%bookmark, %remote, "trunk tracks a remote bookmark");
+        }
+        select_primary_remote(
+            remotes,
+            specified,
+            trunk.as_ref().map(|(_, remote)| remote.as_str()),
+        )
+    }
+
+    /// Select the remote bookmarks are pushed to for `primary`
+    ///
+    /// `fork-remote` from the config files when set (see
+    /// [`select_push_remote`]); PRs are still opened on `primary`.
+    pub fn push_remote(&self, remotes: &[GitRemote], primary: &str) -> Result<String> {
+        select_push_remote(remotes, primary, self.config.fork_remote.as_deref())
+    }
+
+    /// Get the workspace root path
+    pub fn workspace_root(&self) -> &Path {
+        match &self.backend {
+            Backend::Lib(lib) => lib.workspace_root(),
+            Backend::Cli(cli) => cli.workspace_root(),
+        }
+    }
+
+    /// Email of the user running ryu, as configured for jj
+    ///
+    /// Checks `JJ_EMAIL`, then `jj config get user.email`. The settings this
+    /// wrapper loads are synthetic, so jj's own config is consulted instead.
+    /// Returns `None` if no email is configured.
+    pub fn user_email(&self) -> Option<String> {
+        if let Ok(email) = std::env::var("JJ_EMAIL") {
+            if !email.trim().is_empty() {
+                return Some(email.trim().to_string());
+            }
+        }
+
+        let output = std::process::Command::new("jj")
+            .args(["config", "get", "user.email"])
+            .current_dir(self.workspace_root())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!email.is_empty()).then_some(email)
+    }
+}
+
+/// Error for a failed workspace or repo load
+///
+/// A newer jj may have written data the linked jj-lib can't decode; say so
+/// rather than surfacing the raw deserialization error.
+fn load_error(context: &str, err: &dyn std::fmt::Display) -> Error {
+    let detail = format!("{context}: {err}");
+    match check_jj_compatibility(installed_jj_version().as_deref()) {
+        JjCompatibility::Newer(installed) => Error::IncompatibleJj {
+            installed,
+            supported: supported_jj_version(),
+            detail,
+        },
+        _ => Error::Workspace(detail),
+    }
+}
+
+/// Push URL of a remote with `url.<base>.insteadOf` and `pushInsteadOf`
+/// rewrites applied, read via the git CLI
+///
+/// Git reads rewrites from system, global, and repo config alike, which
+/// the repo-scoped config jj opens may not include. Returns `None` if git
+/// is unavailable or the remote has no URL.
+fn rewritten_remote_url(git_dir: &Path, remote: &str) -> Option<String> {
+    let git_config = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .arg("--git-dir")
+            .arg(git_dir)
+            .arg("config")
+            .args(args)
+            .output()
+            .ok()?;
+        // git exits with 1 when nothing matches
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let rewrites = git_config(&["--get-regexp", r"^url\..*\.(push)?insteadof$"])
+        .map(|config| parse_url_rewrites(&config))
+        .unwrap_or_default();
+    // As in git, an explicit pushurl only gets insteadOf rewrites
+    let push_url_key = format!("remote.{remote}.pushurl");
+    if let Some(url) = git_config(&["--get", &push_url_key]) {
+        return Some(rewrite_remote_url(&url, &rewrites, false));
+    }
+    let url_key = format!("remote.{remote}.url");
+    let url = git_config(&["--get", &url_key]).filter(|url| !url.is_empty())?;
+    Some(rewrite_remote_url(&url, &rewrites, true))
+}
+
+/// Remotes `sync` fetches: the primary, plus `upstream` in a fork
+pub fn select_fetch_remotes(remotes: &[GitRemote], primary: &str) -> Vec<String> {
+    let mut fetch = vec![primary.to_string()];
+    if primary != UPSTREAM_REMOTE && remotes.iter().any(|r| r.name == UPSTREAM_REMOTE) {
+        fetch.push(UPSTREAM_REMOTE.to_string());
+    }
+    fetch
+}
+
+/// Remote bookmarks are pushed to: the fork remote if one is configured,
+/// else the primary remote
+pub fn select_push_remote(
+    remotes: &[GitRemote],
+    primary: &str,
+    fork_remote: Option<&str>,
+) -> Result<String> {
+    match fork_remote {
+        Some(fork) if !remotes.iter().any(|r| r.name == fork) => {
+            Err(Error::RemoteNotFound(fork.to_string()))
+        }
+        Some(fork) => Ok(fork.to_string()),
+        None => Ok(primary.to_string()),
+    }
+}
+
+/// Select a remote from a list of available remotes
+///
+/// - If `specified` is provided and exists, use it
+/// - If only one remote exists, use it
+/// - If multiple remotes exist, prefer "origin", else use first
+pub fn select_remote(remotes: &[GitRemote], specified: Option<&str>) -> Result<String> {
+    if remotes.is_empty() {
+        return Err(Error::NoSupportedRemotes);
+    }
+
+    if let Some(name) = specified {
+        if !remotes.iter().any(|r| r.name == name) {
+            return Err(Error::RemoteNotFound(name.to_string()));
+        }
+        return Ok(name.to_string());
+    }
+
+    if remotes.len() == 1 {
+        return Ok(remotes[0].name.clone());
+    }
+
+    // Multiple remotes: prefer "origin", else first
+    Ok(remotes
+        .iter()
+        .find(|r| r.name == "origin")
+        .map_or_else(|| remotes[0].name.clone(), |r| r.name.clone()))
+}
+
+/// Select a remote, preferring the one trunk's bookmark lives on
+///
+/// Like [`select_remote`], except that with several remotes and none
+/// specified, `trunk_remote` wins over "origin". In fork clones trunk
+/// usually tracks `main@upstream` while "origin" is the fork.
+pub fn select_primary_remote(
+    remotes: &[GitRemote],
+    specified: Option<&str>,
+    trunk_remote: Option<&str>,
+) -> Result<String> {
+    if specified.is_none() {
+        if let Some(remote) = trunk_remote.filter(|name| remotes.iter().any(|r| r.name == *name)) {
+            return Ok(remote.to_string());
+        }
+    }
+    select_remote(remotes, specified)
+}
+
+/// Environment variable with comma-separated remotes for `--mirror`
+pub const MIRROR_REMOTES_ENV: &str = "RYU_MIRROR_REMOTES";
+
+/// Environment variable with comma-separated remotes for `--pr-remote`
+pub const PR_REMOTES_ENV: &str = "RYU_PR_REMOTES";
+
+/// Remote names from a repeatable flag, else a comma-separated environment
+/// variable, else a config key
+pub fn requested_remotes(
+    flags: &[String],
+    var: &str,
+    configured: Option<&[String]>,
+) -> Vec<String> {
+    if !flags.is_empty() {
+        return flags.to_vec();
+    }
+    match std::env::var(var) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(ToString::to_string)
+            .collect(),
+        Err(_) => configured.map(<[String]>::to_vec).unwrap_or_default(),
+    }
+}
+
+/// Select mirror remotes that bookmarks are also pushed to
+///
+/// Every requested remote must exist. The primary remote and repeats are
+/// dropped, so callers can pass a shared list unchanged.
+pub fn select_mirror_remotes(
+    remotes: &[GitRemote],
+    primary: &str,
+    requested: &[String],
+) -> Result<Vec<String>> {
+    let mut mirrors: Vec<String> = Vec::new();
+    for name in requested {
+        if !remotes.iter().any(|r| &r.name == name) {
+            return Err(Error::RemoteNotFound(name.clone()));
+        }
+        if name != primary && !mirrors.contains(name) {
+            mirrors.push(name.clone());
+        }
+    }
+    Ok(mirrors)
+}
+
+/// Convert jj timestamp to chrono `DateTime`
+fn timestamp_to_datetime(ts: &Timestamp) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ts.timestamp.0)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_to_datetime() {
+        let ts = Timestamp {
+            timestamp: jj_lib::backend::MillisSinceEpoch(1_700_000_000_000),
+            tz_offset: 0,
+        };
+        let dt = timestamp_to_datetime(&ts);
+        assert_eq!(dt.timestamp_millis(), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_jj_config_candidates_defaults() {
+        let config_dir = PathBuf::from("C:\\Users\\me\\AppData\\Roaming");
+        let home = PathBuf::from("C:\\Users\\me");
+        let candidates = jj_config_candidates(None, Some(config_dir.clone()), Some(home.clone()));
+        assert_eq!(
+            candidates,
+            vec![
+                config_dir.join("jj").join("config.toml"),
+                config_dir.join("jj").join("conf.d"),
+                home.join(".config").join("jj").join("config.toml"),
+                home.join(".config").join("jj").join("conf.d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jj_config_candidates_dedupes_xdg_dir() {
+        // On Linux the platform config dir is ~/.config
+        let home = PathBuf::from("/home/me");
+        let candidates = jj_config_candidates(None, Some(home.join(".config")), Some(home.clone()));
+        assert_eq!(candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_jj_config_env_overrides_defaults() {
+        let paths = std::env::join_paths([Path::new("a.toml"), Path::new("conf")]).unwrap();
+        let candidates = jj_config_candidates(Some(paths), Some(PathBuf::from("cfg")), None);
+        assert_eq!(
+            candidates,
+            vec![PathBuf::from("a.toml"), PathBuf::from("conf")]
+        );
+    }
+
+    #[test]
+    fn test_create_user_settings() {
+        // Should not panic even without user config
+        let settings = create_user_settings();
+        assert!(settings.is_ok());
+    }
+}
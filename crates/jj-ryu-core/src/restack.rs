@@ -0,0 +1,237 @@
+//! Rebasing stacks onto an updated trunk
+//!
+//! After a fetch moves trunk, each stack still sits on the old trunk head.
+//! Restacking rebases every stack's bottom commit (with its descendants)
+//! onto the new head, like `jj rebase -s <bottom> -d 'trunk()'`. Bookmarks
+//! move with the rewritten commits, so the next submission plan force-pushes
+//! them.
+//!
+//! A restack in progress is journaled in `.jj/ryu/restack.json`. When it
+//! stops on conflicts (or an error), the entry stays behind so
+//! `ryu rebase-continue` can finish it once the conflicts are resolved.
+
+use crate::error::{Error, Result};
+use crate::repo::JjWorkspace;
+use crate::state::RyuState;
+use crate::submit::{Phase, ProgressCallback};
+use crate::types::BranchStack;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Journal file name, in the same directory as the state file
+const JOURNAL_FILE: &str = "restack.json";
+
+/// Stacks sharing a bottom commit that isn't on trunk's head
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Restack {
+    /// Commit ID of the stack's bottom change
+    pub root_commit_id: String,
+    /// Bookmarks of every segment above the root, bottom first
+    pub bookmarks: Vec<String>,
+}
+
+/// Commit ID `trunk()` resolves to, if any
+pub fn trunk_commit_id(workspace: &JjWorkspace) -> Result<Option<String>> {
+    Ok(workspace
+        .resolve_revset("trunk()")?
+        .into_iter()
+        .next()
+        .map(|entry| entry.commit_id))
+}
+
+/// Stacks in `stacks` that need rebasing onto `trunk_commit_id`
+///
+/// Forked stacks share their lower segments, so stacks are grouped by bottom
+/// commit and each root is rebased once.
+pub fn plan_restacks(stacks: &[&BranchStack], trunk_commit_id: &str) -> Vec<Restack> {
+    let mut restacks: Vec<Restack> = Vec::new();
+    for stack in stacks {
+        let Some(bottom) = stack
+            .segments
+            .first()
+            .and_then(|segment| segment.changes.last())
+        else {
+            continue;
+        };
+        if bottom
+            .parents
+            .iter()
+            .any(|parent| parent == trunk_commit_id)
+        {
+            continue;
+        }
+
+        let index = match restacks
+            .iter()
+            .position(|restack| restack.root_commit_id == bottom.commit_id)
+        {
+            Some(index) => index,
+            None => {
+                restacks.push(Restack {
+                    root_commit_id: bottom.commit_id.clone(),
+                    bookmarks: Vec::new(),
+                });
+                restacks.len() - 1
+            }
+        };
+        let bookmarks = &mut restacks[index].bookmarks;
+        for bookmark in stack
+            .segments
+            .iter()
+            .filter_map(|seg| seg.bookmarks.first())
+        {
+            if !bookmarks.contains(&bookmark.name) {
+                bookmarks.push(bookmark.name.clone());
+            }
+        }
+    }
+    restacks
+}
+
+/// A restack and how far it got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestackJournal {
+    /// Commit the stacks are being rebased onto
+    pub trunk_commit_id: String,
+    /// Every planned restack, in order
+    pub restacks: Vec<Restack>,
+    /// Number of `restacks` already rebased
+    pub done: usize,
+}
+
+impl RestackJournal {
+    /// Bookmarks of every planned restack, bottom first
+    pub fn bookmarks(&self) -> Vec<String> {
+        self.restacks
+            .iter()
+            .flat_map(|restack| restack.bookmarks.iter().cloned())
+            .collect()
+    }
+}
+
+/// Path of the restack journal for a workspace
+pub fn restack_journal_path(workspace_root: &Path) -> PathBuf {
+    RyuState::path(workspace_root).with_file_name(JOURNAL_FILE)
+}
+
+/// Load the journal of an unfinished restack, if there is one
+pub fn load_restack_journal(workspace_root: &Path) -> Result<Option<RestackJournal>> {
+    let path = restack_journal_path(workspace_root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(&path)?)?))
+}
+
+fn save_restack_journal(workspace_root: &Path, journal: &RestackJournal) -> Result<()> {
+    let path = restack_journal_path(workspace_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(journal)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Drop the restack journal once the restack finished
+pub fn clear_restack_journal(workspace_root: &Path) -> Result<()> {
+    let path = restack_journal_path(workspace_root);
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Rebase each planned stack onto `trunk_commit_id`, reporting every
+/// rebased segment
+///
+/// Returns the restacked bookmarks. Fails with [`Error::RestackConflicts`]
+/// if any of them ended up conflicted; the rebase itself is kept so it can
+/// be resolved (or undone) with jj, and the journal is kept for
+/// [`continue_restack`].
+pub async fn restack_onto_trunk(
+    workspace: &mut JjWorkspace,
+    restacks: &[Restack],
+    trunk_commit_id: &str,
+    progress: &dyn ProgressCallback,
+) -> Result<Vec<String>> {
+    if restacks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut journal = RestackJournal {
+        trunk_commit_id: trunk_commit_id.to_string(),
+        restacks: restacks.to_vec(),
+        done: 0,
+    };
+    run_restacks(workspace, &mut journal, progress).await
+}
+
+/// Finish the restack journaled in the workspace
+///
+/// Fails with [`Error::RestackConflicts`] while the stacks rebased so far
+/// are still conflicted; otherwise rebases the rest and returns every
+/// restacked bookmark.
+pub async fn continue_restack(
+    workspace: &mut JjWorkspace,
+    progress: &dyn ProgressCallback,
+) -> Result<Vec<String>> {
+    let mut journal = load_restack_journal(workspace.workspace_root())?
+        .ok_or_else(|| Error::InvalidArgument("No interrupted restack to continue".to_string()))?;
+
+    let rebased: Vec<String> = journal.restacks[..journal.done]
+        .iter()
+        .flat_map(|restack| restack.bookmarks.iter().cloned())
+        .collect();
+    let conflicted = conflicted_bookmarks(workspace, &rebased)?;
+    if !conflicted.is_empty() {
+        return Err(Error::RestackConflicts(conflicted));
+    }
+
+    run_restacks(workspace, &mut journal, progress).await
+}
+
+/// Rebase the journal's remaining restacks, saving progress after each
+async fn run_restacks(
+    workspace: &mut JjWorkspace,
+    journal: &mut RestackJournal,
+    progress: &dyn ProgressCallback,
+) -> Result<Vec<String>> {
+    let root = workspace.workspace_root().to_path_buf();
+    progress.on_phase(Phase::Restacking).await;
+
+    save_restack_journal(&root, journal)?;
+    while let Some(restack) = journal.restacks.get(journal.done) {
+        workspace.rebase_onto(&restack.root_commit_id, &journal.trunk_commit_id)?;
+        for bookmark in &restack.bookmarks {
+            progress.on_segment_restacked(bookmark).await;
+        }
+        journal.done += 1;
+        save_restack_journal(&root, journal)?;
+    }
+
+    let restacked = journal.bookmarks();
+    let conflicted = conflicted_bookmarks(workspace, &restacked)?;
+    if !conflicted.is_empty() {
+        return Err(Error::RestackConflicts(conflicted));
+    }
+
+    clear_restack_journal(&root)?;
+    Ok(restacked)
+}
+
+/// Bookmarks in `bookmarks` with a conflicted change between trunk and them
+fn conflicted_bookmarks(workspace: &JjWorkspace, bookmarks: &[String]) -> Result<Vec<String>> {
+    let mut conflicted = Vec::new();
+    for bookmark in bookmarks {
+        let Some(local) = workspace.get_local_bookmark(bookmark)? else {
+            continue;
+        };
+        let revset = format!("trunk()..{} & conflicts()", local.commit_id);
+        if !workspace.resolve_revset(&revset)?.is_empty() {
+            conflicted.push(bookmark.clone());
+        }
+    }
+    Ok(conflicted)
+}
@@ -0,0 +1,174 @@
+//! Automatic reviewer assignment from a configured pool
+//!
+//! With a `reviewer-pool` configured (or `RYU_REVIEWERS` set), each newly
+//! created PR gets one reviewer from the pool so a deep stack is spread
+//! across people instead of landing on one.
+//! Round-robin rotation continues across runs via the state store;
+//! least-loaded asks the platform how many open reviews each candidate has.
+
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::types::PullRequest;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Environment variable with the comma-separated reviewer pool
+pub const REVIEWERS_ENV: &str = "RYU_REVIEWERS";
+
+/// Environment variable selecting the [`ReviewerPolicy`]
+pub const REVIEWER_POLICY_ENV: &str = "RYU_REVIEWER_POLICY";
+
+/// How reviewers are picked from the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewerPolicy {
+    /// Rotate through the pool in order
+    #[default]
+    RoundRobin,
+    /// Pick whoever has the fewest open review requests
+    LeastLoaded,
+}
+
+impl FromStr for ReviewerPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "round-robin" | "" => Ok(Self::RoundRobin),
+            "least-loaded" => Ok(Self::LeastLoaded),
+            other => Err(Error::Config(format!(
+                "invalid {REVIEWER_POLICY_ENV} '{other}': expected round-robin or least-loaded"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for ReviewerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::LeastLoaded => write!(f, "least-loaded"),
+        }
+    }
+}
+
+/// A configured pool of reviewers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewerPool {
+    /// Logins or usernames, in rotation order
+    pub reviewers: Vec<String>,
+    /// How to pick from the pool
+    pub policy: ReviewerPolicy,
+}
+
+impl ReviewerPool {
+    /// Pool configured by `reviewer-pool` and `reviewer-policy`
+    ///
+    /// `RYU_REVIEWERS` and `RYU_REVIEWER_POLICY` win over the config files.
+    /// Returns `None` when no reviewers are configured.
+    pub fn from_config(config: &RyuConfig) -> Result<Option<Self>> {
+        let reviewers: Vec<String> = match std::env::var(REVIEWERS_ENV) {
+            Ok(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+            Err(_) => config.reviewer_pool.clone().unwrap_or_default(),
+        };
+        if reviewers.is_empty() {
+            return Ok(None);
+        }
+        let policy = match std::env::var(REVIEWER_POLICY_ENV) {
+            Ok(value) => value.parse()?,
+            Err(_) => config.reviewer_policy.unwrap_or_default(),
+        };
+        Ok(Some(Self { reviewers, policy }))
+    }
+}
+
+/// A reviewer picked for a PR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewerAssignment {
+    /// PR number
+    pub pr_number: u64,
+    /// Login or username of the reviewer
+    pub reviewer: String,
+}
+
+/// Next reviewer in rotation from `cursor`, skipping `exclude`
+///
+/// Advances `cursor` past the pick. Returns `None` if nobody is eligible.
+pub fn next_round_robin(
+    reviewers: &[String],
+    cursor: &mut usize,
+    exclude: Option<&str>,
+) -> Option<String> {
+    for _ in 0..reviewers.len() {
+        let candidate = &reviewers[*cursor % reviewers.len()];
+        *cursor = (*cursor + 1) % reviewers.len();
+        if !exclude.is_some_and(|e| e.eq_ignore_ascii_case(candidate)) {
+            return Some(candidate.clone());
+        }
+    }
+    None
+}
+
+/// Reviewer with the lowest load, earliest in the pool on ties
+///
+/// Increments the pick's load so repeated calls spread the work.
+pub fn next_least_loaded(loads: &mut [(String, usize)]) -> Option<String> {
+    let (reviewer, load) = loads.iter_mut().min_by_key(|(_, load)| *load)?;
+    *load += 1;
+    Some(reviewer.clone())
+}
+
+/// Request one reviewer from `pool` on each of `prs`, in order
+///
+/// The authenticated user is never picked for their own PRs. `cursor` is
+/// the persisted round-robin position and is advanced as reviewers are
+/// picked. Returns the assignments made; a PR whose request fails stops
+/// the run with that error.
+pub async fn assign_reviewers(
+    platform: &dyn PlatformService,
+    pool: &ReviewerPool,
+    prs: &[PullRequest],
+    cursor: &mut usize,
+) -> Result<Vec<ReviewerAssignment>> {
+    if prs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let author = platform.current_user().await.ok();
+    let author = author.as_deref();
+
+    let mut loads = Vec::new();
+    if pool.policy == ReviewerPolicy::LeastLoaded {
+        for reviewer in &pool.reviewers {
+            if author.is_some_and(|a| a.eq_ignore_ascii_case(reviewer)) {
+                continue;
+            }
+            let load = platform.count_review_requests(reviewer).await?;
+            loads.push((reviewer.clone(), load));
+        }
+    }
+
+    let mut assignments = Vec::with_capacity(prs.len());
+    for pr in prs {
+        let reviewer = match pool.policy {
+            ReviewerPolicy::RoundRobin => next_round_robin(&pool.reviewers, cursor, author),
+            ReviewerPolicy::LeastLoaded => next_least_loaded(&mut loads),
+        };
+        let Some(reviewer) = reviewer else {
+            break;
+        };
+        platform
+            .request_reviewers(pr.number, std::slice::from_ref(&reviewer))
+            .await?;
+        assignments.push(ReviewerAssignment {
+            pr_number: pr.number,
+            reviewer,
+        });
+    }
+    Ok(assignments)
+}
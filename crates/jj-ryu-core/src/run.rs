@@ -0,0 +1,132 @@
+//! Context of one ryu run
+//!
+//! Settings a caller gives one run - such as command-line flags or the
+//! retry policy - and what it learns from the core while it works - such as
+//! the writes read-only mode skipped or the waits for a repo lock - go
+//! through a [`RunContext`] rather than process-wide state. A
+//! [`JjWorkspace`] is opened with one and hands it to the platform services
+//! created for it, so two runs in one process (tests, or a server driving
+//! ryu) each get their own.
+//!
+//! [`JjWorkspace`]: crate::repo::JjWorkspace
+
+use crate::budget::{ApiBudget, BudgetUse};
+use crate::config::RyuConfig;
+use crate::metrics::MetricsRecorder;
+use crate::platform::{RetryObserver, RetryPolicy, RetryWait};
+use crate::read_only::ReadOnlyObserver;
+use crate::repo::{LockWait, LockWaitObserver};
+use crate::undo::UndoRecorder;
+use tracing::info;
+
+/// Settings and observers of one run
+#[derive(Default)]
+pub struct RunContext {
+    config: RyuConfig,
+    retry_policy: RetryPolicy,
+    budget: BudgetUse,
+    metrics: MetricsRecorder,
+    undo: UndoRecorder,
+    read_only_observer: Option<Box<dyn ReadOnlyObserver>>,
+    retry_observer: Option<Box<dyn RetryObserver>>,
+    lock_wait_observer: Option<Box<dyn LockWaitObserver>>,
+}
+
+impl RunContext {
+    /// Overlay `config` (e.g. from flags) on the config files of every
+    /// workspace opened for the run
+    #[must_use]
+    pub fn with_config(mut self, config: RyuConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Settings overlaid on the config files
+    pub const fn config(&self) -> &RyuConfig {
+        &self.config
+    }
+
+    /// Retry platform requests as `policy` allows
+    #[must_use]
+    pub const fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// How platform requests are retried
+    pub const fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Cap the run's platform calls and time at `budget`, starting its clock
+    #[must_use]
+    pub fn with_budget(mut self, budget: ApiBudget) -> Self {
+        self.budget = BudgetUse::new(budget);
+        self
+    }
+
+    /// What the run has used of its API budget
+    pub const fn budget(&self) -> &BudgetUse {
+        &self.budget
+    }
+
+    /// The run's counters for verbose summaries
+    pub const fn metrics(&self) -> &MetricsRecorder {
+        &self.metrics
+    }
+
+    /// The run's record for `ryu undo`
+    pub const fn undo(&self) -> &UndoRecorder {
+        &self.undo
+    }
+
+    /// Tell `observer` about each write read-only mode skips
+    #[must_use]
+    pub fn with_read_only_observer(mut self, observer: Box<dyn ReadOnlyObserver>) -> Self {
+        self.read_only_observer = Some(observer);
+        self
+    }
+
+    /// Record that read-only mode skipped `action`
+    pub fn skipped(&self, action: &str) {
+        info!(action, "read-only: skipped");
+        if let Some(observer) = &self.read_only_observer {
+            observer.skipped(action);
+        }
+    }
+
+    /// Tell `observer` whenever a platform request pauses before a retry
+    #[must_use]
+    pub fn with_retry_observer(mut self, observer: Box<dyn RetryObserver>) -> Self {
+        self.retry_observer = Some(observer);
+        self
+    }
+
+    /// Report that a platform request is about to wait before a retry
+    pub fn retry_waiting(&self, wait: &RetryWait) {
+        if let Some(observer) = &self.retry_observer {
+            observer.waiting(wait);
+        }
+    }
+
+    /// Tell `observer` when a repo operation waits for another process's lock
+    #[must_use]
+    pub fn with_lock_wait_observer(mut self, observer: Box<dyn LockWaitObserver>) -> Self {
+        self.lock_wait_observer = Some(observer);
+        self
+    }
+
+    /// Report that a repo operation is about to wait for a lock
+    pub fn lock_waiting(&self, wait: &LockWait) {
+        if let Some(observer) = &self.lock_wait_observer {
+            observer.waiting(wait);
+        }
+    }
+
+    /// Report that a repo operation got its lock or gave up waiting
+    pub fn lock_wait_finished(&self) {
+        if let Some(observer) = &self.lock_wait_observer {
+            observer.finished();
+        }
+    }
+}
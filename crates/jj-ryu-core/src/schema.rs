@@ -0,0 +1,100 @@
+//! JSON Schemas for serialized output
+//!
+//! Integrators consuming ryu's JSON can validate against, or generate code
+//! from, these schemas. They are derived from the Rust types with `schemars`,
+//! so they always match what ryu serializes.
+
+use crate::error::{Error, Result};
+use crate::hover::LineContext;
+use crate::submit::{
+    ExportedPlan, ProgressEvent, SubmissionPlan, SubmissionResult, SubmitReport, SyncReport,
+};
+use crate::types::ChangeGraph;
+use schemars::{Schema, schema_for};
+use std::str::FromStr;
+
+/// A type with a published schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    /// [`ChangeGraph`] - bookmarks and stacks from analysis
+    ChangeGraph,
+    /// [`SubmissionPlan`] - steps a submission will perform
+    SubmissionPlan,
+    /// [`SubmissionResult`] - outcome of a submission
+    SubmissionResult,
+    /// [`ProgressEvent`] - one entry of the progress event stream
+    ProgressEvent,
+    /// [`SubmitReport`] - `ryu submit --format json` output
+    SubmitReport,
+    /// [`SyncReport`] - `ryu sync --format json` output
+    SyncReport,
+    /// [`ExportedPlan`] - plan file written by `ryu plan`
+    ExportedPlan,
+    /// [`LineContext`] - `ryu hover --format json` output (or `null`)
+    LineContext,
+}
+
+impl SchemaType {
+    /// Every published schema type
+    pub const ALL: [Self; 8] = [
+        Self::ChangeGraph,
+        Self::SubmissionPlan,
+        Self::SubmissionResult,
+        Self::ProgressEvent,
+        Self::SubmitReport,
+        Self::SyncReport,
+        Self::ExportedPlan,
+        Self::LineContext,
+    ];
+
+    /// Kebab-case name, as accepted by `ryu schema`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ChangeGraph => "change-graph",
+            Self::SubmissionPlan => "submission-plan",
+            Self::SubmissionResult => "submission-result",
+            Self::ProgressEvent => "progress-event",
+            Self::SubmitReport => "submit-report",
+            Self::SyncReport => "sync-report",
+            Self::ExportedPlan => "exported-plan",
+            Self::LineContext => "line-context",
+        }
+    }
+
+    /// Generate the JSON Schema for this type
+    pub fn schema(self) -> Schema {
+        match self {
+            Self::ChangeGraph => schema_for!(ChangeGraph),
+            Self::SubmissionPlan => schema_for!(SubmissionPlan),
+            Self::SubmissionResult => schema_for!(SubmissionResult),
+            Self::ProgressEvent => schema_for!(ProgressEvent),
+            Self::SubmitReport => schema_for!(SubmitReport),
+            Self::SyncReport => schema_for!(SyncReport),
+            Self::ExportedPlan => schema_for!(ExportedPlan),
+            Self::LineContext => schema_for!(LineContext),
+        }
+    }
+}
+
+impl FromStr for SchemaType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|t| t.name()).collect();
+                Error::InvalidArgument(format!(
+                    "unknown schema '{s}' (expected one of: {})",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+impl std::fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,87 @@
+//! Persistent per-repository state
+//!
+//! Small amounts of user intent that can't be derived from the repository
+//! or the platform (e.g. PR base overrides, stack locks) are stored as JSON under
+//! `.jj/ryu/state.json` in the workspace, next to jj's own data.
+
+use crate::error::Result;
+use crate::landed::LandedChange;
+use crate::lock::StackLock;
+use crate::read_only::is_read_only;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// State directory relative to the workspace root
+const STATE_DIR: &str = ".jj/ryu";
+
+/// State file name within [`STATE_DIR`]
+const STATE_FILE: &str = "state.json";
+
+/// Persistent ryu state for a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RyuState {
+    /// PR base branch overrides by bookmark name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub base_overrides: BTreeMap<String, String>,
+    /// Locks held by long-running stack operations, by stack key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stack_locks: BTreeMap<String, StackLock>,
+    /// Position of the next round-robin reviewer in the reviewer pool
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub reviewer_cursor: usize,
+    /// Local changes whose PR was merged, by change ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub landed: BTreeMap<String, LandedChange>,
+    /// PRs opened outside ryu and taken over with `ryu adopt`, by bookmark
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub adopted_prs: BTreeMap<String, u64>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde passes fields by reference
+const fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl RyuState {
+    /// State directory of a workspace, which also holds ryu's other files
+    pub fn dir(workspace_root: &Path) -> PathBuf {
+        workspace_root.join(STATE_DIR)
+    }
+
+    /// Path of the state file for a workspace
+    pub fn path(workspace_root: &Path) -> PathBuf {
+        Self::dir(workspace_root).join(STATE_FILE)
+    }
+
+    /// Load state for a workspace, returning defaults if none was saved
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = Self::path(workspace_root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Save state for a workspace
+    ///
+    /// Writes to a temporary file first so a crash never leaves a truncated
+    /// state file behind. Does nothing in read-only mode.
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        if is_read_only() {
+            return Ok(());
+        }
+        let path = Self::path(workspace_root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Stack status: what the platform knows about each bookmark
+//!
+//! Joins the change graph with each bookmark's most recent PR (open, draft,
+//! merged or closed) and checks that open PRs target the base submit would
+//! give them, and looks up the CI and review state of open PRs. Read-only;
+//! nothing is pushed or updated.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::{analyze_submission, get_base_branch, select_bookmark_for_segment};
+use crate::types::{
+    ChangeGraph, ChecksState, NarrowedBookmarkSegment, PrState, PullRequest, ReviewState,
+};
+use std::collections::HashMap;
+
+/// Status of one bookmark in a stack
+#[derive(Debug, Clone)]
+pub struct BookmarkStatus {
+    /// The bookmark and its changes
+    pub segment: NarrowedBookmarkSegment,
+    /// Most recent PR for the bookmark, with its state
+    pub pr: Option<(PullRequest, PrState)>,
+    /// Base the PR should target (trunk, the bookmark below, or an override)
+    pub expected_base: String,
+    /// CI state of the PR, if it's open or a draft
+    pub checks: Option<ChecksState>,
+    /// Review state of the PR, if it's open or a draft
+    pub review: Option<ReviewState>,
+}
+
+impl BookmarkStatus {
+    /// Whether an open PR targets the wrong base
+    ///
+    /// Merged and closed PRs are never considered misplaced.
+    pub fn base_mismatch(&self) -> bool {
+        self.pr.as_ref().is_some_and(|(pr, state)| {
+            matches!(state, PrState::Open | PrState::Draft) && pr.base_ref != self.expected_base
+        })
+    }
+}
+
+/// Status of a stack, trunk first
+#[derive(Debug, Clone)]
+pub struct StackStatus {
+    /// Leaf bookmark naming the stack
+    pub leaf: String,
+    /// One entry per segment, from trunk to leaf
+    pub bookmarks: Vec<BookmarkStatus>,
+}
+
+/// Query the platform for the PR state of every bookmark in every stack,
+/// and the CI and review state of the open ones
+///
+/// Each segment is narrowed to one bookmark the way `submit <leaf>` would.
+/// `overrides` are the PR base overrides from the state store.
+pub async fn stack_statuses(
+    graph: &ChangeGraph,
+    platform: &dyn PlatformService,
+    default_branch: &str,
+    overrides: &HashMap<String, String>,
+) -> Result<Vec<StackStatus>> {
+    let mut statuses = Vec::new();
+    for stack in &graph.stacks {
+        let Some(leaf_segment) = stack.segments.last() else {
+            continue;
+        };
+        let leaf = select_bookmark_for_segment(leaf_segment, None).name;
+        let analysis = analyze_submission(graph, &leaf)?;
+
+        let mut bookmarks = Vec::with_capacity(analysis.segments.len());
+        for segment in &analysis.segments {
+            let name = &segment.bookmark.name;
+            let pr = platform.find_latest_pr(name).await?;
+            let (checks, review) = match &pr {
+                Some((pr, PrState::Open | PrState::Draft)) => {
+                    let checks = platform.get_check_status(pr.number).await?;
+                    let reviews = platform.list_reviews(pr.number).await?;
+                    (Some(checks), Some(ReviewState::from_reviews(&reviews)))
+                }
+                _ => (None, None),
+            };
+            bookmarks.push(BookmarkStatus {
+                pr,
+                checks,
+                review,
+                expected_base: get_base_branch(
+                    name,
+                    &analysis.segments,
+                    default_branch,
+                    overrides,
+                )?,
+                segment: segment.clone(),
+            });
+        }
+        statuses.push(StackStatus { leaf, bookmarks });
+    }
+    Ok(statuses)
+}
+
+/// An open PR with its CI and review state
+#[derive(Debug, Clone)]
+pub struct OpenPrState {
+    /// The PR
+    pub pr: PullRequest,
+    /// State of its CI checks
+    pub checks: ChecksState,
+    /// Where its review stands
+    pub review: ReviewState,
+}
+
+/// The open PR of each bookmark that has one, with its CI and review state
+///
+/// Bookmarks without an open PR are left out.
+pub async fn open_pr_states(
+    platform: &dyn PlatformService,
+    bookmarks: &[String],
+) -> Result<HashMap<String, OpenPrState>> {
+    let mut states = HashMap::new();
+    for found in platform.find_branch_prs(bookmarks).await? {
+        let Some(pr) = found.prs.into_iter().next() else {
+            continue;
+        };
+        // Platforms that batch the lookup return the checks and reviews with it
+        let checks = match found.readiness {
+            Some(readiness) => readiness.checks,
+            None => platform.get_check_status(pr.number).await?,
+        };
+        let reviews = match found.reviews {
+            Some(reviews) => reviews,
+            None => platform.list_reviews(pr.number).await?,
+        };
+        let review = ReviewState::from_reviews(&reviews);
+        states.insert(found.head_branch, OpenPrState { pr, checks, review });
+    }
+    Ok(states)
+}
+
+/// Bookmarks whose most recent PR was merged, with that PR
+///
+/// Bookmarks reopened since (a newer open PR) or never submitted are left
+/// out; these are the bookmarks `clean` deletes.
+pub async fn merged_bookmarks(
+    platform: &dyn PlatformService,
+    bookmarks: &[String],
+) -> Result<Vec<(String, PullRequest)>> {
+    let mut merged = Vec::new();
+    for bookmark in bookmarks {
+        if let Some((pr, PrState::Merged)) = platform.find_latest_pr(bookmark).await? {
+            merged.push((bookmark.clone(), pr));
+        }
+    }
+    Ok(merged)
+}
@@ -2,8 +2,12 @@
 //!
 //! Identifies what needs to be submitted for a given target bookmark.
 
+use crate::config::RyuConfig;
 use crate::error::{Error, Result};
+use crate::graph::remote_ahead_behind;
+use crate::repo::JjWorkspace;
 use crate::types::{Bookmark, BookmarkSegment, ChangeGraph, NarrowedBookmarkSegment};
+use std::collections::HashMap;
 
 /// Result of submission analysis
 #[derive(Debug, Clone)]
@@ -12,6 +16,103 @@ pub struct SubmissionAnalysis {
     pub target_bookmark: String,
     /// Segments to submit (from trunk towards target), each narrowed to one bookmark
     pub segments: Vec<NarrowedBookmarkSegment>,
+    /// PR base overrides by bookmark name: the user's (see [`RyuState`]),
+    /// and the fork point of each branch of a tree that doesn't stack on the
+    /// segment before it (see [`analyze_upstack_submission`])
+    ///
+    /// [`RyuState`]: crate::state::RyuState
+    pub base_overrides: HashMap<String, String>,
+}
+
+/// A commit in a submission that was authored by someone else
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignCommit {
+    /// Bookmark whose segment contains the commit
+    pub bookmark: String,
+    /// Git commit ID (hex)
+    pub commit_id: String,
+    /// Author email
+    pub author_email: String,
+    /// First line of commit description
+    pub description: String,
+}
+
+/// Find commits in a submission not authored by `user_email`
+///
+/// Emails are compared case-insensitively. Foreign commits usually mean a
+/// colleague's work was rebased into the stack by accident.
+pub fn find_foreign_commits(analysis: &SubmissionAnalysis, user_email: &str) -> Vec<ForeignCommit> {
+    analysis
+        .segments
+        .iter()
+        .flat_map(|segment| {
+            segment
+                .changes
+                .iter()
+                .filter(|change| !change.author_email.eq_ignore_ascii_case(user_email))
+                .map(|change| ForeignCommit {
+                    bookmark: segment.bookmark.name.clone(),
+                    commit_id: change.commit_id.clone(),
+                    author_email: change.author_email.clone(),
+                    description: change.description_first_line.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Environment variable that disables force pushes when set to `false`
+pub const PUSH_FORCE_ENV: &str = "RYU_PUSH_FORCE";
+
+/// Whether non-fast-forward pushes are allowed (`push-force`, default true)
+///
+/// `RYU_PUSH_FORCE` wins over the config files.
+pub fn force_push_allowed(config: &RyuConfig) -> Result<bool> {
+    let Ok(value) = std::env::var(PUSH_FORCE_ENV) else {
+        return Ok(config.push_force.unwrap_or(true));
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" | "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(Error::Config(format!(
+            "{PUSH_FORCE_ENV} must be true or false, got '{value}'"
+        ))),
+    }
+}
+
+/// A bookmark whose push would rewrite history on the remote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonFastForward {
+    /// Bookmark name
+    pub bookmark: String,
+    /// Local commits not on the remote
+    pub ahead: usize,
+    /// Remote commits the push would drop
+    pub behind: usize,
+}
+
+/// Find bookmarks in a submission whose push to `remote` isn't a fast-forward
+pub fn find_non_fast_forwards(
+    workspace: &JjWorkspace,
+    analysis: &SubmissionAnalysis,
+    remote: &str,
+) -> Result<Vec<NonFastForward>> {
+    let mut found = Vec::new();
+    for segment in &analysis.segments {
+        let bookmark = &segment.bookmark;
+        if !bookmark.has_remote || bookmark.is_synced {
+            continue;
+        }
+        if let Some(divergence) = remote_ahead_behind(workspace, bookmark, remote)? {
+            if !divergence.is_fast_forward() {
+                found.push(NonFastForward {
+                    bookmark: bookmark.name.clone(),
+                    ahead: divergence.ahead,
+                    behind: divergence.behind,
+                });
+            }
+        }
+    }
+    Ok(found)
 }
 
 /// Analyze what needs to be submitted for a given bookmark
@@ -51,6 +152,7 @@ pub fn analyze_submission(
             return Ok(SubmissionAnalysis {
                 target_bookmark: target_bookmark.to_string(),
                 segments: narrowed,
+                base_overrides: HashMap::new(),
             });
         }
     }
@@ -58,6 +160,58 @@ pub fn analyze_submission(
     Err(Error::BookmarkNotFound(target_bookmark.to_string()))
 }
 
+/// Analyze a submission of a bookmark and everything stacked on it
+///
+/// When several bookmarks stack on the same one the descendants form a
+/// tree. They follow the target depth-first, each branch right after the
+/// segment it forks from, so parents are still submitted before children.
+/// A branch that doesn't stack on the segment listed before it gets its
+/// fork point in `base_overrides`, so its PR isn't based on a sibling.
+pub fn analyze_upstack_submission(
+    graph: &ChangeGraph,
+    target_bookmark: &str,
+) -> Result<SubmissionAnalysis> {
+    let mut analysis = analyze_submission(graph, target_bookmark)?;
+    let Some(target_change_id) = graph.bookmark_to_change_id.get(target_bookmark) else {
+        return Ok(analysis);
+    };
+
+    // (segment, bookmark of the segment it stacks on), last popped first
+    let mut pending: Vec<(&str, String)> = graph
+        .children_of(target_change_id)
+        .into_iter()
+        .rev()
+        .map(|child| (child, target_bookmark.to_string()))
+        .collect();
+    while let Some((change_id, parent)) = pending.pop() {
+        let Some(segment) = graph.segment(change_id) else {
+            continue;
+        };
+        let bookmark = select_bookmark_for_segment(segment, None);
+        let after_parent = analysis
+            .segments
+            .last()
+            .is_some_and(|last| last.bookmark.name == parent);
+        if !after_parent {
+            analysis
+                .base_overrides
+                .insert(bookmark.name.clone(), parent);
+        }
+        pending.extend(
+            graph
+                .children_of(change_id)
+                .into_iter()
+                .rev()
+                .map(|child| (child, bookmark.name.clone())),
+        );
+        analysis.segments.push(NarrowedBookmarkSegment {
+            bookmark,
+            changes: segment.changes.clone(),
+        });
+    }
+    Ok(analysis)
+}
+
 /// Select a single bookmark from a segment using heuristics
 ///
 /// Selection priority:
@@ -118,14 +272,19 @@ fn is_temporary_bookmark(name: &str) -> bool {
 /// Get the expected base branch for a bookmark in a submission
 ///
 /// Returns the bookmark name that this bookmark should be based on,
-/// or the default branch name if it's the first in the stack.
+/// or the default branch name if it's the first in the stack. An entry in
+/// `overrides` (e.g. a maintenance branch) takes precedence over both.
 pub fn get_base_branch(
     bookmark_name: &str,
     segments: &[NarrowedBookmarkSegment],
     default_branch: &str,
+    overrides: &HashMap<String, String>,
 ) -> Result<String> {
     for (i, segment) in segments.iter().enumerate() {
         if segment.bookmark.name == bookmark_name {
+            if let Some(base) = overrides.get(bookmark_name) {
+                return Ok(base.clone());
+            }
             if i == 0 {
                 // First segment is based on default branch
                 return Ok(default_branch.to_string());
@@ -218,6 +377,7 @@ mod tests {
             author_name: "Test".to_string(),
             author_email: "test@example.com".to_string(),
             description_first_line: desc.to_string(),
+            description: desc.to_string(),
             parents: vec![],
             local_bookmarks: bookmarks.iter().map(ToString::to_string).collect(),
             remote_bookmarks: vec![],
@@ -233,12 +393,15 @@ mod tests {
         let bm2 = make_bookmark("feat-b");
 
         let stack = BranchStack {
+            id: bm2.change_id.clone(),
             segments: vec![
                 BookmarkSegment {
+                    id: bm1.change_id.clone(),
                     bookmarks: vec![bm1.clone()],
                     changes: vec![make_log_entry("First change", &["feat-a"])],
                 },
                 BookmarkSegment {
+                    id: bm2.change_id.clone(),
                     bookmarks: vec![bm2.clone()],
                     changes: vec![make_log_entry("Second change", &["feat-b"])],
                 },
@@ -255,7 +418,7 @@ mod tests {
             stack_leafs: HashSet::new(),
             stack_roots: HashSet::new(),
             stacks: vec![stack],
-            excluded_bookmark_count: 0,
+            excluded_bookmarks: Vec::new(),
         };
 
         let analysis = analyze_submission(&graph, "feat-b").unwrap();
@@ -279,7 +442,7 @@ mod tests {
             changes: vec![],
         }];
 
-        let base = get_base_branch("feat-a", &segments, "main").unwrap();
+        let base = get_base_branch("feat-a", &segments, "main", &HashMap::new()).unwrap();
         assert_eq!(base, "main");
     }
 
@@ -296,7 +459,7 @@ mod tests {
             },
         ];
 
-        let base = get_base_branch("feat-b", &segments, "main").unwrap();
+        let base = get_base_branch("feat-b", &segments, "main", &HashMap::new()).unwrap();
         assert_eq!(base, "feat-a");
     }
 
@@ -342,6 +505,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_single() {
         let segment = BookmarkSegment {
+            id: "feat-a_change".to_string(),
             bookmarks: vec![make_bookmark("feat-a")],
             changes: vec![],
         };
@@ -353,6 +517,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_prefers_target() {
         let segment = BookmarkSegment {
+            id: "feat-a_change".to_string(),
             bookmarks: vec![make_bookmark("feat-a"), make_bookmark("feat-b")],
             changes: vec![],
         };
@@ -364,6 +529,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_excludes_wip() {
         let segment = BookmarkSegment {
+            id: "feat-a-wip_change".to_string(),
             bookmarks: vec![make_bookmark("feat-a-wip"), make_bookmark("feat-a")],
             changes: vec![],
         };
@@ -375,6 +541,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_excludes_tmp() {
         let segment = BookmarkSegment {
+            id: "tmp-test_change".to_string(),
             bookmarks: vec![make_bookmark("tmp-test"), make_bookmark("feature")],
             changes: vec![],
         };
@@ -386,6 +553,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_excludes_backup() {
         let segment = BookmarkSegment {
+            id: "feat-backup_change".to_string(),
             bookmarks: vec![make_bookmark("feat-backup"), make_bookmark("feat")],
             changes: vec![],
         };
@@ -397,6 +565,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_excludes_old_suffix() {
         let segment = BookmarkSegment {
+            id: "feat-old_change".to_string(),
             bookmarks: vec![make_bookmark("feat-old"), make_bookmark("feat")],
             changes: vec![],
         };
@@ -408,6 +577,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_prefers_shorter() {
         let segment = BookmarkSegment {
+            id: "feature-implementation_change".to_string(),
             bookmarks: vec![
                 make_bookmark("feature-implementation"),
                 make_bookmark("feat"),
@@ -423,6 +593,7 @@ mod tests {
     fn test_select_bookmark_alphabetical_tiebreaker() {
         // Same length names - should pick alphabetically first
         let segment = BookmarkSegment {
+            id: "beta1_change".to_string(),
             bookmarks: vec![make_bookmark("beta1"), make_bookmark("alpha")],
             changes: vec![],
         };
@@ -435,6 +606,7 @@ mod tests {
     fn test_select_bookmark_prefers_shorter_over_alphabetical() {
         // Different length names - should pick shorter even if not alphabetically first
         let segment = BookmarkSegment {
+            id: "alpha_change".to_string(),
             bookmarks: vec![make_bookmark("alpha"), make_bookmark("beta")],
             changes: vec![],
         };
@@ -446,6 +618,7 @@ mod tests {
     #[test]
     fn test_select_bookmark_all_temporary_falls_back() {
         let segment = BookmarkSegment {
+            id: "wip-a_change".to_string(),
             bookmarks: vec![make_bookmark("wip-a"), make_bookmark("tmp-b")],
             changes: vec![],
         };
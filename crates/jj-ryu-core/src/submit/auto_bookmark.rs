@@ -0,0 +1,145 @@
+//! Bookmarks for changes that don't have one
+//!
+//! `submit --auto-bookmark` names every unbookmarked change between trunk
+//! and the target from a template (`auto-bookmark-template`, default
+//! `{user}/{slug}`), so each change gets its own PR without creating
+//! bookmarks by hand.
+
+use crate::error::{Error, Result};
+use crate::types::LogEntry;
+use std::collections::HashSet;
+
+/// Template used when `auto-bookmark-template` isn't set
+pub const DEFAULT_AUTO_BOOKMARK_TEMPLATE: &str = "{user}/{slug}";
+
+/// Longest slug taken from a description
+const MAX_SLUG_LEN: usize = 40;
+
+/// Length of the change ID prefix `{change_id}` expands to
+const SHORT_CHANGE_ID_LEN: usize = 8;
+
+/// A bookmark to create for an unbookmarked change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoBookmark {
+    /// Generated bookmark name
+    pub name: String,
+    /// Commit the bookmark points at
+    pub commit_id: String,
+    /// Change ID of that commit
+    pub change_id: String,
+    /// First line of the change's description
+    pub description: String,
+}
+
+/// Lowercase `text`, keeping letters and digits and joining the rest with
+/// single dashes, cut to a readable length
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+        // Cut at a word boundary when there is one
+        if let Some(end) = slug.rfind('-') {
+            slug.truncate(end);
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// User part of bookmark names: the local part of `email`, slugified
+pub fn bookmark_user(email: Option<&str>) -> String {
+    email
+        .and_then(|email| email.split('@').next())
+        .map(slugify)
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| "ryu".to_string())
+}
+
+/// Expand `template` for `change`
+///
+/// Placeholders: `{user}`, `{slug}` (from the description's first line) and
+/// `{change_id}` (short). A change without a description slugs to its short
+/// change ID.
+pub fn render_bookmark_name(template: &str, user: &str, change: &LogEntry) -> Result<String> {
+    let short_id = &change.change_id[..change.change_id.len().min(SHORT_CHANGE_ID_LEN)];
+    let slug = Some(slugify(&change.description_first_line))
+        .filter(|slug| !slug.is_empty())
+        .unwrap_or_else(|| short_id.to_string());
+
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        name.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            Error::Config(format!(
+                "unclosed '{{' in auto-bookmark-template '{template}'"
+            ))
+        })?;
+        match &rest[start + 1..start + end] {
+            "user" => name.push_str(user),
+            "slug" => name.push_str(&slug),
+            "change_id" => name.push_str(short_id),
+            other => {
+                return Err(Error::Config(format!(
+                    "unknown placeholder '{{{other}}}' in auto-bookmark-template (expected {{user}}, {{slug}} or {{change_id}})"
+                )));
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+/// Bookmarks to create for the changes in `changes` (newest first, as
+/// `trunk()..target` resolves) that have none, bottom first
+///
+/// The working-copy commit is skipped while it has no description. Names
+/// get `prefix` prepended if they don't start with it (so they pass
+/// `branch-prefix`), and a numeric suffix if `taken` already holds them.
+pub fn plan_auto_bookmarks(
+    changes: &[LogEntry],
+    template: &str,
+    user: &str,
+    prefix: Option<&str>,
+    taken: &HashSet<String>,
+) -> Result<Vec<AutoBookmark>> {
+    let mut used = taken.clone();
+    let mut planned = Vec::new();
+    for change in changes.iter().rev() {
+        if !change.local_bookmarks.is_empty() {
+            continue;
+        }
+        if change.is_working_copy && change.description.trim().is_empty() {
+            continue;
+        }
+
+        let mut base = render_bookmark_name(template, user, change)?;
+        if let Some(prefix) = prefix {
+            if !base.starts_with(prefix) {
+                base = format!("{prefix}{base}");
+            }
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while used.contains(&name) {
+            name = format!("{base}-{n}");
+            n += 1;
+        }
+        used.insert(name.clone());
+
+        planned.push(AutoBookmark {
+            name,
+            commit_id: change.commit_id.clone(),
+            change_id: change.change_id.clone(),
+            description: change.description_first_line.clone(),
+        });
+    }
+    Ok(planned)
+}
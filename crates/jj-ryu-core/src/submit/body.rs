@@ -0,0 +1,167 @@
+//! PR bodies rendered from commit messages
+//!
+//! New PRs get a body rendered from the segment's full commit descriptions;
+//! these helpers also rewrite it after commits are reworded.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::commits::apply_commits_section;
+use crate::submit::notes::{apply_notes_section, segment_notes, strip_notes};
+use crate::submit::stack_section::{apply_stack_section, stack_section};
+use crate::types::{LogEntry, NarrowedBookmarkSegment, PullRequest};
+
+/// Render a PR body from a segment's changes (stored newest first)
+///
+/// A single commit contributes its description minus the subject line, which
+/// is already the PR title. Multiple commits become a bulleted change list,
+/// oldest first: each subject line, with the rest of its description
+/// indented beneath it, under a commit list section with their change IDs.
+/// `ryu-note:` trailers are collected into a review notes section at the
+/// end.
+pub fn render_pr_body(changes: &[LogEntry]) -> String {
+    let body = match changes {
+        [] => String::new(),
+        [change] => description_body(&change.description),
+        _ => changes
+            .iter()
+            .rev()
+            .map(|change| {
+                let body = description_body(&change.description);
+                if body.is_empty() {
+                    format!("- {}", change.description_first_line)
+                } else {
+                    format!("- {}\n\n{}", change.description_first_line, indent(&body))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    apply_notes_section(
+        &apply_commits_section(&body, changes),
+        &segment_notes(changes),
+    )
+}
+
+/// Everything after the subject line of a commit description, trimmed and
+/// without note trailers
+fn description_body(description: &str) -> String {
+    strip_notes(description.split_once('\n').map_or("", |(_, rest)| rest))
+        .trim()
+        .to_string()
+}
+
+/// Indent every non-blank line so it continues the list item above
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A PR whose body may need re-rendering
+#[derive(Debug, Clone)]
+pub struct BodyUpdate {
+    /// Bookmark (head branch) of the PR
+    pub bookmark: String,
+    /// The PR itself
+    pub pr: PullRequest,
+    /// Body currently on the platform (line endings normalized)
+    pub current: String,
+    /// Body rendered from the current commit messages
+    pub rendered: String,
+}
+
+impl BodyUpdate {
+    /// Whether the rendered body differs from the current one
+    pub fn is_changed(&self) -> bool {
+        self.current.trim_end() != self.rendered.trim_end()
+    }
+
+    /// Line diff from the current body to the rendered one
+    pub fn diff(&self) -> Vec<DiffLine> {
+        diff_lines(&self.current, &self.rendered)
+    }
+}
+
+/// Fetch each segment's PR and render its body
+///
+/// Returns one entry per segment that has an open PR, in segment order;
+/// bookmarks without a PR are skipped. A stack section in the current body
+/// is carried over to the rendered one.
+pub async fn plan_body_updates(
+    platform: &dyn PlatformService,
+    segments: &[NarrowedBookmarkSegment],
+) -> Result<Vec<BodyUpdate>> {
+    let mut updates = Vec::new();
+    for segment in segments {
+        let Some(pr) = platform.find_existing_pr(&segment.bookmark.name).await? else {
+            continue;
+        };
+        let current = platform.get_pr_body(pr.number).await?.replace("\r\n", "\n");
+        let rendered = apply_stack_section(
+            &render_pr_body(&segment.changes),
+            stack_section(&current).unwrap_or_default(),
+        );
+        updates.push(BodyUpdate {
+            bookmark: segment.bookmark.name.clone(),
+            pr,
+            current,
+            rendered,
+        });
+    }
+    Ok(updates)
+}
+
+/// One line of a [`diff_lines`] result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Present in both
+    Same(String),
+    /// Only in the old text
+    Removed(String),
+    /// Only in the new text
+    Added(String),
+}
+
+/// Line-based diff of two texts (longest common subsequence)
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed((*l).to_string())));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added((*l).to_string())));
+    diff
+}
@@ -0,0 +1,60 @@
+//! Commit list for PRs of multi-commit segments
+//!
+//! A PR covering several commits gets a marker-delimited section near the
+//! top of its body listing them, oldest first, by short change ID and
+//! subject line. Submit rewrites the section as commits are added, dropped
+//! or reworded, leaving the rest of the body alone.
+
+use crate::types::LogEntry;
+
+/// Start of the commit list section in a PR body
+pub const COMMITS_SECTION_START: &str = "<!--- JJ-RYU_COMMITS --->";
+/// End of the commit list section in a PR body
+pub const COMMITS_SECTION_END: &str = "<!--- /JJ-RYU_COMMITS --->";
+
+/// Change ID characters shown per commit
+const SHORT_CHANGE_ID_LEN: usize = 8;
+
+/// Render the commit list section for a segment's changes (stored newest
+/// first); empty for a single commit, whose PR title already says it all
+pub fn render_commits_section(changes: &[LogEntry]) -> String {
+    if changes.len() < 2 {
+        return String::new();
+    }
+    let items: Vec<String> = changes
+        .iter()
+        .rev()
+        .map(|change| {
+            let short_id = &change.change_id[..change.change_id.len().min(SHORT_CHANGE_ID_LEN)];
+            format!("- `{short_id}` {}", change.description_first_line)
+        })
+        .collect();
+    format!(
+        "{COMMITS_SECTION_START}\n### Commits\n\n{}\n{COMMITS_SECTION_END}",
+        items.join("\n")
+    )
+}
+
+/// Replace (or add, or remove) the commit list section in a PR body
+///
+/// Text outside the markers is preserved; a new section goes at the top.
+pub fn apply_commits_section(body: &str, changes: &[LogEntry]) -> String {
+    let section = render_commits_section(changes);
+
+    let existing = body.find(COMMITS_SECTION_START).and_then(|start| {
+        body[start..]
+            .find(COMMITS_SECTION_END)
+            .map(|end| (start, start + end + COMMITS_SECTION_END.len()))
+    });
+
+    let (before, after) = match existing {
+        Some((start, end)) => (body[..start].trim_end(), body[end..].trim_start()),
+        None if section.is_empty() => return body.to_string(),
+        None => ("", body.trim_start()),
+    };
+    [before, section.as_str(), after]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -0,0 +1,65 @@
+//! Drift summary for a submission plan
+//!
+//! Condenses a plan into what is out of sync with the remote, for
+//! consolidated reporting across many stacks (e.g. `ryu sync --dry-run`).
+
+use crate::submit::{ExecutionStep, SubmissionPlan};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How a stack differs from its remote/platform state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StackDrift {
+    /// Leaf bookmark identifying the stack
+    pub leaf: String,
+    /// Bookmarks that need pushing
+    pub needs_push: Vec<String>,
+    /// Bookmarks without a PR
+    pub needs_pr: Vec<String>,
+    /// Bookmarks whose PR targets the wrong base
+    pub needs_retarget: Vec<String>,
+    /// Former parent branches that PRs still target but are no longer in
+    /// the stack (typically merged and deleted)
+    pub merged_parents: Vec<String>,
+}
+
+impl StackDrift {
+    /// Summarize the drift described by a plan
+    pub fn from_plan(leaf: &str, plan: &SubmissionPlan) -> Self {
+        let mut drift = Self {
+            leaf: leaf.to_string(),
+            ..Self::default()
+        };
+
+        for step in &plan.execution_steps {
+            match step {
+                ExecutionStep::Push(bm) => drift.needs_push.push(bm.name.clone()),
+                ExecutionStep::CreatePr(create) => {
+                    drift.needs_pr.push(create.bookmark.name.clone());
+                }
+                ExecutionStep::UpdateBase(update) => {
+                    drift.needs_retarget.push(update.bookmark.name.clone());
+
+                    let base_in_stack = plan
+                        .segments
+                        .iter()
+                        .any(|s| s.bookmark.name == update.current_base);
+                    if update.current_base != plan.default_branch
+                        && !base_in_stack
+                        && !drift.merged_parents.contains(&update.current_base)
+                    {
+                        drift.merged_parents.push(update.current_base.clone());
+                    }
+                }
+                ExecutionStep::PublishPr(_) => {}
+            }
+        }
+
+        drift
+    }
+
+    /// Check if the stack needs no changes
+    pub fn is_in_sync(&self) -> bool {
+        self.needs_push.is_empty() && self.needs_pr.is_empty() && self.needs_retarget.is_empty()
+    }
+}
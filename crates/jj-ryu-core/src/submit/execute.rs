@@ -0,0 +1,1331 @@
+//! Phase 3: Submission execution
+//!
+//! Executes the submission plan: push, create PRs, update bases, add comments.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::submit::commits::apply_commits_section;
+use crate::submit::interdiff::post_interdiff_comments;
+use crate::submit::journal::{CompletedStep, SubmissionJournal, clear_journal, save_journal};
+use crate::submit::notes::{apply_notes_section, segment_notes};
+use crate::submit::plan::{PrBaseUpdate, PrToCreate};
+use crate::submit::rename::{RenamedPr, superseded_comment};
+use crate::submit::stack_section::{apply_stack_section, stack_section, update_stack_section};
+use crate::submit::tracking::{sync_tracking_issue, tracking_issue_title};
+use crate::submit::{ExecutionStep, Phase, ProgressCallback, PushStatus, SubmissionPlan, Warning};
+use crate::types::{Bookmark, CommentWrite, LogEntry, PullRequest, StackInfo};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Result of submission execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SubmissionResult {
+    /// Whether execution succeeded
+    pub success: bool,
+    /// PRs that were created
+    pub created_prs: Vec<PullRequest>,
+    /// PRs that were updated (base changed)
+    pub updated_prs: Vec<PullRequest>,
+    /// PRs closed because their bookmark was renamed
+    pub closed_prs: Vec<PullRequest>,
+    /// Bookmarks that were pushed
+    pub pushed_bookmarks: Vec<String>,
+    /// Bookmarks pushed to each mirror remote
+    pub mirrored_bookmarks: BTreeMap<String, Vec<String>>,
+    /// Errors encountered (non-fatal)
+    pub errors: Vec<String>,
+}
+
+impl SubmissionResult {
+    /// Create a new successful result
+    pub fn new() -> Self {
+        Self {
+            success: true,
+            ..Default::default()
+        }
+    }
+
+    /// Record a fatal error and mark as failed
+    pub fn fail(&mut self, error: String) {
+        self.errors.push(error);
+        self.success = false;
+    }
+
+    /// Record a non-fatal error (soft fail)
+    pub fn soft_fail(&mut self, error: String) {
+        self.errors.push(error);
+    }
+}
+
+/// Outcome of executing a single step
+#[derive(Debug)]
+pub enum StepOutcome {
+    /// Step succeeded, optionally with a PR to track
+    Success(Option<(String, PullRequest)>),
+    /// Step failed fatally - stop execution
+    FatalError(String),
+    /// Step failed but execution should continue (soft fail)
+    SoftError(String),
+}
+
+/// Stack comment data embedded in PR comments
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StackCommentData {
+    /// Schema version, [`STACK_COMMENT_VERSION`] when written by this release
+    pub version: u8,
+    /// PRs in the stack, ordered root to leaf
+    pub stack: Vec<StackItem>,
+    /// Umbrella tracking issue for the stack, if one was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracking_issue: Option<u64>,
+}
+
+/// A single item in the stack
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StackItem {
+    /// Bookmark name for this PR
+    pub bookmark_name: String,
+    /// URL to the PR
+    pub pr_url: String,
+    /// PR number
+    pub pr_number: u64,
+    /// Change ID the bookmark pointed at, used to recognise the PR after
+    /// the bookmark is renamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_id: Option<String>,
+}
+
+/// Version of the stack comment data this release writes
+///
+/// Version 1 guarantees every item records its change ID, which is how a PR
+/// is recognised after its bookmark is renamed. Older comments still parse;
+/// their items may lack one.
+pub const STACK_COMMENT_VERSION: u8 = 1;
+
+/// Prefix for stack comment data
+pub const COMMENT_DATA_PREFIX: &str = "<!--- JJ-RYU_STACK: ";
+/// Prefix used by stack comments from older releases
+pub const COMMENT_DATA_PREFIX_OLD: &str = "<!--- JJ-STACK_INFO: ";
+/// Postfix for stack comment data
+pub const COMMENT_DATA_POSTFIX: &str = " --->";
+/// Marker for the current PR in stack comments
+pub const STACK_COMMENT_THIS_PR: &str = "👈";
+
+// =============================================================================
+// Step Execution Functions (testable in isolation)
+// =============================================================================
+
+/// Execute a run of push steps as one git push
+///
+/// Also returns, for each of `bookmarks`, whether it reached the remote: a
+/// push can fail part-way, and the bookmarks it did update count as pushed.
+/// The error names the ones that didn't.
+pub fn execute_pushes(
+    workspace: &mut JjWorkspace,
+    bookmarks: &[&Bookmark],
+    remote: &str,
+) -> (Vec<bool>, StepOutcome) {
+    let names: Vec<&str> = bookmarks.iter().map(|b| b.name.as_str()).collect();
+    let Err(e) = workspace.git_push_many(&names, remote) else {
+        return (vec![true; bookmarks.len()], StepOutcome::Success(None));
+    };
+    let pushed: Vec<bool> = bookmarks
+        .iter()
+        .map(|bookmark| {
+            workspace
+                .get_remote_bookmark(&bookmark.name, remote)
+                .ok()
+                .flatten()
+                .is_some_and(|on_remote| on_remote.commit_id == bookmark.commit_id)
+        })
+        .collect();
+    let failed: Vec<&str> = names
+        .iter()
+        .zip(&pushed)
+        .filter(|(_, pushed)| !**pushed)
+        .map(|(name, _)| *name)
+        .collect();
+    let failed = if failed.is_empty() { names } else { failed };
+    (
+        pushed,
+        StepOutcome::FatalError(format!("Failed to push {}: {e}", failed.join(", "))),
+    )
+}
+
+/// Mirror a bookmark to a push-only remote
+///
+/// Returns `Ok(false)` without pushing if the mirror already has the commit.
+pub fn mirror_bookmark(
+    workspace: &mut JjWorkspace,
+    bookmark: &Bookmark,
+    remote: &str,
+) -> Result<bool> {
+    let mirrored = workspace.get_remote_bookmark(&bookmark.name, remote)?;
+    if mirrored.is_some_and(|m| m.commit_id == bookmark.commit_id) {
+        return Ok(false);
+    }
+    workspace.git_push(&bookmark.name, remote)?;
+    Ok(true)
+}
+
+/// Fail with [`Error::PrChangedSincePlan`] if the PR `update` retargets was
+/// edited on the platform after planning
+///
+/// The PR must still be open with the base the plan saw. Its `updated_at`
+/// must match too, unless this run pushed the PR's head, which bumps it.
+pub async fn check_pr_unchanged(
+    platform: &dyn PlatformService,
+    update: &PrBaseUpdate,
+    head_pushed: bool,
+) -> Result<()> {
+    let changed = |detail: String| Error::PrChangedSincePlan {
+        pr_number: update.pr.number,
+        detail,
+    };
+    let current = platform
+        .find_open_prs(&update.bookmark.name)
+        .await?
+        .into_iter()
+        .find(|pr| pr.number == update.pr.number)
+        .ok_or_else(|| changed("no longer open".to_string()))?;
+    if current.base_ref != update.current_base {
+        return Err(changed(format!(
+            "base is now {}, planned from {}",
+            current.base_ref, update.current_base
+        )));
+    }
+    match (update.pr.updated_at, current.updated_at) {
+        (Some(planned), Some(now)) if !head_pushed && now != planned => Err(changed(format!(
+            "edited at {}",
+            now.format("%Y-%m-%d %H:%M:%S UTC")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Execute an update base step
+pub async fn execute_update_base(
+    platform: &dyn PlatformService,
+    update: &PrBaseUpdate,
+) -> StepOutcome {
+    match platform
+        .update_pr_base(update.pr.number, &update.expected_base)
+        .await
+    {
+        Ok(updated_pr) => StepOutcome::Success(Some((update.bookmark.name.clone(), updated_pr))),
+        Err(e) => StepOutcome::FatalError(format!(
+            "Failed to update PR base for {}: {e}",
+            update.bookmark.name
+        )),
+    }
+}
+
+/// Execute a create PR step
+pub async fn execute_create_pr(platform: &dyn PlatformService, create: &PrToCreate) -> StepOutcome {
+    match platform
+        .create_pr_with_options(
+            &create.bookmark.name,
+            &create.base_branch,
+            &create.title,
+            &create.body,
+            create.draft,
+        )
+        .await
+    {
+        Ok(pr) => StepOutcome::Success(Some((create.bookmark.name.clone(), pr))),
+        Err(e) => StepOutcome::FatalError(format!(
+            "Failed to create PR for {}: {e}",
+            create.bookmark.name
+        )),
+    }
+}
+
+/// Execute a publish PR step (soft fail on error)
+pub async fn execute_publish_pr(platform: &dyn PlatformService, pr: &PullRequest) -> StepOutcome {
+    match platform.publish_pr(pr.number).await {
+        Ok(updated_pr) => StepOutcome::Success(Some((pr.head_ref.clone(), updated_pr))),
+        Err(e) => StepOutcome::SoftError(format!("Failed to publish PR #{}: {e}", pr.number)),
+    }
+}
+
+// =============================================================================
+// Main Execution Orchestrator
+// =============================================================================
+
+/// Execute a submission plan
+///
+/// This performs the actual operations:
+/// 1. Push bookmarks to remote
+/// 2. Update PR bases
+/// 3. Create new PRs
+/// 4. Publish draft PRs
+/// 5. Push bookmarks to mirror remotes
+/// 6. Write `ryu-note:` review notes and commit lists into PR bodies
+/// 7. Create or update the stack's tracking issue (if the plan asks for one)
+/// 8. Add/update stack comments
+///
+/// Stack comments are written through `comment_platform` when given (see
+/// [`create_comment_platform_service`]), otherwise through `platform`.
+///
+/// Steps 1-4 are recorded in a [`SubmissionJournal`] so a run that
+/// stops on a failed step can be picked up with [`resume_submission`].
+///
+/// [`create_comment_platform_service`]: crate::platform::create_comment_platform_service
+pub async fn execute_submission(
+    plan: &SubmissionPlan,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    comment_platform: Option<&dyn PlatformService>,
+    progress: &dyn ProgressCallback,
+    dry_run: bool,
+) -> Result<SubmissionResult> {
+    for renamed in &plan.renamed_prs {
+        progress
+            .on_warning(&Warning::RenamedBookmark {
+                pr_number: renamed.old_pr.number,
+                old_bookmark: renamed.old_pr.head_ref.clone(),
+                bookmark: renamed.bookmark.clone(),
+            })
+            .await;
+    }
+
+    if dry_run {
+        progress
+            .on_message("Dry run - no changes will be made")
+            .await;
+        report_dry_run(plan, progress).await;
+        return Ok(SubmissionResult::new());
+    }
+
+    let mut journal = SubmissionJournal::new(plan.clone());
+    run_submission(
+        plan,
+        &mut journal,
+        workspace,
+        platform,
+        comment_platform,
+        progress,
+    )
+    .await
+}
+
+/// Continue a submission that stopped part-way, from its journal
+///
+/// Steps the journal records as done are skipped; PRs they created still
+/// count for review notes, the tracking issue and stack comments. Check
+/// [`SubmissionJournal::check_resumable`] first.
+pub async fn resume_submission(
+    mut journal: SubmissionJournal,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    comment_platform: Option<&dyn PlatformService>,
+    progress: &dyn ProgressCallback,
+) -> Result<SubmissionResult> {
+    let plan = journal.plan.clone();
+    progress
+        .on_message(&format!(
+            "Resuming: {} of {} steps already done",
+            journal.completed.len(),
+            plan.execution_steps.len()
+        ))
+        .await;
+    run_submission(
+        &plan,
+        &mut journal,
+        workspace,
+        platform,
+        comment_platform,
+        progress,
+    )
+    .await
+}
+
+/// Save the journal after a step; failing to is only worth a warning
+fn record_progress(workspace_root: &Path, journal: &SubmissionJournal) {
+    if let Err(e) = save_journal(workspace_root, journal) {
+        warn!(error = %e, "failed to save submission journal");
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+async fn run_submission(
+    plan: &SubmissionPlan,
+    journal: &mut SubmissionJournal,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    comment_platform: Option<&dyn PlatformService>,
+    progress: &dyn ProgressCallback,
+) -> Result<SubmissionResult> {
+    let mut result = SubmissionResult::new();
+    let workspace_root = workspace.workspace_root().to_path_buf();
+
+    // Track all PRs (existing + created) for comment generation
+    let mut bookmark_to_pr: HashMap<String, PullRequest> = plan.existing_prs.clone();
+    for done in &journal.completed {
+        if let Some(pr) = &done.pr {
+            bookmark_to_pr.insert(pr.head_ref.clone(), pr.clone());
+        }
+    }
+
+    warn_on_rate_limit(plan, platform, progress).await;
+
+    // Phase: Executing all steps
+    progress.on_phase(Phase::Executing).await;
+
+    let steps = &plan.execution_steps;
+    for (index, step) in steps.iter().enumerate() {
+        if journal.is_completed(index) {
+            continue;
+        }
+
+        // Out of budget: stop here, keeping the journal for --resume
+        if let Some(reason) = workspace.run().budget().exhausted() {
+            record_progress(&workspace_root, journal);
+            let msg = format!(
+                "API budget exhausted ({reason}) with {} steps left",
+                journal.remaining()
+            );
+            progress.on_error(&Error::Platform(msg.clone())).await;
+            result.fail(msg);
+            return Ok(result);
+        }
+
+        // Consecutive pushes have nothing ordered between them, so they go
+        // out as one git push; later iterations skip them as completed
+        if matches!(step, ExecutionStep::Push(_)) {
+            let batch: Vec<(usize, &Bookmark)> = steps
+                .iter()
+                .enumerate()
+                .skip(index)
+                .map_while(|(i, step)| match step {
+                    ExecutionStep::Push(bm) if !journal.is_completed(i) => Some((i, bm)),
+                    _ => None,
+                })
+                .collect();
+            let bookmarks: Vec<&Bookmark> = batch.iter().map(|(_, bm)| *bm).collect();
+            let (pushed, outcome) =
+                push_with_progress(workspace, &bookmarks, &plan.remote, progress).await;
+            // Bookmarks the remote took are journaled even if others in the
+            // batch failed, so --resume doesn't push them again
+            for ((i, bm), _) in batch.iter().zip(&pushed).filter(|(_, pushed)| **pushed) {
+                journal.completed.push(CompletedStep {
+                    index: *i,
+                    pr: None,
+                });
+                result.pushed_bookmarks.push(bm.name.clone());
+            }
+            record_progress(&workspace_root, journal);
+            if let StepOutcome::FatalError(msg) | StepOutcome::SoftError(msg) = outcome {
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.fail(msg);
+                return Ok(result);
+            }
+            continue;
+        }
+
+        // Retargeting overwrites the base; stop rather than clobber an edit
+        // someone made after planning, and drop the journal so the rerun
+        // plans afresh instead of resuming the stale plan
+        if let ExecutionStep::UpdateBase(update) = step {
+            let head_pushed = journal
+                .completed
+                .iter()
+                .any(|done| match &steps[done.index] {
+                    ExecutionStep::Push(bm) => bm.name == update.bookmark.name,
+                    _ => false,
+                });
+            if let Err(e) = check_pr_unchanged(platform, update, head_pushed).await {
+                if matches!(e, Error::PrChangedSincePlan { .. }) {
+                    if let Err(clear_err) = clear_journal(&workspace_root, plan) {
+                        warn!(error = %clear_err, "failed to clear submission journal");
+                    }
+                }
+                progress.on_error(&e).await;
+                result.fail(e.to_string());
+                return Ok(result);
+            }
+        }
+
+        let outcome = execute_step(step, workspace, platform, &plan.remote, progress).await;
+
+        if let StepOutcome::Success(tracked) = &outcome {
+            journal.completed.push(CompletedStep {
+                index,
+                pr: tracked.as_ref().map(|(_, pr)| pr.clone()),
+            });
+            record_progress(&workspace_root, journal);
+        }
+
+        match outcome {
+            StepOutcome::Success(Some((bookmark, pr))) => {
+                // Track the PR for comment generation
+                match step {
+                    ExecutionStep::CreatePr(_) => result.created_prs.push(pr.clone()),
+                    ExecutionStep::UpdateBase(_) | ExecutionStep::PublishPr(_) => {
+                        result.updated_prs.push(pr.clone());
+                    }
+                    ExecutionStep::Push(_) => {}
+                }
+                bookmark_to_pr.insert(bookmark, pr);
+            }
+            StepOutcome::Success(None) => {
+                // Push succeeded - track it
+                if let ExecutionStep::Push(bm) = step {
+                    result.pushed_bookmarks.push(bm.name.clone());
+                }
+            }
+            StepOutcome::FatalError(msg) => {
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.fail(msg);
+                return Ok(result);
+            }
+            StepOutcome::SoftError(msg) => {
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.soft_fail(msg);
+            }
+        }
+    }
+
+    // Every step ran; later phases are soft and safe to repeat
+    if let Err(e) = clear_journal(&workspace_root, plan) {
+        warn!(error = %e, "failed to clear submission journal");
+    }
+
+    // Phase: Closing PRs left under a renamed bookmark's old name
+    let replaced: Vec<(&RenamedPr, &PullRequest)> = plan
+        .renamed_prs
+        .iter()
+        .filter_map(|renamed| Some((renamed, bookmark_to_pr.get(&renamed.bookmark)?)))
+        .collect();
+    if !replaced.is_empty() {
+        progress.on_phase(Phase::ClosingRenamed).await;
+        for (renamed, new_pr) in replaced {
+            if let Err(e) = close_renamed_pr(platform, renamed, new_pr).await {
+                let msg = format!("Failed to close #{}: {e}", renamed.old_pr.number);
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.soft_fail(msg);
+            } else {
+                result.closed_prs.push(renamed.old_pr.clone());
+            }
+        }
+    }
+
+    // Phase: Mirroring (failures don't affect the primary remote)
+    if !plan.mirror_remotes.is_empty() {
+        progress.on_phase(Phase::Mirroring).await;
+        execute_mirror_pushes(plan, workspace, progress, &mut result).await;
+    }
+
+    // Phase: PR body sections (only PRs whose changes carry notes or that
+    // cover several commits)
+    let sectioned: Vec<(&PullRequest, &[LogEntry])> = plan
+        .segments
+        .iter()
+        .filter_map(|segment| {
+            let pr = bookmark_to_pr.get(&segment.bookmark.name)?;
+            let changes = segment.changes.as_slice();
+            (changes.len() > 1 || !segment_notes(changes).is_empty()).then_some((pr, changes))
+        })
+        .collect();
+    if !sectioned.is_empty() {
+        progress.on_phase(Phase::UpdatingNotes).await;
+        for (pr, changes) in sectioned {
+            if let Err(e) = update_body_sections(platform, pr.number, changes).await {
+                let msg = format!("Failed to update PR body for #{}: {e}", pr.number);
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.soft_fail(msg);
+            }
+        }
+    }
+
+    let comment_platform = comment_platform.unwrap_or(platform);
+    let mut stack_data = build_stack_comment_data(plan, &bookmark_to_pr);
+
+    // Phase: Tracking issue (soft fail; the stack comments still go out)
+    if let Some(leaf) = stack_data.stack.last().filter(|_| plan.tracking_issue) {
+        progress.on_phase(Phase::UpdatingTrackingIssue).await;
+        let title = tracking_issue_title(
+            bookmark_to_pr
+                .get(&leaf.bookmark_name)
+                .map_or(leaf.bookmark_name.as_str(), |pr| pr.title.as_str()),
+        );
+        match sync_tracking_issue(platform, comment_platform, &stack_data, &title).await {
+            Ok(number) => stack_data.tracking_issue = Some(number),
+            Err(e) => {
+                let msg = format!("Failed to update tracking issue: {e}");
+                progress.on_error(&Error::Platform(msg.clone())).await;
+                result.soft_fail(msg);
+            }
+        }
+    }
+
+    // Phase: Interdiff comments (soft fail; a PR keeps its old snapshot)
+    if plan.interdiff_comments {
+        progress.on_phase(Phase::PostingInterdiffs).await;
+        let failures =
+            post_interdiff_comments(plan, workspace, comment_platform, &bookmark_to_pr).await;
+        for msg in
+            failures.unwrap_or_else(|e| vec![format!("Failed to post interdiff comments: {e}")])
+        {
+            progress.on_error(&Error::Platform(msg.clone())).await;
+            result.soft_fail(msg);
+        }
+    }
+
+    // Phase: Adding stack comments (or body sections, or neither, per the
+    // config files)
+    if plan.stack_info == StackInfo::Body {
+        progress.on_phase(Phase::AddingComments).await;
+        for (idx, item) in stack_data.stack.iter().enumerate() {
+            if let Err(e) = update_stack_section(platform, &stack_data, idx, item.pr_number).await {
+                let msg = format!(
+                    "Failed to update stack section for {}: {e}",
+                    item.bookmark_name
+                );
+                progress
+                    .on_warning(&Warning::StaleComment {
+                        pr_number: item.pr_number,
+                        bookmark: item.bookmark_name.clone(),
+                        reason: e.to_string(),
+                    })
+                    .await;
+                result.soft_fail(msg);
+            }
+        }
+    } else if plan.stack_info == StackInfo::Comment {
+        progress.on_phase(Phase::AddingComments).await;
+
+        // Read and render every comment first, so the writes can go out together
+        let mut written = Vec::with_capacity(stack_data.stack.len());
+        let mut writes = Vec::with_capacity(stack_data.stack.len());
+        let mut failures = Vec::new();
+        for (idx, item) in stack_data.stack.iter().enumerate() {
+            let pr = bookmark_to_pr.get(&item.bookmark_name);
+            match stack_comment_write(comment_platform, &stack_data, idx, item.pr_number).await {
+                Ok(write) => {
+                    written.push(item);
+                    writes.push(CommentWrite {
+                        pr_node_id: pr.and_then(|pr| pr.node_id.clone()),
+                        ..write
+                    });
+                }
+                Err(e) => failures.push((item, e)),
+            }
+        }
+        let results = comment_platform.write_pr_comments(&writes).await;
+        failures.extend(
+            written
+                .into_iter()
+                .zip(results)
+                .filter_map(|(item, result)| result.err().map(|e| (item, e))),
+        );
+
+        for (item, e) in failures {
+            let msg = format!(
+                "Failed to update stack comment for {}: {e}",
+                item.bookmark_name
+            );
+            progress
+                .on_warning(&Warning::StaleComment {
+                    pr_number: item.pr_number,
+                    bookmark: item.bookmark_name.clone(),
+                    reason: e.to_string(),
+                })
+                .await;
+            result.soft_fail(msg);
+        }
+    }
+
+    progress.on_phase(Phase::Complete).await;
+
+    Ok(result)
+}
+
+/// Warn when the platform's API budget might not last the submission
+///
+/// Counts a request per step and a few per segment for the stack comments.
+/// Failing to read the budget isn't worth bothering the user about.
+async fn warn_on_rate_limit(
+    plan: &SubmissionPlan,
+    platform: &dyn PlatformService,
+    progress: &dyn ProgressCallback,
+) {
+    let needed =
+        u64::try_from(plan.execution_steps.len() + 3 * plan.segments.len()).unwrap_or(u64::MAX);
+    match platform.rate_limit().await {
+        Ok(Some(rate)) if rate.is_nearing(needed) => {
+            progress
+                .on_warning(&Warning::RateLimitNearing {
+                    remaining: rate.remaining,
+                    limit: rate.limit,
+                    resets_at: rate.resets_at,
+                })
+                .await;
+        }
+        Ok(_) => {}
+        Err(e) => debug!(error = %e, "failed to read rate limit"),
+    }
+}
+
+/// Push every bookmark the primary remote has to each mirror remote
+///
+/// Bookmarks that aren't on the primary (e.g. skipped by `--update-only`)
+/// are left alone so mirrors never get ahead of it.
+async fn execute_mirror_pushes(
+    plan: &SubmissionPlan,
+    workspace: &mut JjWorkspace,
+    progress: &dyn ProgressCallback,
+    result: &mut SubmissionResult,
+) {
+    let on_primary: Vec<&Bookmark> = plan
+        .segments
+        .iter()
+        .map(|s| &s.bookmark)
+        .filter(|b| (b.has_remote && b.is_synced) || result.pushed_bookmarks.contains(&b.name))
+        .collect();
+
+    for remote in &plan.mirror_remotes {
+        for bookmark in &on_primary {
+            let status = match mirror_bookmark(workspace, bookmark, remote) {
+                Ok(true) => {
+                    result
+                        .mirrored_bookmarks
+                        .entry(remote.clone())
+                        .or_default()
+                        .push(bookmark.name.clone());
+                    PushStatus::Success
+                }
+                Ok(false) => PushStatus::AlreadySynced,
+                Err(e) => {
+                    let msg = format!("Failed to mirror {} to {remote}: {e}", bookmark.name);
+                    result.soft_fail(msg.clone());
+                    PushStatus::Failed(msg)
+                }
+            };
+            progress
+                .on_mirror_push(&bookmark.name, remote, status)
+                .await;
+        }
+    }
+}
+
+/// Push bookmarks together, reporting each one's progress
+///
+/// Returns which bookmarks reached the remote along with the outcome, as
+/// [`execute_pushes`] does.
+async fn push_with_progress(
+    workspace: &mut JjWorkspace,
+    bookmarks: &[&Bookmark],
+    remote: &str,
+    progress: &dyn ProgressCallback,
+) -> (Vec<bool>, StepOutcome) {
+    for bookmark in bookmarks {
+        progress
+            .on_bookmark_push(&bookmark.name, PushStatus::Started)
+            .await;
+    }
+
+    let (pushed, outcome) = execute_pushes(workspace, bookmarks, remote);
+
+    for (bookmark, pushed) in bookmarks.iter().zip(&pushed) {
+        let status = match &outcome {
+            StepOutcome::FatalError(msg) | StepOutcome::SoftError(msg) if !pushed => {
+                PushStatus::Failed(msg.clone())
+            }
+            _ => PushStatus::Success,
+        };
+        progress.on_bookmark_push(&bookmark.name, status).await;
+    }
+
+    (pushed, outcome)
+}
+
+/// Execute a single step with progress reporting
+async fn execute_step(
+    step: &ExecutionStep,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    remote: &str,
+    progress: &dyn ProgressCallback,
+) -> StepOutcome {
+    match step {
+        ExecutionStep::Push(bookmark) => {
+            push_with_progress(workspace, &[bookmark], remote, progress)
+                .await
+                .1
+        }
+
+        ExecutionStep::UpdateBase(update) => {
+            progress
+                .on_message(&format!(
+                    "Updating {} base: {} → {}",
+                    update.bookmark.name, update.current_base, update.expected_base
+                ))
+                .await;
+
+            let outcome = execute_update_base(platform, update).await;
+
+            if let StepOutcome::Success(Some((bookmark, pr))) = &outcome {
+                progress.on_pr_updated(bookmark, pr).await;
+            }
+
+            outcome
+        }
+
+        ExecutionStep::CreatePr(create) => {
+            let draft_str = if create.draft { " [draft]" } else { "" };
+            progress
+                .on_message(&format!(
+                    "Creating PR for {} (base: {}){draft_str}",
+                    create.bookmark.name, create.base_branch
+                ))
+                .await;
+
+            let outcome = execute_create_pr(platform, create).await;
+
+            if let StepOutcome::Success(Some((bookmark, pr))) = &outcome {
+                progress.on_pr_created(bookmark, pr).await;
+            }
+
+            outcome
+        }
+
+        ExecutionStep::PublishPr(pr) => {
+            progress
+                .on_message(&format!("Publishing PR #{} ({})", pr.number, pr.head_ref))
+                .await;
+
+            execute_publish_pr(platform, pr).await
+        }
+    }
+}
+
+// =============================================================================
+// Dry Run Reporting
+// =============================================================================
+
+/// Report what would be done in a dry run
+async fn report_dry_run(plan: &SubmissionPlan, progress: &dyn ProgressCallback) {
+    if plan.execution_steps.is_empty() {
+        progress.on_message("Nothing to do - already in sync").await;
+        return;
+    }
+
+    progress.on_message("Would execute:").await;
+    for step in &plan.execution_steps {
+        let msg = format_step_for_dry_run(step, &plan.remote);
+        progress.on_message(&msg).await;
+    }
+    if !plan.mirror_remotes.is_empty() {
+        progress
+            .on_message(&format!(
+                "  → mirror pushed bookmarks to {}",
+                plan.mirror_remotes.join(", ")
+            ))
+            .await;
+    }
+    for renamed in &plan.renamed_prs {
+        progress
+            .on_message(&format!(
+                "  → close PR #{} for {} (renamed to {})",
+                renamed.old_pr.number, renamed.old_pr.head_ref, renamed.bookmark
+            ))
+            .await;
+    }
+}
+
+/// Point the old PR of a renamed bookmark at its replacement, then close it
+async fn close_renamed_pr(
+    platform: &dyn PlatformService,
+    renamed: &RenamedPr,
+    new_pr: &PullRequest,
+) -> Result<()> {
+    let body = superseded_comment(renamed, new_pr, platform.config().platform);
+    platform
+        .create_pr_comment(renamed.old_pr.number, &body)
+        .await?;
+    platform.close_pr(renamed.old_pr.number).await
+}
+
+/// Format a step for dry run output
+pub fn format_step_for_dry_run(step: &ExecutionStep, remote: &str) -> String {
+    match step {
+        // Push needs special handling to include remote
+        ExecutionStep::Push(bm) => format!("  → push {} to {}", bm.name, remote),
+        // All other steps use Display impl
+        _ => format!("  → {step}"),
+    }
+}
+
+// =============================================================================
+// Stack Comment Functions
+// =============================================================================
+
+/// Build stack comment data from the plan and PRs
+#[allow(clippy::implicit_hasher)]
+pub fn build_stack_comment_data(
+    plan: &SubmissionPlan,
+    bookmark_to_pr: &HashMap<String, PullRequest>,
+) -> StackCommentData {
+    let stack: Vec<StackItem> = plan
+        .segments
+        .iter()
+        .filter_map(|seg| {
+            bookmark_to_pr.get(&seg.bookmark.name).map(|pr| StackItem {
+                bookmark_name: seg.bookmark.name.clone(),
+                pr_url: pr.html_url.clone(),
+                pr_number: pr.number,
+                change_id: Some(seg.bookmark.change_id.clone()),
+            })
+        })
+        .collect();
+
+    StackCommentData {
+        version: STACK_COMMENT_VERSION,
+        stack,
+        tracking_issue: None,
+    }
+}
+
+/// Format the stack comment body for a PR
+pub fn format_stack_comment(data: &StackCommentData, current_idx: usize) -> Result<String> {
+    let encoded_data = BASE64.encode(
+        serde_json::to_string(data)
+            .map_err(|e| Error::Internal(format!("Failed to serialize stack data: {e}")))?,
+    );
+
+    let mut body = format!("{COMMENT_DATA_PREFIX}{encoded_data}{COMMENT_DATA_POSTFIX}\n");
+
+    // Reverse order: newest/leaf at top, oldest at bottom
+    // Use plain #X format so GitHub auto-links with status indicators
+    let reversed_idx = data.stack.len() - 1 - current_idx;
+    for (i, item) in data.stack.iter().rev().enumerate() {
+        if i == reversed_idx {
+            let _ = writeln!(body, "* **#{} {STACK_COMMENT_THIS_PR}**", item.pr_number);
+        } else {
+            let _ = writeln!(body, "* #{}", item.pr_number);
+        }
+    }
+
+    if let Some(issue) = data.tracking_issue {
+        let _ = writeln!(body, "\nTracking issue: #{issue}");
+    }
+
+    let _ = write!(
+        body,
+        "\n---\nThis stack of pull requests is managed by [jj-ryu](https://github.com/dmmulroy/jj-ryu)."
+    );
+
+    Ok(body)
+}
+
+/// Decode the stack data embedded in a stack comment (either marker format)
+pub fn parse_stack_comment_data(body: &str) -> Option<StackCommentData> {
+    let (_, rest) = body
+        .split_once(COMMENT_DATA_PREFIX)
+        .or_else(|| body.split_once(COMMENT_DATA_PREFIX_OLD))?;
+    let (encoded, _) = rest.split_once(COMMENT_DATA_POSTFIX)?;
+    let json = BASE64.decode(encoded.trim()).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Rewrite the commit list and review notes sections of a PR body, if they
+/// changed
+///
+/// A stack section stays at the end, below any new notes section.
+async fn update_body_sections(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    changes: &[LogEntry],
+) -> Result<()> {
+    let current = platform.get_pr_body(pr_number).await?.replace("\r\n", "\n");
+    let updated = apply_stack_section(
+        &apply_notes_section(
+            &apply_commits_section(&apply_stack_section(&current, ""), changes),
+            &segment_notes(changes),
+        ),
+        stack_section(&current).unwrap_or_default(),
+    );
+    if updated != current {
+        platform.update_pr_body(pr_number, &updated).await?;
+    }
+    Ok(())
+}
+
+/// The write that creates or updates the stack comment on a PR
+///
+/// A tracking issue linked from the existing comment stays linked even when
+/// this submission didn't touch the issue.
+async fn stack_comment_write(
+    platform: &dyn PlatformService,
+    data: &StackCommentData,
+    current_idx: usize,
+    pr_number: u64,
+) -> Result<CommentWrite> {
+    // Find existing comment by looking for our data prefix (check both old and new)
+    let comments = platform.list_pr_comments(pr_number).await?;
+    let existing = comments
+        .into_iter()
+        .find(|c| c.body.contains(COMMENT_DATA_PREFIX) || c.body.contains(COMMENT_DATA_PREFIX_OLD));
+
+    let linked_issue = existing
+        .as_ref()
+        .and_then(|c| parse_stack_comment_data(&c.body))
+        .and_then(|d| d.tracking_issue);
+    let body = match (data.tracking_issue, linked_issue) {
+        (None, Some(issue)) => {
+            let data = StackCommentData {
+                tracking_issue: Some(issue),
+                ..data.clone()
+            };
+            format_stack_comment(&data, current_idx)?
+        }
+        _ => format_stack_comment(data, current_idx)?,
+    };
+
+    Ok(CommentWrite {
+        pr_number,
+        pr_node_id: None,
+        existing,
+        body,
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NarrowedBookmarkSegment;
+
+    fn make_pr(number: u64, bookmark: &str) -> PullRequest {
+        PullRequest {
+            number,
+            html_url: format!("https://github.com/test/test/pull/{number}"),
+            base_ref: "main".to_string(),
+            head_ref: bookmark.to_string(),
+            title: format!("PR for {bookmark}"),
+            node_id: Some(format!("PR_node_{number}")),
+            is_draft: false,
+            updated_at: None,
+        }
+    }
+
+    fn make_bookmark(name: &str) -> Bookmark {
+        Bookmark {
+            name: name.to_string(),
+            commit_id: format!("{name}_commit"),
+            change_id: format!("{name}_change"),
+            has_remote: false,
+            is_synced: false,
+        }
+    }
+
+    // === SubmissionResult tests ===
+
+    #[test]
+    fn test_submission_result_new() {
+        let result = SubmissionResult::new();
+        assert!(result.success);
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn test_submission_result_fail() {
+        let mut result = SubmissionResult::new();
+        result.fail("something went wrong".to_string());
+
+        assert!(!result.success);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0], "something went wrong");
+    }
+
+    #[test]
+    fn test_submission_result_soft_fail() {
+        let mut result = SubmissionResult::new();
+        result.soft_fail("minor issue".to_string());
+
+        // Soft fail records error but doesn't mark as failed
+        assert!(result.success);
+        assert_eq!(result.errors.len(), 1);
+    }
+
+    // === StepOutcome tests ===
+
+    #[test]
+    fn test_step_outcome_success_without_pr() {
+        let outcome = StepOutcome::Success(None);
+        assert!(matches!(outcome, StepOutcome::Success(None)));
+    }
+
+    #[test]
+    fn test_step_outcome_success_with_pr() {
+        let pr = make_pr(1, "feat-a");
+        let outcome = StepOutcome::Success(Some(("feat-a".to_string(), pr)));
+        assert!(matches!(outcome, StepOutcome::Success(Some(_))));
+    }
+
+    #[test]
+    fn test_step_outcome_fatal_error() {
+        let outcome = StepOutcome::FatalError("boom".to_string());
+        assert!(matches!(outcome, StepOutcome::FatalError(_)));
+    }
+
+    #[test]
+    fn test_step_outcome_soft_error() {
+        let outcome = StepOutcome::SoftError("minor".to_string());
+        assert!(matches!(outcome, StepOutcome::SoftError(_)));
+    }
+
+    // === Dry run formatting tests ===
+
+    #[test]
+    fn test_format_step_push() {
+        let bm = make_bookmark("feat-a");
+        let step = ExecutionStep::Push(bm);
+        let output = format_step_for_dry_run(&step, "origin");
+        assert_eq!(output, "  → push feat-a to origin");
+    }
+
+    #[test]
+    fn test_format_step_create_pr() {
+        let bm = make_bookmark("feat-a");
+        let create = PrToCreate {
+            bookmark: bm,
+            base_branch: "main".to_string(),
+            title: "Add feature".to_string(),
+            body: String::new(),
+            draft: false,
+        };
+        let step = ExecutionStep::CreatePr(create);
+        let output = format_step_for_dry_run(&step, "origin");
+        assert_eq!(output, "  → create PR feat-a → main (Add feature)");
+    }
+
+    #[test]
+    fn test_format_step_create_pr_draft() {
+        let bm = make_bookmark("feat-a");
+        let create = PrToCreate {
+            bookmark: bm,
+            base_branch: "main".to_string(),
+            title: "Add feature".to_string(),
+            body: String::new(),
+            draft: true,
+        };
+        let step = ExecutionStep::CreatePr(create);
+        let output = format_step_for_dry_run(&step, "origin");
+        assert!(output.contains("[draft]"));
+    }
+
+    #[test]
+    fn test_format_step_update_base() {
+        let bm = make_bookmark("feat-b");
+        let update = PrBaseUpdate {
+            bookmark: bm,
+            current_base: "main".to_string(),
+            expected_base: "feat-a".to_string(),
+            pr: make_pr(42, "feat-b"),
+        };
+        let step = ExecutionStep::UpdateBase(update);
+        let output = format_step_for_dry_run(&step, "origin");
+        assert_eq!(output, "  → update feat-b (PR #42) main → feat-a");
+    }
+
+    #[test]
+    fn test_format_step_publish() {
+        let pr = make_pr(99, "feat-a");
+        let step = ExecutionStep::PublishPr(pr);
+        let output = format_step_for_dry_run(&step, "origin");
+        assert_eq!(output, "  → publish PR #99 (feat-a)");
+    }
+
+    // === Stack comment tests ===
+
+    #[test]
+    fn test_build_stack_comment_data() {
+        let plan = SubmissionPlan {
+            segments: vec![
+                NarrowedBookmarkSegment {
+                    bookmark: make_bookmark("feat-a"),
+                    changes: vec![],
+                },
+                NarrowedBookmarkSegment {
+                    bookmark: make_bookmark("feat-b"),
+                    changes: vec![],
+                },
+            ],
+            constraints: vec![],
+            execution_steps: vec![],
+            existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
+            remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
+            default_branch: "main".to_string(),
+        };
+
+        let mut bookmark_to_pr = HashMap::new();
+        bookmark_to_pr.insert("feat-a".to_string(), make_pr(1, "feat-a"));
+        bookmark_to_pr.insert("feat-b".to_string(), make_pr(2, "feat-b"));
+
+        let data = build_stack_comment_data(&plan, &bookmark_to_pr);
+
+        assert_eq!(data.version, 0);
+        assert_eq!(data.stack.len(), 2);
+        assert_eq!(data.stack[0].bookmark_name, "feat-a");
+        assert_eq!(data.stack[0].pr_number, 1);
+        assert_eq!(data.stack[1].bookmark_name, "feat-b");
+        assert_eq!(data.stack[1].pr_number, 2);
+    }
+
+    #[test]
+    fn test_build_stack_comment_data_filters_missing_prs() {
+        let plan = SubmissionPlan {
+            segments: vec![
+                NarrowedBookmarkSegment {
+                    bookmark: make_bookmark("feat-a"),
+                    changes: vec![],
+                },
+                NarrowedBookmarkSegment {
+                    bookmark: make_bookmark("feat-b"),
+                    changes: vec![],
+                },
+            ],
+            constraints: vec![],
+            execution_steps: vec![],
+            existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
+            remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
+            default_branch: "main".to_string(),
+        };
+
+        // Only feat-a has a PR
+        let mut bookmark_to_pr = HashMap::new();
+        bookmark_to_pr.insert("feat-a".to_string(), make_pr(1, "feat-a"));
+
+        let data = build_stack_comment_data(&plan, &bookmark_to_pr);
+
+        assert_eq!(data.stack.len(), 1);
+        assert_eq!(data.stack[0].bookmark_name, "feat-a");
+    }
+
+    #[test]
+    fn test_format_stack_comment_marks_current() {
+        let data = StackCommentData {
+            version: 0,
+            stack: vec![
+                StackItem {
+                    bookmark_name: "feat-a".to_string(),
+                    pr_url: "https://example.com/1".to_string(),
+                    pr_number: 1,
+                    change_id: None,
+                },
+                StackItem {
+                    bookmark_name: "feat-b".to_string(),
+                    pr_url: "https://example.com/2".to_string(),
+                    pr_number: 2,
+                    change_id: None,
+                },
+            ],
+            tracking_issue: None,
+        };
+
+        // Format for PR #2 (index 1)
+        let body = format_stack_comment(&data, 1).unwrap();
+        assert!(body.contains(&format!("#{} {STACK_COMMENT_THIS_PR}", 2)));
+        assert!(!body.contains(&format!("#{} {STACK_COMMENT_THIS_PR}", 1)));
+    }
+
+    #[test]
+    fn test_format_stack_comment_contains_prefix() {
+        let data = StackCommentData {
+            version: 0,
+            stack: vec![StackItem {
+                bookmark_name: "feat-a".to_string(),
+                pr_url: "https://example.com/1".to_string(),
+                pr_number: 1,
+                change_id: None,
+            }],
+            tracking_issue: None,
+        };
+
+        let body = format_stack_comment(&data, 0).unwrap();
+        assert!(body.contains(COMMENT_DATA_PREFIX));
+        assert!(body.contains(COMMENT_DATA_POSTFIX));
+    }
+
+    // === Plan helper tests ===
+
+    #[test]
+    fn test_plan_is_empty() {
+        let plan = SubmissionPlan {
+            segments: vec![],
+            constraints: vec![],
+            execution_steps: vec![],
+            existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
+            remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
+            default_branch: "main".to_string(),
+        };
+
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_plan_counts() {
+        let bm = make_bookmark("feat-a");
+        let plan = SubmissionPlan {
+            segments: vec![NarrowedBookmarkSegment {
+                bookmark: bm.clone(),
+                changes: vec![],
+            }],
+            constraints: vec![],
+            execution_steps: vec![
+                ExecutionStep::Push(bm.clone()),
+                ExecutionStep::CreatePr(PrToCreate {
+                    bookmark: bm,
+                    base_branch: "main".to_string(),
+                    title: "Add feat-a".to_string(),
+                    body: String::new(),
+                    draft: false,
+                }),
+            ],
+            existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
+            remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
+            default_branch: "main".to_string(),
+        };
+
+        assert!(!plan.is_empty());
+        assert_eq!(plan.count_pushes(), 1);
+        assert_eq!(plan.count_creates(), 1);
+        assert_eq!(plan.count_updates(), 0);
+        assert_eq!(plan.count_publishes(), 0);
+    }
+}
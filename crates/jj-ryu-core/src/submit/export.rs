@@ -0,0 +1,127 @@
+//! Submission plans made on one machine and applied on another
+//!
+//! `ryu plan` analyzes a stack where the code lives (typically a laptop
+//! without push rights) and writes an [`ExportedPlan`]; `ryu apply` executes
+//! it where the credentials live (typically a CI runner acting as a bot).
+//! The plan records the repository and jj operation it was made from, so
+//! apply refuses to run it against anything else.
+
+use crate::error::{Error, Result};
+use crate::submit::SubmissionPlan;
+use crate::types::{Bookmark, Platform, PlatformConfig};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Format version written by this ryu; newer plans are refused
+pub const EXPORTED_PLAN_VERSION: u32 = 1;
+
+/// A [`SubmissionPlan`] with what's needed to check it still applies
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedPlan {
+    /// Format version ([`EXPORTED_PLAN_VERSION`] when written)
+    pub version: u32,
+    /// Platform of the remote the plan targets
+    pub platform: Platform,
+    /// Custom host, as in [`PlatformConfig::host`]
+    pub host: Option<String>,
+    /// Repository owner
+    pub owner: String,
+    /// Repository name
+    pub repo: String,
+    /// Head jj operation when the plan was made
+    pub operation_id: String,
+    /// When the plan was made
+    pub created_at: DateTime<Utc>,
+    /// The plan to execute
+    pub plan: SubmissionPlan,
+}
+
+/// Why an [`ExportedPlan`] is safe to apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanCompatibility {
+    /// The repo is at the operation the plan was made at
+    SameOperation,
+    /// The operation differs (e.g. another clone), but every planned bookmark
+    /// still points at the commit it was planned for
+    SameCommits,
+}
+
+impl ExportedPlan {
+    /// Wrap `plan` for export from the repo at `operation_id`
+    pub fn new(plan: SubmissionPlan, config: &PlatformConfig, operation_id: String) -> Self {
+        Self {
+            version: EXPORTED_PLAN_VERSION,
+            platform: config.platform,
+            host: config.host.clone(),
+            owner: config.owner.clone(),
+            repo: config.repo.clone(),
+            operation_id,
+            created_at: Utc::now(),
+            plan,
+        }
+    }
+
+    /// Check the plan can be applied to a repo whose remote is `config`, at
+    /// `operation_id`, with `local_bookmarks`
+    ///
+    /// Fails with [`Error::PlanMismatch`] if the plan targets another
+    /// repository, was written by a newer ryu, or any planned bookmark has
+    /// moved since.
+    pub fn check_compatible(
+        &self,
+        config: &PlatformConfig,
+        operation_id: &str,
+        local_bookmarks: &[Bookmark],
+    ) -> Result<PlanCompatibility> {
+        if self.version > EXPORTED_PLAN_VERSION {
+            return Err(Error::PlanMismatch(format!(
+                "it was written by a newer ryu (format version {})",
+                self.version
+            )));
+        }
+        if self.platform != config.platform
+            || self.host != config.host
+            || self.owner != config.owner
+            || self.repo != config.repo
+        {
+            return Err(Error::PlanMismatch(format!(
+                "it targets {}/{} but remote {} is {}/{}",
+                self.owner, self.repo, self.plan.remote, config.owner, config.repo
+            )));
+        }
+        if self.operation_id == operation_id {
+            return Ok(PlanCompatibility::SameOperation);
+        }
+
+        check_bookmarks_unmoved(&self.plan, local_bookmarks)?;
+        Ok(PlanCompatibility::SameCommits)
+    }
+}
+
+/// Fail with [`Error::PlanMismatch`] unless every bookmark in `plan` still
+/// points at the commit it was planned for
+pub(crate) fn check_bookmarks_unmoved(
+    plan: &SubmissionPlan,
+    local_bookmarks: &[Bookmark],
+) -> Result<()> {
+    for segment in &plan.segments {
+        let planned = &segment.bookmark;
+        match local_bookmarks.iter().find(|b| b.name == planned.name) {
+            Some(local) if local.commit_id == planned.commit_id => {}
+            Some(_) => {
+                return Err(Error::PlanMismatch(format!(
+                    "bookmark '{}' has moved since the plan was made",
+                    planned.name
+                )));
+            }
+            None => {
+                return Err(Error::PlanMismatch(format!(
+                    "bookmark '{}' doesn't exist here",
+                    planned.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,394 @@
+//! Interdiff comments: what changed in a PR since it was last submitted
+//!
+//! Every submit records a snapshot of each PR's commits in
+//! `.jj/ryu/snapshots.json`, next to the state file. When a later submit
+//! finds a PR's head moved, it compares the two snapshots change by change,
+//! range-diff style, and posts a comment saying which changes were edited,
+//! added, dropped or merely rebased and which files that touched, so
+//! reviewers can tell whether another look is needed. The comment is
+//! updated in place on later submits.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::read_only::is_read_only;
+use crate::repo::JjWorkspace;
+use crate::state::RyuState;
+use crate::submit::SubmissionPlan;
+use crate::types::{NarrowedBookmarkSegment, PlatformConfig, PullRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshot file name, in the same directory as the state file
+const SNAPSHOT_FILE: &str = "snapshots.json";
+
+/// Marker identifying an interdiff comment written by ryu
+pub const INTERDIFF_MARKER: &str = "<!--- JJ-RYU_INTERDIFF --->";
+
+/// Change and commit ID characters shown in comments
+const SHORT_ID_LEN: usize = 8;
+
+/// One commit of a submitted segment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotChange {
+    /// jj change ID
+    pub change_id: String,
+    /// Commit ID at submit time
+    pub commit_id: String,
+    /// First parent's commit ID
+    pub parent: String,
+    /// Subject line
+    pub subject: String,
+    /// Paths the commit changed
+    pub paths: Vec<String>,
+}
+
+/// A PR's commits as of the last submit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentSnapshot {
+    /// Bookmark (head branch) of the PR
+    pub bookmark: String,
+    /// The PR
+    pub pr_number: u64,
+    /// Commit the bookmark pointed at
+    pub head: String,
+    /// The segment's commits, oldest first
+    pub changes: Vec<SnapshotChange>,
+}
+
+impl SegmentSnapshot {
+    /// Snapshot a segment submitted as PR `pr_number`
+    pub fn capture(
+        workspace: &JjWorkspace,
+        segment: &NarrowedBookmarkSegment,
+        pr_number: u64,
+    ) -> Result<Self> {
+        let mut changes = Vec::with_capacity(segment.changes.len());
+        for change in segment.changes.iter().rev() {
+            changes.push(SnapshotChange {
+                change_id: change.change_id.clone(),
+                commit_id: change.commit_id.clone(),
+                parent: change.parents.first().cloned().unwrap_or_default(),
+                subject: change.description_first_line.clone(),
+                paths: workspace.changed_paths(&change.commit_id)?,
+            });
+        }
+        Ok(Self {
+            bookmark: segment.bookmark.name.clone(),
+            pr_number,
+            head: segment.bookmark.commit_id.clone(),
+            changes,
+        })
+    }
+}
+
+/// How a change differs from its last submitted version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeStatus {
+    /// Its own diff or description changed
+    Edited,
+    /// New since the last submit
+    Added,
+    /// Gone since the last submit
+    Dropped,
+    /// Only moved onto a different parent
+    Rebased,
+    /// The same commit, or one with the same parent, diff and subject
+    Unchanged,
+}
+
+impl std::fmt::Display for ChangeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Edited => write!(f, "edited"),
+            Self::Added => write!(f, "new"),
+            Self::Dropped => write!(f, "dropped"),
+            Self::Rebased => write!(f, "rebased"),
+            Self::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// One change of an [`Interdiff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterdiffChange {
+    /// jj change ID
+    pub change_id: String,
+    /// Subject line (the current one, unless dropped)
+    pub subject: String,
+    /// What happened to it
+    pub status: ChangeStatus,
+}
+
+/// What changed in a PR between two submits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interdiff {
+    /// Head commit at the previous submit
+    pub previous_head: String,
+    /// Head commit now
+    pub head: String,
+    /// Current changes oldest first, then the dropped ones
+    pub changes: Vec<InterdiffChange>,
+    /// Files the edited, added and dropped changes touch, sorted
+    pub files: Vec<String>,
+}
+
+impl Interdiff {
+    /// Whether the changes were only rebased, leaving nothing to re-review
+    pub fn is_rebase_only(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|c| matches!(c.status, ChangeStatus::Rebased | ChangeStatus::Unchanged))
+    }
+}
+
+/// Compare a PR's previous snapshot with its current one; `None` if the
+/// head didn't move
+///
+/// A change whose commit was rewritten counts as edited when its own diff
+/// differs, i.e. files that changed between the two commits but not between
+/// their parents, or its subject line changed; otherwise it was only
+/// rebased.
+pub fn compute_interdiff(
+    workspace: &JjWorkspace,
+    previous: &SegmentSnapshot,
+    current: &SegmentSnapshot,
+) -> Result<Option<Interdiff>> {
+    if previous.head == current.head {
+        return Ok(None);
+    }
+
+    let mut changes = Vec::new();
+    let mut files = BTreeSet::new();
+    for change in &current.changes {
+        let old = previous
+            .changes
+            .iter()
+            .find(|old| old.change_id == change.change_id);
+        let status = match old {
+            None => {
+                files.extend(change.paths.iter().cloned());
+                ChangeStatus::Added
+            }
+            Some(old) if old.commit_id == change.commit_id => ChangeStatus::Unchanged,
+            Some(old) => {
+                let mut own = workspace.diff_paths(&old.commit_id, &change.commit_id)?;
+                if old.parent != change.parent && !own.is_empty() {
+                    let moved = workspace.diff_paths(&old.parent, &change.parent)?;
+                    own.retain(|path| !moved.contains(path));
+                }
+                if !own.is_empty() || old.subject != change.subject {
+                    files.extend(own);
+                    ChangeStatus::Edited
+                } else if old.parent == change.parent {
+                    ChangeStatus::Unchanged
+                } else {
+                    ChangeStatus::Rebased
+                }
+            }
+        };
+        changes.push(InterdiffChange {
+            change_id: change.change_id.clone(),
+            subject: change.subject.clone(),
+            status,
+        });
+    }
+    for old in &previous.changes {
+        if current
+            .changes
+            .iter()
+            .all(|change| change.change_id != old.change_id)
+        {
+            files.extend(old.paths.iter().cloned());
+            changes.push(InterdiffChange {
+                change_id: old.change_id.clone(),
+                subject: old.subject.clone(),
+                status: ChangeStatus::Dropped,
+            });
+        }
+    }
+
+    Ok(Some(Interdiff {
+        previous_head: previous.head.clone(),
+        head: current.head.clone(),
+        changes,
+        files: files.into_iter().collect(),
+    }))
+}
+
+fn short(id: &str) -> &str {
+    &id[..id.len().min(SHORT_ID_LEN)]
+}
+
+/// Render the interdiff comment for a PR on the platform `config` describes
+pub fn render_interdiff_comment(interdiff: &Interdiff, config: &PlatformConfig) -> String {
+    let mut body = format!("{INTERDIFF_MARKER}\n### Changes since the last submit\n\n");
+
+    if interdiff.is_rebase_only() {
+        body.push_str("Only rebased; nothing new to review.\n\n");
+    }
+    for change in &interdiff.changes {
+        let line = format!("`{}` {}", short(&change.change_id), change.subject);
+        let _ = match change.status {
+            ChangeStatus::Unchanged | ChangeStatus::Rebased => {
+                writeln!(body, "- {line} ({})", change.status)
+            }
+            ChangeStatus::Dropped => writeln!(body, "- ~~{line}~~ (**dropped**)"),
+            ChangeStatus::Edited | ChangeStatus::Added => {
+                writeln!(body, "- {line} (**{}**)", change.status)
+            }
+        };
+    }
+
+    if !interdiff.files.is_empty() {
+        let _ = writeln!(body, "\nFiles touched:");
+        for file in &interdiff.files {
+            let _ = writeln!(body, "- `{file}`");
+        }
+    }
+
+    let _ = write!(
+        body,
+        "\n[Compare `{}`...`{}`]({})\n\n---\nUpdated by [jj-ryu](https://github.com/dmmulroy/jj-ryu) on every submit.",
+        short(&interdiff.previous_head),
+        short(&interdiff.head),
+        config.compare_url(&interdiff.previous_head, &interdiff.head)
+    );
+    body
+}
+
+/// Path of the snapshot file for a workspace
+pub fn snapshot_path(workspace_root: &Path) -> PathBuf {
+    RyuState::path(workspace_root).with_file_name(SNAPSHOT_FILE)
+}
+
+/// Snapshots by remote and bookmark
+fn snapshot_key(remote: &str, bookmark: &str) -> String {
+    format!("{remote}/{bookmark}")
+}
+
+/// Load the saved snapshots, by remote and bookmark
+pub fn load_snapshots(workspace_root: &Path) -> Result<BTreeMap<String, SegmentSnapshot>> {
+    let path = snapshot_path(workspace_root);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&path)?)?)
+}
+
+fn save_snapshots(
+    workspace_root: &Path,
+    snapshots: &BTreeMap<String, SegmentSnapshot>,
+) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    let path = snapshot_path(workspace_root);
+    if snapshots.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(snapshots)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Drop the snapshots `keep` rejects, returning their keys
+///
+/// With `dry_run` nothing is written; the keys are still returned.
+pub fn prune_snapshots(
+    workspace_root: &Path,
+    keep: impl Fn(&SegmentSnapshot) -> bool,
+    dry_run: bool,
+) -> Result<Vec<String>> {
+    let mut snapshots = load_snapshots(workspace_root)?;
+    let pruned: Vec<String> = snapshots
+        .iter()
+        .filter(|(_, snapshot)| !keep(snapshot))
+        .map(|(key, _)| key.clone())
+        .collect();
+    if !pruned.is_empty() && !dry_run {
+        snapshots.retain(|key, _| !pruned.contains(key));
+        save_snapshots(workspace_root, &snapshots)?;
+    }
+    Ok(pruned)
+}
+
+/// Post or update the interdiff comment of every PR in `plan` whose head
+/// moved since its snapshot, then snapshot them all
+///
+/// Returns an error message per PR that couldn't be commented on; its old
+/// snapshot is kept, so the next submit reports both rounds of changes. A
+/// PR that was only rebased gets a comment only if it already has one.
+#[allow(clippy::implicit_hasher)]
+pub async fn post_interdiff_comments(
+    plan: &SubmissionPlan,
+    workspace: &JjWorkspace,
+    platform: &dyn PlatformService,
+    bookmark_to_pr: &HashMap<String, PullRequest>,
+) -> Result<Vec<String>> {
+    let root = workspace.workspace_root();
+    let mut snapshots = load_snapshots(root)?;
+    let mut errors = Vec::new();
+
+    for segment in &plan.segments {
+        let Some(pr) = bookmark_to_pr.get(&segment.bookmark.name) else {
+            continue;
+        };
+        let key = snapshot_key(&plan.remote, &segment.bookmark.name);
+        let failed = |e: Error| format!("Failed to post interdiff comment on #{}: {e}", pr.number);
+        let current = match SegmentSnapshot::capture(workspace, segment, pr.number) {
+            Ok(current) => current,
+            Err(e) => {
+                errors.push(failed(e));
+                continue;
+            }
+        };
+        if let Some(previous) = snapshots.get(&key).filter(|s| s.pr_number == pr.number) {
+            if let Err(e) = post_interdiff(workspace, platform, previous, &current).await {
+                errors.push(failed(e));
+                continue;
+            }
+        }
+        snapshots.insert(key, current);
+    }
+
+    save_snapshots(root, &snapshots)?;
+    Ok(errors)
+}
+
+async fn post_interdiff(
+    workspace: &JjWorkspace,
+    platform: &dyn PlatformService,
+    previous: &SegmentSnapshot,
+    current: &SegmentSnapshot,
+) -> Result<()> {
+    let Some(interdiff) = compute_interdiff(workspace, previous, current)? else {
+        return Ok(());
+    };
+    let body = render_interdiff_comment(&interdiff, platform.config());
+    let existing = platform
+        .list_pr_comments(current.pr_number)
+        .await?
+        .into_iter()
+        .find(|c| c.body.contains(INTERDIFF_MARKER));
+    match existing {
+        Some(comment) if comment.body != body => {
+            platform
+                .update_pr_comment(current.pr_number, comment.id, &body)
+                .await
+        }
+        Some(_) => Ok(()),
+        None if interdiff.is_rebase_only() => Ok(()),
+        None => platform.create_pr_comment(current.pr_number, &body).await,
+    }
+}
@@ -0,0 +1,179 @@
+//! Journal of in-progress submissions
+//!
+//! `execute_submission` records each step it completes in
+//! `.jj/ryu/journal.json`, next to the state file, and drops the entry once
+//! every step has run. An entry left behind means a submission stopped
+//! part-way; `ryu submit --resume` replays its plan, skipping the steps that
+//! already succeeded instead of planning from scratch.
+
+use crate::error::Result;
+use crate::read_only::is_read_only;
+use crate::state::RyuState;
+use crate::submit::SubmissionPlan;
+use crate::submit::export::check_bookmarks_unmoved;
+use crate::types::{Bookmark, PullRequest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Journal file name, in the same directory as the state file
+const JOURNAL_FILE: &str = "journal.json";
+
+/// A step of a journaled plan that has already run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedStep {
+    /// Index into the plan's `execution_steps`
+    pub index: usize,
+    /// PR the step created or updated, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr: Option<PullRequest>,
+}
+
+/// A submission plan and how far executing it got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionJournal {
+    /// When execution started
+    pub started_at: DateTime<Utc>,
+    /// The plan being executed
+    pub plan: SubmissionPlan,
+    /// Steps that succeeded, in execution order
+    #[serde(default)]
+    pub completed: Vec<CompletedStep>,
+}
+
+impl SubmissionJournal {
+    /// Start a journal for `plan`
+    pub fn new(plan: SubmissionPlan) -> Self {
+        Self {
+            started_at: Utc::now(),
+            plan,
+            completed: Vec::new(),
+        }
+    }
+
+    /// Key the journal is stored under: the plan's remote and leaf bookmark
+    pub fn key(&self) -> String {
+        journal_key(&self.plan)
+    }
+
+    /// Whether the step at `index` already ran
+    pub fn is_completed(&self, index: usize) -> bool {
+        self.completed.iter().any(|c| c.index == index)
+    }
+
+    /// Number of steps still to run
+    pub fn remaining(&self) -> usize {
+        self.plan.execution_steps.len() - self.completed.len()
+    }
+
+    /// Fail with [`Error::PlanMismatch`] if a planned bookmark has moved
+    /// since the submission started, so resuming would push stale commits
+    ///
+    /// [`Error::PlanMismatch`]: crate::error::Error::PlanMismatch
+    pub fn check_resumable(&self, local_bookmarks: &[Bookmark]) -> Result<()> {
+        check_bookmarks_unmoved(&self.plan, local_bookmarks)
+    }
+}
+
+fn journal_key(plan: &SubmissionPlan) -> String {
+    let leaf = plan
+        .segments
+        .last()
+        .map_or("", |segment| segment.bookmark.name.as_str());
+    format!("{}/{leaf}", plan.remote)
+}
+
+/// Path of the journal file for a workspace
+pub fn journal_path(workspace_root: &Path) -> PathBuf {
+    RyuState::path(workspace_root).with_file_name(JOURNAL_FILE)
+}
+
+fn load_journals(workspace_root: &Path) -> Result<BTreeMap<String, SubmissionJournal>> {
+    let path = journal_path(workspace_root);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&path)?)?)
+}
+
+fn save_journals(
+    workspace_root: &Path,
+    journals: &BTreeMap<String, SubmissionJournal>,
+) -> Result<()> {
+    // A read-only run skipped the steps it would record
+    if is_read_only() {
+        return Ok(());
+    }
+    let path = journal_path(workspace_root);
+    if journals.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(journals)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Find the interrupted submission to `remote` whose stack includes `bookmark`
+pub fn find_journal(
+    workspace_root: &Path,
+    remote: &str,
+    bookmark: &str,
+) -> Result<Option<SubmissionJournal>> {
+    Ok(load_journals(workspace_root)?
+        .into_values()
+        .find(|journal| {
+            journal.plan.remote == remote
+                && journal
+                    .plan
+                    .segments
+                    .iter()
+                    .any(|segment| segment.bookmark.name == bookmark)
+        }))
+}
+
+/// Save `journal`, replacing any earlier one for the same remote and leaf
+pub fn save_journal(workspace_root: &Path, journal: &SubmissionJournal) -> Result<()> {
+    let mut journals = load_journals(workspace_root)?;
+    journals.insert(journal.key(), journal.clone());
+    save_journals(workspace_root, &journals)
+}
+
+/// Drop the journal for `plan` once it has fully run
+pub fn clear_journal(workspace_root: &Path, plan: &SubmissionPlan) -> Result<()> {
+    let mut journals = load_journals(workspace_root)?;
+    if journals.remove(&journal_key(plan)).is_some() {
+        save_journals(workspace_root, &journals)?;
+    }
+    Ok(())
+}
+
+/// Drop the journals `keep` rejects, returning their keys
+///
+/// With `dry_run` nothing is written; the keys are still returned.
+pub fn prune_journals(
+    workspace_root: &Path,
+    keep: impl Fn(&SubmissionJournal) -> bool,
+    dry_run: bool,
+) -> Result<Vec<String>> {
+    let mut journals = load_journals(workspace_root)?;
+    let pruned: Vec<String> = journals
+        .iter()
+        .filter(|(_, journal)| !keep(journal))
+        .map(|(key, _)| key.clone())
+        .collect();
+    if !pruned.is_empty() && !dry_run {
+        journals.retain(|key, _| !pruned.contains(key));
+        save_journals(workspace_root, &journals)?;
+    }
+    Ok(pruned)
+}
@@ -0,0 +1,83 @@
+//! Reviewers, labels and assignees for new PRs
+//!
+//! The config files can name reviewers, labels and assignees for every PR
+//! ryu creates, and a change can ask for more with `Reviewer:`, `Label:` and
+//! `Assignee:` trailers in its description. A segment's PR gets the config
+//! values plus the trailers of all its changes, so nobody has to finish
+//! each PR of a stack in the browser.
+
+use crate::config::RyuConfig;
+use crate::types::{LogEntry, PrMetadata};
+
+/// Trailer key requesting a reviewer
+pub const REVIEWER_TRAILER: &str = "reviewer:";
+/// Trailer key adding a label
+pub const LABEL_TRAILER: &str = "label:";
+/// Trailer key assigning a user
+pub const ASSIGNEE_TRAILER: &str = "assignee:";
+
+/// Values of `key` trailers in a commit description, in order
+///
+/// Keys match case-insensitively and a trailer may list several values
+/// separated by commas.
+fn trailer_values(description: &str, key: &str) -> Vec<String> {
+    description
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let prefix = line.get(..key.len())?;
+            prefix.eq_ignore_ascii_case(key).then(|| &line[key.len()..])
+        })
+        .flat_map(|values| values.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Reviewers, labels and assignees named by trailers in a commit description
+pub fn parse_metadata_trailers(description: &str) -> PrMetadata {
+    PrMetadata {
+        reviewers: trailer_values(description, REVIEWER_TRAILER),
+        labels: trailer_values(description, LABEL_TRAILER),
+        assignees: trailer_values(description, ASSIGNEE_TRAILER),
+    }
+}
+
+/// Metadata for a segment's PR: the config's, then its changes' trailers
+///
+/// Changes are stored newest first and read oldest first. Duplicates are
+/// dropped (case-insensitively, since logins are), keeping the first.
+pub fn segment_metadata(config: &RyuConfig, changes: &[LogEntry]) -> PrMetadata {
+    let mut metadata = PrMetadata {
+        reviewers: config.reviewers.clone().unwrap_or_default(),
+        labels: config.labels.clone().unwrap_or_default(),
+        assignees: config.assignees.clone().unwrap_or_default(),
+    };
+    for change in changes.iter().rev() {
+        let trailers = parse_metadata_trailers(&change.description);
+        metadata.reviewers.extend(trailers.reviewers);
+        metadata.labels.extend(trailers.labels);
+        metadata.assignees.extend(trailers.assignees);
+    }
+    for values in [
+        &mut metadata.reviewers,
+        &mut metadata.labels,
+        &mut metadata.assignees,
+    ] {
+        dedup_ignore_case(values);
+    }
+    metadata
+}
+
+fn dedup_ignore_case(values: &mut Vec<String>) {
+    let mut seen: Vec<String> = Vec::with_capacity(values.len());
+    values.retain(|value| {
+        let lower = value.to_lowercase();
+        if seen.contains(&lower) {
+            return false;
+        }
+        seen.push(lower);
+        true
+    });
+}
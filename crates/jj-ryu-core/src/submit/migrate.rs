@@ -0,0 +1,69 @@
+//! Migration of legacy stack comments
+//!
+//! Older releases marked stack comments with `JJ-STACK_INFO`. Submit already
+//! updates those in place for the PRs it touches; this rewrites them across
+//! every open PR of an author, so a whole organization can upgrade at once.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::execute::{
+    COMMENT_DATA_PREFIX, COMMENT_DATA_PREFIX_OLD, format_stack_comment, parse_stack_comment_data,
+};
+use crate::types::PullRequest;
+
+/// A legacy stack comment rewritten to the current format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedComment {
+    /// PR the comment is on
+    pub pr_number: u64,
+    /// Comment ID
+    pub comment_id: u64,
+}
+
+/// Rewrite a legacy stack comment body in the current format
+///
+/// Returns `None` if the body isn't a legacy stack comment. The list is
+/// re-rendered from the embedded stack data; if that data can't be decoded,
+/// only the marker is replaced.
+pub fn migrate_stack_comment(body: &str, pr_number: u64) -> Option<String> {
+    if !body.contains(COMMENT_DATA_PREFIX_OLD) {
+        return None;
+    }
+
+    let rendered = parse_stack_comment_data(body).and_then(|data| {
+        let idx = data
+            .stack
+            .iter()
+            .position(|item| item.pr_number == pr_number)?;
+        format_stack_comment(&data, idx).ok()
+    });
+    Some(rendered.unwrap_or_else(|| body.replacen(COMMENT_DATA_PREFIX_OLD, COMMENT_DATA_PREFIX, 1)))
+}
+
+/// Find and rewrite legacy stack comments on `prs`
+///
+/// With `dry_run`, reports what would be rewritten without updating anything.
+pub async fn migrate_legacy_comments(
+    platform: &dyn PlatformService,
+    prs: &[PullRequest],
+    dry_run: bool,
+) -> Result<Vec<MigratedComment>> {
+    let mut migrated = Vec::new();
+    for pr in prs {
+        for comment in platform.list_pr_comments(pr.number).await? {
+            let Some(body) = migrate_stack_comment(&comment.body, pr.number) else {
+                continue;
+            };
+            if !dry_run {
+                platform
+                    .update_pr_comment(pr.number, comment.id, &body)
+                    .await?;
+            }
+            migrated.push(MigratedComment {
+                pr_number: pr.number,
+                comment_id: comment.id,
+            });
+        }
+    }
+    Ok(migrated)
+}
@@ -0,0 +1,92 @@
+//! Three-phase submission engine
+//!
+//! Handles the workflow of submitting stacked bookmarks as PRs/MRs:
+//! 1. Analysis - understand what needs to be submitted
+//! 2. Planning - determine what PRs to create/update
+//! 3. Execution - perform the actual operations
+
+mod analysis;
+mod auto_bookmark;
+mod body;
+mod commits;
+mod drift;
+mod execute;
+mod export;
+mod interdiff;
+mod journal;
+mod metadata;
+mod migrate;
+mod multi;
+mod names;
+mod notes;
+mod plan;
+mod progress;
+mod rename;
+mod report;
+mod stack_section;
+mod tracking;
+
+pub use analysis::{
+    ForeignCommit, NonFastForward, PUSH_FORCE_ENV, SubmissionAnalysis, analyze_submission,
+    analyze_upstack_submission, create_narrowed_segments, find_foreign_commits,
+    find_non_fast_forwards, force_push_allowed, generate_pr_title, get_base_branch,
+    select_bookmark_for_segment,
+};
+pub use auto_bookmark::{
+    AutoBookmark, DEFAULT_AUTO_BOOKMARK_TEMPLATE, bookmark_user, plan_auto_bookmarks,
+    render_bookmark_name, slugify,
+};
+pub use body::{BodyUpdate, DiffLine, diff_lines, plan_body_updates, render_pr_body};
+pub use commits::{
+    COMMITS_SECTION_END, COMMITS_SECTION_START, apply_commits_section, render_commits_section,
+};
+pub use drift::StackDrift;
+pub use execute::{
+    STACK_COMMENT_THIS_PR, SubmissionResult, check_pr_unchanged, execute_submission,
+    format_stack_comment, mirror_bookmark, parse_stack_comment_data, resume_submission,
+};
+pub use export::{EXPORTED_PLAN_VERSION, ExportedPlan, PlanCompatibility};
+pub use interdiff::{
+    ChangeStatus, INTERDIFF_MARKER, Interdiff, InterdiffChange, SegmentSnapshot, SnapshotChange,
+    compute_interdiff, load_snapshots, post_interdiff_comments, prune_snapshots,
+    render_interdiff_comment, snapshot_path,
+};
+pub use journal::{
+    CompletedStep, SubmissionJournal, clear_journal, find_journal, journal_path, prune_journals,
+    save_journal,
+};
+
+// Exports for testing stack comment formatting (used by integration tests)
+pub use execute::{
+    COMMENT_DATA_POSTFIX, COMMENT_DATA_PREFIX, COMMENT_DATA_PREFIX_OLD, STACK_COMMENT_VERSION,
+    StackCommentData, StackItem, build_stack_comment_data,
+};
+pub use metadata::{
+    ASSIGNEE_TRAILER, LABEL_TRAILER, REVIEWER_TRAILER, parse_metadata_trailers, segment_metadata,
+};
+pub use migrate::{MigratedComment, migrate_legacy_comments, migrate_stack_comment};
+pub use multi::{
+    PlatformResult, SubmissionTarget, create_multi_submission_plan, execute_multi_submission,
+};
+pub use names::{BookmarkNameIssue, check_bookmark_names, normalize_ref_name, ref_name_problems};
+pub use notes::{
+    NOTE_TRAILER, NOTES_SECTION_END, NOTES_SECTION_START, add_note_trailer, apply_notes_section,
+    parse_notes, render_notes_section, segment_notes, strip_notes,
+};
+pub use plan::{
+    DRAFT_ENV, DuplicatePr, ExecutionConstraint, ExecutionStep, PrBaseUpdate, PrToCreate,
+    SubmissionPlan, create_submission_plan, drafts_by_default,
+};
+pub use progress::{
+    JsonProgress, NoopProgress, Phase, ProgressCallback, ProgressEvent, PushStatus, Warning,
+};
+pub use rename::{RenamedPr, find_renamed_prs, superseded_comment};
+pub use report::{SkippedStack, SubmitReport, SyncReport, SyncStackReport};
+pub use stack_section::{
+    STACK_SECTION_END, STACK_SECTION_START, apply_stack_section, find_stack_data,
+    render_stack_section, stack_section,
+};
+pub use tracking::{
+    TRACKING_ISSUE_ENV, TRACKING_ISSUE_MARKER, find_tracking_issue, pr_reference,
+    render_tracking_issue, sync_tracking_issue, tracking_issue_enabled, tracking_issue_title,
+};
@@ -0,0 +1,100 @@
+//! Submission to several platforms at once
+//!
+//! Projects mirrored across GitHub and GitLab keep one remote per platform.
+//! Each target gets its own plan (existing PRs differ per platform) and is
+//! pushed, opened, and commented on independently.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::submit::{
+    ProgressCallback, SubmissionAnalysis, SubmissionPlan, SubmissionResult, create_submission_plan,
+    execute_submission,
+};
+use crate::types::Platform;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A remote and the platform service hosting its PRs
+pub struct SubmissionTarget {
+    /// Remote to push to
+    pub remote: String,
+    /// Platform service for the remote's repository
+    pub platform: Box<dyn PlatformService>,
+    /// Separate service for stack comments (e.g. a bot account), if any
+    pub comment_platform: Option<Box<dyn PlatformService>>,
+}
+
+/// Result of submitting to one target
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlatformResult {
+    /// Remote that was pushed to
+    pub remote: String,
+    /// Platform the PRs live on
+    pub platform: Platform,
+    /// Submission result for this target
+    pub result: SubmissionResult,
+}
+
+/// Create one plan per target, in target order
+pub async fn create_multi_submission_plan(
+    analysis: &SubmissionAnalysis,
+    targets: &[SubmissionTarget],
+    default_branch: &str,
+) -> Result<Vec<SubmissionPlan>> {
+    let mut plans = Vec::with_capacity(targets.len());
+    for target in targets {
+        plans.push(
+            create_submission_plan(
+                analysis,
+                target.platform.as_ref(),
+                &target.remote,
+                default_branch,
+            )
+            .await?,
+        );
+    }
+    Ok(plans)
+}
+
+/// Execute each target's plan in turn
+///
+/// A failure on one platform doesn't stop the others; check each
+/// [`PlatformResult`] for success.
+pub async fn execute_multi_submission(
+    plans: &[SubmissionPlan],
+    targets: &[SubmissionTarget],
+    workspace: &mut JjWorkspace,
+    progress: &dyn ProgressCallback,
+    dry_run: bool,
+) -> Result<Vec<PlatformResult>> {
+    let mut results = Vec::with_capacity(targets.len());
+    for (plan, target) in plans.iter().zip(targets) {
+        let platform = target.platform.as_ref();
+        if targets.len() > 1 {
+            progress
+                .on_message(&format!(
+                    "{} ({})",
+                    platform.config().platform,
+                    target.remote
+                ))
+                .await;
+        }
+
+        let result = execute_submission(
+            plan,
+            workspace,
+            platform,
+            target.comment_platform.as_deref(),
+            progress,
+            dry_run,
+        )
+        .await?;
+        results.push(PlatformResult {
+            remote: target.remote.clone(),
+            platform: platform.config().platform,
+            result,
+        });
+    }
+    Ok(results)
+}
@@ -0,0 +1,170 @@
+//! Bookmark names git and the platform will accept
+//!
+//! jj accepts bookmark names git can't store as branches (`fix..typo`,
+//! `wip:login`, `feature.lock`), and the push then fails with an opaque
+//! ref error halfway through a submission. Names are checked against git's
+//! ref format rules and the configured `branch-pattern` (a regex, for
+//! platforms or orgs with branch naming policies) before anything is pushed,
+//! with a normalized name to rename to.
+
+use crate::error::{Error, Result};
+use jj_lib::str_util::{StringMatcher, StringPattern};
+
+/// A bookmark whose name can't be pushed as is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookmarkNameIssue {
+    /// The bookmark
+    pub bookmark: String,
+    /// What's wrong with the name
+    pub problems: Vec<String>,
+    /// A valid name to rename it to, if one could be derived
+    pub suggestion: Option<String>,
+}
+
+/// Characters git never allows in a ref name
+const FORBIDDEN: &[char] = &[' ', '~', '^', ':', '?', '*', '[', '\\'];
+
+/// Ways `name` breaks git's rules for branch names (`git check-ref-format --branch`)
+pub fn ref_name_problems(name: &str) -> Vec<&'static str> {
+    let mut problems = Vec::new();
+    if name.is_empty() {
+        return vec!["is empty"];
+    }
+    if name == "@" || name == "HEAD" {
+        problems.push("is reserved by git");
+    }
+    if name.starts_with('-') {
+        problems.push("starts with '-'");
+    }
+    if name
+        .chars()
+        .any(|c| c.is_ascii_control() || FORBIDDEN.contains(&c))
+    {
+        problems.push("contains a space, control character or one of ~ ^ : ? * [ \\");
+    }
+    if name.contains("..") {
+        problems.push("contains '..'");
+    }
+    if name.contains("@{") {
+        problems.push("contains '@{'");
+    }
+    if name.starts_with('/') || name.ends_with('/') || name.contains("//") {
+        problems.push("has an empty path component");
+    }
+    if name.ends_with('.') {
+        problems.push("ends with '.'");
+    }
+    let components: Vec<&str> = name.split('/').collect();
+    if components.iter().any(|c| c.starts_with('.')) {
+        problems.push("has a component starting with '.'");
+    }
+    if components.iter().any(|c| c.ends_with(".lock")) {
+        problems.push("has a component ending with '.lock'");
+    }
+    problems
+}
+
+/// Rewrite `name` into one git accepts, or `None` if nothing is left
+///
+/// Forbidden characters become `-`, empty components are dropped, and
+/// leading dots, trailing dots and `.lock` suffixes are trimmed.
+pub fn normalize_ref_name(name: &str) -> Option<String> {
+    let replaced: String = name
+        .replace("@{", "-")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_control() || FORBIDDEN.contains(&c) {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let mut components = Vec::new();
+    for component in replaced.split('/') {
+        let mut component = component.to_string();
+        while component.contains("..") {
+            component = component.replace("..", ".");
+        }
+        let mut component = component.trim_start_matches('.');
+        loop {
+            let trimmed = component.trim_end_matches('.');
+            match trimmed.strip_suffix(".lock") {
+                Some(rest) => component = rest,
+                None => {
+                    component = trimmed;
+                    break;
+                }
+            }
+        }
+        if !component.is_empty() {
+            components.push(collapse_dashes(component));
+        }
+    }
+
+    let normalized = components.join("/");
+    let normalized = normalized.trim_start_matches('-');
+    (!normalized.is_empty() && ref_name_problems(normalized).is_empty())
+        .then(|| normalized.to_string())
+}
+
+/// Check bookmark names against git's rules and `branch_pattern`
+///
+/// `branch_pattern` is a regex a name must match somewhere (anchor it with
+/// `^` and `$` to match the whole name). Suggestions satisfy both, trying a
+/// lowercased name when the pattern rejects the normalized one.
+pub fn check_bookmark_names<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    branch_pattern: Option<&str>,
+) -> Result<Vec<BookmarkNameIssue>> {
+    let matcher = branch_pattern.map(pattern_matcher).transpose()?;
+    let allowed = |name: &str| matcher.as_ref().is_none_or(|m| m.is_match(name));
+
+    let mut issues = Vec::new();
+    for name in names {
+        let mut problems: Vec<String> = ref_name_problems(name)
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        if !allowed(name) {
+            problems.push(format!(
+                "doesn't match branch-pattern '{}'",
+                branch_pattern.unwrap_or_default()
+            ));
+        }
+        if problems.is_empty() {
+            continue;
+        }
+
+        let suggestion = normalize_ref_name(name).and_then(|normalized| {
+            if allowed(&normalized) {
+                return Some(normalized);
+            }
+            let lower = normalized.to_lowercase();
+            allowed(&lower).then_some(lower)
+        });
+        issues.push(BookmarkNameIssue {
+            bookmark: name.to_string(),
+            problems,
+            suggestion: suggestion.filter(|s| s != name),
+        });
+    }
+    Ok(issues)
+}
+
+fn pattern_matcher(pattern: &str) -> Result<StringMatcher> {
+    StringPattern::regex(pattern)
+        .map(|pattern| pattern.to_matcher())
+        .map_err(|e| Error::Config(format!("invalid branch-pattern '{pattern}': {e}")))
+}
+
+fn collapse_dashes(component: &str) -> String {
+    let mut collapsed = String::with_capacity(component.len());
+    for c in component.chars() {
+        if c != '-' || !collapsed.ends_with('-') {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
@@ -0,0 +1,119 @@
+//! Reviewer notes carried in commit descriptions
+//!
+//! A `ryu-note:` trailer on a change (testing instructions, risk level, ...)
+//! is meant for reviewers rather than history readers. Submit renders a
+//! segment's notes into a marker-delimited section of its PR body, leaving
+//! the rest of the body alone.
+
+use crate::types::LogEntry;
+
+/// Trailer key marking a reviewer note
+pub const NOTE_TRAILER: &str = "ryu-note:";
+
+/// Start of the notes section in a PR body
+pub const NOTES_SECTION_START: &str = "<!--- JJ-RYU_NOTES --->";
+/// End of the notes section in a PR body
+pub const NOTES_SECTION_END: &str = "<!--- /JJ-RYU_NOTES --->";
+
+/// The (possibly empty) note on a trailer line
+fn parse_note_line(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let prefix = line.get(..NOTE_TRAILER.len())?;
+    prefix
+        .eq_ignore_ascii_case(NOTE_TRAILER)
+        .then(|| line[NOTE_TRAILER.len()..].trim())
+}
+
+/// Reviewer notes in a commit description, in order
+pub fn parse_notes(description: &str) -> Vec<String> {
+    description
+        .lines()
+        .filter_map(parse_note_line)
+        .filter(|note| !note.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// A commit description with its note trailers removed
+pub fn strip_notes(description: &str) -> String {
+    description
+        .lines()
+        .filter(|line| parse_note_line(line).is_none())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// Append a note trailer to a commit description
+///
+/// The trailer joins an existing trailer block (a final paragraph of
+/// `key: value` lines) or starts a new one after a blank line.
+pub fn add_note_trailer(description: &str, note: &str) -> String {
+    let description = description.trim_end();
+    let trailer = format!("{NOTE_TRAILER} {}", note.trim());
+    if description.is_empty() {
+        return trailer;
+    }
+
+    let last_paragraph = description.rsplit("\n\n").next().unwrap_or_default();
+    let in_trailer_block = description.contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.contains(' '))
+        });
+    if in_trailer_block {
+        format!("{description}\n{trailer}")
+    } else {
+        format!("{description}\n\n{trailer}")
+    }
+}
+
+/// Notes across a segment's changes (stored newest first), oldest first
+pub fn segment_notes(changes: &[LogEntry]) -> Vec<String> {
+    changes
+        .iter()
+        .rev()
+        .flat_map(|change| parse_notes(&change.description))
+        .collect()
+}
+
+/// Render the notes section for a PR body (empty when there are no notes)
+pub fn render_notes_section(notes: &[String]) -> String {
+    if notes.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = notes.iter().map(|note| format!("- {note}")).collect();
+    format!(
+        "{NOTES_SECTION_START}\n### Review notes\n\n{}\n{NOTES_SECTION_END}",
+        items.join("\n")
+    )
+}
+
+/// Replace (or add, or remove) the notes section in a PR body
+///
+/// Text outside the markers is preserved; a new section goes at the end.
+pub fn apply_notes_section(body: &str, notes: &[String]) -> String {
+    let section = render_notes_section(notes);
+
+    let existing = body.find(NOTES_SECTION_START).and_then(|start| {
+        body[start..]
+            .find(NOTES_SECTION_END)
+            .map(|end| (start, start + end + NOTES_SECTION_END.len()))
+    });
+
+    match existing {
+        Some((start, end)) => {
+            let before = body[..start].trim_end();
+            let after = body[end..].trim_start();
+            [before, section.as_str(), after]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+        None if section.is_empty() => body.to_string(),
+        None if body.trim().is_empty() => section,
+        None => format!("{}\n\n{section}", body.trim_end()),
+    }
+}
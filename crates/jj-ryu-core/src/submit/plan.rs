@@ -2,16 +2,41 @@
 //!
 //! Determines what operations need to be performed to submit a stack.
 
+use crate::config::RyuConfig;
 use crate::error::{Error, Result};
 use crate::platform::PlatformService;
 use crate::submit::SubmissionAnalysis;
 use crate::submit::analysis::{generate_pr_title, get_base_branch};
-use crate::types::{Bookmark, NarrowedBookmarkSegment, PullRequest};
+use crate::submit::body::render_pr_body;
+use crate::submit::rename::{RenamedPr, find_renamed_prs};
+use crate::types::{Bookmark, NarrowedBookmarkSegment, PullRequest, StackInfo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
+/// Environment variable making new PRs drafts by default
+pub const DRAFT_ENV: &str = "RYU_DRAFT";
+
+/// Whether new PRs are created as drafts (`draft`, default false)
+///
+/// `RYU_DRAFT` wins over the config files. Applies to PRs created by both
+/// `submit` and `sync`; `submit --publish` overrides it.
+pub fn drafts_by_default(config: &RyuConfig) -> Result<bool> {
+    let Ok(value) = std::env::var(DRAFT_ENV) else {
+        return Ok(config.draft.unwrap_or(false));
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "" | "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(Error::Config(format!(
+            "{DRAFT_ENV} must be true or false, got '{value}'"
+        ))),
+    }
+}
+
 /// Information about a PR that needs to be created
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PrToCreate {
     /// Bookmark for this PR
     pub bookmark: Bookmark,
@@ -19,12 +44,15 @@ pub struct PrToCreate {
     pub base_branch: String,
     /// Generated PR title
     pub title: String,
+    /// PR body rendered from the segment's commit messages
+    #[serde(default)]
+    pub body: String,
     /// Whether to create as draft
     pub draft: bool,
 }
 
 /// Information about a PR that needs its base updated
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PrBaseUpdate {
     /// Bookmark for this PR
     pub bookmark: Bookmark,
@@ -37,7 +65,7 @@ pub struct PrBaseUpdate {
 }
 
 /// Ordered execution step for a submission plan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ExecutionStep {
     /// Push bookmark to remote
     Push(Bookmark),
@@ -92,15 +120,15 @@ impl std::fmt::Display for ExecutionStep {
 
 /// Typed reference to a Push operation by bookmark name.
 /// Distinct from [`UpdateRef`]/[`CreateRef`] to prevent mixing constraint endpoints.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct PushRef(pub String);
 
 /// Typed reference to an `UpdateBase` operation by bookmark name.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateRef(pub String);
 
 /// Typed reference to a `CreatePr` operation by bookmark name.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct CreateRef(pub String);
 
 /// Dependency constraint between execution operations.
@@ -111,7 +139,7 @@ pub struct CreateRef(pub String);
 /// Constraints may reference operations that don't exist in the current plan
 /// (e.g., a bookmark that's already synced has no `Push` node). Resolution
 /// returns `None` for such constraints, which is expected behavior.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ExecutionConstraint {
     /// Push parent branch before child branch.
     /// Ensures commits are pushed in stack order (ancestors before descendants).
@@ -254,6 +282,20 @@ impl ExecutionConstraint {
     }
 }
 
+/// An open PR that duplicates the canonical PR for a bookmark
+///
+/// Manual creation races can leave a branch with more than one open PR.
+/// The oldest PR is kept as canonical; newer ones are reported here.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicatePr {
+    /// Bookmark (head branch) with more than one open PR
+    pub bookmark: String,
+    /// PR kept for the bookmark (the oldest)
+    pub canonical: PullRequest,
+    /// Newer PR for the same head branch
+    pub duplicate: PullRequest,
+}
+
 /// Internal node for dependency-aware scheduling
 #[derive(Debug, Clone)]
 struct ExecutionNode {
@@ -262,7 +304,7 @@ struct ExecutionNode {
 }
 
 /// Submission plan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SubmissionPlan {
     /// Segments to submit (used for stack comment generation)
     pub segments: Vec<NarrowedBookmarkSegment>,
@@ -272,8 +314,23 @@ pub struct SubmissionPlan {
     pub execution_steps: Vec<ExecutionStep>,
     /// Existing PRs by bookmark name
     pub existing_prs: HashMap<String, PullRequest>,
+    /// Newer open PRs sharing a head branch with an existing PR
+    pub duplicate_prs: Vec<DuplicatePr>,
     /// Remote name to push to
     pub remote: String,
+    /// Push-only remotes that mirror every bookmark pushed to `remote`
+    pub mirror_remotes: Vec<String>,
+    /// Create or update an umbrella tracking issue listing the stack's PRs
+    pub tracking_issue: bool,
+    /// Where stack navigation is published (`stack-info` in the config files)
+    #[serde(default)]
+    pub stack_info: StackInfo,
+    /// Comment on PRs with what changed since the last submit
+    /// (`interdiff-comments` in the config files)
+    #[serde(default)]
+    pub interdiff_comments: bool,
+    /// Open PRs left under a bookmark's old name, closed once the new PR exists
+    pub renamed_prs: Vec<RenamedPr>,
     /// Default branch name (main/master)
     pub default_branch: String,
 }
@@ -315,6 +372,15 @@ impl SubmissionPlan {
             .filter(|s| matches!(s, ExecutionStep::PublishPr(_)))
             .count()
     }
+
+    /// Create every new PR in the plan as a draft
+    pub fn draft_new_prs(&mut self) {
+        for step in &mut self.execution_steps {
+            if let ExecutionStep::CreatePr(create) = step {
+                create.draft = true;
+            }
+        }
+    }
 }
 
 /// Create a submission plan
@@ -332,11 +398,21 @@ pub async fn create_submission_plan(
     let segments = &analysis.segments;
     let bookmarks: Vec<&Bookmark> = segments.iter().map(|s| &s.bookmark).collect();
 
-    // Check for existing PRs
+    // Check for existing PRs, keeping the oldest when a branch has duplicates
+    let names: Vec<String> = bookmarks.iter().map(|b| b.name.clone()).collect();
     let mut existing_prs = HashMap::new();
-    for bookmark in &bookmarks {
-        if let Some(pr) = platform.find_existing_pr(&bookmark.name).await? {
-            existing_prs.insert(bookmark.name.clone(), pr);
+    let mut duplicate_prs = Vec::new();
+    for found in platform.find_branch_prs(&names).await? {
+        let mut prs = found.prs.into_iter();
+        if let Some(canonical) = prs.next() {
+            for duplicate in prs {
+                duplicate_prs.push(DuplicatePr {
+                    bookmark: found.head_branch.clone(),
+                    canonical: canonical.clone(),
+                    duplicate,
+                });
+            }
+            existing_prs.insert(found.head_branch, canonical);
         }
     }
 
@@ -345,7 +421,7 @@ pub async fn create_submission_plan(
     let mut prs_to_create = Vec::new();
     let mut prs_to_update_base = Vec::new();
 
-    for bookmark in &bookmarks {
+    for (bookmark, segment) in bookmarks.iter().zip(segments) {
         // Check if needs push
         if !bookmark.has_remote || !bookmark.is_synced {
             bookmarks_needing_push.push((*bookmark).clone());
@@ -354,7 +430,12 @@ pub async fn create_submission_plan(
         // Check if needs PR creation
         if let Some(pr) = existing_prs.get(&bookmark.name) {
             // PR exists - check if base needs updating
-            let expected_base = get_base_branch(&bookmark.name, segments, default_branch)?;
+            let expected_base = get_base_branch(
+                &bookmark.name,
+                segments,
+                default_branch,
+                &analysis.base_overrides,
+            )?;
 
             if pr.base_ref != expected_base {
                 prs_to_update_base.push(PrBaseUpdate {
@@ -366,18 +447,34 @@ pub async fn create_submission_plan(
             }
         } else {
             // PR doesn't exist - needs creation
-            let base_branch = get_base_branch(&bookmark.name, segments, default_branch)?;
+            let base_branch = get_base_branch(
+                &bookmark.name,
+                segments,
+                default_branch,
+                &analysis.base_overrides,
+            )?;
             let title = generate_pr_title(&bookmark.name, segments)?;
 
             prs_to_create.push(PrToCreate {
                 bookmark: (*bookmark).clone(),
                 base_branch,
                 title,
+                body: render_pr_body(&segment.changes),
                 draft: false,
             });
         }
     }
 
+    // New PRs may replace ones left behind by a bookmark rename; a failed
+    // lookup only means a duplicate PR, so it doesn't stop the submission
+    let stack_bookmarks: Vec<&str> = bookmarks.iter().map(|b| b.name.as_str()).collect();
+    let renamed_prs = find_renamed_prs(platform, &prs_to_create, &stack_bookmarks)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "could not check for renamed bookmarks");
+            Vec::new()
+        });
+
     // Build ordered execution steps
     let (constraints, execution_steps) = build_execution_steps(
         segments,
@@ -392,7 +489,13 @@ pub async fn create_submission_plan(
         constraints,
         execution_steps,
         existing_prs,
+        duplicate_prs,
         remote: remote.to_string(),
+        mirror_remotes: Vec::new(),
+        tracking_issue: false,
+        stack_info: StackInfo::Comment,
+        interdiff_comments: true,
+        renamed_prs,
         default_branch: default_branch.to_string(),
     })
 }
@@ -706,6 +809,7 @@ mod tests {
             title: format!("PR for {bookmark}"),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: false,
+            updated_at: None,
         }
     }
 
@@ -728,6 +832,7 @@ mod tests {
             bookmark: bookmark.clone(),
             base_branch: base_branch.to_string(),
             title: format!("Add {}", bookmark.name),
+            body: String::new(),
             draft: false,
         }
     }
@@ -757,6 +862,7 @@ mod tests {
             bookmark: make_bookmark("feat-a", false, false),
             base_branch: "main".to_string(),
             title: "Add feature A".to_string(),
+            body: String::new(),
             draft: false,
         };
 
@@ -766,6 +872,36 @@ mod tests {
         assert!(!pr_create.draft);
     }
 
+    #[test]
+    fn test_draft_new_prs_only_touches_creates() {
+        let bm = make_bookmark("feat-a", false, false);
+        let mut plan = SubmissionPlan {
+            segments: vec![make_segment("feat-a")],
+            constraints: vec![],
+            execution_steps: vec![
+                ExecutionStep::Push(bm.clone()),
+                ExecutionStep::CreatePr(make_create(&bm, "main")),
+            ],
+            existing_prs: HashMap::new(),
+            duplicate_prs: vec![],
+            remote: "origin".to_string(),
+            mirror_remotes: vec![],
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
+            default_branch: "main".to_string(),
+        };
+
+        plan.draft_new_prs();
+
+        assert_eq!(plan.count_pushes(), 1);
+        assert!(matches!(
+            &plan.execution_steps[1],
+            ExecutionStep::CreatePr(create) if create.draft
+        ));
+    }
+
     #[test]
     fn test_execution_steps_simple_push_order() {
         let segments = vec![make_segment("a"), make_segment("b")];
@@ -888,7 +1024,13 @@ mod tests {
             constraints: vec![],
             execution_steps: vec![],
             existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
             remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
             default_branch: "main".to_string(),
         };
 
@@ -908,7 +1050,13 @@ mod tests {
                 ExecutionStep::CreatePr(make_create(&bm, "main")),
             ],
             existing_prs: HashMap::new(),
+            duplicate_prs: Vec::new(),
             remote: "origin".to_string(),
+            mirror_remotes: Vec::new(),
+            tracking_issue: false,
+            stack_info: StackInfo::Comment,
+            interdiff_comments: false,
+            renamed_prs: Vec::new(),
             default_branch: "main".to_string(),
         };
 
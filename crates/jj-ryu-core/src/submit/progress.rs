@@ -0,0 +1,387 @@
+//! Progress callback trait for interface-agnostic updates
+//!
+//! This trait allows different interfaces (CLI, web server, etc.) to receive
+//! progress updates during submission operations.
+
+use crate::error::Error;
+use crate::types::PullRequest;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Submission phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Phase {
+    /// Analyzing the change graph
+    Analyzing,
+    /// Rebasing stacks onto the updated trunk
+    Restacking,
+    /// Planning what to submit
+    Planning,
+    /// Executing submission operations (push, create, update, publish)
+    Executing,
+    /// Closing PRs left under a renamed bookmark's old name
+    ClosingRenamed,
+    /// Pushing bookmarks to mirror remotes
+    Mirroring,
+    /// Writing review notes and commit lists into PR bodies
+    UpdatingNotes,
+    /// Creating or updating the stack's tracking issue
+    UpdatingTrackingIssue,
+    /// Commenting on PRs with what changed since the last submit
+    PostingInterdiffs,
+    /// Adding/updating stack comments
+    AddingComments,
+    /// Merging a stack's PRs bottom-up
+    Merging,
+    /// Submission complete
+    Complete,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Analyzing => write!(f, "Analyzing"),
+            Self::Restacking => write!(f, "Restacking onto trunk"),
+            Self::Planning => write!(f, "Planning"),
+            Self::Executing => write!(f, "Executing"),
+            Self::ClosingRenamed => write!(f, "Closing PRs of renamed bookmarks"),
+            Self::Mirroring => write!(f, "Mirroring"),
+            Self::UpdatingNotes => write!(f, "Updating PR bodies"),
+            Self::UpdatingTrackingIssue => write!(f, "Updating tracking issue"),
+            Self::PostingInterdiffs => write!(f, "Posting interdiff comments"),
+            Self::AddingComments => write!(f, "Updating stack comments"),
+            Self::Merging => write!(f, "Merging PRs"),
+            Self::Complete => write!(f, "Done"),
+        }
+    }
+}
+
+/// Push operation status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PushStatus {
+    /// Push started
+    Started,
+    /// Push succeeded
+    Success,
+    /// Bookmark already synced with remote
+    AlreadySynced,
+    /// Push failed with error message
+    Failed(String),
+}
+
+impl std::fmt::Display for PushStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Started => write!(f, "started"),
+            Self::Success => write!(f, "success"),
+            Self::AlreadySynced => write!(f, "already synced"),
+            Self::Failed(msg) => write!(f, "failed: {msg}"),
+        }
+    }
+}
+
+/// Something the user should know about that didn't stop the operation
+///
+/// Warnings are reported through [`ProgressCallback::on_warning`], apart
+/// from errors: nothing failed outright, but something was left out or is
+/// about to go wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Warning {
+    /// A bookmark was left out of the operation
+    SkippedBookmark {
+        /// The bookmark left out
+        bookmark: String,
+        /// Why it was left out
+        reason: String,
+    },
+    /// A stack comment couldn't be updated, so it no longer matches the stack
+    StaleComment {
+        /// PR the comment is on
+        pr_number: u64,
+        /// Bookmark of that PR
+        bookmark: String,
+        /// Why the update failed
+        reason: String,
+    },
+    /// A bookmark's PR was found under the bookmark's old name, by the change
+    /// ID its stack comment records; it will be replaced by a new PR
+    RenamedBookmark {
+        /// PR left under the old name
+        pr_number: u64,
+        /// Name the PR's head branch still has
+        old_bookmark: String,
+        /// Current name of the bookmark
+        bookmark: String,
+    },
+    /// The platform's API budget is running low
+    RateLimitNearing {
+        /// Requests left in the current window
+        remaining: u64,
+        /// Requests allowed per window
+        limit: u64,
+        /// When the window resets
+        resets_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SkippedBookmark { bookmark, reason } => {
+                write!(f, "Skipped {bookmark}: {reason}")
+            }
+            Self::StaleComment {
+                pr_number,
+                bookmark,
+                reason,
+            } => write!(
+                f,
+                "Stack comment on #{pr_number} ({bookmark}) is out of date: {reason}"
+            ),
+            Self::RenamedBookmark {
+                pr_number,
+                old_bookmark,
+                bookmark,
+            } => write!(
+                f,
+                "{old_bookmark} was renamed to {bookmark}; #{pr_number} will be replaced by a new PR"
+            ),
+            Self::RateLimitNearing {
+                remaining,
+                limit,
+                resets_at,
+            } => {
+                write!(f, "Only {remaining} of {limit} API requests left")?;
+                match resets_at {
+                    Some(at) => write!(f, " until {}", at.format("%H:%M UTC")),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// A progress update as data
+///
+/// Mirrors the [`ProgressCallback`] methods one-to-one, for interfaces that
+/// stream progress to other processes (one JSON object per event).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// Entered a new phase
+    Phase {
+        /// The phase entered
+        phase: Phase,
+    },
+    /// A segment was rebased onto the updated trunk
+    SegmentRestacked {
+        /// Bookmark of the rebased segment
+        bookmark: String,
+    },
+    /// A bookmark push changed status
+    BookmarkPush {
+        /// Bookmark being pushed
+        bookmark: String,
+        /// Push status
+        status: PushStatus,
+    },
+    /// A bookmark push to a mirror remote changed status
+    MirrorPush {
+        /// Bookmark being mirrored
+        bookmark: String,
+        /// Mirror remote
+        remote: String,
+        /// Push status
+        status: PushStatus,
+    },
+    /// A PR was created
+    PrCreated {
+        /// Bookmark the PR is for
+        bookmark: String,
+        /// The new PR
+        pr: PullRequest,
+    },
+    /// A PR was updated
+    PrUpdated {
+        /// Bookmark the PR is for
+        bookmark: String,
+        /// The updated PR
+        pr: PullRequest,
+    },
+    /// A PR was merged
+    PrMerged {
+        /// Bookmark the PR is for
+        bookmark: String,
+        /// The merged PR
+        pr: PullRequest,
+    },
+    /// A non-fatal error occurred
+    Error {
+        /// Error message
+        message: String,
+    },
+    /// Something was skipped or is about to go wrong, without failing
+    Warning {
+        /// The warning
+        warning: Warning,
+    },
+    /// General status message
+    Message {
+        /// Message text
+        message: String,
+    },
+}
+
+/// Progress callback trait
+///
+/// Implement this trait to receive progress updates during submission.
+/// - CLI implementations can print to terminal
+/// - Web servers can send SSE or WebSocket messages
+#[async_trait]
+pub trait ProgressCallback: Send + Sync {
+    /// Called when entering a new phase
+    async fn on_phase(&self, phase: Phase);
+
+    /// Called when a segment has been rebased onto the updated trunk
+    async fn on_segment_restacked(&self, bookmark: &str);
+
+    /// Called when a bookmark is being pushed
+    async fn on_bookmark_push(&self, bookmark: &str, status: PushStatus);
+
+    /// Called when a bookmark has been pushed (or skipped) on a mirror remote
+    async fn on_mirror_push(&self, bookmark: &str, remote: &str, status: PushStatus);
+
+    /// Called when a PR is created
+    async fn on_pr_created(&self, bookmark: &str, pr: &PullRequest);
+
+    /// Called when a PR is updated
+    async fn on_pr_updated(&self, bookmark: &str, pr: &PullRequest);
+
+    /// Called when a PR has been merged
+    async fn on_pr_merged(&self, bookmark: &str, pr: &PullRequest);
+
+    /// Called when an error occurs (non-fatal)
+    async fn on_error(&self, error: &Error);
+
+    /// Called when something was skipped or is about to go wrong
+    async fn on_warning(&self, warning: &Warning);
+
+    /// Called with a general status message
+    async fn on_message(&self, message: &str);
+}
+
+/// No-op progress callback for testing or when progress isn't needed
+pub struct NoopProgress;
+
+#[async_trait]
+impl ProgressCallback for NoopProgress {
+    async fn on_phase(&self, _phase: Phase) {}
+    async fn on_segment_restacked(&self, _bookmark: &str) {}
+    async fn on_bookmark_push(&self, _bookmark: &str, _status: PushStatus) {}
+    async fn on_mirror_push(&self, _bookmark: &str, _remote: &str, _status: PushStatus) {}
+    async fn on_pr_created(&self, _bookmark: &str, _pr: &PullRequest) {}
+    async fn on_pr_updated(&self, _bookmark: &str, _pr: &PullRequest) {}
+    async fn on_pr_merged(&self, _bookmark: &str, _pr: &PullRequest) {}
+    async fn on_error(&self, _error: &Error) {}
+    async fn on_warning(&self, _warning: &Warning) {}
+    async fn on_message(&self, _message: &str) {}
+}
+
+/// Progress callback that records every update as a [`ProgressEvent`]
+///
+/// Used for JSON output, where the events are reported alongside the result
+/// instead of being printed as they happen.
+#[derive(Debug, Default)]
+pub struct JsonProgress {
+    events: Mutex<Vec<ProgressEvent>>,
+}
+
+impl JsonProgress {
+    /// Create an empty event log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove and return the events recorded so far
+    pub fn take_events(&self) -> Vec<ProgressEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn record(&self, event: ProgressEvent) {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+}
+
+#[async_trait]
+impl ProgressCallback for JsonProgress {
+    async fn on_phase(&self, phase: Phase) {
+        self.record(ProgressEvent::Phase { phase });
+    }
+
+    async fn on_segment_restacked(&self, bookmark: &str) {
+        self.record(ProgressEvent::SegmentRestacked {
+            bookmark: bookmark.to_string(),
+        });
+    }
+
+    async fn on_bookmark_push(&self, bookmark: &str, status: PushStatus) {
+        self.record(ProgressEvent::BookmarkPush {
+            bookmark: bookmark.to_string(),
+            status,
+        });
+    }
+
+    async fn on_mirror_push(&self, bookmark: &str, remote: &str, status: PushStatus) {
+        self.record(ProgressEvent::MirrorPush {
+            bookmark: bookmark.to_string(),
+            remote: remote.to_string(),
+            status,
+        });
+    }
+
+    async fn on_pr_created(&self, bookmark: &str, pr: &PullRequest) {
+        self.record(ProgressEvent::PrCreated {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_pr_updated(&self, bookmark: &str, pr: &PullRequest) {
+        self.record(ProgressEvent::PrUpdated {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_pr_merged(&self, bookmark: &str, pr: &PullRequest) {
+        self.record(ProgressEvent::PrMerged {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_error(&self, error: &Error) {
+        self.record(ProgressEvent::Error {
+            message: error.to_string(),
+        });
+    }
+
+    async fn on_warning(&self, warning: &Warning) {
+        self.record(ProgressEvent::Warning {
+            warning: warning.clone(),
+        });
+    }
+
+    async fn on_message(&self, message: &str) {
+        self.record(ProgressEvent::Message {
+            message: message.to_string(),
+        });
+    }
+}
@@ -0,0 +1,79 @@
+//! Bookmarks renamed after their PR was opened
+//!
+//! A PR's head branch can't be changed, so renaming a bookmark leaves its PR
+//! behind under the old name. Stack comments record the change ID each PR's
+//! bookmark pointed at; a bookmark about to get a new PR is matched against
+//! the user's open PRs whose head branch no longer exists locally. The new
+//! PR is created as usual (children are retargeted onto it by the plan), and
+//! the old one is closed with a pointer to its replacement.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::plan::PrToCreate;
+use crate::submit::stack_section::find_stack_data;
+use crate::submit::tracking::pr_reference;
+use crate::types::{Platform, PullRequest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// An open PR left behind under a bookmark's previous name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RenamedPr {
+    /// Current bookmark name
+    pub bookmark: String,
+    /// PR still open under the old head branch
+    pub old_pr: PullRequest,
+}
+
+/// Find open PRs whose bookmark was renamed to one in `creates`
+///
+/// `stack_bookmarks` are the bookmarks of the whole submission; PRs on those
+/// branches are never candidates. Only the authenticated user's PRs are
+/// considered, and only ones whose stack comment records a change ID.
+pub async fn find_renamed_prs(
+    platform: &dyn PlatformService,
+    creates: &[PrToCreate],
+    stack_bookmarks: &[&str],
+) -> Result<Vec<RenamedPr>> {
+    if creates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let by_change_id: HashMap<&str, &str> = creates
+        .iter()
+        .map(|c| (c.bookmark.change_id.as_str(), c.bookmark.name.as_str()))
+        .collect();
+
+    let user = platform.current_user().await?;
+    let mut renamed = Vec::new();
+    for pr in platform.list_open_prs_by_author(&user).await? {
+        if stack_bookmarks.contains(&pr.head_ref.as_str()) {
+            continue;
+        }
+        let change_id = find_stack_data(platform, pr.number)
+            .await?
+            .into_iter()
+            .flat_map(|data| data.stack)
+            .find(|item| item.pr_number == pr.number)
+            .and_then(|item| item.change_id);
+        if let Some(&bookmark) = change_id.as_deref().and_then(|id| by_change_id.get(id)) {
+            debug!(pr_number = pr.number, old = %pr.head_ref, new = bookmark, "found renamed PR");
+            renamed.push(RenamedPr {
+                bookmark: bookmark.to_string(),
+                old_pr: pr,
+            });
+        }
+    }
+    Ok(renamed)
+}
+
+/// Comment left on the old PR before it is closed
+pub fn superseded_comment(renamed: &RenamedPr, new_pr: &PullRequest, platform: Platform) -> String {
+    format!(
+        "Superseded by {} - the bookmark `{}` was renamed to `{}`.",
+        pr_reference(platform, new_pr.number),
+        renamed.old_pr.head_ref,
+        renamed.bookmark
+    )
+}
@@ -0,0 +1,67 @@
+//! Machine-readable reports for `--format json`
+//!
+//! `submit` and `sync` print one of these as a single JSON document on
+//! stdout. Fatal errors aren't reported here: the command exits non-zero
+//! with the error on stderr.
+
+use crate::submit::{PlatformResult, ProgressEvent, StackDrift, SubmissionPlan, SubmissionResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Output of `ryu submit --format json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SubmitReport {
+    /// Bookmark the submission was for
+    pub bookmark: String,
+    /// Whether this was a dry run (nothing was changed)
+    pub dry_run: bool,
+    /// One plan per platform, the primary remote's first
+    pub plans: Vec<SubmissionPlan>,
+    /// One result per plan; empty if the submission was aborted
+    pub results: Vec<PlatformResult>,
+    /// Progress updates, in order
+    pub events: Vec<ProgressEvent>,
+}
+
+/// Output of `ryu sync --format json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SyncReport {
+    /// Whether this was a dry run (nothing was changed)
+    pub dry_run: bool,
+    /// Stacks considered, in sync order
+    pub stacks: Vec<SyncStackReport>,
+    /// Stacks skipped, with the reason
+    pub skipped: Vec<SkippedStack>,
+    /// Bookmarks rebased onto the updated trunk before syncing
+    pub restacked: Vec<String>,
+    /// Bookmarks that took in commits someone else pushed to their branch
+    #[serde(default)]
+    pub pulled: Vec<String>,
+}
+
+/// One stack of a [`SyncReport`]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SyncStackReport {
+    /// Stable stack ID, as in the change graph
+    pub stack_id: String,
+    /// Leaf bookmark identifying the stack
+    pub bookmark: String,
+    /// What differs from the remote before syncing
+    pub drift: StackDrift,
+    /// Plan for the stack
+    pub plan: SubmissionPlan,
+    /// Outcome; absent on a dry run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<SubmissionResult>,
+    /// Progress updates for this stack, in order
+    pub events: Vec<ProgressEvent>,
+}
+
+/// A stack `sync` left alone
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SkippedStack {
+    /// Leaf bookmark identifying the stack
+    pub bookmark: String,
+    /// Why it was skipped
+    pub reason: String,
+}
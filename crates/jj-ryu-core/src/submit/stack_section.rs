@@ -0,0 +1,109 @@
+//! Stack navigation inside PR bodies
+//!
+//! With `stack-info = "body"` the stack list that would go in a stack
+//! comment is kept in a marker-delimited section at the end of each PR body
+//! instead, hidden stack data included, so nothing is posted as a comment.
+//! Submit rewrites the section as the stack changes, leaving the rest of the
+//! body alone.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::submit::execute::{StackCommentData, format_stack_comment, parse_stack_comment_data};
+
+/// Start of the stack section in a PR body
+pub const STACK_SECTION_START: &str = "<!--- JJ-RYU_STACK_SECTION --->";
+/// End of the stack section in a PR body
+pub const STACK_SECTION_END: &str = "<!--- /JJ-RYU_STACK_SECTION --->";
+
+/// Render the stack section for the PR at `current_idx` of `data`
+pub fn render_stack_section(data: &StackCommentData, current_idx: usize) -> Result<String> {
+    let list = format_stack_comment(data, current_idx)?;
+    Ok(format!(
+        "{STACK_SECTION_START}\n{}\n{STACK_SECTION_END}",
+        list.trim_end()
+    ))
+}
+
+/// Byte range of the stack section in a PR body, markers included
+fn section_range(body: &str) -> Option<(usize, usize)> {
+    body.find(STACK_SECTION_START).and_then(|start| {
+        body[start..]
+            .find(STACK_SECTION_END)
+            .map(|end| (start, start + end + STACK_SECTION_END.len()))
+    })
+}
+
+/// The stack section of a PR body, if it has one
+pub fn stack_section(body: &str) -> Option<&str> {
+    section_range(body).map(|(start, end)| &body[start..end])
+}
+
+/// Replace (or add, or with an empty `section` remove) the stack section in
+/// a PR body
+///
+/// Text outside the markers is preserved; a new section goes at the end.
+pub fn apply_stack_section(body: &str, section: &str) -> String {
+    let (before, after) = match section_range(body) {
+        Some((start, end)) => (body[..start].trim_end(), body[end..].trim_start()),
+        None if section.is_empty() => return body.to_string(),
+        None => (body.trim_end(), ""),
+    };
+    [before, section, after]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Rewrite the stack section of a PR body, if it changed
+///
+/// A tracking issue linked from the existing section stays linked even when
+/// this submission didn't touch the issue.
+pub async fn update_stack_section(
+    platform: &dyn PlatformService,
+    data: &StackCommentData,
+    current_idx: usize,
+    pr_number: u64,
+) -> Result<()> {
+    let current = platform.get_pr_body(pr_number).await?.replace("\r\n", "\n");
+    let linked_issue = stack_section(&current)
+        .and_then(parse_stack_comment_data)
+        .and_then(|d| d.tracking_issue);
+    let section = match (data.tracking_issue, linked_issue) {
+        (None, Some(issue)) => {
+            let data = StackCommentData {
+                tracking_issue: Some(issue),
+                ..data.clone()
+            };
+            render_stack_section(&data, current_idx)?
+        }
+        _ => render_stack_section(data, current_idx)?,
+    };
+    let updated = apply_stack_section(&current, &section);
+    if updated != current {
+        platform.update_pr_body(pr_number, &updated).await?;
+    }
+    Ok(())
+}
+
+/// Stack data recorded on a PR, from its stack comments or, failing
+/// those, the stack section of its body
+pub async fn find_stack_data(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+) -> Result<Vec<StackCommentData>> {
+    let found: Vec<StackCommentData> = platform
+        .list_pr_comments(pr_number)
+        .await?
+        .iter()
+        .filter_map(|c| parse_stack_comment_data(&c.body))
+        .collect();
+    if !found.is_empty() {
+        return Ok(found);
+    }
+    let body = platform.get_pr_body(pr_number).await?;
+    Ok(stack_section(&body)
+        .and_then(parse_stack_comment_data)
+        .into_iter()
+        .collect())
+}
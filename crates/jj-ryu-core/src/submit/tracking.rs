@@ -0,0 +1,139 @@
+//! Umbrella tracking issues for stacks
+//!
+//! Some teams follow a multi-PR feature from a single place. With tracking
+//! issues enabled, submit opens an issue listing every PR in the stack as a
+//! task list and links it from each stack comment. The issue number travels
+//! in the stack comment data, so later submits update the same issue. PRs
+//! that leave the stack (usually because they merged) stay listed, checked.
+
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::submit::execute::StackCommentData;
+use crate::submit::stack_section::find_stack_data;
+use crate::types::Platform;
+use std::fmt::Write;
+
+/// Environment variable enabling tracking issues for every submit and sync
+pub const TRACKING_ISSUE_ENV: &str = "RYU_TRACKING_ISSUE";
+
+/// Marker identifying a tracking issue body written by ryu
+pub const TRACKING_ISSUE_MARKER: &str = "<!--- JJ-RYU_TRACKING --->";
+
+/// Whether tracking issues are enabled (`tracking-issue`, default false)
+///
+/// `RYU_TRACKING_ISSUE` wins over the config files.
+pub fn tracking_issue_enabled(config: &RyuConfig) -> Result<bool> {
+    let Ok(value) = std::env::var(TRACKING_ISSUE_ENV) else {
+        return Ok(config.tracking_issue.unwrap_or(false));
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "" | "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(Error::Config(format!(
+            "{TRACKING_ISSUE_ENV} must be true or false, got '{value}'"
+        ))),
+    }
+}
+
+/// How a PR is referenced in markdown (`#12` on GitHub, `!12` on GitLab)
+pub fn pr_reference(platform: Platform, pr_number: u64) -> String {
+    match platform {
+        Platform::GitHub | Platform::Bitbucket | Platform::Gitea | Platform::Fake => {
+            format!("#{pr_number}")
+        }
+        Platform::GitLab | Platform::AzureDevOps => format!("!{pr_number}"),
+    }
+}
+
+/// Title of a new tracking issue, after the stack's leaf PR
+pub fn tracking_issue_title(leaf_title: &str) -> String {
+    format!("Stack: {leaf_title}")
+}
+
+/// A task list line: (checked, PR number)
+fn parse_task_line(line: &str) -> Option<(bool, u64)> {
+    let rest = line.trim().strip_prefix("- [")?;
+    let (mark, rest) = rest.split_once("] ")?;
+    let checked = match mark {
+        " " => false,
+        "x" | "X" => true,
+        _ => return None,
+    };
+    let number = rest.trim().strip_prefix(['#', '!'])?;
+    Some((checked, number.parse().ok()?))
+}
+
+/// Render a tracking issue body for `data`, bottom of the stack first
+///
+/// Tasks already in `previous` keep their checked state; PRs no longer in the
+/// stack are kept, checked, above the current ones.
+pub fn render_tracking_issue(
+    previous: &str,
+    data: &StackCommentData,
+    platform: Platform,
+) -> String {
+    let previous: Vec<(bool, u64)> = previous.lines().filter_map(parse_task_line).collect();
+    let in_stack = |number: u64| data.stack.iter().any(|item| item.pr_number == number);
+
+    let mut body = format!("{TRACKING_ISSUE_MARKER}\nPRs in this stack, bottom first:\n\n");
+    for &(_, number) in previous.iter().filter(|(_, n)| !in_stack(*n)) {
+        let _ = writeln!(body, "- [x] {}", pr_reference(platform, number));
+    }
+    for item in &data.stack {
+        let checked = previous
+            .iter()
+            .any(|&(checked, n)| checked && n == item.pr_number);
+        let mark = if checked { 'x' } else { ' ' };
+        let _ = writeln!(
+            body,
+            "- [{mark}] {}",
+            pr_reference(platform, item.pr_number)
+        );
+    }
+    let _ = write!(
+        body,
+        "\n---\nThis issue is managed by [jj-ryu](https://github.com/dmmulroy/jj-ryu)."
+    );
+    body
+}
+
+/// Tracking issue recorded in the stack data of any PR in `data`
+pub async fn find_tracking_issue(
+    platform: &dyn PlatformService,
+    data: &StackCommentData,
+) -> Result<Option<u64>> {
+    for item in &data.stack {
+        let issue = find_stack_data(platform, item.pr_number)
+            .await?
+            .into_iter()
+            .find_map(|d| d.tracking_issue);
+        if issue.is_some() {
+            return Ok(issue);
+        }
+    }
+    Ok(None)
+}
+
+/// Create or update the tracking issue for a stack, returning its number
+///
+/// `comment_platform` is where existing stack comments are read from.
+pub async fn sync_tracking_issue(
+    platform: &dyn PlatformService,
+    comment_platform: &dyn PlatformService,
+    data: &StackCommentData,
+    title: &str,
+) -> Result<u64> {
+    let kind = platform.config().platform;
+    if let Some(number) = find_tracking_issue(comment_platform, data).await? {
+        let current = platform.get_issue_body(number).await?.replace("\r\n", "\n");
+        let updated = render_tracking_issue(&current, data, kind);
+        if updated.trim_end() != current.trim_end() {
+            platform.update_issue(number, &updated).await?;
+        }
+        return Ok(number);
+    }
+
+    let body = render_tracking_issue("", data, kind);
+    Ok(platform.create_issue(title, &body).await?.number)
+}
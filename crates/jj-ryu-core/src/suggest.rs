@@ -0,0 +1,179 @@
+//! Restructuring hints for stacks
+//!
+//! A stack orders segments as if each built on the ones below, but often a
+//! segment only needs some of them, or none. `ryu suggest-base` compares the
+//! files each segment changes and points out segments that could go to
+//! review on their own, sit lower in the stack, or be split. File overlap is
+//! only a hint: a segment can depend on code it doesn't touch, so these are
+//! suggestions to check, not changes ryu makes.
+
+use crate::error::Result;
+use crate::repo::JjWorkspace;
+use crate::types::BranchStack;
+use std::collections::BTreeSet;
+
+/// Files changed by one change of a segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeFiles {
+    /// Change ID
+    pub change_id: String,
+    /// Repo-relative paths the change touches
+    pub paths: BTreeSet<String>,
+}
+
+/// Files changed by a segment, change by change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentFiles {
+    /// The segment's bookmark
+    pub bookmark: String,
+    /// Its changes, oldest first
+    pub changes: Vec<ChangeFiles>,
+}
+
+impl SegmentFiles {
+    /// Every path the segment touches
+    pub fn paths(&self) -> BTreeSet<&str> {
+        self.changes
+            .iter()
+            .flat_map(|change| &change.paths)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// A suggested restructuring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Suggestion {
+    /// The segment touches none of the files of the segments below it, so
+    /// it could be a stack of its own on trunk
+    Parallel {
+        /// The segment's bookmark
+        bookmark: String,
+        /// Segments below it, bottom first
+        below: Vec<String>,
+    },
+    /// The segment shares files with a lower segment but not with the ones
+    /// in between, so it could be based on that segment instead
+    LowerBase {
+        /// The segment's bookmark
+        bookmark: String,
+        /// Highest segment below it that it shares files with
+        base: String,
+        /// Segments it could skip, bottom first
+        skipped: Vec<String>,
+    },
+    /// The segment's changes fall into groups touching unrelated files
+    Split {
+        /// The segment's bookmark
+        bookmark: String,
+        /// Change IDs per group, in the order their first change appears
+        groups: Vec<Vec<String>>,
+    },
+}
+
+/// Files changed by each segment of `stack`, bottom first
+pub fn stack_files(workspace: &JjWorkspace, stack: &BranchStack) -> Result<Vec<SegmentFiles>> {
+    let mut segments = Vec::with_capacity(stack.segments.len());
+    for segment in &stack.segments {
+        let Some(bookmark) = segment.bookmarks.first() else {
+            continue;
+        };
+        let mut changes = Vec::with_capacity(segment.changes.len());
+        // Segment changes are newest first
+        for change in segment.changes.iter().rev() {
+            changes.push(ChangeFiles {
+                change_id: change.change_id.clone(),
+                paths: workspace
+                    .changed_paths(&change.commit_id)?
+                    .into_iter()
+                    .collect(),
+            });
+        }
+        segments.push(SegmentFiles {
+            bookmark: bookmark.name.clone(),
+            changes,
+        });
+    }
+    Ok(segments)
+}
+
+/// Suggest restructurings for a stack's segments (bottom first)
+///
+/// Segments that touch no files are left out, since overlap says nothing
+/// about them.
+pub fn suggest_restructure(segments: &[SegmentFiles]) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let paths = segment.paths();
+        if paths.is_empty() {
+            continue;
+        }
+
+        let below = &segments[..index];
+        let overlapping = below
+            .iter()
+            .rposition(|lower| lower.paths().iter().any(|path| paths.contains(path)));
+        match overlapping {
+            None if !below.is_empty() => suggestions.push(Suggestion::Parallel {
+                bookmark: segment.bookmark.clone(),
+                below: below.iter().map(|s| s.bookmark.clone()).collect(),
+            }),
+            Some(base) if base + 1 < index => suggestions.push(Suggestion::LowerBase {
+                bookmark: segment.bookmark.clone(),
+                base: below[base].bookmark.clone(),
+                skipped: below[base + 1..]
+                    .iter()
+                    .map(|s| s.bookmark.clone())
+                    .collect(),
+            }),
+            _ => {}
+        }
+
+        let groups = disjoint_groups(&segment.changes);
+        if groups.len() > 1 {
+            suggestions.push(Suggestion::Split {
+                bookmark: segment.bookmark.clone(),
+                groups,
+            });
+        }
+    }
+    suggestions
+}
+
+/// Group changes that (transitively) share files; changes touching no files
+/// join the group of the change before them
+fn disjoint_groups(changes: &[ChangeFiles]) -> Vec<Vec<String>> {
+    // Group of each change, by index; merging relabels the later group
+    let mut group_of: Vec<usize> = (0..changes.len()).collect();
+    for (index, change) in changes.iter().enumerate() {
+        if change.paths.is_empty() {
+            if index > 0 {
+                group_of[index] = group_of[index - 1];
+            }
+            continue;
+        }
+        for (earlier, other) in changes[..index].iter().enumerate() {
+            if other.paths.is_disjoint(&change.paths) {
+                continue;
+            }
+            let (keep, drop) = (
+                group_of[earlier].min(group_of[index]),
+                group_of[earlier].max(group_of[index]),
+            );
+            for group in &mut group_of {
+                if *group == drop {
+                    *group = keep;
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<(usize, Vec<String>)> = Vec::new();
+    for (change, group) in changes.iter().zip(group_of) {
+        match groups.iter_mut().find(|(id, _)| *id == group) {
+            Some((_, ids)) => ids.push(change.change_id.clone()),
+            None => groups.push((group, vec![change.change_id.clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, ids)| ids).collect()
+}
@@ -0,0 +1,269 @@
+//! Opt-in usage metrics, kept locally
+//!
+//! Off unless enabled with `telemetry = true` in the user config file or
+//! `RYU_TELEMETRY=1`. A repository's `.ryu.toml` can't turn it on. When on,
+//! each command appends one [`UsageEvent`] to `usage.jsonl` in ryu's state
+//! directory: the command name, how long it took, the largest stack it
+//! handled and the category of the error it failed with. Nothing names the
+//! repository, its bookmarks or its remotes.
+//!
+//! `ryu stats --tool` summarizes the file; the summary is uploaded only when
+//! asked to, and only to `telemetry-upload-url`.
+
+use crate::config::RyuConfig;
+use crate::error::{Error, Result};
+use crate::platform::DEFAULT_TIMEOUT_SECS;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+/// Environment variable turning telemetry on or off, overriding the config
+pub const TELEMETRY_ENV: &str = "RYU_TELEMETRY";
+
+/// Environment variable overriding `telemetry-upload-url`
+pub const TELEMETRY_URL_ENV: &str = "RYU_TELEMETRY_URL";
+
+/// Usage file name, in ryu's state directory
+const USAGE_FILE: &str = "usage.jsonl";
+
+/// Events kept; older ones are dropped when the file grows past this
+const MAX_EVENTS: usize = 5000;
+
+/// Whether telemetry is on and where summaries may be uploaded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelemetrySettings {
+    /// Record usage events
+    pub enabled: bool,
+    /// Endpoint `ryu stats --tool --upload` posts the summary to
+    pub upload_url: Option<String>,
+}
+
+impl TelemetrySettings {
+    /// Read the settings from the environment and the user config file
+    pub fn load() -> Result<Self> {
+        let config = match RyuConfig::user_path() {
+            Some(path) => RyuConfig::from_file(&path)?.unwrap_or_default(),
+            None => RyuConfig::default(),
+        };
+        Self::resolve(
+            std::env::var(TELEMETRY_ENV).ok().as_deref(),
+            std::env::var(TELEMETRY_URL_ENV).ok(),
+            &config,
+        )
+    }
+
+    /// Combine an environment value with the user config
+    pub fn resolve(env: Option<&str>, env_url: Option<String>, config: &RyuConfig) -> Result<Self> {
+        let enabled = match env.map(|value| value.trim().to_ascii_lowercase()) {
+            None => config.telemetry.unwrap_or(false),
+            Some(value) => match value.as_str() {
+                "1" | "true" | "on" => true,
+                "0" | "false" | "off" | "" => false,
+                _ => {
+                    return Err(Error::Config(format!(
+                        "{TELEMETRY_ENV} must be true or false, got '{value}'"
+                    )));
+                }
+            },
+        };
+        Ok(Self {
+            enabled,
+            upload_url: env_url.or_else(|| config.telemetry_upload_url.clone()),
+        })
+    }
+}
+
+/// One command run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// When the command finished
+    pub at: DateTime<Utc>,
+    /// ryu version
+    pub version: String,
+    /// Subcommand, e.g. `submit` (`analyze` when none was given)
+    pub command: String,
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+    /// Segments in the largest stack the command handled, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_size: Option<usize>,
+    /// [`Error::category`] of the failure, if the command failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl UsageEvent {
+    /// Event for a command that just finished
+    pub fn new(
+        command: &str,
+        duration: Duration,
+        stack_size: Option<usize>,
+        error: Option<&str>,
+    ) -> Self {
+        Self {
+            at: Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            command: command.to_string(),
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            stack_size,
+            error: error.map(ToString::to_string),
+        }
+    }
+}
+
+/// Path of the usage file (`$XDG_STATE_HOME/ryu/usage.jsonl`, defaulting to
+/// `~/.local/state/ryu/usage.jsonl`)
+pub fn usage_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("state")))
+        .map(|dir| dir.join("ryu").join(USAGE_FILE))
+}
+
+/// Read every event from a usage file, skipping lines that don't parse
+pub fn load_events(path: &Path) -> Result<Vec<UsageEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append `event` to a usage file, dropping the oldest events past the cap
+pub fn record_event(path: &Path, event: &UsageEvent) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    drop(file);
+
+    let events = load_events(path)?;
+    if events.len() > MAX_EVENTS {
+        let mut kept = String::new();
+        for event in &events[events.len() - MAX_EVENTS..] {
+            kept.push_str(&serde_json::to_string(event)?);
+            kept.push('\n');
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        fs::write(&tmp, kept)?;
+        fs::rename(&tmp, path)?;
+    }
+    Ok(())
+}
+
+/// Drop events recorded before `cutoff` from a usage file, returning how
+/// many were (or, with `dry_run`, would be) dropped
+pub fn prune_events(path: &Path, cutoff: DateTime<Utc>, dry_run: bool) -> Result<usize> {
+    let events = load_events(path)?;
+    let kept: Vec<&UsageEvent> = events.iter().filter(|event| event.at >= cutoff).collect();
+    let dropped = events.len() - kept.len();
+    if dropped == 0 || dry_run {
+        return Ok(dropped);
+    }
+    let mut contents = String::new();
+    for event in kept {
+        contents.push_str(&serde_json::to_string(event)?);
+        contents.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(dropped)
+}
+
+/// Aggregated runs of one command
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommandStats {
+    /// Times the command ran
+    pub runs: usize,
+    /// Runs that failed
+    pub failures: usize,
+    /// Median duration in milliseconds
+    pub median_ms: u64,
+    /// 90th percentile duration in milliseconds
+    pub p90_ms: u64,
+    /// Largest stack handled, in segments
+    pub max_stack_size: Option<usize>,
+}
+
+/// Summary of a usage file; this is all an upload contains
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UsageSummary {
+    /// ryu version reporting
+    pub version: String,
+    /// Operating system and architecture
+    pub platform: String,
+    /// Stats per command
+    pub commands: BTreeMap<String, CommandStats>,
+    /// Failures per error category
+    pub errors: BTreeMap<String, usize>,
+}
+
+/// Summarize recorded events per command and error category
+pub fn summarize(events: &[UsageEvent]) -> UsageSummary {
+    let mut durations: BTreeMap<&str, Vec<u64>> = BTreeMap::new();
+    let mut summary = UsageSummary {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        ..UsageSummary::default()
+    };
+
+    for event in events {
+        let stats = summary.commands.entry(event.command.clone()).or_default();
+        stats.runs += 1;
+        stats.max_stack_size = stats.max_stack_size.max(event.stack_size);
+        if let Some(category) = &event.error {
+            stats.failures += 1;
+            *summary.errors.entry(category.clone()).or_default() += 1;
+        }
+        durations
+            .entry(&event.command)
+            .or_default()
+            .push(event.duration_ms);
+    }
+
+    for (command, mut times) in durations {
+        times.sort_unstable();
+        if let Some(stats) = summary.commands.get_mut(command) {
+            stats.median_ms = percentile(&times, 50);
+            stats.p90_ms = percentile(&times, 90);
+        }
+    }
+    summary
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Post a summary to `url`
+pub async fn upload_summary(summary: &UsageSummary, url: &str) -> Result<()> {
+    debug!(url, "uploading usage summary");
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| Error::Platform(format!("failed to create HTTP client: {e}")))?;
+
+    client
+        .post(url)
+        .json(summary)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| Error::Platform(format!("usage upload failed: {e}")))?;
+    Ok(())
+}
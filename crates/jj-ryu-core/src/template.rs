@@ -0,0 +1,105 @@
+//! Stack templates: scaffold a new stack from a named shape
+//!
+//! Recurring kinds of change tend to split the same way (schema, then
+//! implementation, then docs). A template in the config files lists those
+//! segments; `ryu new-stack <name> --template <template>` creates one empty,
+//! described change per segment on trunk, each with its own bookmark, ready
+//! to be filled in.
+//!
+//! ```toml
+//! [templates.api-change]
+//! segments = [
+//!     { bookmark = "schema", description = "Add {name} schema" },
+//!     { bookmark = "implementation", description = "Implement {name}" },
+//!     { bookmark = "docs", description = "Document {name}" },
+//! ]
+//! ```
+
+use crate::error::{Error, Result};
+use crate::repo::JjWorkspace;
+use serde::Deserialize;
+
+/// A stack shape from the config files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StackTemplate {
+    /// Segments from trunk to leaf
+    pub segments: Vec<TemplateSegment>,
+}
+
+/// One segment of a [`StackTemplate`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateSegment {
+    /// Bookmark suffix: the bookmark is `<name>/<bookmark>`
+    pub bookmark: String,
+    /// Change description; `{name}` is replaced with the stack's name
+    /// (default: the bookmark suffix)
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A change to create for a new stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldChange {
+    /// Bookmark to put on the change
+    pub bookmark: String,
+    /// Description of the change
+    pub description: String,
+}
+
+impl StackTemplate {
+    /// The changes a stack called `name` gets, trunk first
+    ///
+    /// `prefix` (`branch-prefix`) is put in front of every bookmark so the
+    /// new stack isn't filtered out of the graph.
+    pub fn scaffold(&self, name: &str, prefix: Option<&str>) -> Result<Vec<ScaffoldChange>> {
+        if self.segments.is_empty() {
+            return Err(Error::Config("stack template has no segments".to_string()));
+        }
+        let prefix = prefix
+            .filter(|prefix| !name.starts_with(prefix))
+            .unwrap_or("");
+        Ok(self
+            .segments
+            .iter()
+            .map(|segment| ScaffoldChange {
+                bookmark: format!("{prefix}{name}/{}", segment.bookmark),
+                description: segment
+                    .description
+                    .as_deref()
+                    .unwrap_or(&segment.bookmark)
+                    .replace("{name}", name),
+            })
+            .collect())
+    }
+}
+
+/// Create the scaffolded changes on `onto`, each on the previous one
+///
+/// Fails before creating anything if one of the bookmarks already exists.
+/// Returns the commit ID of each change, in order.
+pub fn create_stack(
+    workspace: &mut JjWorkspace,
+    changes: &[ScaffoldChange],
+    onto: &str,
+) -> Result<Vec<String>> {
+    for change in changes {
+        if workspace.get_local_bookmark(&change.bookmark)?.is_some() {
+            return Err(Error::InvalidArgument(format!(
+                "bookmark '{}' already exists",
+                change.bookmark
+            )));
+        }
+    }
+
+    let mut parent = onto.to_string();
+    let mut created = Vec::with_capacity(changes.len());
+    for change in changes {
+        let commit_id = workspace.new_change(&parent, &change.description)?;
+        workspace.create_bookmark(&change.bookmark, &commit_id)?;
+        parent.clone_from(&commit_id);
+        created.push(commit_id);
+    }
+    Ok(created)
+}
@@ -0,0 +1,205 @@
+//! UI state and key handling
+
+use crate::status::StackStatus;
+use crate::submit::ProgressEvent;
+use ratatui::crossterm::event::KeyCode;
+
+/// Log lines kept for the log pane
+pub const LOG_LIMIT: usize = 200;
+
+/// Something the user asked the UI to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Submit the stack up to this bookmark (`ryu submit <bookmark>`)
+    Submit(String),
+    /// Fetch, restack and sync the stack with this leaf bookmark
+    Sync(String),
+    /// Re-read the stacks and their PRs
+    Refresh,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Submit(bookmark) => write!(f, "Submitting {bookmark}"),
+            Self::Sync(leaf) => write!(f, "Syncing {leaf}"),
+            Self::Refresh => write!(f, "Refreshing"),
+        }
+    }
+}
+
+/// What a key press asks the event loop to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Leave the UI
+    Quit,
+    /// Run an action
+    Run(Action),
+}
+
+/// Everything the screen shows
+pub struct App {
+    /// Stacks with their PR state, as last loaded
+    pub stacks: Vec<StackStatus>,
+    /// Index of the selected stack
+    pub stack: usize,
+    /// Index of the selected segment in the stack (trunk first)
+    pub segment: usize,
+    /// Progress and results, oldest first
+    pub log: Vec<String>,
+    /// The action running, if any
+    pub running: Option<Action>,
+}
+
+impl App {
+    /// Start on the first stack's leaf
+    pub fn new(stacks: Vec<StackStatus>) -> Self {
+        let mut app = Self {
+            stacks: Vec::new(),
+            stack: 0,
+            segment: 0,
+            log: Vec::new(),
+            running: None,
+        };
+        app.set_stacks(stacks);
+        app
+    }
+
+    /// Replace the stacks, keeping the selection on the same leaf if it's still there
+    pub fn set_stacks(&mut self, stacks: Vec<StackStatus>) {
+        let leaf = self.selected_stack().map(|stack| stack.leaf.clone());
+        self.stacks = stacks;
+        match leaf.and_then(|leaf| self.stacks.iter().position(|s| s.leaf == leaf)) {
+            Some(index) => {
+                self.stack = index;
+                self.segment = self.segment.min(self.segment_count().saturating_sub(1));
+            }
+            None => {
+                self.stack = 0;
+                self.select_leaf();
+            }
+        }
+    }
+
+    /// The selected stack
+    pub fn selected_stack(&self) -> Option<&StackStatus> {
+        self.stacks.get(self.stack)
+    }
+
+    /// Bookmark of the selected segment
+    pub fn selected_bookmark(&self) -> Option<&str> {
+        self.selected_stack()
+            .and_then(|stack| stack.bookmarks.get(self.segment))
+            .map(|status| status.segment.bookmark.name.as_str())
+    }
+
+    /// Map a key press to a command, moving the selection on the way
+    ///
+    /// Keys other than quit are ignored while an action runs.
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<Command> {
+        if matches!(key, KeyCode::Char('q') | KeyCode::Esc) {
+            return Some(Command::Quit);
+        }
+        if self.running.is_some() {
+            return None;
+        }
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.segment = (self.segment + 1).min(self.segment_count().saturating_sub(1));
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.segment = self.segment.saturating_sub(1),
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                self.stack = self
+                    .stack
+                    .checked_sub(1)
+                    .unwrap_or(self.stacks.len().saturating_sub(1));
+                self.select_leaf();
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+                self.stack = if self.stack + 1 < self.stacks.len() {
+                    self.stack + 1
+                } else {
+                    0
+                };
+                self.select_leaf();
+            }
+            KeyCode::Char('s') => {
+                return self
+                    .selected_bookmark()
+                    .map(|bookmark| Command::Run(Action::Submit(bookmark.to_string())));
+            }
+            KeyCode::Char('y') => {
+                return self
+                    .selected_stack()
+                    .map(|stack| Command::Run(Action::Sync(stack.leaf.clone())));
+            }
+            KeyCode::Char('r') => return Some(Command::Run(Action::Refresh)),
+            _ => {}
+        }
+        None
+    }
+
+    /// Mark `action` as running
+    pub fn start(&mut self, action: &Action) {
+        self.push_log(format!("{action}..."));
+        self.running = Some(action.clone());
+    }
+
+    /// Log a progress event
+    pub fn push_event(&mut self, event: &ProgressEvent) {
+        self.push_log(format!("  {}", describe(event)));
+    }
+
+    /// Mark the running action as done, logging its outcome
+    pub fn finish(&mut self, outcome: Result<String, String>) {
+        self.running = None;
+        match outcome {
+            Ok(summary) => self.push_log(format!("✓ {summary}")),
+            Err(error) => self.push_log(format!("✗ {error}")),
+        }
+    }
+
+    /// Append a line to the log
+    pub fn push_log(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > LOG_LIMIT {
+            self.log.drain(..self.log.len() - LOG_LIMIT);
+        }
+    }
+
+    fn segment_count(&self) -> usize {
+        self.selected_stack()
+            .map_or(0, |stack| stack.bookmarks.len())
+    }
+
+    /// Select the leaf segment of the selected stack
+    fn select_leaf(&mut self) {
+        self.segment = self.segment_count().saturating_sub(1);
+    }
+}
+
+/// One log line for an event
+fn describe(event: &ProgressEvent) -> String {
+    match event {
+        ProgressEvent::Phase { phase } => format!("{phase}..."),
+        ProgressEvent::SegmentRestacked { bookmark } => format!("Restacked {bookmark}"),
+        ProgressEvent::BookmarkPush { bookmark, status } => format!("Push {bookmark}: {status}"),
+        ProgressEvent::MirrorPush {
+            bookmark,
+            remote,
+            status,
+        } => format!("Mirror {bookmark} to {remote}: {status}"),
+        ProgressEvent::PrCreated { bookmark, pr } => {
+            format!("Created #{} for {bookmark}: {}", pr.number, pr.html_url)
+        }
+        ProgressEvent::PrUpdated { bookmark, pr } => {
+            format!("Updated #{} for {bookmark}", pr.number)
+        }
+        ProgressEvent::PrMerged { bookmark, pr } => {
+            format!("Merged #{} for {bookmark}", pr.number)
+        }
+        ProgressEvent::Error { message } => format!("Error: {message}"),
+        ProgressEvent::Warning { warning } => format!("Warning: {warning}"),
+        ProgressEvent::Message { message } => message.clone(),
+    }
+}
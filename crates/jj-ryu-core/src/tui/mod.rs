@@ -0,0 +1,11 @@
+//! State and drawing for the interactive terminal UI (`ryu ui`)
+//!
+//! [`App`] turns key presses into [`Command`]s and keeps the log of what ran;
+//! [`draw`] renders it to a frame. The terminal, the event loop and the
+//! workspace the actions run on belong to the frontend.
+
+mod app;
+mod view;
+
+pub use app::{Action, App, Command, LOG_LIMIT};
+pub use view::{KEYS, draw};
@@ -0,0 +1,178 @@
+//! Drawing the screen
+
+use super::app::App;
+use crate::status::BookmarkStatus;
+use crate::types::{ChecksState, PrState, ReviewState};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState};
+
+/// Key help shown in the footer while nothing runs
+pub const KEYS: &str =
+    "j/k segment  h/l stack  s submit to segment  y sync stack  r refresh  q quit";
+
+/// Draw the whole screen
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, log, footer] = Layout::vertical([
+        Constraint::Min(8),
+        Constraint::Length(10),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [stacks, segments] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(main);
+
+    draw_stacks(frame, app, stacks);
+    draw_segments(frame, app, segments);
+    draw_log(frame, app, log);
+
+    let status = app.running.as_ref().map_or_else(
+        || Span::styled(KEYS, Style::new().fg(Color::DarkGray)),
+        |action| Span::styled(format!("{action}..."), Style::new().fg(Color::Yellow)),
+    );
+    frame.render_widget(Paragraph::new(Line::from(status)), footer);
+}
+
+fn draw_stacks(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .stacks
+        .iter()
+        .map(|stack| {
+            let attention = stack.bookmarks.iter().any(needs_attention);
+            let marker = if attention { " !" } else { "" };
+            ListItem::new(format!("{}{marker}", stack.leaf))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" Stacks ({}) ", app.stacks.len())))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.stack));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Segments of the selected stack, leaf on top like `jj log`
+fn draw_segments(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::bordered().title(" Segments ");
+    let Some(stack) = app.selected_stack() else {
+        let empty =
+            Paragraph::new("No stacks: create a bookmark on a change above trunk").block(block);
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let rows: Vec<Row> = stack.bookmarks.iter().rev().map(segment_row).collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(8),
+            Constraint::Length(17),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new([
+            "Bookmark", "Changes", "PR", "State", "Checks", "Review", "Base",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let selected = stack.bookmarks.len().saturating_sub(1 + app.segment);
+    let mut state = TableState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn segment_row(status: &BookmarkStatus) -> Row<'_> {
+    let (number, state) = status.pr.as_ref().map_or_else(
+        || (Cell::from("-"), Cell::from("")),
+        |(pr, state)| {
+            (
+                Cell::from(format!("#{}", pr.number)),
+                Cell::from(state.to_string()).style(Style::new().fg(pr_color(*state))),
+            )
+        },
+    );
+    let checks = status.checks.map_or_else(
+        || Cell::from(""),
+        |checks| Cell::from(checks.to_string()).style(Style::new().fg(checks_color(checks))),
+    );
+    let review = status.review.map_or_else(
+        || Cell::from(""),
+        |review| Cell::from(review.to_string()).style(Style::new().fg(review_color(review))),
+    );
+    let base = if status.base_mismatch() {
+        let actual = status
+            .pr
+            .as_ref()
+            .map_or("", |(pr, _)| pr.base_ref.as_str());
+        Cell::from(format!("{actual} → {}", status.expected_base))
+            .style(Style::new().fg(Color::Red))
+    } else {
+        Cell::from(status.expected_base.clone()).style(Style::new().fg(Color::DarkGray))
+    };
+
+    Row::new([
+        Cell::from(status.segment.bookmark.name.clone()),
+        Cell::from(status.segment.changes.len().to_string()),
+        number,
+        state,
+        checks,
+        review,
+        base,
+    ])
+}
+
+fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
+    // Show the newest lines that fit inside the border
+    let height = usize::from(area.height.saturating_sub(2));
+    let start = app.log.len().saturating_sub(height);
+    let lines: Vec<Line> = app.log[start..]
+        .iter()
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Log ")),
+        area,
+    );
+}
+
+/// Whether a bookmark has no PR yet, failing checks, requested changes or a
+/// misplaced base
+fn needs_attention(status: &BookmarkStatus) -> bool {
+    status.pr.is_none()
+        || status.checks == Some(ChecksState::Failing)
+        || status.review == Some(ReviewState::ChangesRequested)
+        || status.base_mismatch()
+}
+
+const fn pr_color(state: PrState) -> Color {
+    match state {
+        PrState::Open => Color::Green,
+        PrState::Draft => Color::Gray,
+        PrState::Merged => Color::Magenta,
+        PrState::Closed => Color::Red,
+    }
+}
+
+const fn checks_color(checks: ChecksState) -> Color {
+    match checks {
+        ChecksState::None => Color::DarkGray,
+        ChecksState::Pending => Color::Yellow,
+        ChecksState::Passing => Color::Green,
+        ChecksState::Failing => Color::Red,
+    }
+}
+
+const fn review_color(review: ReviewState) -> Color {
+    match review {
+        ReviewState::Pending => Color::DarkGray,
+        ReviewState::Approved => Color::Green,
+        ReviewState::ChangesRequested => Color::Red,
+    }
+}
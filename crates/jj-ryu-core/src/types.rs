@@ -0,0 +1,586 @@
+//! Core types for jj-ryu
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A jj bookmark (branch reference)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct Bookmark {
+    /// Bookmark name
+    pub name: String,
+    /// Git commit ID (hex)
+    pub commit_id: String,
+    /// jj change ID (hex)
+    pub change_id: String,
+    /// Whether this bookmark exists on any remote
+    pub has_remote: bool,
+    /// Whether local and remote are in sync
+    pub is_synced: bool,
+}
+
+/// A commit/change entry from jj log
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LogEntry {
+    /// Git commit ID (hex)
+    pub commit_id: String,
+    /// jj change ID (hex)
+    pub change_id: String,
+    /// Author name
+    pub author_name: String,
+    /// Author email
+    pub author_email: String,
+    /// First line of commit description
+    pub description_first_line: String,
+    /// Full commit description
+    #[serde(default)]
+    pub description: String,
+    /// Parent commit IDs
+    pub parents: Vec<String>,
+    /// Local bookmarks pointing to this commit
+    pub local_bookmarks: Vec<String>,
+    /// Remote bookmarks pointing to this commit (format: "name@remote")
+    pub remote_bookmarks: Vec<String>,
+    /// Whether this is the working copy commit
+    pub is_working_copy: bool,
+    /// When the commit was authored
+    pub authored_at: DateTime<Utc>,
+    /// When the commit was committed
+    pub committed_at: DateTime<Utc>,
+}
+
+/// A segment of changes belonging to one or more bookmarks
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BookmarkSegment {
+    /// Stable ID: the change ID of the segment's tip, which survives rewrites
+    /// and reordering, so tools can match segments across runs
+    pub id: String,
+    /// Bookmarks pointing to the tip of this segment
+    pub bookmarks: Vec<Bookmark>,
+    /// Changes in this segment (newest first)
+    pub changes: Vec<LogEntry>,
+}
+
+/// A segment narrowed to a single bookmark (after user selection)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NarrowedBookmarkSegment {
+    /// The selected bookmark for this segment
+    pub bookmark: Bookmark,
+    /// Changes in this segment (newest first)
+    pub changes: Vec<LogEntry>,
+}
+
+/// A stack of bookmarks from trunk to a leaf
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BranchStack {
+    /// Stable ID: the ID of the leaf segment. Stack order in a graph isn't
+    /// meaningful; match stacks across runs by this instead
+    pub id: String,
+    /// Segments from trunk (index 0) to leaf (last index)
+    pub segments: Vec<BookmarkSegment>,
+}
+
+/// The complete change graph for a repository
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ChangeGraph {
+    /// All bookmarks by name
+    pub bookmarks: HashMap<String, Bookmark>,
+    /// Map from bookmark name to change ID
+    pub bookmark_to_change_id: HashMap<String, String>,
+    /// Adjacency list: child change ID -> parent change ID (for stacking)
+    pub bookmarked_change_adjacency_list: HashMap<String, String>,
+    /// Map from bookmarked change ID to the commits in that segment
+    pub bookmarked_change_id_to_segment: HashMap<String, Vec<LogEntry>>,
+    /// Change IDs that are leaf nodes (no children)
+    pub stack_leafs: HashSet<String>,
+    /// Change IDs that are root nodes (directly on trunk)
+    pub stack_roots: HashSet<String>,
+    /// All detected stacks
+    pub stacks: Vec<BranchStack>,
+    /// Bookmarks excluded from stack detection, with the reason for each
+    pub excluded_bookmarks: Vec<ExcludedBookmark>,
+}
+
+impl ChangeGraph {
+    /// Bookmarked change IDs stacked directly on `change_id`, sorted
+    ///
+    /// Stacks form a tree: each segment has one parent, but several
+    /// segments may stack on the same one. Each path from a root to a leaf
+    /// is one of [`stacks`](Self::stacks), so a fork's shared segments
+    /// appear in every stack through it.
+    pub fn children_of(&self, change_id: &str) -> Vec<&str> {
+        let mut children: Vec<&str> = self
+            .bookmarked_change_adjacency_list
+            .iter()
+            .filter(|(_, parent)| *parent == change_id)
+            .map(|(child, _)| child.as_str())
+            .collect();
+        children.sort_unstable();
+        children
+    }
+
+    /// The segment whose tip is `change_id`, from any stack
+    pub fn segment(&self, change_id: &str) -> Option<&BookmarkSegment> {
+        self.stacks
+            .iter()
+            .flat_map(|stack| &stack.segments)
+            .find(|segment| segment.id == change_id)
+    }
+}
+
+/// Why a bookmark was left out of the change graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ExclusionReason {
+    /// A merge commit sits between trunk and the bookmark
+    MergeCommit {
+        /// Commit ID of the merge (hex)
+        commit_id: String,
+    },
+    /// The bookmark builds on a change already excluded via another bookmark
+    TaintedAncestor {
+        /// Commit ID of the excluded ancestor (hex)
+        commit_id: String,
+    },
+}
+
+impl std::fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MergeCommit { commit_id } => {
+                write!(f, "merge commit {} in history", short_id(commit_id))
+            }
+            Self::TaintedAncestor { commit_id } => {
+                write!(
+                    f,
+                    "builds on excluded commit {} (merge in history)",
+                    short_id(commit_id)
+                )
+            }
+        }
+    }
+}
+
+/// A bookmark excluded from stack detection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExcludedBookmark {
+    /// Bookmark name
+    pub name: String,
+    /// Why it was excluded
+    pub reason: ExclusionReason,
+}
+
+/// Shorten a hex ID for display (first 8 chars)
+fn short_id(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
+
+/// A pull request / merge request
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PullRequest {
+    /// PR/MR number
+    pub number: u64,
+    /// Web URL for the PR/MR
+    pub html_url: String,
+    /// Base branch name
+    pub base_ref: String,
+    /// Head branch name
+    pub head_ref: String,
+    /// PR/MR title
+    pub title: String,
+    /// GraphQL node ID (GitHub only, used for mutations)
+    pub node_id: Option<String>,
+    /// Whether PR is a draft
+    pub is_draft: bool,
+    /// When the PR was last changed, to spot edits made after planning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Lifecycle state of a PR/MR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrState {
+    /// Open and ready for review
+    Open,
+    /// Open as a draft
+    Draft,
+    /// Merged
+    Merged,
+    /// Closed without merging
+    Closed,
+}
+
+impl std::fmt::Display for PrState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Draft => write!(f, "draft"),
+            Self::Merged => write!(f, "merged"),
+            Self::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+/// Combined result of the CI checks on a PR's head commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksState {
+    /// No checks reported
+    None,
+    /// Some checks are still running
+    Pending,
+    /// Every check passed
+    Passing,
+    /// At least one check failed
+    Failing,
+}
+
+impl std::fmt::Display for ChecksState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "no checks"),
+            Self::Pending => write!(f, "checks pending"),
+            Self::Passing => write!(f, "checks passing"),
+            Self::Failing => write!(f, "checks failing"),
+        }
+    }
+}
+
+/// Review and CI state deciding whether a PR can land
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrReadiness {
+    /// Approved by a reviewer, with no outstanding change requests
+    pub approved: bool,
+    /// State of the CI checks
+    pub checks: ChecksState,
+}
+
+impl PrReadiness {
+    /// Approved and green; a PR without checks counts as green
+    pub fn is_ready(&self) -> bool {
+        self.approved && matches!(self.checks, ChecksState::Passing | ChecksState::None)
+    }
+}
+
+/// Where a PR's review stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    /// Approved, with no outstanding change requests
+    Approved,
+    /// A reviewer asked for changes
+    ChangesRequested,
+    /// Nobody has approved or asked for changes yet
+    Pending,
+}
+
+impl ReviewState {
+    /// Overall state of a PR from each reviewer's latest review
+    ///
+    /// One change request outweighs any number of approvals.
+    pub fn from_reviews(reviews: &[PrReview]) -> Self {
+        if reviews.iter().any(|r| r.state == Self::ChangesRequested) {
+            Self::ChangesRequested
+        } else if reviews.iter().any(|r| r.state == Self::Approved) {
+            Self::Approved
+        } else {
+            Self::Pending
+        }
+    }
+}
+
+impl std::fmt::Display for ReviewState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Approved => write!(f, "approved"),
+            Self::ChangesRequested => write!(f, "changes requested"),
+            Self::Pending => write!(f, "review pending"),
+        }
+    }
+}
+
+/// One reviewer's latest verdict on a PR
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrReview {
+    /// Login or username of the reviewer
+    pub reviewer: String,
+    /// What they decided
+    pub state: ReviewState,
+}
+
+/// Open PRs of one head branch, as a batch lookup returns them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchPrs {
+    /// Head branch looked up
+    pub head_branch: String,
+    /// Its open PRs, oldest first
+    pub prs: Vec<PullRequest>,
+    /// Review and CI state of the oldest PR, when the platform returned it
+    /// with the lookup
+    pub readiness: Option<PrReadiness>,
+    /// Latest review of each reviewer on the oldest PR, when the platform
+    /// returned them with the lookup
+    pub reviews: Option<Vec<PrReview>>,
+}
+
+/// API requests left before a platform starts refusing them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RateLimit {
+    /// Requests allowed per window
+    pub limit: u64,
+    /// Requests left in the current window
+    pub remaining: u64,
+    /// When the window resets
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// Fewer than `needed` requests left, or under a tenth of the limit
+    pub const fn is_nearing(&self, needed: u64) -> bool {
+        self.remaining < needed || self.remaining.saturating_mul(10) < self.limit
+    }
+}
+
+/// How a PR is merged into its base
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMethod {
+    /// Merge commit
+    Merge,
+    /// Squash all commits into one
+    #[default]
+    Squash,
+    /// Rebase the commits onto the base
+    Rebase,
+}
+
+impl std::fmt::Display for MergeMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Merge => write!(f, "merge"),
+            Self::Squash => write!(f, "squash"),
+            Self::Rebase => write!(f, "rebase"),
+        }
+    }
+}
+
+impl std::str::FromStr for MergeMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "squash" => Ok(Self::Squash),
+            "rebase" => Ok(Self::Rebase),
+            _ => Err(format!(
+                "unknown merge method '{s}' (expected merge, squash or rebase)"
+            )),
+        }
+    }
+}
+
+/// Where submit publishes a PR's stack navigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StackInfo {
+    /// A stack comment on each PR
+    #[default]
+    Comment,
+    /// A section of each PR's description, for orgs that dislike bot
+    /// comments
+    Body,
+    /// Nowhere
+    None,
+}
+
+impl std::fmt::Display for StackInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Comment => write!(f, "comment"),
+            Self::Body => write!(f, "body"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
+
+impl std::str::FromStr for StackInfo {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "comment" => Ok(Self::Comment),
+            "body" => Ok(Self::Body),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "unknown stack info mode '{s}' (expected comment, body or none)"
+            )),
+        }
+    }
+}
+
+/// An issue (used to track a whole stack)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issue {
+    /// Issue number (IID on GitLab)
+    pub number: u64,
+    /// Web URL for the issue
+    pub html_url: String,
+}
+
+/// A comment on a pull request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrComment {
+    /// Comment ID
+    pub id: u64,
+    /// Comment body text
+    pub body: String,
+    /// GraphQL node ID (GitHub only, used for mutations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+}
+
+/// A comment to write on a PR: a new one, or a new body for an existing one
+#[derive(Debug, Clone)]
+pub struct CommentWrite {
+    /// PR number
+    pub pr_number: u64,
+    /// GraphQL node ID of the PR (GitHub only, used for mutations)
+    pub pr_node_id: Option<String>,
+    /// Comment to update, or `None` to create one
+    pub existing: Option<PrComment>,
+    /// New comment body
+    pub body: String,
+}
+
+/// Reviewers, labels and assignees to set on a PR
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrMetadata {
+    /// Logins or usernames to request reviews from
+    pub reviewers: Vec<String>,
+    /// Label names
+    pub labels: Vec<String>,
+    /// Logins or usernames to assign
+    pub assignees: Vec<String>,
+}
+
+impl PrMetadata {
+    /// Whether there is nothing to set
+    pub fn is_empty(&self) -> bool {
+        self.reviewers.is_empty() && self.labels.is_empty() && self.assignees.is_empty()
+    }
+}
+
+/// A git remote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRemote {
+    /// Remote name (e.g., "origin")
+    pub name: String,
+    /// Remote URL
+    pub url: String,
+}
+
+/// Detected platform type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Platform {
+    /// GitHub or GitHub Enterprise
+    GitHub,
+    /// GitLab or self-hosted GitLab
+    GitLab,
+    /// Bitbucket Cloud
+    Bitbucket,
+    /// Gitea or Forgejo (Codeberg or self-hosted)
+    Gitea,
+    /// Azure DevOps Repos (dev.azure.com or Azure DevOps Server)
+    AzureDevOps,
+    /// Simulated platform kept in the workspace, for demos and offline trials
+    Fake,
+}
+
+impl Platform {
+    /// Cargo feature that compiles in this platform's service
+    pub const fn feature_name(self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Bitbucket => "bitbucket",
+            Self::Gitea => "gitea",
+            Self::AzureDevOps => "azure-devops",
+            Self::Fake => "fake",
+        }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GitHub => write!(f, "GitHub"),
+            Self::GitLab => write!(f, "GitLab"),
+            Self::Bitbucket => write!(f, "Bitbucket"),
+            Self::Gitea => write!(f, "Gitea"),
+            Self::AzureDevOps => write!(f, "Azure DevOps"),
+            Self::Fake => write!(f, "Fake platform"),
+        }
+    }
+}
+
+/// Platform configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformConfig {
+    /// Platform type
+    pub platform: Platform,
+    /// Repository owner (user or organization; for Azure DevOps the
+    /// `organization/project` path)
+    pub owner: String,
+    /// Repository name
+    pub repo: String,
+    /// Custom host (None for github.com/gitlab.com/bitbucket.org/codeberg.org);
+    /// for the fake platform, the workspace root its store lives in
+    pub host: Option<String>,
+    /// Owner of the fork PR head branches live in, when it isn't `owner`
+    /// (GitHub only)
+    pub head_owner: Option<String>,
+}
+
+impl PlatformConfig {
+    /// Web URL of the repository
+    pub fn web_url(&self) -> String {
+        let host = match self.platform {
+            Platform::Fake => return format!("fake://{}/{}", self.owner, self.repo),
+            Platform::GitHub => "github.com",
+            Platform::GitLab => "gitlab.com",
+            Platform::Bitbucket => "bitbucket.org",
+            Platform::Gitea => "codeberg.org",
+            Platform::AzureDevOps => {
+                // An Azure DevOps Server host can carry its own scheme
+                let host = self.host.as_deref().unwrap_or("dev.azure.com");
+                let base = if host.contains("://") {
+                    host.trim_end_matches('/').to_string()
+                } else {
+                    format!("https://{host}")
+                };
+                return format!("{base}/{}/_git/{}", self.owner, self.repo);
+            }
+        };
+        let host = self.host.as_deref().unwrap_or(host);
+        format!("https://{host}/{}/{}", self.owner, self.repo)
+    }
+
+    /// Web URL of the compare view showing `head` against `base`
+    pub fn compare_url(&self, base: &str, head: &str) -> String {
+        match self.platform {
+            Platform::GitHub => format!("{}/compare/{base}...{head}", self.web_url()),
+            Platform::GitLab => format!("{}/-/compare/{base}...{head}", self.web_url()),
+            Platform::Bitbucket => {
+                format!("{}/branches/compare/{head}%0D{base}", self.web_url())
+            }
+            Platform::Gitea | Platform::Fake => {
+                format!("{}/compare/{base}...{head}", self.web_url())
+            }
+            Platform::AzureDevOps => format!(
+                "{}/branchCompare?baseVersion=GB{base}&targetVersion=GB{head}",
+                self.web_url()
+            ),
+        }
+    }
+}
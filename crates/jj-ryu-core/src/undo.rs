@@ -0,0 +1,237 @@
+//! Undoing ryu's last command
+//!
+//! Before a command that changes the repo or the platform runs, the CLI
+//! calls [`UndoRecorder::begin`] with the repo's head jj operation. While it
+//! runs, pushes, branch deletions and PR writes are noted as they succeed.
+//! That happens deep inside the workspace and the platform wrapper, which
+//! reach the recorder through their [`RunContext`], like
+//! [`crate::metrics`]. [`UndoRecorder::finish`] saves it as
+//! `.jj/ryu/undo.json`; `ryu undo` restores the operation and reports what
+//! it can't take back on the remote.
+//!
+//! [`RunContext`]: crate::run::RunContext
+
+use crate::error::Result;
+use crate::read_only::is_read_only;
+use crate::state::RyuState;
+use crate::types::PlatformConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Undo file name, in the same directory as the state file
+const UNDO_FILE: &str = "undo.json";
+
+/// Something a command did outside the local repo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteChange {
+    /// A bookmark was pushed
+    Pushed {
+        /// The bookmark
+        bookmark: String,
+        /// Remote it was pushed to
+        remote: String,
+    },
+    /// A bookmark's branch was deleted on a remote
+    DeletedBranch {
+        /// The bookmark
+        bookmark: String,
+        /// Remote it was deleted from
+        remote: String,
+    },
+    /// A PR was opened
+    CreatedPr {
+        /// PR number
+        number: u64,
+        /// Web URL of the PR
+        url: String,
+        /// Repository the PR was opened in, which for a fork or a separate
+        /// PR remote isn't the primary remote's
+        platform: PlatformConfig,
+    },
+    /// A PR's base, body, comments or metadata were written
+    UpdatedPr {
+        /// PR number
+        number: u64,
+    },
+    /// A PR was merged
+    MergedPr {
+        /// PR number
+        number: u64,
+    },
+    /// A PR was closed
+    ClosedPr {
+        /// PR number
+        number: u64,
+    },
+}
+
+impl fmt::Display for RemoteChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pushed { bookmark, remote } => write!(f, "pushed {bookmark} to {remote}"),
+            Self::DeletedBranch { bookmark, remote } => {
+                write!(f, "deleted branch {bookmark} on {remote}")
+            }
+            Self::CreatedPr { number, .. } => write!(f, "opened PR #{number}"),
+            Self::UpdatedPr { number } => write!(f, "updated PR #{number}"),
+            Self::MergedPr { number } => write!(f, "merged PR #{number}"),
+            Self::ClosedPr { number } => write!(f, "closed PR #{number}"),
+        }
+    }
+}
+
+/// The last ryu command that changed something, and how to take it back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoRecord {
+    /// Command name, e.g. `submit`
+    pub command: String,
+    /// When the command started
+    pub started_at: DateTime<Utc>,
+    /// Head jj operation before the command ran
+    pub operation_id: String,
+    /// Head jj operation once the command finished
+    pub final_operation_id: String,
+    /// What the command did outside the local repo, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remote_changes: Vec<RemoteChange>,
+}
+
+impl UndoRecord {
+    /// Whether the command changed the repo
+    pub fn changed_repo(&self) -> bool {
+        self.operation_id != self.final_operation_id
+    }
+
+    /// PRs the command opened, as number, URL and the repository they're in
+    pub fn created_prs(&self) -> impl Iterator<Item = (u64, &str, &PlatformConfig)> {
+        self.remote_changes
+            .iter()
+            .filter_map(|change| match change {
+                RemoteChange::CreatedPr {
+                    number,
+                    url,
+                    platform,
+                } => Some((*number, url.as_str(), platform)),
+                _ => None,
+            })
+    }
+}
+
+/// A command being recorded
+struct Recording {
+    workspace_root: PathBuf,
+    record: UndoRecord,
+}
+
+/// Records the command of one run, off until begun
+#[derive(Default)]
+pub struct UndoRecorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+impl UndoRecorder {
+    fn recording(&self) -> MutexGuard<'_, Option<Recording>> {
+        self.recording.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start recording `command`, which runs in the repo at `workspace_root`
+    /// whose head operation is `operation_id`
+    pub fn begin(&self, workspace_root: &Path, command: &str, operation_id: String) {
+        *self.recording() = Some(Recording {
+            workspace_root: workspace_root.to_path_buf(),
+            record: UndoRecord {
+                command: command.to_string(),
+                started_at: Utc::now(),
+                final_operation_id: operation_id.clone(),
+                operation_id,
+                remote_changes: Vec::new(),
+            },
+        });
+    }
+
+    /// Note a remote change of the command being recorded, if any
+    ///
+    /// Repeats of a change already noted (a PR updated twice) are dropped.
+    pub fn record(&self, change: RemoteChange) {
+        if let Some(recording) = self.recording().as_mut() {
+            let changes = &mut recording.record.remote_changes;
+            if !changes.contains(&change) {
+                changes.push(change);
+            }
+        }
+    }
+
+    /// Stop recording and save the record, with the repo's head operation
+    /// now read by `head_operation` from the workspace root
+    ///
+    /// A command that changed nothing leaves the previous record in place,
+    /// so a dry run never hides what `ryu undo` would undo. Returns the
+    /// saved record. Does nothing in read-only mode.
+    pub fn finish(
+        &self,
+        head_operation: impl FnOnce(&Path) -> Result<String>,
+    ) -> Result<Option<UndoRecord>> {
+        let Some(Recording {
+            workspace_root,
+            mut record,
+        }) = self.recording().take()
+        else {
+            return Ok(None);
+        };
+        record.final_operation_id = head_operation(&workspace_root)?;
+        if is_read_only() || (!record.changed_repo() && record.remote_changes.is_empty()) {
+            return Ok(None);
+        }
+        save_undo_record(&workspace_root, &record)?;
+        Ok(Some(record))
+    }
+}
+
+/// Path of the undo file for a workspace
+pub fn undo_path(workspace_root: &Path) -> PathBuf {
+    RyuState::path(workspace_root).with_file_name(UNDO_FILE)
+}
+
+/// The saved record of the last command that changed something
+pub fn load_undo_record(workspace_root: &Path) -> Result<Option<UndoRecord>> {
+    let path = undo_path(workspace_root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(&path)?)?))
+}
+
+/// Save `record` as the one `ryu undo` takes back next
+///
+/// Does nothing in read-only mode.
+pub fn save_undo_record(workspace_root: &Path, record: &UndoRecord) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    let path = undo_path(workspace_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(record)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Forget the saved record, once it has been undone
+pub fn clear_undo_record(workspace_root: &Path) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    let path = undo_path(workspace_root);
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Release lookup and verification for self-update
+//!
+//! Release tarballs are published to GitHub releases as
+//! `ryu-<platform>.tar.gz` (`.zip` on Windows), each with a detached
+//! minisign signature (`<archive>.minisig`) made by the release workflow.
+//! Archives are only trusted if that signature checks out against
+//! [`RELEASE_PUBLIC_KEY`], which is built into the binary, so a tampered
+//! release can't vouch for itself.
+
+use crate::error::{Error, Result};
+use crate::platform::DEFAULT_TIMEOUT_SECS;
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::time::Duration;
+use tracing::debug;
+
+/// minisign public key that signs release archives
+///
+/// The matching secret key is the release workflow's `MINISIGN_SECRET_KEY`
+/// secret.
+pub const RELEASE_PUBLIC_KEY: &str = "RWQWmnA6vhSSHq/mlykoZRD25ZKZcHPX4OA5zETF4ss2jycHCgd2e7EC";
+
+/// Name of the detached signature published next to a release archive
+pub fn signature_asset_name(asset_name: &str) -> String {
+    format!("{asset_name}.minisig")
+}
+
+/// A published release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    /// Git tag, e.g. `v0.1.0`
+    pub tag_name: String,
+    /// Whether the release is a pre-release
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Whether the release is an unpublished draft
+    #[serde(default)]
+    pub draft: bool,
+    /// Downloadable files
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    /// Version without the leading `v`
+    pub fn version(&self) -> &str {
+        self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name)
+    }
+
+    /// Find an asset by file name
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// A file attached to a release
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    /// File name
+    pub name: String,
+    /// Direct download URL
+    pub browser_download_url: String,
+}
+
+/// GitHub API URL listing this crate's releases
+pub fn releases_api_url() -> String {
+    let repo = env!("CARGO_PKG_REPOSITORY")
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/');
+    format!("https://api.github.com/repos/{repo}/releases")
+}
+
+/// Release archive name for a platform, if one is published
+///
+/// `os` and `arch` use [`std::env::consts`] names.
+pub fn release_asset_name(os: &str, arch: &str, musl: bool) -> Option<String> {
+    let arch = match arch {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        _ => return None,
+    };
+    match os {
+        "macos" => Some(format!("ryu-darwin-{arch}.tar.gz")),
+        "linux" if musl => Some(format!("ryu-linux-{arch}-musl.tar.gz")),
+        "linux" => Some(format!("ryu-linux-{arch}.tar.gz")),
+        "windows" => Some(format!("ryu-win32-{arch}.zip")),
+        _ => None,
+    }
+}
+
+/// HTTP client for release downloads
+pub fn http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("jj-ryu/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| Error::Platform(format!("failed to create HTTP client: {e}")))
+}
+
+/// List published releases, newest first as reported by the API
+pub async fn fetch_releases(client: &reqwest::Client, url: &str) -> Result<Vec<Release>> {
+    debug!(url, "fetching releases");
+    let releases = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(releases)
+}
+
+/// Download a release asset
+pub async fn download_asset(client: &reqwest::Client, asset: &ReleaseAsset) -> Result<Vec<u8>> {
+    debug!(asset = %asset.name, "downloading release asset");
+    let bytes = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+/// Pick the newest release that is newer than `current`
+///
+/// Drafts are ignored; pre-releases are only considered when
+/// `include_prereleases` is set.
+pub fn select_update<'a>(
+    releases: &'a [Release],
+    current: &str,
+    include_prereleases: bool,
+) -> Option<&'a Release> {
+    releases
+        .iter()
+        .filter(|r| !r.draft && (include_prereleases || !r.prerelease))
+        .filter(|r| compare_versions(r.version(), current) == Ordering::Greater)
+        .max_by(|a, b| compare_versions(a.version(), b.version()))
+}
+
+/// Compare two `major.minor.patch[-pre]` versions with semver precedence
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = a.split_once('-').map_or((a, None), |(c, p)| (c, Some(p)));
+    let (b_core, b_pre) = b.split_once('-').map_or((b, None), |(c, p)| (c, Some(p)));
+
+    let core = |v: &str| -> Vec<u64> { v.split('.').map(|n| n.parse().unwrap_or(0)).collect() };
+    core(a_core)
+        .cmp(&core(b_core))
+        .then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            // A release sorts after its pre-releases
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_prerelease(a, b),
+        })
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_ids = a.split('.');
+    let mut b_ids = b.split('.');
+    loop {
+        match (a_ids.next(), b_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+/// Check a release archive against its minisign signature
+///
+/// `public_key` is the base64 key line of a minisign public key; ryu passes
+/// [`RELEASE_PUBLIC_KEY`]. The signature's trusted comment must name
+/// `file_name`, so one platform's signed archive can't stand in for another's.
+pub fn verify_signature(
+    data: &[u8],
+    signature: &str,
+    public_key: &str,
+    file_name: &str,
+) -> Result<()> {
+    let public_key = PublicKey::from_base64(public_key)
+        .map_err(|e| Error::SelfUpdate(format!("invalid release public key: {e}")))?;
+    let signature = Signature::decode(signature)
+        .map_err(|e| Error::SelfUpdate(format!("invalid signature for {file_name}: {e}")))?;
+    public_key
+        .verify(data, &signature, false)
+        .map_err(|e| Error::SelfUpdate(format!("signature check failed for {file_name}: {e}")))?;
+
+    let expected = format!("file:{file_name}");
+    if !signature
+        .trusted_comment()
+        .split_whitespace()
+        .any(|field| field == expected)
+    {
+        return Err(Error::SelfUpdate(format!(
+            "signature is for another file than {file_name}: {}",
+            signature.trusted_comment()
+        )));
+    }
+    Ok(())
+}
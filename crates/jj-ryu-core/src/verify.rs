@@ -0,0 +1,290 @@
+//! Stack verification: do the platform and the remote agree with the stacks?
+//!
+//! Cross-checks what submit is meant to maintain - one open PR per
+//! bookmark, PR bases following the stack, remote bookmarks at the local
+//! commits and stack comments listing the right PRs - and reports every
+//! violation with the command that fixes it. Read-only, like `fsck`.
+
+use crate::error::Result;
+use crate::platform::PlatformService;
+use crate::repo::JjWorkspace;
+use crate::submit::{
+    COMMENT_DATA_PREFIX, COMMENT_DATA_PREFIX_OLD, analyze_submission, get_base_branch,
+    parse_stack_comment_data, select_bookmark_for_segment, stack_section,
+};
+use crate::types::{ChangeGraph, StackInfo};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A broken invariant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The bookmark has no open PR
+    NoOpenPr {
+        /// Bookmark
+        bookmark: String,
+        /// Leaf of the stack to submit
+        leaf: String,
+    },
+    /// The bookmark has several open PRs
+    DuplicatePrs {
+        /// Bookmark
+        bookmark: String,
+        /// The open PRs, oldest first
+        numbers: Vec<u64>,
+    },
+    /// The open PR targets another base than submit would set
+    WrongBase {
+        /// Bookmark
+        bookmark: String,
+        /// PR number
+        pr_number: u64,
+        /// Base the PR targets
+        base: String,
+        /// Base it should target
+        expected: String,
+    },
+    /// The remote bookmark is missing or not at the local commit
+    RemoteOutOfDate {
+        /// Bookmark
+        bookmark: String,
+        /// Remote checked
+        remote: String,
+        /// Remote commit, `None` if the bookmark isn't on the remote
+        remote_commit: Option<String>,
+        /// Leaf of the stack to submit
+        leaf: String,
+    },
+    /// The PR's stack comment is missing or lists other PRs than the stack
+    StaleStackComment {
+        /// Bookmark
+        bookmark: String,
+        /// PR number
+        pr_number: u64,
+        /// Whether there's a stack comment at all
+        missing: bool,
+        /// Leaf of the stack to submit
+        leaf: String,
+    },
+}
+
+impl Violation {
+    /// Bookmark the violation is about
+    pub fn bookmark(&self) -> &str {
+        match self {
+            Self::NoOpenPr { bookmark, .. }
+            | Self::DuplicatePrs { bookmark, .. }
+            | Self::WrongBase { bookmark, .. }
+            | Self::RemoteOutOfDate { bookmark, .. }
+            | Self::StaleStackComment { bookmark, .. } => bookmark,
+        }
+    }
+
+    /// Command (or action) that fixes the violation
+    pub fn fix(&self) -> String {
+        match self {
+            Self::NoOpenPr { leaf, .. }
+            | Self::RemoteOutOfDate { leaf, .. }
+            | Self::StaleStackComment { leaf, .. } => format!("ryu submit {leaf}"),
+            Self::DuplicatePrs { .. } => "ryu sync (offers to close the newer PRs)".to_string(),
+            Self::WrongBase { .. } => "ryu sync".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoOpenPr { bookmark, .. } => write!(f, "{bookmark} has no open PR"),
+            Self::DuplicatePrs { bookmark, numbers } => {
+                let numbers: Vec<String> = numbers.iter().map(|n| format!("#{n}")).collect();
+                write!(f, "{bookmark} has {} open PRs", numbers.join(", "))
+            }
+            Self::WrongBase {
+                bookmark,
+                pr_number,
+                base,
+                expected,
+            } => write!(
+                f,
+                "{bookmark}: #{pr_number} targets {base}, expected {expected}"
+            ),
+            Self::RemoteOutOfDate {
+                bookmark,
+                remote,
+                remote_commit: None,
+                ..
+            } => write!(f, "{bookmark} isn't on {remote}"),
+            Self::RemoteOutOfDate {
+                bookmark,
+                remote,
+                remote_commit: Some(commit),
+                ..
+            } => write!(
+                f,
+                "{bookmark}@{remote} is at {}, not the local commit",
+                &commit[..commit.len().min(8)]
+            ),
+            Self::StaleStackComment {
+                bookmark,
+                pr_number,
+                missing: true,
+                ..
+            } => write!(f, "{bookmark}: #{pr_number} has no stack comment"),
+            Self::StaleStackComment {
+                bookmark,
+                pr_number,
+                missing: false,
+                ..
+            } => write!(
+                f,
+                "{bookmark}: the stack comment on #{pr_number} lists other PRs"
+            ),
+        }
+    }
+}
+
+/// Check every stack in `graph` against `remote` and the platform
+///
+/// Bookmarks shared by forked stacks are checked once; their stack comment
+/// may list any of the stacks they belong to. `overrides` are the PR base
+/// overrides from the state store. Stack comments, or the stack sections of
+/// PR bodies, are checked according to `stack_info`.
+#[allow(clippy::implicit_hasher)]
+pub async fn verify_stacks(
+    workspace: &JjWorkspace,
+    graph: &ChangeGraph,
+    platform: &dyn PlatformService,
+    remote: &str,
+    default_branch: &str,
+    overrides: &HashMap<String, String>,
+    stack_info: StackInfo,
+) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+    // Bookmarks in the order they were checked, with their stack's leaf
+    let mut checked: Vec<(String, String)> = Vec::new();
+    // Open PR per bookmark, and the (bookmark, PR) lists each belongs to
+    let mut open_prs = HashMap::new();
+    let mut memberships: HashMap<String, Vec<Vec<(String, u64)>>> = HashMap::new();
+
+    for stack in &graph.stacks {
+        let Some(leaf_segment) = stack.segments.last() else {
+            continue;
+        };
+        let leaf = select_bookmark_for_segment(leaf_segment, None).name;
+        let analysis = analyze_submission(graph, &leaf)?;
+
+        for segment in &analysis.segments {
+            let bookmark = &segment.bookmark;
+            if checked.iter().any(|(name, _)| *name == bookmark.name) {
+                continue;
+            }
+            checked.push((bookmark.name.clone(), leaf.clone()));
+
+            let remote_commit = workspace
+                .get_remote_bookmark(&bookmark.name, remote)?
+                .map(|b| b.commit_id);
+            if remote_commit.as_deref() != Some(bookmark.commit_id.as_str()) {
+                violations.push(Violation::RemoteOutOfDate {
+                    bookmark: bookmark.name.clone(),
+                    remote: remote.to_string(),
+                    remote_commit,
+                    leaf: leaf.clone(),
+                });
+            }
+
+            let mut prs = platform.find_open_prs(&bookmark.name).await?;
+            prs.sort_by_key(|pr| pr.number);
+            let Some(pr) = prs.first().cloned() else {
+                violations.push(Violation::NoOpenPr {
+                    bookmark: bookmark.name.clone(),
+                    leaf: leaf.clone(),
+                });
+                continue;
+            };
+            if prs.len() > 1 {
+                violations.push(Violation::DuplicatePrs {
+                    bookmark: bookmark.name.clone(),
+                    numbers: prs.iter().map(|pr| pr.number).collect(),
+                });
+            }
+
+            let expected = get_base_branch(
+                &bookmark.name,
+                &analysis.segments,
+                default_branch,
+                overrides,
+            )?;
+            if pr.base_ref != expected {
+                violations.push(Violation::WrongBase {
+                    bookmark: bookmark.name.clone(),
+                    pr_number: pr.number,
+                    base: pr.base_ref.clone(),
+                    expected,
+                });
+            }
+            open_prs.insert(bookmark.name.clone(), pr.number);
+        }
+
+        let members: Vec<(String, u64)> = analysis
+            .segments
+            .iter()
+            .filter_map(|s| {
+                let number = open_prs.get(&s.bookmark.name)?;
+                Some((s.bookmark.name.clone(), *number))
+            })
+            .collect();
+        for (bookmark, _) in &members {
+            memberships
+                .entry(bookmark.clone())
+                .or_default()
+                .push(members.clone());
+        }
+    }
+
+    if stack_info != StackInfo::None {
+        for (bookmark, leaf) in checked {
+            let (Some(&pr_number), Some(expected)) =
+                (open_prs.get(&bookmark), memberships.get(&bookmark))
+            else {
+                continue;
+            };
+            let recorded = if stack_info == StackInfo::Body {
+                let body = platform.get_pr_body(pr_number).await?;
+                stack_section(&body).map(parse_stack_comment_data)
+            } else {
+                let comments = platform.list_pr_comments(pr_number).await?;
+                comments
+                    .iter()
+                    .find(|c| {
+                        c.body.contains(COMMENT_DATA_PREFIX)
+                            || c.body.contains(COMMENT_DATA_PREFIX_OLD)
+                    })
+                    .map(|c| parse_stack_comment_data(&c.body))
+            };
+            let listed = recorded.map(|data| {
+                data.map(|data| {
+                    data.stack
+                        .into_iter()
+                        .map(|item| (item.bookmark_name, item.pr_number))
+                        .collect::<Vec<_>>()
+                })
+            });
+            let stale = match &listed {
+                None => Some(true),
+                Some(Some(listed)) if expected.contains(listed) => None,
+                Some(_) => Some(false),
+            };
+            if let Some(missing) = stale {
+                violations.push(Violation::StaleStackComment {
+                    bookmark,
+                    pr_number,
+                    missing,
+                    leaf,
+                });
+            }
+        }
+    }
+
+    Ok(violations)
+}
@@ -0,0 +1,137 @@
+//! Adopt command - take over PRs opened outside ryu
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::duplicates::resolve_duplicate_prs;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, arrow, check, cross};
+use crate::cli::sync::select_stacks;
+use crate::cli::{CliProgress, Outcome};
+use anstream::{eprintln, println};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::{create_comment_platform_service, create_platform_service};
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::submit::{
+    ExecutionStep, analyze_submission, create_submission_plan, execute_submission,
+};
+use std::path::Path;
+
+/// Run the adopt command
+///
+/// Matches the bookmarks of the stack containing `bookmark` (every stack
+/// without one) to open PRs by head branch, then does what a submit would
+/// to those PRs short of pushing: bases are fixed to follow the local stack
+/// and stack comments are written. The adopted PRs are recorded in
+/// [`RyuState`]. A dry run reports [`Outcome::ChangesPending`] if any PR
+/// would be adopted.
+pub async fn run_adopt(
+    path: &Path,
+    bookmark: Option<&str>,
+    remote: Option<&str>,
+    dry_run: bool,
+) -> Result<Outcome> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let push_remote = workspace.push_remote(&remotes, &remote_name)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+    let comment_platform = create_comment_platform_service(&platform_config, workspace.run())?;
+
+    let graph = build_change_graph(&workspace)?;
+    let default_branch = workspace.default_branch()?;
+    let mut state = RyuState::load(workspace.workspace_root())?;
+    let stack_info = workspace.config().stack_info();
+    let interdiff_comments = workspace.config().interdiff_comments.unwrap_or(true);
+
+    let mut adopted = 0;
+    let mut failed = 0;
+    for stack in select_stacks(&graph, bookmark)? {
+        let Some(leaf) = stack
+            .segments
+            .last()
+            .and_then(|segment| segment.bookmarks.first())
+        else {
+            continue;
+        };
+        let mut analysis = analyze_submission(&graph, &leaf.name)?;
+        analysis.base_overrides = state.base_overrides.clone().into_iter().collect();
+        let mut plan =
+            create_submission_plan(&analysis, platform.as_ref(), &push_remote, &default_branch)
+                .await?;
+        if plan.existing_prs.is_empty() {
+            continue;
+        }
+
+        println!("{} {}", "Adopting stack:".emphasis(), leaf.name.accent());
+        for segment in &plan.segments {
+            let name = &segment.bookmark.name;
+            match plan.existing_prs.get(name) {
+                Some(pr) => println!("  {name} {} #{}", arrow(), pr.number.accent()),
+                None => println!("  {name} {}", "(no open PR)".muted()),
+            }
+        }
+
+        let duplicates: Vec<_> = plan.duplicate_prs.iter().collect();
+        resolve_duplicate_prs(&duplicates, platform.as_ref(), dry_run).await?;
+
+        // Only the PRs change: nothing is pushed or opened
+        plan.execution_steps
+            .retain(|step| matches!(step, ExecutionStep::UpdateBase(_)));
+        plan.renamed_prs.clear();
+        plan.stack_info = stack_info;
+        plan.interdiff_comments = interdiff_comments;
+        let result = execute_submission(
+            &plan,
+            &mut workspace,
+            platform.as_ref(),
+            comment_platform.as_deref(),
+            &CliProgress::verbose(),
+            dry_run,
+        )
+        .await?;
+        if !result.success {
+            eprintln!("{} Adopting {} failed", cross(), leaf.name.accent());
+            for err in &result.errors {
+                eprintln!("  {}", err.error());
+            }
+            println!();
+            failed += 1;
+            continue;
+        }
+
+        adopted += plan.existing_prs.len();
+        for (bookmark, pr) in &plan.existing_prs {
+            state.adopted_prs.insert(bookmark.clone(), pr.number);
+        }
+        println!();
+    }
+
+    if adopted == 0 && failed == 0 {
+        println!("{}", "No open PRs match these bookmarks".muted());
+        return Ok(Outcome::Done);
+    }
+    if dry_run {
+        return Ok(Outcome::ChangesPending);
+    }
+    if adopted > 0 {
+        state.save(workspace.workspace_root())?;
+        println!(
+            "{} {} PR{} adopted; submit and sync now keep them updated",
+            check(),
+            adopted.accent(),
+            if adopted == 1 { "" } else { "s" }
+        );
+    }
+    if failed > 0 {
+        return Err(Error::StacksFailed(failed));
+    }
+    Ok(Outcome::Done)
+}
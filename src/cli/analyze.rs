@@ -1,22 +1,45 @@
 //! Default analyze command - print stack graph visualization
 
-use crate::cli::style::{self, Stylize, check, pipe, up_arrow};
-use anstream::println;
-use jj_ryu::error::Result;
-use jj_ryu::graph::build_change_graph;
-use jj_ryu::repo::JjWorkspace;
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::output::{is_json, print_json};
+use crate::cli::platform::platform_for_remote;
+use crate::cli::strict::note_excluded_bookmarks;
+use crate::cli::style::{
+    self, Stream, Stylize, bullet, check, checks_marker, pipe, review_marker, up_arrow,
+};
+use crate::cli::width::{display_width, fit};
+use anstream::{eprintln, println};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::landed::LandedChange;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::status::{OpenPrState, open_pr_states};
+use jj_ryu_core::types::{BookmarkSegment, BranchStack, ChangeGraph};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
 /// Run the analyze command (default when no subcommand given)
 ///
-/// Prints a text-based visualization of the bookmark stacks.
+/// Prints a text-based visualization of the bookmark stacks, or the change
+/// graph itself with `--format json`. With `checks` (or `analyze-checks`),
+/// pushed bookmarks with an open PR show its number, CI state and review
+/// state when the platform can be reached.
 #[allow(clippy::too_many_lines)]
-pub async fn run_analyze(path: &Path) -> Result<()> {
+pub async fn run_analyze(path: &Path, checks: bool) -> Result<()> {
     // Open workspace
-    let workspace = JjWorkspace::open(path)?;
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
 
     // Build change graph
     let graph = build_change_graph(&workspace)?;
+    note_excluded_bookmarks(&graph, false);
+
+    if is_json() {
+        return print_json(&serde_json::to_value(&graph)?);
+    }
 
     if graph.stacks.is_empty() {
         println!("{}", "No bookmark stacks found".muted());
@@ -32,109 +55,106 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
         return Ok(());
     }
 
+    // Looking PRs up is opt-in: it needs the network and credentials, and
+    // the graph alone should stay instant
+    let prs = if checks || workspace.config().analyze_checks.unwrap_or(false) {
+        open_prs(&workspace, &graph).await.unwrap_or_else(|e| {
+            eprintln!("{}", format!("PR state unavailable: {e}").muted());
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+    // Recorded by sync; a missing or unreadable state file just hides it
+    let landed = RyuState::load(workspace.workspace_root())
+        .map(|state| state.landed)
+        .unwrap_or_default();
+
     // Print header
     println!("{}", "Bookmark Stacks".emphasis());
     println!();
 
-    for (i, stack) in graph.stacks.iter().enumerate() {
-        if stack.segments.is_empty() {
-            continue;
-        }
-
-        // Print stack header with leaf bookmark name
-        let leaf = stack.segments.last().unwrap();
-        let leaf_name = &leaf.bookmarks[0].name;
+    // Stacks sharing a root are branches of one tree, drawn together
+    let trees = group_into_trees(&graph);
+    for (i, tree) in trees.iter().enumerate() {
+        let leaves: Vec<&str> = tree
+            .iter()
+            .filter_map(|stack| stack.segments.last())
+            .map(|leaf| leaf.bookmarks[0].name.as_str())
+            .collect();
         println!(
             "{} {}",
             format!("Stack #{}:", i + 1).emphasis(),
-            leaf_name.accent()
+            leaves.join(", ").accent()
         );
         println!();
 
-        // Print each segment in reverse order (newest/leaf first, oldest last)
-        for segment in stack.segments.iter().rev() {
-            let bookmark_names: Vec<&str> =
-                segment.bookmarks.iter().map(|b| b.name.as_str()).collect();
-
-            // Print commits in segment (already newest-first from revset)
-            for (j, change) in segment.changes.iter().enumerate() {
-                let is_first_in_segment = j == 0;
-                let commit_short = &change.commit_id[..8.min(change.commit_id.len())];
-                let change_short = &change.change_id[..8.min(change.change_id.len())];
-
-                let desc = if change.description_first_line.is_empty() {
-                    "(no description)"
-                } else {
-                    &change.description_first_line
-                };
-
-                // Truncate description (char-safe for UTF-8)
-                let max_desc = 50;
-                let desc_display = if desc.chars().count() > max_desc {
-                    format!("{}...", desc.chars().take(max_desc - 3).collect::<String>())
-                } else {
-                    desc.to_string()
-                };
-
-                let marker = if change.is_working_copy {
-                    style::CURRENT
-                } else {
-                    style::BULLET
-                };
+        // The first branch runs down to trunk; each later one only down to
+        // where it forks off, and is drawn above the branches before it
+        let mut drawn: HashSet<&str> = HashSet::new();
+        let mut branches: Vec<(&[BookmarkSegment], Option<&BookmarkSegment>)> = Vec::new();
+        for stack in tree {
+            let shared = stack
+                .segments
+                .iter()
+                .take_while(|segment| drawn.contains(segment.id.as_str()))
+                .count();
+            let fork = shared.checked_sub(1).map(|k| &stack.segments[k]);
+            branches.push((&stack.segments[shared..], fork));
+            drawn.extend(stack.segments.iter().map(|segment| segment.id.as_str()));
+        }
 
-                // Show bookmark on first commit of segment (the tip)
-                if is_first_in_segment && !bookmark_names.is_empty() {
-                    for bm in &bookmark_names {
-                        let bookmark = segment.bookmarks.iter().find(|b| b.name == *bm).unwrap();
-                        let sync_status = if bookmark.is_synced {
-                            format!(" {}", check())
-                        } else if bookmark.has_remote {
-                            format!(" {}", up_arrow())
-                        } else {
-                            String::new()
-                        };
-                        println!("       [{}]{}", bm.accent(), sync_status);
-                    }
-                }
-                println!(
-                    "    {}  {} {} {}",
-                    marker,
-                    change_short.muted(),
-                    commit_short.muted(),
-                    desc_display
-                );
-                println!("    {}", pipe());
+        for (segments, fork) in branches.iter().rev() {
+            // Newest (leaf) first, oldest last
+            for segment in segments.iter().rev() {
+                print_segment(segment, &prs, &landed);
+            }
+            match fork {
+                Some(fork) => println!(
+                    "  {}",
+                    format!("forks from [{}]", fork.bookmarks[0].name).muted()
+                ),
+                None => println!("  {}", "trunk()".muted()),
             }
+            println!();
         }
-
-        // Print trunk base at bottom
-        println!("  {}", "trunk()".muted());
-        println!();
     }
 
-    // Summary
-    let total_bookmarks: usize = graph.stacks.iter().map(|s| s.segments.len()).sum();
+    // Summary, counting a fork's shared bookmarks once
+    let total_bookmarks = graph
+        .stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .map(|segment| segment.id.as_str())
+        .collect::<HashSet<_>>()
+        .len();
     println!(
         "{} stack{}, {} bookmark{}",
-        graph.stacks.len().accent(),
-        if graph.stacks.len() == 1 { "" } else { "s" },
+        trees.len().accent(),
+        if trees.len() == 1 { "" } else { "s" },
         total_bookmarks.accent(),
         if total_bookmarks == 1 { "" } else { "s" }
     );
 
-    if graph.excluded_bookmark_count > 0 {
+    if !graph.excluded_bookmarks.is_empty() {
+        let count = graph.excluded_bookmarks.len();
+        println!();
+        println!(
+            "{} bookmark{} excluded from stacks:",
+            count.accent(),
+            if count == 1 { "" } else { "s" }
+        );
+        for excluded in &graph.excluded_bookmarks {
+            println!(
+                "  {} {} {}",
+                bullet(),
+                excluded.name.accent(),
+                format!("- {}", excluded.reason).muted()
+            );
+        }
         println!(
             "{}",
-            format!(
-                "({} bookmark{} excluded due to merge commits)",
-                graph.excluded_bookmark_count,
-                if graph.excluded_bookmark_count == 1 {
-                    ""
-                } else {
-                    "s"
-                }
-            )
-            .muted()
+            "Rebase these bookmarks onto a linear history (jj rebase) to include them.".muted()
         );
     }
 
@@ -149,8 +169,157 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
         )
         .muted()
     );
+    if !prs.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "        #N = open PR; checks {} passing, {} failing, {} running",
+                style::CHECK,
+                style::CROSS,
+                style::PENDING
+            )
+            .muted()
+        );
+    }
     println!();
     println!("To submit a stack: {}", "ryu submit <bookmark>".accent());
 
     Ok(())
 }
+
+/// Stacks grouped by their root segment, longest branch first
+fn group_into_trees(graph: &ChangeGraph) -> Vec<Vec<&BranchStack>> {
+    let mut trees: Vec<Vec<&BranchStack>> = Vec::new();
+    for stack in graph.stacks.iter().filter(|s| !s.segments.is_empty()) {
+        let root = &stack.segments[0].id;
+        match trees
+            .iter_mut()
+            .find(|tree| &tree[0].segments[0].id == root)
+        {
+            Some(tree) => tree.push(stack),
+            None => trees.push(vec![stack]),
+        }
+    }
+    for tree in &mut trees {
+        tree.sort_by(|a, b| {
+            b.segments
+                .len()
+                .cmp(&a.segments.len())
+                .then_with(|| leaf_name(a).cmp(leaf_name(b)))
+        });
+    }
+    trees
+}
+
+fn leaf_name(stack: &BranchStack) -> &str {
+    stack
+        .segments
+        .last()
+        .map_or("", |leaf| leaf.bookmarks[0].name.as_str())
+}
+
+/// Print a segment's bookmarks and commits
+fn print_segment(
+    segment: &BookmarkSegment,
+    prs: &HashMap<String, OpenPrState>,
+    landed: &BTreeMap<String, LandedChange>,
+) {
+    let bookmark_names: Vec<&str> = segment.bookmarks.iter().map(|b| b.name.as_str()).collect();
+
+    // Print commits in segment (already newest-first from revset)
+    for (j, change) in segment.changes.iter().enumerate() {
+        let is_first_in_segment = j == 0;
+        let commit_short = &change.commit_id[..8.min(change.commit_id.len())];
+        let change_short = &change.change_id[..8.min(change.change_id.len())];
+
+        let desc = if change.description_first_line.is_empty() {
+            "(no description)"
+        } else {
+            &change.description_first_line
+        };
+
+        let marker = if change.is_working_copy {
+            style::CURRENT
+        } else {
+            style::BULLET
+        };
+
+        // Show bookmark on first commit of segment (the tip)
+        if is_first_in_segment && !bookmark_names.is_empty() {
+            for bm in &bookmark_names {
+                let bookmark = segment.bookmarks.iter().find(|b| b.name == *bm).unwrap();
+                let sync_status = if bookmark.is_synced {
+                    format!(" {}", check())
+                } else if bookmark.has_remote {
+                    format!(" {}", up_arrow())
+                } else {
+                    String::new()
+                };
+                let pr = prs
+                    .get(*bm)
+                    .map(|open| {
+                        let marker = checks_marker(open.checks)
+                            .map(|marker| format!(" {marker}"))
+                            .unwrap_or_default();
+                        format!(
+                            "  {}{marker} {}",
+                            format!("#{}", open.pr.number).muted(),
+                            review_marker(open.review)
+                        )
+                    })
+                    .unwrap_or_default();
+                let landed = landed
+                    .get(&segment.id)
+                    .filter(|landed| landed.bookmark == *bm)
+                    .map(|landed| {
+                        let short = &landed.commit[..8.min(landed.commit.len())];
+                        format!("  {}", format!("landed as {short}").muted())
+                    })
+                    .unwrap_or_default();
+                println!("       [{}]{}{}{}", bm.accent(), sync_status, pr, landed);
+            }
+        }
+        let used = display_width(&format!("    {marker}  {change_short} {commit_short} "));
+        println!(
+            "    {}  {} {} {}",
+            marker,
+            change_short.muted(),
+            commit_short.muted(),
+            fit(Stream::Stdout, used, desc)
+        );
+        println!("    {}", pipe());
+    }
+}
+
+/// The open PR and its CI and review state for each pushed bookmark
+///
+/// Callers treat an error as best effort: without a supported remote or
+/// credentials the view goes without them.
+async fn open_prs(
+    workspace: &JjWorkspace,
+    graph: &ChangeGraph,
+) -> Result<HashMap<String, OpenPrState>> {
+    let mut bookmarks: Vec<String> = graph
+        .stacks
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.bookmarks)
+        .filter(|bookmark| bookmark.has_remote)
+        .map(|bookmark| bookmark.name.clone())
+        .collect();
+    // A fork's shared segments are in several stacks
+    bookmarks.sort_unstable();
+    bookmarks.dedup();
+    if bookmarks.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, None)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or(Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+    open_pr_states(platform.as_ref(), &bookmarks).await
+}
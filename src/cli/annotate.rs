@@ -0,0 +1,74 @@
+//! Annotate command - attach reviewer notes to a change
+
+use crate::cli::context::open_workspace;
+use crate::cli::style::{Stylize, bullet, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::read_only::is_read_only;
+use jj_ryu_core::submit::{add_note_trailer, parse_notes};
+use std::path::Path;
+use std::process::Command;
+
+/// Add a `ryu-note:` trailer to `revision`, or list its notes if `note` is `None`
+///
+/// The description is rewritten with `jj describe`, so the working copy is
+/// snapshotted and descendants rebased exactly as jj would. Notes are
+/// rendered into the PR body on the next submit.
+pub fn run_annotate(path: &Path, revision: &str, note: Option<&str>) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    let change = match workspace.resolve_revset(revision)?.as_slice() {
+        [change] => change.clone(),
+        [] => {
+            return Err(Error::Revset(format!(
+                "'{revision}' doesn't resolve to any change"
+            )));
+        }
+        _ => {
+            return Err(Error::Revset(format!(
+                "'{revision}' resolves to more than one change"
+            )));
+        }
+    };
+    let short_id = &change.change_id[..change.change_id.len().min(8)];
+
+    let Some(note) = note.map(str::trim).filter(|n| !n.is_empty()) else {
+        let notes = parse_notes(&change.description);
+        if notes.is_empty() {
+            println!("{}", format!("No review notes on {short_id}").muted());
+        }
+        for note in notes {
+            println!("{} {note}", bullet());
+        }
+        return Ok(());
+    };
+
+    let description = add_note_trailer(&change.description, note);
+    if is_read_only() {
+        workspace.run().skipped(&format!("describe {short_id}"));
+        return Ok(());
+    }
+    let output = Command::new("jj")
+        .arg("--repository")
+        .arg(workspace.workspace_root())
+        .args(["describe", "-r", &change.commit_id, "-m", &description])
+        .output()
+        .map_err(|e| Error::Workspace(format!("failed to run jj describe: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::Workspace(format!(
+            "jj describe failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    println!(
+        "{} {} {}",
+        check(),
+        short_id.accent(),
+        change.description_first_line.muted()
+    );
+    println!(
+        "{}",
+        "The note will appear in the PR body on the next submit".muted()
+    );
+    Ok(())
+}
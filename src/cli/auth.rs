@@ -3,9 +3,13 @@
 use crate::cli::style::{Stylize, check, spinner_style};
 use anstream::println;
 use indicatif::ProgressBar;
-use jj_ryu::auth::{get_github_auth, get_gitlab_auth, test_github_auth, test_gitlab_auth};
-use jj_ryu::error::Result;
-use jj_ryu::types::Platform;
+use jj_ryu_core::auth::{
+    AZURE_DEVOPS_ORG_URL_ENV, get_azure_devops_auth, get_bitbucket_auth, get_gitea_auth,
+    get_github_auth, get_gitlab_auth, test_azure_devops_auth, test_bitbucket_auth, test_gitea_auth,
+    test_github_auth, test_gitlab_auth,
+};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::types::Platform;
 use std::time::Duration;
 
 /// Run the auth test command
@@ -22,7 +26,7 @@ pub async fn run_auth_test(platform: Platform) -> Result<()> {
 
             spinner.finish_and_clear();
             println!("{} Authenticated as: {}", check(), username.accent());
-            println!("  {} {:?}", "Token source:".muted(), config.source);
+            println!("  {} {}", "Token source:".muted(), config.source);
         }
         Platform::GitLab => {
             let spinner = ProgressBar::new_spinner();
@@ -35,9 +39,58 @@ pub async fn run_auth_test(platform: Platform) -> Result<()> {
 
             spinner.finish_and_clear();
             println!("{} Authenticated as: {}", check(), username.accent());
-            println!("  {} {:?}", "Token source:".muted(), config.source);
+            println!("  {} {}", "Token source:".muted(), config.source);
             println!("  {} {}", "Host:".muted(), config.host);
         }
+        Platform::Bitbucket => {
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(spinner_style());
+            spinner.set_message("Testing Bitbucket authentication...");
+            spinner.enable_steady_tick(Duration::from_millis(80));
+
+            let config = get_bitbucket_auth()?;
+            let username = test_bitbucket_auth(&config).await?;
+
+            spinner.finish_and_clear();
+            println!("{} Authenticated as: {}", check(), username.accent());
+            println!("  {} {}", "Token source:".muted(), config.source);
+        }
+        Platform::Gitea => {
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(spinner_style());
+            spinner.set_message("Testing Gitea authentication...");
+            spinner.enable_steady_tick(Duration::from_millis(80));
+
+            let config = get_gitea_auth(None)?;
+            let username = test_gitea_auth(&config).await?;
+
+            spinner.finish_and_clear();
+            println!("{} Authenticated as: {}", check(), username.accent());
+            println!("  {} {}", "Token source:".muted(), config.source);
+            println!("  {} {}", "Host:".muted(), config.host);
+        }
+        Platform::AzureDevOps => {
+            let organization_url = std::env::var(AZURE_DEVOPS_ORG_URL_ENV).map_err(|_| {
+                Error::Config(format!(
+                    "Set {AZURE_DEVOPS_ORG_URL_ENV} (e.g. https://dev.azure.com/myorg) to test against"
+                ))
+            })?;
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(spinner_style());
+            spinner.set_message("Testing Azure DevOps authentication...");
+            spinner.enable_steady_tick(Duration::from_millis(80));
+
+            let config = get_azure_devops_auth()?;
+            let username = test_azure_devops_auth(&config, &organization_url).await?;
+
+            spinner.finish_and_clear();
+            println!("{} Authenticated as: {}", check(), username.accent());
+            println!("  {} {}", "Token source:".muted(), config.source);
+            println!("  {} {}", "Organization:".muted(), organization_url);
+        }
+        Platform::Fake => {
+            println!("{} The fake platform needs no authentication", check());
+        }
     }
     Ok(())
 }
@@ -82,6 +135,94 @@ pub fn run_auth_setup(platform: Platform) {
             println!("{}", "For self-hosted GitLab:".muted());
             println!("  {}", "Set GITLAB_HOST to your instance hostname".muted());
         }
+        Platform::Bitbucket => {
+            println!("{}", "Bitbucket Authentication Setup".emphasis());
+            println!();
+            println!("{}", "Create an app password".emphasis());
+            println!(
+                "  {}",
+                "https://bitbucket.org/account/settings/app-passwords/".accent()
+            );
+            println!(
+                "  {}",
+                "Grant Pull requests: Write and Issues: Write".muted()
+            );
+            println!();
+            println!("{}", "Then set environment variables".emphasis());
+            println!(
+                "  Set {} and {}",
+                "BITBUCKET_USERNAME".accent(),
+                "BITBUCKET_APP_PASSWORD".accent()
+            );
+            println!();
+            println!(
+                "{}",
+                "Only Bitbucket Cloud (bitbucket.org) is supported.".muted()
+            );
+        }
+        Platform::Gitea => {
+            println!("{}", "Gitea/Forgejo Authentication Setup".emphasis());
+            println!();
+            println!("{}", "Create an access token".emphasis());
+            println!(
+                "  {}",
+                "Settings > Applications on your instance (e.g. https://codeberg.org/user/settings/applications)"
+                    .accent()
+            );
+            println!(
+                "  {}",
+                "Grant repository and issue read/write access".muted()
+            );
+            println!();
+            println!("{}", "Then set an environment variable".emphasis());
+            println!(
+                "  Set {} or {}",
+                "GITEA_TOKEN".accent(),
+                "FORGEJO_TOKEN".accent()
+            );
+            println!();
+            println!("{}", "For instances other than codeberg.org:".muted());
+            println!("  {}", "Set GITEA_HOST to your instance hostname".muted());
+            println!(
+                "  {}",
+                "or map it with RYU_PLATFORM_HOSTS=host=gitea".muted()
+            );
+        }
+        Platform::AzureDevOps => {
+            println!("{}", "Azure DevOps Authentication Setup".emphasis());
+            println!();
+            println!("{}", "Create a personal access token".emphasis());
+            println!(
+                "  {}",
+                "User settings > Personal access tokens (e.g. https://dev.azure.com/myorg/_usersSettings/tokens)"
+                    .accent()
+            );
+            println!(
+                "  {}",
+                "Grant Code: Read & write and Identity: Read".muted()
+            );
+            println!();
+            println!("{}", "Then set an environment variable".emphasis());
+            println!(
+                "  Set {} or {}",
+                "AZURE_DEVOPS_PAT".accent(),
+                "AZURE_DEVOPS_EXT_PAT".accent()
+            );
+            println!();
+            println!("{}", "To test the token:".muted());
+            println!(
+                "  {}",
+                "Set AZURE_DEVOPS_ORG_URL to your organization URL".muted()
+            );
+            println!("{}", "For Azure DevOps Server:".muted());
+            println!(
+                "  {}",
+                "Map your host with RYU_PLATFORM_HOSTS=host=azure-devops".muted()
+            );
+        }
+        Platform::Fake => {
+            println!("{}", "The fake platform needs no authentication".muted());
+        }
     }
 }
 
@@ -0,0 +1,91 @@
+//! Bookmark creation for `submit --auto-bookmark`
+
+use crate::cli::output::println;
+use crate::cli::style::{Stylize, check};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::{DEFAULT_AUTO_BOOKMARK_TEMPLATE, bookmark_user, plan_auto_bookmarks};
+use std::collections::HashSet;
+
+/// Give every unbookmarked change between trunk and `target` a bookmark
+///
+/// `target` is a bookmark or, when no bookmark has that name, a revision
+/// such as `@-`. Returns the bookmark to submit: `target` itself, or the
+/// one on the topmost change. A dry run only lists the bookmarks it would
+/// create, and returns `None` if the target has no bookmark yet.
+pub fn create_auto_bookmarks(
+    workspace: &mut JjWorkspace,
+    target: &str,
+    dry_run: bool,
+) -> Result<Option<String>> {
+    let existing = workspace.get_local_bookmark(target)?;
+    let tip = match &existing {
+        Some(bookmark) => bookmark.commit_id.clone(),
+        None => match workspace.resolve_revset(target)?.as_slice() {
+            [entry] => entry.commit_id.clone(),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "'{target}' is neither a bookmark nor a single revision"
+                )));
+            }
+        },
+    };
+
+    let changes = workspace.resolve_revset(&format!("trunk()..{tip}"))?;
+    let taken: HashSet<String> = workspace
+        .local_bookmarks()?
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    let template = workspace
+        .config()
+        .auto_bookmark_template
+        .clone()
+        .unwrap_or_else(|| DEFAULT_AUTO_BOOKMARK_TEMPLATE.to_string());
+    let user = bookmark_user(workspace.user_email().as_deref());
+    let prefix = workspace.config().branch_prefix.clone();
+    let planned = plan_auto_bookmarks(&changes, &template, &user, prefix.as_deref(), &taken)?;
+
+    for bookmark in &planned {
+        let short_id = &bookmark.change_id[..bookmark.change_id.len().min(8)];
+        if dry_run {
+            println!(
+                "{} {} {} {}",
+                "Would create bookmark".muted(),
+                bookmark.name.accent(),
+                short_id.muted(),
+                bookmark.description
+            );
+        } else {
+            workspace.create_bookmark(&bookmark.name, &bookmark.commit_id)?;
+            println!(
+                "{} Created bookmark {} {} {}",
+                check(),
+                bookmark.name.accent(),
+                short_id.muted(),
+                bookmark.description
+            );
+        }
+    }
+    if !planned.is_empty() {
+        println!();
+    }
+
+    if existing.is_some() {
+        return Ok(Some(target.to_string()));
+    }
+    if dry_run {
+        return Ok(None);
+    }
+    // The tip keeps a bookmark it already had; otherwise the topmost new one
+    let tip_bookmark = changes
+        .iter()
+        .find(|change| change.commit_id == tip)
+        .and_then(|change| change.local_bookmarks.first().cloned());
+    tip_bookmark
+        .or_else(|| planned.last().map(|bookmark| bookmark.name.clone()))
+        .map(Some)
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!("no changes to submit between trunk and '{target}'"))
+        })
+}
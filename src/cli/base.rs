@@ -0,0 +1,72 @@
+//! Base command - manage per-bookmark PR base overrides
+
+use crate::cli::context::open_workspace;
+use crate::cli::style::{Stylize, arrow, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::state::RyuState;
+use std::path::Path;
+
+/// Make a bookmark's PR target `branch` instead of its stack parent
+pub fn run_base_set(path: &Path, bookmark: &str, branch: &str) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    if workspace.get_local_bookmark(bookmark)?.is_none() {
+        return Err(Error::BookmarkNotFound(bookmark.to_string()));
+    }
+
+    let root = workspace.workspace_root();
+    let mut state = RyuState::load(root)?;
+    state
+        .base_overrides
+        .insert(bookmark.to_string(), branch.to_string());
+    state.save(root)?;
+
+    println!(
+        "{} {} {} {}",
+        check(),
+        bookmark.accent(),
+        arrow(),
+        branch.accent()
+    );
+    println!(
+        "{}",
+        "The PR base will be updated on the next submit or sync".muted()
+    );
+    Ok(())
+}
+
+/// Remove a bookmark's base override
+pub fn run_base_unset(path: &Path, bookmark: &str) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    let root = workspace.workspace_root();
+    let mut state = RyuState::load(root)?;
+
+    if state.base_overrides.remove(bookmark).is_none() {
+        println!("{}", format!("No base override for {bookmark}").muted());
+        return Ok(());
+    }
+    state.save(root)?;
+
+    println!(
+        "{} {} targets its stack parent again",
+        check(),
+        bookmark.accent()
+    );
+    Ok(())
+}
+
+/// List base overrides
+pub fn run_base_list(path: &Path) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    let state = RyuState::load(workspace.workspace_root())?;
+
+    if state.base_overrides.is_empty() {
+        println!("{}", "No base overrides".muted());
+        return Ok(());
+    }
+
+    for (bookmark, branch) in &state.base_overrides {
+        println!("{} {} {}", bookmark.accent(), arrow(), branch);
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! Bisect-stack command - find the segment whose PR introduced a regression
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stream, Stylize, check, cross, hyperlink_url};
+use anstream::println;
+use jj_ryu_core::bisect::{BisectResult, BisectStep, bisect_steps, find_first_failing};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::read_only::is_read_only;
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::restack::trunk_commit_id;
+use jj_ryu_core::submit::analyze_submission;
+use std::path::Path;
+use std::process::Command;
+
+/// Run `command` at trunk and each segment boundary up to `bookmark`
+///
+/// Each boundary is checked out with `jj new`, so the working copy's own
+/// changes stay in their change; the original working-copy change is
+/// edited again afterwards, also when the search fails. The command runs
+/// from the workspace root and passes when it exits 0.
+pub async fn run_bisect_stack(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    command: &[String],
+) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(Error::InvalidArgument(
+            "no command to run; pass it after --".to_string(),
+        ));
+    };
+    if is_read_only() {
+        return Err(Error::InvalidArgument(
+            "bisect-stack checks out each boundary and can't run in read-only mode".to_string(),
+        ));
+    }
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+    let root = workspace.workspace_root().to_path_buf();
+
+    let graph = build_change_graph(&workspace)?;
+    if !graph.bookmarks.contains_key(bookmark) {
+        return Err(Error::BookmarkNotFound(bookmark.to_string()));
+    }
+    let analysis = analyze_submission(&graph, bookmark)?;
+    let trunk = trunk_commit_id(&workspace)?
+        .ok_or_else(|| Error::Revset("trunk() doesn't resolve to a commit".to_string()))?;
+    let steps = bisect_steps(&analysis, &trunk);
+    let original = workspace
+        .resolve_revset("@")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Revset("no working-copy change".to_string()))?;
+
+    println!(
+        "{} {} {}",
+        "Testing".emphasis(),
+        steps.len(),
+        format!("boundaries up to {bookmark}").muted()
+    );
+    let result = find_first_failing(&steps, |step| {
+        run_jj(&root, &["new", &step.commit_id])?;
+        let passed = Command::new(program)
+            .args(args)
+            .current_dir(&root)
+            .status()
+            .map_err(|e| Error::InvalidArgument(format!("failed to run {program}: {e}")))?
+            .success();
+        println!(
+            "{} {} {}",
+            if passed { check() } else { cross() },
+            step_label(step).accent(),
+            short(&step.commit_id).muted()
+        );
+        Ok(passed)
+    });
+
+    // An empty, undescribed working-copy change is abandoned once jj moves
+    // off it; recreate it on its parents then
+    if run_jj(&root, &["edit", &original.change_id]).is_err() {
+        let mut new = vec!["new"];
+        new.extend(original.parents.iter().map(String::as_str));
+        run_jj(&root, &new)?;
+    }
+
+    println!();
+    match result? {
+        BisectResult::TrunkFails => println!(
+            "{}",
+            "The command already fails on trunk; the regression predates the stack".warn()
+        ),
+        BisectResult::AllPass => println!(
+            "{} The command passes at every boundary up to {}",
+            check(),
+            bookmark.accent()
+        ),
+        BisectResult::FirstFailing { bookmark, index } => {
+            println!(
+                "{} First failing segment: {} {}",
+                cross(),
+                bookmark.accent(),
+                format!("({} of {})", index + 1, analysis.segments.len()).muted()
+            );
+            print_pr_link(&workspace, remote, &bookmark).await;
+        }
+    }
+    Ok(())
+}
+
+/// Print the segment's PR, if it has one; lookup failures are only noted
+async fn print_pr_link(workspace: &JjWorkspace, remote: Option<&str>, bookmark: &str) {
+    let lookup = async {
+        let remotes = workspace.git_remotes()?;
+        let remote_name = workspace.primary_remote(&remotes, remote)?;
+        let remote_info = remotes
+            .iter()
+            .find(|r| r.name == remote_name)
+            .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+        let platform_config = platform_for_remote(workspace, &remote_info.url)?;
+        let platform = create_platform_service(&platform_config, workspace.run()).await?;
+        platform.find_existing_pr(bookmark).await
+    };
+    match lookup.await {
+        Ok(Some(pr)) => println!(
+            "  PR #{}: {}",
+            pr.number,
+            hyperlink_url(Stream::Stdout, &pr.html_url)
+        ),
+        Ok(None) => println!("  {}", "(not submitted yet)".muted()),
+        Err(e) => println!("  {}", format!("Could not look up its PR: {e}").muted()),
+    }
+}
+
+fn step_label(step: &BisectStep) -> &str {
+    step.bookmark.as_deref().unwrap_or("trunk")
+}
+
+fn short(commit_id: &str) -> &str {
+    &commit_id[..commit_id.len().min(8)]
+}
+
+/// Run a jj command in the workspace, failing with its stderr
+fn run_jj(root: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("jj")
+        .arg("--repository")
+        .arg(root)
+        .args(args)
+        .output()
+        .map_err(|e| Error::Workspace(format!("failed to run jj {}: {e}", args[0])))?;
+    if !output.status.success() {
+        return Err(Error::Workspace(format!(
+            "jj {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! Clean command - delete bookmarks whose PRs were merged
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::outcome::Outcome;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, check, cross};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::landed::landed_through;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::status::merged_bookmarks;
+use jj_ryu_core::types::PullRequest;
+use std::path::Path;
+
+/// Run the clean command
+///
+/// Deletes the local bookmark of every merged PR. With `fork`, their
+/// branches on the `fork-remote` go too, in the same pass. A dry run reports
+/// [`Outcome::ChangesPending`] if anything would be deleted.
+pub async fn run_clean(
+    path: &Path,
+    remote: Option<&str>,
+    fork: bool,
+    dry_run: bool,
+) -> Result<Outcome> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let fork_remote = if fork {
+        let push_remote = workspace.push_remote(&remotes, &remote_name)?;
+        if push_remote == remote_name {
+            return Err(Error::Config(
+                "--fork needs `fork-remote` set in the config files".to_string(),
+            ));
+        }
+        Some(push_remote)
+    } else {
+        None
+    };
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let default_branch = workspace.default_branch()?;
+    let bookmarks: Vec<String> = workspace
+        .local_bookmarks()?
+        .into_iter()
+        .map(|b| b.name)
+        .filter(|name| *name != default_branch)
+        .collect();
+    let merged = merged_bookmarks(platform.as_ref(), &bookmarks).await?;
+
+    if merged.is_empty() {
+        println!("{}", "No bookmarks with merged PRs".muted());
+        return Ok(Outcome::Done);
+    }
+
+    let mut failures = 0;
+    for (bookmark, pr) in &merged {
+        let on_fork = match &fork_remote {
+            Some(fork) => workspace.get_remote_bookmark(bookmark, fork)?.is_some(),
+            None => false,
+        };
+        let target = match (&fork_remote, on_fork) {
+            (Some(fork), true) => format!("{bookmark} and {bookmark}@{fork}"),
+            _ => bookmark.clone(),
+        };
+
+        if dry_run {
+            println!(
+                "Would delete {} {}",
+                target.accent(),
+                format!("(#{} merged)", pr.number).muted()
+            );
+            continue;
+        }
+
+        // Local first: the jj CLI backend pushes the deletion of a tracked
+        // bookmark only once it's gone locally
+        let result = workspace.delete_bookmark(bookmark).and_then(|()| {
+            match fork_remote.as_deref().filter(|_| on_fork) {
+                Some(fork) => workspace.delete_remote_bookmark(bookmark, fork),
+                None => Ok(()),
+            }
+        });
+        match result {
+            Ok(()) => println!(
+                "{} Deleted {} {}",
+                check(),
+                target.accent(),
+                format!("(#{} merged)", pr.number).muted()
+            ),
+            Err(e) => {
+                failures += 1;
+                println!("{} {bookmark}: {e}", cross().for_stdout());
+            }
+        }
+    }
+
+    print_redundant_changes(&workspace, &merged);
+
+    if dry_run {
+        return Ok(Outcome::ChangesPending);
+    }
+    if failures > 0 {
+        return Err(Error::Git(format!(
+            "Failed to delete {failures} of {} bookmarks",
+            merged.len()
+        )));
+    }
+    Ok(Outcome::Done)
+}
+
+/// List the local changes sync saw land through the merged PRs
+///
+/// They're now redundant with trunk; abandoning them is left to the user.
+fn print_redundant_changes(workspace: &JjWorkspace, merged: &[(String, PullRequest)]) {
+    let Ok(state) = RyuState::load(workspace.workspace_root()) else {
+        return;
+    };
+    let bookmarks: Vec<String> = merged
+        .iter()
+        .map(|(bookmark, _)| bookmark.clone())
+        .collect();
+    let landed = landed_through(&state, &bookmarks);
+    if landed.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{} local change{} landed on trunk:",
+        landed.len().accent(),
+        if landed.len() == 1 { "" } else { "s" }
+    );
+    for (change_id, change) in &landed {
+        let short = &change.commit[..8.min(change.commit.len())];
+        println!(
+            "  {} {}",
+            change_id[..8.min(change_id.len())].accent(),
+            format!("landed as {short} (#{})", change.pr_number).muted()
+        );
+    }
+    let ids: Vec<&str> = landed.keys().copied().collect();
+    println!(
+        "{}",
+        format!("Abandon them with: jj abandon {}", ids.join(" ")).muted()
+    );
+}
@@ -0,0 +1,45 @@
+//! Expected CODEOWNERS review routing for plan output
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, arrow, bullet};
+use jj_ryu_core::codeowners::{CodeOwners, expected_owners};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::SubmissionAnalysis;
+
+/// Print which code owners each PR will request, leaf first
+///
+/// Silent when the workspace has no CODEOWNERS file. This is a prediction,
+/// so failures are reported as warnings rather than stopping the command.
+pub fn print_expected_owners(workspace: &JjWorkspace, analysis: &SubmissionAnalysis) {
+    let owners = CodeOwners::load(workspace.workspace_root())
+        .and_then(|codeowners| {
+            codeowners
+                .map(|c| expected_owners(workspace, &c, analysis))
+                .transpose()
+        })
+        .unwrap_or_else(|e| {
+            println!("{}", format!("Could not match CODEOWNERS: {e}").warn());
+            note_warning();
+            None
+        });
+    let Some(owners) = owners else {
+        return;
+    };
+
+    println!("{}:", "Expected code owners".emphasis());
+    for segment in owners.iter().rev() {
+        let requested = if segment.owners.is_empty() {
+            "(none)".muted().to_string()
+        } else {
+            segment.owners.join(", ")
+        };
+        println!(
+            "  {} {} {} {requested}",
+            bullet(),
+            segment.bookmark.accent(),
+            arrow()
+        );
+    }
+    println!();
+}
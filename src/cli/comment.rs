@@ -0,0 +1,80 @@
+//! Comment command - maintain ryu's stack comments
+
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::submit::migrate_legacy_comments;
+use std::path::Path;
+
+/// Which PRs `comment migrate` scans
+pub enum MigrateScope<'a> {
+    /// The PR of a single bookmark
+    Bookmark(&'a str),
+    /// Every open PR by `author` (default: the authenticated user)
+    All {
+        /// Login or username whose PRs to scan
+        author: Option<&'a str>,
+    },
+}
+
+/// Rewrite legacy `JJ-STACK_INFO` stack comments in the current format
+pub async fn run_comment_migrate(
+    path: &Path,
+    remote: Option<&str>,
+    scope: MigrateScope<'_>,
+    dry_run: bool,
+) -> Result<()> {
+    let workspace = open_workspace(path)?;
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let prs = match scope {
+        MigrateScope::Bookmark(bookmark) => platform
+            .find_existing_pr(bookmark)
+            .await?
+            .into_iter()
+            .collect(),
+        MigrateScope::All { author } => {
+            let author = match author {
+                Some(author) => author.to_string(),
+                None => platform.current_user().await?,
+            };
+            println!("{}", format!("Scanning open PRs by {author}...").muted());
+            platform.list_open_prs_by_author(&author).await?
+        }
+    };
+
+    let migrated = migrate_legacy_comments(platform.as_ref(), &prs, dry_run).await?;
+
+    for comment in &migrated {
+        let verb = if dry_run { "would migrate" } else { "migrated" };
+        println!(
+            "{} {} {verb} comment {}",
+            check(),
+            format!("#{}", comment.pr_number).accent(),
+            comment.comment_id
+        );
+    }
+    println!(
+        "{}",
+        format!(
+            "{} legacy comment(s) {} across {} PR(s) scanned",
+            migrated.len(),
+            if dry_run { "found" } else { "migrated" },
+            prs.len()
+        )
+        .emphasis()
+    );
+
+    Ok(())
+}
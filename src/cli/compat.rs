@@ -0,0 +1,39 @@
+//! Checks of the installed jj against the linked jj-lib
+
+use crate::cli::style::Stylize;
+use anstream::eprintln;
+use jj_ryu_core::repo::{
+    JjCompatibility, JjWorkspace, check_jj_compatibility, installed_jj_version,
+    supported_jj_version,
+};
+
+/// Warn before a mutating command when the installed jj is newer than ryu
+///
+/// The command still runs: newer jj releases usually keep the on-disk format,
+/// and a real incompatibility is reported when the repo fails to load.
+pub fn warn_on_newer_jj() {
+    if let JjCompatibility::Newer(installed) =
+        check_jj_compatibility(installed_jj_version().as_deref())
+    {
+        eprintln!(
+            "{}",
+            format!(
+                "Warning: jj {installed} is newer than jj {}, which this ryu was built for. \
+                 If ryu fails to read the repo, upgrade ryu.",
+                supported_jj_version()
+            )
+            .warn()
+        );
+    }
+}
+
+/// Tell the user when jj-lib failed and the jj CLI is used instead
+pub fn warn_on_cli_fallback(workspace: &JjWorkspace) {
+    if let Some(reason) = workspace.fallback_reason() {
+        eprintln!(
+            "{}",
+            format!("Warning: {reason}. Falling back to the jj CLI (slower); upgrade ryu to fix.")
+                .warn()
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! The run context commands open workspaces with
+//!
+//! Built from the command line at startup and shared by every workspace the
+//! command opens, so the platform services created for them report to the
+//! same observers.
+
+use jj_ryu_core::error::Result;
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::run::RunContext;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Context of this run
+static RUN: OnceLock<Arc<RunContext>> = OnceLock::new();
+
+/// Set the context for this run. Call once at startup.
+pub fn set_run_context(run: RunContext) {
+    let _ = RUN.set(Arc::new(run));
+}
+
+/// Context of this run (a default one if none was set)
+pub fn run_context() -> Arc<RunContext> {
+    Arc::clone(RUN.get_or_init(Arc::default))
+}
+
+/// Open the jj workspace at `path` for this run
+pub fn open_workspace(path: &Path) -> Result<JjWorkspace> {
+    JjWorkspace::open_in(path, run_context())
+}
@@ -0,0 +1,80 @@
+//! Duplicate PR handling shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, arrow, check};
+use dialoguer::Confirm;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::submit::DuplicatePr;
+use std::io::IsTerminal;
+
+/// Report duplicate PRs and offer to close them
+///
+/// The canonical (oldest) PR is always the one used for planning, so
+/// declining leaves the duplicates open but otherwise ignored. Nothing is
+/// closed in dry-run mode or when stdin is not a terminal.
+pub async fn resolve_duplicate_prs(
+    duplicates: &[&DuplicatePr],
+    platform: &dyn PlatformService,
+    dry_run: bool,
+) -> Result<()> {
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    note_warning();
+    println!(
+        "{}",
+        format!(
+            "Found {} duplicate open PR{}:",
+            duplicates.len(),
+            if duplicates.len() == 1 { "" } else { "s" }
+        )
+        .warn()
+    );
+    for dup in duplicates {
+        println!(
+            "  {} #{} {} keeping #{}",
+            dup.bookmark.accent(),
+            dup.duplicate.number,
+            arrow(),
+            dup.canonical.number
+        );
+    }
+    println!();
+
+    if dry_run || !std::io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    for dup in duplicates {
+        let close = Confirm::new()
+            .with_prompt(format!(
+                "Close duplicate PR #{} for {} (keeping #{})?",
+                dup.duplicate.number, dup.bookmark, dup.canonical.number
+            ))
+            .default(false)
+            .interact()
+            .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?;
+        if !close {
+            continue;
+        }
+
+        platform
+            .create_pr_comment(
+                dup.duplicate.number,
+                &format!("Closing as a duplicate of {}.", dup.canonical.html_url),
+            )
+            .await?;
+        platform.close_pr(dup.duplicate.number).await?;
+        println!(
+            "{} Closed PR #{}",
+            check(),
+            dup.duplicate.number.to_string().accent()
+        );
+    }
+    println!();
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+//! Env command - print effective configuration with provenance
+
+use crate::cli::context::open_workspace;
+use crate::cli::platform::{platform_flag, platform_for_remote};
+use crate::cli::project::PROJECT_ENV;
+use crate::cli::style::Stylize;
+use anstream::println;
+use jj_ryu_core::config::RyuConfig;
+use jj_ryu_core::error::Result;
+use jj_ryu_core::notify::WEBHOOK_URL_ENV;
+use jj_ryu_core::platform::{DEFAULT_TIMEOUT_SECS, HOST_ALIASES_ENV, comment_token_env};
+use jj_ryu_core::read_only::READ_ONLY_ENV;
+use jj_ryu_core::repo::{
+    JJ_BACKEND_ENV, JjWorkspace, LOCK_TIMEOUT_ENV, MIRROR_REMOTES_ENV, PR_REMOTES_ENV,
+    select_primary_remote,
+};
+use jj_ryu_core::reviewers::{REVIEWER_POLICY_ENV, REVIEWERS_ENV};
+use jj_ryu_core::submit::{DRAFT_ENV, PUSH_FORCE_ENV, TRACKING_ISSUE_ENV};
+use jj_ryu_core::telemetry::{TELEMETRY_ENV, TelemetrySettings};
+use std::env;
+use std::path::Path;
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// Command-line flag
+    Flag,
+    /// Environment variable
+    Env,
+    /// Repository config file (`.ryu.toml`)
+    RepoConfig,
+    /// User config file (`~/.config/ryu/config.toml`)
+    UserConfig,
+    /// Detected from repository state (remotes, remote HEAD)
+    Detected,
+    /// Built-in default
+    Default,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Flag => write!(f, "flag"),
+            Self::Env => write!(f, "env"),
+            Self::RepoConfig => write!(f, "repo config"),
+            Self::UserConfig => write!(f, "user config"),
+            Self::Detected => write!(f, "detected"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// A single effective setting and its provenance
+struct Setting {
+    /// Kebab-case name, the config file key where there is one
+    key: &'static str,
+    value: String,
+    source: Source,
+}
+
+impl Setting {
+    fn new(key: &'static str, value: impl Into<String>, source: Source) -> Self {
+        Self {
+            key,
+            value: value.into(),
+            source,
+        }
+    }
+
+    /// Setting read from an environment variable, falling back to a default
+    fn from_env(key: &'static str, var: &str, default: &str) -> Self {
+        match env::var(var) {
+            Ok(value) => Self::new(key, value, Source::Env),
+            Err(_) => Self::new(key, default, Source::Default),
+        }
+    }
+}
+
+/// The config layers, read separately so each value can be attributed
+struct ConfigLayers {
+    /// Settings given as flags, overlaid on the files
+    flags: RyuConfig,
+    repo: RyuConfig,
+    user: RyuConfig,
+}
+
+impl ConfigLayers {
+    fn load(workspace_root: &Path, flags: &RyuConfig) -> Result<Self> {
+        let user = match RyuConfig::user_path() {
+            Some(path) => RyuConfig::from_file(&path)?.unwrap_or_default(),
+            None => RyuConfig::default(),
+        };
+        Ok(Self {
+            flags: flags.clone(),
+            repo: RyuConfig::from_file(&RyuConfig::repo_path(workspace_root))?.unwrap_or_default(),
+            user,
+        })
+    }
+
+    /// Value of a config key and where it came from: flags, then the repo
+    /// file, then the user file
+    fn get<T: ToString>(&self, key: impl Fn(&RyuConfig) -> Option<T>) -> Option<(String, Source)> {
+        key(&self.flags)
+            .map(|v| (v.to_string(), Source::Flag))
+            .or_else(|| key(&self.repo).map(|v| (v.to_string(), Source::RepoConfig)))
+            .or_else(|| key(&self.user).map(|v| (v.to_string(), Source::UserConfig)))
+    }
+
+    /// Setting read from the environment, then the config files, then a default
+    fn setting(
+        &self,
+        key: &'static str,
+        var: &str,
+        config_key: impl Fn(&RyuConfig) -> Option<String>,
+        default: &str,
+    ) -> Setting {
+        if let Ok(value) = env::var(var) {
+            return Setting::new(key, value, Source::Env);
+        }
+        self.get(config_key).map_or_else(
+            || Setting::new(key, default, Source::Default),
+            |(value, source)| Setting::new(key, value, source),
+        )
+    }
+
+    /// Setting read from flags and the config files only
+    fn file_setting(
+        &self,
+        key: &'static str,
+        config_key: impl Fn(&RyuConfig) -> Option<String>,
+        default: &str,
+    ) -> Setting {
+        self.get(config_key).map_or_else(
+            || Setting::new(key, default, Source::Default),
+            |(value, source)| Setting::new(key, value, source),
+        )
+    }
+}
+
+/// Run the env command
+///
+/// Prints every effective setting and where its value came from.
+pub fn run_env(path: &Path, remote: Option<&str>) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    let settings = collect_settings(&workspace, remote)?;
+
+    println!("{}", "Effective configuration".emphasis());
+    println!();
+
+    let key_width = settings.iter().map(|s| s.key.len()).max().unwrap_or(0);
+    for setting in &settings {
+        println!(
+            "  {:<key_width$}  {} {}",
+            setting.key,
+            setting.value.accent(),
+            format!("({})", setting.source).muted()
+        );
+    }
+
+    Ok(())
+}
+
+/// Collect effective settings for the workspace
+fn collect_settings(workspace: &JjWorkspace, remote: Option<&str>) -> Result<Vec<Setting>> {
+    let mut settings = Vec::new();
+    let config = ConfigLayers::load(workspace.workspace_root(), workspace.run().config())?;
+    let config_remote = config.get(|c| c.remote.clone());
+
+    let remotes = workspace.git_remotes()?;
+    let specified = remote.or(config_remote.as_ref().map(|(name, _)| name.as_str()));
+    let trunk = workspace.trunk_bookmark().unwrap_or_default();
+    match select_primary_remote(
+        &remotes,
+        specified,
+        trunk.as_ref().map(|(_, remote)| remote.as_str()),
+    ) {
+        Ok(remote_name) => {
+            let trunk_picked = specified.is_none()
+                && trunk
+                    .as_ref()
+                    .is_some_and(|(_, remote)| *remote == remote_name);
+            let source = if remote.is_some() {
+                Source::Flag
+            } else if let Some((_, source)) = config_remote {
+                source
+            } else if remotes.len() == 1 || trunk_picked {
+                Source::Detected
+            } else {
+                Source::Default
+            };
+            let url = remotes
+                .iter()
+                .find(|r| r.name == remote_name)
+                .map(|r| r.url.clone())
+                .unwrap_or_default();
+
+            settings.push(Setting::new("remote", remote_name, source));
+            settings.push(Setting::new("remote-url", url.clone(), Source::Detected));
+
+            match platform_for_remote(workspace, &url) {
+                Ok(platform) => {
+                    let source = if platform_flag().is_some() {
+                        Source::Flag
+                    } else if let Some((_, source)) = config.get(|c| c.platform) {
+                        source
+                    } else {
+                        Source::Detected
+                    };
+                    settings.push(Setting::new(
+                        "platform",
+                        platform.platform.to_string(),
+                        source,
+                    ));
+                    settings.push(Setting::new(
+                        "repository",
+                        format!("{}/{}", platform.owner, platform.repo),
+                        Source::Detected,
+                    ));
+                    if let Some(head_owner) = platform.head_owner {
+                        settings.push(Setting::new("fork-owner", head_owner, Source::Detected));
+                    }
+                    // A token, so only report whether it is set
+                    let token_set = comment_token_env(platform.platform)
+                        .is_some_and(|var| env::var(var).is_ok());
+                    settings.push(if token_set {
+                        Setting::new("comment-token", "(set)", Source::Env)
+                    } else {
+                        Setting::new("comment-token", "(main token)", Source::Default)
+                    });
+                }
+                Err(_) => {
+                    settings.push(Setting::new("platform", "(unsupported)", Source::Detected));
+                }
+            }
+        }
+        Err(_) => {
+            settings.push(Setting::new("remote", "(none)", Source::Detected));
+        }
+    }
+
+    settings.push(config.file_setting("trunk-revset", |c| c.trunk_revset.clone(), "trunk()"));
+    let trunk_source = config
+        .get(|c| c.default_branch.clone())
+        .map_or(Source::Detected, |(_, source)| source);
+    settings.push(Setting::new(
+        "default-branch",
+        workspace.default_branch()?,
+        trunk_source,
+    ));
+    // The remote-tracking bookmark trunk resolves to picks the remote
+    settings.push(match &trunk {
+        Some((bookmark, remote)) => Setting::new(
+            "trunk-tracks",
+            format!("{bookmark}@{remote}"),
+            Source::Detected,
+        ),
+        None => Setting::new("trunk-tracks", "(no single remote)", Source::Detected),
+    });
+    settings.push(config.file_setting("branch-prefix", |c| c.branch_prefix.clone(), "(none)"));
+    settings.push(config.file_setting("branch-pattern", |c| c.branch_pattern.clone(), "(none)"));
+    settings.push(config.file_setting(
+        "mine-only",
+        |c| c.mine_only.map(|m| m.to_string()),
+        "false",
+    ));
+    settings.push(config.file_setting(
+        "include-bookmarks",
+        |c| c.include_bookmarks.as_ref().map(|globs| globs.join(", ")),
+        "(all)",
+    ));
+    settings.push(config.file_setting(
+        "exclude-bookmarks",
+        |c| c.exclude_bookmarks.as_ref().map(|globs| globs.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.file_setting(
+        "merge-commits",
+        |c| c.merge_commits.map(|m| m.to_string()),
+        "exclude",
+    ));
+    settings.push(Setting::from_env("github-host", "GH_HOST", "github.com"));
+    settings.push(Setting::from_env(
+        "gitlab-host",
+        "GITLAB_HOST",
+        "gitlab.com",
+    ));
+    settings.push(Setting::from_env(
+        "host-aliases",
+        HOST_ALIASES_ENV,
+        "(ssh config)",
+    ));
+    settings.push(config.setting("project", PROJECT_ENV, |c| c.project.clone(), "(none)"));
+    settings.push(config.setting(
+        "mirror-remotes",
+        MIRROR_REMOTES_ENV,
+        |c| c.mirror_remotes.as_ref().map(|r| r.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.setting(
+        "pr-remotes",
+        PR_REMOTES_ENV,
+        |c| c.pr_remotes.as_ref().map(|r| r.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.file_setting("fork-remote", |c| c.fork_remote.clone(), "(none)"));
+    settings.push(config.setting(
+        "push-force",
+        PUSH_FORCE_ENV,
+        |c| c.push_force.map(|p| p.to_string()),
+        "true",
+    ));
+    settings.push(config.setting(
+        "read-only",
+        READ_ONLY_ENV,
+        |c| c.read_only.map(|r| r.to_string()),
+        "false",
+    ));
+    settings.push(config.setting(
+        "draft",
+        DRAFT_ENV,
+        |c| c.draft.map(|d| d.to_string()),
+        "false",
+    ));
+    settings.push(config.file_setting(
+        "stack-info",
+        |c| {
+            (c.stack_info.is_some() || c.stack_comments.is_some())
+                .then(|| c.stack_info().to_string())
+        },
+        "comment",
+    ));
+    settings.push(config.file_setting(
+        "interdiff-comments",
+        |c| c.interdiff_comments.map(|s| s.to_string()),
+        "true",
+    ));
+    settings.push(config.file_setting(
+        "reviewers",
+        |c| c.reviewers.as_ref().map(|r| r.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.file_setting(
+        "labels",
+        |c| c.labels.as_ref().map(|l| l.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.file_setting(
+        "assignees",
+        |c| c.assignees.as_ref().map(|a| a.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.file_setting(
+        "merge-method",
+        |c| c.merge_method.map(|m| m.to_string()),
+        "squash",
+    ));
+    settings.push(config.setting(
+        "reviewer-pool",
+        REVIEWERS_ENV,
+        |c| c.reviewer_pool.as_ref().map(|r| r.join(", ")),
+        "(none)",
+    ));
+    settings.push(config.setting(
+        "reviewer-policy",
+        REVIEWER_POLICY_ENV,
+        |c| c.reviewer_policy.map(|p| p.to_string()),
+        "round-robin",
+    ));
+    settings.push(config.setting(
+        "tracking-issue",
+        TRACKING_ISSUE_ENV,
+        |c| c.tracking_issue.map(|t| t.to_string()),
+        "false",
+    ));
+    // The URL usually embeds a secret, so only report whether it is set
+    settings.push(match env::var(WEBHOOK_URL_ENV) {
+        Ok(_) => Setting::new("webhook-url", "(set)", Source::Env),
+        Err(_) => Setting::new("webhook-url", "(none)", Source::Default),
+    });
+    let backend_source = if env::var(JJ_BACKEND_ENV).is_ok() {
+        Source::Env
+    } else {
+        Source::Detected
+    };
+    settings.push(Setting::new(
+        "jj-backend",
+        workspace.backend().to_string(),
+        backend_source,
+    ));
+    settings.push(config.setting(
+        "lock-timeout",
+        LOCK_TIMEOUT_ENV,
+        |c| c.lock_timeout.map(|t| t.to_string()),
+        "30",
+    ));
+    settings.push(config.file_setting(
+        "gc-retention-days",
+        |c| c.gc_retention_days.map(|d| d.to_string()),
+        "30",
+    ));
+    settings.push(config.file_setting(
+        "sync-prune",
+        |c| c.sync_prune.map(|p| p.to_string()),
+        "false",
+    ));
+    settings.push(config.file_setting(
+        "analyze-checks",
+        |c| c.analyze_checks.map(|p| p.to_string()),
+        "false",
+    ));
+    settings.push(Setting::new(
+        "http-timeout",
+        format!("{DEFAULT_TIMEOUT_SECS}s"),
+        Source::Default,
+    ));
+    // Opting in is personal, so the repository file is never consulted
+    settings.push(if env::var(TELEMETRY_ENV).is_ok() {
+        let enabled = TelemetrySettings::load()?.enabled;
+        Setting::new("telemetry", telemetry_state(enabled), Source::Env)
+    } else if let Some(enabled) = config.user.telemetry {
+        Setting::new("telemetry", telemetry_state(enabled), Source::UserConfig)
+    } else {
+        Setting::new("telemetry", telemetry_state(false), Source::Default)
+    });
+    settings.push(match config.user.theme {
+        Some(theme) => Setting::new("theme", theme.to_string(), Source::UserConfig),
+        None => Setting::new("theme", "default", Source::Default),
+    });
+
+    Ok(settings)
+}
+
+/// How the telemetry setting reads in `ryu env`
+const fn telemetry_state(enabled: bool) -> &'static str {
+    if enabled {
+        "on (local, see `ryu stats --tool`)"
+    } else {
+        "off"
+    }
+}
@@ -0,0 +1,52 @@
+//! Force push guard shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::style::{Stylize, bullet};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::{SubmissionAnalysis, find_non_fast_forwards, force_push_allowed};
+
+/// Refuse to plan non-fast-forward pushes when `push-force` is disabled
+///
+/// Some orgs raise security alerts on force pushes; with
+/// `push-force = false` every bookmark that has diverged from `remote` is
+/// reported and planning fails with a suggested way to fast-forward instead.
+pub fn check_force_push(
+    workspace: &JjWorkspace,
+    analysis: &SubmissionAnalysis,
+    remote: &str,
+) -> Result<()> {
+    if force_push_allowed(workspace.config())? {
+        return Ok(());
+    }
+
+    let diverged = find_non_fast_forwards(workspace, analysis, remote)?;
+    let Some(first) = diverged.first() else {
+        return Ok(());
+    };
+
+    println!(
+        "{}",
+        format!(
+            "{} bookmark{} would need a force push to {remote}:",
+            diverged.len(),
+            if diverged.len() == 1 { "" } else { "s" }
+        )
+        .warn()
+    );
+    for entry in &diverged {
+        println!(
+            "  {} {} {}",
+            bullet(),
+            entry.bookmark.accent(),
+            format!("({} ahead, {} behind)", entry.ahead, entry.behind).muted()
+        );
+    }
+    println!();
+
+    Err(Error::ForcePushDisabled {
+        bookmark: first.bookmark.clone(),
+        remote: remote.to_string(),
+        behind: first.behind,
+    })
+}
@@ -0,0 +1,62 @@
+//! Foreign commit guard shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::style::{Stylize, bullet};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::{SubmissionAnalysis, find_foreign_commits};
+
+/// Refuse to submit commits authored by someone else unless allowed
+///
+/// Identity comes from jj's `user.email`, falling back to the author of the
+/// target bookmark's newest change when no email is configured. With `allow`
+/// the foreign commits are only reported.
+pub fn check_foreign_commits(
+    workspace: &JjWorkspace,
+    analysis: &SubmissionAnalysis,
+    allow: bool,
+) -> Result<()> {
+    let tip_author = || {
+        analysis
+            .segments
+            .last()
+            .and_then(|s| s.changes.first())
+            .map(|c| c.author_email.clone())
+    };
+    let Some(user_email) = workspace.user_email().or_else(tip_author) else {
+        return Ok(());
+    };
+
+    let foreign = find_foreign_commits(analysis, &user_email);
+    if foreign.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "{} commit{} not authored by {user_email}:",
+            foreign.len(),
+            if foreign.len() == 1 { "" } else { "s" }
+        )
+        .warn()
+    );
+    for commit in &foreign {
+        let short_id = &commit.commit_id[..commit.commit_id.len().min(8)];
+        println!(
+            "  {} {} {} {} {}",
+            bullet(),
+            short_id.muted(),
+            commit.description,
+            format!("<{}>", commit.author_email).muted(),
+            format!("({})", commit.bookmark).muted()
+        );
+    }
+    println!();
+
+    if allow {
+        Ok(())
+    } else {
+        Err(Error::ForeignCommits(foreign.len()))
+    }
+}
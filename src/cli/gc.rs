@@ -0,0 +1,106 @@
+//! Gc command - prune ryu's local state, journals and usage log
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::outcome::Outcome;
+use crate::cli::style::{Stylize, check};
+use anstream::println;
+use jj_ryu_core::error::Result;
+use jj_ryu_core::gc::{
+    GcOptions, GcReport, GcScope, collect_garbage, local_bookmark_names, retention_days,
+};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::telemetry::usage_path;
+use std::path::Path;
+
+/// Run the gc command
+///
+/// A dry run reports [`Outcome::ChangesPending`] if anything would be pruned.
+pub fn run_gc(path: &Path, dry_run: bool) -> Result<Outcome> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let graph = build_change_graph(&workspace)?;
+    let options = GcOptions {
+        usage_path: usage_path(),
+        dry_run,
+        ..GcOptions::new(GcScope::Full, retention_days(workspace.config()))
+    };
+    let report = collect_garbage(
+        workspace.workspace_root(),
+        &graph,
+        &local_bookmark_names(&workspace)?,
+        &options,
+    )?;
+
+    if report.is_empty() {
+        println!("{}", "Nothing to prune".muted());
+        return Ok(Outcome::Done);
+    }
+    print_report(&report, dry_run, options.retention_days);
+    Ok(Outcome::pending_if(dry_run))
+}
+
+fn print_report(report: &GcReport, dry_run: bool, retention_days: u32) {
+    let mut lines = Vec::new();
+    for bookmark in &report.base_overrides {
+        lines.push(format!(
+            "base override for deleted bookmark {}",
+            bookmark.accent()
+        ));
+    }
+    for bookmark in &report.adopted_prs {
+        lines.push(format!(
+            "adopted PR record for deleted bookmark {}",
+            bookmark.accent()
+        ));
+    }
+    for key in &report.stale_locks {
+        lines.push(format!("stale stack lock {}", key.accent()));
+    }
+    if report.landed > 0 {
+        lines.push(format!(
+            "{} landed-change record{}",
+            report.landed.accent(),
+            plural(report.landed)
+        ));
+    }
+    for key in &report.journals {
+        lines.push(format!(
+            "submission journal {} {}",
+            key.accent(),
+            format!("(older than {retention_days} days or bookmark deleted)").muted()
+        ));
+    }
+    for key in &report.snapshots {
+        lines.push(format!(
+            "interdiff snapshot for deleted bookmark {}",
+            key.accent()
+        ));
+    }
+    if report.restack_journal {
+        lines.push("restack journal for deleted bookmarks".to_string());
+    }
+    if report.usage_events > 0 {
+        lines.push(format!(
+            "{} usage event{} older than {retention_days} days",
+            report.usage_events.accent(),
+            plural(report.usage_events)
+        ));
+    }
+    for path in &report.temp_files {
+        lines.push(format!("temp file {}", path.display().to_string().muted()));
+    }
+
+    for line in lines {
+        if dry_run {
+            println!("Would prune {line}");
+        } else {
+            println!("{} Pruned {line}", check());
+        }
+    }
+}
+
+const fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
@@ -0,0 +1,81 @@
+//! Hover command - which stack segment and PR last changed a line
+
+use crate::cli::context::open_workspace;
+use crate::cli::output::{is_json, print_json, println};
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stream, Stylize, hyperlink_url};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::hover::{line_context, parse_location};
+use jj_ryu_core::platform::create_platform_service;
+use std::path::Path;
+
+/// Run the hover command for a `<file>:<line>` location
+///
+/// The file may be relative to the current directory or absolute. With
+/// `--format json` the [`LineContext`](jj_ryu_core::hover::LineContext) is
+/// printed, or `null` when the line isn't part of any stack.
+pub async fn run_hover(path: &Path, location: &str, remote: Option<&str>) -> Result<()> {
+    let (file, line) = parse_location(location)?;
+    let workspace = open_workspace(path)?;
+    let file = repo_relative(workspace.workspace_root(), Path::new(file))?;
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    let context = line_context(&workspace, &graph, platform.as_ref(), &file, line).await?;
+
+    if is_json() {
+        return print_json(&serde_json::to_value(&context)?);
+    }
+    let Some(context) = context else {
+        println!(
+            "{}",
+            format!("{file}:{line} isn't part of any stack").muted()
+        );
+        return Ok(());
+    };
+    let short_id = &context.change_id[..8.min(context.change_id.len())];
+    println!(
+        "{} {}",
+        short_id.accent(),
+        context.description.as_str().emphasis()
+    );
+    match &context.pr {
+        Some(pr) => println!(
+            "Under review in PR #{} ({}): {}",
+            pr.number,
+            context.bookmark.accent(),
+            hyperlink_url(Stream::Stdout, &pr.html_url)
+        ),
+        None => println!(
+            "In {} {}",
+            context.bookmark.accent(),
+            "(not submitted yet)".muted()
+        ),
+    }
+    Ok(())
+}
+
+/// `file` relative to the workspace root, with `/` separators
+fn repo_relative(root: &Path, file: &Path) -> Result<String> {
+    let root = root.canonicalize()?;
+    let file = file
+        .canonicalize()
+        .map_err(|e| Error::InvalidArgument(format!("{}: {e}", file.display())))?;
+    let relative = file.strip_prefix(&root).map_err(|_| {
+        Error::InvalidArgument(format!("{} is outside the workspace", file.display()))
+    })?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
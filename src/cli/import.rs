@@ -0,0 +1,82 @@
+//! Import command - recreate a teammate's stack locally from one of its PRs
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, arrow, check, cross};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::import::{ImportStatus, find_stack_comment, import_stack, parse_pr_reference};
+use jj_ryu_core::platform::create_platform_service;
+use std::path::Path;
+
+/// Run the import command
+///
+/// Reads the stack comment of `pr` (a PR URL or number), fetches the
+/// stack's branches and tracks them as local bookmarks. Bookmarks that
+/// already exist locally are left where they are.
+pub async fn run_import(path: &Path, pr: &str, remote: Option<&str>) -> Result<()> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let pr_number = parse_pr_reference(pr, &platform_config)?;
+    let data = find_stack_comment(platform.as_ref(), pr_number).await?;
+    let imported = import_stack(&mut workspace, &data, &remote_name)?;
+
+    println!(
+        "{} {}",
+        "Imported stack of".emphasis(),
+        format!("#{pr_number}").accent()
+    );
+    for entry in &imported {
+        let pr = format!("#{}", entry.pr_number);
+        match entry.status {
+            ImportStatus::Created => {
+                println!("  {} {} {} {pr}", check(), entry.bookmark.accent(), arrow());
+            }
+            ImportStatus::Unchanged => println!(
+                "  {} {} {} {pr} {}",
+                check(),
+                entry.bookmark.accent(),
+                arrow(),
+                "(already up to date)".muted()
+            ),
+            ImportStatus::Conflicting => println!(
+                "  {} {} {} {pr} {}",
+                cross(),
+                entry.bookmark.accent(),
+                arrow(),
+                format!("(a local {} points elsewhere; left alone)", entry.bookmark).warn()
+            ),
+            ImportStatus::Missing => println!(
+                "  {} {} {} {pr} {}",
+                cross(),
+                entry.bookmark.accent(),
+                arrow(),
+                format!("(not on {remote_name}, likely merged)").muted()
+            ),
+        }
+    }
+
+    if let Some(leaf) = imported
+        .iter()
+        .rev()
+        .find(|entry| entry.status != ImportStatus::Missing)
+    {
+        println!();
+        println!(
+            "{}",
+            format!("Build on it with `jj new {}`", leaf.bookmark).muted()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,356 @@
+//! Land command - merge every ready stack root, restack, repeat; or merge
+//! one stack bottom-up
+
+use crate::cli::CliProgress;
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{self, Stream, Stylize, check, spinner_style};
+use crate::cli::sync::{SyncOptions, run_sync};
+use crate::cli::width::{display_width, fit_url};
+use anstream::println;
+use indicatif::ProgressBar;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::lock::{StackLock, acquire_stack_lock, post_lock_comment, release_lock_comment};
+use jj_ryu_core::merge::{
+    LandOptions, MERGE_POLL_INTERVAL, MERGE_TIMEOUT, StackRoot, land_stack, merge_and_wait,
+    stack_roots,
+};
+use jj_ryu_core::platform::{PlatformService, create_platform_service};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::restack::{Restack, restack_onto_trunk, trunk_commit_id};
+use jj_ryu_core::submit::{analyze_submission, select_bookmark_for_segment};
+use jj_ryu_core::types::{ChangeGraph, ChecksState, MergeMethod, PrReadiness};
+use std::path::Path;
+use std::time::Duration;
+
+/// Run the land command with `--all-ready`
+///
+/// Each round fetches, finds every stack's bottom PR that is approved and
+/// green, merges them (with `method`, else `merge-method` from the config
+/// files, else squash), rebases the segments above onto the new trunk and
+/// syncs them so their next PR targets trunk. Rounds repeat until no bottom
+/// PR is ready; a failed merge stops the train.
+pub async fn run_land(
+    path: &Path,
+    remote: Option<&str>,
+    method: Option<MergeMethod>,
+) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+    let method = method
+        .or(workspace.config().merge_method)
+        .unwrap_or_default();
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+    drop(workspace);
+
+    let mut landed = 0;
+    loop {
+        let mut workspace = open_workspace(path)?;
+        workspace.git_fetch(&remote_name)?;
+        let graph = build_change_graph(&workspace)?;
+        let default_branch = workspace.default_branch()?;
+
+        let (ready, waiting): (Vec<StackRoot>, Vec<StackRoot>) =
+            stack_roots(&graph, platform.as_ref(), &default_branch)
+                .await?
+                .into_iter()
+                .partition(|root| root.readiness.is_ready());
+        if ready.is_empty() {
+            for root in &waiting {
+                println!(
+                    "{}",
+                    format!(
+                        "Not landing {} (#{}): {}",
+                        root.bookmark,
+                        root.pr.number,
+                        waiting_on(&root.readiness)
+                    )
+                    .muted()
+                );
+            }
+            break;
+        }
+
+        let mut restacks: Vec<Restack> = Vec::new();
+        for root in &ready {
+            land_root(&workspace, platform.as_ref(), root, method).await?;
+            landed += 1;
+            restacks.extend(root.restacks.iter().cloned());
+        }
+
+        workspace.git_fetch(&remote_name)?;
+        for root in &ready {
+            workspace.delete_bookmark(&root.bookmark)?;
+            println!("{} Deleted bookmark {}", check(), root.bookmark.accent());
+        }
+        if let Some(trunk) = trunk_commit_id(&workspace)? {
+            restack_onto_trunk(&mut workspace, &restacks, &trunk, &CliProgress::compact()).await?;
+        }
+        println!();
+        drop(workspace);
+
+        for next in ready.iter().filter_map(|root| root.rest.first()) {
+            run_sync(
+                path,
+                Some(&remote_name),
+                SyncOptions {
+                    stack: Some(next),
+                    include_synced: true,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+        }
+    }
+
+    if landed == 0 {
+        println!("{}", "Nothing ready to land".muted());
+    } else {
+        println!(
+            "{} Landed {landed} PR{}",
+            check(),
+            if landed == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Run the land command with `--stack`
+///
+/// Merges the PRs of the stack up to `bookmark` bottom-up while holding the
+/// stack's lock (see [`land_stack`]), optionally waiting for each PR's
+/// checks. Then fetches, deletes the landed bookmarks, rebases whatever is
+/// stacked above them onto the new trunk and syncs it, as `--all-ready`
+/// does. A PR that can't land stops the run after that cleanup.
+pub async fn run_land_stack(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    method: Option<MergeMethod>,
+    wait_for_checks: bool,
+) -> Result<()> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+    let method = method
+        .or(workspace.config().merge_method)
+        .unwrap_or_default();
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    workspace.git_fetch(&remote_name)?;
+    let graph = build_change_graph(&workspace)?;
+    if !graph.bookmarks.contains_key(bookmark) {
+        return Err(Error::BookmarkNotFound(bookmark.to_string()));
+    }
+    let analysis = analyze_submission(&graph, bookmark)?;
+    let names: Vec<String> = analysis
+        .segments
+        .iter()
+        .map(|segment| segment.bookmark.name.clone())
+        .collect();
+
+    let mut prs = Vec::with_capacity(names.len());
+    for branch in platform.find_branch_prs(&names).await? {
+        let Some(pr) = branch.prs.into_iter().next() else {
+            return Err(Error::InvalidArgument(format!(
+                "'{}' has no open PR - run `ryu submit {bookmark}` first",
+                branch.head_branch
+            )));
+        };
+        if pr.is_draft {
+            return Err(Error::InvalidArgument(format!(
+                "PR #{} for '{}' is a draft - publish it first",
+                pr.number, branch.head_branch
+            )));
+        }
+        prs.push((branch.head_branch, pr));
+    }
+    let default_branch = workspace.default_branch()?;
+    if let Some((_, bottom)) = prs.first().filter(|(_, pr)| pr.base_ref != default_branch) {
+        return Err(Error::InvalidArgument(format!(
+            "PR #{} targets '{}', not '{default_branch}' - run `ryu sync` first",
+            bottom.number, bottom.base_ref
+        )));
+    }
+
+    let lock = StackLock::new("land", names);
+    let guard = acquire_stack_lock(workspace.workspace_root(), bookmark, lock.clone())?;
+    let bottom_pr = prs.first().map(|(_, pr)| pr.number);
+    if let Some(number) = bottom_pr {
+        if let Err(e) = post_lock_comment(platform.as_ref(), number, &lock).await {
+            println!("{}", format!("Could not post lock comment: {e}").warn());
+            note_warning();
+        }
+    }
+    let landing = land_stack(
+        platform.as_ref(),
+        &prs,
+        &default_branch,
+        LandOptions::new(method, wait_for_checks),
+        &CliProgress::verbose(),
+    )
+    .await;
+    if let Some(number) = bottom_pr {
+        if let Err(e) = release_lock_comment(platform.as_ref(), number).await {
+            println!("{}", format!("Could not release lock comment: {e}").warn());
+            note_warning();
+        }
+    }
+    guard.release()?;
+    let landing = landing?;
+
+    let Some(last) = landing.merged.last() else {
+        return landing.stopped.map_or(Ok(()), |(_, e)| Err(e));
+    };
+    let restacks = restacks_above(&graph, last);
+    workspace.git_fetch(&remote_name)?;
+    for name in &landing.merged {
+        workspace.delete_bookmark(name)?;
+        println!("{} Deleted bookmark {}", check(), name.accent());
+    }
+    if let Some(trunk) = trunk_commit_id(&workspace)? {
+        restack_onto_trunk(&mut workspace, &restacks, &trunk, &CliProgress::compact()).await?;
+    }
+    println!();
+    drop(workspace);
+
+    for next in restacks
+        .iter()
+        .filter_map(|restack| restack.bookmarks.first())
+    {
+        run_sync(
+            path,
+            Some(&remote_name),
+            SyncOptions {
+                stack: Some(next),
+                include_synced: true,
+                ..SyncOptions::default()
+            },
+        )
+        .await?;
+    }
+
+    let landed = landing.merged.len();
+    println!(
+        "{} Landed {landed} PR{}",
+        check(),
+        if landed == 1 { "" } else { "s" }
+    );
+    landing.stopped.map_or(Ok(()), |(_, e)| Err(e))
+}
+
+/// Segments stacked directly on `bookmark`'s, to rebase onto trunk once it
+/// lands, each with every bookmark above it
+fn restacks_above(graph: &ChangeGraph, bookmark: &str) -> Vec<Restack> {
+    let Some(change_id) = graph.bookmark_to_change_id.get(bookmark) else {
+        return Vec::new();
+    };
+    graph
+        .children_of(change_id)
+        .into_iter()
+        .filter_map(|child| {
+            let root = graph.segment(child)?.changes.last()?;
+            let mut bookmarks = Vec::new();
+            let mut pending = vec![child];
+            while let Some(id) = pending.pop() {
+                if let Some(segment) = graph.segment(id) {
+                    bookmarks.push(select_bookmark_for_segment(segment, None).name);
+                }
+                pending.extend(graph.children_of(id).into_iter().rev());
+            }
+            Some(Restack {
+                root_commit_id: root.commit_id.clone(),
+                bookmarks,
+            })
+        })
+        .collect()
+}
+
+/// Merge one stack root while holding its stack's lock
+async fn land_root(
+    workspace: &JjWorkspace,
+    platform: &dyn PlatformService,
+    root: &StackRoot,
+    method: MergeMethod,
+) -> Result<()> {
+    let mut locked = vec![root.bookmark.clone()];
+    locked.extend(root.rest.iter().cloned());
+    let lock_key = locked.last().cloned().unwrap_or_default();
+    let lock = StackLock::new("land", locked);
+    let guard = acquire_stack_lock(workspace.workspace_root(), &lock_key, lock.clone())?;
+    if let Err(e) = post_lock_comment(platform, root.pr.number, &lock).await {
+        println!("{}", format!("Could not post lock comment: {e}").warn());
+        note_warning();
+    }
+
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(spinner_style());
+    spinner.set_message(format!(
+        "Merging {} (#{}, {method})...",
+        root.bookmark, root.pr.number
+    ));
+    spinner.enable_steady_tick(Duration::from_millis(80));
+    let merged = merge_and_wait(
+        platform,
+        &root.pr,
+        method,
+        MERGE_POLL_INTERVAL,
+        MERGE_TIMEOUT,
+    )
+    .await;
+    spinner.finish_and_clear();
+
+    if let Err(e) = release_lock_comment(platform, root.pr.number).await {
+        println!("{}", format!("Could not release lock comment: {e}").warn());
+        note_warning();
+    }
+    guard.release()?;
+    merged?;
+    let used = display_width(&format!(
+        "{} Merged {} #{} ()",
+        style::CHECK,
+        root.bookmark,
+        root.pr.number
+    ));
+    println!(
+        "{} Merged {} {} ({})",
+        check(),
+        root.bookmark.accent(),
+        format!("#{}", root.pr.number).accent(),
+        fit_url(Stream::Stdout, used, &root.pr.html_url).muted()
+    );
+    Ok(())
+}
+
+/// What a root that isn't ready is waiting on
+fn waiting_on(readiness: &PrReadiness) -> String {
+    let mut reasons = Vec::new();
+    if !readiness.approved {
+        reasons.push("not approved".to_string());
+    }
+    if matches!(
+        readiness.checks,
+        ChecksState::Pending | ChecksState::Failing
+    ) {
+        reasons.push(readiness.checks.to_string());
+    }
+    reasons.join(", ")
+}
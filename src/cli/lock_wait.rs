@@ -0,0 +1,48 @@
+//! Spinner shown while waiting for another process's repo lock
+
+use crate::cli::style::{Stylize, spinner_style};
+use indicatif::ProgressBar;
+use jj_ryu_core::repo::{LockWait, LockWaitObserver};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Shows one spinner per wait, naming the competing operation once known
+#[derive(Default)]
+struct LockWaitSpinner {
+    spinner: Mutex<Option<ProgressBar>>,
+}
+
+impl LockWaitObserver for LockWaitSpinner {
+    fn waiting(&self, wait: &LockWait) {
+        let mut spinner = self.spinner.lock().unwrap_or_else(|e| e.into_inner());
+        let spinner = spinner.get_or_insert_with(|| {
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(spinner_style());
+            spinner.enable_steady_tick(Duration::from_millis(80));
+            spinner
+        });
+        let holder = wait
+            .competing_operation
+            .as_deref()
+            .map_or_else(String::new, |op| {
+                format!(" (other process: {})", op.accent())
+            });
+        spinner.set_message(format!(
+            "Repo is locked by another process, retrying {}{holder} [attempt {}]",
+            wait.action.emphasis(),
+            wait.attempt
+        ));
+    }
+
+    fn finished(&self) {
+        let mut spinner = self.spinner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(spinner) = spinner.take() {
+            spinner.finish_and_clear();
+        }
+    }
+}
+
+/// Observer showing a spinner whenever a repo operation waits for a lock
+pub fn lock_wait_spinner() -> Box<dyn LockWaitObserver> {
+    Box::new(LockWaitSpinner::default())
+}
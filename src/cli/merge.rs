@@ -0,0 +1,160 @@
+//! Merge command - land the bottom PR of a stack and restack the rest
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{self, Stream, Stylize, check, spinner_style};
+use crate::cli::sync::{SyncOptions, run_sync};
+use crate::cli::width::{display_width, fit_url};
+use anstream::println;
+use indicatif::ProgressBar;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::lock::{StackLock, acquire_stack_lock, post_lock_comment, release_lock_comment};
+use jj_ryu_core::merge::{MERGE_POLL_INTERVAL, MERGE_TIMEOUT, merge_and_wait};
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::submit::select_bookmark_for_segment;
+use jj_ryu_core::types::MergeMethod;
+use std::path::Path;
+use std::time::Duration;
+
+/// Run the merge command
+///
+/// Merges the PR for `bookmark`, which must be at the bottom of its stack
+/// (with `method`, else `merge-method` from the config files, else squash),
+/// then fetches, deletes the bookmark locally and syncs the rest of the
+/// stack so its next PR targets trunk. The stack is locked meanwhile so a
+/// concurrent `sync` or `submit` doesn't retarget under us.
+pub async fn run_merge(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    method: Option<MergeMethod>,
+) -> Result<()> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+    let method = method
+        .or(workspace.config().merge_method)
+        .unwrap_or_default();
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    let stack = graph
+        .stacks
+        .iter()
+        .find(|stack| {
+            stack
+                .segments
+                .iter()
+                .any(|seg| seg.bookmarks.iter().any(|b| b.name == bookmark))
+        })
+        .ok_or_else(|| Error::BookmarkNotFound(bookmark.to_string()))?;
+    if !stack.segments[0]
+        .bookmarks
+        .iter()
+        .any(|b| b.name == bookmark)
+    {
+        let bottom = select_bookmark_for_segment(&stack.segments[0], None).name;
+        return Err(Error::InvalidArgument(format!(
+            "'{bookmark}' is not at the bottom of its stack - merge '{bottom}' first"
+        )));
+    }
+
+    let pr = platform
+        .find_existing_pr(bookmark)
+        .await?
+        .ok_or_else(|| Error::InvalidArgument(format!("'{bookmark}' has no open PR")))?;
+    let default_branch = workspace.default_branch()?;
+    if pr.base_ref != default_branch {
+        return Err(Error::InvalidArgument(format!(
+            "PR #{} targets '{}', not '{default_branch}' - run `ryu sync` first",
+            pr.number, pr.base_ref
+        )));
+    }
+
+    let rest: Vec<String> = stack.segments[1..]
+        .iter()
+        .map(|seg| select_bookmark_for_segment(seg, None).name)
+        .collect();
+    let mut locked = vec![bookmark.to_string()];
+    locked.extend(rest.iter().cloned());
+    let lock_key = locked.last().cloned().unwrap_or_default();
+    let lock = StackLock::new("merge", locked);
+    let guard = acquire_stack_lock(workspace.workspace_root(), &lock_key, lock.clone())?;
+    if let Err(e) = post_lock_comment(platform.as_ref(), pr.number, &lock).await {
+        println!("{}", format!("Could not post lock comment: {e}").warn());
+        note_warning();
+    }
+
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(spinner_style());
+    spinner.set_message(format!("Merging PR #{} ({method})...", pr.number));
+    spinner.enable_steady_tick(Duration::from_millis(80));
+    let merged = merge_and_wait(
+        platform.as_ref(),
+        &pr,
+        method,
+        MERGE_POLL_INTERVAL,
+        MERGE_TIMEOUT,
+    )
+    .await;
+    spinner.finish_and_clear();
+
+    if let Err(e) = release_lock_comment(platform.as_ref(), pr.number).await {
+        println!("{}", format!("Could not release lock comment: {e}").warn());
+        note_warning();
+    }
+    guard.release()?;
+    merged?;
+    let used = display_width(&format!("{} Merged #{} ()", style::CHECK, pr.number));
+    println!(
+        "{} Merged {} ({})",
+        check(),
+        format!("#{}", pr.number).accent(),
+        fit_url(Stream::Stdout, used, &pr.html_url).muted()
+    );
+
+    workspace.git_fetch(&remote_name)?;
+    workspace.delete_bookmark(bookmark)?;
+    println!("{} Deleted bookmark {}", check(), bookmark.accent());
+
+    let Some(next) = rest.first() else {
+        return Ok(());
+    };
+    if method != MergeMethod::Merge {
+        // Squash and rebase merges rewrite the commits, so the rest of the
+        // stack still sits on the originals until it is rebased
+        println!(
+            "{}",
+            format!(
+                "Rebase the rest of the stack onto trunk to drop the merged commits: \
+                 jj rebase -b {next} -d 'trunk()' --skip-emptied"
+            )
+            .muted()
+        );
+    }
+    println!();
+    drop(workspace);
+
+    run_sync(
+        path,
+        Some(&remote_name),
+        SyncOptions {
+            stack: Some(next),
+            include_synced: true,
+            ..SyncOptions::default()
+        },
+    )
+    .await?;
+
+    Ok(())
+}
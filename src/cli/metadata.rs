@@ -0,0 +1,64 @@
+//! Reviewers, labels and assignees for new PRs, shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, check};
+use jj_ryu_core::config::RyuConfig;
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::submit::segment_metadata;
+use jj_ryu_core::types::{NarrowedBookmarkSegment, PrMetadata, PullRequest};
+
+/// Set config and trailer metadata on each newly created PR
+///
+/// Like project filing, failures are reported as warnings since the PRs
+/// already exist.
+pub async fn apply_pr_metadata(
+    platform: &dyn PlatformService,
+    config: &RyuConfig,
+    segments: &[NarrowedBookmarkSegment],
+    prs: &[PullRequest],
+) {
+    for pr in prs {
+        let changes = segments
+            .iter()
+            .find(|s| s.bookmark.name == pr.head_ref)
+            .map_or(&[][..], |s| s.changes.as_slice());
+        let metadata = segment_metadata(config, changes);
+        if metadata.is_empty() {
+            continue;
+        }
+        match platform.update_pr_metadata(pr.number, &metadata).await {
+            Ok(()) => println!(
+                "{} PR #{} {}",
+                check(),
+                pr.number,
+                describe(&metadata).muted()
+            ),
+            Err(e) => {
+                println!(
+                    "{}",
+                    format!(
+                        "Could not set reviewers and labels on PR #{}: {e}",
+                        pr.number
+                    )
+                    .warn()
+                );
+                note_warning();
+            }
+        }
+    }
+}
+
+/// "reviewers alice; labels stacked" for what was set
+fn describe(metadata: &PrMetadata) -> String {
+    [
+        ("reviewers", &metadata.reviewers),
+        ("labels", &metadata.labels),
+        ("assignees", &metadata.assignees),
+    ]
+    .into_iter()
+    .filter(|(_, values)| !values.is_empty())
+    .map(|(what, values)| format!("{what} {}", values.join(", ")))
+    .collect::<Vec<_>>()
+    .join("; ")
+}
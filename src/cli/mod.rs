@@ -2,15 +2,100 @@
 //!
 //! Command implementations for the `ryu` binary.
 
+mod adopt;
 mod analyze;
+mod annotate;
 mod auth;
+mod auto_bookmark;
+mod base;
+mod bisect;
+mod clean;
+mod codeowners;
+mod comment;
+mod compat;
+mod context;
+mod duplicates;
+mod env;
+mod force;
+mod foreign;
+mod gc;
+mod hover;
+mod import;
+mod land;
+mod lock_wait;
+mod merge;
+mod metadata;
+mod names;
+mod new_stack;
+mod next_steps;
+mod open;
+mod outcome;
+mod output;
+mod plan;
+mod platform;
+mod pr;
 mod progress;
+mod project;
+mod pull;
+mod read_only;
+mod rebase_continue;
+mod retry_wait;
+mod reviewers;
+mod schema;
+mod selfupdate;
+mod stats;
+mod status;
+mod strict;
 pub mod style;
 mod submit;
+mod suggest_base;
 mod sync;
+#[cfg(feature = "tui")]
+mod tui;
+mod undo;
+mod verbose;
+mod verify;
+mod webhook;
+mod width;
 
+pub use adopt::run_adopt;
 pub use analyze::run_analyze;
+pub use annotate::run_annotate;
 pub use auth::run_auth;
+pub use base::{run_base_list, run_base_set, run_base_unset};
+pub use bisect::run_bisect_stack;
+pub use clean::run_clean;
+pub use comment::{MigrateScope, run_comment_migrate};
+pub use compat::warn_on_newer_jj;
+pub use context::set_run_context;
+pub use env::run_env;
+pub use gc::run_gc;
+pub use hover::run_hover;
+pub use import::run_import;
+pub use land::{run_land, run_land_stack};
+pub use lock_wait::lock_wait_spinner;
+pub use merge::run_merge;
+pub use new_stack::run_new_stack;
+pub use open::run_open;
+pub use outcome::Outcome;
+pub use output::{OutputFormat, set_format};
+pub use plan::{run_apply, run_plan};
+pub use platform::{PlatformArg, set_platform};
+pub use pr::run_pr_sync_body;
 pub use progress::CliProgress;
-pub use submit::{SubmitOptions, SubmitScope, run_submit};
+pub use read_only::read_only_notice;
+pub use rebase_continue::run_rebase_continue;
+pub use retry_wait::retry_notice;
+pub use schema::run_schema;
+pub use selfupdate::run_selfupdate;
+pub use stats::{begin_usage, finish_usage, note_stack_size, run_stats};
+pub use status::run_status;
+pub use strict::{check_strict, enable_strict};
+pub use submit::{SubmitOptions, SubmitScope, run_submit, run_submit_all};
+pub use suggest_base::run_suggest_base;
 pub use sync::{SyncOptions, run_sync};
+#[cfg(feature = "tui")]
+pub use tui::run_ui;
+pub use undo::{begin_undo, finish_undo, run_undo};
+pub use verbose::{enable_run_summary, print_run_summary};
+pub use verify::run_verify;
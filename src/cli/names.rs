@@ -0,0 +1,90 @@
+//! Bookmark name guard shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::style::{Stylize, arrow, bullet, check};
+use dialoguer::Confirm;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::{SubmissionAnalysis, check_bookmark_names};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+/// Refuse to push bookmarks whose names git or `branch-pattern` reject
+///
+/// Every bad name is reported with the rule it breaks and a normalized name.
+/// With `offer_rename`, on a terminal, each one that has a suggestion can be
+/// renamed with `jj bookmark rename`; the renames are returned (old to new)
+/// so the caller can re-read the stack. Dry runs only report.
+pub fn check_bookmark_name_rules(
+    workspace: &mut JjWorkspace,
+    analysis: &SubmissionAnalysis,
+    offer_rename: bool,
+    dry_run: bool,
+) -> Result<HashMap<String, String>> {
+    let issues = check_bookmark_names(
+        analysis.segments.iter().map(|s| s.bookmark.name.as_str()),
+        workspace.config().branch_pattern.as_deref(),
+    )?;
+    if issues.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "{} bookmark name{} can't be pushed:",
+            issues.len(),
+            if issues.len() == 1 { "" } else { "s" }
+        )
+        .warn()
+    );
+    for issue in &issues {
+        println!(
+            "  {} {} {}",
+            bullet(),
+            issue.bookmark.accent(),
+            issue.problems.join(", ").muted()
+        );
+        if let Some(suggestion) = &issue.suggestion {
+            println!("    {} {}", arrow(), suggestion.accent());
+        }
+    }
+    println!();
+
+    if dry_run {
+        return Ok(HashMap::new());
+    }
+    if !offer_rename || !std::io::stdin().is_terminal() {
+        return Err(Error::InvalidBookmarkNames(issues.len()));
+    }
+
+    let mut renamed = HashMap::new();
+    for issue in &issues {
+        let Some(suggestion) = &issue.suggestion else {
+            continue;
+        };
+        let rename = Confirm::new()
+            .with_prompt(format!("Rename {} to {suggestion}?", issue.bookmark))
+            .default(true)
+            .interact()
+            .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?;
+        if !rename {
+            continue;
+        }
+        workspace.rename_bookmark(&issue.bookmark, suggestion)?;
+        println!(
+            "{} Renamed {} to {}",
+            check(),
+            issue.bookmark,
+            suggestion.accent()
+        );
+        renamed.insert(issue.bookmark.clone(), suggestion.clone());
+    }
+    println!();
+
+    let remaining = issues.len() - renamed.len();
+    if remaining > 0 {
+        return Err(Error::InvalidBookmarkNames(remaining));
+    }
+    Ok(renamed)
+}
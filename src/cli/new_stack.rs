@@ -0,0 +1,63 @@
+//! New-stack command - scaffold a stack from a template
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::style::{Stylize, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::template::create_stack;
+use std::path::Path;
+
+/// Run the new-stack command
+///
+/// Creates one empty, described change per segment of `template` (from the
+/// config files) on `onto`, each on the previous one, and bookmarks them
+/// `<name>/<segment>`. The working copy is left where it is.
+pub fn run_new_stack(path: &Path, name: &str, template: &str, onto: &str) -> Result<()> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let config = workspace.config();
+    let shape = config.templates.get(template).ok_or_else(|| {
+        let known: Vec<&str> = config.templates.keys().map(String::as_str).collect();
+        Error::Config(if known.is_empty() {
+            format!(
+                "no stack template '{template}': add [templates.{template}] to the config files"
+            )
+        } else {
+            format!(
+                "no stack template '{template}' (known: {})",
+                known.join(", ")
+            )
+        })
+    })?;
+    let changes = shape.scaffold(name, config.branch_prefix.as_deref())?;
+
+    let base = match workspace.resolve_revset(onto)?.as_slice() {
+        [entry] => entry.commit_id.clone(),
+        _ => {
+            return Err(Error::InvalidArgument(format!(
+                "'{onto}' doesn't resolve to a single revision"
+            )));
+        }
+    };
+    let created = create_stack(&mut workspace, &changes, &base)?;
+
+    for (change, commit_id) in changes.iter().zip(&created) {
+        println!(
+            "{} Created {} {} {}",
+            check(),
+            change.bookmark.accent(),
+            commit_id[..8.min(commit_id.len())].muted(),
+            change.description
+        );
+    }
+    if let Some(first) = changes.first() {
+        println!();
+        println!(
+            "Start on the first segment with: {}",
+            format!("jj edit {}", first.bookmark).accent()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Next-step suggestions printed after submit and sync
+
+use crate::cli::output::println;
+use crate::cli::style::{Stylize, arrow};
+use jj_ryu_core::next_steps::{stack_prs, suggest_next_steps};
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::types::PullRequest;
+use std::collections::HashMap;
+
+/// Print what to run next for `stacks` (bookmark names, bottom first)
+///
+/// `prs` maps bookmarks to their PRs after the run. Suggestions are only a
+/// hint: if the platform can't tell the PRs' state, nothing is printed.
+pub async fn print_next_steps(
+    platform: &dyn PlatformService,
+    stacks: &[Vec<String>],
+    prs: &HashMap<String, PullRequest>,
+    default_branch: &str,
+    excluded: usize,
+) {
+    let mut stack_states = Vec::with_capacity(stacks.len());
+    for bookmarks in stacks {
+        let Ok(stack) = stack_prs(platform, bookmarks, prs).await else {
+            return;
+        };
+        stack_states.push(stack);
+    }
+
+    let suggestions = suggest_next_steps(&stack_states, default_branch, excluded);
+    if suggestions.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", "Next steps:".emphasis());
+    for suggestion in &suggestions {
+        println!(
+            "  {} {}: {}",
+            arrow(),
+            suggestion.reason,
+            suggestion.command.accent()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Open command - open a bookmark's PR or compare view in the browser
+
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stream, Stylize, arrow, hyperlink_url};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::submit::{analyze_submission, get_base_branch};
+use jj_ryu_core::types::Platform;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Run the open command
+///
+/// Opens the bookmark's PR if it has one. Otherwise (or with `compare`)
+/// opens the platform's compare view of the bookmark against the base it
+/// would be submitted to, for eyeballing the diff before submitting.
+pub async fn run_open(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    compare: bool,
+) -> Result<()> {
+    let workspace = open_workspace(path)?;
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+
+    let existing_pr = if compare {
+        None
+    } else {
+        let platform = create_platform_service(&platform_config, workspace.run()).await?;
+        platform.find_existing_pr(bookmark).await?
+    };
+
+    let url = if let Some(pr) = existing_pr {
+        pr.html_url
+    } else {
+        let graph = build_change_graph(&workspace)?;
+        let mut analysis = analyze_submission(&graph, bookmark)?;
+        analysis.base_overrides = RyuState::load(workspace.workspace_root())?
+            .base_overrides
+            .into_iter()
+            .collect();
+        let base = get_base_branch(
+            bookmark,
+            &analysis.segments,
+            &workspace.default_branch()?,
+            &analysis.base_overrides,
+        )?;
+
+        // The compare view only shows what the remote has
+        let unpushed: Vec<&str> = analysis
+            .segments
+            .iter()
+            .rev()
+            .take(2)
+            .map(|s| &s.bookmark)
+            .filter(|b| !b.has_remote || !b.is_synced)
+            .map(|b| b.name.as_str())
+            .collect();
+        if !unpushed.is_empty() {
+            println!(
+                "{}",
+                format!(
+                    "Not pushed to {remote_name}: {} (the compare view may be stale or empty)",
+                    unpushed.join(", ")
+                )
+                .warn()
+            );
+        }
+
+        platform_config.compare_url(&base, bookmark)
+    };
+
+    println!("{} {}", arrow(), hyperlink_url(Stream::Stdout, &url));
+    // fake:// URLs point nowhere a browser could go
+    if platform_config.platform == Platform::Fake {
+        return Ok(());
+    }
+    if let Err(e) = open_in_browser(&url) {
+        println!("{}", format!("Could not open browser: {e}").warn());
+    }
+
+    Ok(())
+}
+
+/// Open a URL with `$BROWSER` or the platform's default opener
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut cmd = if let Ok(browser) = std::env::var("BROWSER") {
+        Command::new(browser)
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Not `cmd /C start`: cmd would treat `&` in the URL as a separator
+        let mut cmd = Command::new("rundll32");
+        cmd.arg("url.dll,FileProtocolHandler");
+        cmd
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = cmd
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "opener exited with {status}"
+        )))
+    }
+}
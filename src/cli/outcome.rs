@@ -0,0 +1,38 @@
+//! Command outcomes mapped to process exit codes
+
+use std::process::ExitCode;
+
+/// Exit code for a dry run that found changes to make
+///
+/// Not 1, which any error exits with, nor 2, which clap uses for usage
+/// errors, so scripts can tell a pending change from a mistyped command.
+pub const EXIT_CHANGES_PENDING: u8 = 3;
+
+/// What a command found, for scripts checking the exit code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Outcome {
+    /// Finished; nothing left to do
+    #[default]
+    Done,
+    /// A dry run found changes that a real run would make
+    ChangesPending,
+}
+
+impl Outcome {
+    /// `ChangesPending` if `pending`, else `Done`
+    pub const fn pending_if(pending: bool) -> Self {
+        if pending {
+            Self::ChangesPending
+        } else {
+            Self::Done
+        }
+    }
+
+    /// Process exit code: 0 when done, [`EXIT_CHANGES_PENDING`] otherwise
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            Self::Done => ExitCode::SUCCESS,
+            Self::ChangesPending => ExitCode::from(EXIT_CHANGES_PENDING),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Output format selection (`--format`)
+//!
+//! With `--format json`, stdout carries exactly one JSON document. Human
+//! output from the command and its shared helpers is still written, but to
+//! stderr, so warnings stay visible without corrupting the document.
+
+use clap::ValueEnum;
+use jj_ryu_core::error::Result;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How a command reports its outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Styled text for people
+    #[default]
+    Text,
+    /// A single JSON document on stdout
+    Json,
+}
+
+/// Whether JSON output was selected for this run
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Select the output format for this run. Call once at startup.
+pub fn set_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Whether JSON output was selected for this run
+pub fn is_json() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Print `value` as the command's JSON document
+pub fn print_json(value: &Value) -> Result<()> {
+    anstream::println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// `println!` for human output: stdout, or stderr under `--format json`
+macro_rules! println {
+    ($($arg:tt)*) => {
+        if $crate::cli::output::is_json() {
+            anstream::eprintln!($($arg)*)
+        } else {
+            anstream::println!($($arg)*)
+        }
+    };
+}
+
+pub(crate) use println;
@@ -0,0 +1,178 @@
+//! Plan and apply commands - submit from another machine
+//!
+//! `ryu plan` does the read-only half of `submit` and writes the plan to a
+//! file; `ryu apply` executes that file, e.g. on a CI runner holding a bot's
+//! push rights and token.
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::force::check_force_push;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{CHECK, Stylize, arrow, check, cross};
+use crate::cli::{CliProgress, Outcome};
+use anstream::{eprintln, println};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::lock::check_stack_lock;
+use jj_ryu_core::platform::{create_comment_platform_service, create_platform_service};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::submit::{
+    ExportedPlan, PlanCompatibility, SubmissionAnalysis, analyze_submission,
+    create_submission_plan, drafts_by_default, execute_submission, tracking_issue_enabled,
+};
+use jj_ryu_core::types::PlatformConfig;
+use std::path::Path;
+
+/// Run the plan command
+///
+/// Plans submitting `bookmark` like `ryu submit` would and writes the
+/// [`ExportedPlan`] to `output`. Nothing is pushed and no PRs change; the
+/// platform is only read.
+pub async fn run_plan(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    output: &Path,
+) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let (remote_name, platform_config) = remote_platform(&workspace, remote)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    if !graph.bookmarks.contains_key(bookmark) {
+        return Err(Error::BookmarkNotFound(bookmark.to_string()));
+    }
+    let mut analysis = analyze_submission(&graph, bookmark)?;
+    let state = RyuState::load(workspace.workspace_root())?;
+    analysis.base_overrides = state.base_overrides.into_iter().collect();
+
+    let default_branch = workspace.default_branch()?;
+    let mut plan =
+        create_submission_plan(&analysis, platform.as_ref(), &remote_name, &default_branch).await?;
+    plan.tracking_issue = tracking_issue_enabled(workspace.config())?;
+    plan.stack_info = workspace.config().stack_info();
+    plan.interdiff_comments = workspace.config().interdiff_comments.unwrap_or(true);
+    if drafts_by_default(workspace.config())? {
+        plan.draft_new_prs();
+    }
+
+    let steps = plan.execution_steps.len();
+    let exported = ExportedPlan::new(plan, &platform_config, workspace.operation_id()?);
+    std::fs::write(output, serde_json::to_string_pretty(&exported)? + "\n")?;
+    println!(
+        "{} Wrote plan for {} ({} step{}) to {}",
+        check(),
+        bookmark.accent(),
+        steps.accent(),
+        if steps == 1 { "" } else { "s" },
+        output.display()
+    );
+    Ok(())
+}
+
+/// Run the apply command
+///
+/// Executes a plan written by `ryu plan` after checking it was made for this
+/// repository and that its bookmarks haven't moved. A dry run reports
+/// [`Outcome::ChangesPending`] if the plan has steps.
+pub async fn run_apply(path: &Path, file: &Path, dry_run: bool) -> Result<Outcome> {
+    let contents = std::fs::read_to_string(file)?;
+    let exported: ExportedPlan = serde_json::from_str(&contents)?;
+    let plan = &exported.plan;
+
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let (_, platform_config) = remote_platform(&workspace, Some(&plan.remote))?;
+    let compatibility = exported.check_compatible(
+        &platform_config,
+        &workspace.operation_id()?,
+        &workspace.local_bookmarks()?,
+    )?;
+    if compatibility == PlanCompatibility::SameCommits {
+        println!(
+            "{}",
+            "Repo is at a different operation than the plan, but every bookmark is unchanged"
+                .muted()
+        );
+    }
+
+    // Same guard as submit: respect push-force = false on this machine too
+    let analysis = SubmissionAnalysis {
+        target_bookmark: plan
+            .segments
+            .last()
+            .map(|s| s.bookmark.name.clone())
+            .unwrap_or_default(),
+        segments: plan.segments.clone(),
+        base_overrides: std::collections::HashMap::new(),
+    };
+    check_force_push(&workspace, &analysis, &plan.remote)?;
+
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+    let comment_platform = create_comment_platform_service(&platform_config, workspace.run())?;
+    let state = RyuState::load(workspace.workspace_root())?;
+    if let Some(lock) = check_stack_lock(&state, platform.as_ref(), plan).await? {
+        return Err(lock.to_error());
+    }
+
+    println!(
+        "{} {} ({}, planned {})",
+        "Applying plan for".emphasis(),
+        analysis.target_bookmark.accent(),
+        format!("{}/{}", exported.owner, exported.repo),
+        exported.created_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    for step in &plan.execution_steps {
+        println!("  {} {}", arrow(), step);
+    }
+    println!();
+
+    let result = execute_submission(
+        plan,
+        &mut workspace,
+        platform.as_ref(),
+        comment_platform.as_deref(),
+        &CliProgress::verbose(),
+        dry_run,
+    )
+    .await?;
+
+    if dry_run {
+        return Ok(Outcome::pending_if(!plan.is_empty()));
+    }
+    println!();
+    if result.success {
+        println!(
+            "{} {} pushed, {} created, {} updated",
+            format!("{CHECK} Plan applied:").success(),
+            result.pushed_bookmarks.len().accent(),
+            result.created_prs.len().accent(),
+            result.updated_prs.len().accent()
+        );
+    } else {
+        eprintln!("{} Applying the plan failed", cross());
+        for err in &result.errors {
+            eprintln!("  {}", err.error());
+        }
+    }
+    Ok(Outcome::Done)
+}
+
+/// The remote to use (explicit, configured or detected) and its platform
+fn remote_platform(
+    workspace: &JjWorkspace,
+    remote: Option<&str>,
+) -> Result<(String, PlatformConfig)> {
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let config = platform_for_remote(workspace, &remote_info.url)?;
+    Ok((remote_name, config))
+}
@@ -0,0 +1,75 @@
+//! Platform selection (`--platform`)
+//!
+//! Commands normally talk to the platform detected from the remote URL.
+//! `--platform fake`, or `platform = "fake"` in the config files, swaps in
+//! the simulated platform kept in the workspace.
+//!
+//! With `fork-remote` configured, bookmarks are pushed to the fork and PRs
+//! on the primary remote's repository are opened from the fork's branches.
+
+use clap::ValueEnum;
+use jj_ryu_core::config::PlatformChoice;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::platform::resolve_platform_config;
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::types::PlatformConfig;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Platform to use instead of the detected one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PlatformArg {
+    /// Simulated platform kept in the workspace (no tokens or network)
+    Fake,
+}
+
+/// Whether `--platform fake` was given for this run
+static FAKE_PLATFORM: AtomicBool = AtomicBool::new(false);
+
+/// Select the platform for this run. Call once at startup.
+pub fn set_platform(platform: Option<PlatformArg>) {
+    FAKE_PLATFORM.store(platform == Some(PlatformArg::Fake), Ordering::Relaxed);
+}
+
+/// Platform chosen by `--platform`
+pub fn platform_flag() -> Option<PlatformChoice> {
+    FAKE_PLATFORM
+        .load(Ordering::Relaxed)
+        .then_some(PlatformChoice::Fake)
+}
+
+/// Platform chosen by `--platform`, then by the config files
+fn platform_choice(workspace: &JjWorkspace) -> Option<PlatformChoice> {
+    platform_flag().or(workspace.config().platform)
+}
+
+/// Platform config for the remote at `url`, honoring the platform choice
+///
+/// When `fork-remote` names a fork of that repository on the same host, PR
+/// head branches are looked up in (and opened from) the fork.
+pub fn platform_for_remote(workspace: &JjWorkspace, url: &str) -> Result<PlatformConfig> {
+    let choice = platform_choice(workspace);
+    let mut config = resolve_platform_config(url, workspace.workspace_root(), choice)?;
+    if let Some(fork_url) = fork_url(workspace)? {
+        let fork = resolve_platform_config(&fork_url, workspace.workspace_root(), choice)?;
+        if fork.platform == config.platform
+            && fork.host == config.host
+            && fork.owner != config.owner
+        {
+            config.head_owner = Some(fork.owner);
+        }
+    }
+    Ok(config)
+}
+
+/// URL of the `fork-remote`, if one is configured
+fn fork_url(workspace: &JjWorkspace) -> Result<Option<String>> {
+    let Some(fork) = &workspace.config().fork_remote else {
+        return Ok(None);
+    };
+    workspace
+        .git_remotes()?
+        .into_iter()
+        .find(|r| &r.name == fork)
+        .map(|r| Some(r.url))
+        .ok_or_else(|| Error::RemoteNotFound(fork.clone()))
+}
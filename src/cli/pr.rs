@@ -0,0 +1,78 @@
+//! PR command - maintain existing PRs outside of submit
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::submit::{DiffLine, analyze_submission, plan_body_updates};
+use std::path::Path;
+
+/// Re-render PR bodies from the current commit messages
+///
+/// Covers the bookmark's PR, or with `stack` every PR from trunk up to it.
+/// With `diff`, prints what would change without updating anything.
+pub async fn run_pr_sync_body(
+    path: &Path,
+    bookmark: &str,
+    remote: Option<&str>,
+    stack: bool,
+    diff: bool,
+) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    let analysis = analyze_submission(&graph, bookmark)?;
+    let segments = if stack {
+        &analysis.segments[..]
+    } else {
+        &analysis.segments[analysis.segments.len().saturating_sub(1)..]
+    };
+
+    let updates = plan_body_updates(platform.as_ref(), segments).await?;
+    if updates.is_empty() {
+        println!("{}", "No PRs found - run `ryu submit` first".muted());
+        return Ok(());
+    }
+
+    for update in &updates {
+        let label = format!("#{} {}", update.pr.number, update.bookmark);
+        if !update.is_changed() {
+            println!("{} {} {}", check(), label.accent(), "up to date".muted());
+            continue;
+        }
+
+        if diff {
+            println!("{}", label.emphasis());
+            for line in update.diff() {
+                match line {
+                    DiffLine::Same(l) => println!("  {}", l.muted()),
+                    DiffLine::Removed(l) => println!("{}", format!("- {l}").error()),
+                    DiffLine::Added(l) => println!("{}", format!("+ {l}").success()),
+                }
+            }
+            println!();
+            continue;
+        }
+
+        platform
+            .update_pr_body(update.pr.number, &update.rendered)
+            .await?;
+        println!("{} {} body updated", check(), label.accent());
+    }
+
+    Ok(())
+}
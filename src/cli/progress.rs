@@ -1,11 +1,13 @@
 //! Shared CLI progress callback with styled output and spinners
 
-use crate::cli::style::{Stream, Stylize, check, cross, hyperlink_url};
+use crate::cli::strict::note_warning;
+use crate::cli::style::{self, Stream, Stylize, check, cross};
+use crate::cli::width::{display_width, fit_url};
 use anstream::{eprintln, print, println};
 use async_trait::async_trait;
-use jj_ryu::error::Error;
-use jj_ryu::submit::{Phase, ProgressCallback, PushStatus};
-use jj_ryu::types::PullRequest;
+use jj_ryu_core::error::Error;
+use jj_ryu_core::submit::{Phase, ProgressCallback, PushStatus, Warning};
+use jj_ryu_core::types::PullRequest;
 use std::io::Write;
 
 /// CLI progress callback that prints to stdout with styled output
@@ -37,7 +39,15 @@ impl ProgressCallback for CliProgress {
             println!("{}...", phase.to_string().emphasis());
         } else {
             match phase {
-                Phase::Executing | Phase::AddingComments => {
+                Phase::Restacking
+                | Phase::Executing
+                | Phase::ClosingRenamed
+                | Phase::Mirroring
+                | Phase::UpdatingNotes
+                | Phase::UpdatingTrackingIssue
+                | Phase::PostingInterdiffs
+                | Phase::AddingComments
+                | Phase::Merging => {
                     println!("  {}...", phase.to_string().muted());
                 }
                 _ => {}
@@ -45,6 +55,11 @@ impl ProgressCallback for CliProgress {
         }
     }
 
+    async fn on_segment_restacked(&self, bookmark: &str) {
+        let indent = if self.verbose { "  " } else { "    " };
+        println!("{indent}{} Restacked {}", check(), bookmark.emphasis());
+    }
+
     async fn on_bookmark_push(&self, bookmark: &str, status: PushStatus) {
         if self.verbose {
             match &status {
@@ -88,6 +103,32 @@ impl ProgressCallback for CliProgress {
         }
     }
 
+    async fn on_mirror_push(&self, bookmark: &str, remote: &str, status: PushStatus) {
+        let indent = if self.verbose { "  " } else { "    " };
+        match &status {
+            PushStatus::Success => {
+                println!(
+                    "{indent}{} Mirrored {} to {}",
+                    check(),
+                    bookmark.emphasis(),
+                    remote.accent()
+                );
+            }
+            PushStatus::Failed(msg) => {
+                eprintln!("{indent}{} {}", cross(), msg.error());
+            }
+            PushStatus::Started | PushStatus::AlreadySynced => {
+                println!(
+                    "{indent}{} {} {} on {}",
+                    "-".muted(),
+                    bookmark.accent(),
+                    status.to_string().muted(),
+                    remote.accent()
+                );
+            }
+        }
+    }
+
     async fn on_pr_created(&self, bookmark: &str, pr: &PullRequest) {
         let pr_num = format!("#{}", pr.number);
         if self.verbose {
@@ -97,13 +138,14 @@ impl ProgressCallback for CliProgress {
                 pr_num.accent(),
                 bookmark.emphasis()
             );
-            println!("    {}", hyperlink_url(Stream::Stdout, &pr.html_url));
+            println!("    {}", fit_url(Stream::Stdout, 4, &pr.html_url));
         } else {
+            let used = display_width(&format!("    Created PR {pr_num} for {bookmark} ()"));
             println!(
                 "    Created PR {} for {} ({})",
                 pr_num.accent(),
                 bookmark.accent(),
-                hyperlink_url(Stream::Stdout, &pr.html_url)
+                fit_url(Stream::Stdout, used, &pr.html_url)
             );
         }
     }
@@ -126,6 +168,22 @@ impl ProgressCallback for CliProgress {
         }
     }
 
+    async fn on_pr_merged(&self, bookmark: &str, pr: &PullRequest) {
+        let pr_num = format!("#{}", pr.number);
+        let indent = if self.verbose { "  " } else { "    " };
+        let used = display_width(&format!(
+            "{indent}{} Merged PR {pr_num} for {bookmark} ()",
+            style::CHECK
+        ));
+        println!(
+            "{indent}{} Merged PR {} for {} ({})",
+            check(),
+            pr_num.accent(),
+            bookmark.emphasis(),
+            fit_url(Stream::Stdout, used, &pr.html_url).muted()
+        );
+    }
+
     async fn on_error(&self, err: &Error) {
         if self.verbose {
             eprintln!("{}: {}", "error".error(), err);
@@ -134,6 +192,15 @@ impl ProgressCallback for CliProgress {
         }
     }
 
+    async fn on_warning(&self, warning: &Warning) {
+        note_warning();
+        if self.verbose {
+            eprintln!("{}: {}", "warning".warn(), warning);
+        } else {
+            eprintln!("    {}: {}", "warning".warn(), warning);
+        }
+    }
+
     async fn on_message(&self, message: &str) {
         if self.verbose {
             println!("{message}");
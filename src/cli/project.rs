@@ -0,0 +1,43 @@
+//! Project/board filing shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, check};
+use jj_ryu_core::config::RyuConfig;
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::types::PullRequest;
+
+/// Environment variable naming the project for `--project`
+pub const PROJECT_ENV: &str = "RYU_PROJECT";
+
+/// Project new PRs are filed into: `--project`, else `RYU_PROJECT`, else the
+/// `project` config key
+pub fn requested_project(flag: Option<&str>, config: &RyuConfig) -> Option<String> {
+    flag.map(str::to_string)
+        .or_else(|| std::env::var(PROJECT_ENV).ok())
+        .or_else(|| config.project.clone())
+}
+
+/// Add newly created PRs to a project
+///
+/// The PRs already exist at this point, so failures are reported as
+/// warnings rather than failing the command.
+pub async fn add_to_project(platform: &dyn PlatformService, prs: &[PullRequest], project: &str) {
+    for pr in prs {
+        match platform.add_pr_to_project(pr, project).await {
+            Ok(()) => println!(
+                "{} Added PR #{} to {}",
+                check(),
+                pr.number,
+                project.accent()
+            ),
+            Err(e) => {
+                println!(
+                    "{}",
+                    format!("Could not add PR #{} to {project}: {e}", pr.number).warn()
+                );
+                note_warning();
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Pulling in commits others pushed to PR branches, for sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, bullet, check};
+use dialoguer::Confirm;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::pull::{PushedHead, find_remote_commits, pull_remote_commits};
+use jj_ryu_core::repo::JjWorkspace;
+use std::io::IsTerminal;
+
+/// Rebase commits pushed on top of `heads` since they were recorded onto
+/// the local changes
+///
+/// Each branch is offered in turn on a terminal; declining keeps the local
+/// change as it is, so the next push replaces the remote commits. Without a
+/// terminal they're pulled in, since that loses nothing. Returns the
+/// bookmarks that took commits in.
+pub fn pull_pushed_commits(
+    workspace: &mut JjWorkspace,
+    heads: &[PushedHead],
+    remote: &str,
+) -> Result<Vec<String>> {
+    let found = find_remote_commits(workspace, heads, remote)?;
+    if found.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    note_warning();
+    println!(
+        "{}",
+        format!(
+            "{} branch{} on {remote} gained commits since your last push:",
+            found.len(),
+            if found.len() == 1 { "" } else { "es" }
+        )
+        .warn()
+    );
+    for commits in &found {
+        println!(
+            "  {} {} {}",
+            bullet(),
+            commits.bookmark.accent(),
+            format!(
+                "({} commit{} by {})",
+                commits.count,
+                if commits.count == 1 { "" } else { "s" },
+                commits.authors.join(", ")
+            )
+            .muted()
+        );
+    }
+    println!();
+
+    let ask = std::io::stdin().is_terminal();
+    let mut pulled = Vec::new();
+    for commits in &found {
+        if ask
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Rebase them onto your {} change? (no: the next push drops them)",
+                    commits.bookmark
+                ))
+                .default(true)
+                .interact()
+                .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?
+        {
+            continue;
+        }
+        pull_remote_commits(workspace, commits)?;
+        println!(
+            "{} Pulled {} commit{} into {}",
+            check(),
+            commits.count,
+            if commits.count == 1 { "" } else { "s" },
+            commits.bookmark.accent()
+        );
+        pulled.push(commits.bookmark.clone());
+    }
+    println!();
+
+    Ok(pulled)
+}
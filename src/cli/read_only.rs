@@ -0,0 +1,19 @@
+//! Notice shown for each write read-only mode skips
+
+use crate::cli::style::Stylize;
+use anstream::eprintln;
+use jj_ryu_core::read_only::ReadOnlyObserver;
+
+/// Prints one line per skipped write, so the run shows what it would change
+struct ReadOnlyNotice;
+
+impl ReadOnlyObserver for ReadOnlyNotice {
+    fn skipped(&self, action: &str) {
+        eprintln!("{}", format!("Read-only: skipped {action}").warn());
+    }
+}
+
+/// Observer printing a notice whenever read-only mode skips a write
+pub fn read_only_notice() -> Box<dyn ReadOnlyObserver> {
+    Box::new(ReadOnlyNotice)
+}
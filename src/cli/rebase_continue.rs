@@ -0,0 +1,63 @@
+//! Rebase-continue command - finish a restack that stopped on conflicts
+
+use crate::cli::CliProgress;
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::style::{Stylize, check};
+use crate::cli::sync::{SyncOptions, run_sync};
+use anstream::println;
+use jj_ryu_core::error::Result;
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::restack::continue_restack;
+use std::path::Path;
+
+/// Run the rebase-continue command
+///
+/// Checks the conflicts a restack (from `sync` or `land`) stopped on are
+/// resolved, rebases the stacks it hadn't got to yet, then syncs every
+/// stack with a restacked bookmark so the rewritten bookmarks are pushed
+/// and their PRs retargeted.
+pub async fn run_rebase_continue(path: &Path, remote: Option<&str>) -> Result<()> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let restacked = continue_restack(&mut workspace, &CliProgress::compact()).await?;
+    println!(
+        "{} Restack finished: {} bookmark{} rebased",
+        check(),
+        restacked.len().accent(),
+        if restacked.len() == 1 { "" } else { "s" }
+    );
+
+    // One sync per affected stack, named by its leaf
+    let graph = build_change_graph(&workspace)?;
+    let leaves: Vec<String> = graph
+        .stacks
+        .iter()
+        .filter(|stack| {
+            stack
+                .segments
+                .iter()
+                .flat_map(|segment| &segment.bookmarks)
+                .any(|bookmark| restacked.contains(&bookmark.name))
+        })
+        .filter_map(|stack| stack.segments.last()?.bookmarks.first())
+        .map(|bookmark| bookmark.name.clone())
+        .collect();
+    drop(workspace);
+
+    for leaf in &leaves {
+        println!();
+        run_sync(
+            path,
+            remote,
+            SyncOptions {
+                stack: Some(leaf.as_str()),
+                restack: false,
+                ..SyncOptions::default()
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Notice shown while a platform request waits to be retried
+
+use crate::cli::style::Stylize;
+use anstream::eprintln;
+use jj_ryu_core::platform::{RetryObserver, RetryWait};
+
+/// Prints one line per pause, so a rate-limit wait doesn't look like a hang
+struct RetryNotice;
+
+impl RetryObserver for RetryNotice {
+    fn waiting(&self, wait: &RetryWait) {
+        let reason = if wait.rate_limited {
+            "Rate limited by the platform"
+        } else {
+            "Platform request failed"
+        };
+        eprintln!(
+            "{}",
+            format!(
+                "{reason}, retrying {} in {}s [attempt {}]",
+                wait.request,
+                wait.delay.as_secs().max(1),
+                wait.attempt
+            )
+            .warn()
+        );
+    }
+}
+
+/// Observer printing a notice whenever a platform request pauses before a
+/// retry
+pub fn retry_notice() -> Box<dyn RetryObserver> {
+    Box::new(RetryNotice)
+}
@@ -0,0 +1,62 @@
+//! Reviewer pool assignment shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{Stylize, arrow, check};
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::reviewers::{ReviewerPool, assign_reviewers};
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::types::PullRequest;
+use std::path::Path;
+
+/// Request a reviewer from `pool` on each newly created PR
+///
+/// The round-robin position is persisted so the rotation continues on the
+/// next run. Like project filing, failures are reported as warnings since
+/// the PRs already exist.
+pub async fn assign_pool_reviewers(
+    workspace_root: &Path,
+    platform: &dyn PlatformService,
+    prs: &[PullRequest],
+    pool: &ReviewerPool,
+) {
+    if prs.is_empty() {
+        return;
+    }
+
+    let mut state = match RyuState::load(workspace_root) {
+        Ok(state) => state,
+        Err(e) => {
+            println!("{}", format!("Could not assign reviewers: {e}").warn());
+            note_warning();
+            return;
+        }
+    };
+
+    match assign_reviewers(platform, pool, prs, &mut state.reviewer_cursor).await {
+        Ok(assignments) => {
+            for assignment in &assignments {
+                println!(
+                    "{} PR #{} {} {}",
+                    check(),
+                    assignment.pr_number,
+                    arrow(),
+                    assignment.reviewer.accent()
+                );
+            }
+        }
+        Err(e) => {
+            println!("{}", format!("Could not assign reviewers: {e}").warn());
+            note_warning();
+        }
+    }
+
+    // Keep whatever rotation happened, even after a failed request
+    if let Err(e) = state.save(workspace_root) {
+        println!(
+            "{}",
+            format!("Could not save reviewer rotation: {e}").warn()
+        );
+        note_warning();
+    }
+}
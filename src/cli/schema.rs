@@ -0,0 +1,23 @@
+//! Schema command - print JSON Schemas for ryu's JSON output
+
+use anstream::println;
+use jj_ryu_core::error::Result;
+use jj_ryu_core::schema::SchemaType;
+use serde_json::{Map, Value};
+
+/// Run the schema command
+///
+/// Prints one schema by name, or all of them in an object keyed by name.
+pub fn run_schema(name: Option<&str>) -> Result<()> {
+    let json = match name {
+        Some(name) => name.parse::<SchemaType>()?.schema().to_value(),
+        None => Value::Object(
+            SchemaType::ALL
+                .into_iter()
+                .map(|t| (t.name().to_string(), t.schema().to_value()))
+                .collect::<Map<_, _>>(),
+        ),
+    };
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
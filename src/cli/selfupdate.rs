@@ -0,0 +1,178 @@
+//! Selfupdate command - replace the ryu binary with the latest release
+
+use crate::cli::style::{Stylize, arrow, check, spinner_style};
+use anstream::println;
+use indicatif::ProgressBar;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::update::{
+    RELEASE_PUBLIC_KEY, download_asset, fetch_releases, http_client, release_asset_name,
+    releases_api_url, select_update, signature_asset_name, verify_signature,
+};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// Run the selfupdate command
+///
+/// Downloads the release archive for this platform, checks its minisign
+/// signature against the public key built into ryu, and swaps it in for the
+/// running binary. Unsigned or badly signed archives are never installed.
+pub async fn run_selfupdate(check_only: bool, prerelease: bool, force: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let client = http_client()?;
+    let releases = fetch_releases(&client, &releases_api_url()).await?;
+
+    // Pre-release builds keep following pre-releases
+    let include_prereleases = prerelease || current.contains('-');
+    let Some(release) = select_update(&releases, current, include_prereleases) else {
+        println!("{} ryu {} is up to date", check(), current.accent());
+        return Ok(());
+    };
+    let latest = release.version();
+    println!(
+        "New version available: {} {} {}",
+        current.muted(),
+        arrow(),
+        latest.accent()
+    );
+    if check_only {
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()?;
+    if let Some((manager, command)) = package_manager(&exe).filter(|_| !force) {
+        return Err(Error::SelfUpdate(format!(
+            "ryu was installed with {manager}; update it with `{command}` (or pass --force)"
+        )));
+    }
+
+    let asset_name = release_asset_name(
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        cfg!(target_env = "musl"),
+    )
+    .ok_or_else(|| {
+        Error::SelfUpdate(format!(
+            "no prebuilt binary for {} {}; install with `cargo install jj-ryu`",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let asset = release.asset(&asset_name).ok_or_else(|| {
+        Error::SelfUpdate(format!("release {latest} has no {asset_name} archive"))
+    })?;
+    let signature_name = signature_asset_name(&asset_name);
+    let signature_asset = release.asset(&signature_name).ok_or_else(|| {
+        Error::SelfUpdate(format!(
+            "release {latest} has no {signature_name}; refusing to install an unsigned archive"
+        ))
+    })?;
+
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(spinner_style());
+    spinner.set_message(format!("Downloading {}...", asset_name.emphasis()));
+    spinner.enable_steady_tick(Duration::from_millis(80));
+    let archive = download_asset(&client, asset).await?;
+    let signature = download_asset(&client, signature_asset).await?;
+    spinner.finish_and_clear();
+
+    verify_signature(
+        &archive,
+        &String::from_utf8_lossy(&signature),
+        RELEASE_PUBLIC_KEY,
+        &asset_name,
+    )?;
+    println!("{} Verified {} signature", check(), asset_name.accent());
+
+    let staging = std::env::temp_dir().join(format!("ryu-update-{}", std::process::id()));
+    fs::create_dir_all(&staging)?;
+    let result = install_from_archive(&staging, &asset_name, &archive, &exe);
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+
+    println!(
+        "{} Updated ryu {} {} {}",
+        check(),
+        current.muted(),
+        arrow(),
+        latest.accent()
+    );
+    Ok(())
+}
+
+/// Detect binaries managed by a package manager, with its update command
+fn package_manager(exe: &Path) -> Option<(&'static str, &'static str)> {
+    let path = exe.to_string_lossy().replace('\\', "/");
+    if path.contains("/node_modules/") {
+        Some(("npm", "npm update -g jj-ryu"))
+    } else if path.contains("/.cargo/bin/") {
+        Some(("cargo", "cargo install jj-ryu"))
+    } else if path.contains("/Cellar/") || path.contains("/linuxbrew/") {
+        Some(("Homebrew", "brew upgrade jj-ryu"))
+    } else {
+        None
+    }
+}
+
+/// Unpack the archive and replace `exe` with the binary inside
+fn install_from_archive(
+    staging: &Path,
+    asset_name: &str,
+    archive: &[u8],
+    exe: &Path,
+) -> Result<()> {
+    let archive_path = staging.join(asset_name);
+    fs::write(&archive_path, archive)?;
+
+    // tar handles .tar.gz everywhere and .zip with the bsdtar Windows ships
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(staging)
+        .status()
+        .map_err(|e| Error::SelfUpdate(format!("failed to run tar: {e}")))?;
+    if !status.success() {
+        return Err(Error::SelfUpdate(format!(
+            "failed to extract {asset_name} (tar exited with {status})"
+        )));
+    }
+
+    let binary = staging.join(if cfg!(windows) { "ryu.exe" } else { "ryu" });
+    if !binary.is_file() {
+        return Err(Error::SelfUpdate(format!(
+            "{asset_name} does not contain a ryu binary"
+        )));
+    }
+    replace_executable(&binary, exe)
+}
+
+/// Swap `new` in for the executable at `current`
+///
+/// The new binary is staged next to the old one so the final rename stays on
+/// one filesystem.
+fn replace_executable(new: &Path, current: &Path) -> Result<()> {
+    let dir = current
+        .parent()
+        .ok_or_else(|| Error::SelfUpdate(format!("{} has no parent", current.display())))?;
+    let cannot_write =
+        |e: std::io::Error| Error::SelfUpdate(format!("cannot write to {}: {e}", dir.display()));
+
+    let staged = dir.join(".ryu-update");
+    fs::copy(new, &staged).map_err(cannot_write)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)).map_err(cannot_write)?;
+    }
+
+    // Windows can't overwrite a running executable, but it can rename it
+    if cfg!(windows) {
+        let old = current.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(current, &old).map_err(cannot_write)?;
+    }
+    fs::rename(&staged, current).map_err(cannot_write)?;
+    Ok(())
+}
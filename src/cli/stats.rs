@@ -0,0 +1,120 @@
+//! Stats command - local usage stats, recorded when telemetry is opted into
+//!
+//! `main` brackets each command with [`begin_usage`] and [`finish_usage`];
+//! commands that handle stacks report their size with [`note_stack_size`].
+//! Nothing is recorded unless [`TelemetrySettings`] says telemetry is on.
+
+use crate::cli::style::{Stylize, check};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::telemetry::{
+    TELEMETRY_ENV, TelemetrySettings, UsageEvent, load_events, record_event, summarize,
+    upload_summary, usage_path,
+};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Command being run and when it started
+static STARTED: OnceLock<(&'static str, Instant)> = OnceLock::new();
+
+/// Largest stack the command handled, in segments (0 if none)
+static STACK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Note the start of `command`
+pub fn begin_usage(command: &'static str) {
+    let _ = STARTED.set((command, Instant::now()));
+}
+
+/// Note that the command handled a stack of `segments` segments
+pub fn note_stack_size(segments: usize) {
+    STACK_SIZE.fetch_max(segments, Ordering::Relaxed);
+}
+
+/// Record the finished command if telemetry is on
+///
+/// `error` is the failure the command ended with; only its category is
+/// kept. Recording never fails the command.
+pub fn finish_usage(error: Option<&anyhow::Error>) {
+    let Some(&(command, started)) = STARTED.get() else {
+        return;
+    };
+    let enabled = TelemetrySettings::load().is_ok_and(|settings| settings.enabled);
+    let Some(path) = usage_path().filter(|_| enabled) else {
+        return;
+    };
+
+    let category = error.map(|e| e.downcast_ref::<Error>().map_or("other", Error::category));
+    let stack_size = Some(STACK_SIZE.load(Ordering::Relaxed)).filter(|&size| size > 0);
+    let event = UsageEvent::new(command, started.elapsed(), stack_size, category);
+    let _ = record_event(&path, &event);
+}
+
+/// Run the stats command
+///
+/// Prints whether telemetry is on and a per-command summary of the recorded
+/// usage. With `upload`, posts the summary to `telemetry-upload-url`; with
+/// `clear`, deletes the recorded usage.
+pub async fn run_stats(upload: bool, clear: bool) -> Result<()> {
+    let settings = TelemetrySettings::load()?;
+    let path = usage_path()
+        .ok_or_else(|| Error::Config("could not determine the home directory".to_string()))?;
+
+    if clear {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        println!("{} Cleared recorded usage", check());
+        return Ok(());
+    }
+
+    if settings.enabled {
+        println!(
+            "Telemetry is {}, recording to {}",
+            "on".accent(),
+            path.display().to_string().muted()
+        );
+    } else {
+        println!(
+            "Telemetry is {} - set `telemetry = true` in the user config or {TELEMETRY_ENV}=1 to record usage",
+            "off".accent()
+        );
+    }
+
+    let events = load_events(&path)?;
+    if events.is_empty() {
+        println!("{}", "No usage recorded".muted());
+        return Ok(());
+    }
+    let summary = summarize(&events);
+
+    println!();
+    let width = summary.commands.keys().map(String::len).max().unwrap_or(0);
+    for (command, stats) in &summary.commands {
+        let stack = stats
+            .max_stack_size
+            .map(|size| format!(", stacks up to {size}"))
+            .unwrap_or_default();
+        println!(
+            "  {command:<width$}  {} runs, {} failed, median {}ms, p90 {}ms{stack}",
+            stats.runs, stats.failures, stats.median_ms, stats.p90_ms
+        );
+    }
+    if !summary.errors.is_empty() {
+        println!();
+        println!("{}", "Failures by category".emphasis());
+        for (category, count) in &summary.errors {
+            println!("  {category:<width$}  {count}");
+        }
+    }
+
+    if upload {
+        let url = settings.upload_url.as_deref().ok_or_else(|| {
+            Error::Config("set telemetry-upload-url in the user config to upload".to_string())
+        })?;
+        upload_summary(&summary, url).await?;
+        println!();
+        println!("{} Uploaded the summary", check());
+    }
+    Ok(())
+}
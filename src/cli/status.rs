@@ -0,0 +1,183 @@
+//! Status command - show each stack's PRs and their state
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::strict::note_warning;
+use crate::cli::style::{
+    self, Stream, Stylize, check, checks_marker, pipe, review_marker, up_arrow,
+};
+use crate::cli::width::{display_width, fit, fit_url};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::status::{BookmarkStatus, stack_statuses};
+use jj_ryu_core::types::PrState;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Run the status command
+///
+/// Prints every stack like the default view, with each bookmark's PR number,
+/// state and URL, and a warning where an open PR targets the wrong base.
+pub async fn run_status(path: &Path, remote: Option<&str>) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    if graph.stacks.is_empty() {
+        println!("{}", "No bookmark stacks found".muted());
+        return Ok(());
+    }
+
+    let overrides: HashMap<_, _> = RyuState::load(workspace.workspace_root())?
+        .base_overrides
+        .into_iter()
+        .collect();
+    let statuses = stack_statuses(
+        &graph,
+        platform.as_ref(),
+        &workspace.default_branch()?,
+        &overrides,
+    )
+    .await?;
+
+    println!("{}", "Stack Status".emphasis());
+    println!();
+
+    let mut mismatched = 0;
+    for (i, stack) in statuses.iter().enumerate() {
+        println!(
+            "{} {}",
+            format!("Stack #{}:", i + 1).emphasis(),
+            stack.leaf.accent()
+        );
+        println!();
+
+        // Newest/leaf first, like the default view
+        for status in stack.bookmarks.iter().rev() {
+            print_bookmark_status(status);
+            if status.base_mismatch() {
+                mismatched += 1;
+            }
+        }
+
+        println!("  {}", "trunk()".muted());
+        println!();
+    }
+
+    println!(
+        "{}",
+        format!(
+            "Legend: {} = synced with remote, {} = needs push, {} = working copy",
+            style::CHECK,
+            style::UP_ARROW,
+            style::CURRENT
+        )
+        .muted()
+    );
+    println!(
+        "{}",
+        format!(
+            "        after a PR: checks {} passing, {} failing, {} running",
+            style::CHECK,
+            style::CROSS,
+            style::PENDING
+        )
+        .muted()
+    );
+    if mismatched > 0 {
+        println!();
+        println!(
+            "{} PR{} target the wrong base - run {} to retarget",
+            mismatched.accent(),
+            if mismatched == 1 { "" } else { "s" },
+            "ryu sync".accent()
+        );
+    }
+
+    Ok(())
+}
+
+/// Print one bookmark: its name, sync state and PR, then its commits
+fn print_bookmark_status(status: &BookmarkStatus) {
+    let bookmark = &status.segment.bookmark;
+    let sync_status = if bookmark.is_synced {
+        format!(" {}", check())
+    } else if bookmark.has_remote {
+        format!(" {}", up_arrow())
+    } else {
+        String::new()
+    };
+    let used = display_width(&format!("       [{}]{sync_status}  ", bookmark.name));
+
+    let pr_status = match &status.pr {
+        Some((pr, state)) => {
+            let state_label = match state {
+                PrState::Open => state.to_string().success().to_string(),
+                PrState::Draft | PrState::Closed => state.to_string().muted().to_string(),
+                PrState::Merged => state.to_string().accent().to_string(),
+            };
+            let checks = status
+                .checks
+                .and_then(checks_marker)
+                .map(|marker| format!(" {marker}"))
+                .unwrap_or_default();
+            let review = status
+                .review
+                .map(|review| format!(" {}", review_marker(review)))
+                .unwrap_or_default();
+            let used = used + display_width(&format!("#{} {state}{checks}{review} ", pr.number));
+            format!(
+                "  #{} {state_label}{checks}{review} {}",
+                pr.number,
+                fit_url(Stream::Stdout, used, &pr.html_url).muted()
+            )
+        }
+        None => format!("  {}", "no PR".muted()),
+    };
+    println!(
+        "       [{}]{sync_status}{pr_status}",
+        bookmark.name.accent()
+    );
+
+    if let Some((pr, _)) = status.pr.as_ref().filter(|_| status.base_mismatch()) {
+        println!(
+            "       {}",
+            format!("base is {}, expected {}", pr.base_ref, status.expected_base).warn()
+        );
+        note_warning();
+    }
+
+    for change in &status.segment.changes {
+        let marker = if change.is_working_copy {
+            style::CURRENT
+        } else {
+            style::BULLET
+        };
+        let change_short = &change.change_id[..8.min(change.change_id.len())];
+        let desc = if change.description_first_line.is_empty() {
+            "(no description)"
+        } else {
+            &change.description_first_line
+        };
+        let used = display_width(&format!("    {marker}  {change_short} "));
+        println!(
+            "    {marker}  {} {}",
+            change_short.muted(),
+            fit(Stream::Stdout, used, desc)
+        );
+        println!("    {}", pipe());
+    }
+}
@@ -0,0 +1,61 @@
+//! `--strict`: any warning fails the run
+//!
+//! For CI jobs that gate merges on a perfectly clean stack. Warnings are
+//! still printed where they happen; they are also counted here, and a
+//! strict run that saw any exits with an error once the command is done.
+
+use crate::cli::style::Stylize;
+use anstream::eprintln;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::submit::ProgressEvent;
+use jj_ryu_core::types::ChangeGraph;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fail the run on warnings. Call once at startup.
+pub fn enable_strict() {
+    STRICT.store(true, Ordering::Relaxed);
+}
+
+/// Count a warning that was just reported
+pub fn note_warning() {
+    WARNINGS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count the warnings among progress events recorded for JSON output
+pub fn note_warning_events(events: &[ProgressEvent]) {
+    let warnings = events
+        .iter()
+        .filter(|event| matches!(event, ProgressEvent::Warning { .. }))
+        .count();
+    WARNINGS.fetch_add(warnings, Ordering::Relaxed);
+}
+
+/// Count each bookmark left out of the stacks as a warning
+///
+/// `ryu` with no command lists them already. Other commands only hint at
+/// them, so with `print` set they name them in strict runs, where they'd
+/// otherwise fail without saying why.
+pub fn note_excluded_bookmarks(graph: &ChangeGraph, print: bool) {
+    let excluded = &graph.excluded_bookmarks;
+    WARNINGS.fetch_add(excluded.len(), Ordering::Relaxed);
+    if print && STRICT.load(Ordering::Relaxed) && !excluded.is_empty() {
+        let names: Vec<&str> = excluded.iter().map(|b| b.name.as_str()).collect();
+        eprintln!(
+            "{}: excluded from stacks: {}",
+            "warning".warn(),
+            names.join(", ")
+        );
+    }
+}
+
+/// Fail if this is a strict run and warnings were reported
+pub fn check_strict() -> Result<()> {
+    let warnings = WARNINGS.load(Ordering::Relaxed);
+    if STRICT.load(Ordering::Relaxed) && warnings > 0 {
+        return Err(Error::StrictWarnings(warnings));
+    }
+    Ok(())
+}
@@ -14,6 +14,11 @@
 //! | `.muted()`   | Dim    | stdout | Secondary: hints, metadata      |
 //! | `.emphasis()`| Bold   | stdout | Headers, current action         |
 //!
+//! Those are the default theme's colors. The `theme` key in the user config
+//! picks another palette (`colorblind` swaps green and red for blue and
+//! magenta, `monochrome` drops colors), and `[colors]` overrides single
+//! roles; see [`load_palette`].
+//!
 //! # Usage
 //!
 //! ```ignore
@@ -24,21 +29,196 @@
 //! eprintln!("{}", msg.error());
 //! ```
 
+use jj_ryu_core::config::{ColorOverrides, RyuConfig, Theme};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::types::{ChecksState, ReviewState};
 use std::fmt::{self, Display};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub use owo_colors::Stream;
-use owo_colors::{OwoColorize, Style};
+use owo_colors::{AnsiColors, OwoColorize, Style};
 
 // ============================================================================
-// Style definitions (single source of truth for color palette)
+// Palette (single source of truth for colors)
 // ============================================================================
 
-const ACCENT: Style = Style::new().cyan();
-const SUCCESS: Style = Style::new().green();
-const ERROR: Style = Style::new().red();
-const WARN: Style = Style::new().yellow();
-const MUTED: Style = Style::new().dimmed();
-const EMPHASIS: Style = Style::new().bold();
+/// Semantic role of styled text, resolved against the palette when printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Accent,
+    Success,
+    Error,
+    Warn,
+    Muted,
+    Emphasis,
+}
+
+/// A style for each role
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    accent: Style,
+    success: Style,
+    error: Style,
+    warn: Style,
+    muted: Style,
+    emphasis: Style,
+}
+
+impl Palette {
+    /// The built-in palette of a theme
+    pub const fn theme(theme: Theme) -> Self {
+        match theme {
+            Theme::Default => Self {
+                accent: Style::new().cyan(),
+                success: Style::new().green(),
+                error: Style::new().red(),
+                warn: Style::new().yellow(),
+                muted: Style::new().dimmed(),
+                emphasis: Style::new().bold(),
+            },
+            // Blue against magenta stays apart under red-green color blindness
+            Theme::Colorblind => Self {
+                accent: Style::new().cyan(),
+                success: Style::new().bright_blue(),
+                error: Style::new().magenta().bold(),
+                warn: Style::new().yellow(),
+                muted: Style::new().dimmed(),
+                emphasis: Style::new().bold(),
+            },
+            Theme::Monochrome => Self {
+                accent: Style::new().bold(),
+                success: Style::new(),
+                error: Style::new().bold().underline(),
+                warn: Style::new().underline(),
+                muted: Style::new().dimmed(),
+                emphasis: Style::new().bold(),
+            },
+        }
+    }
+
+    /// The configured theme with the `[colors]` overrides applied
+    pub fn from_config(config: &RyuConfig) -> Result<Self> {
+        let mut palette = Self::theme(config.theme.unwrap_or_default());
+        let ColorOverrides {
+            accent,
+            success,
+            error,
+            warn,
+            muted,
+            emphasis,
+        } = &config.colors;
+        for (role, spec) in [
+            (Role::Accent, accent),
+            (Role::Success, success),
+            (Role::Error, error),
+            (Role::Warn, warn),
+            (Role::Muted, muted),
+            (Role::Emphasis, emphasis),
+        ] {
+            if let Some(spec) = spec {
+                *palette.style_mut(role) = parse_style(spec)?;
+            }
+        }
+        Ok(palette)
+    }
+
+    const fn style(&self, role: Role) -> Style {
+        match role {
+            Role::Accent => self.accent,
+            Role::Success => self.success,
+            Role::Error => self.error,
+            Role::Warn => self.warn,
+            Role::Muted => self.muted,
+            Role::Emphasis => self.emphasis,
+        }
+    }
+
+    const fn style_mut(&mut self, role: Role) -> &mut Style {
+        match role {
+            Role::Accent => &mut self.accent,
+            Role::Success => &mut self.success,
+            Role::Error => &mut self.error,
+            Role::Warn => &mut self.warn,
+            Role::Muted => &mut self.muted,
+            Role::Emphasis => &mut self.emphasis,
+        }
+    }
+}
+
+static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+/// The palette in use, the default theme unless one was loaded
+fn palette() -> &'static Palette {
+    PALETTE.get_or_init(|| Palette::theme(Theme::Default))
+}
+
+/// Use the palette from the user config file. Call once at startup.
+///
+/// Like telemetry, colors are a personal choice, so the repository file is
+/// never consulted.
+pub fn load_palette() -> Result<()> {
+    let config = match RyuConfig::user_path() {
+        Some(path) => RyuConfig::from_file(&path)?.unwrap_or_default(),
+        None => RyuConfig::default(),
+    };
+    let _ = PALETTE.set(Palette::from_config(&config)?);
+    Ok(())
+}
+
+/// Parse a style like `bright-blue bold` or `#d55e00 underline`
+fn parse_style(spec: &str) -> Result<Style> {
+    let mut style = Style::new();
+    for word in spec.split_whitespace() {
+        style = match word.to_ascii_lowercase().as_str() {
+            "bold" => style.bold(),
+            "dimmed" | "dim" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            hex if hex.starts_with('#') => {
+                let (r, g, b) = parse_hex(hex).ok_or_else(|| invalid_style(spec, word))?;
+                style.truecolor(r, g, b)
+            }
+            name => style.color(ansi_color(name).ok_or_else(|| invalid_style(spec, word))?),
+        };
+    }
+    Ok(style)
+}
+
+fn invalid_style(spec: &str, word: &str) -> Error {
+    Error::Config(format!(
+        "invalid color '{word}' in '{spec}': use a color such as red, bright-blue or #d55e00, \
+         and bold, dimmed, italic or underline"
+    ))
+}
+
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    let digits = hex.strip_prefix('#').filter(|d| d.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn ansi_color(name: &str) -> Option<AnsiColors> {
+    Some(match name {
+        "black" => AnsiColors::Black,
+        "red" => AnsiColors::Red,
+        "green" => AnsiColors::Green,
+        "yellow" => AnsiColors::Yellow,
+        "blue" => AnsiColors::Blue,
+        "magenta" => AnsiColors::Magenta,
+        "cyan" => AnsiColors::Cyan,
+        "white" => AnsiColors::White,
+        "bright-black" => AnsiColors::BrightBlack,
+        "bright-red" => AnsiColors::BrightRed,
+        "bright-green" => AnsiColors::BrightGreen,
+        "bright-yellow" => AnsiColors::BrightYellow,
+        "bright-blue" => AnsiColors::BrightBlue,
+        "bright-magenta" => AnsiColors::BrightMagenta,
+        "bright-cyan" => AnsiColors::BrightCyan,
+        "bright-white" => AnsiColors::BrightWhite,
+        _ => return None,
+    })
+}
 
 // ============================================================================
 // Styled wrapper
@@ -52,15 +232,15 @@ const EMPHASIS: Style = Style::new().bold();
 #[derive(Clone, Debug)]
 pub struct Styled<T> {
     value: T,
-    style: Style,
+    role: Role,
     stream: Stream,
 }
 
 impl<T> Styled<T> {
-    const fn new(value: T, style: Style, stream: Stream) -> Self {
+    const fn new(value: T, role: Role, stream: Stream) -> Self {
         Self {
             value,
-            style,
+            role,
             stream,
         }
     }
@@ -84,11 +264,12 @@ impl<T: Display> Display for Styled<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Single point where color detection + rendering happens.
         // owo-colors handles NO_COLOR, CLICOLOR, CLICOLOR_FORCE, TTY detection.
+        let style = palette().style(self.role);
         write!(
             f,
             "{}",
             self.value
-                .if_supports_color(self.stream, |v| v.style(self.style))
+                .if_supports_color(self.stream, |v| v.style(style))
         )
     }
 }
@@ -106,14 +287,14 @@ pub trait Stylize: Display {
     ///
     /// Use for: bookmark names, counts, URLs, commands
     fn accent(&self) -> Styled<&Self> {
-        Styled::new(self, ACCENT, Stream::Stdout)
+        Styled::new(self, Role::Accent, Stream::Stdout)
     }
 
     /// Success color (green) for completion states.
     ///
     /// Use for: checkmarks, "done", successful operations
     fn success(&self) -> Styled<&Self> {
-        Styled::new(self, SUCCESS, Stream::Stdout)
+        Styled::new(self, Role::Success, Stream::Stdout)
     }
 
     /// Error color (red) for failures.
@@ -121,7 +302,7 @@ pub trait Stylize: Display {
     /// Use for: error messages, failure indicators
     /// Default stream: stderr
     fn error(&self) -> Styled<&Self> {
-        Styled::new(self, ERROR, Stream::Stderr)
+        Styled::new(self, Role::Error, Stream::Stderr)
     }
 
     /// Warning color (yellow) for attention-needed states.
@@ -129,21 +310,21 @@ pub trait Stylize: Display {
     /// Use for: warnings, "needs push", uncommitted changes
     /// Default stream: stderr
     fn warn(&self) -> Styled<&Self> {
-        Styled::new(self, WARN, Stream::Stderr)
+        Styled::new(self, Role::Warn, Stream::Stderr)
     }
 
     /// Muted style (dim) for secondary information.
     ///
     /// Use for: hints, metadata, timestamps, de-emphasized text
     fn muted(&self) -> Styled<&Self> {
-        Styled::new(self, MUTED, Stream::Stdout)
+        Styled::new(self, Role::Muted, Stream::Stdout)
     }
 
     /// Emphasis style (bold) for important text.
     ///
     /// Use for: headers, current action, key information
     fn emphasis(&self) -> Styled<&Self> {
-        Styled::new(self, EMPHASIS, Stream::Stdout)
+        Styled::new(self, Role::Emphasis, Stream::Stdout)
     }
 }
 
@@ -175,6 +356,9 @@ pub const PIPE: &str = "│";
 /// Up arrow for "needs push" indicator
 pub const UP_ARROW: &str = "↑";
 
+/// Circling arrow for "checks running" indicator
+pub const PENDING: &str = "⟳";
+
 // ============================================================================
 // Pre-styled symbol helpers
 // ============================================================================
@@ -182,37 +366,81 @@ pub const UP_ARROW: &str = "↑";
 /// Green checkmark for success states.
 #[inline]
 pub const fn check() -> Styled<&'static str> {
-    Styled::new(CHECK, SUCCESS, Stream::Stdout)
+    Styled::new(CHECK, Role::Success, Stream::Stdout)
 }
 
 /// Red cross for error/failure states (renders to stderr by default).
 #[inline]
 pub const fn cross() -> Styled<&'static str> {
-    Styled::new(CROSS, ERROR, Stream::Stderr)
+    Styled::new(CROSS, Role::Error, Stream::Stderr)
 }
 
 /// Cyan arrow for action steps.
 #[inline]
 pub const fn arrow() -> Styled<&'static str> {
-    Styled::new(ARROW, ACCENT, Stream::Stdout)
+    Styled::new(ARROW, Role::Accent, Stream::Stdout)
 }
 
 /// Dimmed bullet for list items.
 #[inline]
 pub const fn bullet() -> Styled<&'static str> {
-    Styled::new(BULLET, MUTED, Stream::Stdout)
+    Styled::new(BULLET, Role::Muted, Stream::Stdout)
 }
 
 /// Dimmed pipe for tree structure.
 #[inline]
 pub const fn pipe() -> Styled<&'static str> {
-    Styled::new(PIPE, MUTED, Stream::Stdout)
+    Styled::new(PIPE, Role::Muted, Stream::Stdout)
 }
 
 /// Yellow up-arrow for "needs push" indicator.
 #[inline]
 pub const fn up_arrow() -> Styled<&'static str> {
-    Styled::new(UP_ARROW, WARN, Stream::Stdout)
+    Styled::new(UP_ARROW, Role::Warn, Stream::Stdout)
+}
+
+/// Marker for a PR's CI state: green check, red cross or yellow circling
+/// arrow; `None` when the PR has no checks.
+pub const fn checks_marker(checks: ChecksState) -> Option<Styled<&'static str>> {
+    match checks {
+        ChecksState::Passing => Some(check()),
+        ChecksState::Failing => Some(Styled::new(CROSS, Role::Error, Stream::Stdout)),
+        ChecksState::Pending => Some(Styled::new(PENDING, Role::Warn, Stream::Stdout)),
+        ChecksState::None => None,
+    }
+}
+
+/// Label for a PR's review state: green when approved, red when changes
+/// were requested, muted while waiting for a reviewer
+pub const fn review_marker(review: ReviewState) -> Styled<&'static str> {
+    match review {
+        ReviewState::Approved => Styled::new("approved", Role::Success, Stream::Stdout),
+        ReviewState::ChangesRequested => {
+            Styled::new("changes requested", Role::Error, Stream::Stdout)
+        }
+        ReviewState::Pending => Styled::new("review pending", Role::Muted, Stream::Stdout),
+    }
+}
+
+// ============================================================================
+// Console setup
+// ============================================================================
+
+/// Whether the console interprets escape sequences
+static ANSI_CONSOLE: AtomicBool = AtomicBool::new(true);
+
+/// Prepare the console for styled output. Call once at startup.
+///
+/// Windows consoles print raw escape codes unless virtual terminal processing
+/// is switched on. Colors written through `anstream` fall back to console API
+/// calls either way, but OSC 8 links have no fallback and are disabled.
+#[cfg_attr(not(windows), allow(clippy::missing_const_for_fn))]
+pub fn init_console() {
+    #[cfg(windows)]
+    ANSI_CONSOLE.store(
+        anstyle_query::windows::enable_ansi_colors().unwrap_or(false),
+        Ordering::Relaxed,
+    );
 }
 
 // ============================================================================
@@ -227,15 +455,27 @@ const fn to_hyperlink_stream(stream: Stream) -> supports_hyperlinks::Stream {
     }
 }
 
+/// Whether `stream` renders OSC 8 hyperlinks
+pub fn hyperlinks_on(stream: Stream) -> bool {
+    ANSI_CONSOLE.load(Ordering::Relaxed) && supports_hyperlinks::on(to_hyperlink_stream(stream))
+}
+
+/// Create a clickable hyperlink showing `text`.
+///
+/// Falls back to plain `text` in terminals that don't support OSC 8 hyperlinks.
+pub fn hyperlink(stream: Stream, text: &str, url: &str) -> String {
+    if hyperlinks_on(stream) {
+        terminal_link::Link::new(text, url).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
 /// Create a clickable hyperlink showing the URL itself.
 ///
 /// Falls back to plain URL text in terminals that don't support OSC 8 hyperlinks.
 pub fn hyperlink_url(stream: Stream, url: &str) -> String {
-    if supports_hyperlinks::on(to_hyperlink_stream(stream)) {
-        terminal_link::Link::new(url, url).to_string()
-    } else {
-        url.to_string()
-    }
+    hyperlink(stream, url, url)
 }
 
 // ============================================================================
@@ -243,7 +483,6 @@ pub fn hyperlink_url(stream: Stream, url: &str) -> String {
 // ============================================================================
 
 use indicatif::ProgressStyle;
-use std::sync::OnceLock;
 
 /// Default spinner style - cyan dots.
 ///
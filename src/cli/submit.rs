@@ -1,18 +1,47 @@
 //! Submit command - submit a bookmark stack as PRs
 
-use crate::cli::CliProgress;
-use crate::cli::style::{CHECK, Stylize, arrow, bullet, cross};
-use anstream::{eprintln, println};
+use crate::cli::auto_bookmark::create_auto_bookmarks;
+use crate::cli::codeowners::print_expected_owners;
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::{open_workspace, run_context};
+use crate::cli::duplicates::resolve_duplicate_prs;
+use crate::cli::force::check_force_push;
+use crate::cli::foreign::check_foreign_commits;
+use crate::cli::metadata::apply_pr_metadata;
+use crate::cli::names::check_bookmark_name_rules;
+use crate::cli::next_steps::print_next_steps;
+use crate::cli::output::{is_json, print_json, println};
+use crate::cli::platform::platform_for_remote;
+use crate::cli::project::{add_to_project, requested_project};
+use crate::cli::reviewers::assign_pool_reviewers;
+use crate::cli::strict::{note_excluded_bookmarks, note_warning, note_warning_events};
+use crate::cli::style::{CHECK, Stylize, arrow, bullet, check, cross};
+use crate::cli::webhook::notify_webhook;
+use crate::cli::{CliProgress, Outcome, note_stack_size};
+use anstream::eprintln;
 use dialoguer::Confirm;
-use jj_ryu::error::{Error, Result};
-use jj_ryu::graph::build_change_graph;
-use jj_ryu::platform::{PlatformService, create_platform_service, parse_repo_info};
-use jj_ryu::repo::{JjWorkspace, select_remote};
-use jj_ryu::submit::{
-    ExecutionStep, SubmissionAnalysis, SubmissionPlan, analyze_submission, create_submission_plan,
-    execute_submission,
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::{
+    build_change_graph, build_change_graph_onto, resolve_base_branch, working_copy_bookmark,
 };
-use jj_ryu::types::ChangeGraph;
+use jj_ryu_core::lock::check_stack_lock;
+use jj_ryu_core::next_steps::prs_after;
+use jj_ryu_core::notify::WebhookEvent;
+use jj_ryu_core::platform::{
+    PlatformService, create_comment_platform_service, create_platform_service,
+};
+use jj_ryu_core::repo::{
+    JjWorkspace, MIRROR_REMOTES_ENV, PR_REMOTES_ENV, requested_remotes, select_mirror_remotes,
+};
+use jj_ryu_core::reviewers::ReviewerPool;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::submit::{
+    ExecutionStep, JsonProgress, PlatformResult, ProgressCallback, SubmissionAnalysis,
+    SubmissionPlan, SubmissionTarget, SubmitReport, analyze_submission, analyze_upstack_submission,
+    create_multi_submission_plan, drafts_by_default, execute_multi_submission, find_journal,
+    resume_submission, tracking_issue_enabled,
+};
+use jj_ryu_core::types::{ChangeGraph, StackInfo};
 use std::path::Path;
 
 /// Scope of bookmark submission (mutually exclusive options)
@@ -60,16 +89,39 @@ pub struct SubmitOptions<'a> {
     pub publish: bool,
     /// Interactively select which bookmarks to submit
     pub select: bool,
+    /// Submit even if the stack contains commits authored by someone else
+    pub allow_foreign_commits: bool,
+    /// Project (GitHub) or board list label (GitLab) for created PRs
+    pub project: Option<&'a str>,
+    /// Push-only remotes to mirror pushed bookmarks to
+    pub mirrors: &'a [String],
+    /// Additional remotes whose platforms also get PRs
+    pub pr_remotes: &'a [String],
+    /// Create or update a tracking issue for the stack
+    pub tracking_issue: bool,
+    /// Continue the interrupted submission from its journal instead of planning
+    pub resume: bool,
+    /// Create bookmarks for unbookmarked changes between trunk and the target
+    pub auto_bookmark: bool,
+    /// Branch the stack starts from and its root PR targets, instead of trunk
+    pub base: Option<&'a str>,
+    /// Where stack navigation goes, overriding `stack-info`
+    pub stack_info: Option<StackInfo>,
 }
 
 /// Run the submit command
+///
+/// A dry run reports [`Outcome::ChangesPending`] if submitting would change
+/// anything. With `--format json` a [`SubmitReport`] is printed instead of
+/// the summary. Without a bookmark, the stack the working copy is on is
+/// submitted.
 #[allow(clippy::too_many_lines)]
 pub async fn run_submit(
     path: &Path,
-    bookmark: &str,
+    bookmark: Option<&str>,
     remote: Option<&str>,
     options: SubmitOptions<'_>,
-) -> Result<()> {
+) -> Result<Outcome> {
     // Validate conflicting options (scope conflicts handled by clap arg groups)
     if options.draft && options.publish {
         return Err(Error::InvalidArgument(
@@ -78,11 +130,44 @@ pub async fn run_submit(
     }
 
     // Open workspace
-    let mut workspace = JjWorkspace::open(path)?;
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let reviewer_pool = ReviewerPool::from_config(workspace.config())?;
+    let project = requested_project(options.project, workspace.config());
+    let tracking_issue = options.tracking_issue || tracking_issue_enabled(workspace.config())?;
+    let pr_remotes = requested_remotes(
+        options.pr_remotes,
+        PR_REMOTES_ENV,
+        workspace.config().pr_remotes.as_deref(),
+    );
+    let mirrors = requested_remotes(
+        options.mirrors,
+        MIRROR_REMOTES_ENV,
+        workspace.config().mirror_remotes.as_deref(),
+    );
+
+    let bookmark = match bookmark {
+        Some(bookmark) => bookmark.to_string(),
+        // --auto-bookmark creates the bookmarks up to the working copy
+        None if options.auto_bookmark => "@".to_string(),
+        None => bookmark_at_working_copy(&workspace)?,
+    };
+    let bookmark = bookmark.as_str();
+
+    // RYU_DRAFT and the config files only set the default; --publish still wins
+    let options = SubmitOptions {
+        draft: options.draft || (!options.publish && drafts_by_default(workspace.config())?),
+        ..options
+    };
+    let stack_info = options
+        .stack_info
+        .unwrap_or_else(|| workspace.config().stack_info());
+    let interdiff_comments = workspace.config().interdiff_comments.unwrap_or(true);
 
     // Get remotes and select one
     let remotes = workspace.git_remotes()?;
-    let remote_name = select_remote(&remotes, remote)?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
 
     // Detect platform from remote URL
     let remote_info = remotes
@@ -90,17 +175,64 @@ pub async fn run_submit(
         .find(|r| r.name == remote_name)
         .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
 
-    let platform_config = parse_repo_info(&remote_info.url)?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+
+    // Create platform services: the primary first, then each --pr-remote
+    // In a fork workflow bookmarks go to the fork, PRs to the primary remote
+    let mut targets = vec![SubmissionTarget {
+        remote: workspace.push_remote(&remotes, &remote_name)?,
+        platform: create_platform_service(&platform_config, workspace.run()).await?,
+        comment_platform: create_comment_platform_service(&platform_config, workspace.run())?,
+    }];
+    for name in select_mirror_remotes(&remotes, &remote_name, &pr_remotes)? {
+        let url = remotes
+            .iter()
+            .find(|r| r.name == name)
+            .map(|r| r.url.as_str())
+            .unwrap_or_default();
+        let config = platform_for_remote(&workspace, url)?;
+        targets.push(SubmissionTarget {
+            remote: name,
+            platform: create_platform_service(&config, workspace.run()).await?,
+            comment_platform: create_comment_platform_service(&config, workspace.run())?,
+        });
+    }
+    let platform = targets[0].platform.as_ref();
 
-    // Create platform service
-    let platform = create_platform_service(&platform_config).await?;
+    if options.resume {
+        return resume(bookmark, &targets, &mut workspace, &options).await;
+    }
 
-    // Build change graph
-    let graph = build_change_graph(&workspace)?;
+    // With --auto-bookmark the target may be a revision; submit its bookmark
+    let bookmark = if options.auto_bookmark {
+        match create_auto_bookmarks(&mut workspace, bookmark, options.dry_run)? {
+            Some(name) => name,
+            None => return Ok(Outcome::ChangesPending),
+        }
+    } else {
+        bookmark.to_string()
+    };
+    let bookmark = bookmark.as_str();
+
+    // PR remotes are pushed to by their own plans already
+    let mut mirror_remotes = select_mirror_remotes(&remotes, &remote_name, &mirrors)?;
+    mirror_remotes.retain(|m| !targets.iter().any(|t| &t.remote == m));
+
+    // Build change graph, measured from --base when given
+    let base_commit = options
+        .base
+        .map(|base| resolve_base_branch(&workspace, base, &remote_name))
+        .transpose()?;
+    let graph = match &base_commit {
+        Some(commit) => build_change_graph_onto(&workspace, commit)?,
+        None => build_change_graph(&workspace)?,
+    };
+    note_excluded_bookmarks(&graph, true);
 
     if graph.bookmarks.is_empty() {
         println!("{}", "No bookmarks found in repository".muted());
-        return Ok(());
+        print_report(bookmark, &options, &[], &[], &JsonProgress::new())?;
+        return Ok(Outcome::Done);
     }
 
     // Check if target bookmark exists
@@ -109,34 +241,98 @@ pub async fn run_submit(
     }
 
     // Analyze submission based on options
-    let analysis = build_analysis(&graph, bookmark, &options, platform.as_ref()).await?;
+    let mut analysis = build_analysis(&graph, bookmark, &options, platform).await?;
+
+    // Catch names git would reject before anything is pushed; renaming
+    // changes the stack, so analyze it again under the new names
+    let renamed = check_bookmark_name_rules(&mut workspace, &analysis, true, options.dry_run)?;
+    let bookmark = renamed.get(bookmark).map_or(bookmark, String::as_str);
+    let graph = if renamed.is_empty() {
+        graph
+    } else {
+        let graph = match &base_commit {
+            Some(commit) => build_change_graph_onto(&workspace, commit)?,
+            None => build_change_graph(&workspace)?,
+        };
+        analysis = build_analysis(&graph, bookmark, &options, platform).await?;
+        graph
+    };
+    let state = RyuState::load(workspace.workspace_root())?;
+    // The user's overrides win over the fork points of --stack
+    analysis.base_overrides.extend(state.base_overrides.clone());
 
     // Display what will be submitted
     print_submission_summary(&analysis, &options);
 
-    // Get default branch
-    let default_branch = workspace.default_branch()?;
+    // Refuse to push colleagues' commits by accident (dry runs only report)
+    check_foreign_commits(
+        &workspace,
+        &analysis,
+        options.allow_foreign_commits || options.dry_run,
+    )?;
+
+    // With push.force disabled, diverged bookmarks can't be pushed at all
+    for target in &targets {
+        check_force_push(&workspace, &analysis, &target.remote)?;
+    }
+
+    // Predict review routing before anything is published
+    if options.dry_run || options.confirm {
+        print_expected_owners(&workspace, &analysis);
+    }
+
+    // The root PR targets --base, or the default branch
+    let default_branch = match options.base {
+        Some(base) => base.to_string(),
+        None => workspace.default_branch()?,
+    };
 
-    // Create submission plan
-    let mut plan =
-        create_submission_plan(&analysis, platform.as_ref(), &remote_name, &default_branch).await?;
+    // Create one submission plan per platform
+    let mut plans = create_multi_submission_plan(&analysis, &targets, &default_branch).await?;
+
+    // Don't retarget PRs under an operation that's landing this stack
+    if let Some(lock) = check_stack_lock(&state, platform, &plans[0]).await? {
+        return Err(lock.to_error());
+    }
 
-    // Apply plan modifications based on options
-    apply_plan_options(&mut plan, &options);
+    for (plan, target) in plans.iter_mut().zip(&targets) {
+        // Report duplicate PRs (planning already settled on the oldest)
+        let duplicates: Vec<_> = plan.duplicate_prs.iter().collect();
+        resolve_duplicate_prs(&duplicates, target.platform.as_ref(), options.dry_run).await?;
+
+        // Apply plan modifications based on options
+        apply_plan_options(plan, &options);
+        plan.tracking_issue = tracking_issue;
+        plan.stack_info = stack_info;
+        plan.interdiff_comments = interdiff_comments;
+    }
+    plans[0].mirror_remotes = mirror_remotes;
 
     // Handle interactive selection
     if options.select {
         let selected = interactive_select(&analysis)?;
         if selected.is_empty() {
             println!("{}", "No bookmarks selected, aborting".muted());
-            return Ok(());
+            print_report(bookmark, &options, &[], &[], &JsonProgress::new())?;
+            return Ok(Outcome::Done);
+        }
+        for plan in &mut plans {
+            filter_plan_to_selection(plan, &selected);
         }
-        filter_plan_to_selection(&mut plan, &selected);
     }
 
     // Show confirmation if requested
     if options.confirm && !options.dry_run {
-        print_plan_preview(&plan);
+        for (plan, target) in plans.iter().zip(&targets) {
+            if targets.len() > 1 {
+                println!(
+                    "{} ({})",
+                    target.platform.config().platform.to_string().emphasis(),
+                    target.remote.accent()
+                );
+            }
+            print_plan_preview(plan);
+        }
         if !Confirm::new()
             .with_prompt("Proceed with submission?")
             .default(true)
@@ -144,26 +340,71 @@ pub async fn run_submit(
             .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?
         {
             println!("{}", "Aborted".muted());
-            return Ok(());
+            print_report(bookmark, &options, &plans, &[], &JsonProgress::new())?;
+            return Ok(Outcome::Done);
         }
         println!();
     }
 
-    // Execute plan
-    let progress = CliProgress::verbose();
-    let result = execute_submission(
-        &plan,
-        &mut workspace,
-        platform.as_ref(),
-        &progress,
-        options.dry_run,
-    )
-    .await?;
+    // Execute plans
+    let cli_progress = CliProgress::verbose();
+    let json_progress = JsonProgress::new();
+    let progress: &dyn ProgressCallback = if is_json() {
+        &json_progress
+    } else {
+        &cli_progress
+    };
+    let results =
+        execute_multi_submission(&plans, &targets, &mut workspace, progress, options.dry_run)
+            .await?;
+    let result = &results[0].result;
+
+    // Project references are platform-specific, so only file primary PRs
+    if let Some(project) = project.filter(|_| !options.dry_run) {
+        add_to_project(platform, &result.created_prs, &project).await;
+    }
+    if !options.dry_run {
+        apply_pr_metadata(
+            platform,
+            workspace.config(),
+            &analysis.segments,
+            &result.created_prs,
+        )
+        .await;
+    }
+    if let Some(pool) = reviewer_pool.as_ref().filter(|_| !options.dry_run) {
+        assign_pool_reviewers(
+            workspace.workspace_root(),
+            platform,
+            &result.created_prs,
+            pool,
+        )
+        .await;
+    }
 
-    // Summary
     if !options.dry_run {
+        notify_webhook(
+            WebhookEvent::Submit,
+            &workspace,
+            platform.config(),
+            &analysis.target_bookmark,
+            analysis.segments.len(),
+            result,
+        )
+        .await;
+    }
+
+    let pending = options.dry_run && plans.iter().any(|plan| !plan.is_empty());
+
+    // Summary
+    if is_json() {
+        print_report(bookmark, &options, &plans, &results, &json_progress)?;
+    } else if !options.dry_run {
         println!();
-        if result.success {
+        if results.len() > 1 {
+            print_platform_results(&results);
+        }
+        if results.iter().all(|r| r.result.success) {
             println!(
                 "{} {} bookmark{}",
                 format!("{CHECK} Successfully submitted").success(),
@@ -186,15 +427,271 @@ pub async fn run_submit(
                     }
                 );
             }
+
+            for pr in &result.closed_prs {
+                println!(
+                    "Closed {} ({} was renamed)",
+                    format!("#{}", pr.number).accent(),
+                    pr.head_ref
+                );
+            }
+
+            for (mirror, bookmarks) in &result.mirrored_bookmarks {
+                println!(
+                    "Mirrored {} bookmark{} to {}",
+                    bookmarks.len().accent(),
+                    if bookmarks.len() == 1 { "" } else { "s" },
+                    mirror.accent()
+                );
+            }
+
+            let bookmarks: Vec<String> = plans[0]
+                .segments
+                .iter()
+                .map(|segment| segment.bookmark.name.clone())
+                .collect();
+            print_next_steps(
+                platform,
+                &[bookmarks],
+                &prs_after(&plans[0], result),
+                &default_branch,
+                graph.excluded_bookmarks.len(),
+            )
+            .await;
         } else {
             eprintln!("{} Submission failed", cross());
-            for err in &result.errors {
+            for r in &results {
+                for err in &r.result.errors {
+                    if results.len() > 1 {
+                        eprintln!("  {}: {}", r.platform, err.error());
+                    } else {
+                        eprintln!("  {}", err.error());
+                    }
+                }
+            }
+            let root = workspace.workspace_root();
+            for r in &results {
+                if find_journal(root, &r.remote, bookmark)?.is_some() {
+                    eprintln!(
+                        "Rerun with {} to continue where it stopped",
+                        format!("ryu submit {bookmark} --resume").accent()
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(Outcome::pending_if(pending))
+}
+
+/// Submit every stack, one after another (`submit --all`)
+///
+/// Each stack's leaf goes through [`run_submit`] with the same options, so
+/// stacks without any PRs yet are published too. A stack that fails is
+/// reported and the rest still go out; once the API budget runs out the
+/// remaining stacks are left for the next run.
+pub async fn run_submit_all(
+    path: &Path,
+    remote: Option<&str>,
+    options: SubmitOptions<'_>,
+) -> Result<Outcome> {
+    // One report per stack would make several JSON documents
+    if is_json() {
+        return Err(Error::InvalidArgument(
+            "--format json isn't supported with submit --all".to_string(),
+        ));
+    }
+
+    let graph = build_change_graph(&open_workspace(path)?)?;
+    let leaves: Vec<String> = graph
+        .stacks
+        .iter()
+        .filter_map(|stack| stack.segments.last()?.bookmarks.first())
+        .map(|bookmark| bookmark.name.clone())
+        .collect();
+
+    if leaves.is_empty() {
+        println!("{}", "No stacks to submit".muted());
+        return Ok(Outcome::Done);
+    }
+
+    let mut pending = false;
+    let mut failed = 0;
+    for (i, leaf) in leaves.iter().enumerate() {
+        if let Some(reason) = run_context().budget().exhausted() {
+            println!(
+                "{}",
+                format!(
+                    "API budget exhausted ({reason}); {} stack{} left for the next run",
+                    leaves.len() - i,
+                    if leaves.len() - i == 1 { "" } else { "s" }
+                )
+                .warn()
+            );
+            note_warning();
+            break;
+        }
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "{} {} {}",
+            "Submitting stack:".emphasis(),
+            leaf.accent(),
+            format!("({} of {})", i + 1, leaves.len()).muted()
+        );
+        match run_submit(path, Some(leaf), remote, options.clone()).await {
+            Ok(outcome) => pending |= outcome == Outcome::ChangesPending,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {e}", cross(), leaf.accent());
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(Error::StacksFailed(failed));
+    }
+    Ok(Outcome::pending_if(pending))
+}
+
+/// Bookmark of the stack segment the working copy is on
+fn bookmark_at_working_copy(workspace: &JjWorkspace) -> Result<String> {
+    let graph = build_change_graph(workspace)?;
+    working_copy_bookmark(workspace, &graph)?.ok_or_else(|| {
+        Error::InvalidArgument(
+            "the working copy isn't on any stack; name a bookmark to submit, \
+             or use --auto-bookmark to create bookmarks up to @"
+                .to_string(),
+        )
+    })
+}
+
+/// Continue each target's interrupted submission of `bookmark`
+///
+/// Replays the journaled plans without asking the platform again, so the
+/// bookmarks must not have moved since the submission started.
+async fn resume(
+    bookmark: &str,
+    targets: &[SubmissionTarget],
+    workspace: &mut JjWorkspace,
+    options: &SubmitOptions<'_>,
+) -> Result<Outcome> {
+    let root = workspace.workspace_root().to_path_buf();
+    let local_bookmarks = workspace.local_bookmarks()?;
+    let mut journaled = Vec::new();
+    for target in targets {
+        if let Some(journal) = find_journal(&root, &target.remote, bookmark)? {
+            journal.check_resumable(&local_bookmarks)?;
+            journaled.push((journal, target));
+        }
+    }
+    if journaled.is_empty() {
+        return Err(Error::InvalidArgument(format!(
+            "No interrupted submission of '{bookmark}' to resume"
+        )));
+    }
+
+    let cli_progress = CliProgress::verbose();
+    let json_progress = JsonProgress::new();
+    let progress: &dyn ProgressCallback = if is_json() {
+        &json_progress
+    } else {
+        &cli_progress
+    };
+
+    let mut plans = Vec::with_capacity(journaled.len());
+    let mut results = Vec::with_capacity(journaled.len());
+    for (journal, target) in journaled {
+        let platform = target.platform.as_ref();
+        println!(
+            "{} {} to {} ({} of {} steps left, started {})",
+            "Resuming submission of".emphasis(),
+            bookmark.accent(),
+            target.remote.accent(),
+            journal.remaining(),
+            journal.plan.execution_steps.len(),
+            journal.started_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        plans.push(journal.plan.clone());
+        let result = resume_submission(
+            journal,
+            workspace,
+            platform,
+            target.comment_platform.as_deref(),
+            progress,
+        )
+        .await?;
+        results.push(PlatformResult {
+            remote: target.remote.clone(),
+            platform: platform.config().platform,
+            result,
+        });
+    }
+
+    if is_json() {
+        print_report(bookmark, options, &plans, &results, &json_progress)?;
+        return Ok(Outcome::Done);
+    }
+    println!();
+    if results.len() > 1 {
+        print_platform_results(&results);
+    }
+    if results.iter().all(|r| r.result.success) {
+        println!(
+            "{}",
+            format!("{CHECK} Submission resumed and finished").success()
+        );
+    } else {
+        eprintln!(
+            "{} Submission failed again; fix the error and rerun with --resume",
+            cross()
+        );
+        for r in &results {
+            for err in &r.result.errors {
                 eprintln!("  {}", err.error());
             }
         }
     }
+    Ok(Outcome::Done)
+}
 
-    Ok(())
+/// Print the [`SubmitReport`] for `--format json` (nothing for text output)
+fn print_report(
+    bookmark: &str,
+    options: &SubmitOptions<'_>,
+    plans: &[SubmissionPlan],
+    results: &[PlatformResult],
+    progress: &JsonProgress,
+) -> Result<()> {
+    if !is_json() {
+        return Ok(());
+    }
+    let events = progress.take_events();
+    note_warning_events(&events);
+    let report = SubmitReport {
+        bookmark: bookmark.to_string(),
+        dry_run: options.dry_run,
+        plans: plans.to_vec(),
+        results: results.to_vec(),
+        events,
+    };
+    print_json(&serde_json::to_value(&report)?)
+}
+
+/// Print one summary line per platform for multi-platform submissions
+fn print_platform_results(results: &[PlatformResult]) {
+    for r in results {
+        let status = if r.result.success { check() } else { cross() };
+        println!(
+            "{status} {} ({}): {} created, {} updated",
+            r.platform.to_string().emphasis(),
+            r.remote.accent(),
+            r.result.created_prs.len().accent(),
+            r.result.updated_prs.len().accent()
+        );
+    }
 }
 
 /// Build submission analysis based on options
@@ -206,6 +703,7 @@ async fn build_analysis(
 ) -> Result<SubmissionAnalysis> {
     // Start with standard analysis
     let mut analysis = analyze_submission(graph, bookmark)?;
+    note_stack_size(analysis.segments.len());
 
     match options.scope {
         SubmitScope::Default => {}
@@ -264,71 +762,15 @@ async fn build_analysis(
         }
 
         SubmitScope::Stack => {
-            // Handle --stack (upstack): include descendants
-            let descendants = find_all_descendants(graph, bookmark);
-            for descendant_name in descendants {
-                // Get analysis for each descendant and merge segments
-                if let Ok(desc_analysis) = analyze_submission(graph, &descendant_name) {
-                    // Add segments that aren't already in our analysis
-                    for segment in desc_analysis.segments {
-                        if !analysis
-                            .segments
-                            .iter()
-                            .any(|s| s.bookmark.name == segment.bookmark.name)
-                        {
-                            analysis.segments.push(segment);
-                        }
-                    }
-                }
-            }
+            // Handle --stack (upstack): include descendants, every branch
+            // of a tree with its own base
+            analysis = analyze_upstack_submission(graph, bookmark)?;
         }
     }
 
     Ok(analysis)
 }
 
-/// Find all descendant bookmarks (across all branching stacks)
-///
-/// Note: This function operates on linear stacks only. The graph builder
-/// excludes merge commits, so diamond topologies are not represented.
-fn find_all_descendants(graph: &ChangeGraph, bookmark: &str) -> Vec<String> {
-    use std::collections::HashSet;
-
-    let mut seen = HashSet::new();
-
-    // Get the change_id for this bookmark
-    let Some(bookmark_change_id) = graph.bookmark_to_change_id.get(bookmark) else {
-        return Vec::new();
-    };
-
-    // For each stack, check if our bookmark appears in the path
-    for stack in &graph.stacks {
-        let mut found_bookmark = false;
-        for segment in &stack.segments {
-            // Check if any bookmark in this segment matches
-            if segment
-                .bookmarks
-                .iter()
-                .any(|b| graph.bookmark_to_change_id.get(&b.name) == Some(bookmark_change_id))
-            {
-                found_bookmark = true;
-                continue; // Skip the bookmark itself
-            }
-
-            // After finding our bookmark, all subsequent bookmarks are descendants
-            if found_bookmark {
-                for b in &segment.bookmarks {
-                    if b.name != bookmark {
-                        seen.insert(b.name.clone());
-                    }
-                }
-            }
-        }
-    }
-
-    seen.into_iter().collect()
-}
-
 /// Apply plan modifications based on options
 fn apply_plan_options(plan: &mut SubmissionPlan, options: &SubmitOptions<'_>) {
     // Handle --update-only: remove PR creation steps and filter to existing PRs
@@ -345,11 +787,7 @@ fn apply_plan_options(plan: &mut SubmissionPlan, options: &SubmitOptions<'_>) {
     // Handle --draft: mark new PRs as drafts (unless --publish is also set)
     // When both flags are present, --publish takes precedence and --draft is ignored
     if options.draft && !options.publish {
-        for step in &mut plan.execution_steps {
-            if let ExecutionStep::CreatePr(create) = step {
-                create.draft = true;
-            }
-        }
+        plan.draft_new_prs();
     }
 
     // Handle --publish: publish existing draft PRs
@@ -457,10 +895,16 @@ fn print_submission_summary(analysis: &SubmissionAnalysis, options: &SubmitOptio
         } else {
             String::new()
         };
+        let base = analysis
+            .base_overrides
+            .get(&segment.bookmark.name)
+            .map(|base| format!(" {}", format!("(on {base})").muted()))
+            .unwrap_or_default();
         println!(
-            "  {} {}{}",
+            "  {} {}{}{}",
             bullet(),
             segment.bookmark.name.accent(),
+            base,
             synced
         );
     }
@@ -481,6 +925,13 @@ fn print_plan_preview(plan: &SubmissionPlan) {
     for step in &plan.execution_steps {
         println!("    {} {}", arrow(), step);
     }
+    if !plan.mirror_remotes.is_empty() {
+        println!(
+            "    {} mirror pushed bookmarks to {}",
+            arrow(),
+            plan.mirror_remotes.join(", ")
+        );
+    }
 
     println!();
 }
@@ -0,0 +1,124 @@
+//! Suggest-base command - hint at better ways to structure stacks
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::style::{Stylize, bullet};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::suggest::{SegmentFiles, Suggestion, stack_files, suggest_restructure};
+use std::path::Path;
+
+/// Run the suggest-base command for every stack, or the one holding `bookmark`
+pub fn run_suggest_base(path: &Path, bookmark: Option<&str>) -> Result<()> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let graph = build_change_graph(&workspace)?;
+    let stacks: Vec<_> = graph
+        .stacks
+        .iter()
+        .filter(|stack| {
+            bookmark.is_none_or(|name| {
+                stack
+                    .segments
+                    .iter()
+                    .any(|seg| seg.bookmarks.iter().any(|b| b.name == name))
+            })
+        })
+        .collect();
+    if let (Some(name), true) = (bookmark, stacks.is_empty()) {
+        return Err(Error::BookmarkNotFound(name.to_string()));
+    }
+
+    let mut suggested = false;
+    for stack in stacks {
+        let segments = stack_files(&workspace, stack)?;
+        let suggestions = suggest_restructure(&segments);
+        if suggestions.is_empty() {
+            continue;
+        }
+        if suggested {
+            println!();
+        }
+        suggested = true;
+
+        let leaf = segments.last().map_or("", |s| s.bookmark.as_str());
+        println!("{} {}", "Stack".emphasis(), leaf.accent());
+        for suggestion in &suggestions {
+            print_suggestion(suggestion, &parent_of(&segments, suggestion));
+        }
+    }
+
+    if suggested {
+        println!();
+        println!(
+            "{}",
+            "Based on the files each segment changes; check that nothing depends on code it doesn't touch"
+                .muted()
+        );
+    } else {
+        println!("{}", "No restructuring to suggest".muted());
+    }
+    Ok(())
+}
+
+/// Bookmark of the segment right below the suggestion's, or trunk
+fn parent_of(segments: &[SegmentFiles], suggestion: &Suggestion) -> String {
+    let bookmark = match suggestion {
+        Suggestion::Parallel { bookmark, .. }
+        | Suggestion::LowerBase { bookmark, .. }
+        | Suggestion::Split { bookmark, .. } => bookmark,
+    };
+    segments
+        .iter()
+        .position(|s| s.bookmark == *bookmark)
+        .and_then(|index| index.checked_sub(1))
+        .map_or_else(|| "trunk()".to_string(), |i| segments[i].bookmark.clone())
+}
+
+fn print_suggestion(suggestion: &Suggestion, parent: &str) {
+    match suggestion {
+        Suggestion::Parallel { bookmark, below } => {
+            println!(
+                "  {} {} touches none of the files of {}; consider a parallel stack on trunk",
+                bullet(),
+                bookmark.accent(),
+                below.join(", ")
+            );
+            println!(
+                "    {}",
+                format!("jj rebase -r '{parent}..{bookmark}' -d 'trunk()'").muted()
+            );
+        }
+        Suggestion::LowerBase {
+            bookmark,
+            base,
+            skipped,
+        } => {
+            println!(
+                "  {} {} shares no files with {}; consider basing it on {}",
+                bullet(),
+                bookmark.accent(),
+                skipped.join(", "),
+                base.accent()
+            );
+            println!(
+                "    {}",
+                format!("jj rebase -r '{parent}..{bookmark}' -d {base}").muted()
+            );
+        }
+        Suggestion::Split { bookmark, groups } => {
+            println!(
+                "  {} {}'s changes touch unrelated files in {} groups; consider splitting it",
+                bullet(),
+                bookmark.accent(),
+                groups.len()
+            );
+            for group in groups {
+                let ids: Vec<&str> = group.iter().map(|id| &id[..8.min(id.len())]).collect();
+                println!("    {}", ids.join(" ").muted());
+            }
+        }
+    }
+}
@@ -1,18 +1,48 @@
 //! Sync command - sync all stacks with remote
 
-use crate::cli::CliProgress;
-use crate::cli::style::{CHECK, Stylize, arrow, check, spinner_style};
-use anstream::println;
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::duplicates::resolve_duplicate_prs;
+use crate::cli::force::check_force_push;
+use crate::cli::foreign::check_foreign_commits;
+use crate::cli::metadata::apply_pr_metadata;
+use crate::cli::names::check_bookmark_name_rules;
+use crate::cli::next_steps::print_next_steps;
+use crate::cli::output::{is_json, print_json, println};
+use crate::cli::platform::platform_for_remote;
+use crate::cli::project::{add_to_project, requested_project};
+use crate::cli::pull::pull_pushed_commits;
+use crate::cli::reviewers::assign_pool_reviewers;
+use crate::cli::strict::{note_excluded_bookmarks, note_warning_events};
+use crate::cli::style::{CHECK, Stream, Stylize, arrow, check, spinner_style};
+use crate::cli::webhook::notify_webhook;
+use crate::cli::width::{display_width, ellipsize, fit_columns};
+use crate::cli::{CliProgress, Outcome, note_stack_size};
 use dialoguer::Confirm;
 use indicatif::ProgressBar;
-use jj_ryu::error::{Error, Result};
-use jj_ryu::graph::build_change_graph;
-use jj_ryu::platform::{create_platform_service, parse_repo_info};
-use jj_ryu::repo::{JjWorkspace, select_remote};
-use jj_ryu::submit::{
-    SubmissionPlan, analyze_submission, create_submission_plan, execute_submission,
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::gc::light_gc;
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::landed::refresh_landed;
+use jj_ryu_core::lock::check_stack_lock;
+use jj_ryu_core::next_steps::prs_after;
+use jj_ryu_core::notify::WebhookEvent;
+use jj_ryu_core::platform::{create_comment_platform_service, create_platform_service};
+use jj_ryu_core::prune::{PruneReport, prune_merged};
+use jj_ryu_core::pull::pushed_heads;
+use jj_ryu_core::repo::{
+    MIRROR_REMOTES_ENV, requested_remotes, select_fetch_remotes, select_mirror_remotes,
 };
-use jj_ryu::types::BranchStack;
+use jj_ryu_core::restack::{plan_restacks, restack_onto_trunk, trunk_commit_id};
+use jj_ryu_core::reviewers::ReviewerPool;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::submit::{
+    JsonProgress, ProgressCallback, SkippedStack, StackDrift, SubmissionPlan, SyncReport,
+    SyncStackReport, Warning, analyze_submission, create_submission_plan, drafts_by_default,
+    execute_submission, tracking_issue_enabled,
+};
+use jj_ryu_core::types::{BranchStack, ChangeGraph, StackInfo};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::time::Duration;
 
@@ -25,17 +55,45 @@ pub struct SyncOptions<'a> {
     pub confirm: bool,
     /// Only sync the stack containing this bookmark
     pub stack: Option<&'a str>,
+    /// Sync even if a stack contains commits authored by someone else
+    pub allow_foreign_commits: bool,
+    /// Project (GitHub) or board list label (GitLab) for created PRs
+    pub project: Option<&'a str>,
+    /// Push-only remotes to mirror pushed bookmarks to
+    pub mirrors: &'a [String],
+    /// Also sync stacks whose bookmarks all match the remote, so PRs left
+    /// pointing at a merged parent are retargeted
+    pub include_synced: bool,
+    /// Rebase stacks onto trunk's new head after fetching
+    pub restack: bool,
+    /// Delete merged bookmarks and abandon their changes after fetching
+    /// (also on with `sync-prune`)
+    pub prune: bool,
+    /// Suggest what to run next once the stacks are synced
+    pub next_steps: bool,
+    /// Where stack navigation goes, overriding `stack-info`
+    pub stack_info: Option<StackInfo>,
 }
 
 /// Run the sync command
+///
+/// A dry run reports [`Outcome::ChangesPending`] if any stack has drifted.
+/// With `--format json` a [`SyncReport`] is printed instead of the summary.
 #[allow(clippy::too_many_lines)]
-pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions<'_>) -> Result<()> {
+pub async fn run_sync(
+    path: &Path,
+    remote: Option<&str>,
+    options: SyncOptions<'_>,
+) -> Result<Outcome> {
     // Open workspace
-    let mut workspace = JjWorkspace::open(path)?;
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+    let reviewer_pool = ReviewerPool::from_config(workspace.config())?;
+    let project = requested_project(options.project, workspace.config());
 
     // Get remotes and select one
     let remotes = workspace.git_remotes()?;
-    let remote_name = select_remote(&remotes, remote)?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
 
     // Detect platform
     let remote_info = remotes
@@ -43,75 +101,157 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions<'_
         .find(|r| r.name == remote_name)
         .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
 
-    let platform_config = parse_repo_info(&remote_info.url)?;
-
-    // Create platform service
-    let platform = create_platform_service(&platform_config).await?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let push_remote = workspace.push_remote(&remotes, &remote_name)?;
+    let mirrors = requested_remotes(
+        options.mirrors,
+        MIRROR_REMOTES_ENV,
+        workspace.config().mirror_remotes.as_deref(),
+    );
+    let mirror_remotes = select_mirror_remotes(&remotes, &remote_name, &mirrors)?;
+    let tracking_issue = tracking_issue_enabled(workspace.config())?;
+    let draft = drafts_by_default(workspace.config())?;
+    let stack_info = options
+        .stack_info
+        .unwrap_or_else(|| workspace.config().stack_info());
+    let interdiff_comments = workspace.config().interdiff_comments.unwrap_or(true);
+
+    // Create platform services (stack comments may come from a bot account)
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+    let comment_platform = create_comment_platform_service(&platform_config, workspace.run())?;
+
+    // Remember where the pushed bookmarks were, to spot commits others push
+    // on top of them
+    let pushed = if options.dry_run {
+        Vec::new()
+    } else {
+        let graph = build_change_graph(&workspace)?;
+        pushed_heads(
+            &workspace,
+            &select_stacks(&graph, options.stack)?,
+            &push_remote,
+        )?
+    };
 
-    // Fetch from remote with spinner
+    // Fetch from remote (and upstream or the fork remote, in a fork) with spinner
     if !options.dry_run {
+        let mut fetch_remotes = select_fetch_remotes(&remotes, &remote_name);
+        if !fetch_remotes.contains(&push_remote) {
+            fetch_remotes.push(push_remote.clone());
+        }
         let spinner = ProgressBar::new_spinner();
         spinner.set_style(spinner_style());
-        spinner.set_message(format!("Fetching from {}...", remote_name.emphasis()));
+        spinner.set_message(format!(
+            "Fetching from {}...",
+            fetch_remotes.join(", ").emphasis()
+        ));
         spinner.enable_steady_tick(Duration::from_millis(80));
 
-        workspace.git_fetch(&remote_name)?;
+        workspace.git_fetch_all(&fetch_remotes)?;
 
         spinner.finish_with_message(format!(
             "{} Fetched from {}",
             check(),
-            remote_name.emphasis()
+            fetch_remotes.join(", ").emphasis()
         ));
     }
 
+    // Keep reviewers' commits (e.g. suggestions applied on the web) from
+    // being overwritten by the next push
+    let pulled = pull_pushed_commits(&mut workspace, &pushed, &push_remote)?;
+
     // Build change graph
-    let graph = build_change_graph(&workspace)?;
+    let mut graph = build_change_graph(&workspace)?;
+    note_excluded_bookmarks(&graph, true);
+
+    // Remember where merged PRs landed, for analyze and clean
+    if !options.dry_run {
+        let mut state = RyuState::load(workspace.workspace_root())?;
+        if refresh_landed(&graph, platform.as_ref(), &mut state).await? {
+            state.save(workspace.workspace_root())?;
+        }
+    }
+
+    // Clean up after merged PRs before restacking what's left
+    if options.prune || workspace.config().sync_prune.unwrap_or(false) {
+        let pruned = prune_merged(
+            &mut workspace,
+            &select_stacks(&graph, options.stack)?,
+            platform.as_ref(),
+            &push_remote,
+            options.dry_run,
+        )
+        .await?;
+        if !pruned.is_empty() {
+            print_pruned(&pruned, options.dry_run);
+            if !options.dry_run {
+                graph = build_change_graph(&workspace)?;
+            }
+        }
+    }
+
+    let mut report = SyncReport {
+        dry_run: options.dry_run,
+        pulled,
+        ..SyncReport::default()
+    };
 
     if graph.stacks.is_empty() {
         println!("{}", "No stacks to sync".muted());
-        return Ok(());
+        print_report(&report)?;
+        return Ok(Outcome::Done);
     }
 
-    // Filter stacks if --stack is specified
-    let stacks_to_sync: Vec<&BranchStack> = if let Some(stack_bookmark) = options.stack {
-        // Find the stack containing this bookmark
-        let matching_stack = graph.stacks.iter().find(|stack| {
-            stack
-                .segments
-                .iter()
-                .any(|seg| seg.bookmarks.iter().any(|b| b.name == stack_bookmark))
-        });
+    let cli_progress = CliProgress::compact();
+    let json_progress = JsonProgress::new();
+    let progress: &dyn ProgressCallback = if is_json() {
+        &json_progress
+    } else {
+        &cli_progress
+    };
 
-        match matching_stack {
-            Some(stack) => vec![stack],
-            None => {
-                return Err(Error::BookmarkNotFound(format!(
-                    "Bookmark '{stack_bookmark}' not found in any stack"
-                )));
-            }
-        }
+    // Rebase stacks left on the old trunk head, then re-read the graph so
+    // the plans below push the rewritten bookmarks
+    let trunk = if options.restack && !options.dry_run {
+        trunk_commit_id(&workspace)?
     } else {
-        graph.stacks.iter().collect()
+        None
     };
+    if let Some(trunk) = trunk {
+        let restacks = plan_restacks(&select_stacks(&graph, options.stack)?, &trunk);
+        if !restacks.is_empty() {
+            report.restacked =
+                restack_onto_trunk(&mut workspace, &restacks, &trunk, progress).await?;
+            // Restacked bookmarks are listed in the report, not a stack's events
+            note_warning_events(&json_progress.take_events());
+            graph = build_change_graph(&workspace)?;
+        }
+    }
+
+    let stacks_to_sync = select_stacks(&graph, options.stack)?;
 
-    // Filter out stacks where all bookmarks are already synced
+    // Filter out stacks where all bookmarks are already synced (the dry-run
+    // report keeps them so in-sync stacks are listed too)
     let stacks_to_sync: Vec<&BranchStack> = stacks_to_sync
         .into_iter()
         .filter(|stack| {
-            stack
-                .segments
-                .iter()
-                .any(|seg| seg.bookmarks.iter().any(|b| !b.has_remote || !b.is_synced))
+            options.dry_run
+                || options.include_synced
+                || stack
+                    .segments
+                    .iter()
+                    .any(|seg| seg.bookmarks.iter().any(|b| !b.has_remote || !b.is_synced))
         })
         .collect();
 
     if stacks_to_sync.is_empty() {
         println!("{}", "No stacks to sync".muted());
-        return Ok(());
+        print_report(&report)?;
+        return Ok(Outcome::Done);
     }
 
     let default_branch = workspace.default_branch()?;
-    let progress = CliProgress::compact();
+    let state = RyuState::load(workspace.workspace_root())?;
 
     // Build plans for all stacks first (for confirmation)
     let mut stack_plans: Vec<(&str, SubmissionPlan)> = Vec::new();
@@ -126,16 +266,91 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions<'_
         };
         let leaf_bookmark = &leaf_bm.name;
 
-        let analysis = analyze_submission(&graph, leaf_bookmark)?;
-        let plan =
-            create_submission_plan(&analysis, platform.as_ref(), &remote_name, &default_branch)
+        // Out of budget: leave the rest for the next sync
+        if let Some(reason) = workspace.run().budget().exhausted() {
+            skip_stack(&mut report, &cli_progress, leaf_bookmark, &reason).await;
+            continue;
+        }
+
+        let mut analysis = analyze_submission(&graph, leaf_bookmark)?;
+        note_stack_size(analysis.segments.len());
+        analysis.base_overrides = state.base_overrides.clone().into_iter().collect();
+        check_foreign_commits(
+            &workspace,
+            &analysis,
+            options.allow_foreign_commits || options.dry_run,
+        )?;
+        check_bookmark_name_rules(&mut workspace, &analysis, false, options.dry_run)?;
+        check_force_push(&workspace, &analysis, &push_remote)?;
+        let mut plan =
+            create_submission_plan(&analysis, platform.as_ref(), &push_remote, &default_branch)
                 .await?;
+        plan.mirror_remotes.clone_from(&mirror_remotes);
+        plan.tracking_issue = tracking_issue;
+        plan.stack_info = stack_info;
+        plan.interdiff_comments = interdiff_comments;
+        if draft {
+            plan.draft_new_prs();
+        }
+
+        // Another machine may be landing this stack; don't retarget under it
+        if let Some(lock) = check_stack_lock(&state, platform.as_ref(), &plan).await? {
+            let reason = lock.to_error().to_string();
+            // JSON output lists skipped stacks in the report instead
+            cli_progress
+                .on_warning(&Warning::SkippedBookmark {
+                    bookmark: leaf_bookmark.clone(),
+                    reason: reason.clone(),
+                })
+                .await;
+            report.skipped.push(SkippedStack {
+                bookmark: leaf_bookmark.clone(),
+                reason,
+            });
+            continue;
+        }
 
         stack_plans.push((leaf_bookmark, plan));
     }
 
+    // Report duplicate PRs once, even when stacks share bookmarks
+    let mut duplicates: Vec<_> = stack_plans
+        .iter()
+        .flat_map(|(_, plan)| &plan.duplicate_prs)
+        .collect();
+    duplicates.sort_by_key(|dup| dup.duplicate.number);
+    duplicates.dedup_by_key(|dup| dup.duplicate.number);
+    resolve_duplicate_prs(&duplicates, platform.as_ref(), options.dry_run).await?;
+
+    // Dry run: one consolidated drift report instead of per-stack replays
+    if options.dry_run {
+        let drifts: Vec<StackDrift> = stack_plans
+            .iter()
+            .map(|(leaf_bookmark, plan)| StackDrift::from_plan(leaf_bookmark, plan))
+            .collect();
+        let pending = drifts.iter().any(|drift| !drift.is_in_sync());
+        if is_json() {
+            report.stacks = stack_plans
+                .into_iter()
+                .zip(drifts)
+                .map(|((leaf_bookmark, plan), drift)| SyncStackReport {
+                    stack_id: stack_id(&plan),
+                    bookmark: leaf_bookmark.to_string(),
+                    drift,
+                    plan,
+                    result: None,
+                    events: Vec::new(),
+                })
+                .collect();
+            print_report(&report)?;
+        } else {
+            print_drift_report(&drifts);
+        }
+        return Ok(Outcome::pending_if(pending));
+    }
+
     // Show confirmation if requested
-    if options.confirm && !options.dry_run {
+    if options.confirm {
         print_sync_preview(&stack_plans);
         if !Confirm::new()
             .with_prompt("Proceed with sync?")
@@ -144,7 +359,8 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions<'_
             .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?
         {
             println!("{}", "Aborted".muted());
-            return Ok(());
+            print_report(&report)?;
+            return Ok(Outcome::Done);
         }
         println!();
     }
@@ -153,39 +369,216 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions<'_
     let mut total_pushed = 0;
     let mut total_created = 0;
     let mut total_updated = 0;
+    let mut total_mirrored: BTreeMap<String, usize> = BTreeMap::new();
 
     for (leaf_bookmark, plan) in stack_plans {
+        if let Some(reason) = workspace.run().budget().exhausted() {
+            skip_stack(&mut report, &cli_progress, leaf_bookmark, &reason).await;
+            continue;
+        }
         println!("{} {}", "Syncing stack:".emphasis(), leaf_bookmark.accent());
 
+        let drift = StackDrift::from_plan(leaf_bookmark, &plan);
         let result = execute_submission(
             &plan,
             &mut workspace,
             platform.as_ref(),
-            &progress,
-            options.dry_run,
+            comment_platform.as_deref(),
+            progress,
+            false,
         )
         .await?;
 
+        if let Some(project) = &project {
+            add_to_project(platform.as_ref(), &result.created_prs, project).await;
+        }
+        apply_pr_metadata(
+            platform.as_ref(),
+            workspace.config(),
+            &plan.segments,
+            &result.created_prs,
+        )
+        .await;
+        if let Some(pool) = &reviewer_pool {
+            assign_pool_reviewers(
+                workspace.workspace_root(),
+                platform.as_ref(),
+                &result.created_prs,
+                pool,
+            )
+            .await;
+        }
+        notify_webhook(
+            WebhookEvent::Sync,
+            &workspace,
+            platform.config(),
+            leaf_bookmark,
+            plan.segments.len(),
+            &result,
+        )
+        .await;
+
         total_pushed += result.pushed_bookmarks.len();
         total_created += result.created_prs.len();
         total_updated += result.updated_prs.len();
+        for (mirror, bookmarks) in &result.mirrored_bookmarks {
+            *total_mirrored.entry(mirror.clone()).or_default() += bookmarks.len();
+        }
+        let events = json_progress.take_events();
+        note_warning_events(&events);
+        report.stacks.push(SyncStackReport {
+            stack_id: stack_id(&plan),
+            bookmark: leaf_bookmark.to_string(),
+            drift,
+            plan,
+            result: Some(result),
+            events,
+        });
+    }
+
+    // Nothing is mid-flight after a clean run, so prune what's left behind
+    let all_succeeded = report
+        .stacks
+        .iter()
+        .all(|stack| stack.result.as_ref().is_some_and(|r| r.success));
+    if report.skipped.is_empty() && all_succeeded {
+        light_gc(&workspace, &graph);
     }
 
     // Summary
+    if is_json() {
+        print_report(&report)?;
+        return Ok(Outcome::Done);
+    }
     println!();
-    if options.dry_run {
-        println!("{}", "Dry run complete".muted());
-    } else {
-        println!(
-            "{} {} pushed, {} created, {} updated",
-            format!("{CHECK} Sync complete:").success(),
-            total_pushed.accent(),
-            total_created.accent(),
-            total_updated.accent()
-        );
+    println!(
+        "{} {} pushed, {} created, {} updated",
+        format!("{CHECK} Sync complete:").success(),
+        total_pushed.accent(),
+        total_created.accent(),
+        total_updated.accent()
+    );
+    for (mirror, count) in &total_mirrored {
+        println!("  {} mirrored to {}", count.accent(), mirror.accent());
     }
 
-    Ok(())
+    if options.next_steps {
+        let stacks: Vec<Vec<String>> = report
+            .stacks
+            .iter()
+            .map(|stack| {
+                stack
+                    .plan
+                    .segments
+                    .iter()
+                    .map(|segment| segment.bookmark.name.clone())
+                    .collect()
+            })
+            .collect();
+        let mut prs = HashMap::new();
+        for stack in &report.stacks {
+            if let Some(result) = &stack.result {
+                prs.extend(prs_after(&stack.plan, result));
+            }
+        }
+        print_next_steps(
+            platform.as_ref(),
+            &stacks,
+            &prs,
+            &default_branch,
+            graph.excluded_bookmarks.len(),
+        )
+        .await;
+    }
+
+    Ok(Outcome::Done)
+}
+
+/// Skip a stack because the API budget ran out
+async fn skip_stack(report: &mut SyncReport, progress: &CliProgress, leaf: &str, reason: &str) {
+    let reason = format!("API budget exhausted ({reason}); run sync again to continue");
+    // JSON output lists skipped stacks in the report instead
+    progress
+        .on_warning(&Warning::SkippedBookmark {
+            bookmark: leaf.to_string(),
+            reason: reason.clone(),
+        })
+        .await;
+    report.skipped.push(SkippedStack {
+        bookmark: leaf.to_string(),
+        reason,
+    });
+}
+
+/// Stacks to act on: the one containing `stack_bookmark` if given, else all
+pub fn select_stacks<'a>(
+    graph: &'a ChangeGraph,
+    stack_bookmark: Option<&str>,
+) -> Result<Vec<&'a BranchStack>> {
+    let Some(stack_bookmark) = stack_bookmark else {
+        return Ok(graph.stacks.iter().collect());
+    };
+    graph
+        .stacks
+        .iter()
+        .find(|stack| {
+            stack
+                .segments
+                .iter()
+                .any(|seg| seg.bookmarks.iter().any(|b| b.name == stack_bookmark))
+        })
+        .map(|stack| vec![stack])
+        .ok_or_else(|| {
+            Error::BookmarkNotFound(format!(
+                "Bookmark '{stack_bookmark}' not found in any stack"
+            ))
+        })
+}
+
+/// Print what a prune deleted (or would delete)
+fn print_pruned(report: &PruneReport, dry_run: bool) {
+    for pruned in &report.bookmarks {
+        let merged = format!("(#{} merged)", pruned.pr_number);
+        if dry_run {
+            println!(
+                "Would delete {} {}",
+                pruned.bookmark.accent(),
+                merged.muted()
+            );
+        } else {
+            println!(
+                "{} Deleted {} {}",
+                check(),
+                pruned.bookmark.accent(),
+                merged.muted()
+            );
+        }
+    }
+    if !report.abandoned.is_empty() {
+        let count = report.abandoned.len();
+        let changes = format!("{count} change{}", if count == 1 { "" } else { "s" });
+        if dry_run {
+            println!("Would abandon {}", changes.accent());
+        } else {
+            println!("{} Abandoned {}", check(), changes.accent());
+        }
+    }
+}
+
+/// ID of the stack a plan was made for: the change ID of its leaf bookmark
+fn stack_id(plan: &SubmissionPlan) -> String {
+    plan.segments
+        .last()
+        .map(|seg| seg.bookmark.change_id.clone())
+        .unwrap_or_default()
+}
+
+/// Print the [`SyncReport`] for `--format json` (nothing for text output)
+fn print_report(report: &SyncReport) -> Result<()> {
+    if !is_json() {
+        return Ok(());
+    }
+    print_json(&serde_json::to_value(report)?)
 }
 
 /// Print sync preview for --confirm
@@ -206,7 +599,102 @@ fn print_sync_preview(stack_plans: &[(&str, SubmissionPlan)]) {
         for step in &plan.execution_steps {
             println!("    {} {}", arrow(), step);
         }
+        if !plan.mirror_remotes.is_empty() {
+            println!(
+                "    {} mirror pushed bookmarks to {}",
+                arrow(),
+                plan.mirror_remotes.join(", ")
+            );
+        }
 
         println!();
     }
 }
+
+/// Print the consolidated drift report for --dry-run
+fn print_drift_report(drifts: &[StackDrift]) {
+    const HEADERS: [&str; 6] = [
+        "Stack",
+        "Push",
+        "New PR",
+        "Retarget",
+        "Merged parent",
+        "Status",
+    ];
+
+    let rows: Vec<[String; 6]> = drifts
+        .iter()
+        .map(|drift| {
+            [
+                drift.leaf.clone(),
+                drift.needs_push.len().to_string(),
+                drift.needs_pr.len().to_string(),
+                drift.needs_retarget.len().to_string(),
+                if drift.merged_parents.is_empty() {
+                    "-".to_string()
+                } else {
+                    drift.merged_parents.join(", ")
+                },
+                if drift.is_in_sync() {
+                    "in sync".to_string()
+                } else {
+                    "drift".to_string()
+                },
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    // Indent plus the separators between cells; the leaf and merged parent
+    // columns are the ones cut to fit
+    fit_columns(
+        Stream::Stdout,
+        2 + 2 * (HEADERS.len() - 1),
+        &mut widths,
+        &[0, 4],
+    );
+
+    println!("{}:", "Drift report".emphasis());
+    println!();
+
+    let header: Vec<String> = HEADERS
+        .iter()
+        .zip(widths)
+        .map(|(h, w)| format!("{h:<w$}"))
+        .collect();
+    println!("  {}", header.join("  ").muted());
+
+    for (row, drift) in rows.iter().zip(drifts) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| format!("{:<w$}", ellipsize(cell, w)))
+            .collect();
+        let status = if drift.is_in_sync() {
+            cells[5].success().to_string()
+        } else {
+            cells[5].warn().to_string()
+        };
+        println!(
+            "  {}  {}  {}",
+            cells[0].accent(),
+            cells[1..5].join("  "),
+            status
+        );
+    }
+
+    let in_sync = drifts.iter().filter(|d| d.is_in_sync()).count();
+    println!();
+    println!(
+        "{} of {} stack{} in sync",
+        in_sync.accent(),
+        drifts.len().accent(),
+        if drifts.len() == 1 { "" } else { "s" }
+    );
+    println!("{}", "Dry run complete".muted());
+}
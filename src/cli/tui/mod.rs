@@ -0,0 +1,92 @@
+//! Interactive terminal UI (`ryu ui`)
+//!
+//! Shows every stack with the PR and CI state of its segments, and submits
+//! or syncs the selected one. Actions run on the same analyze, plan and
+//! execute engine as the commands; their progress comes back over a channel
+//! so the screen keeps redrawing while they run.
+
+mod progress;
+mod session;
+
+use jj_ryu_core::error::Result;
+use jj_ryu_core::tui::{Action, App, Command, draw};
+use progress::ChannelProgress;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use session::Session;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for a key before redrawing
+const TICK: Duration = Duration::from_millis(250);
+
+/// Run the interactive UI until the user quits
+pub async fn run_ui(path: &Path, remote: Option<&str>) -> Result<()> {
+    let mut session = Session::open(path, remote).await?;
+    let app = App::new(session.load_stacks().await?);
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut session, app).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    session: &mut Session,
+    mut app: App,
+) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, &app))?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key.code) {
+            Some(Command::Quit) => return Ok(()),
+            Some(Command::Run(action)) => run_action(terminal, session, &mut app, &action).await?,
+            None => {}
+        }
+    }
+}
+
+/// Run `action`, redrawing as its progress comes in, then reload the stacks
+async fn run_action(
+    terminal: &mut DefaultTerminal,
+    session: &mut Session,
+    app: &mut App,
+    action: &Action,
+) -> Result<()> {
+    app.start(action);
+    terminal.draw(|frame| draw(frame, app))?;
+
+    let (progress, mut events) = ChannelProgress::new();
+    let outcome = {
+        let op = session.run(action, &progress);
+        tokio::pin!(op);
+        loop {
+            tokio::select! {
+                outcome = &mut op => break outcome,
+                Some(event) = events.recv() => {
+                    app.push_event(&event);
+                    terminal.draw(|frame| draw(frame, app))?;
+                }
+            }
+        }
+    };
+    while let Ok(event) = events.try_recv() {
+        app.push_event(&event);
+    }
+    app.finish(outcome.map_err(|e| e.to_string()));
+
+    match session.load_stacks().await {
+        Ok(stacks) => app.set_stacks(stacks),
+        Err(e) => app.push_log(format!("✗ Couldn't reload stacks: {e}")),
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Progress adapter that streams updates over a channel
+
+use async_trait::async_trait;
+use jj_ryu_core::error::Error;
+use jj_ryu_core::submit::{Phase, ProgressCallback, ProgressEvent, PushStatus, Warning};
+use jj_ryu_core::types::PullRequest;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Progress callback that sends every update as a [`ProgressEvent`]
+///
+/// The UI drains the receiver while a submission runs, so the screen keeps
+/// up without the engine knowing it's being drawn.
+pub struct ChannelProgress {
+    sender: UnboundedSender<ProgressEvent>,
+}
+
+impl ChannelProgress {
+    /// Create the callback and the receiving end of its channel
+    pub fn new() -> (Self, UnboundedReceiver<ProgressEvent>) {
+        let (sender, receiver) = unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    fn send(&self, event: ProgressEvent) {
+        // The UI may have stopped listening; nothing is lost that matters
+        let _ = self.sender.send(event);
+    }
+}
+
+#[async_trait]
+impl ProgressCallback for ChannelProgress {
+    async fn on_phase(&self, phase: Phase) {
+        self.send(ProgressEvent::Phase { phase });
+    }
+
+    async fn on_segment_restacked(&self, bookmark: &str) {
+        self.send(ProgressEvent::SegmentRestacked {
+            bookmark: bookmark.to_string(),
+        });
+    }
+
+    async fn on_bookmark_push(&self, bookmark: &str, status: PushStatus) {
+        self.send(ProgressEvent::BookmarkPush {
+            bookmark: bookmark.to_string(),
+            status,
+        });
+    }
+
+    async fn on_mirror_push(&self, bookmark: &str, remote: &str, status: PushStatus) {
+        self.send(ProgressEvent::MirrorPush {
+            bookmark: bookmark.to_string(),
+            remote: remote.to_string(),
+            status,
+        });
+    }
+
+    async fn on_pr_created(&self, bookmark: &str, pr: &PullRequest) {
+        self.send(ProgressEvent::PrCreated {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_pr_updated(&self, bookmark: &str, pr: &PullRequest) {
+        self.send(ProgressEvent::PrUpdated {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_pr_merged(&self, bookmark: &str, pr: &PullRequest) {
+        self.send(ProgressEvent::PrMerged {
+            bookmark: bookmark.to_string(),
+            pr: pr.clone(),
+        });
+    }
+
+    async fn on_error(&self, error: &Error) {
+        self.send(ProgressEvent::Error {
+            message: error.to_string(),
+        });
+    }
+
+    async fn on_warning(&self, warning: &Warning) {
+        self.send(ProgressEvent::Warning {
+            warning: warning.clone(),
+        });
+    }
+
+    async fn on_message(&self, message: &str) {
+        self.send(ProgressEvent::Message {
+            message: message.to_string(),
+        });
+    }
+}
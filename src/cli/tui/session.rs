@@ -0,0 +1,199 @@
+//! The repository and platform the UI works on, and the actions it runs
+
+use crate::cli::context::open_workspace;
+use crate::cli::platform::platform_for_remote;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::lock::check_stack_lock;
+use jj_ryu_core::platform::{
+    PlatformService, create_comment_platform_service, create_platform_service,
+};
+use jj_ryu_core::repo::{JjWorkspace, select_fetch_remotes};
+use jj_ryu_core::restack::{plan_restacks, restack_onto_trunk, trunk_commit_id};
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::status::{StackStatus, stack_statuses};
+use jj_ryu_core::submit::{
+    ProgressCallback, SubmissionResult, analyze_submission, check_bookmark_names,
+    create_submission_plan, drafts_by_default, execute_submission, find_foreign_commits,
+    find_non_fast_forwards, force_push_allowed, tracking_issue_enabled,
+};
+use jj_ryu_core::tui::Action;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Workspace and platform services, opened once for the whole session
+pub struct Session {
+    workspace: JjWorkspace,
+    platform: Box<dyn PlatformService>,
+    comment_platform: Option<Box<dyn PlatformService>>,
+    fetch_remotes: Vec<String>,
+    push_remote: String,
+}
+
+impl Session {
+    /// Open the workspace and connect to the platform of `remote`
+    pub async fn open(path: &Path, remote: Option<&str>) -> Result<Self> {
+        let workspace = open_workspace(path)?;
+        let remotes = workspace.git_remotes()?;
+        let remote_name = workspace.primary_remote(&remotes, remote)?;
+        let remote_info = remotes
+            .iter()
+            .find(|r| r.name == remote_name)
+            .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+        let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+        let push_remote = workspace.push_remote(&remotes, &remote_name)?;
+        let mut fetch_remotes = select_fetch_remotes(&remotes, &remote_name);
+        if !fetch_remotes.contains(&push_remote) {
+            fetch_remotes.push(push_remote.clone());
+        }
+
+        Ok(Self {
+            platform: create_platform_service(&platform_config, workspace.run()).await?,
+            comment_platform: create_comment_platform_service(&platform_config, workspace.run())?,
+            workspace,
+            fetch_remotes,
+            push_remote,
+        })
+    }
+
+    /// Every stack with the PR and CI state of its bookmarks
+    pub async fn load_stacks(&self) -> Result<Vec<StackStatus>> {
+        let graph = build_change_graph(&self.workspace)?;
+        let overrides: HashMap<_, _> = RyuState::load(self.workspace.workspace_root())?
+            .base_overrides
+            .into_iter()
+            .collect();
+        stack_statuses(
+            &graph,
+            self.platform.as_ref(),
+            &self.workspace.default_branch()?,
+            &overrides,
+        )
+        .await
+    }
+
+    /// Run a submit or sync, returning a one-line summary
+    pub async fn run(
+        &mut self,
+        action: &Action,
+        progress: &dyn ProgressCallback,
+    ) -> Result<String> {
+        let (done, result) = match action {
+            Action::Submit(bookmark) => (
+                format!("Submitted {bookmark}"),
+                self.submit(bookmark, progress).await?,
+            ),
+            Action::Sync(leaf) => {
+                self.fetch_and_restack(leaf, progress).await?;
+                (format!("Synced {leaf}"), self.submit(leaf, progress).await?)
+            }
+            Action::Refresh => return Ok("Refreshed".to_string()),
+        };
+        if !result.success {
+            return Err(Error::Internal(result.errors.join("; ")));
+        }
+        Ok(format!(
+            "{done}: {} pushed, {} created, {} updated",
+            result.pushed_bookmarks.len(),
+            result.created_prs.len(),
+            result.updated_prs.len()
+        ))
+    }
+
+    /// Plan and execute `ryu submit <bookmark>` with the configured defaults
+    ///
+    /// Commits by other authors, bookmark names git rejects and disallowed
+    /// force pushes stop the submission: the UI can't ask for confirmation
+    /// the way the commands do.
+    async fn submit(
+        &mut self,
+        bookmark: &str,
+        progress: &dyn ProgressCallback,
+    ) -> Result<SubmissionResult> {
+        let graph = build_change_graph(&self.workspace)?;
+        let state = RyuState::load(self.workspace.workspace_root())?;
+        let mut analysis = analyze_submission(&graph, bookmark)?;
+        analysis.base_overrides = state.base_overrides.clone().into_iter().collect();
+
+        if let Some(email) = self.workspace.user_email() {
+            let foreign = find_foreign_commits(&analysis, &email);
+            if !foreign.is_empty() {
+                return Err(Error::ForeignCommits(foreign.len()));
+            }
+        }
+        let bad_names = check_bookmark_names(
+            analysis.segments.iter().map(|s| s.bookmark.name.as_str()),
+            self.workspace.config().branch_pattern.as_deref(),
+        )?;
+        if !bad_names.is_empty() {
+            return Err(Error::InvalidBookmarkNames(bad_names.len()));
+        }
+        if !force_push_allowed(self.workspace.config())? {
+            let diverged = find_non_fast_forwards(&self.workspace, &analysis, &self.push_remote)?;
+            if let Some(first) = diverged.first() {
+                return Err(Error::ForcePushDisabled {
+                    bookmark: first.bookmark.clone(),
+                    remote: self.push_remote.clone(),
+                    behind: first.behind,
+                });
+            }
+        }
+
+        let mut plan = create_submission_plan(
+            &analysis,
+            self.platform.as_ref(),
+            &self.push_remote,
+            &self.workspace.default_branch()?,
+        )
+        .await?;
+        plan.tracking_issue = tracking_issue_enabled(self.workspace.config())?;
+        plan.stack_info = self.workspace.config().stack_info();
+        plan.interdiff_comments = self.workspace.config().interdiff_comments.unwrap_or(true);
+        if drafts_by_default(self.workspace.config())? {
+            plan.draft_new_prs();
+        }
+        if let Some(lock) = check_stack_lock(&state, self.platform.as_ref(), &plan).await? {
+            return Err(lock.to_error());
+        }
+
+        execute_submission(
+            &plan,
+            &mut self.workspace,
+            self.platform.as_ref(),
+            self.comment_platform.as_deref(),
+            progress,
+            false,
+        )
+        .await
+    }
+
+    /// Fetch, then rebase the stack with leaf `leaf` onto trunk's new head
+    async fn fetch_and_restack(
+        &mut self,
+        leaf: &str,
+        progress: &dyn ProgressCallback,
+    ) -> Result<()> {
+        progress
+            .on_message(&format!("Fetching from {}", self.fetch_remotes.join(", ")))
+            .await;
+        self.workspace.git_fetch_all(&self.fetch_remotes)?;
+
+        let Some(trunk) = trunk_commit_id(&self.workspace)? else {
+            return Ok(());
+        };
+        let graph = build_change_graph(&self.workspace)?;
+        let stacks: Vec<_> = graph
+            .stacks
+            .iter()
+            .filter(|stack| {
+                stack
+                    .segments
+                    .iter()
+                    .any(|seg| seg.bookmarks.iter().any(|b| b.name == leaf))
+            })
+            .collect();
+        let restacks = plan_restacks(&stacks, &trunk);
+        restack_onto_trunk(&mut self.workspace, &restacks, &trunk, progress).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Undo command - restore the repo to before ryu's last command
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::{open_workspace, run_context};
+use crate::cli::outcome::Outcome;
+use crate::cli::style::{Stylize, arrow, check, cross};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::platform::{PlatformService, create_platform_service};
+use jj_ryu_core::run::RunContext;
+use jj_ryu_core::types::PlatformConfig;
+use jj_ryu_core::undo::{
+    RemoteChange, UndoRecord, clear_undo_record, load_undo_record, save_undo_record,
+};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Length operation IDs are shown at, as `jj op log` does
+const SHORT_OP_ID: usize = 12;
+
+/// Start recording `command` for `ryu undo`
+///
+/// Outside a jj repo there is nothing to record; the command reports that
+/// itself.
+pub fn begin_undo(path: &Path, command: &str) {
+    if let Ok(workspace) = open_workspace(path) {
+        if let Ok(operation_id) = workspace.operation_id() {
+            workspace
+                .run()
+                .undo()
+                .begin(workspace.workspace_root(), command, operation_id);
+        }
+    }
+}
+
+/// Save the record of the command that just ran, even if it failed part-way
+///
+/// Recording never fails the command.
+pub fn finish_undo() {
+    let _ = run_context()
+        .undo()
+        .finish(|root| open_workspace(root)?.operation_id());
+}
+
+/// Run the undo command
+///
+/// Restores the repo to the jj operation recorded before the last ryu
+/// command that changed something, then lists what that command did on the
+/// remote, which restoring doesn't take back. With `close_created`, the PRs
+/// it opened are closed; those that can't be stay recorded for the next
+/// `ryu undo --close-created`. Refuses if the repo changed after the
+/// command, unless `force`. A dry run reports [`Outcome::ChangesPending`] if
+/// there is something to undo.
+pub async fn run_undo(
+    path: &Path,
+    close_created: bool,
+    dry_run: bool,
+    force: bool,
+) -> Result<Outcome> {
+    let mut workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let root = workspace.workspace_root().to_path_buf();
+    let Some(record) = load_undo_record(&root)? else {
+        println!("{}", "Nothing to undo".muted());
+        return Ok(Outcome::Done);
+    };
+
+    let head = workspace.snapshot_operation_id()?;
+    if head != record.final_operation_id && !force {
+        return Err(Error::Workspace(format!(
+            "the repo changed after `ryu {}` (now at operation {}); restoring would \
+             discard that too - pass --force to restore anyway",
+            record.command,
+            short_op(&head)
+        )));
+    }
+
+    println!(
+        "{} {}",
+        if dry_run { "Would undo" } else { "Undoing" }.emphasis(),
+        format!(
+            "`ryu {}` from {}",
+            record.command,
+            record.started_at.format("%Y-%m-%d %H:%M UTC")
+        )
+        .accent()
+    );
+    let operation = short_op(&record.operation_id);
+    // Until the PRs are closed, a record to close them from
+    let mut pending = None;
+    if dry_run {
+        println!(
+            "  {} would restore the repo to operation {}",
+            arrow(),
+            operation.accent()
+        );
+    } else {
+        if record.changed_repo() {
+            workspace.restore_operation(&record.operation_id)?;
+            println!(
+                "  {} Restored the repo to operation {}",
+                check(),
+                operation.accent()
+            );
+        }
+        if close_created {
+            pending = unclosed(&record, workspace.snapshot_operation_id()?, |_| true);
+        }
+        match &pending {
+            Some(pending) => save_undo_record(&root, pending)?,
+            None => clear_undo_record(&root)?,
+        }
+    }
+
+    let failed = if close_created {
+        close_created_prs(&record, workspace.run(), dry_run).await
+    } else {
+        HashSet::new()
+    };
+    print_remote_changes(&record, close_created);
+
+    if let Some(pending) = pending {
+        match unclosed(&pending, pending.operation_id.clone(), |n| {
+            failed.contains(&n)
+        }) {
+            Some(still_open) => save_undo_record(&root, &still_open)?,
+            None => clear_undo_record(&root)?,
+        }
+    }
+    if failed.is_empty() {
+        return Ok(Outcome::pending_if(dry_run));
+    }
+    Err(Error::Platform(format!(
+        "Failed to close {} of {} PRs; `ryu undo --close-created` tries them again",
+        failed.len(),
+        record.created_prs().count()
+    )))
+}
+
+/// Record of the PRs of `record` that `keep` selects, to close with a later
+/// `ryu undo --close-created` once the repo is at `operation_id`
+fn unclosed(
+    record: &UndoRecord,
+    operation_id: String,
+    keep: impl Fn(u64) -> bool,
+) -> Option<UndoRecord> {
+    let remote_changes: Vec<RemoteChange> = record
+        .remote_changes
+        .iter()
+        .filter(|change| matches!(change, RemoteChange::CreatedPr { number, .. } if keep(*number)))
+        .cloned()
+        .collect();
+    (!remote_changes.is_empty()).then(|| UndoRecord {
+        final_operation_id: operation_id.clone(),
+        operation_id,
+        remote_changes,
+        ..record.clone()
+    })
+}
+
+/// Close the PRs `record`'s command opened, each in the repository it was
+/// opened in, returning the numbers of those that couldn't be closed
+async fn close_created_prs(
+    record: &UndoRecord,
+    run: &Arc<RunContext>,
+    dry_run: bool,
+) -> HashSet<u64> {
+    let mut failed = HashSet::new();
+    // One service per repository, or why it couldn't be created
+    let mut platforms: Vec<(
+        &PlatformConfig,
+        std::result::Result<Box<dyn PlatformService>, String>,
+    )> = Vec::new();
+    for (number, url, config) in record.created_prs() {
+        let pr = format!("#{number}");
+        if dry_run {
+            println!("  {} would close {} {}", arrow(), pr.accent(), url.muted());
+            continue;
+        }
+        let index = if let Some(index) = platforms.iter().position(|(c, _)| *c == config) {
+            index
+        } else {
+            let platform = create_platform_service(config, run)
+                .await
+                .map_err(|e| e.to_string());
+            platforms.push((config, platform));
+            platforms.len() - 1
+        };
+        let closed = match &platforms[index].1 {
+            Ok(platform) => platform.close_pr(number).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.clone()),
+        };
+        match closed {
+            Ok(()) => println!("  {} Closed {} {}", check(), pr.accent(), url.muted()),
+            Err(e) => {
+                failed.insert(number);
+                println!("  {} {pr}: {e}", cross().for_stdout());
+            }
+        }
+    }
+    failed
+}
+
+/// List what restoring didn't take back
+fn print_remote_changes(record: &UndoRecord, closed_created: bool) {
+    let changes: Vec<String> = record
+        .remote_changes
+        .iter()
+        .filter(|change| !(closed_created && matches!(change, RemoteChange::CreatedPr { .. })))
+        .map(ToString::to_string)
+        .collect();
+    if changes.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{}", "Not undone on the remote:".emphasis());
+    for change in &changes {
+        println!("  {} {change}", arrow());
+    }
+    if record
+        .remote_changes
+        .iter()
+        .any(|change| matches!(change, RemoteChange::Pushed { .. }))
+    {
+        println!();
+        println!(
+            "{}",
+            "Pushed branches keep their new commits until the restored bookmarks are \
+             pushed again, e.g. by `ryu submit`"
+                .muted()
+        );
+    }
+}
+
+fn short_op(operation_id: &str) -> &str {
+    &operation_id[..operation_id.len().min(SHORT_OP_ID)]
+}
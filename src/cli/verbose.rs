@@ -0,0 +1,88 @@
+//! Run summary for `--verbose`
+//!
+//! Counts platform calls, response bytes, cache hits and git operations
+//! while the command runs, then prints where the time went - the numbers
+//! to attach when reporting that ryu is slow.
+
+use crate::cli::context::run_context;
+use crate::cli::style::Stylize;
+use anstream::eprintln;
+use jj_ryu_core::metrics::{CallStats, Metrics};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Start counting for the summary. Call at startup, once the run context is
+/// set.
+pub fn enable_run_summary() {
+    run_context().metrics().enable();
+}
+
+/// Print the summary to stderr, if `--verbose` started counting
+pub fn print_run_summary() {
+    if let Some(metrics) = run_context().metrics().snapshot() {
+        for line in summary_lines(&metrics) {
+            eprintln!("{line}");
+        }
+    }
+}
+
+fn summary_lines(metrics: &Metrics) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} {}",
+        "Run summary".emphasis(),
+        format!("({})", seconds(metrics.started.elapsed())).muted()
+    )];
+
+    let api = metrics.api_total();
+    let mut api_line = format!("  Platform calls: {} ({})", api.calls, seconds(api.time));
+    if metrics.response_bytes > 0 {
+        api_line.push_str(&format!(", {} received", bytes(metrics.response_bytes)));
+    }
+    if metrics.cache_hits > 0 {
+        api_line.push_str(&format!(
+            ", {} cache hit{}",
+            metrics.cache_hits,
+            if metrics.cache_hits == 1 { "" } else { "s" }
+        ));
+    }
+    lines.push(api_line);
+    lines.extend(breakdown(&metrics.api_calls));
+
+    let git = metrics.git_total();
+    lines.push(format!(
+        "  Git operations: {} ({})",
+        git.calls,
+        seconds(git.time)
+    ));
+    lines.extend(breakdown(&metrics.git_ops));
+    lines
+}
+
+/// One muted line per entry, slowest first
+fn breakdown(stats: &BTreeMap<&'static str, CallStats>) -> Vec<String> {
+    let width = stats.keys().map(|name| name.len()).max().unwrap_or(0);
+    let mut entries: Vec<_> = stats.iter().collect();
+    entries.sort_by(|a, b| b.1.time.cmp(&a.1.time));
+    entries
+        .into_iter()
+        .map(|(name, s)| {
+            format!(
+                "    {}",
+                format!("{name:<width$}  {:>3}  {}", s.calls, seconds(s.time)).muted()
+            )
+        })
+        .collect()
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn bytes(count: u64) -> String {
+    match count {
+        0..1024 => format!("{count} B"),
+        1024..1_048_576 => format!("{:.1} KiB", count as f64 / 1024.0),
+        _ => format!("{:.1} MiB", count as f64 / 1_048_576.0),
+    }
+}
@@ -0,0 +1,81 @@
+//! Verify command - check that stacks, PRs and the remote agree
+
+use crate::cli::compat::warn_on_cli_fallback;
+use crate::cli::context::open_workspace;
+use crate::cli::outcome::Outcome;
+use crate::cli::platform::platform_for_remote;
+use crate::cli::style::{Stylize, arrow, check, cross};
+use anstream::println;
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::platform::create_platform_service;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::verify::verify_stacks;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Run the verify command
+///
+/// Lists every broken invariant with the command that fixes it. Finding any
+/// is reported through the exit code, like a dry run with changes pending.
+pub async fn run_verify(path: &Path, remote: Option<&str>) -> Result<Outcome> {
+    let workspace = open_workspace(path)?;
+    warn_on_cli_fallback(&workspace);
+
+    let remotes = workspace.git_remotes()?;
+    let remote_name = workspace.primary_remote(&remotes, remote)?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = platform_for_remote(&workspace, &remote_info.url)?;
+    let push_remote = workspace.push_remote(&remotes, &remote_name)?;
+    let platform = create_platform_service(&platform_config, workspace.run()).await?;
+
+    let graph = build_change_graph(&workspace)?;
+    if graph.stacks.is_empty() {
+        println!("{}", "No bookmark stacks found".muted());
+        return Ok(Outcome::Done);
+    }
+
+    let overrides: HashMap<_, _> = RyuState::load(workspace.workspace_root())?
+        .base_overrides
+        .into_iter()
+        .collect();
+    let violations = verify_stacks(
+        &workspace,
+        &graph,
+        platform.as_ref(),
+        &push_remote,
+        &workspace.default_branch()?,
+        &overrides,
+        workspace.config().stack_info(),
+    )
+    .await?;
+
+    let stacks = graph.stacks.len();
+    if violations.is_empty() {
+        println!(
+            "{} {} stack{} agree{} with {} and the platform",
+            check(),
+            stacks.accent(),
+            if stacks == 1 { "" } else { "s" },
+            if stacks == 1 { "s" } else { "" },
+            push_remote.accent()
+        );
+        return Ok(Outcome::Done);
+    }
+
+    for violation in &violations {
+        println!("{} {violation}", cross().for_stdout());
+        println!("    {} {}", arrow(), violation.fix().accent());
+    }
+    println!();
+    println!(
+        "{} problem{} found",
+        violations.len().accent(),
+        if violations.len() == 1 { "" } else { "s" }
+    );
+
+    Ok(Outcome::ChangesPending)
+}
@@ -0,0 +1,46 @@
+//! Webhook notification shared by submit and sync
+
+use crate::cli::output::println;
+use crate::cli::strict::note_warning;
+use crate::cli::style::Stylize;
+use jj_ryu_core::notify::{WEBHOOK_URL_ENV, WebhookEvent, WebhookPayload, send_webhook};
+use jj_ryu_core::repo::JjWorkspace;
+use jj_ryu_core::submit::SubmissionResult;
+use jj_ryu_core::types::PlatformConfig;
+
+/// Post a notification if a webhook URL is configured
+///
+/// Runs that changed nothing are not announced. Failures only warn, since
+/// the submission itself already happened.
+pub async fn notify_webhook(
+    event: WebhookEvent,
+    workspace: &JjWorkspace,
+    config: &PlatformConfig,
+    stack: &str,
+    pr_count: usize,
+    result: &SubmissionResult,
+) {
+    let Ok(url) = std::env::var(WEBHOOK_URL_ENV) else {
+        return;
+    };
+    if result.pushed_bookmarks.is_empty()
+        && result.created_prs.is_empty()
+        && result.updated_prs.is_empty()
+    {
+        return;
+    }
+
+    let repository = format!("{}/{}", config.owner, config.repo);
+    let payload = WebhookPayload::new(
+        event,
+        &repository,
+        workspace.user_email(),
+        stack,
+        pr_count,
+        result,
+    );
+    if let Err(e) = send_webhook(&url, &payload, workspace.run()).await {
+        println!("{}", format!("Webhook notification failed: {e}").warn());
+        note_warning();
+    }
+}
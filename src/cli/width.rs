@@ -0,0 +1,110 @@
+//! Terminal width - fitting output lines to the terminal
+//!
+//! Lines written to a terminal are cut to its width by ellipsizing the part
+//! that can give: descriptions, titles, link text and table columns. Piped
+//! output isn't cut, so it wraps in a pager and `grep` still sees every
+//! character. `COLUMNS` overrides the detected width either way.
+
+use crate::cli::style::{Stream, hyperlink, hyperlinks_on};
+use console::Term;
+use std::borrow::Cow;
+
+/// Narrowest a fitted field gets, however little room the line leaves
+const MIN_FIELD_WIDTH: usize = 12;
+
+/// Marks text that was cut
+const ELLIPSIS: &str = "…";
+
+/// Columns available on `stream`, or `None` when it isn't a terminal
+pub fn terminal_width(stream: Stream) -> Option<usize> {
+    let columns = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse::<usize>().ok())
+        .filter(|&columns| columns > 0);
+    if columns.is_some() {
+        return columns;
+    }
+    let term = match stream {
+        Stream::Stdout => Term::stdout(),
+        Stream::Stderr => Term::stderr(),
+    };
+    term.size_checked().map(|(_, columns)| usize::from(columns))
+}
+
+/// Columns `text` takes up, ignoring escape sequences
+pub fn display_width(text: &str) -> usize {
+    console::measure_text_width(text)
+}
+
+/// `text` cut to `width` columns, ending in an ellipsis if it was cut
+pub fn ellipsize(text: &str, width: usize) -> Cow<'_, str> {
+    // `truncate_str` cuts text that exactly fills `width`, and has no room
+    // for even the ellipsis at zero
+    if display_width(text) <= width {
+        Cow::Borrowed(text)
+    } else if width == 0 {
+        Cow::Borrowed("")
+    } else {
+        console::truncate_str(text, width, ELLIPSIS)
+    }
+}
+
+/// Fit `text` into what's left of a line once `used` columns are taken
+///
+/// `text` is returned whole when `stream` isn't a terminal.
+pub fn fit(stream: Stream, used: usize, text: &str) -> Cow<'_, str> {
+    fit_within(terminal_width(stream), used, text)
+}
+
+/// [`fit`] for a line `width` columns wide, `None` meaning unlimited
+fn fit_within(width: Option<usize>, used: usize, text: &str) -> Cow<'_, str> {
+    match width {
+        Some(width) => ellipsize(text, width.saturating_sub(used).max(MIN_FIELD_WIDTH)),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Fit a URL into what's left of a line once `used` columns are taken
+///
+/// Only the shown text is cut, and only when it links to the full URL; a
+/// URL printed as plain text stays whole so it can still be copied.
+pub fn fit_url(stream: Stream, used: usize, url: &str) -> String {
+    let shown = url_text(terminal_width(stream), hyperlinks_on(stream), used, url);
+    hyperlink(stream, &shown, url)
+}
+
+/// The text [`fit_url`] shows for `url`
+fn url_text(width: Option<usize>, linked: bool, used: usize, url: &str) -> Cow<'_, str> {
+    if linked {
+        fit_within(width, used, url)
+    } else {
+        Cow::Borrowed(url)
+    }
+}
+
+/// Narrow the `flexible` columns of a table, widest first, until a row fits
+///
+/// `used` is the width of a row outside its cells (indent and separators).
+/// Nothing changes when `stream` isn't a terminal or the row already fits.
+pub fn fit_columns(stream: Stream, used: usize, widths: &mut [usize], flexible: &[usize]) {
+    fit_columns_within(terminal_width(stream), used, widths, flexible);
+}
+
+/// [`fit_columns`] for a line `width` columns wide, `None` meaning unlimited
+fn fit_columns_within(width: Option<usize>, used: usize, widths: &mut [usize], flexible: &[usize]) {
+    let Some(width) = width else {
+        return;
+    };
+    let mut total = used + widths.iter().sum::<usize>();
+    while total > width {
+        let Some(&widest) = flexible
+            .iter()
+            .filter(|&&i| widths[i] > MIN_FIELD_WIDTH)
+            .max_by_key(|&&i| widths[i])
+        else {
+            return;
+        };
+        widths[widest] -= 1;
+        total -= 1;
+    }
+}
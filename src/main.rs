@@ -3,21 +3,91 @@
 //! CLI binary for managing stacked pull requests with jj.
 
 use anyhow::Result;
+use clap::builder::PossibleValuesParser;
 use clap::{Parser, Subcommand};
-use jj_ryu::types::Platform;
+use jj_ryu_core::budget::{ApiBudget, parse_time_budget};
+use jj_ryu_core::config::RyuConfig;
+use jj_ryu_core::crash::install_crash_handler;
+use jj_ryu_core::platform::RetryPolicy;
+use jj_ryu_core::read_only::{read_only_from_env, set_read_only};
+use jj_ryu_core::redact::{RedactingFields, redact_secrets};
+use jj_ryu_core::run::RunContext;
+use jj_ryu_core::schema::SchemaType;
+use jj_ryu_core::types::{MergeMethod, Platform, StackInfo};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
 
 mod cli;
 
 #[derive(Parser)]
 #[command(name = "ryu")]
-#[command(about = "Stacked PRs for Jujutsu - GitHub & GitLab")]
+#[command(about = "Stacked PRs for Jujutsu - GitHub, GitLab, Bitbucket, Gitea & Azure DevOps")]
 #[command(version)]
 struct Cli {
     /// Path to jj repository (defaults to current directory)
     #[arg(short, long, global = true)]
     path: Option<PathBuf>,
 
+    /// Output format; json prints one JSON document on stdout (analyze, submit, sync and hover)
+    #[arg(long, global = true, value_enum, default_value_t = cli::OutputFormat::Text)]
+    format: cli::OutputFormat,
+
+    /// Use this platform instead of detecting it from the remote; fake keeps PRs in the workspace
+    #[arg(long, global = true, value_enum)]
+    platform: Option<cli::PlatformArg>,
+
+    /// Revset to use as trunk instead of jj's `trunk()` alias (e.g. develop@origin)
+    #[arg(long, global = true, value_name = "REVSET")]
+    trunk_revset: Option<String>,
+
+    /// Only consider bookmarks on changes you authored (jj's mine())
+    #[arg(long, global = true)]
+    mine: bool,
+
+    /// Only consider bookmarks matching GLOB (repeatable)
+    #[arg(long = "include-bookmarks", global = true, value_name = "GLOB")]
+    include_bookmarks: Vec<String>,
+
+    /// Ignore bookmarks matching GLOB (repeatable)
+    #[arg(long = "exclude-bookmarks", global = true, value_name = "GLOB")]
+    exclude_bookmarks: Vec<String>,
+
+    /// Stop submit/sync gracefully after N platform API calls (resume later)
+    #[arg(long, global = true, value_name = "N")]
+    max_api_calls: Option<u64>,
+
+    /// Stop submit/sync gracefully after this long, e.g. 30s or 5m (resume later)
+    #[arg(long, global = true, value_name = "TIME", value_parser = parse_time_budget)]
+    budget: Option<Duration>,
+
+    /// Retry a failed or rate-limited platform request up to N times (default 3; 0 disables)
+    #[arg(long, global = true, value_name = "N")]
+    max_retries: Option<u32>,
+
+    /// Longest one platform request may wait across its retries, e.g. 30s or 5m (default 2m)
+    #[arg(long, global = true, value_name = "TIME", value_parser = parse_time_budget)]
+    retry_budget: Option<Duration>,
+
+    /// Skip every platform write and repo change, logging each instead
+    /// [default: $RYU_READ_ONLY, else `read-only` config]
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Exit with an error if any warning was reported, for CI
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Print platform call, git operation and timing counts when the command finishes
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// With no command, also look up each pushed bookmark's open PR and its
+    /// CI and review state [default: `analyze-checks` config]
+    #[arg(long)]
+    checks: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -26,10 +96,19 @@ struct Cli {
 enum Commands {
     /// Submit a bookmark stack as PRs
     Submit {
-        /// Bookmark name to submit
-        bookmark: String,
+        /// Bookmark name to submit (or a revision, with --auto-bookmark);
+        /// defaults to the stack the working copy is on
+        bookmark: Option<String>,
+
+        /// Submit every stack, each up to its leaf bookmark
+        #[arg(
+            long,
+            conflicts_with_all = ["bookmark", "scope", "select", "resume", "auto_bookmark", "base"]
+        )]
+        all: bool,
 
         /// Dry run - show what would be done without making changes
+        /// (exits 3 if there are changes to submit)
         #[arg(long)]
         dry_run: bool,
 
@@ -65,14 +144,123 @@ enum Commands {
         #[arg(long, short = 'i')]
         select: bool,
 
+        /// Submit even if the stack contains commits authored by someone else
+        #[arg(long)]
+        allow_foreign_commits: bool,
+
+        /// Add created PRs to a GitHub project (owner/number) or GitLab board list label
+        /// [default: $RYU_PROJECT, else `project` config]
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Also push bookmarks to this remote; PRs stay on the primary (repeatable)
+        /// [default: $RYU_MIRROR_REMOTES, else `mirror-remotes` config]
+        #[arg(long = "mirror", value_name = "REMOTE")]
+        mirrors: Vec<String>,
+
+        /// Also create/update PRs on this remote's platform, e.g. a GitLab mirror (repeatable)
+        /// [default: $RYU_PR_REMOTES, else `pr-remotes` config]
+        #[arg(long = "pr-remote", value_name = "REMOTE")]
+        pr_remotes: Vec<String>,
+
+        /// Create or update an issue tracking every PR in the stack
+        /// [default: $RYU_TRACKING_ISSUE, else `tracking-issue` config]
+        #[arg(long)]
+        tracking_issue: bool,
+
+        /// Continue a submission that stopped part-way, skipping the steps
+        /// that already succeeded
+        #[arg(
+            long,
+            conflicts_with_all = ["dry_run", "confirm", "scope", "update_only", "draft", "publish", "select", "stack_info"]
+        )]
+        resume: bool,
+
+        /// Create bookmarks for changes between trunk and the target that
+        /// have none, named from auto-bookmark-template; the target may then
+        /// be a revision such as @-
+        #[arg(long, conflicts_with = "resume")]
+        auto_bookmark: bool,
+
+        /// Branch the stack is based on and its root PR targets, instead of
+        /// trunk, e.g. a release branch
+        #[arg(long, value_name = "BRANCH", conflicts_with_all = ["resume", "auto_bookmark"])]
+        base: Option<String>,
+
+        /// Where each PR lists its stack: comment, body or none
+        /// [default: stack-info from the config files, else comment]
+        #[arg(long, value_name = "MODE")]
+        stack_info: Option<StackInfo>,
+
         /// Git remote to push to
         #[arg(long)]
         remote: Option<String>,
     },
 
+    /// Plan submitting a stack and write the plan to a file for `ryu apply`
+    ///
+    /// Only reads the platform, so it works without push rights.
+    Plan {
+        /// Bookmark name to plan
+        bookmark: String,
+
+        /// File to write the plan to
+        #[arg(long, short = 'o')]
+        output: PathBuf,
+
+        /// Git remote to plan against
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Execute a plan written by `ryu plan`, e.g. on a CI runner
+    ///
+    /// Refuses plans made for another repository or whose bookmarks have moved.
+    Apply {
+        /// Plan file from `ryu plan`
+        file: PathBuf,
+
+        /// Show the plan's steps without executing them
+        /// (exits 3 if the plan has steps)
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Take over PRs opened outside ryu, e.g. with `git push` and the web UI
+    ///
+    /// Matches bookmarks to open PRs by head branch, fixes their bases to
+    /// follow the local stack and writes stack comments. Nothing is pushed.
+    Adopt {
+        /// A bookmark of the stack to adopt; defaults to every stack
+        bookmark: Option<String>,
+
+        /// Show what would change without touching the PRs
+        /// (exits 3 if any PR would be adopted)
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Git remote the PRs are on
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Recreate a teammate's stack locally from the stack comment on one of
+    /// its PRs
+    ///
+    /// Fetches the stack's branches and tracks them as local bookmarks.
+    Import {
+        /// URL or number of any PR in the stack
+        pr: String,
+
+        /// Git remote the stack's branches are on
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
     /// Sync all stacks with remote
     Sync {
-        /// Dry run - show what would be done without making changes
+        /// Dry run - report per-stack drift without making changes
+        /// (exits 3 if any stack has drifted)
         #[arg(long)]
         dry_run: bool,
 
@@ -84,11 +272,304 @@ enum Commands {
         #[arg(long)]
         stack: Option<String>,
 
+        /// Sync even if a stack contains commits authored by someone else
+        #[arg(long)]
+        allow_foreign_commits: bool,
+
+        /// Add created PRs to a GitHub project (owner/number) or GitLab board list label
+        /// [default: $RYU_PROJECT, else `project` config]
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Also push bookmarks to this remote; PRs stay on the primary (repeatable)
+        /// [default: $RYU_MIRROR_REMOTES, else `mirror-remotes` config]
+        #[arg(long = "mirror", value_name = "REMOTE")]
+        mirrors: Vec<String>,
+
+        /// Don't rebase stacks onto trunk's new head after fetching
+        #[arg(long)]
+        no_restack: bool,
+
+        /// Delete bookmarks whose PRs were merged, abandon their changes and
+        /// delete their remote branches [default: `sync-prune` config]
+        #[arg(long)]
+        prune: bool,
+
+        /// Where each PR lists its stack: comment, body or none
+        /// [default: stack-info from the config files, else comment]
+        #[arg(long, value_name = "MODE")]
+        stack_info: Option<StackInfo>,
+
+        /// Git remote to sync with
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Merge the PR at the bottom of a stack, then restack the rest onto trunk
+    Merge {
+        /// Bookmark whose PR to merge (must be at the bottom of its stack)
+        bookmark: String,
+
+        /// How to merge: merge, squash or rebase
+        /// [default: merge-method from the config files, else squash]
+        #[arg(long)]
+        method: Option<MergeMethod>,
+
+        /// Git remote the PR belongs to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Merge every stack's bottom PR that is approved and green, restack,
+    /// and repeat until nothing else is ready; or land one whole stack
+    Land {
+        /// Land all ready PRs across stacks
+        #[arg(long, group = "land_scope", required_unless_present = "stack")]
+        all_ready: bool,
+
+        /// Merge the PRs of the stack up to BOOKMARK, bottom first
+        #[arg(long, group = "land_scope", value_name = "BOOKMARK")]
+        stack: Option<String>,
+
+        /// With --stack, wait for each PR's checks to pass before merging it
+        #[arg(long, requires = "stack")]
+        wait_for_checks: bool,
+
+        /// How to merge: merge, squash or rebase
+        /// [default: merge-method from the config files, else squash]
+        #[arg(long)]
+        method: Option<MergeMethod>,
+
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Finish a restack that stopped on conflicts, then sync the restacked stacks
+    ///
+    /// Run after resolving the conflicts `sync` or `land` reported.
+    RebaseContinue {
         /// Git remote to sync with
         #[arg(long)]
         remote: Option<String>,
     },
 
+    /// Show each stack's PRs with their state (open/draft/merged/closed)
+    Status {
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Check that stacks, their PRs and the remote agree, suggesting fixes
+    ///
+    /// Every bookmark should have one open PR targeting the bookmark below it
+    /// (or trunk), be pushed at its local commit, and carry a stack comment
+    /// listing its stack. Exits with status 3 when something is off.
+    Verify {
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Browse stacks and submit or sync them interactively
+    #[cfg(feature = "tui")]
+    Ui {
+        /// Git remote to submit and sync against
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Suggest reordering or splitting stacks based on the files segments change
+    SuggestBase {
+        /// Only look at the stack containing this bookmark
+        bookmark: Option<String>,
+    },
+
+    /// Find the first segment of a stack at which a command fails
+    ///
+    /// Checks out trunk and then each segment's bookmark, from trunk up to
+    /// BOOKMARK, with `jj new` and runs the command there; the first failing
+    /// segment is reported with its PR. The working copy is restored after.
+    BisectStack {
+        /// Bookmark to test up to (usually the stack's leaf)
+        bookmark: String,
+
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+
+        /// Command to run at each boundary; passes when it exits 0
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+
+    /// Delete local bookmarks whose PRs were merged
+    Clean {
+        /// Also delete their branches on the fork remote (`fork-remote`)
+        #[arg(long)]
+        fork: bool,
+
+        /// Show what would be deleted without deleting it
+        /// (exits 3 if anything would be deleted)
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Prune ryu's local state, journals and usage log
+    ///
+    /// Drops base overrides and journals for deleted bookmarks, stale stack
+    /// locks, journals and usage events older than `gc-retention-days`
+    /// (default 30) and leftover temp files. A successful `sync` prunes the
+    /// state and journals itself.
+    Gc {
+        /// Show what would be pruned without pruning it
+        /// (exits 3 if anything would be pruned)
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Restore the repo to before the last ryu command that changed something
+    ///
+    /// Runs `jj op restore` to the operation recorded before the command,
+    /// then lists what it did on the remote (pushes, PRs), which restoring
+    /// doesn't take back.
+    Undo {
+        /// Also close the PRs the command opened
+        #[arg(long)]
+        close_created: bool,
+
+        /// Show what would be undone without undoing it
+        /// (exits 3 if there is something to undo)
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Restore even if the repo changed after the command
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print effective configuration and where each value came from
+    Env {
+        /// Git remote to report on
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Show which stack segment and PR last changed a line, for editor integrations
+    Hover {
+        /// Location as <file>:<line> (line numbers start at 1)
+        location: String,
+
+        /// Git remote the stacks are pushed to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Open a bookmark's PR, or its compare view if it has none, in the browser
+    Open {
+        /// Bookmark to open
+        bookmark: String,
+
+        /// Open the compare view (base...head) even if a PR exists
+        #[arg(long)]
+        compare: bool,
+
+        /// Git remote the bookmark is pushed to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Attach a reviewer note (testing instructions, risk, ...) to a change
+    ///
+    /// Adds a `ryu-note:` trailer to the change's description; submit renders
+    /// a segment's notes into its PR body. Without a note, lists the change's
+    /// notes.
+    Annotate {
+        /// Note to add
+        note: Option<String>,
+
+        /// Change to annotate
+        #[arg(short, long, default_value = "@")]
+        revision: String,
+    },
+
+    /// Create a stack of empty, described changes from a template
+    ///
+    /// Templates are defined under `[templates.<name>]` in the config files.
+    NewStack {
+        /// Name of the stack; bookmarks are `<name>/<segment>`
+        name: String,
+
+        /// Template to scaffold from
+        #[arg(short, long)]
+        template: String,
+
+        /// Revision to build the stack on
+        #[arg(long, default_value = "trunk()")]
+        onto: String,
+    },
+
+    /// Manage per-bookmark PR base overrides
+    Base {
+        #[command(subcommand)]
+        action: BaseAction,
+    },
+
+    /// Maintain ryu's stack comments
+    Comment {
+        #[command(subcommand)]
+        action: CommentAction,
+    },
+
+    /// Maintain existing PRs
+    Pr {
+        #[command(subcommand)]
+        action: PrAction,
+    },
+
+    /// Print JSON Schemas for ryu's JSON output
+    Schema {
+        /// Schema to print (default: all, keyed by name)
+        #[arg(value_parser = PossibleValuesParser::new(SchemaType::ALL.map(SchemaType::name)))]
+        name: Option<String>,
+    },
+
+    /// Update ryu to the latest release (for release-tarball installs)
+    Selfupdate {
+        /// Only check whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Include pre-releases
+        #[arg(long)]
+        prerelease: bool,
+
+        /// Update even if ryu was installed by a package manager
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show local usage stats (recorded only when telemetry is opted into)
+    Stats {
+        /// Stats about ryu itself: command timings, stack sizes, failures
+        /// (currently the only kind)
+        #[arg(long, required = true)]
+        tool: bool,
+
+        /// Post the summary to telemetry-upload-url from the user config
+        #[arg(long)]
+        upload: bool,
+
+        /// Delete the recorded usage
+        #[arg(long, conflicts_with = "upload")]
+        clear: bool,
+    },
+
     /// Authentication management
     Auth {
         #[command(subcommand)]
@@ -96,6 +577,71 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum BaseAction {
+    /// Make a bookmark's PR target a specific branch
+    Set {
+        /// Bookmark whose PR base to override
+        bookmark: String,
+        /// Branch the PR should target (e.g. a maintenance branch)
+        branch: String,
+    },
+    /// Remove a bookmark's base override
+    Unset {
+        /// Bookmark whose override to remove
+        bookmark: String,
+    },
+    /// List base overrides
+    List,
+}
+
+#[derive(Subcommand)]
+enum CommentAction {
+    /// Rewrite legacy JJ-STACK_INFO stack comments in the current format
+    Migrate {
+        /// Bookmark whose PR to migrate
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        bookmark: Option<String>,
+
+        /// Scan every open PR by --author instead of one bookmark's
+        #[arg(long)]
+        all: bool,
+
+        /// Whose PRs to scan with --all, e.g. a bot account (default: you)
+        #[arg(long, requires = "all")]
+        author: Option<String>,
+
+        /// Report legacy comments without rewriting them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PrAction {
+    /// Re-render PR bodies from the current commit messages
+    SyncBody {
+        /// Bookmark whose PR to update
+        bookmark: String,
+
+        /// Update every PR from trunk up to the bookmark
+        #[arg(long, short = 's')]
+        stack: bool,
+
+        /// Show what would change without updating
+        #[arg(long)]
+        diff: bool,
+
+        /// Git remote the PRs belong to
+        #[arg(long)]
+        remote: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum AuthPlatform {
     /// GitHub authentication
@@ -108,6 +654,21 @@ enum AuthPlatform {
         #[command(subcommand)]
         action: AuthAction,
     },
+    /// Bitbucket Cloud authentication
+    Bitbucket {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Gitea/Forgejo (incl. Codeberg) authentication
+    Gitea {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Azure DevOps authentication
+    AzureDevops {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
 }
 
 #[derive(Subcommand)]
@@ -119,17 +680,175 @@ enum AuthAction {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
+    install_crash_handler();
+    init_tracing();
+    cli::style::init_console();
+
+    let result = run().await;
+    cli::finish_undo();
+    cli::finish_usage(result.as_ref().err());
+    cli::print_run_summary();
+
+    // HTTP errors can embed request URLs; scrub the whole error chain
+    result
+        .map(cli::Outcome::exit_code)
+        .map_err(|e| anyhow::anyhow!(redact_secrets(&format!("{e:?}"))))
+}
+
+/// Log to stderr as configured by `RUST_LOG` (off by default)
+fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("off"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .fmt_fields(RedactingFields)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+impl Commands {
+    /// Whether the command supports `--format json`
+    const fn supports_json(&self) -> bool {
+        matches!(
+            self,
+            Self::Submit { .. } | Self::Sync { .. } | Self::Hover { .. }
+        )
+    }
+
+    /// Whether the command writes to the jj repo
+    const fn mutates_repo(&self) -> bool {
+        #[cfg(feature = "tui")]
+        if matches!(self, Self::Ui { .. }) {
+            return true;
+        }
+        matches!(
+            self,
+            Self::Submit { .. }
+                | Self::Apply { .. }
+                | Self::Adopt { .. }
+                | Self::Import { .. }
+                | Self::Sync { .. }
+                | Self::Merge { .. }
+                | Self::Land { .. }
+                | Self::RebaseContinue { .. }
+                | Self::NewStack { .. }
+                | Self::BisectStack { .. }
+                | Self::Clean { .. }
+                | Self::Undo { .. }
+                | Self::Annotate { note: Some(_), .. }
+        )
+    }
+
+    /// Whether `ryu undo` should be able to take the command back
+    const fn records_undo(&self) -> bool {
+        !matches!(self, Self::Undo { .. })
+            && (self.mutates_repo() || matches!(self, Self::Comment { .. } | Self::Pr { .. }))
+    }
+
+    /// Name recorded in usage stats
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Submit { .. } => "submit",
+            Self::Plan { .. } => "plan",
+            Self::Apply { .. } => "apply",
+            Self::Adopt { .. } => "adopt",
+            Self::Import { .. } => "import",
+            Self::Sync { .. } => "sync",
+            Self::Merge { .. } => "merge",
+            Self::Land { .. } => "land",
+            Self::RebaseContinue { .. } => "rebase-continue",
+            Self::Status { .. } => "status",
+            Self::Verify { .. } => "verify",
+            #[cfg(feature = "tui")]
+            Self::Ui { .. } => "ui",
+            Self::SuggestBase { .. } => "suggest-base",
+            Self::BisectStack { .. } => "bisect-stack",
+            Self::Clean { .. } => "clean",
+            Self::Gc { .. } => "gc",
+            Self::Undo { .. } => "undo",
+            Self::Env { .. } => "env",
+            Self::Hover { .. } => "hover",
+            Self::Open { .. } => "open",
+            Self::Annotate { .. } => "annotate",
+            Self::NewStack { .. } => "new-stack",
+            Self::Base { .. } => "base",
+            Self::Comment { .. } => "comment",
+            Self::Pr { .. } => "pr",
+            Self::Schema { .. } => "schema",
+            Self::Selfupdate { .. } => "selfupdate",
+            Self::Stats { .. } => "stats",
+            Self::Auth { .. } => "auth",
+        }
+    }
+}
+
+async fn run() -> Result<cli::Outcome> {
     let cli = Cli::parse();
     let path = cli.path.unwrap_or_else(|| PathBuf::from("."));
 
+    if cli.format == cli::OutputFormat::Json
+        && !cli.command.as_ref().is_none_or(Commands::supports_json)
+    {
+        anyhow::bail!(
+            "--format json is only supported by analyze (no subcommand), submit, sync and hover"
+        );
+    }
+    cli::style::load_palette()?;
+    cli::set_format(cli.format);
+    cli::set_platform(cli.platform);
+    set_read_only(cli.read_only || read_only_from_env()?.unwrap_or(false));
+    // --mine adds to the config files; non-empty globs and --trunk-revset
+    // replace theirs
+    let flags = RyuConfig {
+        mine_only: cli.mine.then_some(true),
+        trunk_revset: cli.trunk_revset,
+        include_bookmarks: Some(cli.include_bookmarks).filter(|globs| !globs.is_empty()),
+        exclude_bookmarks: Some(cli.exclude_bookmarks).filter(|globs| !globs.is_empty()),
+        ..RyuConfig::default()
+    };
+    let defaults = RetryPolicy::default();
+    cli::set_run_context(
+        RunContext::default()
+            .with_config(flags)
+            .with_retry_policy(RetryPolicy {
+                max_retries: cli.max_retries.unwrap_or(defaults.max_retries),
+                budget: cli.retry_budget.unwrap_or(defaults.budget),
+            })
+            .with_budget(ApiBudget {
+                max_calls: cli.max_api_calls,
+                time: cli.budget,
+            })
+            .with_read_only_observer(cli::read_only_notice())
+            .with_retry_observer(cli::retry_notice())
+            .with_lock_wait_observer(cli::lock_wait_spinner()),
+    );
+    if cli.strict {
+        cli::enable_strict();
+    }
+    if cli.verbose {
+        cli::enable_run_summary();
+    }
+
+    if cli.command.as_ref().is_some_and(Commands::mutates_repo) {
+        cli::warn_on_newer_jj();
+    }
+    // Looking at the stats shouldn't add to them
+    if !matches!(cli.command, Some(Commands::Stats { .. })) {
+        cli::begin_usage(cli.command.as_ref().map_or("analyze", Commands::name));
+    }
+    if let Some(command) = cli.command.as_ref().filter(|c| c.records_undo()) {
+        cli::begin_undo(&path, command.name());
+    }
+
+    let mut outcome = cli::Outcome::Done;
     match cli.command {
         None => {
             // Default: interactive mode
-            cli::run_analyze(&path).await?;
+            cli::run_analyze(&path, cli.checks).await?;
         }
         Some(Commands::Submit {
             bookmark,
+            all,
             dry_run,
             confirm,
             upto,
@@ -139,6 +858,15 @@ async fn main() -> Result<()> {
             draft,
             publish,
             select,
+            allow_foreign_commits,
+            project,
+            mirrors,
+            pr_remotes,
+            tracking_issue,
+            resume,
+            auto_bookmark,
+            base,
+            stack_info,
             remote,
         }) => {
             // Determine scope from mutually exclusive flags (enforced by clap arg groups)
@@ -153,40 +881,223 @@ async fn main() -> Result<()> {
                 (cli::SubmitScope::Default, None)
             };
 
-            cli::run_submit(
-                &path,
-                &bookmark,
-                remote.as_deref(),
-                cli::SubmitOptions {
-                    dry_run,
-                    confirm,
-                    scope,
-                    upto_bookmark,
-                    update_only,
-                    draft,
-                    publish,
-                    select,
-                },
-            )
-            .await?;
+            let options = cli::SubmitOptions {
+                dry_run,
+                confirm,
+                scope,
+                upto_bookmark,
+                update_only,
+                draft,
+                publish,
+                select,
+                allow_foreign_commits,
+                project: project.as_deref(),
+                mirrors: &mirrors,
+                pr_remotes: &pr_remotes,
+                tracking_issue,
+                resume,
+                auto_bookmark,
+                base: base.as_deref(),
+                stack_info,
+            };
+            outcome = if all {
+                cli::run_submit_all(&path, remote.as_deref(), options).await?
+            } else {
+                cli::run_submit(&path, bookmark.as_deref(), remote.as_deref(), options).await?
+            };
         }
         Some(Commands::Sync {
             dry_run,
             confirm,
             stack,
+            allow_foreign_commits,
+            project,
+            mirrors,
+            no_restack,
+            prune,
+            stack_info,
             remote,
         }) => {
-            cli::run_sync(
+            outcome = cli::run_sync(
                 &path,
                 remote.as_deref(),
                 cli::SyncOptions {
                     dry_run,
                     confirm,
                     stack: stack.as_deref(),
+                    allow_foreign_commits,
+                    project: project.as_deref(),
+                    mirrors: &mirrors,
+                    include_synced: false,
+                    restack: !no_restack,
+                    prune,
+                    next_steps: true,
+                    stack_info,
                 },
             )
             .await?;
         }
+        Some(Commands::Plan {
+            bookmark,
+            output,
+            remote,
+        }) => {
+            cli::run_plan(&path, &bookmark, remote.as_deref(), &output).await?;
+        }
+        Some(Commands::Apply { file, dry_run }) => {
+            outcome = cli::run_apply(&path, &file, dry_run).await?;
+        }
+        Some(Commands::Import { pr, remote }) => {
+            cli::run_import(&path, &pr, remote.as_deref()).await?;
+        }
+        Some(Commands::Adopt {
+            bookmark,
+            dry_run,
+            remote,
+        }) => {
+            outcome =
+                cli::run_adopt(&path, bookmark.as_deref(), remote.as_deref(), dry_run).await?;
+        }
+        Some(Commands::Merge {
+            bookmark,
+            method,
+            remote,
+        }) => {
+            cli::run_merge(&path, &bookmark, remote.as_deref(), method).await?;
+        }
+        Some(Commands::Land {
+            all_ready: _,
+            stack,
+            wait_for_checks,
+            method,
+            remote,
+        }) => {
+            if let Some(bookmark) = stack {
+                cli::run_land_stack(&path, &bookmark, remote.as_deref(), method, wait_for_checks)
+                    .await?;
+            } else {
+                cli::run_land(&path, remote.as_deref(), method).await?;
+            }
+        }
+        Some(Commands::RebaseContinue { remote }) => {
+            cli::run_rebase_continue(&path, remote.as_deref()).await?;
+        }
+        Some(Commands::Status { remote }) => {
+            cli::run_status(&path, remote.as_deref()).await?;
+        }
+        Some(Commands::Verify { remote }) => {
+            outcome = cli::run_verify(&path, remote.as_deref()).await?;
+        }
+        #[cfg(feature = "tui")]
+        Some(Commands::Ui { remote }) => {
+            cli::run_ui(&path, remote.as_deref()).await?;
+        }
+        Some(Commands::SuggestBase { bookmark }) => {
+            cli::run_suggest_base(&path, bookmark.as_deref())?;
+        }
+        Some(Commands::BisectStack {
+            bookmark,
+            remote,
+            command,
+        }) => {
+            cli::run_bisect_stack(&path, &bookmark, remote.as_deref(), &command).await?;
+        }
+        Some(Commands::Clean {
+            fork,
+            dry_run,
+            remote,
+        }) => {
+            outcome = cli::run_clean(&path, remote.as_deref(), fork, dry_run).await?;
+        }
+        Some(Commands::Gc { dry_run }) => {
+            outcome = cli::run_gc(&path, dry_run)?;
+        }
+        Some(Commands::Undo {
+            close_created,
+            dry_run,
+            force,
+        }) => {
+            outcome = cli::run_undo(&path, close_created, dry_run, force).await?;
+        }
+        Some(Commands::Env { remote }) => {
+            cli::run_env(&path, remote.as_deref())?;
+        }
+        Some(Commands::Hover { location, remote }) => {
+            cli::run_hover(&path, &location, remote.as_deref()).await?;
+        }
+        Some(Commands::Open {
+            bookmark,
+            compare,
+            remote,
+        }) => {
+            cli::run_open(&path, &bookmark, remote.as_deref(), compare).await?;
+        }
+        Some(Commands::Annotate { note, revision }) => {
+            cli::run_annotate(&path, &revision, note.as_deref())?;
+        }
+        Some(Commands::NewStack {
+            name,
+            template,
+            onto,
+        }) => {
+            cli::run_new_stack(&path, &name, &template, &onto)?;
+        }
+        Some(Commands::Base { action }) => match action {
+            BaseAction::Set { bookmark, branch } => {
+                cli::run_base_set(&path, &bookmark, &branch)?;
+            }
+            BaseAction::Unset { bookmark } => {
+                cli::run_base_unset(&path, &bookmark)?;
+            }
+            BaseAction::List => {
+                cli::run_base_list(&path)?;
+            }
+        },
+        Some(Commands::Comment { action }) => match action {
+            CommentAction::Migrate {
+                bookmark,
+                all: _,
+                author,
+                dry_run,
+                remote,
+            } => {
+                // clap guarantees a bookmark unless --all was given
+                let scope = bookmark.as_deref().map_or(
+                    cli::MigrateScope::All {
+                        author: author.as_deref(),
+                    },
+                    cli::MigrateScope::Bookmark,
+                );
+                cli::run_comment_migrate(&path, remote.as_deref(), scope, dry_run).await?;
+            }
+        },
+        Some(Commands::Pr { action }) => match action {
+            PrAction::SyncBody {
+                bookmark,
+                stack,
+                diff,
+                remote,
+            } => {
+                cli::run_pr_sync_body(&path, &bookmark, remote.as_deref(), stack, diff).await?;
+            }
+        },
+        Some(Commands::Schema { name }) => {
+            cli::run_schema(name.as_deref())?;
+        }
+        Some(Commands::Selfupdate {
+            check,
+            prerelease,
+            force,
+        }) => {
+            cli::run_selfupdate(check, prerelease, force).await?;
+        }
+        Some(Commands::Stats {
+            tool: _,
+            upload,
+            clear,
+        }) => {
+            cli::run_stats(upload, clear).await?;
+        }
         Some(Commands::Auth { platform }) => match platform {
             AuthPlatform::Github { action } => {
                 let action_str = match action {
@@ -202,8 +1113,30 @@ async fn main() -> Result<()> {
                 };
                 cli::run_auth(Platform::GitLab, action_str).await?;
             }
+            AuthPlatform::Bitbucket { action } => {
+                let action_str = match action {
+                    AuthAction::Test => "test",
+                    AuthAction::Setup => "setup",
+                };
+                cli::run_auth(Platform::Bitbucket, action_str).await?;
+            }
+            AuthPlatform::Gitea { action } => {
+                let action_str = match action {
+                    AuthAction::Test => "test",
+                    AuthAction::Setup => "setup",
+                };
+                cli::run_auth(Platform::Gitea, action_str).await?;
+            }
+            AuthPlatform::AzureDevops { action } => {
+                let action_str = match action {
+                    AuthAction::Test => "test",
+                    AuthAction::Setup => "setup",
+                };
+                cli::run_auth(Platform::AzureDevOps, action_str).await?;
+            }
         },
     }
 
-    Ok(())
+    cli::check_strict()?;
+    Ok(outcome)
 }
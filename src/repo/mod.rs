@@ -1,7 +0,0 @@
-//! jj-lib workspace wrapper
-//!
-//! Provides a high-level interface to jj repository operations.
-
-mod workspace;
-
-pub use workspace::{JjWorkspace, select_remote};
@@ -6,9 +6,11 @@
 #![allow(dead_code)]
 
 use chrono::Utc;
-use jj_ryu::types::{
-    Bookmark, BookmarkSegment, BranchStack, ChangeGraph, LogEntry, Platform, PlatformConfig,
-    PrComment, PullRequest,
+use jj_ryu_core::status::{BookmarkStatus, StackStatus};
+use jj_ryu_core::submit::{STACK_COMMENT_VERSION, StackCommentData, StackItem};
+use jj_ryu_core::types::{
+    Bookmark, BookmarkSegment, BranchStack, ChangeGraph, LogEntry, NarrowedBookmarkSegment,
+    Platform, PlatformConfig, PrComment, PrState, PullRequest,
 };
 use std::collections::HashMap;
 
@@ -56,6 +58,7 @@ pub fn make_log_entry_with_ids(
         author_name: "Test Author".to_string(),
         author_email: "test@example.com".to_string(),
         description_first_line: desc.to_string(),
+        description: desc.to_string(),
         parents: vec![],
         local_bookmarks: bookmarks.iter().map(ToString::to_string).collect(),
         remote_bookmarks: vec![],
@@ -65,6 +68,14 @@ pub fn make_log_entry_with_ids(
     }
 }
 
+/// Create a log entry for a commit with a multi-line `description`
+pub fn make_log_entry(description: &str) -> LogEntry {
+    LogEntry {
+        description: description.to_string(),
+        ..make_log_entry_with_ids(description.lines().next().unwrap_or(""), "c1", "ch1", &[])
+    }
+}
+
 /// Create a pull request with default values
 pub fn make_pr(number: u64, head: &str, base: &str) -> PullRequest {
     PullRequest {
@@ -75,6 +86,7 @@ pub fn make_pr(number: u64, head: &str, base: &str) -> PullRequest {
         title: format!("PR for {head}"),
         node_id: Some(format!("PR_node_{number}")),
         is_draft: false,
+        updated_at: None,
     }
 }
 
@@ -88,6 +100,7 @@ pub fn make_pr_draft(number: u64, head: &str, base: &str) -> PullRequest {
         title: format!("PR for {head}"),
         node_id: Some(format!("PR_node_{number}")),
         is_draft: true,
+        updated_at: None,
     }
 }
 
@@ -96,6 +109,26 @@ pub fn make_pr_comment(id: u64, body: &str) -> PrComment {
     PrComment {
         id,
         body: body.to_string(),
+        node_id: None,
+    }
+}
+
+/// Create stack comment data for PRs `numbers`, root first
+///
+/// PR `n` is for bookmark `feat-{n}` at change `ch{n}`.
+pub fn make_stack_data(numbers: &[u64]) -> StackCommentData {
+    StackCommentData {
+        version: STACK_COMMENT_VERSION,
+        stack: numbers
+            .iter()
+            .map(|&n| StackItem {
+                bookmark_name: format!("feat-{n}"),
+                pr_url: format!("https://github.com/test/repo/pull/{n}"),
+                pr_number: n,
+                change_id: Some(format!("ch{n}")),
+            })
+            .collect(),
+        tracking_issue: None,
     }
 }
 
@@ -106,6 +139,7 @@ pub fn github_config() -> PlatformConfig {
         owner: "testowner".to_string(),
         repo: "testrepo".to_string(),
         host: None,
+        head_owner: None,
     }
 }
 
@@ -116,6 +150,34 @@ pub fn gitlab_config() -> PlatformConfig {
         owner: "testowner".to_string(),
         repo: "testrepo".to_string(),
         host: None,
+        head_owner: None,
+    }
+}
+
+/// Status of a stack of `bookmarks`, trunk first, each with an open PR on
+/// the right base
+pub fn make_stack_status(bookmarks: &[&str]) -> StackStatus {
+    let bases = std::iter::once("main").chain(bookmarks.iter().copied());
+    let statuses = bookmarks
+        .iter()
+        .zip(bases)
+        .zip(1..)
+        .map(|((name, base), number)| BookmarkStatus {
+            segment: NarrowedBookmarkSegment {
+                bookmark: make_bookmark_synced(name),
+                changes: Vec::new(),
+            },
+            pr: Some((make_pr(number, name, base), PrState::Open)),
+            expected_base: base.to_string(),
+            checks: None,
+            review: None,
+        })
+        .collect();
+    StackStatus {
+        leaf: bookmarks
+            .last()
+            .map_or_else(String::new, ToString::to_string),
+        bookmarks: statuses,
     }
 }
 
@@ -152,6 +214,7 @@ pub fn make_linear_stack(names: &[&str]) -> ChangeGraph {
         }
 
         segments.push(BookmarkSegment {
+            id: change_id.clone(),
             bookmarks: vec![bm],
             changes: vec![log_entry],
         });
@@ -159,6 +222,10 @@ pub fn make_linear_stack(names: &[&str]) -> ChangeGraph {
 
     let leaf_id = format!("{}_change", names.last().unwrap());
     let root_id = format!("{}_change", names[0]);
+    let stack = BranchStack {
+        id: leaf_id.clone(),
+        segments,
+    };
 
     ChangeGraph {
         bookmarks,
@@ -167,8 +234,8 @@ pub fn make_linear_stack(names: &[&str]) -> ChangeGraph {
         bookmarked_change_id_to_segment: change_to_segment,
         stack_leafs: std::iter::once(leaf_id).collect(),
         stack_roots: std::iter::once(root_id).collect(),
-        stacks: vec![BranchStack { segments }],
-        excluded_bookmark_count: 0,
+        stacks: vec![stack],
+        excluded_bookmarks: Vec::new(),
     }
 }
 
@@ -195,6 +262,7 @@ pub fn make_multi_bookmark_segment(names: &[&str]) -> ChangeGraph {
     let log_entry = make_log_entry_with_ids("Shared commit", &commit_id, &change_id, names);
 
     let segment = BookmarkSegment {
+        id: change_id.clone(),
         bookmarks: names
             .iter()
             .map(|n| make_bookmark_with_ids(n, &commit_id, &change_id))
@@ -211,10 +279,59 @@ pub fn make_multi_bookmark_segment(names: &[&str]) -> ChangeGraph {
         bookmarked_change_adjacency_list: HashMap::new(),
         bookmarked_change_id_to_segment: change_to_segment,
         stack_leafs: std::iter::once(change_id.clone()).collect(),
-        stack_roots: std::iter::once(change_id).collect(),
+        stack_roots: std::iter::once(change_id.clone()).collect(),
         stacks: vec![BranchStack {
+            id: change_id,
             segments: vec![segment],
         }],
-        excluded_bookmark_count: 0,
+        excluded_bookmarks: Vec::new(),
+    }
+}
+
+/// Build a forked stack: trunk -> `base`, with each of `branches` stacked
+/// on `base`
+///
+/// Every branch is its own `BranchStack` sharing the `base` segment.
+pub fn make_forked_stack(base: &str, branches: &[&str]) -> ChangeGraph {
+    let mut graph = make_linear_stack(&[base]);
+    let base_segment = graph.stacks[0].segments[0].clone();
+    let base_change_id = base_segment.id.clone();
+    graph.stacks.clear();
+    graph.stack_leafs.clear();
+
+    for name in branches {
+        let change_id = format!("{name}_change");
+        let commit_id = format!("{name}_commit");
+        let bm = make_bookmark_with_ids(name, &commit_id, &change_id);
+        let log_entry = make_log_entry_with_ids(
+            &format!("Commit for {name}"),
+            &commit_id,
+            &change_id,
+            &[name],
+        );
+
+        graph.bookmarks.insert(name.to_string(), bm.clone());
+        graph
+            .bookmark_to_change_id
+            .insert(name.to_string(), change_id.clone());
+        graph
+            .bookmarked_change_id_to_segment
+            .insert(change_id.clone(), vec![log_entry.clone()]);
+        graph
+            .bookmarked_change_adjacency_list
+            .insert(change_id.clone(), base_change_id.clone());
+        graph.stack_leafs.insert(change_id.clone());
+        graph.stacks.push(BranchStack {
+            id: change_id.clone(),
+            segments: vec![
+                base_segment.clone(),
+                BookmarkSegment {
+                    id: change_id,
+                    bookmarks: vec![bm],
+                    changes: vec![log_entry],
+                },
+            ],
+        });
     }
+    graph
 }
@@ -6,9 +6,12 @@
 #![allow(dead_code)]
 
 use async_trait::async_trait;
-use jj_ryu::error::{Error, Result};
-use jj_ryu::platform::PlatformService;
-use jj_ryu::types::{PlatformConfig, PrComment, PullRequest};
+use jj_ryu_core::error::{Error, Result};
+use jj_ryu_core::platform::PlatformService;
+use jj_ryu_core::types::{
+    ChecksState, CommentWrite, Issue, MergeMethod, PlatformConfig, PrComment, PrMetadata,
+    PrReadiness, PrReview, PrState, PullRequest, RateLimit,
+};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -19,6 +22,7 @@ pub struct CreatePrCall {
     pub head: String,
     pub base: String,
     pub title: String,
+    pub body: String,
 }
 
 /// Call record for `update_pr_base`
@@ -35,6 +39,14 @@ pub struct CreateCommentCall {
     pub body: String,
 }
 
+/// Call record for `update_pr_comment`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateCommentCall {
+    pub pr_number: u64,
+    pub comment_id: u64,
+    pub body: String,
+}
+
 /// Simple mock platform service for testing
 ///
 /// This manually implements `PlatformService` rather than using mockall,
@@ -50,12 +62,32 @@ pub struct MockPlatformService {
     next_pr_number: AtomicU64,
     find_pr_responses: Mutex<HashMap<String, Option<PullRequest>>>,
     list_comments_responses: Mutex<HashMap<u64, Vec<PrComment>>>,
+    open_prs_responses: Mutex<HashMap<String, Vec<PullRequest>>>,
+    latest_pr_responses: Mutex<HashMap<String, Option<(PullRequest, PrState)>>>,
+    pr_bodies: Mutex<HashMap<u64, String>>,
+    author_prs_responses: Mutex<HashMap<String, Vec<PullRequest>>>,
+    review_loads: Mutex<HashMap<String, usize>>,
+    issue_bodies: Mutex<HashMap<u64, String>>,
+    pr_readiness: Mutex<HashMap<u64, PrReadiness>>,
+    pr_reviews: Mutex<HashMap<u64, Vec<PrReview>>>,
+    merge_commits: Mutex<HashMap<u64, String>>,
+    rate_limit: Mutex<Option<RateLimit>>,
     // Call tracking
     find_pr_calls: Mutex<Vec<String>>,
     create_pr_calls: Mutex<Vec<CreatePrCall>>,
     update_base_calls: Mutex<Vec<UpdateBaseCall>>,
     create_comment_calls: Mutex<Vec<CreateCommentCall>>,
+    update_comment_calls: Mutex<Vec<UpdateCommentCall>>,
     list_comments_calls: Mutex<Vec<u64>>,
+    write_comments_batches: Mutex<Vec<usize>>,
+    close_pr_calls: Mutex<Vec<u64>>,
+    merge_pr_calls: Mutex<Vec<(u64, MergeMethod)>>,
+    add_to_project_calls: Mutex<Vec<(u64, String)>>,
+    update_body_calls: Mutex<Vec<(u64, String)>>,
+    request_reviewers_calls: Mutex<Vec<(u64, Vec<String>)>>,
+    update_pr_metadata_calls: Mutex<Vec<(u64, PrMetadata)>>,
+    create_issue_calls: Mutex<Vec<(String, String)>>,
+    update_issue_calls: Mutex<Vec<(u64, String)>>,
     // Error injection
     error_on_find_pr: Mutex<Option<String>>,
     error_on_create_pr: Mutex<Option<String>>,
@@ -70,11 +102,31 @@ impl MockPlatformService {
             next_pr_number: AtomicU64::new(1),
             find_pr_responses: Mutex::new(HashMap::new()),
             list_comments_responses: Mutex::new(HashMap::new()),
+            open_prs_responses: Mutex::new(HashMap::new()),
+            latest_pr_responses: Mutex::new(HashMap::new()),
+            pr_bodies: Mutex::new(HashMap::new()),
+            author_prs_responses: Mutex::new(HashMap::new()),
+            review_loads: Mutex::new(HashMap::new()),
+            issue_bodies: Mutex::new(HashMap::new()),
+            pr_readiness: Mutex::new(HashMap::new()),
+            pr_reviews: Mutex::new(HashMap::new()),
+            merge_commits: Mutex::new(HashMap::new()),
+            rate_limit: Mutex::new(None),
             find_pr_calls: Mutex::new(Vec::new()),
             create_pr_calls: Mutex::new(Vec::new()),
             update_base_calls: Mutex::new(Vec::new()),
             create_comment_calls: Mutex::new(Vec::new()),
+            update_comment_calls: Mutex::new(Vec::new()),
             list_comments_calls: Mutex::new(Vec::new()),
+            write_comments_batches: Mutex::new(Vec::new()),
+            close_pr_calls: Mutex::new(Vec::new()),
+            merge_pr_calls: Mutex::new(Vec::new()),
+            add_to_project_calls: Mutex::new(Vec::new()),
+            update_body_calls: Mutex::new(Vec::new()),
+            request_reviewers_calls: Mutex::new(Vec::new()),
+            update_pr_metadata_calls: Mutex::new(Vec::new()),
+            create_issue_calls: Mutex::new(Vec::new()),
+            update_issue_calls: Mutex::new(Vec::new()),
             error_on_find_pr: Mutex::new(None),
             error_on_create_pr: Mutex::new(None),
             error_on_update_base: Mutex::new(None),
@@ -106,6 +158,17 @@ impl MockPlatformService {
             .insert(branch.to_string(), pr);
     }
 
+    /// Set every open PR for a branch, simulating duplicates
+    ///
+    /// Overrides `set_find_pr_response` for `find_open_prs`.
+    #[allow(dead_code)]
+    pub fn set_open_prs_response(&self, branch: &str, prs: Vec<PullRequest>) {
+        self.open_prs_responses
+            .lock()
+            .unwrap()
+            .insert(branch.to_string(), prs);
+    }
+
     /// Set the response for `list_pr_comments` for a specific PR
     pub fn set_list_comments_response(&self, pr_number: u64, comments: Vec<PrComment>) {
         self.list_comments_responses
@@ -114,8 +177,141 @@ impl MockPlatformService {
             .insert(pr_number, comments);
     }
 
+    /// Set the response for `find_latest_pr` for a specific branch
+    ///
+    /// Without one, `find_latest_pr` reports the `find_existing_pr` response
+    /// as open (or draft).
+    #[allow(dead_code)]
+    pub fn set_latest_pr_response(&self, branch: &str, pr: Option<(PullRequest, PrState)>) {
+        self.latest_pr_responses
+            .lock()
+            .unwrap()
+            .insert(branch.to_string(), pr);
+    }
+
+    /// Set the body returned by `get_pr_body` for a specific PR
+    pub fn set_pr_body(&self, pr_number: u64, body: &str) {
+        self.pr_bodies
+            .lock()
+            .unwrap()
+            .insert(pr_number, body.to_string());
+    }
+
+    /// Set the response for `list_open_prs_by_author` for a specific author
+    pub fn set_author_prs_response(&self, author: &str, prs: Vec<PullRequest>) {
+        self.author_prs_responses
+            .lock()
+            .unwrap()
+            .insert(author.to_string(), prs);
+    }
+
+    /// Set the open review count reported for a reviewer
+    #[allow(dead_code)]
+    pub fn set_review_load(&self, reviewer: &str, count: usize) {
+        self.review_loads
+            .lock()
+            .unwrap()
+            .insert(reviewer.to_string(), count);
+    }
+
+    /// Set the body returned by `get_issue_body` for a specific issue
+    #[allow(dead_code)]
+    pub fn set_issue_body(&self, issue_number: u64, body: &str) {
+        self.issue_bodies
+            .lock()
+            .unwrap()
+            .insert(issue_number, body.to_string());
+    }
+
+    /// Set the commit a merged PR landed as
+    #[allow(dead_code)]
+    pub fn set_merge_commit(&self, pr_number: u64, commit: &str) {
+        self.merge_commits
+            .lock()
+            .unwrap()
+            .insert(pr_number, commit.to_string());
+    }
+
+    /// Set the review and CI state `get_pr_readiness` reports for a PR
+    /// (unapproved with no checks by default)
+    #[allow(dead_code)]
+    pub fn set_pr_readiness(&self, pr_number: u64, approved: bool, checks: ChecksState) {
+        self.pr_readiness
+            .lock()
+            .unwrap()
+            .insert(pr_number, PrReadiness { approved, checks });
+    }
+
+    /// Set the reviews `list_reviews` reports for a PR (none by default)
+    #[allow(dead_code)]
+    pub fn set_pr_reviews(&self, pr_number: u64, reviews: Vec<PrReview>) {
+        self.pr_reviews.lock().unwrap().insert(pr_number, reviews);
+    }
+
+    /// Set the API budget `rate_limit` reports (none by default)
+    #[allow(dead_code)]
+    pub fn set_rate_limit(&self, limit: u64, remaining: u64) {
+        *self.rate_limit.lock().unwrap() = Some(RateLimit {
+            limit,
+            remaining,
+            resets_at: None,
+        });
+    }
+
     // === Call verification methods ===
 
+    /// Get all `update_pr_comment` calls
+    pub fn get_update_comment_calls(&self) -> Vec<UpdateCommentCall> {
+        self.update_comment_calls.lock().unwrap().clone()
+    }
+
+    /// Get all (PR number, body) pairs `update_pr_body` was called with
+    pub fn get_update_body_calls(&self) -> Vec<(u64, String)> {
+        self.update_body_calls.lock().unwrap().clone()
+    }
+
+    /// Get all PR numbers that `close_pr` was called with
+    #[allow(dead_code)]
+    pub fn get_close_pr_calls(&self) -> Vec<u64> {
+        self.close_pr_calls.lock().unwrap().clone()
+    }
+
+    /// Get all (PR number, method) pairs `merge_pr` was called with
+    #[allow(dead_code)]
+    pub fn get_merge_pr_calls(&self) -> Vec<(u64, MergeMethod)> {
+        self.merge_pr_calls.lock().unwrap().clone()
+    }
+
+    /// Get all (PR number, project) pairs `add_pr_to_project` was called with
+    #[allow(dead_code)]
+    pub fn get_add_to_project_calls(&self) -> Vec<(u64, String)> {
+        self.add_to_project_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `request_reviewers` calls as (PR number, reviewers)
+    #[allow(dead_code)]
+    pub fn get_request_reviewers_calls(&self) -> Vec<(u64, Vec<String>)> {
+        self.request_reviewers_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `update_pr_metadata` calls as (PR number, metadata)
+    #[allow(dead_code)]
+    pub fn get_update_pr_metadata_calls(&self) -> Vec<(u64, PrMetadata)> {
+        self.update_pr_metadata_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `create_issue` calls as (title, body)
+    #[allow(dead_code)]
+    pub fn get_create_issue_calls(&self) -> Vec<(String, String)> {
+        self.create_issue_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `update_issue` calls as (issue number, body)
+    #[allow(dead_code)]
+    pub fn get_update_issue_calls(&self) -> Vec<(u64, String)> {
+        self.update_issue_calls.lock().unwrap().clone()
+    }
+
     /// Get all branches that `find_existing_pr` was called with
     pub fn get_find_pr_calls(&self) -> Vec<String> {
         self.find_pr_calls.lock().unwrap().clone()
@@ -141,6 +337,11 @@ impl MockPlatformService {
         self.list_comments_calls.lock().unwrap().clone()
     }
 
+    /// Get the number of writes in each `write_pr_comments` call
+    pub fn get_write_comments_batches(&self) -> Vec<usize> {
+        self.write_comments_batches.lock().unwrap().clone()
+    }
+
     /// Assert that `create_pr` was called with specific head and base
     pub fn assert_create_pr_called(&self, head: &str, base: &str) {
         let calls = self.get_create_pr_calls();
@@ -190,17 +391,71 @@ impl PlatformService for MockPlatformService {
         Ok(responses.get(head_branch).cloned().flatten())
     }
 
+    async fn find_open_prs(&self, head_branch: &str) -> Result<Vec<PullRequest>> {
+        let overridden = self
+            .open_prs_responses
+            .lock()
+            .unwrap()
+            .get(head_branch)
+            .cloned();
+        match overridden {
+            Some(prs) => {
+                self.find_pr_calls
+                    .lock()
+                    .unwrap()
+                    .push(head_branch.to_string());
+                Ok(prs)
+            }
+            None => Ok(self
+                .find_existing_pr(head_branch)
+                .await?
+                .into_iter()
+                .collect()),
+        }
+    }
+
+    async fn find_latest_pr(&self, head_branch: &str) -> Result<Option<(PullRequest, PrState)>> {
+        if let Some(response) = self.latest_pr_responses.lock().unwrap().get(head_branch) {
+            return Ok(response.clone());
+        }
+        let pr = self
+            .find_pr_responses
+            .lock()
+            .unwrap()
+            .get(head_branch)
+            .cloned();
+        Ok(pr.flatten().map(|pr| {
+            let state = if pr.is_draft {
+                PrState::Draft
+            } else {
+                PrState::Open
+            };
+            (pr, state)
+        }))
+    }
+
+    async fn list_open_prs_by_author(&self, author: &str) -> Result<Vec<PullRequest>> {
+        let responses = self.author_prs_responses.lock().unwrap();
+        Ok(responses.get(author).cloned().unwrap_or_default())
+    }
+
+    async fn current_user(&self) -> Result<String> {
+        Ok("test-user".to_string())
+    }
+
     async fn create_pr_with_options(
         &self,
         head: &str,
         base: &str,
         title: &str,
+        body: &str,
         draft: bool,
     ) -> Result<PullRequest> {
         self.create_pr_calls.lock().unwrap().push(CreatePrCall {
             head: head.to_string(),
             base: base.to_string(),
             title: title.to_string(),
+            body: body.to_string(),
         });
 
         // Check for injected error
@@ -217,7 +472,12 @@ impl PlatformService for MockPlatformService {
             title: title.to_string(),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: draft,
+            updated_at: None,
         };
+        self.pr_bodies
+            .lock()
+            .unwrap()
+            .insert(number, body.to_string());
         Ok(pr)
     }
 
@@ -240,6 +500,7 @@ impl PlatformService for MockPlatformService {
             title: "Updated PR".to_string(),
             node_id: Some(format!("PR_node_{pr_number}")),
             is_draft: false,
+            updated_at: None,
         })
     }
 
@@ -260,15 +521,36 @@ impl PlatformService for MockPlatformService {
         Ok(())
     }
 
-    async fn update_pr_comment(
-        &self,
-        _pr_number: u64,
-        _comment_id: u64,
-        _body: &str,
-    ) -> Result<()> {
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        self.update_comment_calls
+            .lock()
+            .unwrap()
+            .push(UpdateCommentCall {
+                pr_number,
+                comment_id,
+                body: body.to_string(),
+            });
         Ok(())
     }
 
+    async fn write_pr_comments(&self, writes: &[CommentWrite]) -> Vec<Result<()>> {
+        self.write_comments_batches
+            .lock()
+            .unwrap()
+            .push(writes.len());
+        let mut results = Vec::with_capacity(writes.len());
+        for write in writes {
+            results.push(match &write.existing {
+                Some(comment) => {
+                    self.update_pr_comment(write.pr_number, comment.id, &write.body)
+                        .await
+                }
+                None => self.create_pr_comment(write.pr_number, &write.body).await,
+            });
+        }
+        results
+    }
+
     async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
         Ok(PullRequest {
             number: pr_number,
@@ -278,9 +560,156 @@ impl PlatformService for MockPlatformService {
             title: "Published PR".to_string(),
             node_id: Some(format!("PR_node_{pr_number}")),
             is_draft: false, // After publishing, is_draft is false
+            updated_at: None,
         })
     }
 
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.close_pr_calls.lock().unwrap().push(pr_number);
+        Ok(())
+    }
+
+    async fn merge_pr(&self, pr_number: u64, method: MergeMethod) -> Result<()> {
+        self.merge_pr_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, method));
+        // Report the PR as merged from now on
+        let merged = self
+            .find_pr_responses
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .find(|pr| pr.number == pr_number)
+            .cloned();
+        if let Some(pr) = merged {
+            self.find_pr_responses
+                .lock()
+                .unwrap()
+                .insert(pr.head_ref.clone(), None);
+            self.latest_pr_responses
+                .lock()
+                .unwrap()
+                .insert(pr.head_ref.clone(), Some((pr, PrState::Merged)));
+        }
+        Ok(())
+    }
+
+    async fn get_merge_commit(&self, pr_number: u64) -> Result<Option<String>> {
+        Ok(self.merge_commits.lock().unwrap().get(&pr_number).cloned())
+    }
+
+    async fn list_reviews(&self, pr_number: u64) -> Result<Vec<PrReview>> {
+        Ok(self
+            .pr_reviews
+            .lock()
+            .unwrap()
+            .get(&pr_number)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_pr_readiness(&self, pr_number: u64) -> Result<PrReadiness> {
+        Ok(self
+            .pr_readiness
+            .lock()
+            .unwrap()
+            .get(&pr_number)
+            .copied()
+            .unwrap_or(PrReadiness {
+                approved: false,
+                checks: ChecksState::None,
+            }))
+    }
+
+    async fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        Ok(self.rate_limit.lock().unwrap().clone())
+    }
+
+    async fn add_pr_to_project(&self, pr: &PullRequest, project: &str) -> Result<()> {
+        self.add_to_project_calls
+            .lock()
+            .unwrap()
+            .push((pr.number, project.to_string()));
+        Ok(())
+    }
+
+    async fn request_reviewers(&self, pr_number: u64, reviewers: &[String]) -> Result<()> {
+        self.request_reviewers_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, reviewers.to_vec()));
+        Ok(())
+    }
+
+    async fn update_pr_metadata(&self, pr_number: u64, metadata: &PrMetadata) -> Result<()> {
+        self.update_pr_metadata_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, metadata.clone()));
+        Ok(())
+    }
+
+    async fn count_review_requests(&self, reviewer: &str) -> Result<usize> {
+        let loads = self.review_loads.lock().unwrap();
+        Ok(loads.get(reviewer).copied().unwrap_or_default())
+    }
+
+    async fn create_issue(&self, title: &str, body: &str) -> Result<Issue> {
+        // Issues and PRs share a number sequence, as on GitHub
+        let number = self.next_pr_number.fetch_add(1, Ordering::SeqCst);
+        self.create_issue_calls
+            .lock()
+            .unwrap()
+            .push((title.to_string(), body.to_string()));
+        self.issue_bodies
+            .lock()
+            .unwrap()
+            .insert(number, body.to_string());
+        Ok(Issue {
+            number,
+            html_url: format!("https://github.com/test/repo/issues/{number}"),
+        })
+    }
+
+    async fn get_issue_body(&self, issue_number: u64) -> Result<String> {
+        let bodies = self.issue_bodies.lock().unwrap();
+        bodies
+            .get(&issue_number)
+            .cloned()
+            .ok_or_else(|| Error::Platform(format!("issue #{issue_number} not found")))
+    }
+
+    async fn update_issue(&self, issue_number: u64, body: &str) -> Result<()> {
+        self.update_issue_calls
+            .lock()
+            .unwrap()
+            .push((issue_number, body.to_string()));
+        self.issue_bodies
+            .lock()
+            .unwrap()
+            .insert(issue_number, body.to_string());
+        Ok(())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        let bodies = self.pr_bodies.lock().unwrap();
+        Ok(bodies.get(&pr_number).cloned().unwrap_or_default())
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.update_body_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, body.to_string()));
+        self.pr_bodies
+            .lock()
+            .unwrap()
+            .insert(pr_number, body.to_string());
+        Ok(())
+    }
+
     fn config(&self) -> &PlatformConfig {
         &self.config
     }
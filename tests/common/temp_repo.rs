@@ -1,6 +1,6 @@
 //! Temporary jj repository for testing
 
-use jj_ryu::repo::JjWorkspace;
+use jj_ryu_core::repo::{JjBackend, JjWorkspace};
 use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
@@ -65,6 +65,16 @@ impl TempJjRepo {
         })
     }
 
+    /// Open as `JjWorkspace` backed by the jj CLI instead of jj-lib
+    pub fn cli_workspace(&self) -> JjWorkspace {
+        JjWorkspace::open_with(self.dir.path(), JjBackend::Cli).unwrap_or_else(|e| {
+            panic!(
+                "failed to open CLI workspace at {}: {e}",
+                self.dir.path().display()
+            )
+        })
+    }
+
     /// Create a new commit with the given message
     pub fn commit(&self, message: &str) {
         let output = Command::new("jj")
@@ -155,6 +165,18 @@ impl TempJjRepo {
         self.run_jj(&["bookmark", "move", name, "--to", to_rev]);
     }
 
+    /// Create bookmark `name` as a conflict between `a` and `b`
+    ///
+    /// Both creations fork from the same operation, so jj merges them into a
+    /// conflicted bookmark on the next command.
+    #[allow(dead_code)]
+    pub fn conflict_bookmark(&self, name: &str, a: &str, b: &str) {
+        let op = self.run_jj(&["op", "log", "--no-graph", "-n", "1", "-T", "id"]);
+        let op = op.trim();
+        self.run_jj(&["--at-op", op, "bookmark", "create", name, "-r", a]);
+        self.run_jj(&["--at-op", op, "bookmark", "create", name, "-r", b]);
+    }
+
     /// Get the change ID for a bookmark
     #[allow(dead_code)]
     pub fn change_id(&self, bookmark: &str) -> String {
@@ -162,6 +184,69 @@ impl TempJjRepo {
         output.trim().to_string()
     }
 
+    /// Create a merge commit of `parents` as the working copy
+    ///
+    /// Bookmarks created afterwards point at the merge.
+    #[allow(dead_code)]
+    pub fn create_merge(&self, parents: &[&str], message: &str) {
+        let mut args = vec!["new"];
+        args.extend_from_slice(parents);
+        args.extend_from_slice(&["-m", message]);
+        self.run_jj(&args);
+    }
+
+    /// Create a new change on top of `parent` as the working copy
+    #[allow(dead_code)]
+    pub fn new_change(&self, parent: &str, message: &str) {
+        self.run_jj(&["new", parent, "-m", message]);
+    }
+
+    /// Squash the working copy into `rev`, rebasing its descendants
+    #[allow(dead_code)]
+    pub fn squash_into(&self, rev: &str) {
+        self.run_jj(&["squash", "--into", rev]);
+    }
+
+    /// Write a file (creating parent directories) in the working copy
+    #[allow(dead_code)]
+    pub fn write_file(&self, path: &str, contents: &str) {
+        let path = self.dir.path().join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create directories");
+        }
+        std::fs::write(path, contents).expect("failed to write file");
+    }
+
+    /// Add a bare git repository as remote `name`
+    ///
+    /// The remote lives in the returned directory; keep it alive for as long
+    /// as the repo pushes to or fetches from it.
+    #[allow(dead_code)]
+    pub fn add_bare_remote(&self, name: &str) -> TempDir {
+        let remote = TempDir::new().expect("failed to create temp directory for remote");
+        let output = Command::new("git")
+            .args(["init", "--bare", "--quiet"])
+            .current_dir(remote.path())
+            .output()
+            .expect("git binary not found - is git installed and in PATH?");
+
+        assert!(
+            output.status.success(),
+            "git init --bare failed at {}: {}",
+            remote.path().display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        self.run_jj(&[
+            "git",
+            "remote",
+            "add",
+            name,
+            &remote.path().to_string_lossy(),
+        ]);
+        remote
+    }
+
     /// Create an empty commit (useful for testing without file changes)
     #[allow(dead_code)]
     pub fn empty_commit(&self, message: &str) {
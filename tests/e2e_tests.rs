@@ -8,9 +8,11 @@
 //!
 //! Run with: `JJ_RYU_E2E_TESTS=1 cargo test --test e2e_tests -- --include-ignored`
 
-use jj_ryu::platform::{GitHubService, PlatformService};
-use jj_ryu::submit::STACK_COMMENT_THIS_PR;
-use jj_ryu::types::Platform;
+#![cfg(feature = "github")]
+
+use jj_ryu_core::platform::{GitHubService, PlatformService};
+use jj_ryu_core::submit::STACK_COMMENT_THIS_PR;
+use jj_ryu_core::types::Platform;
 use std::env;
 use std::path::PathBuf;
 use std::process::{Command, Output};
@@ -6,8 +6,8 @@
 mod common;
 
 use common::{MockPlatformService, TempJjRepo, github_config, make_pr, make_pr_draft};
-use jj_ryu::graph::build_change_graph;
-use jj_ryu::submit::{ExecutionStep, analyze_submission, create_submission_plan};
+use jj_ryu_core::graph::build_change_graph;
+use jj_ryu_core::submit::{ExecutionStep, analyze_submission, create_submission_plan};
 
 // =============================================================================
 // Helper Functions
@@ -599,7 +599,7 @@ async fn test_constraint_display_formatting() {
 /// which the type system prevents in normal usage.
 #[test]
 fn test_scheduler_cycle_error_format() {
-    use jj_ryu::error::Error;
+    use jj_ryu_core::error::Error;
 
     let error = Error::SchedulerCycle {
         message: "test cycle".to_string(),
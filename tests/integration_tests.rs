@@ -5,10 +5,49 @@
 mod common;
 
 use assert_cmd::Command;
-use common::{MockPlatformService, TempJjRepo, github_config, make_pr};
-use jj_ryu::graph::build_change_graph;
-use jj_ryu::submit::{ExecutionStep, analyze_submission, create_submission_plan};
+use common::{
+    MockPlatformService, TempJjRepo, github_config, gitlab_config, make_pr, make_pr_comment,
+};
+use jj_ryu_core::config::RyuConfig;
+use jj_ryu_core::error::Error;
+use jj_ryu_core::graph::{
+    ahead_behind, build_change_graph, build_change_graph_onto, resolve_base_branch,
+};
+use jj_ryu_core::hover::line_context;
+use jj_ryu_core::landed::LandedChange;
+use jj_ryu_core::merge::merge_and_wait;
+#[cfg(feature = "fake")]
+use jj_ryu_core::platform::{FakeService, PlatformService, fake_platform_config};
+use jj_ryu_core::prune::prune_merged;
+use jj_ryu_core::pull::{RemoteCommits, pull_remote_commits};
+use jj_ryu_core::repo::{JjBackend, JjWorkspace};
+use jj_ryu_core::restack::{
+    continue_restack, load_restack_journal, plan_restacks, restack_onto_trunk,
+};
+use jj_ryu_core::run::RunContext;
+use jj_ryu_core::state::RyuState;
+use jj_ryu_core::status::{merged_bookmarks, stack_statuses};
+#[cfg(feature = "fake")]
+use jj_ryu_core::submit::DRAFT_ENV;
+use jj_ryu_core::submit::{
+    COMMENT_DATA_PREFIX, ExecutionStep, INTERDIFF_MARKER, JsonProgress, NoopProgress,
+    ProgressEvent, StackCommentData, StackDrift, StackItem, SubmissionTarget, Warning,
+    analyze_submission, create_multi_submission_plan, create_submission_plan,
+    execute_multi_submission, execute_submission, find_journal, format_stack_comment,
+    parse_stack_comment_data, plan_auto_bookmarks, plan_body_updates, resume_submission,
+    stack_section,
+};
+use jj_ryu_core::template::{StackTemplate, TemplateSegment, create_stack};
+use jj_ryu_core::types::{
+    ChecksState, ExclusionReason, MergeMethod, Platform, PrReview, PrState, ReviewState, StackInfo,
+};
+#[cfg(feature = "fake")]
+use jj_ryu_core::undo::{RemoteChange, load_undo_record, save_undo_record};
+use jj_ryu_core::verify::{Violation, verify_stacks};
 use predicates::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 
 // =============================================================================
 // CLI Tests
@@ -34,6 +73,36 @@ fn test_cli_version() {
         .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
 }
 
+#[test]
+fn test_read_only_is_a_global_flag() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["sync", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--read-only"));
+}
+
+#[test]
+fn test_strict_is_a_global_flag() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["submit", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--strict"));
+}
+
+#[test]
+fn test_land_stack_conflicts_with_all_ready() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["land", "--all-ready", "--stack", "feat-a"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn test_submit_help() {
     let mut cmd = Command::cargo_bin("ryu").unwrap();
@@ -41,121 +110,2011 @@ fn test_submit_help() {
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("Submit a bookmark stack"));
+        .stdout(predicate::str::contains("Submit a bookmark stack"))
+        .stdout(predicate::str::contains("--project"))
+        .stdout(predicate::str::contains("--mirror"))
+        .stdout(predicate::str::contains("--pr-remote"))
+        .stdout(predicate::str::contains("exits 2"));
+}
+
+#[test]
+fn test_submit_all_conflicts_with_bookmark() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["submit", "feat-a", "--all"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_submit_all_conflicts_with_base() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["submit", "--all", "--base", "release/1.2"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_adopt_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["adopt", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Take over PRs opened outside ryu"))
+        .stdout(predicate::str::contains("--dry-run"));
+}
+
+#[test]
+fn test_sync_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["sync", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Sync all stacks"))
+        .stdout(predicate::str::contains("--project"))
+        .stdout(predicate::str::contains("--mirror"))
+        .stdout(predicate::str::contains("exits 2"));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn test_ui_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["ui", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("interactively"))
+        .stdout(predicate::str::contains("--remote"));
+}
+
+#[test]
+fn test_auth_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["auth", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("github"))
+        .stdout(predicate::str::contains("gitlab"));
+}
+
+#[test]
+fn test_env_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["env", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("effective configuration"));
+}
+
+#[test]
+fn test_open_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["open", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--compare"));
+}
+
+#[test]
+fn test_bisect_stack_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["bisect-stack", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("COMMAND"));
+}
+
+#[test]
+fn test_bisect_stack_requires_command() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["bisect-stack", "feat-a"]);
+
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_base_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["base", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("set"))
+        .stdout(predicate::str::contains("unset"))
+        .stdout(predicate::str::contains("list"));
+}
+
+#[test]
+fn test_schema_command_prints_json_schema() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["schema", "submission-plan"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"$schema\""))
+        .stdout(predicate::str::contains("\"SubmissionPlan\""));
+}
+
+#[test]
+fn test_schema_command_rejects_unknown_name() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["schema", "nope"]);
+
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_selfupdate_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["selfupdate", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--check"))
+        .stdout(predicate::str::contains("--prerelease"))
+        .stdout(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_pr_sync_body_help() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["pr", "sync-body", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--stack"))
+        .stdout(predicate::str::contains("--diff"));
+}
+
+#[test]
+fn test_annotate_adds_note_trailer() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.arg("--path").arg(repo.path()).args([
+        "annotate",
+        "-r",
+        "feat-a",
+        "Run the migration twice",
+    ]);
+    cmd.assert().success();
+
+    let ws = repo.workspace();
+    let entry = ws.resolve_revset("feat-a").unwrap().remove(0);
+    assert_eq!(entry.description_first_line, "Add A");
+    assert_eq!(
+        jj_ryu_core::submit::parse_notes(&entry.description),
+        vec!["Run the migration twice"]
+    );
+}
+
+#[test]
+fn test_analyze_json_prints_change_graph() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.arg("--path")
+        .arg(repo.path())
+        .args(["--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let graph: serde_json::Value = serde_json::from_slice(&output).expect("stdout is JSON");
+    assert_eq!(graph["stacks"].as_array().map(Vec::len), Some(1));
+    assert!(graph["bookmarks"].get("feat-b").is_some());
+}
+
+#[test]
+fn test_analyze_checks_draws_stacks_when_platform_fails() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+    let _remote = repo.add_bare_remote("origin");
+    repo.workspace().git_push("feat-a", "origin").expect("push");
+
+    // Without --checks the platform isn't consulted at all
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.arg("--path").arg(repo.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("feat-a"))
+        .stderr(predicate::str::contains("PR state unavailable").not());
+
+    // A local path is no platform ryu knows, so the lookup fails
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.arg("--path").arg(repo.path()).arg("--checks");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("feat-a"))
+        .stderr(predicate::str::contains("PR state unavailable"));
+}
+
+#[test]
+fn test_descriptions_fit_the_columns() {
+    const LONG: &str = "Teach the widget factory to ellipsize change descriptions that run long";
+    const WIDE: &str = "日本語の説明がとても長いのでこの行は切られます";
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", LONG), ("feat-b", WIDE)]);
+    let analyze = |columns: Option<&str>| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path").arg(repo.path());
+        match columns {
+            Some(columns) => cmd.env("COLUMNS", columns),
+            None => cmd.env_remove("COLUMNS"),
+        };
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    // Piped output keeps every character
+    let whole = analyze(None);
+    assert!(whole.contains(LONG), "{whole}");
+    assert!(whole.contains(WIDE), "{whole}");
+
+    // COLUMNS cuts descriptions to what their line leaves, never splitting a
+    // wide character
+    let fitted = analyze(Some("50"));
+    assert!(!fitted.contains(LONG), "{fitted}");
+    assert!(!fitted.contains(WIDE), "{fitted}");
+    for (description, start) in [(LONG, "Teach the"), (WIDE, "日本語")] {
+        let line = fitted
+            .lines()
+            .find(|line| line.contains(start))
+            .unwrap_or_else(|| panic!("no line for {description}: {fitted}"));
+        assert!(line.ends_with('…'), "{line}");
+        assert!(console::measure_text_width(line) <= 50, "{line}");
+        let shown = line[line.find(start).unwrap()..].trim_end_matches('…');
+        assert!(description.starts_with(shown), "{line}");
+    }
+
+    // However narrow the terminal, some of the description stays readable
+    let narrow = analyze(Some("10"));
+    assert!(narrow.contains("Teach the w…"), "{narrow}");
+}
+
+#[cfg(feature = "fake")]
+#[test]
+fn test_drift_report_fits_the_columns() {
+    const LEAF: &str = "feat-a-very-long-bookmark-name-for-the-drift-report";
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[(LEAF, "Add A")]);
+    let _remote = repo.add_bare_remote("origin");
+    let dry_run = |columns: Option<&str>| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .args(["--platform", "fake", "sync", "--dry-run"]);
+        match columns {
+            Some(columns) => cmd.env("COLUMNS", columns),
+            None => cmd.env_remove("COLUMNS"),
+        };
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    };
+
+    let whole = dry_run(None);
+    assert!(whole.contains("Drift report"), "{whole}");
+    assert!(whole.contains(LEAF), "{whole}");
+
+    // The stack column gives way, down to a readable minimum
+    let fitted = dry_run(Some("50"));
+    assert!(!fitted.contains(LEAF), "{fitted}");
+    assert!(fitted.contains("feat-a-very… "), "{fitted}");
+}
+
+#[cfg(feature = "fake")]
+#[test]
+fn test_dry_run_exit_codes() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+    let _remote = repo.add_bare_remote("origin");
+    let ryu = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .args(["--platform", "fake"])
+            .args(args);
+        cmd
+    };
+
+    // Nothing submitted yet, so a dry run has changes pending
+    ryu(&["submit", "feat-a", "--dry-run"]).assert().code(3);
+
+    ryu(&["submit", "feat-a"]).assert().code(0);
+    ryu(&["submit", "feat-a", "--dry-run"]).assert().code(0);
+
+    // Errors and usage errors never look like pending changes
+    ryu(&["submit", "no-such-bookmark", "--dry-run"])
+        .assert()
+        .code(1);
+    ryu(&["submit", "--no-such-flag"]).assert().code(2);
+}
+
+#[cfg(feature = "fake")]
+#[test]
+fn test_draft_default_and_overrides() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+    let _remote = repo.add_bare_remote("origin");
+    let ryu = |draft_env: Option<&str>, args: &[&str]| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .args(["--platform", "fake"])
+            .args(args);
+        match draft_env {
+            Some(value) => cmd.env(DRAFT_ENV, value),
+            None => cmd.env_remove(DRAFT_ENV),
+        };
+        cmd
+    };
+    let dry_run = ["submit", "feat-a", "--dry-run"];
+    let draft = predicate::str::contains("create PR feat-a → main (Add A) [draft]");
+
+    // The default alone: from the environment...
+    ryu(Some("1"), &dry_run)
+        .assert()
+        .code(3)
+        .stdout(draft.clone());
+    ryu(None, &dry_run)
+        .assert()
+        .code(3)
+        .stdout(draft.clone().not());
+
+    // ...or the config files, which the environment beats
+    repo.write_file(".ryu.toml", "draft = true\n");
+    ryu(None, &dry_run).assert().code(3).stdout(draft.clone());
+    ryu(Some("0"), &dry_run)
+        .assert()
+        .code(3)
+        .stdout(draft.clone().not());
+
+    // --draft wins over a default of ready PRs
+    ryu(Some("0"), &["submit", "feat-a", "--dry-run", "--draft"])
+        .assert()
+        .code(3)
+        .stdout(draft.clone());
+
+    // --publish (ready for review) wins over a default of drafts
+    ryu(Some("1"), &["submit", "feat-a", "--dry-run", "--publish"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("create PR feat-a").and(draft.not()));
+
+    // Exported plans follow the default too
+    let plan = repo.path().join("plan.json");
+    ryu(Some("1"), &["plan", "feat-a", "-o"])
+        .arg(&plan)
+        .assert()
+        .success();
+    let exported = std::fs::read_to_string(&plan).expect("plan file");
+    assert!(exported.contains("\"draft\": true"), "{exported}");
+}
+
+#[test]
+fn test_json_format_rejected_for_other_commands() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["--format", "json", "base", "list"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--format json"));
+}
+
+#[test]
+fn test_invalid_path() {
+    let mut cmd = Command::cargo_bin("ryu").unwrap();
+    cmd.args(["--path", "/nonexistent/path/to/repo"]);
+
+    cmd.assert().failure();
+}
+
+// =============================================================================
+// Submit Flow Tests
+// =============================================================================
+
+#[test]
+fn test_temp_repo_graph_building() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    // Should have both bookmarks
+    assert!(graph.bookmarks.contains_key("feat-a"));
+    assert!(graph.bookmarks.contains_key("feat-b"));
+
+    // Should have one stack with two segments
+    assert_eq!(graph.stacks.len(), 1);
+    assert_eq!(graph.stacks[0].segments.len(), 2);
+}
+
+#[test]
+fn test_graph_onto_base_branch() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("release", "Cut release"), ("feat-x", "Backport X")]);
+
+    let workspace = repo.workspace();
+    let base = resolve_base_branch(&workspace, "release", "origin").expect("resolve base");
+    let graph = build_change_graph_onto(&workspace, &base).expect("build graph");
+
+    // The release commit is the base, so feat-x is a root on its own
+    assert_eq!(graph.stacks.len(), 1);
+    assert_eq!(graph.stacks[0].segments.len(), 1);
+    assert_eq!(graph.stacks[0].segments[0].bookmarks[0].name, "feat-x");
+
+    let missing = resolve_base_branch(&workspace, "release/9.9", "origin");
+    assert!(matches!(missing, Err(Error::InvalidArgument(_))));
+}
+
+#[test]
+fn test_stack_and_segment_ids_stable_across_runs() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let graph = build_change_graph(&repo.workspace()).expect("build graph");
+    let stack = &graph.stacks[0];
+    assert_eq!(stack.id, repo.change_id("feat-b"));
+    assert_eq!(stack.segments[0].id, repo.change_id("feat-a"));
+    assert_eq!(stack.segments[1].id, repo.change_id("feat-b"));
+
+    // A second stack forking off feat-a leaves the first one's IDs alone
+    repo.new_change("feat-a", "Add C");
+    repo.create_bookmark("feat-c");
+    let graph = build_change_graph(&repo.workspace()).expect("rebuild graph");
+    assert_eq!(graph.stacks.len(), 2);
+    let same = graph
+        .stacks
+        .iter()
+        .find(|s| s.id == stack.id)
+        .expect("stack keeps its ID");
+    let segment_ids: Vec<&str> = same.segments.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(
+        segment_ids,
+        [stack.segments[0].id.as_str(), stack.segments[1].id.as_str()]
+    );
+}
+
+#[test]
+fn test_analyze_real_repo_stack() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[
+        ("feat-a", "Add A"),
+        ("feat-b", "Add B"),
+        ("feat-c", "Add C"),
+    ]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    // Analyze middle of stack
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    assert_eq!(analysis.target_bookmark, "feat-b");
+    assert_eq!(analysis.segments.len(), 2);
+    assert_eq!(analysis.segments[0].bookmark.name, "feat-a");
+    assert_eq!(analysis.segments[1].bookmark.name, "feat-b");
+}
+
+#[tokio::test]
+async fn test_full_submit_flow_new_stack() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add feature A"), ("feat-b", "Add feature B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    // Mock returns None for all find_existing_pr calls (default behavior)
+    let mock = MockPlatformService::with_config(github_config());
+
+    let plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+
+    // Verify plan
+    assert_eq!(plan.count_creates(), 2);
+    assert_eq!(plan.count_pushes(), 2);
+    assert_eq!(plan.count_updates(), 0);
+
+    // Find CreatePr steps and verify base branches
+    let creates: Vec<_> = plan
+        .execution_steps
+        .iter()
+        .filter_map(|s| match s {
+            ExecutionStep::CreatePr(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(creates[0].base_branch, "main");
+    assert_eq!(creates[1].base_branch, "feat-a");
+
+    // Verify titles are not empty
+    assert!(!creates[0].title.is_empty());
+    assert!(!creates[1].title.is_empty());
+}
+
+#[tokio::test]
+async fn test_multi_platform_plans_are_independent() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add feature A"), ("feat-b", "Add feature B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    // feat-a already has a PR on GitHub, but nothing exists on the GitLab mirror
+    let github = MockPlatformService::with_config(github_config());
+    github.set_find_pr_response("feat-a", Some(make_pr(7, "feat-a", "main")));
+    let gitlab = MockPlatformService::with_config(gitlab_config());
+
+    let targets = vec![
+        SubmissionTarget {
+            remote: "origin".to_string(),
+            platform: Box::new(github),
+            comment_platform: None,
+        },
+        SubmissionTarget {
+            remote: "gitlab".to_string(),
+            platform: Box::new(gitlab),
+            comment_platform: None,
+        },
+    ];
+
+    let plans = create_multi_submission_plan(&analysis, &targets, "main")
+        .await
+        .expect("create plans");
+    assert_eq!(plans.len(), 2);
+    assert_eq!(plans[0].remote, "origin");
+    assert_eq!(plans[0].count_creates(), 1);
+    assert_eq!(plans[1].remote, "gitlab");
+    assert_eq!(plans[1].count_creates(), 2);
+
+    let results = execute_multi_submission(&plans, &targets, &mut workspace, &NoopProgress, true)
+        .await
+        .expect("dry run");
+    let platforms: Vec<_> = results.iter().map(|r| r.platform).collect();
+    assert_eq!(platforms, vec![Platform::GitHub, Platform::GitLab]);
+    assert!(results.iter().all(|r| r.result.success));
+}
+
+#[tokio::test]
+async fn test_stack_comments_use_comment_platform() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let human = MockPlatformService::with_config(github_config());
+    human.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    human.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+    let bot = MockPlatformService::with_config(github_config());
+
+    let mut plan = create_submission_plan(&analysis, &human, "origin", "main")
+        .await
+        .expect("create plan");
+    // Nothing is on the remote; skip pushes to exercise the comments phase only
+    plan.execution_steps.clear();
+
+    let result = execute_submission(
+        &plan,
+        &mut workspace,
+        &human,
+        Some(&bot),
+        &NoopProgress,
+        false,
+    )
+    .await
+    .expect("execute");
+
+    assert!(result.success);
+    assert!(human.get_create_comment_calls().is_empty());
+    assert!(human.get_list_comments_calls().is_empty());
+    assert_eq!(bot.get_create_comment_calls().len(), 2);
+}
+
+#[tokio::test]
+async fn test_interdiff_comment_after_resubmit() {
+    let repo = TempJjRepo::new();
+    repo.write_file("a.txt", "a\n");
+    repo.commit("Add A");
+    repo.create_bookmark("feat-a");
+    repo.write_file("b.txt", "b\n");
+    repo.commit("Add B");
+    repo.create_bookmark("feat-b");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+
+    let submit = || async {
+        let mut workspace = repo.workspace();
+        let graph = build_change_graph(&workspace).expect("build graph");
+        let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+        let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+            .await
+            .expect("create plan");
+        plan.execution_steps.clear();
+        plan.stack_info = StackInfo::None;
+        let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+            .await
+            .expect("execute");
+        assert!(result.success, "{:?}", result.errors);
+    };
+
+    // The first submit only records snapshots
+    submit().await;
+    assert!(mock.get_create_comment_calls().is_empty());
+
+    // Edit feat-a; feat-b is only rebased, so it gets no comment
+    repo.new_change("feat-b", "fixup");
+    repo.write_file("a.txt", "a, reviewed\n");
+    repo.squash_into("feat-a");
+    submit().await;
+
+    let comments = mock.get_create_comment_calls();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].pr_number, 1);
+    assert!(comments[0].body.contains(INTERDIFF_MARKER));
+    assert!(comments[0].body.contains("Add A (**edited**)"));
+    assert!(comments[0].body.contains("- `a.txt`"));
+    assert!(!comments[0].body.contains("b.txt"));
+}
+
+#[tokio::test]
+async fn test_stack_info_body_writes_pr_bodies_not_comments() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+    mock.set_pr_body(1, "Adds A");
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps.clear();
+    plan.stack_info = StackInfo::Body;
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+
+    assert!(result.success);
+    assert!(mock.get_create_comment_calls().is_empty());
+    let bodies = mock.get_update_body_calls();
+    assert_eq!(bodies.len(), 2);
+    let (number, body) = &bodies[0];
+    assert_eq!(*number, 1);
+    assert!(body.starts_with("Adds A\n\n"));
+    let section = stack_section(body).expect("stack section");
+    let data = parse_stack_comment_data(section).expect("stack data");
+    assert_eq!(data.stack.len(), 2);
+}
+
+#[tokio::test]
+async fn test_stack_comments_are_written_in_one_batch() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[
+        ("feat-a", "Add A"),
+        ("feat-b", "Add B"),
+        ("feat-c", "Add C"),
+    ]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-c").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+    mock.set_find_pr_response("feat-c", Some(make_pr(3, "feat-c", "feat-b")));
+    let old_comment = format!("{COMMENT_DATA_PREFIX}e30= -->\nold stack");
+    mock.set_list_comments_response(2, vec![make_pr_comment(20, &old_comment)]);
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps.clear();
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+
+    assert!(result.success);
+    assert_eq!(mock.get_write_comments_batches(), vec![3]);
+    assert_eq!(mock.get_create_comment_calls().len(), 2);
+    let updates = mock.get_update_comment_calls();
+    assert_eq!(updates.len(), 1);
+    assert_eq!((updates[0].pr_number, updates[0].comment_id), (2, 20));
+}
+
+#[tokio::test]
+async fn test_retarget_stops_when_pr_changed_after_planning() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "main")));
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps
+        .retain(|step| !matches!(step, ExecutionStep::Push(_)));
+    plan.stack_info = StackInfo::None;
+    assert_eq!(plan.count_updates(), 1);
+
+    // Someone retargets feat-b by hand between planning and executing
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "release")));
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+
+    assert!(!result.success);
+    assert!(result.errors[0].contains("PR #2 changed since the submission was planned"));
+    assert!(result.errors[0].contains("base is now release"));
+    assert!(mock.get_update_base_calls().is_empty());
+    assert!(
+        find_journal(repo.path(), "origin", "feat-b")
+            .expect("read journal")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_renamed_bookmark_closes_old_pr() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    let change_id = analysis.segments[1].bookmark.change_id.clone();
+
+    // feat-b used to be called old-b, and its PR is still open under that name
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_author_prs_response(
+        "test-user",
+        vec![make_pr(1, "feat-a", "main"), make_pr(2, "old-b", "feat-a")],
+    );
+    let old_comment = format_stack_comment(
+        &StackCommentData {
+            version: 0,
+            stack: vec![StackItem {
+                bookmark_name: "old-b".to_string(),
+                pr_url: "https://github.com/test/repo/pull/2".to_string(),
+                pr_number: 2,
+                change_id: Some(change_id),
+            }],
+            tracking_issue: None,
+        },
+        0,
+    )
+    .expect("format comment");
+    mock.set_list_comments_response(2, vec![make_pr_comment(20, &old_comment)]);
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    assert_eq!(plan.renamed_prs.len(), 1);
+    assert_eq!(plan.renamed_prs[0].bookmark, "feat-b");
+    assert_eq!(plan.renamed_prs[0].old_pr.number, 2);
+
+    // Nothing is on the remote; keep only the platform steps
+    plan.execution_steps
+        .retain(|step| !matches!(step, ExecutionStep::Push(_)));
+    plan.stack_info = StackInfo::None;
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+
+    assert!(result.success);
+    assert_eq!(result.created_prs.len(), 1);
+    assert_eq!(mock.get_close_pr_calls(), vec![2]);
+    assert_eq!(result.closed_prs.len(), 1);
+    let comments = mock.get_create_comment_calls();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].pr_number, 2);
+    assert!(comments[0].body.contains("renamed to `feat-b`"));
+}
+
+#[tokio::test]
+async fn test_submission_warns_when_rate_limit_is_nearly_spent() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_rate_limit(5000, 3);
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps
+        .retain(|step| !matches!(step, ExecutionStep::Push(_)));
+    plan.stack_info = StackInfo::None;
+
+    let progress = JsonProgress::new();
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &progress, false)
+        .await
+        .expect("execute");
+
+    // The warning doesn't stop the submission
+    assert!(result.success);
+    let warnings: Vec<Warning> = progress
+        .take_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            ProgressEvent::Warning { warning } => Some(warning),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![Warning::RateLimitNearing {
+            remaining: 3,
+            limit: 5000,
+            resets_at: None,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_submission_warns_about_renamed_bookmark() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-a").expect("analyze");
+    let change_id = graph.bookmarks["feat-a"].change_id.clone();
+
+    // The PR was opened before feat-a was renamed from old-a
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_author_prs_response("test-user", vec![make_pr(5, "old-a", "main")]);
+    let comment = format_stack_comment(
+        &StackCommentData {
+            version: 1,
+            stack: vec![StackItem {
+                bookmark_name: "old-a".to_string(),
+                pr_url: "https://github.com/test/repo/pull/5".to_string(),
+                pr_number: 5,
+                change_id: Some(change_id),
+            }],
+            tracking_issue: None,
+        },
+        0,
+    )
+    .expect("format comment");
+    mock.set_list_comments_response(5, vec![make_pr_comment(50, &comment)]);
+
+    let plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    assert_eq!(plan.renamed_prs.len(), 1);
+
+    let progress = JsonProgress::new();
+    execute_submission(&plan, &mut workspace, &mock, None, &progress, true)
+        .await
+        .expect("execute");
+
+    let warnings: Vec<Warning> = progress
+        .take_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            ProgressEvent::Warning { warning } => Some(warning),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![Warning::RenamedBookmark {
+            pr_number: 5,
+            old_bookmark: "old-a".to_string(),
+            bookmark: "feat-a".to_string(),
+        }]
+    );
+}
+
+#[cfg(feature = "fake")]
+#[tokio::test]
+async fn test_fake_platform_keeps_prs_in_the_workspace() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let config = fake_platform_config(workspace.workspace_root());
+    let fake = FakeService::new(config.clone()).expect("fake platform");
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    let mut plan = create_submission_plan(&analysis, &fake, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps
+        .retain(|step| !matches!(step, ExecutionStep::Push(_)));
+
+    let result = execute_submission(&plan, &mut workspace, &fake, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+    assert!(result.success);
+    assert_eq!(result.created_prs.len(), 2);
+
+    // A fresh service reads back what the first one stored
+    let fake = FakeService::new(config).expect("fake platform");
+    let pr_b = fake
+        .find_existing_pr("feat-b")
+        .await
+        .expect("find")
+        .expect("feat-b has a PR");
+    assert_eq!(pr_b.base_ref, "feat-a");
+    assert!(pr_b.html_url.starts_with("fake://local/"));
+    let comments = fake.list_pr_comments(pr_b.number).await.expect("comments");
+    assert_eq!(comments.len(), 1);
+
+    // Submitting again finds the PRs instead of creating new ones
+    let plan = create_submission_plan(&analysis, &fake, "origin", "main")
+        .await
+        .expect("create plan");
+    assert_eq!(plan.existing_prs.len(), 2);
+}
+
+#[cfg(feature = "fake")]
+#[tokio::test]
+async fn test_adopt_takes_over_prs_opened_outside_ryu() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    let _remote = repo.add_bare_remote("origin");
+    let root = repo.workspace().workspace_root().to_path_buf();
+
+    // Both PRs were opened by hand, feat-b's against trunk
+    let fake = FakeService::new(fake_platform_config(&root)).expect("fake platform");
+    let pr_a = fake.create_pr("feat-a", "main", "Add A").await.unwrap();
+    let pr_b = fake.create_pr("feat-b", "main", "Add B").await.unwrap();
+    let ryu = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .args(["--platform", "fake"])
+            .args(args);
+        cmd
+    };
+
+    // A dry run shows the match and changes nothing
+    ryu(&["adopt", "--dry-run"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("Adopting stack: feat-b"))
+        .stdout(predicate::str::contains(format!(
+            "feat-b → #{}",
+            pr_b.number
+        )));
+    let unchanged = fake.find_existing_pr("feat-b").await.unwrap().unwrap();
+    assert_eq!(unchanged.base_ref, "main");
+    assert!(fake.list_pr_comments(pr_b.number).await.unwrap().is_empty());
+    assert!(RyuState::load(&root).unwrap().adopted_prs.is_empty());
+
+    ryu(&["adopt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 PRs adopted"));
+
+    // feat-b now follows the stack, both PRs carry a stack comment, and
+    // nothing new was opened
+    let adopted = fake.find_existing_pr("feat-b").await.unwrap().unwrap();
+    assert_eq!(adopted.number, pr_b.number);
+    assert_eq!(adopted.base_ref, "feat-a");
+    for pr in [&pr_a, &pr_b] {
+        let comments = fake.list_pr_comments(pr.number).await.unwrap();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].body.contains(COMMENT_DATA_PREFIX));
+    }
+    let state = RyuState::load(&root).unwrap();
+    assert_eq!(state.adopted_prs.get("feat-a"), Some(&pr_a.number));
+    assert_eq!(state.adopted_prs.get("feat-b"), Some(&pr_b.number));
+}
+
+#[cfg(feature = "fake")]
+#[tokio::test]
+async fn test_undo_keeps_prs_it_could_not_close() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    let _remote = repo.add_bare_remote("origin");
+    let root = repo.workspace().workspace_root().to_path_buf();
+    let ryu = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .args(["--platform", "fake"])
+            .args(args);
+        cmd
+    };
+
+    ryu(&["submit", "feat-b"]).assert().success();
+    let mut record = load_undo_record(&root).unwrap().expect("submit recorded");
+    assert_eq!(record.created_prs().count(), 2);
+    let config = record.created_prs().next().unwrap().2.clone();
+    assert_eq!(config.platform, Platform::Fake);
+
+    // A PR the platform no longer has can't be closed
+    record.remote_changes.push(RemoteChange::CreatedPr {
+        number: 99,
+        url: "fake://local/gone/pull/99".to_string(),
+        platform: config.clone(),
+    });
+    save_undo_record(&root, &record).unwrap();
+
+    ryu(&["undo", "--close-created"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Closed #1"))
+        .stdout(predicate::str::contains("Closed #2"))
+        .stderr(predicate::str::contains("Failed to close 1 of 3 PRs"));
+    let fake = FakeService::new(config.clone()).expect("fake platform");
+    for bookmark in ["feat-a", "feat-b"] {
+        let (_, state) = fake.find_latest_pr(bookmark).await.unwrap().unwrap();
+        assert_eq!(state, PrState::Closed, "{bookmark}");
+    }
+
+    // Only the PR still open is left to close, with the repo already restored
+    let pending = load_undo_record(&root).unwrap().expect("kept for a retry");
+    assert_eq!(
+        pending.remote_changes,
+        vec![RemoteChange::CreatedPr {
+            number: 99,
+            url: "fake://local/gone/pull/99".to_string(),
+            platform: config,
+        }]
+    );
+    assert!(!pending.changed_repo());
+
+    ryu(&["undo", "--close-created"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Restored").not())
+        .stderr(predicate::str::contains("Failed to close 1 of 1 PRs"));
+    assert_eq!(load_undo_record(&root).unwrap(), Some(pending));
+}
+
+#[tokio::test]
+async fn test_resume_skips_steps_that_already_ran() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    // feat-a's PR needs retargeting; feat-b needs a PR, which fails
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "develop")));
+    mock.fail_create_pr("rate limited");
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    // Nothing is on the remote; keep only the platform steps
+    plan.execution_steps
+        .retain(|step| !matches!(step, ExecutionStep::Push(_)));
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+    assert!(!result.success);
+
+    let journal = find_journal(repo.path(), "origin", "feat-a")
+        .expect("load journal")
+        .expect("journal left behind");
+    assert_eq!(journal.completed.len(), 1);
+    assert_eq!(journal.remaining(), 1);
+    journal
+        .check_resumable(&workspace.local_bookmarks().expect("bookmarks"))
+        .expect("bookmarks unchanged");
+
+    let retry = MockPlatformService::with_config(github_config());
+    let result = resume_submission(journal, &mut workspace, &retry, None, &NoopProgress)
+        .await
+        .expect("resume");
+
+    assert!(result.success);
+    assert!(retry.get_update_base_calls().is_empty());
+    retry.assert_create_pr_called("feat-b", "feat-a");
+    // The PR retargeted before the failure still gets its stack comment
+    assert_eq!(retry.get_create_comment_calls().len(), 2);
+    assert!(
+        find_journal(repo.path(), "origin", "feat-b")
+            .expect("load journal")
+            .is_none()
+    );
+}
+
+/// Whether a bare repository has `branch`
+fn remote_has_branch(remote: &std::path::Path, branch: &str) -> bool {
+    std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(remote)
+        .args(["rev-parse", "--quiet", "--verify"])
+        .arg(format!("refs/heads/{branch}"))
+        .output()
+        .expect("run git")
+        .status
+        .success()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_resume_after_push_batch_fails_part_way() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[
+        ("feat-a", "Add A"),
+        ("feat-b", "Add B"),
+        ("feat-c", "Add C"),
+    ]);
+    let remote = repo.add_bare_remote("origin");
+    // The remote turns feat-b away, and only feat-b, while the marker exists
+    let hook = remote.path().join("hooks").join("update");
+    std::fs::write(
+        &hook,
+        "#!/bin/sh\n\
+         if [ \"$1\" = refs/heads/feat-b ] && [ -e \"$GIT_DIR/reject-feat-b\" ]; then\n\
+         \techo 'feat-b is frozen' >&2\n\
+         \texit 1\n\
+         fi\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let marker = remote.path().join("reject-feat-b");
+    std::fs::write(&marker, "").unwrap();
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-c").expect("analyze");
+    let mock = MockPlatformService::with_config(github_config());
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    // Only the pushes, so they all go out as one batch
+    plan.execution_steps
+        .retain(|step| matches!(step, ExecutionStep::Push(_)));
+    plan.stack_info = StackInfo::None;
+    assert_eq!(plan.execution_steps.len(), 3);
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+    assert!(!result.success);
+    assert!(
+        result.errors[0].starts_with("Failed to push feat-b:"),
+        "{:?}",
+        result.errors
+    );
+    assert_eq!(result.pushed_bookmarks, vec!["feat-a", "feat-c"]);
+    assert!(remote_has_branch(remote.path(), "feat-a"));
+    assert!(!remote_has_branch(remote.path(), "feat-b"));
+
+    // What landed is journaled, so resuming only pushes feat-b
+    let journal = find_journal(repo.path(), "origin", "feat-c")
+        .expect("load journal")
+        .expect("journal left behind");
+    let done: Vec<&str> = journal
+        .completed
+        .iter()
+        .filter_map(|done| match &journal.plan.execution_steps[done.index] {
+            ExecutionStep::Push(bm) => Some(bm.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(done, vec!["feat-a", "feat-c"]);
+    assert_eq!(journal.remaining(), 1);
+
+    std::fs::remove_file(&marker).unwrap();
+    let result = resume_submission(journal, &mut workspace, &mock, None, &NoopProgress)
+        .await
+        .expect("resume");
+    assert!(result.success, "{:?}", result.errors);
+    assert_eq!(result.pushed_bookmarks, vec!["feat-b"]);
+    assert!(remote_has_branch(remote.path(), "feat-b"));
+    assert!(
+        find_journal(repo.path(), "origin", "feat-c")
+            .expect("load journal")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_restack_moves_stack_onto_new_trunk() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    // Trunk moved: a new commit the stack isn't based on
+    repo.new_change("root()", "Trunk moved");
+
+    let mut workspace = repo.workspace();
+    let trunk = workspace.resolve_revset("@").expect("resolve @")[0]
+        .commit_id
+        .clone();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let stacks: Vec<_> = graph.stacks.iter().collect();
+    let restacks = plan_restacks(&stacks, &trunk);
+    assert_eq!(restacks.len(), 1);
+    assert_eq!(restacks[0].bookmarks, vec!["feat-a", "feat-b"]);
+
+    let restacked = restack_onto_trunk(&mut workspace, &restacks, &trunk, &NoopProgress)
+        .await
+        .expect("restack");
+    assert_eq!(restacked, vec!["feat-a", "feat-b"]);
+
+    let workspace = repo.workspace();
+    let feat_b = workspace
+        .get_local_bookmark("feat-b")
+        .expect("get bookmark")
+        .expect("feat-b exists");
+    let on_trunk = workspace
+        .resolve_revset(&format!("{trunk}::{}", feat_b.commit_id))
+        .expect("resolve range");
+    // Trunk, the initial commit, A and B
+    assert_eq!(on_trunk.len(), 4);
+
+    // Already on trunk: nothing left to restack
+    let graph = build_change_graph(&workspace).expect("rebuild graph");
+    let stacks: Vec<_> = graph.stacks.iter().collect();
+    assert!(plan_restacks(&stacks, &trunk).is_empty());
+    assert!(
+        load_restack_journal(repo.path())
+            .expect("load journal")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_pull_remote_commits_restacks_upstack() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    // A reviewer's suggestion on top of the pushed feat-a
+    repo.new_change("feat-a", "Apply suggestion");
+
+    let mut workspace = repo.workspace();
+    let feat_a = workspace
+        .get_local_bookmark("feat-a")
+        .expect("get bookmark")
+        .expect("feat-a exists")
+        .commit_id;
+    let suggestion = workspace.resolve_revset("@").expect("resolve @")[0]
+        .commit_id
+        .clone();
+    let commits = RemoteCommits {
+        bookmark: "feat-a".to_string(),
+        local: feat_a.clone(),
+        previous: feat_a,
+        remote: suggestion.clone(),
+        authors: vec!["Reviewer".to_string()],
+        count: 1,
+    };
+    pull_remote_commits(&mut workspace, &commits).expect("pull");
+
+    let workspace = repo.workspace();
+    let feat_a = workspace
+        .get_local_bookmark("feat-a")
+        .expect("get bookmark")
+        .expect("feat-a exists");
+    assert_eq!(feat_a.commit_id, suggestion);
+    let feat_b = workspace
+        .get_local_bookmark("feat-b")
+        .expect("get bookmark")
+        .expect("feat-b exists");
+    let parents = workspace
+        .resolve_revset(&format!("{}-", feat_b.commit_id))
+        .expect("resolve parent");
+    assert_eq!(parents[0].commit_id, suggestion);
+
+    let graph = build_change_graph(&workspace).expect("build graph");
+    // Still one linear stack, the suggestion part of feat-a's segment
+    assert_eq!(graph.stacks.len(), 1);
+    assert_eq!(graph.stacks[0].segments.len(), 2);
+}
+
+#[tokio::test]
+async fn test_conflicted_restack_is_journaled_for_continue() {
+    let repo = TempJjRepo::new();
+    repo.write_file("shared.txt", "from the stack\n");
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    repo.new_change("root()", "Trunk moved");
+    repo.write_file("shared.txt", "from trunk\n");
+    repo.commit("Trunk moved");
+
+    let mut workspace = repo.workspace();
+    let trunk = workspace.resolve_revset("@-").expect("resolve @-")[0]
+        .commit_id
+        .clone();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let stacks: Vec<_> = graph.stacks.iter().collect();
+    let restacks = plan_restacks(&stacks, &trunk);
+
+    let err = restack_onto_trunk(&mut workspace, &restacks, &trunk, &NoopProgress)
+        .await
+        .expect_err("restack conflicts");
+    assert!(
+        matches!(err, Error::RestackConflicts(ref bookmarks) if bookmarks.contains(&"feat-a".to_string()))
+    );
+
+    let journal = load_restack_journal(repo.path())
+        .expect("load journal")
+        .expect("journal kept");
+    assert_eq!(journal.trunk_commit_id, trunk);
+    assert_eq!(journal.done, 1);
+
+    // Still conflicted: continuing refuses and keeps the journal
+    let mut workspace = repo.workspace();
+    let err = continue_restack(&mut workspace, &NoopProgress)
+        .await
+        .expect_err("still conflicted");
+    assert!(matches!(err, Error::RestackConflicts(_)));
+    assert!(
+        load_restack_journal(repo.path())
+            .expect("load journal")
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn test_tracking_issue_created_and_linked() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+
+    let mut plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+    plan.execution_steps.clear();
+    plan.tracking_issue = true;
+
+    let result = execute_submission(&plan, &mut workspace, &mock, None, &NoopProgress, false)
+        .await
+        .expect("execute");
+
+    assert!(result.success);
+    let issues = mock.get_create_issue_calls();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].0, "Stack: PR for feat-b");
+    assert!(issues[0].1.contains("- [ ] #1\n- [ ] #2\n"));
+
+    let comments = mock.get_create_comment_calls();
+    assert_eq!(comments.len(), 2);
+    assert!(
+        comments
+            .iter()
+            .all(|c| c.body.contains("Tracking issue: #"))
+    );
+}
+
+#[tokio::test]
+async fn test_stack_statuses_report_pr_state_and_base() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_latest_pr_response(
+        "feat-a",
+        Some((make_pr(1, "feat-a", "main"), PrState::Merged)),
+    );
+    // Still targets main although feat-a sits below it
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "main")));
+    mock.set_pr_readiness(2, false, ChecksState::Failing);
+    mock.set_pr_reviews(
+        2,
+        vec![PrReview {
+            reviewer: "alice".to_string(),
+            state: ReviewState::ChangesRequested,
+        }],
+    );
+
+    let statuses = stack_statuses(&graph, &mock, "main", &HashMap::new())
+        .await
+        .expect("statuses");
+
+    assert_eq!(statuses.len(), 1);
+    let stack = &statuses[0];
+    assert_eq!(stack.leaf, "feat-b");
+    let states: Vec<_> = stack
+        .bookmarks
+        .iter()
+        .map(|b| b.pr.as_ref().map(|(_, state)| *state))
+        .collect();
+    assert_eq!(states, vec![Some(PrState::Merged), Some(PrState::Open)]);
+    assert!(!stack.bookmarks[0].base_mismatch());
+    assert!(stack.bookmarks[1].base_mismatch());
+    assert_eq!(stack.bookmarks[1].expected_base, "feat-a");
+    // Checks and reviews only matter while a PR is open
+    assert_eq!(stack.bookmarks[0].checks, None);
+    assert_eq!(stack.bookmarks[1].checks, Some(ChecksState::Failing));
+    assert_eq!(stack.bookmarks[0].review, None);
+    assert_eq!(
+        stack.bookmarks[1].review,
+        Some(ReviewState::ChangesRequested)
+    );
+}
+
+#[tokio::test]
+async fn test_merged_bookmarks_skips_open_and_unsubmitted() {
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_latest_pr_response(
+        "feat-a",
+        Some((make_pr(1, "feat-a", "main"), PrState::Merged)),
+    );
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+    mock.set_latest_pr_response(
+        "feat-c",
+        Some((make_pr(3, "feat-c", "main"), PrState::Closed)),
+    );
+
+    let bookmarks: Vec<String> = ["feat-a", "feat-b", "feat-c", "feat-d"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let merged = merged_bookmarks(&mock, &bookmarks).await.expect("merged");
+
+    let merged: Vec<_> = merged
+        .iter()
+        .map(|(name, pr)| (name.as_str(), pr.number))
+        .collect();
+    assert_eq!(merged, vec![("feat-a", 1)]);
+}
+
+#[tokio::test]
+async fn test_verify_reports_broken_invariants() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    // Still targets main although feat-a sits below it
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "main")));
+    let item = |name: &str, number: u64| StackItem {
+        bookmark_name: name.to_string(),
+        pr_url: format!("https://github.com/test/repo/pull/{number}"),
+        pr_number: number,
+        change_id: None,
+    };
+    let comment = format_stack_comment(
+        &StackCommentData {
+            version: 0,
+            stack: vec![item("feat-a", 1), item("feat-b", 2)],
+            tracking_issue: None,
+        },
+        0,
+    )
+    .expect("format comment");
+    mock.set_list_comments_response(1, vec![make_pr_comment(10, &comment)]);
+
+    let violations = verify_stacks(
+        &workspace,
+        &graph,
+        &mock,
+        "origin",
+        "main",
+        &HashMap::new(),
+        StackInfo::Comment,
+    )
+    .await
+    .expect("verify");
+
+    // Nothing was pushed, so both bookmarks are behind the remote too
+    let remote_bookmarks: Vec<_> = violations
+        .iter()
+        .filter(|v| matches!(v, Violation::RemoteOutOfDate { .. }))
+        .map(Violation::bookmark)
+        .collect();
+    assert_eq!(remote_bookmarks, vec!["feat-a", "feat-b"]);
+    assert!(violations.contains(&Violation::WrongBase {
+        bookmark: "feat-b".to_string(),
+        pr_number: 2,
+        base: "main".to_string(),
+        expected: "feat-a".to_string(),
+    }));
+    assert!(violations.contains(&Violation::StaleStackComment {
+        bookmark: "feat-b".to_string(),
+        pr_number: 2,
+        missing: true,
+        leaf: "feat-b".to_string(),
+    }));
+    assert!(
+        !violations
+            .iter()
+            .any(|v| v.bookmark() == "feat-a" && !matches!(v, Violation::RemoteOutOfDate { .. }))
+    );
+    assert_eq!(violations.len(), 4);
+}
+
+#[tokio::test]
+async fn test_merge_bottom_pr_and_drop_bookmark() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mock = MockPlatformService::with_config(github_config());
+    let pr = make_pr(1, "feat-a", "main");
+    mock.set_find_pr_response("feat-a", Some(pr.clone()));
+
+    merge_and_wait(
+        &mock,
+        &pr,
+        MergeMethod::Rebase,
+        Duration::ZERO,
+        Duration::from_secs(1),
+    )
+    .await
+    .expect("merge");
+    assert_eq!(mock.get_merge_pr_calls(), vec![(1, MergeMethod::Rebase)]);
+
+    let mut workspace = repo.workspace();
+    workspace
+        .delete_bookmark("feat-a")
+        .expect("delete bookmark");
+    assert!(workspace.get_local_bookmark("feat-a").unwrap().is_none());
+    assert!(workspace.delete_bookmark("feat-a").is_err());
+
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    let names: Vec<_> = analysis
+        .segments
+        .iter()
+        .map(|s| s.bookmark.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["feat-b"]);
 }
 
-#[test]
-fn test_sync_help() {
-    let mut cmd = Command::cargo_bin("ryu").unwrap();
-    cmd.args(["sync", "--help"]);
+#[tokio::test]
+async fn test_prune_drops_merged_bookmark_and_landed_change() {
+    let repo = TempJjRepo::new();
+    repo.write_file("a.txt", "a\n");
+    repo.build_stack(&[("feat-a", "Add A")]);
+    repo.write_file("b.txt", "b\n");
+    repo.build_stack(&[("feat-b", "Add B")]);
+
+    let mut workspace = repo.workspace();
+    let mut graph = build_change_graph(&workspace).expect("build graph");
+    for bm in graph.stacks[0]
+        .segments
+        .iter_mut()
+        .flat_map(|segment| &mut segment.bookmarks)
+    {
+        bm.has_remote = true;
+    }
+    let landed_change = repo.change_id("feat-a");
+    let mut state = RyuState::default();
+    state.landed.insert(
+        landed_change.clone(),
+        LandedChange {
+            bookmark: "feat-a".to_string(),
+            pr_number: 1,
+            commit: "abc123".to_string(),
+        },
+    );
+    state.save(repo.path()).expect("save state");
 
-    cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("Sync all stacks"));
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_latest_pr_response(
+        "feat-a",
+        Some((make_pr(1, "feat-a", "main"), PrState::Merged)),
+    );
+    mock.set_latest_pr_response(
+        "feat-b",
+        Some((make_pr(2, "feat-b", "feat-a"), PrState::Open)),
+    );
+
+    let stacks: Vec<_> = graph.stacks.iter().collect();
+    let dry = prune_merged(&mut workspace, &stacks, &mock, "origin", true)
+        .await
+        .expect("dry run");
+    assert!(dry.abandoned.contains(&landed_change));
+    assert!(workspace.get_local_bookmark("feat-a").unwrap().is_some());
+
+    let report = prune_merged(&mut workspace, &stacks, &mock, "origin", false)
+        .await
+        .expect("prune");
+    assert_eq!(report, dry);
+    let pruned: Vec<_> = report
+        .bookmarks
+        .iter()
+        .map(|p| p.bookmark.as_str())
+        .collect();
+    assert_eq!(pruned, vec!["feat-a"]);
+    assert!(!report.bookmarks[0].on_remote);
+    assert!(workspace.get_local_bookmark("feat-a").unwrap().is_none());
+
+    // feat-b moved onto Add A's parent and is a stack of its own
+    let graph = build_change_graph(&workspace).expect("rebuild graph");
+    let segments = &graph.stacks[0].segments;
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].changes[0].description_first_line, "Add B");
+    assert!(
+        !segments[0]
+            .changes
+            .iter()
+            .any(|change| change.description_first_line == "Add A")
+    );
 }
 
-#[test]
-fn test_auth_help() {
-    let mut cmd = Command::cargo_bin("ryu").unwrap();
-    cmd.args(["auth", "--help"]);
+#[tokio::test]
+async fn test_hover_finds_segment_that_last_changed_line() {
+    let repo = TempJjRepo::new();
+    repo.write_file("lib.rs", "fn a() {}\n");
+    repo.build_stack(&[("feat-a", "Add A")]);
+    repo.write_file("lib.rs", "fn a() {}\nfn b() {}\n");
+    repo.build_stack(&[("feat-b", "Add B")]);
 
-    cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("github"))
-        .stdout(predicate::str::contains("gitlab"));
+    let mock = MockPlatformService::with_config(github_config());
+    for backend in [JjBackend::Lib, JjBackend::Cli] {
+        let workspace = JjWorkspace::open_with(repo.path(), backend).expect("open");
+        let graph = build_change_graph(&workspace).expect("build graph");
+
+        let context = line_context(&workspace, &graph, &mock, "lib.rs", 2)
+            .await
+            .expect("hover")
+            .expect("line is in a stack");
+        assert_eq!(context.bookmark, "feat-b", "{backend:?}");
+        assert_eq!(context.stack, "feat-b");
+        assert_eq!(context.description, "Add B");
+        // Never pushed, so there's no PR to look up
+        assert!(context.pr.is_none());
+
+        let first = line_context(&workspace, &graph, &mock, "lib.rs", 1)
+            .await
+            .expect("hover")
+            .expect("line is in a stack");
+        assert_eq!(first.bookmark, "feat-a");
+        assert_eq!(first.stack, "feat-b");
+
+        assert!(
+            line_context(&workspace, &graph, &mock, "lib.rs", 3)
+                .await
+                .is_err()
+        );
+    }
 }
 
 #[test]
-fn test_invalid_path() {
-    let mut cmd = Command::cargo_bin("ryu").unwrap();
-    cmd.args(["--path", "/nonexistent/path/to/repo"]);
+fn test_auto_bookmarks_turn_each_change_into_a_segment() {
+    let repo = TempJjRepo::new();
+    repo.commit("Add login form");
+    repo.commit("Validate login input");
+
+    let mut workspace = repo.workspace();
+    let changes = workspace.resolve_revset("trunk()..@-").expect("resolve");
+    let planned = plan_auto_bookmarks(&changes, "{user}/{slug}", "alice", None, &HashSet::new())
+        .expect("plan");
+    let names: Vec<_> = planned.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["alice/add-login-form", "alice/validate-login-input"]
+    );
+
+    for bookmark in &planned {
+        workspace
+            .create_bookmark(&bookmark.name, &bookmark.commit_id)
+            .expect("create bookmark");
+    }
+    assert!(
+        workspace
+            .create_bookmark(&planned[0].name, &planned[0].commit_id)
+            .is_err()
+    );
 
-    cmd.assert().failure();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "alice/validate-login-input").expect("analyze");
+    assert_eq!(analysis.segments.len(), 2);
+    assert_eq!(analysis.segments[0].bookmark.name, "alice/add-login-form");
 }
 
-// =============================================================================
-// Submit Flow Tests
-// =============================================================================
-
 #[test]
-fn test_temp_repo_graph_building() {
+fn test_repo_config_file_sets_trunk_and_prefix() {
     let repo = TempJjRepo::new();
-    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    repo.build_stack(&[("spike", "Try something"), ("me/feat-a", "Add A")]);
+    repo.write_file(
+        ".ryu.toml",
+        "default-branch = \"develop\"\nbranch-prefix = \"me/\"\n",
+    );
 
     let workspace = repo.workspace();
+    assert_eq!(workspace.default_branch().unwrap(), "develop");
+
     let graph = build_change_graph(&workspace).expect("build graph");
+    assert!(graph.bookmarks.contains_key("me/feat-a"));
+    assert!(!graph.bookmarks.contains_key("spike"));
+}
 
-    // Should have both bookmarks
-    assert!(graph.bookmarks.contains_key("feat-a"));
-    assert!(graph.bookmarks.contains_key("feat-b"));
+#[test]
+fn test_env_reports_config_keys_with_env_overrides() {
+    let repo = TempJjRepo::new();
+    let user_config = tempfile::tempdir().unwrap();
+    repo.write_file(
+        ".ryu.toml",
+        "reviewer-pool = [\"carol\", \"dave\"]\n\
+         reviewer-policy = \"least-loaded\"\n\
+         tracking-issue = true\n\
+         mirror-remotes = [\"backup\"]\n\
+         lock-timeout = 60\n",
+    );
+    let ryu_env = || {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .arg("env")
+            .env("XDG_CONFIG_HOME", user_config.path());
+        for var in [
+            "RYU_REVIEWERS",
+            "RYU_REVIEWER_POLICY",
+            "RYU_TRACKING_ISSUE",
+            "RYU_MIRROR_REMOTES",
+            "RYU_LOCK_TIMEOUT",
+            "RYU_READ_ONLY",
+        ] {
+            cmd.env_remove(var);
+        }
+        cmd
+    };
+
+    ryu_env()
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"reviewer-pool\s+carol, dave \(repo config\)").unwrap())
+        .stdout(
+            predicate::str::is_match(r"reviewer-policy\s+least-loaded \(repo config\)").unwrap(),
+        )
+        .stdout(predicate::str::is_match(r"tracking-issue\s+true \(repo config\)").unwrap())
+        .stdout(predicate::str::is_match(r"mirror-remotes\s+backup \(repo config\)").unwrap())
+        .stdout(predicate::str::is_match(r"read-only\s+false \(default\)").unwrap());
+
+    // The environment still wins over the files
+    ryu_env()
+        .env("RYU_LOCK_TIMEOUT", "5")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"lock-timeout\s+5 \(env\)").unwrap());
+}
 
-    // Should have one stack with two segments
-    assert_eq!(graph.stacks.len(), 1);
-    assert_eq!(graph.stacks[0].segments.len(), 2);
+#[test]
+fn test_env_attributes_each_source() {
+    let repo = TempJjRepo::new();
+    let _origin = repo.add_bare_remote("origin");
+    let _backup = repo.add_bare_remote("backup");
+    let user_config = tempfile::tempdir().unwrap();
+    let ryu_env = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("ryu").unwrap();
+        cmd.arg("--path")
+            .arg(repo.path())
+            .arg("env")
+            .args(args)
+            .env("XDG_CONFIG_HOME", user_config.path())
+            .env_remove("RYU_DRAFT");
+        cmd
+    };
+
+    ryu_env(&[])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"draft\s+false \(default\)").unwrap());
+
+    // The user file applies where the repo file says nothing
+    let user_dir = user_config.path().join("ryu");
+    std::fs::create_dir_all(&user_dir).unwrap();
+    std::fs::write(
+        user_dir.join("config.toml"),
+        "draft = true\nremote = \"backup\"\n",
+    )
+    .unwrap();
+    ryu_env(&[])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"draft\s+true \(user config\)").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^\s*remote\s+backup \(user config\)").unwrap());
+
+    // The repo file wins over the user file
+    repo.write_file(".ryu.toml", "draft = false\nremote = \"origin\"\n");
+    ryu_env(&[])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"draft\s+false \(repo config\)").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^\s*remote\s+origin \(repo config\)").unwrap());
+
+    // The environment wins over both files, and a flag over everything
+    ryu_env(&["--remote", "backup"])
+        .env("RYU_DRAFT", "true")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"draft\s+true \(env\)").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^\s*remote\s+backup \(flag\)").unwrap());
 }
 
 #[test]
-fn test_analyze_real_repo_stack() {
+fn test_bookmark_globs_filter_stacks() {
     let repo = TempJjRepo::new();
     repo.build_stack(&[
-        ("feat-a", "Add A"),
-        ("feat-b", "Add B"),
-        ("feat-c", "Add C"),
+        ("feat/a", "Add A"),
+        ("backup/a", "Backup"),
+        ("feat/b", "Add B"),
+        ("wip", "Try something"),
     ]);
+    repo.write_file(
+        ".ryu.toml",
+        "include-bookmarks = [\"feat/*\", \"backup/*\"]\nexclude-bookmarks = [\"backup/*\"]\n",
+    );
 
     let workspace = repo.workspace();
     let graph = build_change_graph(&workspace).expect("build graph");
+    let mut names: Vec<_> = graph.bookmarks.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["feat/a", "feat/b"]);
+
+    // The run's globs (e.g. --exclude-bookmarks) replace the file's
+    let run = RunContext::default().with_config(RyuConfig {
+        exclude_bookmarks: Some(vec!["feat/b".to_string()]),
+        ..RyuConfig::default()
+    });
+    let workspace = JjWorkspace::open_in(repo.path(), Arc::new(run)).expect("open");
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let mut names: Vec<_> = graph.bookmarks.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["backup/a", "feat/a"]);
 
-    // Analyze middle of stack
-    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    repo.write_file(".ryu.toml", "include-bookmarks = [\"[\"]\n");
+    assert!(build_change_graph(&repo.workspace()).is_err());
+}
 
-    assert_eq!(analysis.target_bookmark, "feat-b");
-    assert_eq!(analysis.segments.len(), 2);
-    assert_eq!(analysis.segments[0].bookmark.name, "feat-a");
-    assert_eq!(analysis.segments[1].bookmark.name, "feat-b");
+#[test]
+fn test_trunk_follows_jj_revset_aliases() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    repo.write_file(
+        ".jj/repo/config.toml",
+        "[revset-aliases]\n'trunk()' = 'base()'\n'base()' = 'feat-a'\n",
+    );
+
+    for workspace in [repo.workspace(), repo.cli_workspace()] {
+        let graph = build_change_graph(&workspace).expect("build graph");
+        let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+        let names: Vec<_> = analysis
+            .segments
+            .iter()
+            .map(|s| s.bookmark.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["feat-b"]);
+    }
+
+    // trunk-revset beats the alias
+    repo.write_file(".ryu.toml", "trunk-revset = \"root()\"\n");
+    for workspace in [repo.workspace(), repo.cli_workspace()] {
+        let graph = build_change_graph(&workspace).expect("build graph");
+        let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+        assert_eq!(analysis.segments.len(), 2);
+    }
+
+    // --trunk-revset beats the file
+    let run = RunContext::default().with_config(RyuConfig {
+        trunk_revset: Some("feat-a".to_string()),
+        ..RyuConfig::default()
+    });
+    let workspace = JjWorkspace::open_in(repo.path(), Arc::new(run)).expect("open");
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    assert_eq!(analysis.segments.len(), 1);
 }
 
 #[tokio::test]
-async fn test_full_submit_flow_new_stack() {
+async fn test_submit_flow_partial_existing_prs() {
     let repo = TempJjRepo::new();
-    repo.build_stack(&[("feat-a", "Add feature A"), ("feat-b", "Add feature B")]);
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
 
     let workspace = repo.workspace();
     let graph = build_change_graph(&workspace).expect("build graph");
     let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
 
-    // Mock returns None for all find_existing_pr calls (default behavior)
     let mock = MockPlatformService::with_config(github_config());
+    // First PR exists
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    // Second PR doesn't exist (default)
 
     let plan = create_submission_plan(&analysis, &mock, "origin", "main")
         .await
         .expect("create plan");
 
-    // Verify plan
-    assert_eq!(plan.count_creates(), 2);
-    assert_eq!(plan.count_pushes(), 2);
-    assert_eq!(plan.count_updates(), 0);
+    // Only one PR to create
+    assert_eq!(plan.count_creates(), 1);
 
-    // Find CreatePr steps and verify base branches
-    let creates: Vec<_> = plan
+    let create = plan
         .execution_steps
         .iter()
-        .filter_map(|s| match s {
+        .find_map(|s| match s {
             ExecutionStep::CreatePr(c) => Some(c),
             _ => None,
         })
-        .collect();
+        .expect("should have create step");
 
-    assert_eq!(creates[0].base_branch, "main");
-    assert_eq!(creates[1].base_branch, "feat-a");
+    assert_eq!(create.bookmark.name, "feat-b");
 
-    // Verify titles are not empty
-    assert!(!creates[0].title.is_empty());
-    assert!(!creates[1].title.is_empty());
+    // One existing PR
+    assert_eq!(plan.existing_prs.len(), 1);
+    assert!(plan.existing_prs.contains_key("feat-a"));
 }
 
 #[tokio::test]
-async fn test_submit_flow_partial_existing_prs() {
+async fn test_submit_flow_duplicate_prs_keep_oldest() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    // Newer duplicate listed first, as the platform might return it
+    mock.set_open_prs_response(
+        "feat-a",
+        vec![make_pr(7, "feat-a", "main"), make_pr(3, "feat-a", "main")],
+    );
+    mock.set_find_pr_response("feat-b", Some(make_pr(4, "feat-b", "feat-a")));
+
+    let plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+
+    // Oldest PR is canonical regardless of listing order
+    assert_eq!(plan.existing_prs["feat-a"].number, 3);
+    assert_eq!(plan.count_creates(), 0);
+
+    assert_eq!(plan.duplicate_prs.len(), 1);
+    let dup = &plan.duplicate_prs[0];
+    assert_eq!(dup.bookmark, "feat-a");
+    assert_eq!(dup.canonical.number, 3);
+    assert_eq!(dup.duplicate.number, 7);
+
+    // Planning never closes anything on its own
+    assert!(mock.get_close_pr_calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_stack_drift_reports_merged_parent() {
     let repo = TempJjRepo::new();
     repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
 
@@ -164,17 +2123,55 @@ async fn test_submit_flow_partial_existing_prs() {
     let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
 
     let mock = MockPlatformService::with_config(github_config());
-    // First PR exists
     mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
-    // Second PR doesn't exist (default)
+    // feat-b still targets a parent branch that is no longer in the stack
+    mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "old-parent")));
 
     let plan = create_submission_plan(&analysis, &mock, "origin", "main")
         .await
         .expect("create plan");
+    let drift = StackDrift::from_plan("feat-b", &plan);
+
+    assert_eq!(drift.leaf, "feat-b");
+    assert_eq!(drift.needs_push, vec!["feat-a", "feat-b"]);
+    assert!(drift.needs_pr.is_empty());
+    assert_eq!(drift.needs_retarget, vec!["feat-b"]);
+    assert_eq!(drift.merged_parents, vec!["old-parent"]);
+    assert!(!drift.is_in_sync());
+}
 
-    // Only one PR to create
-    assert_eq!(plan.count_creates(), 1);
+#[tokio::test]
+async fn test_submit_flow_respects_base_override() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    let mut analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    analysis
+        .base_overrides
+        .insert("feat-a".to_string(), "release/2.1".to_string());
+
+    let mock = MockPlatformService::with_config(github_config());
+    // Existing PR targets main and must be retargeted to the override
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+
+    let plan = create_submission_plan(&analysis, &mock, "origin", "main")
+        .await
+        .expect("create plan");
+
+    let update = plan
+        .execution_steps
+        .iter()
+        .find_map(|s| match s {
+            ExecutionStep::UpdateBase(u) => Some(u),
+            _ => None,
+        })
+        .expect("should retarget feat-a");
+    assert_eq!(update.bookmark.name, "feat-a");
+    assert_eq!(update.expected_base, "release/2.1");
 
+    // feat-b still stacks on feat-a
     let create = plan
         .execution_steps
         .iter()
@@ -182,13 +2179,8 @@ async fn test_submit_flow_partial_existing_prs() {
             ExecutionStep::CreatePr(c) => Some(c),
             _ => None,
         })
-        .expect("should have create step");
-
-    assert_eq!(create.bookmark.name, "feat-b");
-
-    // One existing PR
-    assert_eq!(plan.existing_prs.len(), 1);
-    assert!(plan.existing_prs.contains_key("feat-a"));
+        .expect("should create feat-b");
+    assert_eq!(create.base_branch, "feat-a");
 }
 
 #[tokio::test]
@@ -281,6 +2273,55 @@ fn test_three_level_deep_stack() {
     assert_eq!(graph.stacks[0].segments[2].bookmarks[0].name, "feat-c");
 }
 
+#[test]
+fn test_merge_commit_bookmark_reported_as_excluded() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+    repo.new_change("root()", "Side change");
+    repo.create_merge(&["feat-a", "@"], "Merge side");
+    repo.create_bookmark("feat-merge");
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    assert_eq!(graph.excluded_bookmarks.len(), 1);
+    let excluded = &graph.excluded_bookmarks[0];
+    assert_eq!(excluded.name, "feat-merge");
+    assert!(matches!(
+        excluded.reason,
+        ExclusionReason::MergeCommit { .. }
+    ));
+
+    // The linear bookmark is still stacked
+    assert_eq!(graph.stacks.len(), 1);
+    assert_eq!(graph.stacks[0].segments[0].bookmarks[0].name, "feat-a");
+}
+
+#[test]
+fn test_merge_commit_followed_by_first_parent() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A")]);
+    repo.new_change("root()", "Side change");
+    repo.create_bookmark("side");
+    repo.create_merge(&["feat-a", "side"], "Merge side");
+    repo.create_bookmark("feat-merge");
+    repo.commit("Add B");
+    repo.create_bookmark("feat-b");
+    repo.write_file(".ryu.toml", "merge-commits = \"first-parent\"\n");
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+
+    assert!(graph.excluded_bookmarks.is_empty());
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    let names: Vec<&str> = analysis
+        .segments
+        .iter()
+        .map(|s| s.bookmark.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["feat-a", "feat-merge", "feat-b"]);
+}
+
 #[tokio::test]
 async fn test_plan_verifies_pr_queries_for_stack() {
     let repo = TempJjRepo::new();
@@ -336,3 +2377,303 @@ async fn test_plan_pr_numbers_increment() {
     assert_eq!(creates[0].bookmark.name, "feat-a");
     assert_eq!(creates[1].bookmark.name, "feat-b");
 }
+
+// =============================================================================
+// jj CLI Backend Tests
+// =============================================================================
+
+#[test]
+fn test_cli_backend_matches_lib_bookmarks() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let lib = repo.workspace();
+    let cli = repo.cli_workspace();
+    assert_eq!(lib.backend(), JjBackend::Lib);
+    assert_eq!(cli.backend(), JjBackend::Cli);
+
+    let mut lib_bookmarks = lib.local_bookmarks().expect("lib bookmarks");
+    let mut cli_bookmarks = cli.local_bookmarks().expect("cli bookmarks");
+    lib_bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    cli_bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(cli_bookmarks, lib_bookmarks);
+
+    assert_eq!(
+        cli.get_local_bookmark("feat-b").expect("get bookmark"),
+        lib.get_local_bookmark("feat-b").expect("get bookmark")
+    );
+    assert!(cli.get_local_bookmark("missing").unwrap().is_none());
+}
+
+#[test]
+fn test_cli_backend_bookmark_tracking_state() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[
+        ("synced", "Add A"),
+        ("ahead", "Add B"),
+        ("local-only", "Add C"),
+    ]);
+    let _remote = repo.add_bare_remote("origin");
+    let mut ws = repo.workspace();
+    ws.git_push("synced", "origin").expect("push");
+    ws.git_push("ahead", "origin").expect("push");
+    repo.move_bookmark("ahead", "local-only");
+    repo.conflict_bookmark("conflicted", "synced", "local-only");
+
+    let mut cli_bookmarks = repo
+        .cli_workspace()
+        .local_bookmarks()
+        .expect("cli bookmarks");
+    cli_bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    let state: Vec<_> = cli_bookmarks
+        .iter()
+        .map(|b| (b.name.as_str(), b.has_remote, b.is_synced))
+        .collect();
+    // The conflicted bookmark has no single commit, so it is left out
+    assert_eq!(
+        state,
+        vec![
+            ("ahead", true, false),
+            ("local-only", false, false),
+            ("synced", true, true),
+        ]
+    );
+
+    let mut lib_bookmarks = repo.workspace().local_bookmarks().expect("lib bookmarks");
+    lib_bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(cli_bookmarks, lib_bookmarks);
+}
+
+#[test]
+fn test_cli_backend_matches_lib_revset() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add \"B\"")]);
+
+    let lib = repo.workspace();
+    let cli = repo.cli_workspace();
+    let revset = "::feat-b & ~root()";
+
+    let lib_entries = lib.resolve_revset(revset).expect("lib revset");
+    let cli_entries = cli.resolve_revset(revset).expect("cli revset");
+    assert_eq!(cli_entries.len(), lib_entries.len());
+    for (cli_entry, lib_entry) in cli_entries.iter().zip(&lib_entries) {
+        assert_eq!(cli_entry.commit_id, lib_entry.commit_id);
+        assert_eq!(cli_entry.change_id, lib_entry.change_id);
+        assert_eq!(cli_entry.parents, lib_entry.parents);
+        assert_eq!(cli_entry.local_bookmarks, lib_entry.local_bookmarks);
+        assert_eq!(
+            cli_entry.description_first_line,
+            lib_entry.description_first_line
+        );
+        assert_eq!(cli_entry.description, lib_entry.description);
+        assert_eq!(cli_entry.author_email, lib_entry.author_email);
+        assert_eq!(cli_entry.authored_at, lib_entry.authored_at);
+    }
+}
+
+#[test]
+fn test_fetch_all_fetches_each_remote_in_one_operation() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    let origin = repo.add_bare_remote("origin");
+    let upstream = repo.add_bare_remote("upstream");
+    let mut ws = repo.workspace();
+    ws.git_push("feat-a", "origin").expect("push");
+    ws.git_push("feat-b", "upstream").expect("push");
+    let commit_b = ws.get_local_bookmark("feat-b").unwrap().unwrap().commit_id;
+
+    // Branches pushed to the remotes from elsewhere
+    let push_elsewhere = |remote: &std::path::Path, branch: &str| {
+        let output = std::process::Command::new("git")
+            .args(["update-ref", &format!("refs/heads/{branch}"), &commit_b])
+            .current_dir(remote)
+            .output()
+            .expect("git");
+        assert!(output.status.success());
+    };
+    let fetched = |branch: &str, remote: &str| {
+        repo.workspace()
+            .get_remote_bookmark(branch, remote)
+            .unwrap()
+            .is_some()
+    };
+    let remotes = ["origin".to_string(), "upstream".to_string()];
+
+    for (backend, mut ws) in [("lib", repo.workspace()), ("cli", repo.cli_workspace())] {
+        let (from_origin, from_upstream) = (format!("{backend}-o"), format!("{backend}-u"));
+        push_elsewhere(origin.path(), &from_origin);
+        push_elsewhere(upstream.path(), &from_upstream);
+        let before = ws.operation_id().unwrap();
+
+        ws.git_fetch_all(&remotes).expect("fetch");
+        assert!(fetched(&from_origin, "origin"), "{backend}");
+        assert!(fetched(&from_upstream, "upstream"), "{backend}");
+
+        // Both remotes land in a single operation on top of the old head
+        let output = std::process::Command::new("jj")
+            .args(["op", "log", "--ignore-working-copy", "--no-graph"])
+            .args(["--limit", "2", "-T", "id ++ \"\\n\""])
+            .current_dir(repo.path())
+            .output()
+            .expect("jj");
+        let ops = String::from_utf8_lossy(&output.stdout).to_string();
+        assert_eq!(ops.lines().nth(1), Some(before.as_str()), "{backend}");
+    }
+}
+
+#[test]
+fn test_ahead_behind_between_bookmarks() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+    repo.new_change("feat-a", "Add C");
+    repo.create_bookmark("feat-c");
+
+    let ws = repo.workspace();
+    let commit = |name: &str| ws.get_local_bookmark(name).unwrap().unwrap().commit_id;
+
+    // feat-b descends from feat-a: moving feat-a there is a fast-forward
+    let forward = ahead_behind(&ws, &commit("feat-b"), &commit("feat-a")).unwrap();
+    assert!(forward.ahead > 0);
+    assert_eq!(forward.behind, 0);
+    assert!(forward.is_fast_forward());
+
+    // feat-c forked off feat-a: replacing feat-b with it drops commits
+    let diverged = ahead_behind(&ws, &commit("feat-c"), &commit("feat-b")).unwrap();
+    assert_eq!(diverged.ahead, 1);
+    assert!(diverged.behind > 0);
+    assert!(!diverged.is_fast_forward());
+
+    let same = ahead_behind(&ws, &commit("feat-a"), &commit("feat-a")).unwrap();
+    assert_eq!((same.ahead, same.behind), (0, 0));
+}
+
+#[test]
+fn test_changed_paths_match_between_backends() {
+    let repo = TempJjRepo::new();
+    repo.write_file("src/auth/login.rs", "fn login() {}\n");
+    repo.write_file("README.md", "# Test\n");
+    repo.commit("Add login");
+
+    let lib = repo.workspace();
+    let cli = repo.cli_workspace();
+    let commit_id = lib.resolve_revset("@-").unwrap().remove(0).commit_id;
+
+    let mut lib_paths = lib.changed_paths(&commit_id).expect("lib paths");
+    let mut cli_paths = cli.changed_paths(&commit_id).expect("cli paths");
+    lib_paths.sort();
+    cli_paths.sort();
+    assert_eq!(lib_paths, vec!["README.md", "src/auth/login.rs"]);
+    assert_eq!(cli_paths, lib_paths);
+}
+
+#[test]
+fn test_restore_operation_moves_bookmarks_back() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let mut ws = repo.workspace();
+    let before = ws.snapshot_operation_id().expect("operation");
+    let feat_b = ws.get_local_bookmark("feat-b").unwrap().unwrap().commit_id;
+    repo.move_bookmark("feat-b", "feat-a");
+    assert_ne!(ws.snapshot_operation_id().expect("operation"), before);
+
+    ws.restore_operation(&before).expect("restore");
+    assert_eq!(
+        ws.get_local_bookmark("feat-b").unwrap().unwrap().commit_id,
+        feat_b
+    );
+}
+
+#[test]
+fn test_cli_backend_builds_same_graph() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A"), ("feat-b", "Add B")]);
+
+    let graph = build_change_graph(&repo.cli_workspace()).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+    let names: Vec<_> = analysis
+        .segments
+        .iter()
+        .map(|s| s.bookmark.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["feat-a", "feat-b"]);
+    assert_eq!(
+        repo.cli_workspace()
+            .default_branch()
+            .expect("default branch"),
+        repo.workspace().default_branch().expect("default branch")
+    );
+}
+
+#[tokio::test]
+async fn test_plan_body_updates_renders_commit_messages() {
+    let repo = TempJjRepo::new();
+    repo.build_stack(&[("feat-a", "Add A\n\nExplain why A."), ("feat-b", "Add B")]);
+
+    let graph = build_change_graph(&repo.workspace()).expect("build graph");
+    let analysis = analyze_submission(&graph, "feat-b").expect("analyze");
+
+    let mock = MockPlatformService::with_config(github_config());
+    mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+    mock.set_pr_body(1, "Stale\r\nbody");
+
+    let updates = plan_body_updates(&mock, &analysis.segments)
+        .await
+        .expect("plan body updates");
+
+    // feat-b has no PR yet
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].bookmark, "feat-a");
+    assert_eq!(updates[0].current, "Stale\nbody");
+    assert!(updates[0].rendered.contains("Explain why A."));
+    assert!(updates[0].is_changed());
+}
+
+#[test]
+fn test_new_stack_scaffolds_bookmarked_changes() {
+    let repo = TempJjRepo::new();
+    let template = StackTemplate {
+        segments: vec![
+            TemplateSegment {
+                bookmark: "schema".to_string(),
+                description: Some("Add {name} schema".to_string()),
+            },
+            TemplateSegment {
+                bookmark: "docs".to_string(),
+                description: None,
+            },
+        ],
+    };
+    let changes = template.scaffold("limits", None).expect("scaffold");
+
+    let mut workspace = repo.workspace();
+    let trunk = workspace.resolve_revset("trunk()").expect("resolve trunk")[0]
+        .commit_id
+        .clone();
+    create_stack(&mut workspace, &changes, &trunk).expect("create stack");
+
+    let workspace = repo.workspace();
+    let graph = build_change_graph(&workspace).expect("build graph");
+    assert_eq!(graph.stacks.len(), 1);
+    let segments: Vec<(&str, &str)> = graph.stacks[0]
+        .segments
+        .iter()
+        .map(|seg| {
+            (
+                seg.bookmarks[0].name.as_str(),
+                seg.changes[0].description_first_line.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            ("limits/schema", "Add limits schema"),
+            ("limits/docs", "docs")
+        ]
+    );
+
+    // Bookmarks are taken now
+    let mut workspace = repo.workspace();
+    assert!(create_stack(&mut workspace, &changes, &trunk).is_err());
+}
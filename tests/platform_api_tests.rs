@@ -0,0 +1,1443 @@
+//! Platform services against mock HTTP servers
+//!
+//! Each module drives one service's public API at a `mockito` server and
+//! checks the requests it sends and how it reads the answers. Modules are
+//! gated on the platform's cargo feature.
+
+#[cfg(feature = "bitbucket")]
+mod bitbucket_test {
+    use jj_ryu_core::error::Error;
+    use jj_ryu_core::platform::{BitbucketService, PlatformService};
+    use jj_ryu_core::types::{PrReview, PrState, ReviewState};
+    use mockito::{Matcher, ServerGuard};
+    use serde_json::{Value, json};
+
+    /// `Authorization` header for user "alice" with app password "secret"
+    const ALICE_AUTH: &str = "Basic YWxpY2U6c2VjcmV0";
+
+    /// The repository's pull request endpoint
+    const PRS: &str = "/2.0/repositories/ws/repo/pullrequests";
+
+    fn service(server: &ServerGuard) -> BitbucketService {
+        BitbucketService::new(
+            "alice".to_string(),
+            "secret".to_string(),
+            "ws".to_string(),
+            "repo".to_string(),
+        )
+        .unwrap()
+        .with_api_root(format!("{}/2.0", server.url()))
+    }
+
+    fn pr_json(id: u64, head: &str, base: &str, state: &str) -> Value {
+        json!({
+            "id": id,
+            "title": format!("Add {head}"),
+            "description": format!("{head} body"),
+            "state": state,
+            "source": { "branch": { "name": head } },
+            "destination": { "branch": { "name": base } },
+            "links": { "html": { "href": format!("https://bitbucket.org/ws/repo/pull-requests/{id}") } },
+            "author": { "nickname": "alice", "uuid": "{alice}" },
+        })
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> Matcher {
+        Matcher::AllOf(
+            pairs
+                .iter()
+                .map(|(key, value)| Matcher::UrlEncoded((*key).to_string(), (*value).to_string()))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_find_existing_pr_queries_open_prs_of_branch() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", PRS)
+            .match_header("authorization", ALICE_AUTH)
+            .match_query(query(&[
+                ("state", "OPEN"),
+                ("q", "source.branch.name = \"feat-a\""),
+                ("pagelen", "50"),
+            ]))
+            .with_body(
+                json!({ "values": [
+                    pr_json(9, "feat-a", "main", "OPEN"),
+                    pr_json(7, "feat-a", "main", "OPEN"),
+                ]})
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let pr = service(&server)
+            .find_existing_pr("feat-a")
+            .await
+            .unwrap()
+            .expect("open PR");
+
+        mock.assert_async().await;
+        // The oldest of duplicates wins
+        assert_eq!(pr.number, 7);
+        assert_eq!(pr.head_ref, "feat-a");
+        assert_eq!(pr.base_ref, "main");
+        assert_eq!(pr.html_url, "https://bitbucket.org/ws/repo/pull-requests/7");
+    }
+
+    #[tokio::test]
+    async fn test_lists_follow_next_links() {
+        let mut server = mockito::Server::new_async().await;
+        let next = format!("{}{PRS}?page=2&pagelen=50", server.url());
+        let first = server
+            .mock("GET", PRS)
+            .match_query(query(&[("state", "OPEN"), ("pagelen", "50")]))
+            .with_body(
+                json!({ "values": [pr_json(1, "feat-a", "main", "OPEN")], "next": next })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", PRS)
+            .match_header("authorization", ALICE_AUTH)
+            .match_query(query(&[("page", "2")]))
+            .with_body(json!({ "values": [pr_json(2, "feat-b", "feat-a", "OPEN")] }).to_string())
+            .create_async()
+            .await;
+
+        let prs = service(&server)
+            .list_open_prs_by_author("alice")
+            .await
+            .unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        let numbers: Vec<u64> = prs.iter().map(|pr| pr.number).collect();
+        assert_eq!(numbers, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_find_latest_pr_maps_states() {
+        for (state, draft, expected) in [
+            ("OPEN", false, PrState::Open),
+            ("OPEN", true, PrState::Draft),
+            ("MERGED", false, PrState::Merged),
+            ("DECLINED", false, PrState::Closed),
+            ("SUPERSEDED", false, PrState::Closed),
+        ] {
+            let mut server = mockito::Server::new_async().await;
+            let mut pr = pr_json(3, "feat-a", "main", state);
+            pr["draft"] = json!(draft);
+            let mock = server
+                .mock("GET", PRS)
+                .match_query(query(&[
+                    ("state", "MERGED"),
+                    ("state", "DECLINED"),
+                    ("q", "source.branch.name = \"feat-a\""),
+                    ("sort", "-created_on"),
+                    ("pagelen", "1"),
+                ]))
+                .with_body(json!({ "values": [pr] }).to_string())
+                .create_async()
+                .await;
+
+            let (pr, pr_state) = service(&server)
+                .find_latest_pr("feat-a")
+                .await
+                .unwrap()
+                .expect("latest PR");
+
+            mock.assert_async().await;
+            assert_eq!(pr.number, 3, "{state}");
+            assert_eq!(pr_state, expected, "{state} draft={draft}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_pr_posts_branches() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", PRS)
+            .match_header("authorization", ALICE_AUTH)
+            .match_body(Matcher::Json(json!({
+                "title": "Add B",
+                "description": "Body",
+                "source": { "branch": { "name": "feat-b" } },
+                "destination": { "branch": { "name": "feat-a" } },
+                "draft": true,
+            })))
+            .with_status(201)
+            .with_body(pr_json(4, "feat-b", "feat-a", "OPEN").to_string())
+            .create_async()
+            .await;
+
+        let pr = service(&server)
+            .create_pr_with_options("feat-b", "feat-a", "Add B", "Body", true)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pr.number, 4);
+        assert_eq!(pr.base_ref, "feat-a");
+    }
+
+    #[tokio::test]
+    async fn test_update_pr_base_resends_title() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", format!("{PRS}/4").as_str())
+            .with_body(pr_json(4, "feat-b", "main", "OPEN").to_string())
+            .create_async()
+            .await;
+        // Bitbucket rejects updates without a title
+        let put = server
+            .mock("PUT", format!("{PRS}/4").as_str())
+            .match_header("authorization", ALICE_AUTH)
+            .match_body(Matcher::Json(json!({
+                "title": "Add feat-b",
+                "destination": { "branch": { "name": "feat-a" } },
+            })))
+            .with_body(pr_json(4, "feat-b", "feat-a", "OPEN").to_string())
+            .create_async()
+            .await;
+
+        let pr = service(&server).update_pr_base(4, "feat-a").await.unwrap();
+
+        get.assert_async().await;
+        put.assert_async().await;
+        assert_eq!(pr.base_ref, "feat-a");
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_reads_participants() {
+        let mut server = mockito::Server::new_async().await;
+        let mut pr = pr_json(5, "feat-a", "main", "OPEN");
+        pr["participants"] = json!([
+            { "approved": true, "user": { "nickname": "bob" } },
+            { "approved": false, "state": "changes_requested", "user": { "nickname": "carol" } },
+            { "approved": false, "user": { "nickname": "dave" } },
+        ]);
+        let _mock = server
+            .mock("GET", format!("{PRS}/5").as_str())
+            .with_body(pr.to_string())
+            .create_async()
+            .await;
+
+        let reviews = service(&server).list_reviews(5).await.unwrap();
+
+        assert_eq!(
+            reviews,
+            vec![
+                PrReview {
+                    reviewer: "bob".to_string(),
+                    state: ReviewState::Approved,
+                },
+                PrReview {
+                    reviewer: "carol".to_string(),
+                    state: ReviewState::ChangesRequested,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_current_user_reads_nickname() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/2.0/user")
+            .match_header("authorization", ALICE_AUTH)
+            .with_body(json!({ "nickname": "alice", "uuid": "{alice}" }).to_string())
+            .create_async()
+            .await;
+
+        assert_eq!(service(&server).current_user().await.unwrap(), "alice");
+    }
+
+    #[tokio::test]
+    async fn test_http_errors_are_bitbucket_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", format!("{PRS}/6").as_str())
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let err = service(&server).get_pr_body(6).await.unwrap_err();
+
+        assert!(
+            matches!(&err, Error::BitbucketApi(msg) if msg.contains("401")),
+            "{err}"
+        );
+    }
+}
+
+#[cfg(feature = "gitea")]
+mod gitea_test {
+    use jj_ryu_core::error::Error;
+    use jj_ryu_core::platform::{GiteaService, PlatformService};
+    use jj_ryu_core::types::{PrReview, PrState, ReviewState};
+    use mockito::{Matcher, ServerGuard};
+    use serde_json::{Value, json};
+
+    /// `Authorization` header for token "secret"
+    const AUTH: &str = "Bearer secret";
+
+    /// The repository's pull request endpoint
+    const PULLS: &str = "/api/v1/repos/o/r/pulls";
+
+    fn service(server: &ServerGuard) -> GiteaService {
+        GiteaService::new(
+            "secret".to_string(),
+            "o".to_string(),
+            "r".to_string(),
+            Some(server.url()),
+        )
+        .unwrap()
+    }
+
+    fn pr_json(number: u64, head: &str, base: &str, title: &str) -> Value {
+        json!({
+            "number": number,
+            "title": title,
+            "body": format!("{head} body"),
+            "state": "open",
+            "html_url": format!("https://codeberg.org/o/r/pulls/{number}"),
+            "head": { "ref": head, "sha": format!("{head}-sha") },
+            "base": { "ref": base },
+            "user": { "login": "alice" },
+        })
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> Matcher {
+        Matcher::AllOf(
+            pairs
+                .iter()
+                .map(|(key, value)| Matcher::UrlEncoded((*key).to_string(), (*value).to_string()))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_find_existing_pr_filters_open_prs_by_head() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", PULLS)
+            .match_header("authorization", AUTH)
+            .match_query(query(&[("state", "open"), ("page", "1"), ("limit", "50")]))
+            .with_body(
+                json!([
+                    pr_json(9, "feat-a", "main", "Add A again"),
+                    pr_json(8, "feat-b", "feat-a", "Add B"),
+                    pr_json(7, "feat-a", "main", "Add A"),
+                ])
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let pr = service(&server)
+            .find_existing_pr("feat-a")
+            .await
+            .unwrap()
+            .expect("open PR");
+
+        mock.assert_async().await;
+        // The oldest of duplicates wins
+        assert_eq!(pr.number, 7);
+        assert_eq!(pr.head_ref, "feat-a");
+        assert_eq!(pr.base_ref, "main");
+        assert_eq!(pr.html_url, "https://codeberg.org/o/r/pulls/7");
+        assert!(!pr.is_draft);
+    }
+
+    #[tokio::test]
+    async fn test_lists_fetch_pages_until_a_short_one() {
+        let mut server = mockito::Server::new_async().await;
+        let full: Vec<Value> = (1..=50)
+            .map(|n| pr_json(n, &format!("feat-{n}"), "main", "Add"))
+            .collect();
+        let first = server
+            .mock("GET", PULLS)
+            .match_query(query(&[("state", "open"), ("page", "1")]))
+            .with_body(Value::Array(full).to_string())
+            .create_async()
+            .await;
+        let mut other = pr_json(52, "feat-52", "main", "Add");
+        other["user"] = json!({ "login": "bob" });
+        let second = server
+            .mock("GET", PULLS)
+            .match_query(query(&[("state", "open"), ("page", "2")]))
+            .with_body(json!([pr_json(51, "feat-51", "main", "Add"), other]).to_string())
+            .create_async()
+            .await;
+
+        let prs = service(&server)
+            .list_open_prs_by_author("Alice")
+            .await
+            .unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(prs.len(), 51);
+        assert_eq!(prs.last().map(|pr| pr.number), Some(51));
+    }
+
+    #[tokio::test]
+    async fn test_find_latest_pr_maps_states() {
+        for (state, merged, title, expected) in [
+            ("open", false, "Add A", PrState::Open),
+            ("open", false, "WIP: Add A", PrState::Draft),
+            ("open", false, "[WIP] Add A", PrState::Draft),
+            ("closed", true, "Add A", PrState::Merged),
+            ("closed", false, "Add A", PrState::Closed),
+        ] {
+            let mut server = mockito::Server::new_async().await;
+            let mut pr = pr_json(3, "feat-a", "main", title);
+            pr["state"] = json!(state);
+            pr["merged"] = json!(merged);
+            let mock = server
+                .mock("GET", PULLS)
+                .match_query(query(&[("state", "all")]))
+                .with_body(json!([pr_json(2, "feat-a", "main", "Add A"), pr]).to_string())
+                .create_async()
+                .await;
+
+            let (pr, pr_state) = service(&server)
+                .find_latest_pr("feat-a")
+                .await
+                .unwrap()
+                .expect("latest PR");
+
+            mock.assert_async().await;
+            assert_eq!(pr.number, 3, "{title}");
+            assert_eq!(pr_state, expected, "{state} {title}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_draft_pr_gets_wip_title() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", PULLS)
+            .match_header("authorization", AUTH)
+            .match_body(Matcher::Json(json!({
+                "head": "feat-b",
+                "base": "feat-a",
+                "title": "WIP: Add B",
+                "body": "Body",
+            })))
+            .with_status(201)
+            .with_body(pr_json(4, "feat-b", "feat-a", "WIP: Add B").to_string())
+            .create_async()
+            .await;
+
+        let pr = service(&server)
+            .create_pr_with_options("feat-b", "feat-a", "Add B", "Body", true)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pr.number, 4);
+        assert!(pr.is_draft);
+    }
+
+    #[tokio::test]
+    async fn test_publish_pr_strips_wip_prefix() {
+        for title in ["WIP: Add A", "[wip]Add A"] {
+            let mut server = mockito::Server::new_async().await;
+            let get = server
+                .mock("GET", format!("{PULLS}/4").as_str())
+                .with_body(pr_json(4, "feat-a", "main", title).to_string())
+                .create_async()
+                .await;
+            let patch = server
+                .mock("PATCH", format!("{PULLS}/4").as_str())
+                .match_header("authorization", AUTH)
+                .match_body(Matcher::Json(json!({ "title": "Add A" })))
+                .with_body(pr_json(4, "feat-a", "main", "Add A").to_string())
+                .create_async()
+                .await;
+
+            let pr = service(&server).publish_pr(4).await.unwrap();
+
+            get.assert_async().await;
+            patch.assert_async().await;
+            assert!(!pr.is_draft, "{title}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_pr_base_and_body() {
+        let mut server = mockito::Server::new_async().await;
+        let base = server
+            .mock("PATCH", format!("{PULLS}/4").as_str())
+            .match_body(Matcher::Json(json!({ "base": "feat-a" })))
+            .with_body(pr_json(4, "feat-b", "feat-a", "Add B").to_string())
+            .create_async()
+            .await;
+        let body = server
+            .mock("PATCH", format!("{PULLS}/4").as_str())
+            .match_body(Matcher::Json(json!({ "body": "Stack: #3, #4" })))
+            .with_body(pr_json(4, "feat-b", "feat-a", "Add B").to_string())
+            .create_async()
+            .await;
+
+        let service = service(&server);
+        let pr = service.update_pr_base(4, "feat-a").await.unwrap();
+        service.update_pr_body(4, "Stack: #3, #4").await.unwrap();
+
+        base.assert_async().await;
+        body.assert_async().await;
+        assert_eq!(pr.base_ref, "feat-a");
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_keeps_latest_verdicts() {
+        let mut server = mockito::Server::new_async().await;
+        let review = |login: &str, state: &str, dismissed: bool| json!({ "state": state, "dismissed": dismissed, "user": { "login": login } });
+        let mut stale = review("dave", "APPROVED", false);
+        stale["stale"] = json!(true);
+        let _mock = server
+            .mock("GET", format!("{PULLS}/5/reviews").as_str())
+            .match_query(query(&[("page", "1"), ("limit", "50")]))
+            .with_body(
+                json!([
+                    review("alice", "APPROVED", false),
+                    review("bob", "REQUEST_CHANGES", false),
+                    review("carol", "APPROVED", true),
+                    stale,
+                    review("alice", "COMMENT", false),
+                    review("bob", "APPROVED", false),
+                ])
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let reviews = service(&server).list_reviews(5).await.unwrap();
+
+        assert_eq!(
+            reviews,
+            vec![
+                PrReview {
+                    reviewer: "alice".to_string(),
+                    state: ReviewState::Approved,
+                },
+                PrReview {
+                    reviewer: "bob".to_string(),
+                    state: ReviewState::Approved,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pr_comments_live_on_the_issue() {
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/api/v1/repos/o/r/issues/5/comments")
+            .with_body(json!([{ "id": 11, "body": "Stack" }, { "id": 12 }]).to_string())
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/api/v1/repos/o/r/issues/5/comments")
+            .match_header("authorization", AUTH)
+            .match_body(Matcher::Json(json!({ "body": "New" })))
+            .with_status(201)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/api/v1/repos/o/r/issues/comments/11")
+            .match_body(Matcher::Json(json!({ "body": "Stack v2" })))
+            .create_async()
+            .await;
+
+        let service = service(&server);
+        let comments = service.list_pr_comments(5).await.unwrap();
+        service.create_pr_comment(5, "New").await.unwrap();
+        service.update_pr_comment(5, 11, "Stack v2").await.unwrap();
+
+        list.assert_async().await;
+        create.assert_async().await;
+        update.assert_async().await;
+        let bodies: Vec<(u64, &str)> = comments.iter().map(|c| (c.id, c.body.as_str())).collect();
+        assert_eq!(bodies, vec![(11, "Stack"), (12, "")]);
+    }
+
+    #[tokio::test]
+    async fn test_host_with_trailing_slash() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/user")
+            .match_header("authorization", AUTH)
+            .with_body(json!({ "login": "alice" }).to_string())
+            .create_async()
+            .await;
+
+        let service = GiteaService::new(
+            "secret".to_string(),
+            "o".to_string(),
+            "r".to_string(),
+            Some(format!("{}/", server.url())),
+        )
+        .unwrap();
+
+        assert_eq!(service.current_user().await.unwrap(), "alice");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_http_errors_are_gitea_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", format!("{PULLS}/6").as_str())
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let err = service(&server).get_pr_body(6).await.unwrap_err();
+
+        assert!(
+            matches!(&err, Error::GiteaApi(msg) if msg.contains("401")),
+            "{err}"
+        );
+    }
+}
+
+#[cfg(feature = "github")]
+mod github_test {
+    use jj_ryu_core::error::Error;
+    use jj_ryu_core::platform::{GitHubService, PlatformService};
+    use jj_ryu_core::types::PullRequest;
+    use mockito::{Matcher, ServerGuard};
+    use serde_json::{Value, json};
+
+    /// The comments endpoint of PR 5
+    const COMMENTS: &str = "/repos/owner/repo/issues/5/comments";
+
+    fn service(server: &ServerGuard) -> GitHubService {
+        GitHubService::with_api_root(
+            "token",
+            "owner".to_string(),
+            "repo".to_string(),
+            &server.url(),
+        )
+        .unwrap()
+    }
+
+    fn pr(number: u64, node_id: Option<&str>) -> PullRequest {
+        PullRequest {
+            number,
+            html_url: format!("https://github.com/owner/repo/pull/{number}"),
+            base_ref: "main".to_string(),
+            head_ref: "feat-a".to_string(),
+            title: "Add A".to_string(),
+            node_id: node_id.map(ToString::to_string),
+            is_draft: false,
+            updated_at: None,
+        }
+    }
+
+    /// An issue comment as the REST API returns it
+    fn comment_json(id: u64) -> Value {
+        let user = "https://api.github.com/users/alice";
+        json!({
+            "id": id,
+            "node_id": format!("IC_{id}"),
+            "url": format!("https://api.github.com/repos/owner/repo/issues/comments/{id}"),
+            "html_url": format!("https://github.com/owner/repo/pull/5#issuecomment-{id}"),
+            "body": format!("comment {id}"),
+            "author_association": "OWNER",
+            "created_at": "2024-01-01T00:00:00Z",
+            "user": {
+                "login": "alice",
+                "id": 1,
+                "node_id": "U_1",
+                "avatar_url": "https://avatars.githubusercontent.com/u/1",
+                "gravatar_id": "",
+                "url": user,
+                "html_url": "https://github.com/alice",
+                "followers_url": format!("{user}/followers"),
+                "following_url": format!("{user}/following"),
+                "gists_url": format!("{user}/gists"),
+                "starred_url": format!("{user}/starred"),
+                "subscriptions_url": format!("{user}/subscriptions"),
+                "organizations_url": format!("{user}/orgs"),
+                "repos_url": format!("{user}/repos"),
+                "events_url": format!("{user}/events"),
+                "received_events_url": format!("{user}/received_events"),
+                "type": "User",
+                "site_admin": false
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_pr_comments_reads_every_page() {
+        let mut server = mockito::Server::new_async().await;
+        let first: Vec<_> = (1..=100).map(comment_json).collect();
+        let second: Vec<_> = (101..=130).map(comment_json).collect();
+        let next = format!("{}{COMMENTS}?per_page=100&page=2", server.url());
+        let first = server
+            .mock("GET", COMMENTS)
+            .match_query(Matcher::Exact("per_page=100".to_string()))
+            .with_header("link", &format!("<{next}>; rel=\"next\""))
+            .with_body(json!(first).to_string())
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", COMMENTS)
+            .match_query(Matcher::UrlEncoded("page".to_string(), "2".to_string()))
+            .with_body(json!(second).to_string())
+            .create_async()
+            .await;
+
+        let comments = service(&server).list_pr_comments(5).await.unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(comments.len(), 130);
+        assert_eq!(comments[0].id, 1);
+        assert_eq!(comments[129].id, 130);
+        assert_eq!(comments[129].body, "comment 130");
+        assert_eq!(comments[129].node_id.as_deref(), Some("IC_130"));
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_to_project_adds_item_to_resolved_project() {
+        let mut server = mockito::Server::new_async().await;
+        let lookup = server
+            .mock("POST", "/graphql")
+            .match_body(Matcher::PartialJson(
+                json!({ "variables": { "login": "acme", "number": 7 } }),
+            ))
+            .with_body(
+                json!({ "data": { "repositoryOwner": { "projectV2": { "id": "PVT_1" } } } })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/graphql")
+            .match_body(Matcher::PartialJson(
+                json!({ "variables": { "projectId": "PVT_1", "contentId": "PR_node" } }),
+            ))
+            .with_body(
+                json!({ "data": { "addProjectV2ItemById": { "item": { "id": "PVTI_1" } } } })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        service(&server)
+            .add_pr_to_project(&pr(3, Some("PR_node")), "acme/7")
+            .await
+            .unwrap();
+
+        lookup.assert_async().await;
+        add.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_to_project_reports_missing_project() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/graphql")
+            .with_body(
+                json!({
+                    "data": { "repositoryOwner": { "projectV2": null } },
+                    "errors": [{ "message": "Could not resolve to a ProjectV2 with the number 9." }]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let err = service(&server)
+            .add_pr_to_project(&pr(3, Some("PR_node")), "acme/9")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Could not resolve"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_to_project_rejects_bad_input_before_calling_github() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let service = service(&server);
+
+        for bad in [
+            "acme",
+            "acme/",
+            "/7",
+            "acme/seven",
+            "acme/-1",
+            "acme/7/8",
+            "",
+        ] {
+            let err = service
+                .add_pr_to_project(&pr(3, Some("PR_node")), bad)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(&err, Error::InvalidArgument(m) if m.contains("owner/number")),
+                "{bad}: {err}"
+            );
+        }
+        // GraphQL needs the PR's node ID
+        assert!(
+            service
+                .add_pr_to_project(&pr(3, None), "acme/7")
+                .await
+                .is_err()
+        );
+
+        mock.assert_async().await;
+    }
+}
+
+#[cfg(feature = "gitlab")]
+mod gitlab_test {
+    use jj_ryu_core::error::Error;
+    use jj_ryu_core::platform::{GitLabService, PlatformService};
+    use jj_ryu_core::types::{PrReview, PullRequest, ReviewState};
+    use mockito::{Matcher, ServerGuard};
+    use serde_json::json;
+
+    /// The endpoint of MR 7
+    const MR: &str = "/api/v4/projects/owner%2Frepo/merge_requests/7";
+
+    fn service(server: &ServerGuard) -> GitLabService {
+        GitLabService::new(
+            "token".to_string(),
+            "owner".to_string(),
+            "repo".to_string(),
+            None,
+        )
+        .unwrap()
+        .with_api_root(format!("{}/api/v4", server.url()))
+    }
+
+    fn mr(iid: u64) -> PullRequest {
+        PullRequest {
+            number: iid,
+            html_url: format!("https://gitlab.com/owner/repo/-/merge_requests/{iid}"),
+            base_ref: "main".to_string(),
+            head_ref: "feat-a".to_string(),
+            title: "Add A".to_string(),
+            node_id: None,
+            is_draft: false,
+            updated_at: None,
+        }
+    }
+
+    /// MR 7's approvals, approved by nobody
+    async fn no_approvals(server: &mut ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", format!("{MR}/approvals").as_str())
+            .with_body(json!({ "approved": false, "approved_by": [] }).to_string())
+            .create_async()
+            .await
+    }
+
+    fn changes_requested(reviewers: &[&str]) -> Vec<PrReview> {
+        reviewers
+            .iter()
+            .map(|reviewer| PrReview {
+                reviewer: (*reviewer).to_string(),
+                state: ReviewState::ChangesRequested,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_to_project_adds_board_label() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", MR)
+            .match_header("private-token", "token")
+            .match_body(Matcher::Json(json!({ "add_labels": "workflow::doing" })))
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        service(&server)
+            .add_pr_to_project(&mr(7), "workflow::doing")
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_add_pr_to_project_reports_api_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("PUT", MR).with_status(403).create_async().await;
+
+        let err = service(&server)
+            .add_pr_to_project(&mr(7), "workflow::doing")
+            .await
+            .unwrap_err();
+
+        mock.assert_async().await;
+        assert!(matches!(err, Error::GitLabApi(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_follows_x_next_page() {
+        let mut server = mockito::Server::new_async().await;
+        let reviewers = format!("{MR}/reviewers");
+        let approvals = server
+            .mock("GET", format!("{MR}/approvals").as_str())
+            .with_body(
+                json!({
+                    "approved": false,
+                    "approved_by": [{ "user": { "username": "carol" } }]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let first = server
+            .mock("GET", reviewers.as_str())
+            .match_query(Matcher::Exact("per_page=100".to_string()))
+            .with_header("x-next-page", "2")
+            .with_body(
+                json!([
+                    { "user": { "username": "alice" }, "state": "reviewed" },
+                    { "user": { "username": "bob" }, "state": "requested_changes" }
+                ])
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        // The last page names no next one
+        let second = server
+            .mock("GET", reviewers.as_str())
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("per_page".to_string(), "100".to_string()),
+                Matcher::UrlEncoded("page".to_string(), "2".to_string()),
+            ]))
+            .with_header("x-next-page", "")
+            .with_body(
+                json!([{ "user": { "username": "carol" }, "state": "requested_changes" }])
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let mut reviews = service(&server).list_reviews(7).await.unwrap();
+
+        approvals.assert_async().await;
+        first.assert_async().await;
+        second.assert_async().await;
+        // Requested changes replace an earlier approval
+        reviews.sort_by(|a, b| a.reviewer.cmp(&b.reviewer));
+        assert_eq!(reviews, changes_requested(&["bob", "carol"]));
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_follows_next_links_on_the_same_host() {
+        let mut server = mockito::Server::new_async().await;
+        let reviewers = format!("{MR}/reviewers");
+        let _approvals = no_approvals(&mut server).await;
+        let next = format!("{}{reviewers}?id_after=9", server.url());
+        let first_page = format!("{}{reviewers}", server.url());
+        let first = server
+            .mock("GET", reviewers.as_str())
+            .match_query(Matcher::Exact("per_page=100".to_string()))
+            .with_header(
+                "link",
+                &format!("<{next}>; rel=\"next\", <{first_page}>; rel=\"first\""),
+            )
+            .with_body(
+                json!([{ "user": { "username": "alice" }, "state": "requested_changes" }])
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", reviewers.as_str())
+            .match_query(Matcher::Exact("id_after=9".to_string()))
+            .with_body(
+                json!([{ "user": { "username": "bob" }, "state": "requested_changes" }])
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let reviews = service(&server).list_reviews(7).await.unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(reviews, changes_requested(&["alice", "bob"]));
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_ignores_next_links_to_other_hosts() {
+        let mut server = mockito::Server::new_async().await;
+        let _approvals = no_approvals(&mut server).await;
+        // Following this would hand the token to another host
+        let first = server
+            .mock("GET", format!("{MR}/reviewers").as_str())
+            .match_query(Matcher::Any)
+            .with_header("link", "<https://evil.example/next>; rel=\"next\"")
+            .with_body(
+                json!([{ "user": { "username": "alice" }, "state": "requested_changes" }])
+                    .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let reviews = service(&server).list_reviews(7).await.unwrap();
+
+        first.assert_async().await;
+        assert_eq!(reviews, changes_requested(&["alice"]));
+    }
+
+    #[tokio::test]
+    async fn test_request_reviewers_looks_up_users_through_get_all() {
+        let mut server = mockito::Server::new_async().await;
+        let users = server
+            .mock("GET", "/api/v4/users")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("username".to_string(), "alice".to_string()),
+                Matcher::UrlEncoded("per_page".to_string(), "100".to_string()),
+            ]))
+            .with_body(json!([{ "id": 42 }]).to_string())
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", MR)
+            .match_body(Matcher::Json(json!({ "reviewer_ids": [42] })))
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        service(&server)
+            .request_reviewers(7, &["alice".to_string()])
+            .await
+            .unwrap();
+
+        users.assert_async().await;
+        update.assert_async().await;
+    }
+}
+
+#[cfg(feature = "azure-devops")]
+mod azure_devops_test {
+    use jj_ryu_core::auth::{AuthSource, AzureDevOpsAuthConfig, test_azure_devops_auth};
+    use jj_ryu_core::error::Error;
+    use jj_ryu_core::platform::{AzureDevOpsService, PlatformService, parse_repo_info};
+    use jj_ryu_core::types::{ChecksState, PrReview, ReviewState};
+    use mockito::{Matcher, ServerGuard};
+    use serde_json::{Value, json};
+
+    /// `Authorization` header for the token "secret" (empty user name)
+    const SECRET_AUTH: &str = "Basic OnNlY3JldA==";
+
+    /// The repository API of `org/proj` repo `repo`
+    const REPO: &str = "/org/proj/_apis/git/repositories/repo";
+
+    /// Service talking to a mock server as an Azure DevOps Server host
+    fn service(server: &ServerGuard, owner: &str, repo: &str) -> AzureDevOpsService {
+        AzureDevOpsService::new(
+            "secret".to_string(),
+            owner.to_string(),
+            repo.to_string(),
+            Some(server.url()),
+        )
+        .unwrap()
+    }
+
+    fn api_version() -> Matcher {
+        Matcher::UrlEncoded("api-version".to_string(), "7.1".to_string())
+    }
+
+    fn pr_json(id: u64, head: &str, base: &str) -> Value {
+        json!({
+            "pullRequestId": id,
+            "title": format!("Add {head}"),
+            "status": "active",
+            "isDraft": false,
+            "sourceRefName": format!("refs/heads/{head}"),
+            "targetRefName": format!("refs/heads/{base}"),
+        })
+    }
+
+    fn reviewer_json(name: &str, vote: i32) -> Value {
+        json!({
+            "displayName": name,
+            "uniqueName": format!("{name}@example.com"),
+            "vote": vote,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_requests_send_token_as_basic_auth() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                "/org/My%20Project/_apis/git/repositories/repo/pullrequests",
+            )
+            .match_header("authorization", SECRET_AUTH)
+            .match_query(Matcher::AllOf(vec![
+                api_version(),
+                Matcher::UrlEncoded("searchCriteria.status".to_string(), "active".to_string()),
+                Matcher::UrlEncoded(
+                    "searchCriteria.sourceRefName".to_string(),
+                    "refs/heads/feat-a".to_string(),
+                ),
+            ]))
+            .with_body(
+                json!({ "value": [pr_json(9, "feat-a", "main"), pr_json(7, "feat-a", "main")] })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let pr = service(&server, "org/My%20Project", "repo")
+            .find_existing_pr("feat-a")
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert_async().await;
+        // The oldest PR wins
+        assert_eq!(pr.number, 7);
+        assert_eq!(pr.head_ref, "feat-a");
+        assert_eq!(pr.base_ref, "main");
+        assert_eq!(
+            pr.html_url,
+            format!("{}/org/My%20Project/_git/repo/pullrequest/7", server.url())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parsed_remote_reaches_repo_api() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "POST",
+                "/org/My%20Project/_apis/git/repositories/repo/pullrequests",
+            )
+            .match_header("authorization", SECRET_AUTH)
+            .match_query(api_version())
+            .match_body(Matcher::Json(json!({
+                "sourceRefName": "refs/heads/feat-b",
+                "targetRefName": "refs/heads/feat-a",
+                "title": "Add B",
+                "description": "",
+                "isDraft": false,
+            })))
+            .with_status(201)
+            .with_body(pr_json(8, "feat-b", "feat-a").to_string())
+            .create_async()
+            .await;
+
+        let config = parse_repo_info("git@ssh.dev.azure.com:v3/org/My%20Project/repo").unwrap();
+        let pr = service(&server, &config.owner, &config.repo)
+            .create_pr("feat-b", "feat-a", "Add B")
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pr.number, 8);
+        assert_eq!(pr.base_ref, "feat-a");
+    }
+
+    #[tokio::test]
+    async fn test_server_collection_urls() {
+        let mut server = mockito::Server::new_async().await;
+        let collection = "/tfs/DefaultCollection";
+        let identities = server
+            .mock("GET", format!("{collection}/_apis/identities").as_str())
+            .match_query(Matcher::UrlEncoded(
+                "filterValue".to_string(),
+                "alice".to_string(),
+            ))
+            .with_body(json!({ "value": [{ "id": "id-alice" }] }).to_string())
+            .create_async()
+            .await;
+        let reviewer = server
+            .mock(
+                "PUT",
+                format!(
+                    "{collection}/proj/_apis/git/repositories/r/pullrequests/3/reviewers/id-alice"
+                )
+                .as_str(),
+            )
+            .match_query(api_version())
+            .match_body(Matcher::Json(json!({ "vote": 0 })))
+            .with_body("{}")
+            .create_async()
+            .await;
+        // A trailing slash on the host is dropped
+        let service = AzureDevOpsService::new(
+            "secret".to_string(),
+            "tfs/DefaultCollection/proj".to_string(),
+            "r".to_string(),
+            Some(format!("{}/", server.url())),
+        )
+        .unwrap();
+
+        service
+            .request_reviewers(3, &["alice".to_string()])
+            .await
+            .unwrap();
+
+        identities.assert_async().await;
+        reviewer.assert_async().await;
+        assert_eq!(
+            service.config().web_url(),
+            format!("{}/tfs/DefaultCollection/proj/_git/r", server.url())
+        );
+    }
+
+    #[test]
+    fn test_cloud_web_url() {
+        let cloud =
+            AzureDevOpsService::new(String::new(), "org/proj".to_string(), "r".to_string(), None)
+                .unwrap();
+        assert_eq!(
+            cloud.config().web_url(),
+            "https://dev.azure.com/org/proj/_git/r"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_reviews_maps_votes() {
+        let mut server = mockito::Server::new_async().await;
+        let mut pr = pr_json(3, "feat-a", "main");
+        pr["reviewers"] = json!([
+            reviewer_json("alice", 10),
+            reviewer_json("bob", -5),
+            reviewer_json("carol", 0),
+            reviewer_json("dave", 5),
+            reviewer_json("erin", -10),
+            { "displayName": "Build Team", "vote": 10 },
+        ]);
+        let _mock = server
+            .mock("GET", format!("{REPO}/pullrequests/3").as_str())
+            .match_query(api_version())
+            .with_body(pr.to_string())
+            .create_async()
+            .await;
+
+        let reviews = service(&server, "org/proj", "repo")
+            .list_reviews(3)
+            .await
+            .unwrap();
+
+        // Suggestions count as approval and waiting for the author as
+        // changes requested; identities without a unique name go by their
+        // display name
+        let review = |reviewer: &str, state| PrReview {
+            reviewer: reviewer.to_string(),
+            state,
+        };
+        assert_eq!(
+            reviews,
+            vec![
+                review("alice@example.com", ReviewState::Approved),
+                review("bob@example.com", ReviewState::ChangesRequested),
+                review("dave@example.com", ReviewState::Approved),
+                review("erin@example.com", ReviewState::ChangesRequested),
+                review("Build Team", ReviewState::Approved),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_review_requests_matches_either_name() {
+        let mut server = mockito::Server::new_async().await;
+        let mut waiting = pr_json(1, "feat-a", "main");
+        waiting["reviewers"] = json!([reviewer_json("carol", 0)]);
+        let mut voted = pr_json(2, "feat-b", "main");
+        voted["reviewers"] = json!([reviewer_json("carol", 10)]);
+        let _mock = server
+            .mock("GET", format!("{REPO}/pullrequests").as_str())
+            .match_query(Matcher::UrlEncoded(
+                "searchCriteria.status".to_string(),
+                "active".to_string(),
+            ))
+            .with_body(json!({ "value": [waiting, voted] }).to_string())
+            .create_async()
+            .await;
+        let service = service(&server, "org/proj", "repo");
+
+        for name in ["CAROL", "carol@example.com"] {
+            assert_eq!(
+                service.count_review_requests(name).await.unwrap(),
+                1,
+                "{name}"
+            );
+        }
+        assert_eq!(service.count_review_requests("dave").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_status_keeps_latest_per_context() {
+        let status = |name: &str, state: &str| json!({ "state": state, "context": { "name": name, "genre": "ci" } });
+        let cases = [
+            (vec![], ChecksState::None),
+            (
+                vec![status("build", "failed"), status("build", "succeeded")],
+                ChecksState::Passing,
+            ),
+            (
+                vec![status("build", "succeeded"), status("lint", "pending")],
+                ChecksState::Pending,
+            ),
+            (
+                vec![status("build", "error"), status("lint", "pending")],
+                ChecksState::Failing,
+            ),
+        ];
+
+        for (statuses, expected) in cases {
+            let mut server = mockito::Server::new_async().await;
+            let _mock = server
+                .mock("GET", format!("{REPO}/pullrequests/3/statuses").as_str())
+                .match_query(api_version())
+                .with_body(json!({ "value": statuses }).to_string())
+                .create_async()
+                .await;
+
+            let checks = service(&server, "org/proj", "repo")
+                .get_check_status(3)
+                .await
+                .unwrap();
+            assert_eq!(checks, expected, "{statuses:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_pr_comments_skips_system_and_deleted_threads() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", format!("{REPO}/pullrequests/3/threads").as_str())
+            .match_query(api_version())
+            .with_body(
+                json!({
+                    "value": [
+                        { "id": 7, "comments": [
+                            { "id": 1, "content": "stack", "commentType": "text" },
+                            { "id": 2, "content": "reply", "commentType": "text" },
+                        ]},
+                        { "id": 8, "comments": [
+                            { "id": 1, "content": "Policy updated", "commentType": "system" },
+                        ]},
+                        { "id": 9, "isDeleted": true, "comments": [
+                            { "id": 1, "content": "gone", "commentType": "text" },
+                        ]},
+                    ],
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let comments = service(&server, "org/proj", "repo")
+            .list_pr_comments(3)
+            .await
+            .unwrap();
+
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].id, 7);
+        assert_eq!(comments[0].body, "stack");
+    }
+
+    #[tokio::test]
+    async fn test_current_user_reads_account() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/org/_apis/connectionData")
+            .match_header("authorization", SECRET_AUTH)
+            .with_body(
+                json!({
+                    "authenticatedUser": {
+                        "providerDisplayName": "Alice",
+                        "properties": {
+                            "Account": { "$type": "System.String", "$value": "alice@example.com" }
+                        },
+                    },
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let user = service(&server, "org/proj", "repo")
+            .current_user()
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(user, "alice@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_page_is_auth_error() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/org/_apis/connectionData")
+            .with_status(203)
+            .with_body("<html>Sign in</html>")
+            .create_async()
+            .await;
+
+        let err = service(&server, "org/proj", "repo")
+            .current_user()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Auth(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_auth_check_against_org_url() {
+        let mut server = mockito::Server::new_async().await;
+        let _ok = server
+            .mock("GET", "/org/_apis/connectionData")
+            .match_header("authorization", SECRET_AUTH)
+            .with_body(
+                json!({ "authenticatedUser": { "providerDisplayName": "Alice" } }).to_string(),
+            )
+            .create_async()
+            .await;
+        let _expired = server
+            .mock("GET", "/expired/_apis/connectionData")
+            .with_status(401)
+            .create_async()
+            .await;
+        let _sign_in = server
+            .mock("GET", "/signin/_apis/connectionData")
+            .with_status(203)
+            .with_body("<html>Sign in</html>")
+            .create_async()
+            .await;
+        let auth = AzureDevOpsAuthConfig {
+            token: "secret".to_string(),
+            source: AuthSource::EnvVar,
+        };
+
+        let name = test_azure_devops_auth(&auth, &format!("{}/org/", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(name, "Alice");
+        for org in ["expired", "signin"] {
+            let err = test_azure_devops_auth(&auth, &format!("{}/{org}", server.url()))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Auth(_)), "{org}: {err}");
+        }
+    }
+}
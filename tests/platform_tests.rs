@@ -0,0 +1,161 @@
+//! Cross-platform tests against real jj and git binaries
+//!
+//! Exercises push, fetch, and output paths that differ between operating
+//! systems (Windows in particular). They don't depend on any CI service, but
+//! they spawn `jj` and `git` and write to the temp dir, so they are opt-in.
+//!
+//! These tests require:
+//! - `JJ_RYU_PLATFORM_TESTS=1` environment variable
+//! - `jj` and `git` CLIs installed
+//!
+//! Run with: `JJ_RYU_PLATFORM_TESTS=1 cargo test --test platform_tests -- --include-ignored`
+
+#![allow(deprecated)] // cargo_bin is the standard way to test CLI binaries
+
+use assert_cmd::Command as CargoCommand;
+use jj_ryu_core::repo::JjWorkspace;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Check if platform tests should run
+fn platform_tests_enabled() -> bool {
+    env::var("JJ_RYU_PLATFORM_TESTS").is_ok()
+}
+
+fn run(program: &str, args: &[&str], dir: &Path) {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap_or_else(|e| panic!("failed to run {program}: {e}"));
+    assert!(
+        output.status.success(),
+        "{program} {args:?} failed in {}: {}",
+        dir.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// A jj repo and a bare git remote, both under a path with spaces
+struct RepoWithRemote {
+    _dir: TempDir,
+    repo: PathBuf,
+    remote: PathBuf,
+}
+
+impl RepoWithRemote {
+    fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("ryu platform tests");
+        let repo = root.join("work repo");
+        let remote = root.join("remote.git");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::create_dir_all(&remote).unwrap();
+
+        run("git", &["init", "--bare", "--quiet"], &remote);
+        run("jj", &["git", "init"], &repo);
+        run(
+            "jj",
+            &["git", "remote", "add", "origin", &remote.to_string_lossy()],
+            &repo,
+        );
+
+        Self {
+            _dir: dir,
+            repo,
+            remote,
+        }
+    }
+
+    fn commit_with_bookmark(&self, file: &str, contents: &str, bookmark: &str) {
+        std::fs::write(self.repo.join(file), contents).unwrap();
+        run("jj", &["commit", "-m", &format!("Add {file}")], &self.repo);
+        run(
+            "jj",
+            &["bookmark", "create", bookmark, "-r", "@-"],
+            &self.repo,
+        );
+    }
+}
+
+#[test]
+#[ignore = "platform test requiring JJ_RYU_PLATFORM_TESTS=1"]
+fn test_push_and_fetch_with_spaces_in_paths() {
+    if !platform_tests_enabled() {
+        return;
+    }
+    let setup = RepoWithRemote::new();
+    setup.commit_with_bookmark("notes.txt", "line one\r\nline two\r\n", "feat-a");
+
+    let mut workspace = JjWorkspace::open(&setup.repo).unwrap();
+    workspace.git_push("feat-a", "origin").unwrap();
+    workspace.git_fetch("origin").unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "refs/heads/feat-a"])
+        .current_dir(&setup.remote)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "feat-a missing on remote");
+
+    // CRLF content must survive the round trip byte for byte
+    let blob = Command::new("git")
+        .args(["show", "feat-a:notes.txt"])
+        .current_dir(&setup.remote)
+        .output()
+        .unwrap();
+    assert_eq!(blob.stdout, b"line one\r\nline two\r\n");
+}
+
+#[test]
+#[ignore = "platform test requiring JJ_RYU_PLATFORM_TESTS=1"]
+fn test_push_many_pushes_every_bookmark() {
+    if !platform_tests_enabled() {
+        return;
+    }
+    let setup = RepoWithRemote::new();
+    setup.commit_with_bookmark("a.txt", "a", "feat-a");
+    setup.commit_with_bookmark("b.txt", "b", "feat-b");
+
+    let mut workspace = JjWorkspace::open(&setup.repo).unwrap();
+    workspace
+        .git_push_many(&["feat-a", "feat-b"], "origin")
+        .unwrap();
+
+    for bookmark in ["feat-a", "feat-b"] {
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", &format!("refs/heads/{bookmark}")])
+            .current_dir(&setup.remote)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{bookmark} missing on remote");
+        let tracked = workspace.get_remote_bookmark(bookmark, "origin").unwrap();
+        assert!(tracked.is_some(), "{bookmark}@origin not tracked");
+    }
+}
+
+#[test]
+#[ignore = "platform test requiring JJ_RYU_PLATFORM_TESTS=1"]
+fn test_piped_output_has_no_escape_codes() {
+    if !platform_tests_enabled() {
+        return;
+    }
+    let setup = RepoWithRemote::new();
+    setup.commit_with_bookmark("a.txt", "a", "feat-a");
+
+    let output = CargoCommand::cargo_bin("ryu")
+        .unwrap()
+        .arg("--path")
+        .arg(&setup.repo)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("feat-a"));
+    assert!(
+        !stdout.contains('\u{1b}'),
+        "escape codes in piped output: {stdout:?}"
+    );
+}